@@ -0,0 +1,591 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::canvas_data::{Backend, ColorMatrix, DrawOptions, Filter, GenericDrawTarget};
+use crate::canvas_data::{GenericPathBuilder, Path, Pattern, SourceSurface, StrokeOptions};
+use azure::azure::AzFloat;
+use azure::azure_hl::{self, AntialiasMode, CapStyle, CompositionOp, JoinStyle};
+use azure::azure_hl::{BackendType, DrawTarget, PathBuilder, SurfaceFormat, SurfacePattern};
+use azure::azure_hl::{Color, ColorPattern, DrawSurfaceOptions};
+use azure::azure_hl::{ColorMatrixAttribute, ColorMatrixInput, FilterType, Matrix5x4};
+use azure::azure_hl::{ExtendMode, GradientStop, LinearGradientPattern, RadialGradientPattern};
+use azure::azure_hl::{GaussianBlurAttribute, GaussianBlurInput};
+use canvas_traits::canvas::*;
+use cssparser::RGBA;
+use euclid::{Point2D, Rect, Size2D, Transform2D, Vector2D};
+
+/// The backend drawing through Skia with the azure bindings.
+pub struct AzureBackend;
+
+impl Backend for AzureBackend {
+    fn create_drawtarget(&self, size: Size2D<u32>) -> Box<dyn GenericDrawTarget> {
+        // FIXME(nox): Why is the size made of i32 values?
+        Box::new(DrawTarget::new(
+            BackendType::Skia,
+            size.to_i32(),
+            SurfaceFormat::B8G8R8A8,
+        ))
+    }
+}
+
+impl GenericPathBuilder for PathBuilder {
+    fn arc(
+        &mut self,
+        origin: Point2D<f32>,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        anticlockwise: bool,
+    ) {
+        PathBuilder::arc(self, origin, radius, start_angle, end_angle, anticlockwise);
+    }
+
+    fn bezier_curve_to(
+        &mut self,
+        control_point1: &Point2D<f32>,
+        control_point2: &Point2D<f32>,
+        control_point3: &Point2D<f32>,
+    ) {
+        PathBuilder::bezier_curve_to(self, control_point1, control_point2, control_point3);
+    }
+
+    fn close(&mut self) {
+        PathBuilder::close(self);
+    }
+
+    fn ellipse(
+        &mut self,
+        origin: Point2D<f32>,
+        radius_x: f32,
+        radius_y: f32,
+        rotation_angle: f32,
+        start_angle: f32,
+        end_angle: f32,
+        anticlockwise: bool,
+    ) {
+        PathBuilder::ellipse(
+            self,
+            origin,
+            radius_x,
+            radius_y,
+            rotation_angle,
+            start_angle,
+            end_angle,
+            anticlockwise,
+        );
+    }
+
+    fn get_current_point(&self) -> Point2D<f32> {
+        let point = PathBuilder::get_current_point(self);
+        Point2D::new(point.x, point.y)
+    }
+
+    fn line_to(&mut self, point: Point2D<f32>) {
+        PathBuilder::line_to(self, point);
+    }
+
+    fn move_to(&mut self, point: Point2D<f32>) {
+        PathBuilder::move_to(self, point);
+    }
+
+    fn quadratic_curve_to(&mut self, control_point: &Point2D<f32>, end_point: &Point2D<f32>) {
+        PathBuilder::quadratic_curve_to(self, control_point, end_point);
+    }
+
+    fn finish(&mut self) -> Path {
+        Path::Azure(PathBuilder::finish(self))
+    }
+}
+
+impl GenericDrawTarget for DrawTarget {
+    fn clear_rect(&self, rect: &Rect<f32>) {
+        DrawTarget::clear_rect(self, rect);
+    }
+
+    fn copy_surface(&self, surface: SourceSurface, source: Rect<i32>, destination: Point2D<i32>) {
+        DrawTarget::copy_surface(self, surface.into_azure(), source, destination);
+    }
+
+    fn create_path_builder(&self) -> Box<dyn GenericPathBuilder> {
+        Box::new(DrawTarget::create_path_builder(self))
+    }
+
+    fn create_pattern(&self, style: &FillOrStrokeStyle) -> Option<Pattern> {
+        style.to_azure_pattern(self).map(Pattern::Azure)
+    }
+
+    fn create_similar_draw_target(&self, size: &Size2D<i32>) -> Box<dyn GenericDrawTarget> {
+        Box::new(DrawTarget::create_similar_draw_target(
+            self,
+            size,
+            self.get_format(),
+        ))
+    }
+
+    fn create_source_surface_from_data(
+        &self,
+        data: &[u8],
+        size: Size2D<i32>,
+        stride: i32,
+    ) -> Option<SourceSurface> {
+        DrawTarget::create_source_surface_from_data(
+            self,
+            data,
+            size,
+            stride,
+            SurfaceFormat::B8G8R8A8,
+        )
+        .map(SourceSurface::Azure)
+    }
+
+    fn draw_surface(
+        &self,
+        surface: SourceSurface,
+        dest: Rect<f32>,
+        source: Rect<f32>,
+        filter: Filter,
+        draw_options: &DrawOptions,
+    ) {
+        DrawTarget::draw_surface(
+            self,
+            surface.into_azure(),
+            dest,
+            source,
+            DrawSurfaceOptions::new(filter.to_azure_style(), true),
+            draw_options.to_azure_style(),
+        );
+    }
+
+    fn draw_surface_with_shadow(
+        &self,
+        surface: SourceSurface,
+        dest: &Point2D<f32>,
+        color: &RGBA,
+        offset: &Vector2D<f32>,
+        sigma: f32,
+        operator: CompositionOrBlending,
+    ) {
+        DrawTarget::draw_surface_with_shadow(
+            self,
+            surface.into_azure(),
+            dest,
+            &color.to_azure_style(),
+            offset,
+            sigma,
+            operator.to_azure_style(),
+        );
+    }
+
+    fn draw_gaussian_blur(&self, surface: SourceSurface, std_deviation: f32) {
+        let rect = Rect::from_size(self.get_size().to_f32());
+        let blur = self.create_filter(FilterType::GaussianBlur);
+        blur.set_attribute(GaussianBlurAttribute::StdDeviation(std_deviation));
+        blur.set_input(GaussianBlurInput, &surface.into_azure());
+        self.draw_filter(&blur, &rect, &rect.origin, unfiltered_draw_options());
+    }
+
+    fn draw_color_matrix(&self, surface: SourceSurface, matrix: &ColorMatrix) {
+        let rect = Rect::from_size(self.get_size().to_f32());
+        let color_matrix = self.create_filter(FilterType::ColorMatrix);
+        color_matrix.set_attribute(ColorMatrixAttribute::Matrix(to_matrix5x4(matrix)));
+        color_matrix.set_input(ColorMatrixInput, &surface.into_azure());
+        self.draw_filter(
+            &color_matrix,
+            &rect,
+            &rect.origin,
+            unfiltered_draw_options(),
+        );
+    }
+
+    fn fill(&self, path: &Path, pattern: &Pattern, draw_options: &DrawOptions) {
+        DrawTarget::fill(
+            self,
+            path.as_azure(),
+            pattern.as_azure().to_pattern_ref(),
+            &draw_options.to_azure_style(),
+        );
+    }
+
+    fn fill_rect(&self, rect: &Rect<f32>, pattern: &Pattern, draw_options: &DrawOptions) {
+        DrawTarget::fill_rect(
+            self,
+            rect,
+            pattern.as_azure().to_pattern_ref(),
+            Some(&draw_options.to_azure_style()),
+        );
+    }
+
+    fn get_size(&self) -> Size2D<i32> {
+        DrawTarget::get_size(self)
+    }
+
+    fn get_transform(&self) -> Transform2D<f32> {
+        DrawTarget::get_transform(self)
+    }
+
+    fn pop_clip(&self) {
+        DrawTarget::pop_clip(self);
+    }
+
+    fn push_clip(&self, path: &Path) {
+        DrawTarget::push_clip(self, path.as_azure());
+    }
+
+    fn set_transform(&self, matrix: &Transform2D<f32>) {
+        DrawTarget::set_transform(self, matrix);
+    }
+
+    fn snapshot(&self) -> SourceSurface {
+        SourceSurface::Azure(DrawTarget::snapshot(self))
+    }
+
+    #[allow(unsafe_code)]
+    fn snapshot_data(&self) -> Vec<u8> {
+        let data_surface = DrawTarget::snapshot(self).get_data_surface();
+        unsafe { data_surface.data().to_vec() }
+    }
+
+    fn stroke(
+        &self,
+        path: &Path,
+        pattern: &Pattern,
+        stroke_options: &StrokeOptions,
+        draw_options: &DrawOptions,
+    ) {
+        DrawTarget::stroke(
+            self,
+            path.as_azure(),
+            pattern.as_azure().to_pattern_ref(),
+            &stroke_options.to_azure_style(),
+            &draw_options.to_azure_style(),
+        );
+    }
+
+    fn stroke_line(
+        &self,
+        start: Point2D<f32>,
+        end: Point2D<f32>,
+        pattern: &Pattern,
+        stroke_options: &StrokeOptions,
+        draw_options: &DrawOptions,
+    ) {
+        DrawTarget::stroke_line(
+            self,
+            start,
+            end,
+            pattern.as_azure().to_pattern_ref(),
+            &stroke_options.to_azure_style(),
+            &draw_options.to_azure_style(),
+        );
+    }
+
+    fn stroke_rect(
+        &self,
+        rect: &Rect<f32>,
+        pattern: &Pattern,
+        stroke_options: &StrokeOptions,
+        draw_options: &DrawOptions,
+    ) {
+        DrawTarget::stroke_rect(
+            self,
+            rect,
+            pattern.as_azure().to_pattern_ref(),
+            &stroke_options.to_azure_style(),
+            &draw_options.to_azure_style(),
+        );
+    }
+}
+
+fn unfiltered_draw_options() -> azure_hl::DrawOptions {
+    azure_hl::DrawOptions::new(1.0, CompositionOp::Over, AntialiasMode::None)
+}
+
+impl Path {
+    fn as_azure(&self) -> &azure_hl::Path {
+        match *self {
+            Path::Azure(ref path) => path,
+            _ => unreachable!("Not an azure path."),
+        }
+    }
+}
+
+impl Pattern {
+    fn as_azure(&self) -> &azure_hl::Pattern {
+        match *self {
+            Pattern::Azure(ref pattern) => pattern,
+            _ => unreachable!("Not an azure pattern."),
+        }
+    }
+}
+
+impl SourceSurface {
+    fn into_azure(self) -> azure_hl::SourceSurface {
+        match self {
+            SourceSurface::Azure(surface) => surface,
+            _ => unreachable!("Not an azure surface."),
+        }
+    }
+}
+
+pub fn is_zero_size_gradient(pattern: &azure_hl::Pattern) -> bool {
+    if let &azure_hl::Pattern::LinearGradient(ref gradient) = pattern {
+        if gradient.is_zero_size() {
+            return true;
+        }
+    }
+    false
+}
+
+pub fn surface_repeat(pattern: &azure_hl::Pattern) -> Option<(Size2D<i32>, bool, bool)> {
+    match *pattern {
+        azure_hl::Pattern::Surface(ref surface) => {
+            let size = surface.size();
+            Some((
+                Size2D::new(size.width as i32, size.height as i32),
+                surface.repeat_x,
+                surface.repeat_y,
+            ))
+        },
+        _ => None,
+    }
+}
+
+/// Azure multiplies a row vector by the matrix, so it wants it transposed.
+fn to_matrix5x4(matrix: &ColorMatrix) -> Matrix5x4 {
+    Matrix5x4 {
+        m11: matrix[0][0],
+        m12: matrix[1][0],
+        m13: matrix[2][0],
+        m14: matrix[3][0],
+        m21: matrix[0][1],
+        m22: matrix[1][1],
+        m23: matrix[2][1],
+        m24: matrix[3][1],
+        m31: matrix[0][2],
+        m32: matrix[1][2],
+        m33: matrix[2][2],
+        m34: matrix[3][2],
+        m41: matrix[0][3],
+        m42: matrix[1][3],
+        m43: matrix[2][3],
+        m44: matrix[3][3],
+        m51: matrix[0][4],
+        m52: matrix[1][4],
+        m53: matrix[2][4],
+        m54: matrix[3][4],
+    }
+}
+
+pub trait ToAzureStyle {
+    type Target;
+    fn to_azure_style(self) -> Self::Target;
+}
+
+impl<'a> ToAzureStyle for &'a DrawOptions {
+    type Target = azure_hl::DrawOptions;
+
+    fn to_azure_style(self) -> azure_hl::DrawOptions {
+        let antialias = if self.antialias {
+            AntialiasMode::Default
+        } else {
+            AntialiasMode::None
+        };
+        azure_hl::DrawOptions::new(self.alpha, self.composition.to_azure_style(), antialias)
+    }
+}
+
+impl<'a> ToAzureStyle for &'a StrokeOptions {
+    type Target = azure_hl::StrokeOptions<'static>;
+
+    fn to_azure_style(self) -> azure_hl::StrokeOptions<'static> {
+        azure_hl::StrokeOptions::new(
+            self.line_width,
+            self.line_join.to_azure_style(),
+            self.line_cap.to_azure_style(),
+            self.miter_limit,
+            &[],
+        )
+    }
+}
+
+impl ToAzureStyle for Filter {
+    type Target = azure_hl::Filter;
+
+    fn to_azure_style(self) -> azure_hl::Filter {
+        match self {
+            Filter::Good => azure_hl::Filter::Good,
+            Filter::Linear => azure_hl::Filter::Linear,
+            Filter::Point => azure_hl::Filter::Point,
+        }
+    }
+}
+
+impl ToAzureStyle for LineCapStyle {
+    type Target = CapStyle;
+
+    fn to_azure_style(self) -> CapStyle {
+        match self {
+            LineCapStyle::Butt => CapStyle::Butt,
+            LineCapStyle::Round => CapStyle::Round,
+            LineCapStyle::Square => CapStyle::Square,
+        }
+    }
+}
+
+impl ToAzureStyle for LineJoinStyle {
+    type Target = JoinStyle;
+
+    fn to_azure_style(self) -> JoinStyle {
+        match self {
+            LineJoinStyle::Round => JoinStyle::Round,
+            LineJoinStyle::Bevel => JoinStyle::Bevel,
+            LineJoinStyle::Miter => JoinStyle::Miter,
+        }
+    }
+}
+
+impl ToAzureStyle for CompositionStyle {
+    type Target = CompositionOp;
+
+    fn to_azure_style(self) -> CompositionOp {
+        match self {
+            CompositionStyle::SrcIn => CompositionOp::In,
+            CompositionStyle::SrcOut => CompositionOp::Out,
+            CompositionStyle::SrcOver => CompositionOp::Over,
+            CompositionStyle::SrcAtop => CompositionOp::Atop,
+            CompositionStyle::DestIn => CompositionOp::DestIn,
+            CompositionStyle::DestOut => CompositionOp::DestOut,
+            CompositionStyle::DestOver => CompositionOp::DestOver,
+            CompositionStyle::DestAtop => CompositionOp::DestAtop,
+            CompositionStyle::Copy => CompositionOp::Source,
+            CompositionStyle::Lighter => CompositionOp::Add,
+            CompositionStyle::Xor => CompositionOp::Xor,
+        }
+    }
+}
+
+impl ToAzureStyle for BlendingStyle {
+    type Target = CompositionOp;
+
+    fn to_azure_style(self) -> CompositionOp {
+        match self {
+            BlendingStyle::Multiply => CompositionOp::Multiply,
+            BlendingStyle::Screen => CompositionOp::Screen,
+            BlendingStyle::Overlay => CompositionOp::Overlay,
+            BlendingStyle::Darken => CompositionOp::Darken,
+            BlendingStyle::Lighten => CompositionOp::Lighten,
+            BlendingStyle::ColorDodge => CompositionOp::ColorDodge,
+            BlendingStyle::ColorBurn => CompositionOp::ColorBurn,
+            BlendingStyle::HardLight => CompositionOp::HardLight,
+            BlendingStyle::SoftLight => CompositionOp::SoftLight,
+            BlendingStyle::Difference => CompositionOp::Difference,
+            BlendingStyle::Exclusion => CompositionOp::Exclusion,
+            BlendingStyle::Hue => CompositionOp::Hue,
+            BlendingStyle::Saturation => CompositionOp::Saturation,
+            BlendingStyle::Color => CompositionOp::Color,
+            BlendingStyle::Luminosity => CompositionOp::Luminosity,
+        }
+    }
+}
+
+impl ToAzureStyle for CompositionOrBlending {
+    type Target = CompositionOp;
+
+    fn to_azure_style(self) -> CompositionOp {
+        match self {
+            CompositionOrBlending::Composition(op) => op.to_azure_style(),
+            CompositionOrBlending::Blending(op) => op.to_azure_style(),
+        }
+    }
+}
+
+pub trait ToAzurePattern {
+    fn to_azure_pattern(&self, drawtarget: &DrawTarget) -> Option<azure_hl::Pattern>;
+}
+
+impl ToAzurePattern for FillOrStrokeStyle {
+    fn to_azure_pattern(&self, drawtarget: &DrawTarget) -> Option<azure_hl::Pattern> {
+        Some(match *self {
+            FillOrStrokeStyle::Color(ref color) => {
+                azure_hl::Pattern::Color(ColorPattern::new(color.to_azure_style()))
+            },
+            FillOrStrokeStyle::LinearGradient(ref linear_gradient_style) => {
+                let gradient_stops: Vec<GradientStop> = linear_gradient_style
+                    .stops
+                    .iter()
+                    .map(|s| GradientStop {
+                        offset: s.offset as AzFloat,
+                        color: s.color.to_azure_style(),
+                    })
+                    .collect();
+
+                azure_hl::Pattern::LinearGradient(LinearGradientPattern::new(
+                    &Point2D::new(
+                        linear_gradient_style.x0 as AzFloat,
+                        linear_gradient_style.y0 as AzFloat,
+                    ),
+                    &Point2D::new(
+                        linear_gradient_style.x1 as AzFloat,
+                        linear_gradient_style.y1 as AzFloat,
+                    ),
+                    drawtarget.create_gradient_stops(&gradient_stops, ExtendMode::Clamp),
+                    &Transform2D::identity(),
+                ))
+            },
+            FillOrStrokeStyle::RadialGradient(ref radial_gradient_style) => {
+                let gradient_stops: Vec<GradientStop> = radial_gradient_style
+                    .stops
+                    .iter()
+                    .map(|s| GradientStop {
+                        offset: s.offset as AzFloat,
+                        color: s.color.to_azure_style(),
+                    })
+                    .collect();
+
+                azure_hl::Pattern::RadialGradient(RadialGradientPattern::new(
+                    &Point2D::new(
+                        radial_gradient_style.x0 as AzFloat,
+                        radial_gradient_style.y0 as AzFloat,
+                    ),
+                    &Point2D::new(
+                        radial_gradient_style.x1 as AzFloat,
+                        radial_gradient_style.y1 as AzFloat,
+                    ),
+                    radial_gradient_style.r0 as AzFloat,
+                    radial_gradient_style.r1 as AzFloat,
+                    drawtarget.create_gradient_stops(&gradient_stops, ExtendMode::Clamp),
+                    &Transform2D::identity(),
+                ))
+            },
+            FillOrStrokeStyle::Surface(ref surface_style) => {
+                let source_surface = drawtarget.create_source_surface_from_data(
+                    &surface_style.surface_data,
+                    // FIXME(nox): Why are those i32 values?
+                    surface_style.surface_size.to_i32(),
+                    surface_style.surface_size.width as i32 * 4,
+                    SurfaceFormat::B8G8R8A8,
+                )?;
+                azure_hl::Pattern::Surface(SurfacePattern::new(
+                    source_surface.azure_source_surface,
+                    surface_style.repeat_x,
+                    surface_style.repeat_y,
+                    &Transform2D::identity(),
+                ))
+            },
+        })
+    }
+}
+
+impl ToAzureStyle for RGBA {
+    type Target = Color;
+
+    fn to_azure_style(self) -> Color {
+        Color::rgba(
+            self.red_f32() as AzFloat,
+            self.green_f32() as AzFloat,
+            self.blue_f32() as AzFloat,
+            self.alpha_f32() as AzFloat,
+        )
+    }
+}
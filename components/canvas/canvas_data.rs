@@ -2,26 +2,291 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use azure::azure::AzFloat;
-use azure::azure_hl::SurfacePattern;
-use azure::azure_hl::{AntialiasMode, AsAzurePoint, CapStyle, CompositionOp, JoinStyle};
-use azure::azure_hl::{
-    BackendType, DrawOptions, DrawTarget, Pattern, StrokeOptions, SurfaceFormat,
-};
-use azure::azure_hl::{Color, ColorPattern, DrawSurfaceOptions, Filter, Path, PathBuilder};
-use azure::azure_hl::{ExtendMode, GradientStop, LinearGradientPattern, RadialGradientPattern};
+#[cfg(feature = "canvas2d-azure")]
+use crate::azure_backend::{self, AzureBackend};
+use crate::software_backend::{self, SoftwareBackend};
 use canvas_traits::canvas::*;
 use cssparser::RGBA;
 use euclid::{Point2D, Rect, Size2D, Transform2D, Vector2D};
 use ipc_channel::ipc::{IpcSender, IpcSharedMemory};
 use num_traits::ToPrimitive;
+use servo_config::prefs::PREFS;
 use std::mem;
 use std::sync::Arc;
 use webrender::api::DirtyRect;
 
+/// A rasterization backend for the 2D context.
+pub trait Backend {
+    /// Creates a transparent draw target of the given size.
+    fn create_drawtarget(&self, size: Size2D<u32>) -> Box<dyn GenericDrawTarget>;
+}
+
+/// Returns the backend selected by the `gfx.canvas2d.backend` pref.
+pub fn create_backend() -> Box<dyn Backend> {
+    match PREFS.get("gfx.canvas2d.backend").as_string() {
+        #[cfg(feature = "canvas2d-azure")]
+        Some("azure") => Box::new(AzureBackend),
+        Some("software") => Box::new(SoftwareBackend),
+        Some(name) => {
+            warn!("Unknown canvas 2D backend {}, using the default one.", name);
+            default_backend()
+        },
+        None => default_backend(),
+    }
+}
+
+#[cfg(feature = "canvas2d-azure")]
+fn default_backend() -> Box<dyn Backend> {
+    Box::new(AzureBackend)
+}
+
+#[cfg(not(feature = "canvas2d-azure"))]
+fn default_backend() -> Box<dyn Backend> {
+    Box::new(SoftwareBackend)
+}
+
+/// A path builder, which records its points in the space of the draw target
+/// it was created by.
+pub trait GenericPathBuilder {
+    fn arc(
+        &mut self,
+        origin: Point2D<f32>,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        anticlockwise: bool,
+    );
+    fn bezier_curve_to(
+        &mut self,
+        control_point1: &Point2D<f32>,
+        control_point2: &Point2D<f32>,
+        control_point3: &Point2D<f32>,
+    );
+    fn close(&mut self);
+    fn ellipse(
+        &mut self,
+        origin: Point2D<f32>,
+        radius_x: f32,
+        radius_y: f32,
+        rotation_angle: f32,
+        start_angle: f32,
+        end_angle: f32,
+        anticlockwise: bool,
+    );
+    fn get_current_point(&self) -> Point2D<f32>;
+    fn line_to(&mut self, point: Point2D<f32>);
+    fn move_to(&mut self, point: Point2D<f32>);
+    fn quadratic_curve_to(&mut self, control_point: &Point2D<f32>, end_point: &Point2D<f32>);
+    fn finish(&mut self) -> Path;
+}
+
+/// The operations `CanvasData` needs from the surface it draws to. Unless
+/// stated otherwise, they honour the transform and the clip of the draw target.
+pub trait GenericDrawTarget {
+    /// Clears a rectangle to transparent black.
+    fn clear_rect(&self, rect: &Rect<f32>);
+    /// Copies a part of a surface as is, ignoring the transform and the clip.
+    fn copy_surface(&self, surface: SourceSurface, source: Rect<i32>, destination: Point2D<i32>);
+    fn create_path_builder(&self) -> Box<dyn GenericPathBuilder>;
+    fn create_pattern(&self, style: &FillOrStrokeStyle) -> Option<Pattern>;
+    fn create_similar_draw_target(&self, size: &Size2D<i32>) -> Box<dyn GenericDrawTarget>;
+    /// Creates a surface from premultiplied BGRA pixels.
+    fn create_source_surface_from_data(
+        &self,
+        data: &[u8],
+        size: Size2D<i32>,
+        stride: i32,
+    ) -> Option<SourceSurface>;
+    fn draw_surface(
+        &self,
+        surface: SourceSurface,
+        dest: Rect<f32>,
+        source: Rect<f32>,
+        filter: Filter,
+        draw_options: &DrawOptions,
+    );
+    /// Draws a surface and its shadow at `dest`, ignoring the transform.
+    fn draw_surface_with_shadow(
+        &self,
+        surface: SourceSurface,
+        dest: &Point2D<f32>,
+        color: &RGBA,
+        offset: &Vector2D<f32>,
+        sigma: f32,
+        operator: CompositionOrBlending,
+    );
+    /// Draws a blurred copy of a surface at the origin, ignoring the transform.
+    fn draw_gaussian_blur(&self, surface: SourceSurface, std_deviation: f32);
+    /// Draws a surface run through a color matrix at the origin, ignoring
+    /// the transform.
+    fn draw_color_matrix(&self, surface: SourceSurface, matrix: &ColorMatrix);
+    fn fill(&self, path: &Path, pattern: &Pattern, draw_options: &DrawOptions);
+    fn fill_rect(&self, rect: &Rect<f32>, pattern: &Pattern, draw_options: &DrawOptions);
+    fn get_size(&self) -> Size2D<i32>;
+    fn get_transform(&self) -> Transform2D<f32>;
+    fn pop_clip(&self);
+    fn push_clip(&self, path: &Path);
+    fn set_transform(&self, matrix: &Transform2D<f32>);
+    fn snapshot(&self) -> SourceSurface;
+    /// Returns a copy of the pixels of the draw target, as premultiplied BGRA.
+    fn snapshot_data(&self) -> Vec<u8>;
+    fn stroke(
+        &self,
+        path: &Path,
+        pattern: &Pattern,
+        stroke_options: &StrokeOptions,
+        draw_options: &DrawOptions,
+    );
+    fn stroke_line(
+        &self,
+        start: Point2D<f32>,
+        end: Point2D<f32>,
+        pattern: &Pattern,
+        stroke_options: &StrokeOptions,
+        draw_options: &DrawOptions,
+    );
+    fn stroke_rect(
+        &self,
+        rect: &Rect<f32>,
+        pattern: &Pattern,
+        stroke_options: &StrokeOptions,
+        draw_options: &DrawOptions,
+    );
+}
+
+/// A finished path. Paths can only be used with draw targets of the backend
+/// that built them.
+pub enum Path {
+    #[cfg(feature = "canvas2d-azure")]
+    Azure(azure::azure_hl::Path),
+    Software(software_backend::Path),
+}
+
+impl Path {
+    pub fn contains_point(&self, x: f64, y: f64, path_transform: &Transform2D<f32>) -> bool {
+        match *self {
+            #[cfg(feature = "canvas2d-azure")]
+            Path::Azure(ref path) => path.contains_point(x, y, path_transform),
+            Path::Software(ref path) => path.contains_point(x, y, path_transform),
+        }
+    }
+
+    pub fn copy_to_builder(&self) -> Box<dyn GenericPathBuilder> {
+        match *self {
+            #[cfg(feature = "canvas2d-azure")]
+            Path::Azure(ref path) => Box::new(path.copy_to_builder()),
+            Path::Software(ref path) => Box::new(path.copy_to_builder()),
+        }
+    }
+
+    pub fn transformed_copy_to_builder(
+        &self,
+        transform: &Transform2D<f32>,
+    ) -> Box<dyn GenericPathBuilder> {
+        match *self {
+            #[cfg(feature = "canvas2d-azure")]
+            Path::Azure(ref path) => Box::new(path.transformed_copy_to_builder(transform)),
+            Path::Software(ref path) => Box::new(path.transformed_copy_to_builder(transform)),
+        }
+    }
+}
+
+/// The source of the colors painted by fills and strokes.
+#[derive(Clone)]
+pub enum Pattern {
+    #[cfg(feature = "canvas2d-azure")]
+    Azure(azure::azure_hl::Pattern),
+    Software(software_backend::Pattern),
+}
+
+impl Pattern {
+    /// Whether this is a linear gradient whose start and end points are the same.
+    pub fn is_zero_size_gradient(&self) -> bool {
+        match *self {
+            #[cfg(feature = "canvas2d-azure")]
+            Pattern::Azure(ref pattern) => azure_backend::is_zero_size_gradient(pattern),
+            Pattern::Software(ref pattern) => pattern.is_zero_size_gradient(),
+        }
+    }
+
+    /// For surface patterns, the size of the surface and whether it repeats
+    /// horizontally and vertically.
+    pub fn surface_repeat(&self) -> Option<(Size2D<i32>, bool, bool)> {
+        match *self {
+            #[cfg(feature = "canvas2d-azure")]
+            Pattern::Azure(ref pattern) => azure_backend::surface_repeat(pattern),
+            Pattern::Software(ref pattern) => pattern.surface_repeat(),
+        }
+    }
+}
+
+/// An immutable image that can be drawn onto draw targets of the backend
+/// that created it.
+pub enum SourceSurface {
+    #[cfg(feature = "canvas2d-azure")]
+    Azure(azure::azure_hl::SourceSurface),
+    Software(software_backend::Surface),
+}
+
+/// How a drawing operation is composited onto its draw target.
+#[derive(Clone, Copy, Debug)]
+pub struct DrawOptions {
+    pub alpha: f32,
+    pub composition: CompositionOrBlending,
+    pub antialias: bool,
+}
+
+impl DrawOptions {
+    pub fn new(alpha: f32, composition: CompositionOrBlending, antialias: bool) -> DrawOptions {
+        DrawOptions {
+            alpha,
+            composition,
+            antialias,
+        }
+    }
+}
+
+/// The geometry of strokes.
+#[derive(Clone, Copy, Debug)]
+pub struct StrokeOptions {
+    pub line_width: f32,
+    pub line_join: LineJoinStyle,
+    pub line_cap: LineCapStyle,
+    pub miter_limit: f32,
+}
+
+impl StrokeOptions {
+    pub fn new(
+        line_width: f32,
+        line_join: LineJoinStyle,
+        line_cap: LineCapStyle,
+        miter_limit: f32,
+    ) -> StrokeOptions {
+        StrokeOptions {
+            line_width,
+            line_join,
+            line_cap,
+            miter_limit,
+        }
+    }
+}
+
+/// The filter used to sample surfaces when they are scaled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    Good,
+    Linear,
+    Point,
+}
+
+/// A color matrix whose rows are the output channels and whose columns are
+/// the input channels followed by an offset, like the values of an SVG
+/// feColorMatrix. It applies to unpremultiplied colors.
+pub type ColorMatrix = [[f32; 5]; 4];
+
 /// The canvas data stores a state machine for the current status of
 /// the path data and any relevant transformations that are
-/// applied to it. The Azure drawing API expects the path to be in
+/// applied to it. The drawing backends expect the path to be in
 /// userspace. However, when a path is being built but the canvas'
 /// transform changes, we choose to transform the path and perform
 /// further operations to it in device space. When it's time to
@@ -31,13 +296,13 @@ enum PathState {
     /// Path builder in user-space. If a transform has been applied
     /// but no further path operations have occurred, it is stored
     /// in the optional field.
-    UserSpacePathBuilder(PathBuilder, Option<Transform2D<AzFloat>>),
+    UserSpacePathBuilder(Box<dyn GenericPathBuilder>, Option<Transform2D<f32>>),
     /// Path builder in device-space.
-    DeviceSpacePathBuilder(PathBuilder),
+    DeviceSpacePathBuilder(Box<dyn GenericPathBuilder>),
     /// Path in user-space. If a transform has been applied but
     /// but no further path operations have occurred, it is stored
     /// in the optional field.
-    UserSpacePath(Path, Option<Transform2D<AzFloat>>),
+    UserSpacePath(Path, Option<Transform2D<f32>>),
 }
 
 impl PathState {
@@ -61,22 +326,22 @@ impl PathState {
 /// A wrapper around a stored PathBuilder and an optional transformation that should be
 /// applied to any points to ensure they are in the matching device space.
 struct PathBuilderRef<'a> {
-    builder: &'a PathBuilder,
-    transform: Transform2D<AzFloat>,
+    builder: &'a mut Box<dyn GenericPathBuilder>,
+    transform: Transform2D<f32>,
 }
 
 impl<'a> PathBuilderRef<'a> {
-    fn line_to(&self, pt: &Point2D<AzFloat>) {
+    fn line_to(&mut self, pt: &Point2D<f32>) {
         let pt = self.transform.transform_point(pt);
         self.builder.line_to(pt);
     }
 
-    fn move_to(&self, pt: &Point2D<AzFloat>) {
+    fn move_to(&mut self, pt: &Point2D<f32>) {
         let pt = self.transform.transform_point(pt);
         self.builder.move_to(pt);
     }
 
-    fn rect(&self, rect: &Rect<f32>) {
+    fn rect(&mut self, rect: &Rect<f32>) {
         let (first, second, third, fourth) = (
             Point2D::new(rect.origin.x, rect.origin.y),
             Point2D::new(rect.origin.x + rect.size.width, rect.origin.y),
@@ -95,19 +360,14 @@ impl<'a> PathBuilderRef<'a> {
         self.builder.close();
     }
 
-    fn quadratic_curve_to(&self, cp: &Point2D<AzFloat>, endpoint: &Point2D<AzFloat>) {
+    fn quadratic_curve_to(&mut self, cp: &Point2D<f32>, endpoint: &Point2D<f32>) {
         self.builder.quadratic_curve_to(
             &self.transform.transform_point(cp),
             &self.transform.transform_point(endpoint),
         )
     }
 
-    fn bezier_curve_to(
-        &self,
-        cp1: &Point2D<AzFloat>,
-        cp2: &Point2D<AzFloat>,
-        endpoint: &Point2D<AzFloat>,
-    ) {
+    fn bezier_curve_to(&mut self, cp1: &Point2D<f32>, cp2: &Point2D<f32>, endpoint: &Point2D<f32>) {
         self.builder.bezier_curve_to(
             &self.transform.transform_point(cp1),
             &self.transform.transform_point(cp2),
@@ -116,11 +376,11 @@ impl<'a> PathBuilderRef<'a> {
     }
 
     fn arc(
-        &self,
-        center: &Point2D<AzFloat>,
-        radius: AzFloat,
-        start_angle: AzFloat,
-        end_angle: AzFloat,
+        &mut self,
+        center: &Point2D<f32>,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
         ccw: bool,
     ) {
         let center = self.transform.transform_point(center);
@@ -129,13 +389,13 @@ impl<'a> PathBuilderRef<'a> {
     }
 
     pub fn ellipse(
-        &self,
-        center: &Point2D<AzFloat>,
-        radius_x: AzFloat,
-        radius_y: AzFloat,
-        rotation_angle: AzFloat,
-        start_angle: AzFloat,
-        end_angle: AzFloat,
+        &mut self,
+        center: &Point2D<f32>,
+        radius_x: f32,
+        radius_y: f32,
+        rotation_angle: f32,
+        start_angle: f32,
+        end_angle: f32,
         ccw: bool,
     ) {
         let center = self.transform.transform_point(center);
@@ -150,7 +410,7 @@ impl<'a> PathBuilderRef<'a> {
         );
     }
 
-    fn current_point(&self) -> Option<Point2D<AzFloat>> {
+    fn current_point(&self) -> Option<Point2D<f32>> {
         let inverse = match self.transform.inverse() {
             Some(i) => i,
             None => return None,
@@ -160,11 +420,12 @@ impl<'a> PathBuilderRef<'a> {
     }
 }
 
-pub struct CanvasData<'a> {
-    drawtarget: DrawTarget,
+pub struct CanvasData {
+    backend: Box<dyn Backend>,
+    drawtarget: Box<dyn GenericDrawTarget>,
     path_state: Option<PathState>,
-    state: CanvasPaintState<'a>,
-    saved_states: Vec<CanvasPaintState<'a>>,
+    state: CanvasPaintState,
+    saved_states: Vec<CanvasPaintState>,
     webrender_api: webrender_api::RenderApi,
     image_key: Option<webrender_api::ImageKey>,
     /// An old webrender image key that can be deleted when the next epoch ends.
@@ -174,19 +435,21 @@ pub struct CanvasData<'a> {
     pub canvas_id: CanvasId,
 }
 
-impl<'a> CanvasData<'a> {
+impl CanvasData {
     pub fn new(
         size: Size2D<u32>,
         webrender_api_sender: webrender_api::RenderApiSender,
-        antialias: AntialiasMode,
+        antialias: bool,
         canvas_id: CanvasId,
-    ) -> CanvasData<'a> {
-        let draw_target = CanvasData::create(size);
+    ) -> CanvasData {
+        let backend = create_backend();
+        let draw_target = backend.create_drawtarget(size);
         let webrender_api = webrender_api_sender.create_api();
         CanvasData {
+            state: CanvasPaintState::new(&*draw_target, antialias),
+            backend: backend,
             drawtarget: draw_target,
             path_state: None,
-            state: CanvasPaintState::new(antialias),
             saved_states: vec![],
             webrender_api: webrender_api,
             image_key: None,
@@ -204,6 +467,19 @@ impl<'a> CanvasData<'a> {
         source_rect: Rect<f64>,
         smoothing_enabled: bool,
     ) {
+        // From spec https://html.spec.whatwg.org/multipage/#dom-context-2d-drawimage
+        // When scaling up, if the imageSmoothingEnabled attribute is set to true, the user agent should attempt
+        // to apply a smoothing algorithm to the image data when it is scaled.
+        // Otherwise, the image must be rendered using nearest-neighbor interpolation.
+        let filter = if !smoothing_enabled {
+            Filter::Point
+        } else {
+            match self.state.image_smoothing_quality {
+                SmoothingQuality::Low => Filter::Linear,
+                SmoothingQuality::Medium | SmoothingQuality::High => Filter::Good,
+            }
+        };
+
         // We round up the floating pixel values to draw the pixels
         let source_rect = source_rect.ceil();
         // It discards the extra pixels (if any) that won't be painted
@@ -213,13 +489,27 @@ impl<'a> CanvasData<'a> {
             image_data.into()
         };
 
-        let writer = |draw_target: &DrawTarget| {
+        if !self.state.filter.is_empty() {
+            return self.draw_filtered(|draw_target, draw_options| {
+                write_image(
+                    draw_target,
+                    image_data,
+                    source_rect.size,
+                    dest_rect,
+                    filter,
+                    draw_options.composition,
+                    draw_options.alpha,
+                );
+            });
+        }
+
+        let writer = |draw_target: &dyn GenericDrawTarget| {
             write_image(
-                &draw_target,
+                draw_target,
                 image_data,
                 source_rect.size,
                 dest_rect,
-                smoothing_enabled,
+                filter,
                 self.state.draw_options.composition,
                 self.state.draw_options.alpha,
             );
@@ -233,7 +523,7 @@ impl<'a> CanvasData<'a> {
 
             self.draw_with_shadow(&rect, writer);
         } else {
-            writer(&self.drawtarget);
+            writer(&*self.drawtarget);
         }
     }
 
@@ -257,42 +547,42 @@ impl<'a> CanvasData<'a> {
     }
 
     pub fn fill_rect(&self, rect: &Rect<f32>) {
-        if is_zero_size_gradient(&self.state.fill_style) {
+        if self.state.fill_style.is_zero_size_gradient() {
             return; // Paint nothing if gradient size is zero.
         }
 
         let draw_rect = Rect::new(
             rect.origin,
-            match self.state.fill_style {
-                Pattern::Surface(ref surface) => {
-                    let surface_size = surface.size();
-                    match (surface.repeat_x, surface.repeat_y) {
-                        (true, true) => rect.size,
-                        (true, false) => Size2D::new(rect.size.width, surface_size.height as f32),
-                        (false, true) => Size2D::new(surface_size.width as f32, rect.size.height),
-                        (false, false) => {
-                            Size2D::new(surface_size.width as f32, surface_size.height as f32)
-                        },
-                    }
+            match self.state.fill_style.surface_repeat() {
+                Some((surface_size, repeat_x, repeat_y)) => match (repeat_x, repeat_y) {
+                    (true, true) => rect.size,
+                    (true, false) => Size2D::new(rect.size.width, surface_size.height as f32),
+                    (false, true) => Size2D::new(surface_size.width as f32, rect.size.height),
+                    (false, false) => {
+                        Size2D::new(surface_size.width as f32, surface_size.height as f32)
+                    },
                 },
-                _ => rect.size,
+                None => rect.size,
             },
         );
 
+        if !self.state.filter.is_empty() {
+            return self.draw_filtered(|draw_target, draw_options| {
+                draw_target.fill_rect(&draw_rect, &self.state.fill_style, draw_options);
+            });
+        }
+
         if self.need_to_draw_shadow() {
-            self.draw_with_shadow(&draw_rect, |new_draw_target: &DrawTarget| {
+            self.draw_with_shadow(&draw_rect, |new_draw_target| {
                 new_draw_target.fill_rect(
                     &draw_rect,
-                    self.state.fill_style.to_pattern_ref(),
-                    Some(&self.state.draw_options),
+                    &self.state.fill_style,
+                    &self.state.draw_options,
                 );
             });
         } else {
-            self.drawtarget.fill_rect(
-                &draw_rect,
-                self.state.fill_style.to_pattern_ref(),
-                Some(&self.state.draw_options),
-            );
+            self.drawtarget
+                .fill_rect(&draw_rect, &self.state.fill_style, &self.state.draw_options);
         }
     }
 
@@ -301,43 +591,51 @@ impl<'a> CanvasData<'a> {
     }
 
     pub fn stroke_rect(&self, rect: &Rect<f32>) {
-        if is_zero_size_gradient(&self.state.stroke_style) {
+        if self.state.stroke_style.is_zero_size_gradient() {
             return; // Paint nothing if gradient size is zero.
         }
 
+        if !self.state.filter.is_empty() {
+            return self.draw_filtered(|draw_target, draw_options| {
+                draw_target.stroke_rect(
+                    rect,
+                    &self.state.stroke_style,
+                    &self.state.stroke_opts,
+                    draw_options,
+                );
+            });
+        }
+
         if self.need_to_draw_shadow() {
-            self.draw_with_shadow(&rect, |new_draw_target: &DrawTarget| {
+            self.draw_with_shadow(&rect, |new_draw_target| {
                 new_draw_target.stroke_rect(
                     rect,
-                    self.state.stroke_style.to_pattern_ref(),
+                    &self.state.stroke_style,
                     &self.state.stroke_opts,
                     &self.state.draw_options,
                 );
             });
         } else if rect.size.width == 0. || rect.size.height == 0. {
             let cap = match self.state.stroke_opts.line_join {
-                JoinStyle::Round => CapStyle::Round,
-                _ => CapStyle::Butt,
+                LineJoinStyle::Round => LineCapStyle::Round,
+                _ => LineCapStyle::Butt,
             };
 
-            let stroke_opts = StrokeOptions::new(
-                self.state.stroke_opts.line_width,
-                self.state.stroke_opts.line_join,
-                cap,
-                self.state.stroke_opts.miter_limit,
-                self.state.stroke_opts.mDashPattern,
-            );
+            let stroke_opts = StrokeOptions {
+                line_cap: cap,
+                ..self.state.stroke_opts
+            };
             self.drawtarget.stroke_line(
                 rect.origin,
                 rect.bottom_right(),
-                self.state.stroke_style.to_pattern_ref(),
+                &self.state.stroke_style,
                 &stroke_opts,
                 &self.state.draw_options,
             );
         } else {
             self.drawtarget.stroke_rect(
                 rect,
-                self.state.stroke_style.to_pattern_ref(),
+                &self.state.stroke_style,
                 &self.state.stroke_opts,
                 &self.state.draw_options,
             );
@@ -364,7 +662,7 @@ impl<'a> CanvasData<'a> {
 
         // If a user-space builder exists, create a finished path from it.
         let new_state = match *self.path_state.as_mut().unwrap() {
-            PathState::UserSpacePathBuilder(ref builder, ref mut transform) => {
+            PathState::UserSpacePathBuilder(ref mut builder, ref mut transform) => {
                 Some((builder.finish(), transform.take()))
             },
             PathState::DeviceSpacePathBuilder(..) | PathState::UserSpacePath(..) => None,
@@ -389,8 +687,8 @@ impl<'a> CanvasData<'a> {
 
         // If a device-space builder is present, create a user-space path from its
         // finished path by inverting the initial transformation.
-        let new_state = match self.path_state.as_ref().unwrap() {
-            PathState::DeviceSpacePathBuilder(ref builder) => {
+        let new_state = match *self.path_state.as_mut().unwrap() {
+            PathState::DeviceSpacePathBuilder(ref mut builder) => {
                 let path = builder.finish();
                 let inverse = match self.drawtarget.get_transform().inverse() {
                     Some(m) => m,
@@ -399,7 +697,7 @@ impl<'a> CanvasData<'a> {
                         return;
                     },
                 };
-                let builder = path.transformed_copy_to_builder(&inverse);
+                let mut builder = path.transformed_copy_to_builder(&inverse);
                 Some(builder.finish())
             },
             PathState::UserSpacePathBuilder(..) | PathState::UserSpacePath(..) => None,
@@ -419,27 +717,44 @@ impl<'a> CanvasData<'a> {
     }
 
     pub fn fill(&mut self) {
-        if is_zero_size_gradient(&self.state.fill_style) {
+        if self.state.fill_style.is_zero_size_gradient() {
             return; // Paint nothing if gradient size is zero.
         }
 
         self.ensure_path();
+        if !self.state.filter.is_empty() {
+            return self.draw_filtered(|draw_target, draw_options| {
+                draw_target.fill(&self.path(), &self.state.fill_style, draw_options);
+            });
+        }
+
         self.drawtarget.fill(
             &self.path(),
-            self.state.fill_style.to_pattern_ref(),
+            &self.state.fill_style,
             &self.state.draw_options,
         );
     }
 
     pub fn stroke(&mut self) {
-        if is_zero_size_gradient(&self.state.stroke_style) {
+        if self.state.stroke_style.is_zero_size_gradient() {
             return; // Paint nothing if gradient size is zero.
         }
 
         self.ensure_path();
+        if !self.state.filter.is_empty() {
+            return self.draw_filtered(|draw_target, draw_options| {
+                draw_target.stroke(
+                    &self.path(),
+                    &self.state.stroke_style,
+                    &self.state.stroke_opts,
+                    draw_options,
+                );
+            });
+        }
+
         self.drawtarget.stroke(
             &self.path(),
-            self.state.stroke_style.to_pattern_ref(),
+            &self.state.stroke_style,
             &self.state.stroke_opts,
             &self.state.draw_options,
         );
@@ -469,11 +784,43 @@ impl<'a> CanvasData<'a> {
         chan.send(result).unwrap();
     }
 
-    pub fn move_to(&mut self, point: &Point2D<AzFloat>) {
+    pub fn is_point_in_stroke(&mut self, x: f64, y: f64, chan: IpcSender<bool>) {
+        self.ensure_path();
+        let result = match self.path_state.as_ref() {
+            Some(PathState::UserSpacePath(ref path, ref transform)) => {
+                let target_transform = self.drawtarget.get_transform();
+                let path_transform = transform.as_ref().unwrap_or(&target_transform);
+
+                // Stroke the path into a single pixel whose center is the point
+                // being tested, and check whether anything got painted there.
+                let draw_target = self
+                    .drawtarget
+                    .create_similar_draw_target(&Size2D::new(1, 1));
+                let matrix = Transform2D::identity()
+                    .pre_translate(Vector2D::new(0.5 - x as f32, 0.5 - y as f32))
+                    .pre_mul(path_transform);
+                draw_target.set_transform(&matrix);
+                let pattern = draw_target
+                    .create_pattern(&FillOrStrokeStyle::Color(RGBA::new(0, 0, 0, 255)))
+                    .unwrap();
+                draw_target.stroke(
+                    path,
+                    &pattern,
+                    &self.state.stroke_opts,
+                    &DrawOptions::new(1.0, CompositionOrBlending::default(), false),
+                );
+                draw_target.snapshot_data()[3] != 0
+            },
+            Some(_) | None => false,
+        };
+        chan.send(result).unwrap();
+    }
+
+    pub fn move_to(&mut self, point: &Point2D<f32>) {
         self.path_builder().move_to(point);
     }
 
-    pub fn line_to(&mut self, point: &Point2D<AzFloat>) {
+    pub fn line_to(&mut self, point: &Point2D<f32>) {
         self.path_builder().line_to(point);
     }
 
@@ -488,56 +835,37 @@ impl<'a> CanvasData<'a> {
         // Rust is not pleased by returning a reference to a builder in some branches
         // and overwriting path_state in other ones. The following awkward use of duplicate
         // matches works around the resulting borrow errors.
-        let new_state = {
-            match self.path_state.as_ref().unwrap() {
-                &PathState::UserSpacePathBuilder(_, None) |
-                &PathState::DeviceSpacePathBuilder(_) => None,
-                &PathState::UserSpacePathBuilder(ref builder, Some(ref transform)) => {
-                    let path = builder.finish();
-                    Some(PathState::DeviceSpacePathBuilder(
-                        path.transformed_copy_to_builder(transform),
-                    ))
-                },
-                &PathState::UserSpacePath(ref path, Some(ref transform)) => Some(
-                    PathState::DeviceSpacePathBuilder(path.transformed_copy_to_builder(transform)),
-                ),
-                &PathState::UserSpacePath(ref path, None) => Some(PathState::UserSpacePathBuilder(
-                    path.copy_to_builder(),
-                    None,
-                )),
-            }
-        };
-        match new_state {
-            // There's a new builder value that needs to be stored.
-            Some(state) => self.path_state = Some(state),
-            // There's an existing builder value that can be returned immediately.
-            None => match self.path_state.as_ref().unwrap() {
-                &PathState::UserSpacePathBuilder(ref builder, None) => {
-                    return PathBuilderRef {
-                        builder,
-                        transform: Transform2D::identity(),
-                    };
-                },
-                &PathState::DeviceSpacePathBuilder(ref builder) => {
-                    return PathBuilderRef {
-                        builder,
-                        transform: self.drawtarget.get_transform(),
-                    };
-                },
-                _ => unreachable!(),
+        let new_state = match *self.path_state.as_mut().unwrap() {
+            PathState::UserSpacePathBuilder(_, None) | PathState::DeviceSpacePathBuilder(_) => None,
+            PathState::UserSpacePathBuilder(ref mut builder, Some(ref transform)) => {
+                let path = builder.finish();
+                Some(PathState::DeviceSpacePathBuilder(
+                    path.transformed_copy_to_builder(transform),
+                ))
             },
+            PathState::UserSpacePath(ref path, Some(ref transform)) => Some(
+                PathState::DeviceSpacePathBuilder(path.transformed_copy_to_builder(transform)),
+            ),
+            PathState::UserSpacePath(ref path, None) => Some(PathState::UserSpacePathBuilder(
+                path.copy_to_builder(),
+                None,
+            )),
+        };
+        // There may be a new builder value that needs to be stored.
+        if let Some(state) = new_state {
+            self.path_state = Some(state);
         }
 
-        match self.path_state.as_ref().unwrap() {
-            &PathState::UserSpacePathBuilder(ref builder, None) => PathBuilderRef {
+        let transform = self.drawtarget.get_transform();
+        match *self.path_state.as_mut().unwrap() {
+            PathState::UserSpacePathBuilder(ref mut builder, None) => PathBuilderRef {
                 builder,
                 transform: Transform2D::identity(),
             },
-            &PathState::DeviceSpacePathBuilder(ref builder) => PathBuilderRef {
-                builder,
-                transform: self.drawtarget.get_transform(),
+            PathState::DeviceSpacePathBuilder(ref mut builder) => {
+                PathBuilderRef { builder, transform }
             },
-            &PathState::UserSpacePathBuilder(..) | &PathState::UserSpacePath(..) => unreachable!(),
+            PathState::UserSpacePathBuilder(..) | PathState::UserSpacePath(..) => unreachable!(),
         }
     }
 
@@ -545,34 +873,34 @@ impl<'a> CanvasData<'a> {
         self.path_builder().rect(rect);
     }
 
-    pub fn quadratic_curve_to(&mut self, cp: &Point2D<AzFloat>, endpoint: &Point2D<AzFloat>) {
+    pub fn quadratic_curve_to(&mut self, cp: &Point2D<f32>, endpoint: &Point2D<f32>) {
         self.path_builder().quadratic_curve_to(cp, endpoint);
     }
 
     pub fn bezier_curve_to(
         &mut self,
-        cp1: &Point2D<AzFloat>,
-        cp2: &Point2D<AzFloat>,
-        endpoint: &Point2D<AzFloat>,
+        cp1: &Point2D<f32>,
+        cp2: &Point2D<f32>,
+        endpoint: &Point2D<f32>,
     ) {
         self.path_builder().bezier_curve_to(cp1, cp2, endpoint);
     }
 
     pub fn arc(
         &mut self,
-        center: &Point2D<AzFloat>,
-        radius: AzFloat,
-        start_angle: AzFloat,
-        end_angle: AzFloat,
+        center: &Point2D<f32>,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
         ccw: bool,
     ) {
         self.path_builder()
             .arc(center, radius, start_angle, end_angle, ccw);
     }
 
-    pub fn arc_to(&mut self, cp1: &Point2D<AzFloat>, cp2: &Point2D<AzFloat>, radius: AzFloat) {
+    pub fn arc_to(&mut self, cp1: &Point2D<f32>, cp2: &Point2D<f32>, radius: f32) {
         let cp0 = match self.path_builder().current_point() {
-            Some(p) => p.as_azure_point(),
+            Some(p) => p,
             None => return,
         };
         let cp1 = *cp1;
@@ -635,12 +963,12 @@ impl<'a> CanvasData<'a> {
 
     pub fn ellipse(
         &mut self,
-        center: &Point2D<AzFloat>,
-        radius_x: AzFloat,
-        radius_y: AzFloat,
-        rotation_angle: AzFloat,
-        start_angle: AzFloat,
-        end_angle: AzFloat,
+        center: &Point2D<f32>,
+        radius_x: f32,
+        radius_y: f32,
+        rotation_angle: f32,
+        start_angle: f32,
+        end_angle: f32,
         ccw: bool,
     ) {
         self.path_builder().ellipse(
@@ -655,13 +983,13 @@ impl<'a> CanvasData<'a> {
     }
 
     pub fn set_fill_style(&mut self, style: FillOrStrokeStyle) {
-        if let Some(pattern) = style.to_azure_pattern(&self.drawtarget) {
+        if let Some(pattern) = self.drawtarget.create_pattern(&style) {
             self.state.fill_style = pattern
         }
     }
 
     pub fn set_stroke_style(&mut self, style: FillOrStrokeStyle) {
-        if let Some(pattern) = style.to_azure_pattern(&self.drawtarget) {
+        if let Some(pattern) = self.drawtarget.create_pattern(&style) {
             self.state.stroke_style = pattern
         }
     }
@@ -671,11 +999,11 @@ impl<'a> CanvasData<'a> {
     }
 
     pub fn set_line_cap(&mut self, cap: LineCapStyle) {
-        self.state.stroke_opts.line_cap = cap.to_azure_style();
+        self.state.stroke_opts.line_cap = cap;
     }
 
     pub fn set_line_join(&mut self, join: LineJoinStyle) {
-        self.state.stroke_opts.line_join = join.to_azure_style();
+        self.state.stroke_opts.line_join = join;
     }
 
     pub fn set_miter_limit(&mut self, limit: f32) {
@@ -703,19 +1031,20 @@ impl<'a> CanvasData<'a> {
     }
 
     pub fn set_global_composition(&mut self, op: CompositionOrBlending) {
-        self.state
-            .draw_options
-            .set_composition_op(op.to_azure_style());
+        self.state.draw_options.composition = op;
     }
 
-    pub fn create(size: Size2D<u32>) -> DrawTarget {
-        // FIXME(nox): Why is the size made of i32 values?
-        DrawTarget::new(BackendType::Skia, size.to_i32(), SurfaceFormat::B8G8R8A8)
+    pub fn set_filter(&mut self, filter: Vec<FilterFunction>) {
+        self.state.filter = filter;
+    }
+
+    pub fn set_image_smoothing_quality(&mut self, quality: SmoothingQuality) {
+        self.state.image_smoothing_quality = quality;
     }
 
     pub fn recreate(&mut self, size: Size2D<u32>) {
-        self.drawtarget = CanvasData::create(size);
-        self.state = CanvasPaintState::new(self.state.draw_options.antialias);
+        self.drawtarget = self.backend.create_drawtarget(size);
+        self.state = CanvasPaintState::new(&*self.drawtarget, self.state.draw_options.antialias);
         self.saved_states.clear();
         // Webrender doesn't let images change size, so we clear the webrender image key.
         // TODO: there is an annying race condition here: the display list builder
@@ -730,15 +1059,11 @@ impl<'a> CanvasData<'a> {
         }
     }
 
-    #[allow(unsafe_code)]
     pub fn send_pixels(&mut self, chan: IpcSender<IpcSharedMemory>) {
-        let data = IpcSharedMemory::from_bytes(unsafe {
-            self.drawtarget.snapshot().get_data_surface().data()
-        });
+        let data = IpcSharedMemory::from_bytes(&self.drawtarget.snapshot_data());
         chan.send(data).unwrap();
     }
 
-    #[allow(unsafe_code)]
     pub fn send_data(&mut self, chan: IpcSender<CanvasImageData>) {
         let size = self.drawtarget.get_size();
 
@@ -750,9 +1075,7 @@ impl<'a> CanvasData<'a> {
             is_opaque: false,
             allow_mipmaps: false,
         };
-        let data = webrender_api::ImageData::Raw(Arc::new(unsafe {
-            self.drawtarget.snapshot().get_data_surface().data().into()
-        }));
+        let data = webrender_api::ImageData::Raw(Arc::new(self.drawtarget.snapshot_data()));
 
         let mut txn = webrender_api::Transaction::new();
 
@@ -793,7 +1116,6 @@ impl<'a> CanvasData<'a> {
                 &imagedata,
                 rect.size.to_i32(),
                 rect.size.width as i32 * 4,
-                SurfaceFormat::B8G8R8A8,
             )
             .unwrap();
         self.drawtarget.copy_surface(
@@ -815,26 +1137,23 @@ impl<'a> CanvasData<'a> {
         self.state.shadow_blur = value;
     }
 
-    pub fn set_shadow_color(&mut self, value: Color) {
+    pub fn set_shadow_color(&mut self, value: RGBA) {
         self.state.shadow_color = value;
     }
 
     // https://html.spec.whatwg.org/multipage/#when-shadows-are-drawn
     fn need_to_draw_shadow(&self) -> bool {
-        self.state.shadow_color.a != 0.0f32 &&
+        self.state.shadow_color.alpha != 0 &&
             (self.state.shadow_offset_x != 0.0f64 ||
                 self.state.shadow_offset_y != 0.0f64 ||
                 self.state.shadow_blur != 0.0f64)
     }
 
-    fn create_draw_target_for_shadow(&self, source_rect: &Rect<f32>) -> DrawTarget {
-        let draw_target = self.drawtarget.create_similar_draw_target(
-            &Size2D::new(
-                source_rect.size.width as i32,
-                source_rect.size.height as i32,
-            ),
-            self.drawtarget.get_format(),
-        );
+    fn create_draw_target_for_shadow(&self, source_rect: &Rect<f32>) -> Box<dyn GenericDrawTarget> {
+        let draw_target = self.drawtarget.create_similar_draw_target(&Size2D::new(
+            source_rect.size.width as i32,
+            source_rect.size.height as i32,
+        ));
         let matrix = Transform2D::identity()
             .pre_translate(-source_rect.origin.to_vector().cast())
             .pre_mul(&self.state.transform);
@@ -844,31 +1163,100 @@ impl<'a> CanvasData<'a> {
 
     fn draw_with_shadow<F>(&self, rect: &Rect<f32>, draw_shadow_source: F)
     where
-        F: FnOnce(&DrawTarget),
+        F: FnOnce(&dyn GenericDrawTarget),
     {
         let shadow_src_rect = self.state.transform.transform_rect(rect);
         let new_draw_target = self.create_draw_target_for_shadow(&shadow_src_rect);
-        draw_shadow_source(&new_draw_target);
+        draw_shadow_source(&*new_draw_target);
         self.drawtarget.draw_surface_with_shadow(
             new_draw_target.snapshot(),
-            &Point2D::new(
-                shadow_src_rect.origin.x as AzFloat,
-                shadow_src_rect.origin.y as AzFloat,
-            ),
+            &Point2D::new(shadow_src_rect.origin.x, shadow_src_rect.origin.y),
             &self.state.shadow_color,
             &Vector2D::new(
-                self.state.shadow_offset_x as AzFloat,
-                self.state.shadow_offset_y as AzFloat,
+                self.state.shadow_offset_x as f32,
+                self.state.shadow_offset_y as f32,
             ),
-            (self.state.shadow_blur / 2.0f64) as AzFloat,
+            (self.state.shadow_blur / 2.0f64) as f32,
             self.state.draw_options.composition,
         );
     }
 
+    // https://html.spec.whatwg.org/multipage/#drawing-model
+    /// Paints a drawing operation when a filter is set: the operation is
+    /// rendered into a scratch surface, which then gets filtered and
+    /// composited onto the canvas together with its shadow.
+    fn draw_filtered<F>(&self, draw: F)
+    where
+        F: FnOnce(&dyn GenericDrawTarget, &DrawOptions),
+    {
+        let size = self.drawtarget.get_size();
+        let scratch = self.drawtarget.create_similar_draw_target(&size);
+        scratch.set_transform(&self.state.transform);
+        let draw_options = DrawOptions::new(
+            self.state.draw_options.alpha,
+            CompositionOrBlending::default(),
+            self.state.draw_options.antialias,
+        );
+        draw(&*scratch, &draw_options);
+        let filtered = self.apply_filter(scratch.snapshot(), &size);
+
+        // The filtered surface and its shadow are in device space.
+        self.drawtarget.set_transform(&Transform2D::identity());
+        if self.need_to_draw_shadow() {
+            self.drawtarget.draw_surface_with_shadow(
+                filtered,
+                &Point2D::zero(),
+                &self.state.shadow_color,
+                &Vector2D::new(
+                    self.state.shadow_offset_x as f32,
+                    self.state.shadow_offset_y as f32,
+                ),
+                (self.state.shadow_blur / 2.0f64) as f32,
+                self.state.draw_options.composition,
+            );
+        } else {
+            let rect = Rect::from_size(size.to_f32());
+            self.drawtarget.draw_surface(
+                filtered,
+                rect,
+                rect,
+                Filter::Point,
+                &DrawOptions::new(1.0, self.state.draw_options.composition, false),
+            );
+        }
+        self.drawtarget.set_transform(&self.state.transform);
+    }
+
+    /// Runs a surface through each function of the current filter in turn.
+    fn apply_filter(&self, mut surface: SourceSurface, size: &Size2D<i32>) -> SourceSurface {
+        for function in &self.state.filter {
+            let draw_target = self.drawtarget.create_similar_draw_target(size);
+            match *function {
+                FilterFunction::Blur(std_deviation) => {
+                    draw_target.draw_gaussian_blur(surface, std_deviation);
+                },
+                FilterFunction::DropShadow(ref offset, radius, color) => {
+                    draw_target.draw_surface_with_shadow(
+                        surface,
+                        &Point2D::zero(),
+                        &color,
+                        offset,
+                        radius / 2.0,
+                        CompositionOrBlending::default(),
+                    );
+                },
+                ref function => {
+                    draw_target.draw_color_matrix(surface, &to_color_matrix(function));
+                },
+            }
+            surface = draw_target.snapshot();
+        }
+        surface
+    }
+
     /// It reads image data from the canvas
     /// canvas_size: The size of the canvas we're reading from
     /// read_rect: The area of the canvas we want to read from
-    #[allow(unsafe_code)]
     pub fn read_pixels(&self, read_rect: Rect<u32>, canvas_size: Size2D<u32>) -> Vec<u8> {
         let canvas_rect = Rect::from_size(canvas_size);
         if canvas_rect
@@ -877,9 +1265,8 @@ impl<'a> CanvasData<'a> {
         {
             return vec![];
         }
-        let data_surface = self.drawtarget.snapshot().get_data_surface();
         pixels::rgba8_get_rect(
-            unsafe { data_surface.data() },
+            &self.drawtarget.snapshot_data(),
             canvas_size.to_u32(),
             read_rect.to_u32(),
         )
@@ -887,7 +1274,7 @@ impl<'a> CanvasData<'a> {
     }
 }
 
-impl<'a> Drop for CanvasData<'a> {
+impl Drop for CanvasData {
     fn drop(&mut self) {
         let mut txn = webrender_api::Transaction::new();
 
@@ -903,48 +1290,137 @@ impl<'a> Drop for CanvasData<'a> {
 }
 
 #[derive(Clone)]
-struct CanvasPaintState<'a> {
+struct CanvasPaintState {
     draw_options: DrawOptions,
     fill_style: Pattern,
     stroke_style: Pattern,
-    stroke_opts: StrokeOptions<'a>,
+    stroke_opts: StrokeOptions,
     /// The current 2D transform matrix.
     transform: Transform2D<f32>,
     shadow_offset_x: f64,
     shadow_offset_y: f64,
     shadow_blur: f64,
-    shadow_color: Color,
+    shadow_color: RGBA,
+    filter: Vec<FilterFunction>,
+    image_smoothing_quality: SmoothingQuality,
 }
 
-impl<'a> CanvasPaintState<'a> {
-    fn new(antialias: AntialiasMode) -> CanvasPaintState<'a> {
+impl CanvasPaintState {
+    fn new(drawtarget: &dyn GenericDrawTarget, antialias: bool) -> CanvasPaintState {
+        let black = drawtarget
+            .create_pattern(&FillOrStrokeStyle::Color(RGBA::new(0, 0, 0, 255)))
+            .unwrap();
         CanvasPaintState {
-            draw_options: DrawOptions::new(1.0, CompositionOp::Over, antialias),
-            fill_style: Pattern::Color(ColorPattern::new(Color::black())),
-            stroke_style: Pattern::Color(ColorPattern::new(Color::black())),
-            stroke_opts: StrokeOptions::new(
-                1.0,
-                JoinStyle::MiterOrBevel,
-                CapStyle::Butt,
-                10.0,
-                &[],
-            ),
+            draw_options: DrawOptions::new(1.0, CompositionOrBlending::default(), antialias),
+            fill_style: black.clone(),
+            stroke_style: black,
+            stroke_opts: StrokeOptions::new(1.0, LineJoinStyle::Miter, LineCapStyle::Butt, 10.0),
             transform: Transform2D::identity(),
             shadow_offset_x: 0.0,
             shadow_offset_y: 0.0,
             shadow_blur: 0.0,
-            shadow_color: Color::transparent(),
+            shadow_color: RGBA::transparent(),
+            filter: vec![],
+            image_smoothing_quality: SmoothingQuality::Low,
         }
     }
 }
 
-fn is_zero_size_gradient(pattern: &Pattern) -> bool {
-    if let &Pattern::LinearGradient(ref gradient) = pattern {
-        if gradient.is_zero_size() {
-            return true;
-        }
+// https://drafts.fxtf.org/filter-effects/#supported-filter-functions
+/// Returns the color matrix equivalent to a filter function.
+fn to_color_matrix(function: &FilterFunction) -> ColorMatrix {
+    fn rgb(m: [[f32; 3]; 3]) -> [[f32; 5]; 4] {
+        [
+            [m[0][0], m[0][1], m[0][2], 0., 0.],
+            [m[1][0], m[1][1], m[1][2], 0., 0.],
+            [m[2][0], m[2][1], m[2][2], 0., 0.],
+            [0., 0., 0., 1., 0.],
+        ]
+    }
+
+    match *function {
+        FilterFunction::Brightness(a) => rgb([[a, 0., 0.], [0., a, 0.], [0., 0., a]]),
+        FilterFunction::Contrast(a) => {
+            let offset = 0.5 - 0.5 * a;
+            [
+                [a, 0., 0., 0., offset],
+                [0., a, 0., 0., offset],
+                [0., 0., a, 0., offset],
+                [0., 0., 0., 1., 0.],
+            ]
+        },
+        FilterFunction::Grayscale(a) => {
+            let a = 1. - a;
+            rgb([
+                [
+                    0.2126 + 0.7874 * a,
+                    0.7152 - 0.7152 * a,
+                    0.0722 - 0.0722 * a,
+                ],
+                [
+                    0.2126 - 0.2126 * a,
+                    0.7152 + 0.2848 * a,
+                    0.0722 - 0.0722 * a,
+                ],
+                [
+                    0.2126 - 0.2126 * a,
+                    0.7152 - 0.7152 * a,
+                    0.0722 + 0.9278 * a,
+                ],
+            ])
+        },
+        FilterFunction::HueRotate(angle) => {
+            let (sin, cos) = (angle.sin(), angle.cos());
+            rgb([
+                [
+                    0.213 + cos * 0.787 - sin * 0.213,
+                    0.715 - cos * 0.715 - sin * 0.715,
+                    0.072 - cos * 0.072 + sin * 0.928,
+                ],
+                [
+                    0.213 - cos * 0.213 + sin * 0.143,
+                    0.715 + cos * 0.285 + sin * 0.140,
+                    0.072 - cos * 0.072 - sin * 0.283,
+                ],
+                [
+                    0.213 - cos * 0.213 - sin * 0.787,
+                    0.715 - cos * 0.715 + sin * 0.715,
+                    0.072 + cos * 0.928 + sin * 0.072,
+                ],
+            ])
+        },
+        FilterFunction::Invert(a) => {
+            let slope = 1. - 2. * a;
+            [
+                [slope, 0., 0., 0., a],
+                [0., slope, 0., 0., a],
+                [0., 0., slope, 0., a],
+                [0., 0., 0., 1., 0.],
+            ]
+        },
+        FilterFunction::Opacity(a) => [
+            [1., 0., 0., 0., 0.],
+            [0., 1., 0., 0., 0.],
+            [0., 0., 1., 0., 0.],
+            [0., 0., 0., a, 0.],
+        ],
+        FilterFunction::Saturate(s) => rgb([
+            [0.213 + 0.787 * s, 0.715 - 0.715 * s, 0.072 - 0.072 * s],
+            [0.213 - 0.213 * s, 0.715 + 0.285 * s, 0.072 - 0.072 * s],
+            [0.213 - 0.213 * s, 0.715 - 0.715 * s, 0.072 + 0.928 * s],
+        ]),
+        FilterFunction::Sepia(a) => {
+            let a = 1. - a;
+            rgb([
+                [0.393 + 0.607 * a, 0.769 - 0.769 * a, 0.189 - 0.189 * a],
+                [0.349 - 0.349 * a, 0.686 + 0.314 * a, 0.168 - 0.168 * a],
+                [0.272 - 0.272 * a, 0.534 - 0.534 * a, 0.131 + 0.869 * a],
+            ])
+        },
+        FilterFunction::Blur(..) | FilterFunction::DropShadow(..) => {
+            unreachable!("Not a color matrix filter function.")
+        },
     }
-    false
 }
 
 /// It writes an image to the destination target
@@ -952,48 +1428,32 @@ fn is_zero_size_gradient(pattern: &Pattern) -> bool {
 /// image_data: Pixel information of the image to be written. It takes RGBA8
 /// image_size: The size of the image to be written
 /// dest_rect: Area of the destination target where the pixels will be copied
-/// smoothing_enabled: It determines if smoothing is applied to the image result
+/// filter: The filter used when scaling the image, which depends on image smoothing
 fn write_image(
-    draw_target: &DrawTarget,
+    draw_target: &dyn GenericDrawTarget,
     image_data: Vec<u8>,
     image_size: Size2D<f64>,
     dest_rect: Rect<f64>,
-    smoothing_enabled: bool,
-    composition_op: CompositionOp,
+    filter: Filter,
+    composition_op: CompositionOrBlending,
     global_alpha: f32,
 ) {
     if image_data.is_empty() {
         return;
     }
     let image_rect = Rect::new(Point2D::zero(), image_size);
-
-    // From spec https://html.spec.whatwg.org/multipage/#dom-context-2d-drawimage
-    // When scaling up, if the imageSmoothingEnabled attribute is set to true, the user agent should attempt
-    // to apply a smoothing algorithm to the image data when it is scaled.
-    // Otherwise, the image must be rendered using nearest-neighbor interpolation.
-    let filter = if smoothing_enabled {
-        Filter::Linear
-    } else {
-        Filter::Point
-    };
     let image_size = image_size.to_i32();
 
     let source_surface = draw_target
-        .create_source_surface_from_data(
-            &image_data,
-            image_size,
-            image_size.width * 4,
-            SurfaceFormat::B8G8R8A8,
-        )
+        .create_source_surface_from_data(&image_data, image_size, image_size.width * 4)
         .unwrap();
-    let draw_surface_options = DrawSurfaceOptions::new(filter, true);
-    let draw_options = DrawOptions::new(global_alpha, composition_op, AntialiasMode::None);
+    let draw_options = DrawOptions::new(global_alpha, composition_op, false);
     draw_target.draw_surface(
         source_surface,
-        dest_rect.to_azure_style(),
-        image_rect.to_azure_style(),
-        draw_surface_options,
-        draw_options,
+        dest_rect.to_f32(),
+        image_rect.to_f32(),
+        filter,
+        &draw_options,
     );
 }
 
@@ -1043,188 +1503,3 @@ impl RectToi32 for Rect<f64> {
         )
     }
 }
-
-pub trait ToAzureStyle {
-    type Target;
-    fn to_azure_style(self) -> Self::Target;
-}
-
-impl ToAzureStyle for Rect<f64> {
-    type Target = Rect<AzFloat>;
-
-    fn to_azure_style(self) -> Rect<AzFloat> {
-        Rect::new(
-            Point2D::new(self.origin.x as AzFloat, self.origin.y as AzFloat),
-            Size2D::new(self.size.width as AzFloat, self.size.height as AzFloat),
-        )
-    }
-}
-
-impl ToAzureStyle for LineCapStyle {
-    type Target = CapStyle;
-
-    fn to_azure_style(self) -> CapStyle {
-        match self {
-            LineCapStyle::Butt => CapStyle::Butt,
-            LineCapStyle::Round => CapStyle::Round,
-            LineCapStyle::Square => CapStyle::Square,
-        }
-    }
-}
-
-impl ToAzureStyle for LineJoinStyle {
-    type Target = JoinStyle;
-
-    fn to_azure_style(self) -> JoinStyle {
-        match self {
-            LineJoinStyle::Round => JoinStyle::Round,
-            LineJoinStyle::Bevel => JoinStyle::Bevel,
-            LineJoinStyle::Miter => JoinStyle::Miter,
-        }
-    }
-}
-
-impl ToAzureStyle for CompositionStyle {
-    type Target = CompositionOp;
-
-    fn to_azure_style(self) -> CompositionOp {
-        match self {
-            CompositionStyle::SrcIn => CompositionOp::In,
-            CompositionStyle::SrcOut => CompositionOp::Out,
-            CompositionStyle::SrcOver => CompositionOp::Over,
-            CompositionStyle::SrcAtop => CompositionOp::Atop,
-            CompositionStyle::DestIn => CompositionOp::DestIn,
-            CompositionStyle::DestOut => CompositionOp::DestOut,
-            CompositionStyle::DestOver => CompositionOp::DestOver,
-            CompositionStyle::DestAtop => CompositionOp::DestAtop,
-            CompositionStyle::Copy => CompositionOp::Source,
-            CompositionStyle::Lighter => CompositionOp::Add,
-            CompositionStyle::Xor => CompositionOp::Xor,
-        }
-    }
-}
-
-impl ToAzureStyle for BlendingStyle {
-    type Target = CompositionOp;
-
-    fn to_azure_style(self) -> CompositionOp {
-        match self {
-            BlendingStyle::Multiply => CompositionOp::Multiply,
-            BlendingStyle::Screen => CompositionOp::Screen,
-            BlendingStyle::Overlay => CompositionOp::Overlay,
-            BlendingStyle::Darken => CompositionOp::Darken,
-            BlendingStyle::Lighten => CompositionOp::Lighten,
-            BlendingStyle::ColorDodge => CompositionOp::ColorDodge,
-            BlendingStyle::ColorBurn => CompositionOp::ColorBurn,
-            BlendingStyle::HardLight => CompositionOp::HardLight,
-            BlendingStyle::SoftLight => CompositionOp::SoftLight,
-            BlendingStyle::Difference => CompositionOp::Difference,
-            BlendingStyle::Exclusion => CompositionOp::Exclusion,
-            BlendingStyle::Hue => CompositionOp::Hue,
-            BlendingStyle::Saturation => CompositionOp::Saturation,
-            BlendingStyle::Color => CompositionOp::Color,
-            BlendingStyle::Luminosity => CompositionOp::Luminosity,
-        }
-    }
-}
-
-impl ToAzureStyle for CompositionOrBlending {
-    type Target = CompositionOp;
-
-    fn to_azure_style(self) -> CompositionOp {
-        match self {
-            CompositionOrBlending::Composition(op) => op.to_azure_style(),
-            CompositionOrBlending::Blending(op) => op.to_azure_style(),
-        }
-    }
-}
-
-pub trait ToAzurePattern {
-    fn to_azure_pattern(&self, drawtarget: &DrawTarget) -> Option<Pattern>;
-}
-
-impl ToAzurePattern for FillOrStrokeStyle {
-    fn to_azure_pattern(&self, drawtarget: &DrawTarget) -> Option<Pattern> {
-        Some(match *self {
-            FillOrStrokeStyle::Color(ref color) => {
-                Pattern::Color(ColorPattern::new(color.to_azure_style()))
-            },
-            FillOrStrokeStyle::LinearGradient(ref linear_gradient_style) => {
-                let gradient_stops: Vec<GradientStop> = linear_gradient_style
-                    .stops
-                    .iter()
-                    .map(|s| GradientStop {
-                        offset: s.offset as AzFloat,
-                        color: s.color.to_azure_style(),
-                    })
-                    .collect();
-
-                Pattern::LinearGradient(LinearGradientPattern::new(
-                    &Point2D::new(
-                        linear_gradient_style.x0 as AzFloat,
-                        linear_gradient_style.y0 as AzFloat,
-                    ),
-                    &Point2D::new(
-                        linear_gradient_style.x1 as AzFloat,
-                        linear_gradient_style.y1 as AzFloat,
-                    ),
-                    drawtarget.create_gradient_stops(&gradient_stops, ExtendMode::Clamp),
-                    &Transform2D::identity(),
-                ))
-            },
-            FillOrStrokeStyle::RadialGradient(ref radial_gradient_style) => {
-                let gradient_stops: Vec<GradientStop> = radial_gradient_style
-                    .stops
-                    .iter()
-                    .map(|s| GradientStop {
-                        offset: s.offset as AzFloat,
-                        color: s.color.to_azure_style(),
-                    })
-                    .collect();
-
-                Pattern::RadialGradient(RadialGradientPattern::new(
-                    &Point2D::new(
-                        radial_gradient_style.x0 as AzFloat,
-                        radial_gradient_style.y0 as AzFloat,
-                    ),
-                    &Point2D::new(
-                        radial_gradient_style.x1 as AzFloat,
-                        radial_gradient_style.y1 as AzFloat,
-                    ),
-                    radial_gradient_style.r0 as AzFloat,
-                    radial_gradient_style.r1 as AzFloat,
-                    drawtarget.create_gradient_stops(&gradient_stops, ExtendMode::Clamp),
-                    &Transform2D::identity(),
-                ))
-            },
-            FillOrStrokeStyle::Surface(ref surface_style) => {
-                let source_surface = drawtarget.create_source_surface_from_data(
-                    &surface_style.surface_data,
-                    // FIXME(nox): Why are those i32 values?
-                    surface_style.surface_size.to_i32(),
-                    surface_style.surface_size.width as i32 * 4,
-                    SurfaceFormat::B8G8R8A8,
-                )?;
-                Pattern::Surface(SurfacePattern::new(
-                    source_surface.azure_source_surface,
-                    surface_style.repeat_x,
-                    surface_style.repeat_y,
-                    &Transform2D::identity(),
-                ))
-            },
-        })
-    }
-}
-
-impl ToAzureStyle for RGBA {
-    type Target = Color;
-
-    fn to_azure_style(self) -> Color {
-        Color::rgba(
-            self.red_f32() as AzFloat,
-            self.green_f32() as AzFloat,
-            self.blue_f32() as AzFloat,
-            self.alpha_f32() as AzFloat,
-        )
-    }
-}
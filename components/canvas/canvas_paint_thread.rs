@@ -3,7 +3,6 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::canvas_data::*;
-use azure::azure_hl::AntialiasMode;
 use canvas_traits::canvas::*;
 use euclid::Size2D;
 use ipc_channel::ipc::{self, IpcSender};
@@ -11,13 +10,13 @@ use std::borrow::ToOwned;
 use std::collections::HashMap;
 use std::thread;
 
-pub struct CanvasPaintThread<'a> {
-    canvases: HashMap<CanvasId, CanvasData<'a>>,
+pub struct CanvasPaintThread {
+    canvases: HashMap<CanvasId, CanvasData>,
     next_canvas_id: CanvasId,
 }
 
-impl<'a> CanvasPaintThread<'a> {
-    fn new() -> CanvasPaintThread<'a> {
+impl CanvasPaintThread {
+    fn new() -> CanvasPaintThread {
         CanvasPaintThread {
             canvases: HashMap::new(),
             next_canvas_id: CanvasId(0),
@@ -81,12 +80,6 @@ impl<'a> CanvasPaintThread<'a> {
         webrender_api_sender: webrender_api::RenderApiSender,
         antialias: bool,
     ) -> CanvasId {
-        let antialias = if antialias {
-            AntialiasMode::Default
-        } else {
-            AntialiasMode::None
-        };
-
         let canvas_id = self.next_canvas_id.clone();
         self.next_canvas_id.0 += 1;
 
@@ -112,6 +105,9 @@ impl<'a> CanvasPaintThread<'a> {
             Canvas2dMsg::IsPointInPath(x, y, fill_rule, chan) => self
                 .canvas(canvas_id)
                 .is_point_in_path(x, y, fill_rule, chan),
+            Canvas2dMsg::IsPointInStroke(x, y, chan) => {
+                self.canvas(canvas_id).is_point_in_stroke(x, y, chan)
+            },
             Canvas2dMsg::DrawImage(
                 imagedata,
                 image_size,
@@ -195,13 +191,17 @@ impl<'a> CanvasPaintThread<'a> {
                 self.canvas(canvas_id).set_shadow_offset_y(value)
             },
             Canvas2dMsg::SetShadowBlur(value) => self.canvas(canvas_id).set_shadow_blur(value),
-            Canvas2dMsg::SetShadowColor(ref color) => self
-                .canvas(canvas_id)
-                .set_shadow_color(color.to_azure_style()),
+            Canvas2dMsg::SetShadowColor(ref color) => {
+                self.canvas(canvas_id).set_shadow_color(*color)
+            },
+            Canvas2dMsg::SetFilter(filter) => self.canvas(canvas_id).set_filter(filter),
+            Canvas2dMsg::SetImageSmoothingQuality(quality) => {
+                self.canvas(canvas_id).set_image_smoothing_quality(quality)
+            },
         }
     }
 
-    fn canvas(&mut self, canvas_id: CanvasId) -> &mut CanvasData<'a> {
+    fn canvas(&mut self, canvas_id: CanvasId) -> &mut CanvasData {
         self.canvases.get_mut(&canvas_id).expect("Bogus canvas id")
     }
 }
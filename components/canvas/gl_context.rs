@@ -261,6 +261,11 @@ fn map_limits(limits: RawGLLimits) -> GLLimits {
         max_varying_vectors: limits.max_varying_vectors,
         max_vertex_texture_image_units: limits.max_vertex_texture_image_units,
         max_vertex_uniform_vectors: limits.max_vertex_uniform_vectors,
+        max_uniform_buffer_bindings: 0,
+        max_transform_feedback_separate_attribs: 0,
+        uniform_buffer_offset_alignment: 0,
+        max_color_attachments: 1,
+        max_draw_buffers: 1,
     }
 }
 
@@ -7,8 +7,11 @@
 #[macro_use]
 extern crate log;
 
+#[cfg(feature = "canvas2d-azure")]
+pub mod azure_backend;
 pub mod canvas_data;
 pub mod canvas_paint_thread;
 pub mod gl_context;
+pub mod software_backend;
 mod webgl_mode;
 pub mod webgl_thread;
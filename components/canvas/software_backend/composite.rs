@@ -0,0 +1,191 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Compositing of premultiplied colors.
+//! https://drafts.fxtf.org/compositing-1/
+
+use crate::software_backend::paint::Color;
+use canvas_traits::canvas::{BlendingStyle, CompositionOrBlending, CompositionStyle};
+
+/// Whether an operator leaves the destination untouched where the source is
+/// transparent. The other ones apply to the whole clip area.
+pub fn is_bounded(operator: CompositionOrBlending) -> bool {
+    match operator {
+        CompositionOrBlending::Composition(CompositionStyle::Copy) |
+        CompositionOrBlending::Composition(CompositionStyle::SrcIn) |
+        CompositionOrBlending::Composition(CompositionStyle::SrcOut) |
+        CompositionOrBlending::Composition(CompositionStyle::DestIn) |
+        CompositionOrBlending::Composition(CompositionStyle::DestAtop) => false,
+        _ => true,
+    }
+}
+
+/// Composites a source color over a destination color.
+pub fn composite(operator: CompositionOrBlending, source: Color, destination: Color) -> Color {
+    match operator {
+        CompositionOrBlending::Composition(style) => porter_duff(style, source, destination),
+        CompositionOrBlending::Blending(style) => blend(style, source, destination),
+    }
+}
+
+fn porter_duff(style: CompositionStyle, source: Color, destination: Color) -> Color {
+    let (source_alpha, destination_alpha) = (source[3], destination[3]);
+    let (source_factor, destination_factor) = match style {
+        CompositionStyle::SrcOver => (1., 1. - source_alpha),
+        CompositionStyle::SrcIn => (destination_alpha, 0.),
+        CompositionStyle::SrcOut => (1. - destination_alpha, 0.),
+        CompositionStyle::SrcAtop => (destination_alpha, 1. - source_alpha),
+        CompositionStyle::DestOver => (1. - destination_alpha, 1.),
+        CompositionStyle::DestIn => (0., source_alpha),
+        CompositionStyle::DestOut => (0., 1. - source_alpha),
+        CompositionStyle::DestAtop => (1. - destination_alpha, source_alpha),
+        CompositionStyle::Copy => (1., 0.),
+        CompositionStyle::Lighter => (1., 1.),
+        CompositionStyle::Xor => (1. - destination_alpha, 1. - source_alpha),
+    };
+    let mut result = [0.; 4];
+    for i in 0..4 {
+        result[i] = (source[i] * source_factor + destination[i] * destination_factor).min(1.);
+    }
+    result
+}
+
+// https://drafts.fxtf.org/compositing-1/#blending
+fn blend(style: BlendingStyle, source: Color, destination: Color) -> Color {
+    let (source_alpha, destination_alpha) = (source[3], destination[3]);
+    let unpremultiply = |color: Color| {
+        if color[3] == 0. {
+            [0.; 3]
+        } else {
+            [
+                color[0] / color[3],
+                color[1] / color[3],
+                color[2] / color[3],
+            ]
+        }
+    };
+    let (cs, cb) = (unpremultiply(source), unpremultiply(destination));
+    let blended = match style {
+        BlendingStyle::Hue => set_lum(set_sat(cs, sat(cb)), lum(cb)),
+        BlendingStyle::Saturation => set_lum(set_sat(cb, sat(cs)), lum(cb)),
+        BlendingStyle::Color => set_lum(cs, lum(cb)),
+        BlendingStyle::Luminosity => set_lum(cb, lum(cs)),
+        _ => [
+            blend_channel(style, cb[0], cs[0]),
+            blend_channel(style, cb[1], cs[1]),
+            blend_channel(style, cb[2], cs[2]),
+        ],
+    };
+    let both = source_alpha * destination_alpha;
+    let mut result = [0.; 4];
+    for i in 0..3 {
+        result[i] = source[i] * (1. - destination_alpha) +
+            destination[i] * (1. - source_alpha) +
+            both * blended[i];
+    }
+    result[3] = source_alpha + destination_alpha - both;
+    result
+}
+
+fn blend_channel(style: BlendingStyle, cb: f32, cs: f32) -> f32 {
+    match style {
+        BlendingStyle::Multiply => cb * cs,
+        BlendingStyle::Screen => cb + cs - cb * cs,
+        BlendingStyle::Overlay => blend_channel(BlendingStyle::HardLight, cs, cb),
+        BlendingStyle::Darken => cb.min(cs),
+        BlendingStyle::Lighten => cb.max(cs),
+        BlendingStyle::ColorDodge => {
+            if cb == 0. {
+                0.
+            } else if cs >= 1. {
+                1.
+            } else {
+                (cb / (1. - cs)).min(1.)
+            }
+        },
+        BlendingStyle::ColorBurn => {
+            if cb >= 1. {
+                1.
+            } else if cs == 0. {
+                0.
+            } else {
+                1. - ((1. - cb) / cs).min(1.)
+            }
+        },
+        BlendingStyle::HardLight => {
+            if cs <= 0.5 {
+                cb * 2. * cs
+            } else {
+                let cs = 2. * cs - 1.;
+                cb + cs - cb * cs
+            }
+        },
+        BlendingStyle::SoftLight => {
+            if cs <= 0.5 {
+                cb - (1. - 2. * cs) * cb * (1. - cb)
+            } else {
+                let d = if cb <= 0.25 {
+                    ((16. * cb - 12.) * cb + 4.) * cb
+                } else {
+                    cb.sqrt()
+                };
+                cb + (2. * cs - 1.) * (d - cb)
+            }
+        },
+        BlendingStyle::Difference => (cb - cs).abs(),
+        BlendingStyle::Exclusion => cb + cs - 2. * cb * cs,
+        BlendingStyle::Hue |
+        BlendingStyle::Saturation |
+        BlendingStyle::Color |
+        BlendingStyle::Luminosity => unreachable!("Not a separable blend mode."),
+    }
+}
+
+fn lum(c: [f32; 3]) -> f32 {
+    0.3 * c[0] + 0.59 * c[1] + 0.11 * c[2]
+}
+
+fn clip_color(c: [f32; 3]) -> [f32; 3] {
+    let l = lum(c);
+    let n = c[0].min(c[1]).min(c[2]);
+    let x = c[0].max(c[1]).max(c[2]);
+    let mut c = c;
+    for channel in c.iter_mut() {
+        if n < 0. {
+            *channel = l + (*channel - l) * l / (l - n);
+        }
+        if x > 1. {
+            *channel = l + (*channel - l) * (1. - l) / (x - l);
+        }
+    }
+    c
+}
+
+fn set_lum(c: [f32; 3], l: f32) -> [f32; 3] {
+    let d = l - lum(c);
+    clip_color([c[0] + d, c[1] + d, c[2] + d])
+}
+
+fn sat(c: [f32; 3]) -> f32 {
+    c[0].max(c[1]).max(c[2]) - c[0].min(c[1]).min(c[2])
+}
+
+fn set_sat(c: [f32; 3], s: f32) -> [f32; 3] {
+    let max = c[0].max(c[1]).max(c[2]);
+    let min = c[0].min(c[1]).min(c[2]);
+    if max <= min {
+        return [0.; 3];
+    }
+    let mut result = [0.; 3];
+    for i in 0..3 {
+        result[i] = if c[i] == max {
+            s
+        } else if c[i] == min {
+            0.
+        } else {
+            (c[i] - min) * s / (max - min)
+        };
+    }
+    result
+}
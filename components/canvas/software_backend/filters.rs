@@ -0,0 +1,151 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::canvas_data::ColorMatrix;
+use crate::software_backend::paint::{Color, Surface};
+use euclid::Size2D;
+
+/// An image made of premultiplied colors.
+pub struct Image {
+    pub size: Size2D<i32>,
+    pub pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn from_surface(surface: &Surface) -> Image {
+        let pixels = surface
+            .data()
+            .chunks(4)
+            .map(|pixel| {
+                [
+                    pixel[2] as f32 / 255.,
+                    pixel[1] as f32 / 255.,
+                    pixel[0] as f32 / 255.,
+                    pixel[3] as f32 / 255.,
+                ]
+            })
+            .collect();
+        Image {
+            size: surface.size(),
+            pixels,
+        }
+    }
+
+    pub fn to_surface(&self) -> Surface {
+        let mut data = Vec::with_capacity(self.pixels.len() * 4);
+        for color in &self.pixels {
+            data.extend_from_slice(&to_bgra(color));
+        }
+        Surface::new(self.size, data)
+    }
+}
+
+pub fn to_bgra(color: &Color) -> [u8; 4] {
+    let byte = |value: f32| (value.max(0.).min(1.) * 255.).round() as u8;
+    [
+        byte(color[2]),
+        byte(color[1]),
+        byte(color[0]),
+        byte(color[3]),
+    ]
+}
+
+/// Blurs an image with a gaussian of the given standard deviation. The result
+/// is larger than the image by the returned number of pixels on each side.
+pub fn gaussian_blur(image: &Image, std_deviation: f32) -> (Image, i32) {
+    if !(std_deviation > 0.) {
+        return (
+            Image {
+                size: image.size,
+                pixels: image.pixels.clone(),
+            },
+            0,
+        );
+    }
+    let radius = (std_deviation * 3.).ceil().min(1000.) as i32;
+    let weights: Vec<f32> = (-radius..radius + 1)
+        .map(|x| (-(x * x) as f32 / (2. * std_deviation * std_deviation)).exp())
+        .collect();
+    let total: f32 = weights.iter().sum();
+    let weights: Vec<f32> = weights.iter().map(|weight| weight / total).collect();
+
+    let size = Size2D::new(
+        image.size.width + 2 * radius,
+        image.size.height + 2 * radius,
+    );
+    let (width, height) = (size.width as usize, size.height as usize);
+    // Blurs the rows into a padded image, then its columns.
+    let mut horizontal = vec![[0.; 4]; width * height];
+    for y in 0..image.size.height as usize {
+        let source = &image.pixels[y * image.size.width as usize..];
+        let row = &mut horizontal[(y + radius as usize) * width..];
+        for x in 0..image.size.width as usize {
+            let color = source[x];
+            if color[3] == 0. && color[0] == 0. && color[1] == 0. && color[2] == 0. {
+                continue;
+            }
+            for (i, weight) in weights.iter().enumerate() {
+                let pixel = &mut row[x + i];
+                for c in 0..4 {
+                    pixel[c] += color[c] * weight;
+                }
+            }
+        }
+    }
+    let mut pixels = vec![[0.; 4]; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0.; 4];
+            for (i, weight) in weights.iter().enumerate() {
+                let source_y = y as i32 + i as i32 - radius;
+                if source_y < 0 || source_y >= height as i32 {
+                    continue;
+                }
+                let color = horizontal[source_y as usize * width + x];
+                for c in 0..4 {
+                    sum[c] += color[c] * weight;
+                }
+            }
+            pixels[y * width + x] = sum;
+        }
+    }
+    (Image { size, pixels }, radius)
+}
+
+/// Runs the unpremultiplied colors of an image through a color matrix.
+pub fn color_matrix(image: &Image, matrix: &ColorMatrix) -> Image {
+    let pixels = image
+        .pixels
+        .iter()
+        .map(|color| {
+            let alpha = color[3];
+            let input = if alpha == 0. {
+                [0., 0., 0., 0., 1.]
+            } else {
+                [
+                    color[0] / alpha,
+                    color[1] / alpha,
+                    color[2] / alpha,
+                    alpha,
+                    1.,
+                ]
+            };
+            let mut output = [0.; 4];
+            for (value, row) in output.iter_mut().zip(matrix.iter()) {
+                let sum: f32 = row.iter().zip(input.iter()).map(|(m, v)| m * v).sum();
+                *value = sum.max(0.).min(1.);
+            }
+            [
+                output[0] * output[3],
+                output[1] * output[3],
+                output[2] * output[3],
+                output[3],
+            ]
+        })
+        .collect();
+    Image {
+        size: image.size,
+        pixels,
+    }
+}
@@ -0,0 +1,487 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A backend rasterizing on the CPU without any native dependency, which
+//! keeps the pixels of its draw targets in memory.
+
+mod composite;
+mod filters;
+mod paint;
+mod path;
+mod rasterizer;
+mod stroke;
+
+pub use self::paint::{Pattern, Surface};
+pub use self::path::{Path, PathBuilder};
+
+use self::composite::{composite, is_bounded};
+use self::filters::{to_bgra, Image};
+use self::paint::{lerp, premultiplied, Color, Extend, Shader, TRANSPARENT};
+use self::rasterizer::{Mask, Polygon};
+use crate::canvas_data::{self, Backend, ColorMatrix, DrawOptions, Filter};
+use crate::canvas_data::{GenericDrawTarget, GenericPathBuilder, StrokeOptions};
+use canvas_traits::canvas::{CompositionOrBlending, CompositionStyle, FillOrStrokeStyle};
+use cssparser::RGBA;
+use euclid::{Point2D, Rect, Size2D, Transform2D, Vector2D};
+use std::cell::{Cell, RefCell};
+
+pub struct SoftwareBackend;
+
+impl Backend for SoftwareBackend {
+    fn create_drawtarget(&self, size: Size2D<u32>) -> Box<dyn GenericDrawTarget> {
+        Box::new(DrawTarget::new(size.to_i32()))
+    }
+}
+
+pub struct DrawTarget {
+    size: Size2D<i32>,
+    /// The premultiplied BGRA pixels of the draw target.
+    pixels: RefCell<Vec<u8>>,
+    transform: Cell<Transform2D<f32>>,
+    /// For each pushed clip, the coverage of its intersection with the
+    /// previous ones over the whole draw target.
+    clips: RefCell<Vec<Vec<f32>>>,
+}
+
+impl DrawTarget {
+    pub fn new(size: Size2D<i32>) -> DrawTarget {
+        let size = Size2D::new(size.width.max(0), size.height.max(0));
+        DrawTarget {
+            size,
+            pixels: RefCell::new(vec![0; size.width as usize * size.height as usize * 4]),
+            transform: Cell::new(Transform2D::identity()),
+            clips: RefCell::new(vec![]),
+        }
+    }
+
+    /// Composites a source over the pixels of the draw target. Bounded
+    /// operators only touch the pixels of `bounds` with a coverage, the other
+    /// ones apply to the whole clip area with a transparent source outside
+    /// of `bounds`.
+    fn composite<C, S>(
+        &self,
+        bounds: Rect<i32>,
+        operator: CompositionOrBlending,
+        alpha: f32,
+        coverage: C,
+        source: S,
+    ) where
+        C: Fn(i32, i32) -> f32,
+        S: Fn(i32, i32) -> Color,
+    {
+        let bounded = is_bounded(operator);
+        let target = Rect::from_size(self.size);
+        let area = if bounded {
+            match bounds.intersection(&target) {
+                Some(area) => area,
+                None => return,
+            }
+        } else {
+            target
+        };
+        let clips = self.clips.borrow();
+        let clip = clips.last();
+        let mut pixels = self.pixels.borrow_mut();
+        for y in area.min_y()..area.max_y() {
+            for x in area.min_x()..area.max_x() {
+                let index = (y * self.size.width + x) as usize;
+                let clip = clip.map_or(1., |clip| clip[index]);
+                if clip == 0. {
+                    continue;
+                }
+                let coverage = if bounds.contains(&Point2D::new(x, y)) {
+                    coverage(x, y)
+                } else {
+                    0.
+                };
+                if bounded && coverage == 0. {
+                    continue;
+                }
+                let source = if coverage == 0. {
+                    TRANSPARENT
+                } else {
+                    let factor = if bounded { alpha } else { alpha * coverage };
+                    let color = source(x, y);
+                    [
+                        color[0] * factor,
+                        color[1] * factor,
+                        color[2] * factor,
+                        color[3] * factor,
+                    ]
+                };
+                let pixel = &mut pixels[index * 4..index * 4 + 4];
+                let destination = [
+                    pixel[2] as f32 / 255.,
+                    pixel[1] as f32 / 255.,
+                    pixel[0] as f32 / 255.,
+                    pixel[3] as f32 / 255.,
+                ];
+                let result = composite(operator, source, destination);
+                let amount = if bounded { coverage * clip } else { clip };
+                pixel.copy_from_slice(&to_bgra(&lerp(destination, result, amount)));
+            }
+        }
+    }
+
+    fn fill_polygons(
+        &self,
+        polygons: &[Polygon],
+        shader: &Shader,
+        operator: CompositionOrBlending,
+        alpha: f32,
+        antialias: bool,
+    ) {
+        let mask = rasterizer::rasterize(polygons, self.size, antialias).unwrap_or(Mask {
+            rect: Rect::zero(),
+            coverage: vec![],
+        });
+        self.composite(
+            mask.rect,
+            operator,
+            alpha,
+            |x, y| mask.get(x, y),
+            |x, y| shader.shade(x, y),
+        );
+    }
+
+    fn fill_with_pattern(&self, polygons: &[Polygon], pattern: &Pattern, options: &DrawOptions) {
+        if let Some(shader) = pattern.shader(&self.transform.get()) {
+            self.fill_polygons(
+                polygons,
+                &shader,
+                options.composition,
+                options.alpha,
+                options.antialias,
+            );
+        }
+    }
+
+    /// Draws a surface with its top left corner at a point of device space.
+    fn blit(&self, surface: &Surface, origin: Point2D<f32>, operator: CompositionOrBlending) {
+        // Keeps the bounds below in range whatever the shadow offsets are.
+        let limit = (1 << 24) as f32;
+        let origin = Point2D::new(
+            origin.x.max(-limit).min(limit),
+            origin.y.max(-limit).min(limit),
+        );
+        let filter = if origin.x.fract() == 0. && origin.y.fract() == 0. {
+            Filter::Point
+        } else {
+            Filter::Linear
+        };
+        let shader = Shader::Image {
+            inverse: Transform2D::create_translation(-origin.x, -origin.y),
+            surface,
+            filter,
+            extend: [Extend::Decal, Extend::Decal],
+        };
+        let top_left = origin.floor();
+        let bottom_right = (origin + surface.size().to_f32().to_vector()).ceil();
+        let bounds = Rect::new(
+            Point2D::new(top_left.x as i32, top_left.y as i32),
+            Size2D::new(
+                (bottom_right.x - top_left.x) as i32,
+                (bottom_right.y - top_left.y) as i32,
+            ),
+        );
+        self.composite(bounds, operator, 1., |_, _| 1., |x, y| shader.shade(x, y));
+    }
+
+    fn rect_polygon(&self, rect: &Rect<f32>) -> Polygon {
+        let transform = self.transform.get();
+        vec![
+            rect.origin,
+            rect.top_right(),
+            rect.bottom_right(),
+            rect.bottom_left(),
+        ]
+        .iter()
+        .map(|point| transform.transform_point(point))
+        .collect()
+    }
+}
+
+impl GenericDrawTarget for DrawTarget {
+    fn clear_rect(&self, rect: &Rect<f32>) {
+        let polygons = [self.rect_polygon(rect)];
+        let operator = CompositionOrBlending::Composition(CompositionStyle::DestOut);
+        self.fill_polygons(
+            &polygons,
+            &Shader::Solid([0., 0., 0., 1.]),
+            operator,
+            1.,
+            true,
+        );
+    }
+
+    fn copy_surface(
+        &self,
+        surface: canvas_data::SourceSurface,
+        source: Rect<i32>,
+        destination: Point2D<i32>,
+    ) {
+        let surface = surface.into_software();
+        let source = match source.intersection(&Rect::from_size(surface.size())) {
+            Some(source) => source,
+            None => return,
+        };
+        let offset = destination - source.origin;
+        let destination = match source
+            .translate(&offset)
+            .intersection(&Rect::from_size(self.size))
+        {
+            Some(destination) => destination,
+            None => return,
+        };
+        let mut pixels = self.pixels.borrow_mut();
+        let row_length = destination.size.width as usize * 4;
+        for y in destination.min_y()..destination.max_y() {
+            let start = ((y * self.size.width + destination.origin.x) * 4) as usize;
+            let source_start = (((y - offset.y) * surface.size().width + destination.origin.x -
+                offset.x) *
+                4) as usize;
+            pixels[start..start + row_length]
+                .copy_from_slice(&surface.data()[source_start..source_start + row_length]);
+        }
+    }
+
+    fn create_path_builder(&self) -> Box<dyn GenericPathBuilder> {
+        Box::new(PathBuilder::new())
+    }
+
+    fn create_pattern(&self, style: &FillOrStrokeStyle) -> Option<canvas_data::Pattern> {
+        Pattern::new(style).map(canvas_data::Pattern::Software)
+    }
+
+    fn create_similar_draw_target(&self, size: &Size2D<i32>) -> Box<dyn GenericDrawTarget> {
+        Box::new(DrawTarget::new(*size))
+    }
+
+    fn create_source_surface_from_data(
+        &self,
+        data: &[u8],
+        size: Size2D<i32>,
+        stride: i32,
+    ) -> Option<canvas_data::SourceSurface> {
+        Surface::from_data(data, size, stride).map(canvas_data::SourceSurface::Software)
+    }
+
+    fn draw_surface(
+        &self,
+        surface: canvas_data::SourceSurface,
+        dest: Rect<f32>,
+        source: Rect<f32>,
+        filter: Filter,
+        draw_options: &DrawOptions,
+    ) {
+        if dest.size.width == 0. || dest.size.height == 0. {
+            return;
+        }
+        let inverse = match self.transform.get().inverse() {
+            Some(inverse) => inverse,
+            None => return,
+        };
+        let surface = surface.into_software();
+        let user_to_surface = Transform2D::create_translation(-dest.origin.x, -dest.origin.y)
+            .post_scale(
+                source.size.width / dest.size.width,
+                source.size.height / dest.size.height,
+            )
+            .post_translate(source.origin.to_vector());
+        let shader = Shader::Image {
+            inverse: inverse.post_mul(&user_to_surface),
+            surface: &surface,
+            filter,
+            extend: [Extend::Clamp, Extend::Clamp],
+        };
+        self.fill_polygons(
+            &[self.rect_polygon(&dest)],
+            &shader,
+            draw_options.composition,
+            draw_options.alpha,
+            draw_options.antialias,
+        );
+    }
+
+    fn draw_surface_with_shadow(
+        &self,
+        surface: canvas_data::SourceSurface,
+        dest: &Point2D<f32>,
+        color: &RGBA,
+        offset: &Vector2D<f32>,
+        sigma: f32,
+        operator: CompositionOrBlending,
+    ) {
+        let surface = surface.into_software();
+        let color = premultiplied(color);
+        let mut shadow = Image::from_surface(&surface);
+        for pixel in shadow.pixels.iter_mut() {
+            let alpha = pixel[3];
+            *pixel = [
+                color[0] * alpha,
+                color[1] * alpha,
+                color[2] * alpha,
+                color[3] * alpha,
+            ];
+        }
+        let (shadow, padding) = filters::gaussian_blur(&shadow, sigma);
+        let padding = Vector2D::new(padding as f32, padding as f32);
+        self.blit(&shadow.to_surface(), *dest + *offset - padding, operator);
+        self.blit(&surface, *dest, operator);
+    }
+
+    fn draw_gaussian_blur(&self, surface: canvas_data::SourceSurface, std_deviation: f32) {
+        let image = Image::from_surface(&surface.into_software());
+        let (blurred, padding) = filters::gaussian_blur(&image, std_deviation);
+        let origin = Point2D::new(-padding as f32, -padding as f32);
+        self.blit(
+            &blurred.to_surface(),
+            origin,
+            CompositionOrBlending::default(),
+        );
+    }
+
+    fn draw_color_matrix(&self, surface: canvas_data::SourceSurface, matrix: &ColorMatrix) {
+        let image = Image::from_surface(&surface.into_software());
+        let filtered = filters::color_matrix(&image, matrix);
+        self.blit(
+            &filtered.to_surface(),
+            Point2D::zero(),
+            CompositionOrBlending::default(),
+        );
+    }
+
+    fn fill(
+        &self,
+        path: &canvas_data::Path,
+        pattern: &canvas_data::Pattern,
+        draw_options: &DrawOptions,
+    ) {
+        let polygons = path.as_software().fill_polygons(&self.transform.get());
+        self.fill_with_pattern(&polygons, pattern.as_software(), draw_options);
+    }
+
+    fn fill_rect(
+        &self,
+        rect: &Rect<f32>,
+        pattern: &canvas_data::Pattern,
+        draw_options: &DrawOptions,
+    ) {
+        let polygons = [self.rect_polygon(rect)];
+        self.fill_with_pattern(&polygons, pattern.as_software(), draw_options);
+    }
+
+    fn get_size(&self) -> Size2D<i32> {
+        self.size
+    }
+
+    fn get_transform(&self) -> Transform2D<f32> {
+        self.transform.get()
+    }
+
+    fn pop_clip(&self) {
+        self.clips.borrow_mut().pop();
+    }
+
+    fn push_clip(&self, path: &canvas_data::Path) {
+        let polygons = path.as_software().fill_polygons(&self.transform.get());
+        let mask = rasterizer::rasterize(&polygons, self.size, true);
+        let mut clips = self.clips.borrow_mut();
+        let mut clip = vec![0.; (self.size.width * self.size.height) as usize];
+        if let Some(mask) = mask {
+            for y in mask.rect.min_y()..mask.rect.max_y() {
+                for x in mask.rect.min_x()..mask.rect.max_x() {
+                    let index = (y * self.size.width + x) as usize;
+                    let previous = clips.last().map_or(1., |clip| clip[index]);
+                    clip[index] = previous * mask.get(x, y);
+                }
+            }
+        }
+        clips.push(clip);
+    }
+
+    fn set_transform(&self, matrix: &Transform2D<f32>) {
+        self.transform.set(*matrix);
+    }
+
+    fn snapshot(&self) -> canvas_data::SourceSurface {
+        canvas_data::SourceSurface::Software(Surface::new(self.size, self.snapshot_data()))
+    }
+
+    fn snapshot_data(&self) -> Vec<u8> {
+        self.pixels.borrow().clone()
+    }
+
+    fn stroke(
+        &self,
+        path: &canvas_data::Path,
+        pattern: &canvas_data::Pattern,
+        stroke_options: &StrokeOptions,
+        draw_options: &DrawOptions,
+    ) {
+        let polygons =
+            stroke::stroke_polygons(path.as_software(), &self.transform.get(), stroke_options);
+        self.fill_with_pattern(&polygons, pattern.as_software(), draw_options);
+    }
+
+    fn stroke_line(
+        &self,
+        start: Point2D<f32>,
+        end: Point2D<f32>,
+        pattern: &canvas_data::Pattern,
+        stroke_options: &StrokeOptions,
+        draw_options: &DrawOptions,
+    ) {
+        let mut builder = PathBuilder::new();
+        builder.move_to(start);
+        builder.line_to(end);
+        self.stroke(&builder.finish(), pattern, stroke_options, draw_options);
+    }
+
+    fn stroke_rect(
+        &self,
+        rect: &Rect<f32>,
+        pattern: &canvas_data::Pattern,
+        stroke_options: &StrokeOptions,
+        draw_options: &DrawOptions,
+    ) {
+        let mut builder = PathBuilder::new();
+        builder.move_to(rect.origin);
+        builder.line_to(rect.top_right());
+        builder.line_to(rect.bottom_right());
+        builder.line_to(rect.bottom_left());
+        builder.close();
+        self.stroke(&builder.finish(), pattern, stroke_options, draw_options);
+    }
+}
+
+impl canvas_data::Path {
+    fn as_software(&self) -> &Path {
+        match *self {
+            canvas_data::Path::Software(ref path) => path,
+            #[cfg(feature = "canvas2d-azure")]
+            _ => unreachable!("Not a software path."),
+        }
+    }
+}
+
+impl canvas_data::Pattern {
+    fn as_software(&self) -> &Pattern {
+        match *self {
+            canvas_data::Pattern::Software(ref pattern) => pattern,
+            #[cfg(feature = "canvas2d-azure")]
+            _ => unreachable!("Not a software pattern."),
+        }
+    }
+}
+
+impl canvas_data::SourceSurface {
+    fn into_software(self) -> Surface {
+        match self {
+            canvas_data::SourceSurface::Software(surface) => surface,
+            #[cfg(feature = "canvas2d-azure")]
+            _ => unreachable!("Not a software surface."),
+        }
+    }
+}
@@ -0,0 +1,402 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::canvas_data::Filter;
+use canvas_traits::canvas::{CanvasGradientStop, FillOrStrokeStyle};
+use cssparser::RGBA;
+use euclid::{Point2D, Size2D, Transform2D, Vector2D};
+use std::sync::Arc;
+
+/// A color with premultiplied red, green, blue and alpha components from 0 to 1.
+pub type Color = [f32; 4];
+
+pub const TRANSPARENT: Color = [0.; 4];
+
+pub fn premultiplied(color: &RGBA) -> Color {
+    let alpha = color.alpha_f32();
+    [
+        color.red_f32() * alpha,
+        color.green_f32() * alpha,
+        color.blue_f32() * alpha,
+        alpha,
+    ]
+}
+
+/// An immutable image made of premultiplied BGRA pixels.
+#[derive(Clone)]
+pub struct Surface {
+    size: Size2D<i32>,
+    data: Arc<Vec<u8>>,
+}
+
+impl Surface {
+    pub fn new(size: Size2D<i32>, data: Vec<u8>) -> Surface {
+        debug_assert_eq!(data.len(), size.width as usize * size.height as usize * 4);
+        Surface {
+            size,
+            data: Arc::new(data),
+        }
+    }
+
+    /// Copies the pixels of an image whose rows are `stride` bytes apart.
+    pub fn from_data(data: &[u8], size: Size2D<i32>, stride: i32) -> Option<Surface> {
+        if size.width < 0 || size.height < 0 || stride < size.width * 4 {
+            return None;
+        }
+        let row_length = size.width as usize * 4;
+        let mut pixels = Vec::with_capacity(row_length * size.height as usize);
+        for y in 0..size.height as usize {
+            let start = y * stride as usize;
+            pixels.extend_from_slice(data.get(start..start + row_length)?);
+        }
+        Some(Surface::new(size, pixels))
+    }
+
+    pub fn size(&self) -> Size2D<i32> {
+        self.size
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn pixel(&self, x: i32, y: i32) -> Color {
+        let index = (y * self.size.width + x) as usize * 4;
+        let pixel = &self.data[index..index + 4];
+        [
+            pixel[2] as f32 / 255.,
+            pixel[1] as f32 / 255.,
+            pixel[0] as f32 / 255.,
+            pixel[3] as f32 / 255.,
+        ]
+    }
+
+    /// Returns the color of the surface at a point, with pixel centers at
+    /// half-integer coordinates.
+    pub fn sample(&self, point: Point2D<f32>, filter: Filter, extend: [Extend; 2]) -> Color {
+        if self.size.width == 0 || self.size.height == 0 {
+            return TRANSPARENT;
+        }
+        if !point.x.is_finite() || !point.y.is_finite() {
+            return TRANSPARENT;
+        }
+        // Keeps the coordinates representable once converted to integers.
+        let limit = (1 << 24) as f32;
+        let point = Point2D::new(
+            point.x.max(-limit).min(limit),
+            point.y.max(-limit).min(limit),
+        );
+        let fetch = |x: i32, y: i32| match (
+            extend[0].apply(x, self.size.width),
+            extend[1].apply(y, self.size.height),
+        ) {
+            (Some(x), Some(y)) => self.pixel(x, y),
+            _ => TRANSPARENT,
+        };
+        if filter == Filter::Point {
+            return fetch(point.x.floor() as i32, point.y.floor() as i32);
+        }
+
+        let x = point.x - 0.5;
+        let y = point.y - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        let top = lerp(fetch(x0, y0), fetch(x0 + 1, y0), fx);
+        let bottom = lerp(fetch(x0, y0 + 1), fetch(x0 + 1, y0 + 1), fx);
+        lerp(top, bottom, fy)
+    }
+}
+
+/// How a surface is sampled outside of its bounds along an axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Extend {
+    Clamp,
+    Repeat,
+    /// Transparent outside of the surface.
+    Decal,
+}
+
+impl Extend {
+    fn apply(self, coordinate: i32, length: i32) -> Option<i32> {
+        match self {
+            Extend::Clamp => Some(coordinate.max(0).min(length - 1)),
+            Extend::Repeat => Some(((coordinate % length) + length) % length),
+            Extend::Decal if coordinate < 0 || coordinate >= length => None,
+            Extend::Decal => Some(coordinate),
+        }
+    }
+}
+
+pub fn lerp(from: Color, to: Color, t: f32) -> Color {
+    [
+        from[0] + (to[0] - from[0]) * t,
+        from[1] + (to[1] - from[1]) * t,
+        from[2] + (to[2] - from[2]) * t,
+        from[3] + (to[3] - from[3]) * t,
+    ]
+}
+
+/// The number of precomputed colors of a gradient.
+const RAMP_SIZE: usize = 256;
+
+#[derive(Clone)]
+pub enum Pattern {
+    Color(Color),
+    LinearGradient {
+        start: Point2D<f32>,
+        end: Point2D<f32>,
+        ramp: Arc<Vec<Color>>,
+    },
+    RadialGradient {
+        center0: Point2D<f32>,
+        radius0: f32,
+        center1: Point2D<f32>,
+        radius1: f32,
+        ramp: Arc<Vec<Color>>,
+    },
+    Surface {
+        surface: Surface,
+        repeat_x: bool,
+        repeat_y: bool,
+    },
+}
+
+impl Pattern {
+    pub fn new(style: &FillOrStrokeStyle) -> Option<Pattern> {
+        Some(match *style {
+            FillOrStrokeStyle::Color(ref color) => Pattern::Color(premultiplied(color)),
+            FillOrStrokeStyle::LinearGradient(ref style) => Pattern::LinearGradient {
+                start: Point2D::new(style.x0 as f32, style.y0 as f32),
+                end: Point2D::new(style.x1 as f32, style.y1 as f32),
+                ramp: Arc::new(ramp(&style.stops)),
+            },
+            FillOrStrokeStyle::RadialGradient(ref style) => Pattern::RadialGradient {
+                center0: Point2D::new(style.x0 as f32, style.y0 as f32),
+                radius0: style.r0 as f32,
+                center1: Point2D::new(style.x1 as f32, style.y1 as f32),
+                radius1: style.r1 as f32,
+                ramp: Arc::new(ramp(&style.stops)),
+            },
+            FillOrStrokeStyle::Surface(ref style) => Pattern::Surface {
+                surface: Surface::from_data(
+                    &style.surface_data,
+                    style.surface_size.to_i32(),
+                    style.surface_size.width as i32 * 4,
+                )?,
+                repeat_x: style.repeat_x,
+                repeat_y: style.repeat_y,
+            },
+        })
+    }
+
+    pub fn is_zero_size_gradient(&self) -> bool {
+        match *self {
+            Pattern::LinearGradient { start, end, .. } => start == end,
+            _ => false,
+        }
+    }
+
+    pub fn surface_repeat(&self) -> Option<(Size2D<i32>, bool, bool)> {
+        match *self {
+            Pattern::Surface {
+                ref surface,
+                repeat_x,
+                repeat_y,
+            } => Some((surface.size(), repeat_x, repeat_y)),
+            _ => None,
+        }
+    }
+
+    /// Returns the shader painting this pattern under a transform, if the
+    /// transform is invertible.
+    pub fn shader(&self, transform: &Transform2D<f32>) -> Option<Shader> {
+        let inverse = transform.inverse()?;
+        Some(match *self {
+            Pattern::Color(color) => Shader::Solid(color),
+            Pattern::LinearGradient {
+                start,
+                end,
+                ref ramp,
+            } => Shader::Linear {
+                inverse,
+                start,
+                delta: end - start,
+                ramp,
+            },
+            Pattern::RadialGradient {
+                center0,
+                radius0,
+                center1,
+                radius1,
+                ref ramp,
+            } => Shader::Radial {
+                inverse,
+                center0,
+                radius0,
+                center_delta: center1 - center0,
+                radius_delta: radius1 - radius0,
+                ramp,
+            },
+            Pattern::Surface {
+                ref surface,
+                repeat_x,
+                repeat_y,
+            } => {
+                let extend = |repeat| {
+                    if repeat {
+                        Extend::Repeat
+                    } else {
+                        Extend::Clamp
+                    }
+                };
+                Shader::Image {
+                    inverse,
+                    surface,
+                    filter: Filter::Good,
+                    extend: [extend(repeat_x), extend(repeat_y)],
+                }
+            },
+        })
+    }
+}
+
+/// Precomputes the colors of a gradient, which are interpolated between
+/// unpremultiplied stops.
+fn ramp(stops: &[CanvasGradientStop]) -> Vec<Color> {
+    let mut stops: Vec<_> = stops.iter().collect();
+    stops.sort_by(|a, b| a.offset.partial_cmp(&b.offset).unwrap());
+    if stops.is_empty() {
+        return vec![TRANSPARENT; RAMP_SIZE];
+    }
+    let unpremultiplied = |color: &RGBA| {
+        [
+            color.red_f32(),
+            color.green_f32(),
+            color.blue_f32(),
+            color.alpha_f32(),
+        ]
+    };
+    (0..RAMP_SIZE)
+        .map(|i| {
+            let t = i as f64 / (RAMP_SIZE - 1) as f64;
+            let next = stops.iter().position(|stop| stop.offset > t);
+            let color = match next {
+                Some(0) => unpremultiplied(&stops[0].color),
+                None => unpremultiplied(&stops[stops.len() - 1].color),
+                Some(next) => {
+                    let (from, to) = (stops[next - 1], stops[next]);
+                    let t = (t - from.offset) / (to.offset - from.offset);
+                    lerp(
+                        unpremultiplied(&from.color),
+                        unpremultiplied(&to.color),
+                        t as f32,
+                    )
+                },
+            };
+            [
+                color[0] * color[3],
+                color[1] * color[3],
+                color[2] * color[3],
+                color[3],
+            ]
+        })
+        .collect()
+}
+
+fn ramp_color(ramp: &[Color], t: f32) -> Color {
+    let position = t.max(0.).min(1.) * (ramp.len() - 1) as f32;
+    let index = position.floor() as usize;
+    if index + 1 >= ramp.len() {
+        return ramp[ramp.len() - 1];
+    }
+    lerp(ramp[index], ramp[index + 1], position - index as f32)
+}
+
+/// Computes the colors of a pattern in device space.
+pub enum Shader<'a> {
+    Solid(Color),
+    Linear {
+        inverse: Transform2D<f32>,
+        start: Point2D<f32>,
+        delta: Vector2D<f32>,
+        ramp: &'a [Color],
+    },
+    Radial {
+        inverse: Transform2D<f32>,
+        center0: Point2D<f32>,
+        radius0: f32,
+        center_delta: Vector2D<f32>,
+        radius_delta: f32,
+        ramp: &'a [Color],
+    },
+    Image {
+        /// Maps device space to the space of the surface.
+        inverse: Transform2D<f32>,
+        surface: &'a Surface,
+        filter: Filter,
+        extend: [Extend; 2],
+    },
+}
+
+impl<'a> Shader<'a> {
+    /// Returns the color of the pixel at the given device coordinates.
+    pub fn shade(&self, x: i32, y: i32) -> Color {
+        let device_point = Point2D::new(x as f32 + 0.5, y as f32 + 0.5);
+        match *self {
+            Shader::Solid(color) => color,
+            Shader::Linear {
+                ref inverse,
+                start,
+                delta,
+                ramp,
+            } => {
+                let point = inverse.transform_point(&device_point);
+                let t = (point - start).dot(delta) / delta.square_length();
+                ramp_color(ramp, t)
+            },
+            // https://html.spec.whatwg.org/multipage/#dom-context-2d-createradialgradient
+            Shader::Radial {
+                ref inverse,
+                center0,
+                radius0,
+                center_delta,
+                radius_delta,
+                ramp,
+            } => {
+                // Finds the largest ω for which the point lies on the circle of
+                // center center0 + ω·center_delta and radius radius0 + ω·radius_delta
+                // that is not negative, by solving a·ω² - 2b·ω + c = 0.
+                let point = inverse.transform_point(&device_point) - center0;
+                let a = center_delta.square_length() - radius_delta * radius_delta;
+                let b = point.dot(center_delta) + radius0 * radius_delta;
+                let c = point.square_length() - radius0 * radius0;
+                let candidates = if a.abs() < 1e-6 {
+                    if b == 0. {
+                        return TRANSPARENT;
+                    }
+                    [c / (2. * b), ::std::f32::NAN]
+                } else {
+                    let discriminant = b * b - a * c;
+                    if discriminant < 0. {
+                        return TRANSPARENT;
+                    }
+                    let root = discriminant.sqrt();
+                    let (w1, w2) = ((b + root) / a, (b - root) / a);
+                    [w1.max(w2), w1.min(w2)]
+                };
+                candidates
+                    .iter()
+                    .find(|w| w.is_finite() && radius0 + **w * radius_delta >= 0.)
+                    .map_or(TRANSPARENT, |w| ramp_color(ramp, *w))
+            },
+            Shader::Image {
+                ref inverse,
+                surface,
+                filter,
+                extend,
+            } => surface.sample(inverse.transform_point(&device_point), filter, extend),
+        }
+    }
+}
@@ -0,0 +1,321 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::canvas_data::{self, GenericPathBuilder};
+use crate::software_backend::rasterizer::{self, Polygon};
+use euclid::{Point2D, Transform2D, Vector2D};
+use std::f32::consts::{FRAC_PI_2, PI};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PathOp {
+    MoveTo(Point2D<f32>),
+    LineTo(Point2D<f32>),
+    QuadTo(Point2D<f32>, Point2D<f32>),
+    CubicTo(Point2D<f32>, Point2D<f32>, Point2D<f32>),
+    Close,
+}
+
+impl PathOp {
+    fn transformed(&self, transform: &Transform2D<f32>) -> PathOp {
+        let t = |point: &Point2D<f32>| transform.transform_point(point);
+        match *self {
+            PathOp::MoveTo(ref p) => PathOp::MoveTo(t(p)),
+            PathOp::LineTo(ref p) => PathOp::LineTo(t(p)),
+            PathOp::QuadTo(ref c, ref p) => PathOp::QuadTo(t(c), t(p)),
+            PathOp::CubicTo(ref c1, ref c2, ref p) => PathOp::CubicTo(t(c1), t(c2), t(p)),
+            PathOp::Close => PathOp::Close,
+        }
+    }
+}
+
+/// A subpath flattened into line segments.
+pub struct Contour {
+    pub points: Vec<Point2D<f32>>,
+    pub closed: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Path {
+    ops: Vec<PathOp>,
+}
+
+impl Path {
+    /// Flattens the path into polylines after applying `transform` to it, such
+    /// that no point of a curve is further than `tolerance` from its polyline.
+    pub fn flatten(&self, transform: &Transform2D<f32>, tolerance: f32) -> Vec<Contour> {
+        let mut contours = vec![];
+        let mut current: Option<Contour> = None;
+        let mut last = Point2D::zero();
+        for op in &self.ops {
+            match op.transformed(transform) {
+                PathOp::MoveTo(p) => {
+                    contours.extend(current.take());
+                    current = Some(Contour {
+                        points: vec![p],
+                        closed: false,
+                    });
+                    last = p;
+                },
+                PathOp::LineTo(p) => {
+                    push_point(&mut current, p);
+                    last = p;
+                },
+                PathOp::QuadTo(c, p) => {
+                    let dd = (last - c * 2. + p.to_vector()).length();
+                    let count = segment_count(0.25 * dd, tolerance);
+                    for i in 1..count + 1 {
+                        let t = i as f32 / count as f32;
+                        let mt = 1. - t;
+                        let point = last * (mt * mt) +
+                            c.to_vector() * (2. * mt * t) +
+                            p.to_vector() * (t * t);
+                        push_point(&mut current, point);
+                    }
+                    last = p;
+                },
+                PathOp::CubicTo(c1, c2, p) => {
+                    let dd1 = (last - c1 * 2. + c2.to_vector()).length();
+                    let dd2 = (c1 - c2 * 2. + p.to_vector()).length();
+                    let count = segment_count(0.75 * dd1.max(dd2), tolerance);
+                    for i in 1..count + 1 {
+                        let t = i as f32 / count as f32;
+                        let mt = 1. - t;
+                        let point = last * (mt * mt * mt) +
+                            c1.to_vector() * (3. * mt * mt * t) +
+                            c2.to_vector() * (3. * mt * t * t) +
+                            p.to_vector() * (t * t * t);
+                        push_point(&mut current, point);
+                    }
+                    last = p;
+                },
+                PathOp::Close => {
+                    if let Some(mut contour) = current.take() {
+                        contour.closed = true;
+                        last = contour.points[0];
+                        contours.push(contour);
+                    }
+                },
+            }
+        }
+        contours.extend(current.take());
+        contours
+    }
+
+    /// Returns the polygons to fill for this path in device space.
+    pub fn fill_polygons(&self, transform: &Transform2D<f32>) -> Vec<Polygon> {
+        self.flatten(transform, rasterizer::TOLERANCE)
+            .into_iter()
+            .map(|contour| contour.points)
+            .collect()
+    }
+
+    pub fn contains_point(&self, x: f64, y: f64, path_transform: &Transform2D<f32>) -> bool {
+        let point = Point2D::new(x as f32, y as f32);
+        let polygons = self.fill_polygons(path_transform);
+        rasterizer::winding_number(&polygons, point) != 0
+    }
+
+    pub fn copy_to_builder(&self) -> PathBuilder {
+        PathBuilder::from_ops(self.ops.clone())
+    }
+
+    pub fn transformed_copy_to_builder(&self, transform: &Transform2D<f32>) -> PathBuilder {
+        PathBuilder::from_ops(
+            self.ops
+                .iter()
+                .map(|op| op.transformed(transform))
+                .collect(),
+        )
+    }
+}
+
+fn push_point(contour: &mut Option<Contour>, point: Point2D<f32>) {
+    match *contour {
+        Some(ref mut contour) => contour.points.push(point),
+        None => unreachable!("Path operations always start with a move."),
+    }
+}
+
+fn segment_count(deviation: f32, tolerance: f32) -> usize {
+    let count = (deviation / tolerance).sqrt().ceil();
+    if count.is_finite() {
+        count.max(1.).min(1000.) as usize
+    } else {
+        1
+    }
+}
+
+pub struct PathBuilder {
+    ops: Vec<PathOp>,
+    /// The start of the current subpath.
+    start: Option<Point2D<f32>>,
+    /// The last point of the current subpath.
+    current: Option<Point2D<f32>>,
+    /// Whether the current subpath was closed, in which case further drawing
+    /// starts a new one at the same point.
+    closed: bool,
+}
+
+impl PathBuilder {
+    pub fn new() -> PathBuilder {
+        PathBuilder {
+            ops: vec![],
+            start: None,
+            current: None,
+            closed: false,
+        }
+    }
+
+    fn from_ops(ops: Vec<PathOp>) -> PathBuilder {
+        let mut builder = PathBuilder::new();
+        for op in ops {
+            match op {
+                PathOp::MoveTo(p) => builder.move_to(p),
+                PathOp::LineTo(p) => builder.line_to(p),
+                PathOp::QuadTo(ref c, ref p) => builder.quadratic_curve_to(c, p),
+                PathOp::CubicTo(ref c1, ref c2, ref p) => builder.bezier_curve_to(c1, c2, p),
+                PathOp::Close => builder.close(),
+            }
+        }
+        builder
+    }
+
+    /// Makes sure there is an open subpath to draw from, starting a new one
+    /// at `point` if there is none.
+    fn ensure_subpath(&mut self, point: Point2D<f32>) {
+        match self.current {
+            None => self.move_to(point),
+            Some(current) if self.closed => self.move_to(current),
+            Some(_) => {},
+        }
+    }
+}
+
+impl GenericPathBuilder for PathBuilder {
+    fn arc(
+        &mut self,
+        origin: Point2D<f32>,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        anticlockwise: bool,
+    ) {
+        self.ellipse(
+            origin,
+            radius,
+            radius,
+            0.,
+            start_angle,
+            end_angle,
+            anticlockwise,
+        );
+    }
+
+    fn bezier_curve_to(
+        &mut self,
+        control_point1: &Point2D<f32>,
+        control_point2: &Point2D<f32>,
+        control_point3: &Point2D<f32>,
+    ) {
+        self.ensure_subpath(*control_point1);
+        self.ops.push(PathOp::CubicTo(
+            *control_point1,
+            *control_point2,
+            *control_point3,
+        ));
+        self.current = Some(*control_point3);
+    }
+
+    fn close(&mut self) {
+        if self.current.is_some() && !self.closed {
+            self.ops.push(PathOp::Close);
+            self.current = self.start;
+            self.closed = true;
+        }
+    }
+
+    // Approximates the arc with cubic béziers spanning at most a quarter
+    // turn each, sweeping the same way as Azure does.
+    fn ellipse(
+        &mut self,
+        origin: Point2D<f32>,
+        radius_x: f32,
+        radius_y: f32,
+        rotation_angle: f32,
+        start_angle: f32,
+        end_angle: f32,
+        anticlockwise: bool,
+    ) {
+        let direction = if anticlockwise { -1. } else { 1. };
+        let mut sweep_left = (end_angle - start_angle) * direction;
+        let mut start_angle = start_angle;
+        if sweep_left < 0. {
+            // A reverse sweep is wrapped into range rather than clamped.
+            sweep_left = 2. * PI + sweep_left % (2. * PI);
+            start_angle = end_angle - sweep_left * direction;
+        } else if sweep_left > 2. * PI {
+            sweep_left = 2. * PI;
+        }
+
+        let (sin, cos) = rotation_angle.sin_cos();
+        let to_point = |offset: Vector2D<f32>| {
+            let x = offset.x * radius_x;
+            let y = offset.y * radius_y;
+            Point2D::new(origin.x + x * cos - y * sin, origin.y + x * sin + y * cos)
+        };
+
+        let mut current_angle = start_angle;
+        let mut current_offset = Vector2D::new(start_angle.cos(), start_angle.sin());
+        let start_point = to_point(current_offset);
+        if self.current.is_some() {
+            self.line_to(start_point);
+        } else {
+            self.move_to(start_point);
+        }
+
+        while sweep_left > 0. {
+            let end_angle = current_angle + sweep_left.min(FRAC_PI_2) * direction;
+            let end_offset = Vector2D::new(end_angle.cos(), end_angle.sin());
+            let kappa = (4. / 3.) * ((end_angle - current_angle) / 4.).tan();
+            let control_point1 = to_point(
+                current_offset + Vector2D::new(-current_offset.y, current_offset.x) * kappa,
+            );
+            let control_point2 =
+                to_point(end_offset + Vector2D::new(end_offset.y, -end_offset.x) * kappa);
+            self.bezier_curve_to(&control_point1, &control_point2, &to_point(end_offset));
+            sweep_left -= FRAC_PI_2;
+            current_angle = end_angle;
+            current_offset = end_offset;
+        }
+    }
+
+    fn get_current_point(&self) -> Point2D<f32> {
+        self.current.unwrap_or(Point2D::zero())
+    }
+
+    fn line_to(&mut self, point: Point2D<f32>) {
+        self.ensure_subpath(point);
+        self.ops.push(PathOp::LineTo(point));
+        self.current = Some(point);
+    }
+
+    fn move_to(&mut self, point: Point2D<f32>) {
+        self.ops.push(PathOp::MoveTo(point));
+        self.start = Some(point);
+        self.current = Some(point);
+        self.closed = false;
+    }
+
+    fn quadratic_curve_to(&mut self, control_point: &Point2D<f32>, end_point: &Point2D<f32>) {
+        self.ensure_subpath(*control_point);
+        self.ops.push(PathOp::QuadTo(*control_point, *end_point));
+        self.current = Some(*end_point);
+    }
+
+    fn finish(&mut self) -> canvas_data::Path {
+        canvas_data::Path::Software(Path {
+            ops: self.ops.clone(),
+        })
+    }
+}
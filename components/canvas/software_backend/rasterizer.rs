@@ -0,0 +1,208 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Scan conversion of polygons into coverage masks, accumulating the signed
+//! area covered by each edge in every pixel, in the manner of font-rs.
+
+use euclid::{Point2D, Rect, Size2D};
+
+/// The maximum distance, in device pixels, between a curve and the polyline
+/// approximating it.
+pub const TOLERANCE: f32 = 0.1;
+
+/// A closed polygon in device space.
+pub type Polygon = Vec<Point2D<f32>>;
+
+/// The coverage of a shape over the pixels of a rectangle of a draw target,
+/// from 0 to 1.
+pub struct Mask {
+    pub rect: Rect<i32>,
+    pub coverage: Vec<f32>,
+}
+
+impl Mask {
+    /// Returns the coverage at the given pixel, which is 0 outside of the mask.
+    pub fn get(&self, x: i32, y: i32) -> f32 {
+        if !self.rect.contains(&Point2D::new(x, y)) {
+            return 0.;
+        }
+        let index = (y - self.rect.origin.y) * self.rect.size.width + x - self.rect.origin.x;
+        self.coverage[index as usize]
+    }
+}
+
+/// Rasterizes polygons with the nonzero fill rule over a draw target of the
+/// given size. Returns `None` if no pixel of the target can be covered.
+pub fn rasterize(polygons: &[Polygon], size: Size2D<i32>, antialias: bool) -> Option<Mask> {
+    let rect = bounds(polygons, size)?;
+    let width = rect.size.width as usize;
+    let height = rect.size.height as usize;
+    // Edges are clamped horizontally to the mask, which can make them write up
+    // to two cells after its last column.
+    let stride = width + 2;
+    let mut accumulator = Accumulator {
+        cells: vec![0.; stride * height],
+        stride,
+        height,
+    };
+    let origin = rect.origin.to_f32().to_vector();
+    let right = width as f32;
+    for polygon in polygons {
+        let edges = polygon
+            .iter()
+            .zip(polygon.iter().skip(1).chain(polygon.first()));
+        for (from, to) in edges {
+            accumulator.add_edge(*from - origin, *to - origin, right);
+        }
+    }
+
+    let mut coverage = Vec::with_capacity(width * height);
+    for row in accumulator.cells.chunks(stride) {
+        let mut area = 0.;
+        for cell in &row[..width] {
+            area += *cell;
+            let value = area.abs().min(1.);
+            coverage.push(if antialias {
+                value
+            } else if value >= 0.5 {
+                1.
+            } else {
+                0.
+            });
+        }
+    }
+    Some(Mask { rect, coverage })
+}
+
+/// Returns the pixel bounds of the polygons clamped to the draw target.
+fn bounds(polygons: &[Polygon], size: Size2D<i32>) -> Option<Rect<i32>> {
+    let mut min = Point2D::new(size.width as f32, size.height as f32);
+    let mut max = Point2D::new(0f32, 0f32);
+    for point in polygons.iter().flat_map(|polygon| polygon.iter()) {
+        if !point.x.is_finite() || !point.y.is_finite() {
+            return None;
+        }
+        min = min.min(*point);
+        max = max.max(*point);
+    }
+    let min = min.max(Point2D::zero()).floor();
+    let max = max
+        .min(Point2D::new(size.width as f32, size.height as f32))
+        .ceil();
+    if max.x <= min.x || max.y <= min.y {
+        return None;
+    }
+    Some(Rect::new(
+        Point2D::new(min.x as i32, min.y as i32),
+        Size2D::new((max.x - min.x) as i32, (max.y - min.y) as i32),
+    ))
+}
+
+struct Accumulator {
+    cells: Vec<f32>,
+    stride: usize,
+    height: usize,
+}
+
+impl Accumulator {
+    /// Adds an edge, splitting it where it crosses the left and right sides
+    /// of the mask so that the parts outside can be clamped to them.
+    fn add_edge(&mut self, from: Point2D<f32>, to: Point2D<f32>, right: f32) {
+        let mut splits = [0f32, 1., 1., 1.];
+        let mut count = 1;
+        for &x in &[0., right] {
+            if (from.x - x) * (to.x - x) < 0. {
+                splits[count] = (x - from.x) / (to.x - from.x);
+                count += 1;
+            }
+        }
+        let splits = &mut splits[..count + 1];
+        splits.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let point_at = |t: f32| from + (to - from) * t;
+        for pair in splits.windows(2) {
+            let (p0, p1) = (point_at(pair[0]), point_at(pair[1]));
+            let clamp = |p: Point2D<f32>| Point2D::new(p.x.max(0.).min(right), p.y);
+            self.add_line(clamp(p0), clamp(p1));
+        }
+    }
+
+    fn add_line(&mut self, p0: Point2D<f32>, p1: Point2D<f32>) {
+        if (p0.y - p1.y).abs() <= ::std::f32::EPSILON {
+            return;
+        }
+        let (direction, p0, p1) = if p0.y < p1.y {
+            (1., p0, p1)
+        } else {
+            (-1., p1, p0)
+        };
+        let height = self.height as f32;
+        let width = (self.stride - 2) as f32;
+        if p1.y <= 0. || p0.y >= height {
+            return;
+        }
+        let dxdy = (p1.x - p0.x) / (p1.y - p0.y);
+        let mut x = p0.x;
+        if p0.y < 0. {
+            x = (x - p0.y * dxdy).max(0.).min(width);
+        }
+        let first_row = p0.y.max(0.) as usize;
+        let last_row = p1.y.min(height).ceil() as usize;
+        for y in first_row..last_row {
+            let row = &mut self.cells[y * self.stride..(y + 1) * self.stride];
+            let dy = ((y + 1) as f32).min(p1.y) - (y as f32).max(p0.y);
+            // Rounding errors must not take the edge out of the mask.
+            let x_next = (x + dxdy * dy).max(0.).min(width);
+            let d = dy * direction;
+            let (x0, x1) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let x0_floor = x0.floor();
+            let x0i = x0_floor as usize;
+            let x1_ceil = x1.ceil();
+            let x1i = x1_ceil as usize;
+            if x1i <= x0i + 1 {
+                let xmf = 0.5 * (x + x_next) - x0_floor;
+                row[x0i] += d - d * xmf;
+                row[x0i + 1] += d * xmf;
+            } else {
+                let s = (x1 - x0).recip();
+                let x0f = x0 - x0_floor;
+                let a0 = 0.5 * s * (1. - x0f) * (1. - x0f);
+                let x1f = x1 - x1_ceil + 1.;
+                let am = 0.5 * s * x1f * x1f;
+                row[x0i] += d * a0;
+                if x1i == x0i + 2 {
+                    row[x0i + 1] += d * (1. - a0 - am);
+                } else {
+                    let a1 = s * (1.5 - x0f);
+                    row[x0i + 1] += d * (a1 - a0);
+                    for cell in &mut row[x0i + 2..x1i - 1] {
+                        *cell += d * s;
+                    }
+                    let a2 = a1 + (x1i - x0i - 3) as f32 * s;
+                    row[x1i - 1] += d * (1. - a2 - am);
+                }
+                row[x1i] += d * am;
+            }
+            x = x_next;
+        }
+    }
+}
+
+/// Returns the winding number of the polygons around a point.
+pub fn winding_number(polygons: &[Polygon], point: Point2D<f32>) -> i32 {
+    let mut winding = 0;
+    for polygon in polygons {
+        let edges = polygon
+            .iter()
+            .zip(polygon.iter().skip(1).chain(polygon.first()));
+        for (from, to) in edges {
+            let side = (to.x - from.x) * (point.y - from.y) - (point.x - from.x) * (to.y - from.y);
+            if from.y <= point.y && to.y > point.y && side > 0. {
+                winding += 1;
+            } else if from.y > point.y && to.y <= point.y && side < 0. {
+                winding -= 1;
+            }
+        }
+    }
+    winding
+}
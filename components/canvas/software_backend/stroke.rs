@@ -0,0 +1,200 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Stroking, which turns a path into polygons that are filled with the
+//! nonzero rule. Each segment, join and cap gets its own polygon, all of them
+//! with the same orientation so that their union is what gets painted.
+
+use crate::canvas_data::StrokeOptions;
+use crate::software_backend::path::Path;
+use crate::software_backend::rasterizer::{Polygon, TOLERANCE};
+use canvas_traits::canvas::{LineCapStyle, LineJoinStyle};
+use euclid::{Point2D, Transform2D, Vector2D};
+use std::f32::consts::PI;
+
+/// Returns the polygons covered by the stroke of a path in device space. The
+/// path is stroked in user space, where lines have the same width all along.
+pub fn stroke_polygons(
+    path: &Path,
+    transform: &Transform2D<f32>,
+    options: &StrokeOptions,
+) -> Vec<Polygon> {
+    let half_width = options.line_width / 2.;
+    if !(half_width > 0.) {
+        return vec![];
+    }
+    // The largest factor by which the transform scales user space lengths.
+    let scale = (transform.m11.hypot(transform.m12))
+        .max(transform.m21.hypot(transform.m22))
+        .max(::std::f32::EPSILON);
+    let mut stroker = Stroker {
+        polygons: vec![],
+        half_width,
+        options,
+        // Round joins and caps are approximated so that they deviate from the
+        // circle by less than the tolerance in device space.
+        circle_segments: {
+            let radius = half_width * scale;
+            let step = 2. * (1. - TOLERANCE / radius).max(-1.).acos();
+            if step > 0. {
+                (2. * PI / step).ceil().max(8.).min(256.) as usize
+            } else {
+                8
+            }
+        },
+    };
+    for contour in path.flatten(&Transform2D::identity(), TOLERANCE / scale) {
+        let mut points = contour.points;
+        // A subpath made of a single move is not painted, unlike a subpath
+        // whose segments all have a zero length.
+        let has_segments = points.len() > 1 || contour.closed;
+        points.dedup_by(|a, b| (*a - *b).square_length() < 1e-12);
+        if contour.closed && points.len() > 1 && points[0] == points[points.len() - 1] {
+            points.pop();
+        }
+        if has_segments {
+            stroker.add_contour(&points, contour.closed);
+        }
+    }
+
+    stroker
+        .polygons
+        .into_iter()
+        .map(|polygon| {
+            polygon
+                .iter()
+                .map(|point| transform.transform_point(point))
+                .collect()
+        })
+        .collect()
+}
+
+struct Stroker<'a> {
+    polygons: Vec<Polygon>,
+    half_width: f32,
+    options: &'a StrokeOptions,
+    circle_segments: usize,
+}
+
+impl<'a> Stroker<'a> {
+    fn add_contour(&mut self, points: &[Point2D<f32>], closed: bool) {
+        if points.len() == 1 {
+            return self.add_zero_length_cap(points[0]);
+        }
+
+        let count = points.len();
+        let segment_count = if closed { count } else { count - 1 };
+        for i in 0..segment_count {
+            self.add_segment(points[i], points[(i + 1) % count]);
+        }
+        let joins = if closed { 0..count } else { 1..count - 1 };
+        for i in joins {
+            let previous = points[(i + count - 1) % count];
+            let next = points[(i + 1) % count];
+            self.add_join(previous, points[i], next);
+        }
+        if !closed {
+            self.add_cap(points[1], points[0]);
+            self.add_cap(points[count - 2], points[count - 1]);
+        }
+    }
+
+    fn add_polygon(&mut self, mut polygon: Polygon) {
+        let area: f32 = polygon
+            .iter()
+            .zip(polygon.iter().skip(1).chain(polygon.first()))
+            .map(|(a, b)| a.x * b.y - b.x * a.y)
+            .sum();
+        if area < 0. {
+            polygon.reverse();
+        }
+        self.polygons.push(polygon);
+    }
+
+    fn normal(&self, from: Point2D<f32>, to: Point2D<f32>) -> Vector2D<f32> {
+        let direction = (to - from).normalize();
+        Vector2D::new(-direction.y, direction.x) * self.half_width
+    }
+
+    fn add_segment(&mut self, from: Point2D<f32>, to: Point2D<f32>) {
+        let normal = self.normal(from, to);
+        self.add_polygon(vec![from + normal, to + normal, to - normal, from - normal]);
+    }
+
+    fn add_join(&mut self, previous: Point2D<f32>, point: Point2D<f32>, next: Point2D<f32>) {
+        let d0 = (point - previous).normalize();
+        let d1 = (next - point).normalize();
+        let cross = d0.cross(d1);
+        let dot = d0.dot(d1);
+        if cross.abs() < 1e-6 && dot > 0. {
+            return;
+        }
+        if self.options.line_join == LineJoinStyle::Round {
+            return self.add_circle(point);
+        }
+
+        // Only the outer side of the turn needs filling.
+        let side = if cross > 0. { -1. } else { 1. };
+        let outer0 = self.normal(previous, point) * side;
+        let outer1 = self.normal(point, next) * side;
+        // https://html.spec.whatwg.org/multipage/#trace-a-path
+        let miter_ratio = ((1. + dot) / 2.).sqrt().recip();
+        if self.options.line_join == LineJoinStyle::Miter && miter_ratio <= self.options.miter_limit
+        {
+            let miter = (outer0 + outer1).normalize() * (self.half_width * miter_ratio);
+            self.add_polygon(vec![point, point + outer0, point + miter, point + outer1]);
+        } else {
+            self.add_polygon(vec![point, point + outer0, point + outer1]);
+        }
+    }
+
+    /// Adds the cap at the `end` of a segment coming from `from`.
+    fn add_cap(&mut self, from: Point2D<f32>, end: Point2D<f32>) {
+        match self.options.line_cap {
+            LineCapStyle::Butt => {},
+            LineCapStyle::Round => self.add_circle(end),
+            LineCapStyle::Square => {
+                let normal = self.normal(from, end);
+                let extension = Vector2D::new(normal.y, -normal.x);
+                self.add_polygon(vec![
+                    end + normal,
+                    end + normal + extension,
+                    end - normal + extension,
+                    end - normal,
+                ]);
+            },
+        }
+    }
+
+    /// Adds the caps of a subpath whose points are all the same.
+    fn add_zero_length_cap(&mut self, point: Point2D<f32>) {
+        match self.options.line_cap {
+            LineCapStyle::Butt => {},
+            LineCapStyle::Round => self.add_circle(point),
+            LineCapStyle::Square => {
+                let h = self.half_width;
+                self.add_polygon(vec![
+                    Point2D::new(point.x - h, point.y - h),
+                    Point2D::new(point.x + h, point.y - h),
+                    Point2D::new(point.x + h, point.y + h),
+                    Point2D::new(point.x - h, point.y + h),
+                ]);
+            },
+        }
+    }
+
+    fn add_circle(&mut self, center: Point2D<f32>) {
+        let step = 2. * PI / self.circle_segments as f32;
+        let circle = (0..self.circle_segments)
+            .map(|i| {
+                let (sin, cos) = (i as f32 * step).sin_cos();
+                Point2D::new(
+                    center.x + cos * self.half_width,
+                    center.y + sin * self.half_width,
+                )
+            })
+            .collect();
+        self.add_polygon(circle);
+    }
+}
@@ -9,9 +9,12 @@ use euclid::Size2D;
 use fnv::FnvHashMap;
 use gleam::gl;
 use half::f16;
+use ipc_channel::ipc::IpcBytesSender;
 use offscreen_gl_context::{DrawBuffer, GLContext, NativeGLContextMethods};
 use pixels::{self, PixelFormat};
 use std::borrow::Cow;
+use std::mem;
+use std::slice;
 use std::thread;
 
 /// WebGL Threading API entry point that lives in the constellation.
@@ -30,6 +33,9 @@ pub struct GLState {
     stencil_clear_value: i32,
     depth_write_mask: bool,
     depth_clear_value: f64,
+    /// Native sync objects are pointers, script only ever sees their ids.
+    syncs: FnvHashMap<WebGLSyncId, gl::GLsync>,
+    next_sync_id: u32,
 }
 
 impl Default for GLState {
@@ -41,6 +47,8 @@ impl Default for GLState {
             stencil_clear_value: 0,
             depth_write_mask: true,
             depth_clear_value: 1.,
+            syncs: Default::default(),
+            next_sync_id: 1,
         }
     }
 }
@@ -120,7 +128,7 @@ impl<VR: WebVRRenderHandler + 'static> WebGLThread<VR> {
             WebGLMsg::CreateContext(version, size, attributes, result_sender) => {
                 let result = self.create_webgl_context(version, size, attributes);
                 result_sender
-                    .send(result.map(|(id, limits, share_mode)| {
+                    .send(result.map(|(id, mut limits, share_mode)| {
                         let data = Self::make_current_if_needed(
                             id,
                             &self.contexts,
@@ -128,6 +136,10 @@ impl<VR: WebVRRenderHandler + 'static> WebGLThread<VR> {
                         )
                         .expect("WebGLContext not found");
                         let glsl_version = Self::get_glsl_version(&data.ctx);
+                        if version == WebGLVersion::WebGL2 {
+                            Self::get_webgl2_limits(data.ctx.gl(), &mut limits);
+                        }
+                        Self::get_capability_limits(data.ctx.gl(), version, &mut limits);
 
                         // FIXME(nox): Should probably be done by offscreen_gl_context.
                         if !is_gles() {
@@ -692,6 +704,48 @@ impl<VR: WebVRRenderHandler + 'static> WebGLThread<VR> {
 
         WebGLSLVersion { major, minor }
     }
+
+    /// Fills the limits which only make sense for WebGL 2 contexts.
+    #[allow(unsafe_code)]
+    fn get_webgl2_limits(gl: &dyn gl::Gl, limits: &mut GLLimits) {
+        let get = |param| {
+            let mut value = [0];
+            unsafe {
+                gl.get_integer_v(param, &mut value);
+            }
+            value[0].max(0) as u32
+        };
+        limits.max_uniform_buffer_bindings = get(gl::MAX_UNIFORM_BUFFER_BINDINGS);
+        limits.max_transform_feedback_separate_attribs =
+            get(gl::MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS);
+        limits.uniform_buffer_offset_alignment = get(gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT);
+    }
+
+    /// Fills the limits of optional GL capabilities, which are only exposed
+    /// to WebGL 1 through extensions.
+    #[allow(unsafe_code)]
+    fn get_capability_limits(gl: &dyn gl::Gl, version: WebGLVersion, limits: &mut GLLimits) {
+        // Multiple draw buffers are core in desktop GL and GLES 3, GLES 2
+        // drivers expose them through an extension.
+        let supports_draw_buffers = version == WebGLVersion::WebGL2 ||
+            !is_gles() ||
+            gl.get_string(gl::EXTENSIONS)
+                .split(' ')
+                .any(|ext| ext == "GL_EXT_draw_buffers" || ext == "GL_NV_draw_buffers");
+        if !supports_draw_buffers {
+            return;
+        }
+
+        let get = |param| {
+            let mut value = [0];
+            unsafe {
+                gl.get_integer_v(param, &mut value);
+            }
+            value[0].max(1) as u32
+        };
+        limits.max_color_attachments = get(gl::MAX_COLOR_ATTACHMENTS);
+        limits.max_draw_buffers = get(gl::MAX_DRAW_BUFFERS);
+    }
 }
 
 impl<VR: WebVRRenderHandler + 'static> Drop for WebGLThread<VR> {
@@ -1123,7 +1177,7 @@ impl WebGLImpl {
                     size.width as i32,
                     size.height as i32,
                     0,
-                    format.as_gl_constant(),
+                    format.to_pixel_transfer_format(),
                     effective_data_type,
                     Some(&pixels),
                 );
@@ -1163,7 +1217,7 @@ impl WebGLImpl {
                     yoffset,
                     size.width as i32,
                     size.height as i32,
-                    format.as_gl_constant(),
+                    format.to_pixel_transfer_format(),
                     effective_data_type,
                     &pixels,
                 );
@@ -1255,6 +1309,14 @@ impl WebGLImpl {
                 }
                 sender.send(value[0]).unwrap()
             },
+            WebGLCommand::GetProgramActiveUniformBlocks(program, ref sender) => {
+                let mut value = [0];
+                unsafe {
+                    ctx.gl()
+                        .get_program_iv(program.get(), gl::ACTIVE_UNIFORM_BLOCKS, &mut value);
+                }
+                sender.send(value[0]).unwrap()
+            },
             WebGLCommand::GetCurrentVertexAttrib(index, ref sender) => {
                 let mut value = [0.; 4];
                 unsafe {
@@ -1422,6 +1484,185 @@ impl WebGLImpl {
                 depth,
                 stencil,
             } => Self::initialize_framebuffer(ctx.gl(), state, color, depth, stencil),
+            WebGLCommand::GetBufferSubData(target, offset, length, ref sender) => {
+                Self::get_buffer_sub_data(ctx.gl(), target, offset, length, sender)
+            },
+            WebGLCommand::BindBufferBase(target, index, id) => {
+                ctx.gl()
+                    .bind_buffer_base(target, index, id.map_or(0, WebGLBufferId::get))
+            },
+            WebGLCommand::BindBufferRange(target, index, id, offset, size) => {
+                ctx.gl().bind_buffer_range(
+                    target,
+                    index,
+                    id.map_or(0, WebGLBufferId::get),
+                    offset as isize,
+                    size as isize,
+                )
+            },
+            WebGLCommand::GetUniformBlockIndex(program_id, ref name, ref sender) => {
+                let index = ctx
+                    .gl()
+                    .get_uniform_block_index(program_id.get(), &to_name_in_compiled_shader(name));
+                sender.send(index).unwrap();
+            },
+            WebGLCommand::GetUniformIndices(program_id, ref names, ref sender) => {
+                let names = names
+                    .iter()
+                    .map(|name| to_name_in_compiled_shader(name))
+                    .collect::<Vec<_>>();
+                let names = names.iter().map(|name| &**name).collect::<Vec<_>>();
+                let indices = ctx.gl().get_uniform_indices(program_id.get(), &names);
+                sender.send(indices).unwrap();
+            },
+            WebGLCommand::GetActiveUniforms(program_id, ref indices, pname, ref sender) => {
+                let values =
+                    ctx.gl()
+                        .get_active_uniforms_iv(program_id.get(), indices.clone(), pname);
+                sender.send(values).unwrap();
+            },
+            WebGLCommand::GetActiveUniformBlockName(program_id, index, ref sender) => {
+                let name = ctx
+                    .gl()
+                    .get_active_uniform_block_name(program_id.get(), index);
+                sender.send(from_name_in_compiled_shader(&name)).unwrap();
+            },
+            WebGLCommand::GetActiveUniformBlockParameter(program_id, index, pname, ref sender) => {
+                let values = ctx
+                    .gl()
+                    .get_active_uniform_block_iv(program_id.get(), index, pname);
+                sender.send(values).unwrap();
+            },
+            WebGLCommand::UniformBlockBinding(program_id, index, binding) => ctx
+                .gl()
+                .uniform_block_binding(program_id.get(), index, binding),
+            WebGLCommand::CreateQuery(ref chan) => Self::create_query(ctx.gl(), chan),
+            WebGLCommand::DeleteQuery(id) => ctx.gl().delete_queries(&[id.get()]),
+            WebGLCommand::BeginQuery(target, id) => ctx.gl().begin_query(target, id.get()),
+            WebGLCommand::EndQuery(target) => ctx.gl().end_query(target),
+            WebGLCommand::GetQueryParameter(id, pname, ref sender) => {
+                let value = ctx.gl().get_query_object_ui64v(id.get(), pname);
+                sender.send(value).unwrap();
+            },
+            WebGLCommand::CreateSampler(ref chan) => Self::create_sampler(ctx.gl(), chan),
+            WebGLCommand::DeleteSampler(id) => ctx.gl().delete_samplers(&[id.get()]),
+            WebGLCommand::BindSampler(unit, id) => ctx
+                .gl()
+                .bind_sampler(unit, id.map_or(0, WebGLSamplerId::get)),
+            WebGLCommand::SamplerParameteri(id, pname, value) => {
+                ctx.gl().sampler_parameter_i(id.get(), pname, value)
+            },
+            WebGLCommand::SamplerParameterf(id, pname, value) => {
+                ctx.gl().sampler_parameter_f(id.get(), pname, value)
+            },
+            WebGLCommand::GetSamplerParameterInt(id, pname, ref sender) => {
+                sender
+                    .send(ctx.gl().get_sampler_parameter_iv(id.get(), pname))
+                    .unwrap();
+            },
+            WebGLCommand::GetSamplerParameterFloat(id, pname, ref sender) => {
+                sender
+                    .send(ctx.gl().get_sampler_parameter_fv(id.get(), pname))
+                    .unwrap();
+            },
+            WebGLCommand::FenceSync(ref chan) => Self::fence_sync(ctx.gl(), state, chan),
+            WebGLCommand::ClientWaitSync(id, flags, timeout, ref sender) => {
+                let status = match state.syncs.get(&id) {
+                    Some(&sync) => ctx.gl().client_wait_sync(sync, flags, timeout),
+                    None => gl::WAIT_FAILED,
+                };
+                sender.send(status).unwrap();
+            },
+            WebGLCommand::WaitSync(id, flags, timeout) => {
+                if let Some(&sync) = state.syncs.get(&id) {
+                    ctx.gl().wait_sync(sync, flags, timeout as u64);
+                }
+            },
+            WebGLCommand::GetSyncParameter(id, pname, ref sender) => {
+                let value = match state.syncs.get(&id) {
+                    Some(&sync) => ctx.gl().get_sync_iv(sync, pname) as u32,
+                    None => 0,
+                };
+                sender.send(value).unwrap();
+            },
+            WebGLCommand::DeleteSync(id) => {
+                if let Some(sync) = state.syncs.remove(&id) {
+                    ctx.gl().delete_sync(sync);
+                }
+            },
+            WebGLCommand::CreateTransformFeedback(ref chan) => {
+                Self::create_transform_feedback(ctx.gl(), chan)
+            },
+            WebGLCommand::DeleteTransformFeedback(id) => {
+                ctx.gl().delete_transform_feedbacks(&[id.get()])
+            },
+            WebGLCommand::BindTransformFeedback(target, id) => ctx
+                .gl()
+                .bind_transform_feedback(target, id.map_or(0, WebGLTransformFeedbackId::get)),
+            WebGLCommand::BeginTransformFeedback(mode) => ctx.gl().begin_transform_feedback(mode),
+            WebGLCommand::EndTransformFeedback => ctx.gl().end_transform_feedback(),
+            WebGLCommand::PauseTransformFeedback => ctx.gl().pause_transform_feedback(),
+            WebGLCommand::ResumeTransformFeedback => ctx.gl().resume_transform_feedback(),
+            WebGLCommand::TransformFeedbackVaryings(program_id, ref varyings, buffer_mode) => {
+                let varyings = varyings
+                    .iter()
+                    .map(|name| to_name_in_compiled_shader(name))
+                    .collect::<Vec<_>>();
+                ctx.gl()
+                    .transform_feedback_varyings(program_id.get(), &varyings, buffer_mode)
+            },
+            WebGLCommand::GetTransformFeedbackVarying(program_id, index, ref sender) => {
+                Self::transform_feedback_varying(ctx.gl(), program_id, index, sender)
+            },
+            WebGLCommand::DrawBuffers(ref buffers) => {
+                // The default framebuffer of a WebGL context is an FBO, its
+                // single color buffer is known to GL as COLOR_ATTACHMENT0.
+                let buffers = buffers
+                    .iter()
+                    .map(|&buffer| match buffer {
+                        gl::BACK => gl::COLOR_ATTACHMENT0,
+                        _ => buffer,
+                    })
+                    .collect::<Vec<_>>();
+                ctx.gl().draw_buffers(&buffers)
+            },
+            WebGLCommand::CompressedTexImage2D {
+                target,
+                level,
+                internal_format,
+                size,
+                ref data,
+            } => ctx.gl().compressed_tex_image_2d(
+                target,
+                level as i32,
+                internal_format,
+                size.width as i32,
+                size.height as i32,
+                0,
+                &*data,
+            ),
+            WebGLCommand::CompressedTexSubImage2D {
+                target,
+                level,
+                xoffset,
+                yoffset,
+                size,
+                format,
+                ref data,
+            } => ctx.gl().compressed_tex_sub_image_2d(
+                target,
+                level,
+                xoffset,
+                yoffset,
+                size.width as i32,
+                size.height as i32,
+                format,
+                &*data,
+            ),
+            WebGLCommand::GetString(name, ref sender) => {
+                sender.send(ctx.gl().get_string(name)).unwrap()
+            },
+            WebGLCommand::ResetState => Self::reset_state(ctx, state),
         }
 
         // TODO: update test expectations in order to enable debug assertions
@@ -1719,6 +1960,94 @@ impl WebGLImpl {
         chan.send(vao).unwrap();
     }
 
+    #[allow(unsafe_code)]
+    fn create_query(gl: &dyn gl::Gl, chan: &WebGLSender<Option<WebGLQueryId>>) {
+        let query = gl.gen_queries(1)[0];
+        let query = if query == 0 {
+            None
+        } else {
+            Some(unsafe { WebGLQueryId::new(query) })
+        };
+        chan.send(query).unwrap();
+    }
+
+    #[allow(unsafe_code)]
+    fn create_sampler(gl: &dyn gl::Gl, chan: &WebGLSender<Option<WebGLSamplerId>>) {
+        let sampler = gl.gen_samplers(1)[0];
+        let sampler = if sampler == 0 {
+            None
+        } else {
+            Some(unsafe { WebGLSamplerId::new(sampler) })
+        };
+        chan.send(sampler).unwrap();
+    }
+
+    #[allow(unsafe_code)]
+    fn create_transform_feedback(
+        gl: &dyn gl::Gl,
+        chan: &WebGLSender<Option<WebGLTransformFeedbackId>>,
+    ) {
+        let transform_feedback = gl.gen_transform_feedbacks(1)[0];
+        let transform_feedback = if transform_feedback == 0 {
+            None
+        } else {
+            Some(unsafe { WebGLTransformFeedbackId::new(transform_feedback) })
+        };
+        chan.send(transform_feedback).unwrap();
+    }
+
+    #[allow(unsafe_code)]
+    fn fence_sync(gl: &dyn gl::Gl, state: &mut GLState, chan: &WebGLSender<Option<WebGLSyncId>>) {
+        let sync = gl.fence_sync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0);
+        if sync.is_null() {
+            return chan.send(None).unwrap();
+        }
+        let id = unsafe { WebGLSyncId::new(state.next_sync_id) };
+        state.next_sync_id += 1;
+        state.syncs.insert(id, sync);
+        chan.send(Some(id)).unwrap();
+    }
+
+    #[allow(unsafe_code)]
+    fn get_buffer_sub_data(
+        gl: &dyn gl::Gl,
+        target: u32,
+        offset: usize,
+        length: usize,
+        chan: &IpcBytesSender,
+    ) {
+        if length == 0 {
+            return chan.send(&[]).unwrap();
+        }
+        let ptr = gl.map_buffer_range(target, offset as isize, length as isize, gl::MAP_READ_BIT);
+        let data = unsafe { slice::from_raw_parts(ptr as *const u8, length) };
+        chan.send(data).unwrap();
+        gl.unmap_buffer(target);
+    }
+
+    #[allow(unsafe_code)]
+    fn transform_feedback_varying(
+        gl: &dyn gl::Gl,
+        program: WebGLProgramId,
+        index: u32,
+        chan: &WebGLSender<Option<ActiveTransformFeedbackVaryingInfo>>,
+    ) {
+        let mut count = [0];
+        unsafe {
+            gl.get_program_iv(program.get(), gl::TRANSFORM_FEEDBACK_VARYINGS, &mut count);
+        }
+        if index >= count[0].max(0) as u32 {
+            return chan.send(None).unwrap();
+        }
+        let (size, type_, name) = gl.get_transform_feedback_varying(program.get(), index);
+        chan.send(Some(ActiveTransformFeedbackVaryingInfo {
+            name: from_name_in_compiled_shader(&name),
+            size,
+            type_,
+        }))
+        .unwrap();
+    }
+
     #[inline]
     fn bind_framebuffer<Native: NativeGLContextMethods>(
         gl: &dyn gl::Gl,
@@ -1736,6 +2065,79 @@ impl WebGLImpl {
         gl.bind_framebuffer(target, id);
     }
 
+    /// Puts the context back into the state of a newly created one, objects
+    /// created by script are kept alive until script deletes them.
+    fn reset_state<Native: NativeGLContextMethods>(ctx: &GLContext<Native>, state: &mut GLState) {
+        let gl = ctx.gl();
+        Self::bind_framebuffer(
+            gl,
+            gl::FRAMEBUFFER,
+            WebGLFramebufferBindingRequest::Default,
+            ctx,
+        );
+        gl.bind_renderbuffer(gl::RENDERBUFFER, 0);
+        gl.bind_buffer(gl::ARRAY_BUFFER, 0);
+        gl.bind_buffer(gl::ELEMENT_ARRAY_BUFFER, 0);
+        gl.use_program(0);
+
+        let mut max_texture_units = [0];
+        #[allow(unsafe_code)]
+        unsafe {
+            gl.get_integer_v(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS, &mut max_texture_units);
+        }
+        for unit in 0..max_texture_units[0].max(0) as u32 {
+            gl.active_texture(gl::TEXTURE0 + unit);
+            gl.bind_texture(gl::TEXTURE_2D, 0);
+            gl.bind_texture(gl::TEXTURE_CUBE_MAP, 0);
+        }
+        gl.active_texture(gl::TEXTURE0);
+
+        for &cap in &[
+            gl::BLEND,
+            gl::CULL_FACE,
+            gl::DEPTH_TEST,
+            gl::POLYGON_OFFSET_FILL,
+            gl::SAMPLE_ALPHA_TO_COVERAGE,
+            gl::SAMPLE_COVERAGE,
+            gl::SCISSOR_TEST,
+            gl::STENCIL_TEST,
+        ] {
+            gl.disable(cap);
+        }
+        gl.enable(gl::DITHER);
+
+        gl.blend_color(0., 0., 0., 0.);
+        gl.blend_equation(gl::FUNC_ADD);
+        gl.blend_func(gl::ONE, gl::ZERO);
+        gl.color_mask(true, true, true, true);
+        gl.cull_face(gl::BACK);
+        gl.front_face(gl::CCW);
+        gl.depth_func(gl::LESS);
+        gl.depth_range(0., 1.);
+        gl.line_width(1.);
+        gl.polygon_offset(0., 0.);
+        gl.sample_coverage(1., false);
+        gl.stencil_func(gl::ALWAYS, 0, 0xFFFFFFFF);
+        gl.stencil_op(gl::KEEP, gl::KEEP, gl::KEEP);
+        gl.stencil_mask(0xFFFFFFFF);
+        gl.depth_mask(true);
+        gl.clear_color(0., 0., 0., 0.);
+        gl.clear_depth(1.);
+        gl.clear_stencil(0);
+        gl.pixel_store_i(gl::PACK_ALIGNMENT, 4);
+        gl.pixel_store_i(gl::UNPACK_ALIGNMENT, 4);
+
+        let size = ctx.borrow_draw_buffer().unwrap().size();
+        gl.viewport(0, 0, size.width, size.height);
+        gl.scissor(0, 0, size.width, size.height);
+
+        *state = GLState {
+            syncs: mem::replace(&mut state.syncs, Default::default()),
+            next_sync_id: state.next_sync_id,
+            ..Default::default()
+        };
+    }
+
     #[inline]
     fn compile_shader(gl: &dyn gl::Gl, shader_id: WebGLShaderId, source: &str) {
         gl.shader_source(shader_id.get(), &[source.as_bytes()]);
@@ -1852,8 +2254,10 @@ fn image_to_tex_image_data(
     }
 
     match (format, data_type) {
-        (TexFormat::RGBA, TexDataType::UnsignedByte) => pixels,
-        (TexFormat::RGB, TexDataType::UnsignedByte) => {
+        (TexFormat::RGBA, TexDataType::UnsignedByte) |
+        (TexFormat::SRGBAlpha, TexDataType::UnsignedByte) => pixels,
+        (TexFormat::RGB, TexDataType::UnsignedByte) |
+        (TexFormat::SRGB, TexDataType::UnsignedByte) => {
             for i in 0..pixel_count {
                 let rgb = {
                     let rgb = &pixels[i * 4..i * 4 + 3];
@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use cssparser::RGBA;
-use euclid::{Point2D, Rect, Size2D, Transform2D};
+use euclid::{Point2D, Rect, Size2D, Transform2D, Vector2D};
 use ipc_channel::ipc::{IpcBytesReceiver, IpcBytesSender, IpcSender, IpcSharedMemory};
 use serde_bytes::ByteBuf;
 use std::default::Default;
@@ -56,6 +56,7 @@ pub enum Canvas2dMsg {
     FillRect(Rect<f32>),
     GetImageData(Rect<u32>, Size2D<u32>, IpcBytesSender),
     IsPointInPath(f64, f64, FillRule, IpcSender<bool>),
+    IsPointInStroke(f64, f64, IpcSender<bool>),
     LineTo(Point2D<f32>),
     MoveTo(Point2D<f32>),
     PutImageData(Rect<u32>, IpcBytesReceiver),
@@ -78,6 +79,8 @@ pub enum Canvas2dMsg {
     SetShadowOffsetY(f64),
     SetShadowBlur(f64),
     SetShadowColor(RGBA),
+    SetFilter(Vec<FilterFunction>),
+    SetImageSmoothingQuality(SmoothingQuality),
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -228,6 +231,32 @@ impl FromStr for LineJoinStyle {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum SmoothingQuality {
+    Low,
+    Medium,
+    High,
+}
+
+/// A single filter function of the canvas `filter` attribute, with all lengths
+/// resolved to pixels and all factors resolved to numbers.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum FilterFunction {
+    /// `blur()`, with the standard deviation of the gaussian.
+    Blur(f32),
+    Brightness(f32),
+    Contrast(f32),
+    /// `drop-shadow()`, with the shadow offset, blur radius and color.
+    DropShadow(Vector2D<f32>, f32, RGBA),
+    Grayscale(f32),
+    /// `hue-rotate()`, with the angle in radians.
+    HueRotate(f32),
+    Invert(f32),
+    Opacity(f32),
+    Saturate(f32),
+    Sepia(f32),
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub enum RepetitionStyle {
     Repeat,
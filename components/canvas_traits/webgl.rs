@@ -349,6 +349,7 @@ pub enum WebGLCommand {
     GetParameterFloat4(ParameterFloat4, WebGLSender<[f32; 4]>),
     GetProgramValidateStatus(WebGLProgramId, WebGLSender<bool>),
     GetProgramActiveUniforms(WebGLProgramId, WebGLSender<i32>),
+    GetProgramActiveUniformBlocks(WebGLProgramId, WebGLSender<i32>),
     GetCurrentVertexAttrib(u32, WebGLSender<[f32; 4]>),
     GetTexParameterFloat(u32, TexParameterFloat, WebGLSender<f32>),
     GetTexParameterInt(u32, TexParameterInt, WebGLSender<i32>),
@@ -401,6 +402,66 @@ pub enum WebGLCommand {
         depth: bool,
         stencil: bool,
     },
+    GetBufferSubData(u32, usize, usize, IpcBytesSender),
+    BindBufferBase(u32, u32, Option<WebGLBufferId>),
+    BindBufferRange(u32, u32, Option<WebGLBufferId>, i64, i64),
+    GetUniformBlockIndex(WebGLProgramId, String, WebGLSender<u32>),
+    GetUniformIndices(WebGLProgramId, Vec<String>, WebGLSender<Vec<u32>>),
+    GetActiveUniforms(WebGLProgramId, Vec<u32>, u32, WebGLSender<Vec<i32>>),
+    GetActiveUniformBlockName(WebGLProgramId, u32, WebGLSender<String>),
+    GetActiveUniformBlockParameter(WebGLProgramId, u32, u32, WebGLSender<Vec<i32>>),
+    UniformBlockBinding(WebGLProgramId, u32, u32),
+    CreateQuery(WebGLSender<Option<WebGLQueryId>>),
+    DeleteQuery(WebGLQueryId),
+    BeginQuery(u32, WebGLQueryId),
+    EndQuery(u32),
+    GetQueryParameter(WebGLQueryId, u32, WebGLSender<u64>),
+    CreateSampler(WebGLSender<Option<WebGLSamplerId>>),
+    DeleteSampler(WebGLSamplerId),
+    BindSampler(u32, Option<WebGLSamplerId>),
+    SamplerParameteri(WebGLSamplerId, u32, i32),
+    SamplerParameterf(WebGLSamplerId, u32, f32),
+    GetSamplerParameterInt(WebGLSamplerId, u32, WebGLSender<i32>),
+    GetSamplerParameterFloat(WebGLSamplerId, u32, WebGLSender<f32>),
+    FenceSync(WebGLSender<Option<WebGLSyncId>>),
+    ClientWaitSync(WebGLSyncId, u32, u64, WebGLSender<u32>),
+    WaitSync(WebGLSyncId, u32, i64),
+    GetSyncParameter(WebGLSyncId, u32, WebGLSender<u32>),
+    DeleteSync(WebGLSyncId),
+    CreateTransformFeedback(WebGLSender<Option<WebGLTransformFeedbackId>>),
+    DeleteTransformFeedback(WebGLTransformFeedbackId),
+    BindTransformFeedback(u32, Option<WebGLTransformFeedbackId>),
+    BeginTransformFeedback(u32),
+    EndTransformFeedback,
+    PauseTransformFeedback,
+    ResumeTransformFeedback,
+    TransformFeedbackVaryings(WebGLProgramId, Vec<String>, u32),
+    GetTransformFeedbackVarying(
+        WebGLProgramId,
+        u32,
+        WebGLSender<Option<ActiveTransformFeedbackVaryingInfo>>,
+    ),
+    DrawBuffers(Vec<u32>),
+    CompressedTexImage2D {
+        target: u32,
+        level: u32,
+        internal_format: u32,
+        size: Size2D<u32>,
+        data: TruncatedDebug<IpcSharedMemory>,
+    },
+    CompressedTexSubImage2D {
+        target: u32,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        size: Size2D<u32>,
+        format: u32,
+        data: TruncatedDebug<IpcSharedMemory>,
+    },
+    GetString(u32, WebGLSender<String>),
+    /// Resets the GL state of the context to its defaults, used when a lost
+    /// context is restored.
+    ResetState,
 }
 
 macro_rules! define_resource_id {
@@ -474,6 +535,10 @@ define_resource_id!(WebGLTextureId);
 define_resource_id!(WebGLProgramId);
 define_resource_id!(WebGLShaderId);
 define_resource_id!(WebGLVertexArrayId);
+define_resource_id!(WebGLQueryId);
+define_resource_id!(WebGLSamplerId);
+define_resource_id!(WebGLSyncId);
+define_resource_id!(WebGLTransformFeedbackId);
 
 #[derive(
     Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, Ord, PartialEq, PartialOrd, Serialize,
@@ -576,6 +641,17 @@ pub struct ActiveUniformInfo {
     pub type_: u32,
 }
 
+/// Description of a single transform feedback varying.
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct ActiveTransformFeedbackVaryingInfo {
+    /// The name of the varying.
+    pub name: String,
+    /// The size of the varying.
+    pub size: i32,
+    /// The type of the varying.
+    pub type_: u32,
+}
+
 impl ActiveUniformInfo {
     pub fn name(&self) -> Cow<str> {
         if self.size.is_some() {
@@ -739,11 +815,28 @@ macro_rules! gl_enums {
 gl_enums! {
     pub enum TexFormat {
         DepthComponent = gl::DEPTH_COMPONENT,
+        DepthStencil = gl::DEPTH_STENCIL,
         Alpha = gl::ALPHA,
         RGB = gl::RGB,
         RGBA = gl::RGBA,
         Luminance = gl::LUMINANCE,
         LuminanceAlpha = gl::LUMINANCE_ALPHA,
+        SRGB = gl::SRGB,
+        SRGBAlpha = gl::SRGB_ALPHA,
+        CompressedRgbS3tcDxt1 = gl::COMPRESSED_RGB_S3TC_DXT1_EXT,
+        CompressedRgbaS3tcDxt1 = gl::COMPRESSED_RGBA_S3TC_DXT1_EXT,
+        CompressedRgbaS3tcDxt3 = gl::COMPRESSED_RGBA_S3TC_DXT3_EXT,
+        CompressedRgbaS3tcDxt5 = gl::COMPRESSED_RGBA_S3TC_DXT5_EXT,
+        CompressedR11Eac = gl::COMPRESSED_R11_EAC,
+        CompressedSignedR11Eac = gl::COMPRESSED_SIGNED_R11_EAC,
+        CompressedRg11Eac = gl::COMPRESSED_RG11_EAC,
+        CompressedSignedRg11Eac = gl::COMPRESSED_SIGNED_RG11_EAC,
+        CompressedRgb8Etc2 = gl::COMPRESSED_RGB8_ETC2,
+        CompressedSrgb8Etc2 = gl::COMPRESSED_SRGB8_ETC2,
+        CompressedRgb8PunchthroughAlpha1Etc2 = gl::COMPRESSED_RGB8_PUNCHTHROUGH_ALPHA1_ETC2,
+        CompressedSrgb8PunchthroughAlpha1Etc2 = gl::COMPRESSED_SRGB8_PUNCHTHROUGH_ALPHA1_ETC2,
+        CompressedRgba8Etc2Eac = gl::COMPRESSED_RGBA8_ETC2_EAC,
+        CompressedSrgb8Alpha8Etc2Eac = gl::COMPRESSED_SRGB8_ALPHA8_ETC2_EAC,
     }
 
     pub enum TexDataType {
@@ -753,6 +846,9 @@ gl_enums! {
         UnsignedShort565 = gl::UNSIGNED_SHORT_5_6_5,
         Float = gl::FLOAT,
         HalfFloat = gl::HALF_FLOAT_OES,
+        UnsignedShort = gl::UNSIGNED_SHORT,
+        UnsignedInt = gl::UNSIGNED_INT,
+        UnsignedInt248 = gl::UNSIGNED_INT_24_8,
     }
 }
 
@@ -762,13 +858,57 @@ impl TexFormat {
     pub fn components(&self) -> u32 {
         match *self {
             TexFormat::DepthComponent => 1,
+            TexFormat::DepthStencil => 1,
             TexFormat::Alpha => 1,
             TexFormat::Luminance => 1,
             TexFormat::LuminanceAlpha => 2,
-            TexFormat::RGB => 3,
-            TexFormat::RGBA => 4,
+            TexFormat::RGB | TexFormat::SRGB => 3,
+            TexFormat::RGBA | TexFormat::SRGBAlpha => 4,
+            // Compressed formats are uploaded as whole blocks, their size
+            // doesn't depend on the number of components.
+            _ => 1,
+        }
+    }
+
+    /// Returns whether this format is one of the compressed texture formats
+    /// exposed by extensions.
+    pub fn is_compressed(&self) -> bool {
+        match *self {
+            TexFormat::CompressedRgbS3tcDxt1 |
+            TexFormat::CompressedRgbaS3tcDxt1 |
+            TexFormat::CompressedRgbaS3tcDxt3 |
+            TexFormat::CompressedRgbaS3tcDxt5 |
+            TexFormat::CompressedR11Eac |
+            TexFormat::CompressedSignedR11Eac |
+            TexFormat::CompressedRg11Eac |
+            TexFormat::CompressedSignedRg11Eac |
+            TexFormat::CompressedRgb8Etc2 |
+            TexFormat::CompressedSrgb8Etc2 |
+            TexFormat::CompressedRgb8PunchthroughAlpha1Etc2 |
+            TexFormat::CompressedSrgb8PunchthroughAlpha1Etc2 |
+            TexFormat::CompressedRgba8Etc2Eac |
+            TexFormat::CompressedSrgb8Alpha8Etc2Eac => true,
+            _ => false,
         }
     }
+
+    pub fn is_depth(&self) -> bool {
+        match *self {
+            TexFormat::DepthComponent | TexFormat::DepthStencil => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the format to pass to the GL when uploading pixels, desktop
+    /// GL only knows about the sRGB formats as internal formats.
+    pub fn to_pixel_transfer_format(&self) -> u32 {
+        match *self {
+            TexFormat::SRGB if !is_gles() => gl::RGB,
+            TexFormat::SRGBAlpha if !is_gles() => gl::RGBA,
+            _ => self.as_gl_constant(),
+        }
+    }
+}
 }
 
 impl TexDataType {
@@ -782,6 +922,8 @@ impl TexDataType {
             TexDataType::UnsignedShort565 => 2,
             TexDataType::Float => 4,
             TexDataType::HalfFloat => 2,
+            TexDataType::UnsignedShort => 2,
+            TexDataType::UnsignedInt | TexDataType::UnsignedInt248 => 4,
         }
     }
 
@@ -795,6 +937,9 @@ impl TexDataType {
             TexDataType::UnsignedShort4444 => 4,
             TexDataType::Float => 1,
             TexDataType::HalfFloat => 1,
+            TexDataType::UnsignedShort |
+            TexDataType::UnsignedInt |
+            TexDataType::UnsignedInt248 => 1,
         }
     }
 }
@@ -833,4 +978,14 @@ pub struct GLLimits {
     pub max_varying_vectors: u32,
     pub max_vertex_texture_image_units: u32,
     pub max_vertex_uniform_vectors: u32,
+    /// Only queried for WebGL 2 contexts, zero otherwise.
+    pub max_uniform_buffer_bindings: u32,
+    /// Only queried for WebGL 2 contexts, zero otherwise.
+    pub max_transform_feedback_separate_attribs: u32,
+    /// Only queried for WebGL 2 contexts, zero otherwise.
+    pub uniform_buffer_offset_alignment: u32,
+    /// One if the GL doesn't support multiple draw buffers.
+    pub max_color_attachments: u32,
+    /// One if the GL doesn't support multiple draw buffers.
+    pub max_draw_buffers: u32,
 }
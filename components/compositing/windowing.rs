@@ -4,7 +4,7 @@
 
 //! Abstract windowing methods. The concrete implementations of these can be found in `platform/`.
 
-use embedder_traits::EventLoopWaker;
+use embedder_traits::{EventLoopWaker, GeolocationPosition};
 use euclid::TypedScale;
 #[cfg(feature = "gl")]
 use gleam::gl;
@@ -93,6 +93,12 @@ pub enum WindowEvent {
     ToggleWebRenderDebug(WebRenderDebugOption),
     /// Capture current WebRender
     CaptureWebRender,
+    /// Sent when the user's preferred languages change, most preferred first.
+    SetLanguages(Vec<String>),
+    /// Sent to switch the network offline or back online.
+    SetOffline(bool),
+    /// Sent when the position of the device changes, `None` if it became unavailable.
+    SetGeolocation(Option<GeolocationPosition>),
 }
 
 impl Debug for WindowEvent {
@@ -120,6 +126,9 @@ impl Debug for WindowEvent {
             WindowEvent::SelectBrowser(..) => write!(f, "SelectBrowser"),
             WindowEvent::ToggleWebRenderDebug(..) => write!(f, "ToggleWebRenderDebug"),
             WindowEvent::CaptureWebRender => write!(f, "CaptureWebRender"),
+            WindowEvent::SetLanguages(..) => write!(f, "SetLanguages"),
+            WindowEvent::SetOffline(..) => write!(f, "SetOffline"),
+            WindowEvent::SetGeolocation(..) => write!(f, "SetGeolocation"),
         }
     }
 }
@@ -76,6 +76,13 @@ impl PrefValue {
             _ => None,
         }
     }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            PrefValue::Number(x) => Some(x),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -127,6 +134,23 @@ pub fn default_prefs() -> Preferences {
     prefs
 }
 
+/// The languages used when the `intl.accept_languages` preference is missing or empty.
+const DEFAULT_LANGUAGES: &'static [&'static str] = &["en-US", "en"];
+
+/// Parse a comma-separated list of language tags, in order of preference.
+/// Entries that are not well-formed language tags are skipped.
+pub fn parse_language_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|tag| tag.trim())
+        .filter(|tag| is_language_tag(tag))
+        .map(|tag| tag.to_owned())
+        .collect()
+}
+
+fn is_language_tag(tag: &str) -> bool {
+    !tag.is_empty() && tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
 pub fn read_prefs(txt: &str) -> Result<HashMap<String, Pref>, ()> {
     let json: Value = serde_json::from_str(txt).or_else(|e| {
         println!("Ignoring invalid JSON in preferences: {:?}.", e);
@@ -254,4 +278,31 @@ impl Preferences {
     pub fn is_webgl2_enabled(&self) -> bool {
         self.get("dom.webgl2.enabled").as_boolean().unwrap_or(false)
     }
+
+    pub fn is_network_offline(&self) -> bool {
+        self.get("network.offline").as_boolean().unwrap_or(false)
+    }
+
+    /// The user's preferred languages, most preferred first. Never empty.
+    pub fn languages(&self) -> Vec<String> {
+        let languages = self
+            .get("intl.accept_languages")
+            .as_string()
+            .map(parse_language_list)
+            .unwrap_or_default();
+        if languages.is_empty() {
+            return DEFAULT_LANGUAGES
+                .iter()
+                .map(|tag| tag.to_string())
+                .collect();
+        }
+        languages
+    }
+
+    pub fn set_languages(&self, languages: &[String]) {
+        self.set(
+            "intl.accept_languages",
+            PrefValue::String(languages.join(", ")),
+        );
+    }
 }
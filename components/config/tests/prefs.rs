@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use servo_config::basedir;
-use servo_config::prefs::{read_prefs, PrefValue, PREFS};
+use servo_config::prefs::{parse_language_list, read_prefs, PrefValue, PREFS};
 use std::fs::{self, File};
 use std::io::{Read, Write};
 
@@ -58,6 +58,19 @@ fn test_get_set_reset_extend() {
     assert_eq!(*PREFS.get("extra.stuff"), PrefValue::Boolean(false));
 }
 
+#[test]
+fn test_parse_language_list() {
+    assert_eq!(
+        parse_language_list("fr-CA, fr,en-US , en"),
+        vec!["fr-CA", "fr", "en-US", "en"]
+    );
+    assert_eq!(
+        parse_language_list("de,, x y,en;q=0.5"),
+        vec!["de".to_owned()]
+    );
+    assert!(parse_language_list("").is_empty());
+}
+
 #[cfg(not(target_os = "android"))]
 #[test]
 fn test_default_config_dir_create_read_write() {
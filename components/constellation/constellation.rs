@@ -113,7 +113,7 @@ use compositing::compositor_thread::Msg as ToCompositorMsg;
 use compositing::SendableFrameTree;
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::{ChromeToDevtoolsControlMsg, DevtoolsControlMsg};
-use embedder_traits::{Cursor, EmbedderMsg, EmbedderProxy};
+use embedder_traits::{Cursor, EmbedderMsg, EmbedderProxy, GeolocationPosition};
 use euclid::{Size2D, TypedScale, TypedSize2D};
 use gfx::font_cache_thread::FontCacheThread;
 use gfx_traits::Epoch;
@@ -129,6 +129,7 @@ use msg::constellation_msg::{
     BrowsingContextId, HistoryStateId, PipelineId, TopLevelBrowsingContextId,
 };
 use msg::constellation_msg::{PipelineNamespace, PipelineNamespaceId, TraversalDirection};
+use net_traits::cache_storage_thread::CacheStorageThreadMsg;
 use net_traits::pub_domains::reg_host;
 use net_traits::request::RequestInit;
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
@@ -151,7 +152,7 @@ use script_traits::{LayoutMsg as FromLayoutMsg, ScriptMsg as FromScriptMsg, Scri
 use script_traits::{SWManagerMsg, ScopeThings, UpdatePipelineIdReason, WebDriverCommandMsg};
 use serde::{Deserialize, Serialize};
 use servo_config::opts;
-use servo_config::prefs::PREFS;
+use servo_config::prefs::{PrefValue, PREFS};
 use servo_rand::{random, Rng, SeedableRng, ServoRng};
 use servo_remutex::ReentrantMutex;
 use servo_url::{Host, ImmutableOrigin, ServoUrl};
@@ -346,6 +347,10 @@ pub struct Constellation<Message, LTF, STF> {
     /// Document states for loaded pipelines (used only when writing screenshots).
     document_states: HashMap<PipelineId, DocumentState>,
 
+    /// Whether the user granted or denied permissions, by serialized origin and
+    /// permission name. Opaque origins don't keep permissions.
+    permissions: HashMap<(String, String), bool>,
+
     /// Are we shutting down?
     shutting_down: bool,
 
@@ -686,6 +691,7 @@ where
                     webdriver: WebDriverData::new(),
                     scheduler_chan: TimerScheduler::start(),
                     document_states: HashMap::new(),
+                    permissions: HashMap::new(),
                     webrender_document: state.webrender_document,
                     webrender_api_sender: state.webrender_api_sender,
                     shutting_down: false,
@@ -1036,6 +1042,55 @@ where
                 // store service worker manager for communicating with it.
                 self.swmanager_chan = Some(sw_sender);
             },
+            SWManagerMsg::UpdateFinished(pipeline_id, scope, result) => {
+                let msg = ConstellationControlMsg::ServiceWorkerUpdateFinished(
+                    pipeline_id,
+                    scope,
+                    result,
+                );
+                self.send_to_pipeline(pipeline_id, msg);
+            },
+            SWManagerMsg::StateChanged(scope, worker_id, script_url, state) => {
+                // Registrations live in the script threads of the pages that registered
+                // them, so every page of the scope's origin gets told about the change.
+                let origin = scope.origin();
+                let pipeline_ids: Vec<PipelineId> = self
+                    .pipelines
+                    .iter()
+                    .filter(|&(_, pipeline)| pipeline.url.origin() == origin)
+                    .map(|(pipeline_id, _)| *pipeline_id)
+                    .collect();
+                for pipeline_id in pipeline_ids {
+                    let msg = ConstellationControlMsg::ServiceWorkerStateChanged(
+                        pipeline_id,
+                        scope.clone(),
+                        worker_id,
+                        script_url.clone(),
+                        state,
+                    );
+                    self.send_to_pipeline(pipeline_id, msg);
+                }
+            },
+            SWManagerMsg::SetController(pipeline_id, scope) => {
+                let msg = ConstellationControlMsg::SetServiceWorkerController(pipeline_id, scope);
+                self.send_to_pipeline(pipeline_id, msg);
+            },
+            SWManagerMsg::GetPermission(origin, name, sender) => {
+                self.handle_get_permission(origin, name, sender);
+            },
+            SWManagerMsg::ForwardToEmbedder(embedder_msg) => {
+                self.embedder_proxy.send((None, embedder_msg));
+            },
+        }
+    }
+
+    fn send_to_pipeline(&mut self, pipeline_id: PipelineId, msg: ConstellationControlMsg) {
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => return debug!("Pipeline {:?} got message after closure.", pipeline_id),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
         }
     }
 
@@ -1195,6 +1250,11 @@ where
                 self.forward_event(destination_pipeline_id, event);
             },
             FromCompositorMsg::SetCursor(cursor) => self.handle_set_cursor_msg(cursor),
+            FromCompositorMsg::SetLanguages(languages) => self.handle_set_languages_msg(languages),
+            FromCompositorMsg::SetOffline(offline) => self.handle_set_offline_msg(offline),
+            FromCompositorMsg::SetGeolocation(position) => {
+                self.handle_set_geolocation_msg(position)
+            },
         }
     }
 
@@ -1349,6 +1409,22 @@ where
                 self.compositor_proxy
                     .send(ToCompositorMsg::GetScreenAvailSize(send));
             },
+            FromScriptMsg::RecordPermission(origin, name, granted) => {
+                if origin.is_tuple() {
+                    self.permissions
+                        .insert((origin.ascii_serialization(), name), granted);
+                }
+            },
+            FromScriptMsg::GetPermission(origin, name, sender) => {
+                self.handle_get_permission(origin, name, sender);
+            },
+            FromScriptMsg::ShowPersistentNotification(scope, notification) => {
+                if let Some(ref mgr) = self.swmanager_chan {
+                    let _ = mgr.send(ServiceWorkerMsg::ShowNotification(scope, notification));
+                } else {
+                    warn!("Unable to show a notification without a service worker manager");
+                }
+            },
             FromScriptMsg::LogEntry(thread_name, entry) => {
                 self.handle_log_entry(Some(source_top_ctx_id), thread_name, entry);
             },
@@ -1446,6 +1522,24 @@ where
         }
     }
 
+    fn handle_get_permission(
+        &self,
+        origin: ImmutableOrigin,
+        name: String,
+        sender: IpcSender<Option<bool>>,
+    ) {
+        let granted = if origin.is_tuple() {
+            self.permissions
+                .get(&(origin.ascii_serialization(), name))
+                .cloned()
+        } else {
+            None
+        };
+        if let Err(e) = sender.send(granted) {
+            warn!("Sending reply to get permission failed ({:?}).", e);
+        }
+    }
+
     fn handle_broadcast_storage_event(
         &self,
         pipeline_id: PipelineId,
@@ -1545,6 +1639,8 @@ where
         let (core_sender, core_receiver) = ipc::channel().expect("Failed to create IPC channel!");
         let (storage_sender, storage_receiver) =
             ipc::channel().expect("Failed to create IPC channel!");
+        let (cache_storage_sender, cache_storage_receiver) =
+            ipc::channel().expect("Failed to create IPC channel!");
 
         debug!("Exiting core resource threads.");
         if let Err(e) = self
@@ -1574,6 +1670,14 @@ where
             warn!("Exit storage thread failed ({})", e);
         }
 
+        debug!("Exiting cache storage thread.");
+        if let Err(e) = self
+            .public_resource_threads
+            .send(CacheStorageThreadMsg::Exit(cache_storage_sender))
+        {
+            warn!("Exit cache storage thread failed ({})", e);
+        }
+
         debug!("Exiting bluetooth thread.");
         if let Err(e) = self.bluetooth_thread.send(BluetoothRequest::Exit) {
             warn!("Exit bluetooth thread failed ({})", e);
@@ -1620,6 +1724,9 @@ where
         if let Err(e) = storage_receiver.recv() {
             warn!("Exit storage thread failed ({})", e);
         }
+        if let Err(e) = cache_storage_receiver.recv() {
+            warn!("Exit cache storage thread failed ({})", e);
+        }
 
         debug!("Asking compositor to complete shutdown.");
         self.compositor_proxy
@@ -1629,6 +1736,9 @@ where
     fn handle_pipeline_exited(&mut self, pipeline_id: PipelineId) {
         debug!("Pipeline {:?} exited.", pipeline_id);
         self.pipelines.remove(&pipeline_id);
+        if let Some(ref mgr) = self.swmanager_chan {
+            let _ = mgr.send(ServiceWorkerMsg::PipelineExited(pipeline_id));
+        }
     }
 
     fn handle_send_error(&mut self, pipeline_id: PipelineId, err: IpcError) {
@@ -1760,6 +1870,58 @@ where
         }
     }
 
+    fn handle_set_languages_msg(&mut self, languages: Vec<String>) {
+        // Network requests read the languages from the preferences of this process,
+        // script threads update their own copy when they get the message.
+        PREFS.set_languages(&languages);
+        for (pipeline_id, pipeline) in &self.pipelines {
+            let msg = ConstellationControlMsg::SetLanguages(*pipeline_id, languages.clone());
+            if let Err(e) = pipeline.event_loop.send(msg) {
+                warn!("Sending languages to {} failed ({:?}).", pipeline_id, e);
+            }
+        }
+    }
+
+    fn handle_set_offline_msg(&mut self, offline: bool) {
+        if PREFS.is_network_offline() == offline {
+            return;
+        }
+        // New pipelines and net threads pick the state up from the preferences.
+        PREFS.set("network.offline", PrefValue::Boolean(offline));
+        for resource_threads in &[
+            &self.public_resource_threads,
+            &self.private_resource_threads,
+        ] {
+            let msg = net_traits::CoreResourceMsg::SetOffline(offline);
+            if let Err(e) = resource_threads.send(msg) {
+                warn!("Sending offline state to resource thread failed ({})", e);
+            }
+        }
+        for (pipeline_id, pipeline) in &self.pipelines {
+            let msg = ConstellationControlMsg::SetOffline(*pipeline_id, offline);
+            if let Err(e) = pipeline.event_loop.send(msg) {
+                warn!("Sending offline state to {} failed ({:?}).", pipeline_id, e);
+            }
+        }
+        if let Some(ref mgr) = self.swmanager_chan {
+            if let Err(e) = mgr.send(ServiceWorkerMsg::SetOffline(offline)) {
+                warn!(
+                    "Sending offline state to service worker manager failed ({:?}).",
+                    e
+                );
+            }
+        }
+    }
+
+    fn handle_set_geolocation_msg(&mut self, position: Option<GeolocationPosition>) {
+        for (pipeline_id, pipeline) in &self.pipelines {
+            let msg = ConstellationControlMsg::SetGeolocation(*pipeline_id, position.clone());
+            if let Err(e) = pipeline.event_loop.send(msg) {
+                warn!("Sending position to {} failed ({:?}).", pipeline_id, e);
+            }
+        }
+    }
+
     fn forward_event(&mut self, destination_pipeline_id: PipelineId, event: CompositorEvent) {
         let msg = ConstellationControlMsg::SendEvent(destination_pipeline_id, event);
         let result = match self.pipelines.get(&destination_pipeline_id) {
@@ -4035,6 +4197,18 @@ where
             ExitPipelineMode::Normal => pipeline.exit(dbc),
             ExitPipelineMode::Force => pipeline.force_exit(dbc),
         }
+
+        // Terminate the fetches started by this pipeline, except for keepalive ones,
+        // which the resource threads keep running until they complete.
+        for resource_threads in &[
+            &self.public_resource_threads,
+            &self.private_resource_threads,
+        ] {
+            let msg = net_traits::CoreResourceMsg::TerminateFetchGroup(pipeline_id);
+            if let Err(e) = resource_threads.send(msg) {
+                warn!("Terminating fetch group failed ({})", e);
+            }
+        }
         debug!("Closed pipeline {:?}.", pipeline_id);
     }
 
@@ -158,6 +158,10 @@ pub enum EmbedderMsg {
     ShowIME(InputMethodType),
     /// Request to hide the IME when the editable element is blurred.
     HideIME,
+    /// Display a notification, reporting what happens to it on the given sender if any.
+    ShowNotification(Notification, Option<IpcSender<NotificationEvent>>),
+    /// Close the notification with the given id.
+    CloseNotification(String),
     /// Servo has shut down
     Shutdown,
 }
@@ -186,6 +190,8 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::SelectFiles(..) => write!(f, "SelectFiles"),
             EmbedderMsg::ShowIME(..) => write!(f, "ShowIME"),
             EmbedderMsg::HideIME => write!(f, "HideIME"),
+            EmbedderMsg::ShowNotification(..) => write!(f, "ShowNotification"),
+            EmbedderMsg::CloseNotification(..) => write!(f, "CloseNotification"),
             EmbedderMsg::Shutdown => write!(f, "Shutdown"),
             EmbedderMsg::AllowOpeningBrowser(..) => write!(f, "AllowOpeningBrowser"),
             EmbedderMsg::BrowserCreated(..) => write!(f, "BrowserCreated"),
@@ -193,6 +199,60 @@ impl Debug for EmbedderMsg {
     }
 }
 
+/// A notification to be displayed by the embedder.
+/// <https://notifications.spec.whatwg.org/#concept-notification>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Notification {
+    /// Identifies the notification in `CloseNotification` messages.
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    /// A notification replaces the displayed notification of the same origin with the
+    /// same non-empty tag.
+    pub tag: String,
+    pub origin: String,
+    pub icon: Option<ServoUrl>,
+    pub lang: String,
+    /// The direction of the text, `auto`, `ltr` or `rtl`.
+    pub dir: String,
+    pub require_interaction: bool,
+    pub silent: bool,
+}
+
+/// What happened to a notification, as reported by the embedder.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum NotificationEvent {
+    /// The notification is displayed.
+    Shown,
+    /// The user activated the notification.
+    Clicked,
+    /// The notification was dismissed by the user or the system, or replaced. This is
+    /// the last event of a notification, the sender can be dropped after it.
+    Closed,
+    /// The notification could not be displayed.
+    Error,
+}
+
+/// A position of the device, as acquired by the embedder.
+/// <https://w3c.github.io/geolocation-api/#coordinates_interface>
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct GeolocationPosition {
+    /// In decimal degrees.
+    pub latitude: f64,
+    /// In decimal degrees.
+    pub longitude: f64,
+    /// The accuracy of the latitude and longitude, in meters.
+    pub accuracy: f64,
+    /// In meters above the WGS84 ellipsoid.
+    pub altitude: Option<f64>,
+    /// In meters.
+    pub altitude_accuracy: Option<f64>,
+    /// In degrees clockwise from true north.
+    pub heading: Option<f64>,
+    /// In meters per second.
+    pub speed: Option<f64>,
+}
+
 /// Filter for file selection;
 /// the `String` content is expected to be extension (e.g, "doc", without the prefixing ".")
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -15,7 +15,9 @@ extern crate serde;
 
 pub mod print_tree;
 
+use euclid::Rect;
 use range::RangeIndex;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// A newtype struct for denoting the age of messages; prevents race conditions.
@@ -107,4 +109,18 @@ pub fn node_id_from_scroll_id(id: usize) -> Option<usize> {
 pub trait DisplayList {
     /// Returns true if this display list contains meaningful content.
     fn is_contentful(&self) -> bool;
+
+    /// Returns what this display list paints for each DOM node, keyed by the address of
+    /// the node.
+    fn painted_nodes(&self) -> HashMap<usize, PaintedNode>;
+}
+
+/// What a display list paints for a DOM node, as used by the Largest Contentful Paint
+/// and Layout Instability metrics.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PaintedNode {
+    /// The union of the bounds of the display items of the node, in CSS pixels.
+    pub bounds: Rect<f32>,
+    /// The visible area of the text and images of the node, in square CSS pixels.
+    pub contentful_area: f32,
 }
@@ -416,7 +416,11 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
             },
             Some(LayoutNodeType::Element(LayoutElementType::HTMLMediaElement)) => {
                 let data = node.media_data().unwrap();
-                SpecificFragmentInfo::Media(Box::new(MediaFragmentInfo::new(data)))
+                SpecificFragmentInfo::Media(Box::new(MediaFragmentInfo::new(
+                    data,
+                    node,
+                    &self.layout_context,
+                )))
             },
             Some(LayoutNodeType::Element(LayoutElementType::HTMLObjectElement)) => {
                 let elem = node.as_element().unwrap();
@@ -24,8 +24,8 @@ use crate::display_list::items::{TextOrientation, WebRenderImageInfo};
 use crate::display_list::ToLayout;
 use crate::flow::{BaseFlow, Flow, FlowFlags};
 use crate::flow_ref::FlowRef;
-use crate::fragment::SpecificFragmentInfo;
 use crate::fragment::{CanvasFragmentSource, CoordinateSystem, Fragment, ScannedTextFragmentInfo};
+use crate::fragment::{MediaFragmentInfo, SpecificFragmentInfo};
 use crate::inline::InlineFragmentNodeFlags;
 use crate::model::MaybeAuto;
 use crate::table_cell::CollapsedBordersForCell;
@@ -41,9 +41,11 @@ use ipc_channel::ipc;
 use msg::constellation_msg::PipelineId;
 use net_traits::image_cache::UsePlaceholder;
 use range::Range;
+use script_layout_interface::HTMLMediaCueAlign;
 use script_traits::IFrameSize;
 use servo_config::opts;
 use servo_geometry::MaxRect;
+use std::cmp::{max, min};
 use std::default::Default;
 use std::f32;
 use std::mem;
@@ -1747,7 +1749,7 @@ impl Fragment {
                         clip,
                     );
                 }
-            }
+            },
             SpecificFragmentInfo::ScannedText(ref text_fragment) => {
                 // Create the main text display item.
                 self.build_display_list_for_text_fragment(
@@ -1854,10 +1856,17 @@ impl Fragment {
                         },
                     );
                 }
+                self.build_display_list_for_media_cues(
+                    state,
+                    fragment_info,
+                    stacking_relative_content_box,
+                    clip,
+                );
             },
             SpecificFragmentInfo::Canvas(ref canvas_fragment_info) => {
                 let image_key = match canvas_fragment_info.source {
-                    CanvasFragmentSource::WebGL(image_key) => image_key,
+                    CanvasFragmentSource::WebGL(image_key) |
+                    CanvasFragmentSource::Placeholder(image_key) => image_key,
                     CanvasFragmentSource::Image(ref ipc_renderer) => match *ipc_renderer {
                         Some(ref ipc_renderer) => {
                             let ipc_renderer = ipc_renderer.lock().unwrap();
@@ -1949,6 +1958,115 @@ impl Fragment {
         )
     }
 
+    /// Creates the display items for the text track cues rendered over a video, laying
+    /// out each cue box according to its settings.
+    ///
+    /// <https://w3c.github.io/webvtt/#processing-cue-settings>
+    fn build_display_list_for_media_cues(
+        &self,
+        state: &mut DisplayListBuildState,
+        fragment_info: &MediaFragmentInfo,
+        video_box: Rect<Au>,
+        clip: Rect<Au>,
+    ) {
+        // The bottom of the area left for the cues with an automatic line
+        // position, which are stacked upwards from the bottom of the video.
+        let mut auto_bottom = video_box.max_y();
+
+        for cue_info in &fragment_info.cues {
+            let cue = &cue_info.cue;
+            let lines = cue_info
+                .lines
+                .iter()
+                .filter_map(|line| match line.specific {
+                    SpecificFragmentInfo::ScannedText(ref info) => Some((line, &**info)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            if lines.is_empty() {
+                continue;
+            }
+
+            // The horizontal position and size of the cue box.
+            let box_width = video_box.size.width.scale_by(cue.size as f32 / 100.);
+            let position = cue.position.unwrap_or(match cue.align {
+                HTMLMediaCueAlign::Start => 0.,
+                HTMLMediaCueAlign::Center => 50.,
+                HTMLMediaCueAlign::End => 100.,
+            });
+            let anchor = video_box.size.width.scale_by(position as f32 / 100.);
+            let box_left = match cue.align {
+                HTMLMediaCueAlign::Start => anchor,
+                HTMLMediaCueAlign::Center => anchor - box_width / 2,
+                HTMLMediaCueAlign::End => anchor - box_width,
+            };
+            let box_left =
+                video_box.origin.x + max(Au(0), min(box_left, video_box.size.width - box_width));
+
+            // The vertical position of the cue box.
+            let line_height = lines[0].1.content_size.block;
+            let box_height = lines
+                .iter()
+                .fold(Au(0), |height, &(_, info)| height + info.content_size.block);
+            let box_top = match cue.line {
+                None => {
+                    auto_bottom = auto_bottom - box_height;
+                    auto_bottom
+                },
+                Some(line) if cue.snap_to_lines => {
+                    if line >= 0. {
+                        video_box.origin.y + line_height.scale_by(line as f32)
+                    } else {
+                        video_box.max_y() - box_height + line_height.scale_by(line as f32 + 1.)
+                    }
+                },
+                Some(line) => {
+                    video_box.origin.y + video_box.size.height.scale_by(line as f32 / 100.)
+                },
+            };
+            let box_top = max(
+                video_box.origin.y,
+                min(box_top, video_box.max_y() - box_height),
+            );
+
+            let mut line_top = box_top;
+            for (line, info) in lines {
+                let line_width = min(info.content_size.inline, box_width);
+                let line_left = box_left +
+                    match cue.align {
+                        HTMLMediaCueAlign::Start => Au(0),
+                        HTMLMediaCueAlign::Center => (box_width - line_width) / 2,
+                        HTMLMediaCueAlign::End => box_width - line_width,
+                    };
+                let line_box = Rect::new(
+                    Point2D::new(line_left, line_top),
+                    Size2D::new(line_width, info.content_size.block),
+                );
+                line_top = line_top + info.content_size.block;
+
+                let background_color = line
+                    .style
+                    .resolve_color(line.style.get_background().background_color);
+                if background_color.alpha != 0 {
+                    let base = state.create_base_display_item(
+                        line_box,
+                        clip,
+                        self.node,
+                        get_cursor(&line.style, Cursor::Default),
+                        DisplayListSection::Content,
+                    );
+                    state.add_display_item(DisplayItem::Rectangle(CommonDisplayItem::new(
+                        base,
+                        webrender_api::RectangleDisplayItem {
+                            color: background_color.to_layout(),
+                        },
+                    )));
+                }
+                line.build_display_list_for_text_fragment(state, info, line_box, &[], clip);
+            }
+        }
+    }
+
     /// Creates the text display item for one text fragment. This can be called multiple times for
     /// one fragment if there are text shadows.
     ///
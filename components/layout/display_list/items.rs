@@ -14,7 +14,7 @@
 
 use euclid::{SideOffsets2D, TypedRect, Vector2D};
 use gfx_traits::print_tree::PrintTree;
-use gfx_traits::{self, StackingContextId};
+use gfx_traits::{self, PaintedNode, StackingContextId};
 use msg::constellation_msg::PipelineId;
 use net_traits::image::base::Image;
 use servo_geometry::MaxRect;
@@ -152,6 +152,52 @@ impl gfx_traits::DisplayList for DisplayList {
 
         false
     }
+
+    /// Collect the area painted for each DOM node. Only items that paint something are taken
+    /// into account, and only text and images count towards the contentful area. Used by the
+    /// Largest Contentful Paint and Layout Instability metrics.
+    fn painted_nodes(&self) -> HashMap<usize, PaintedNode> {
+        let mut painted_nodes: HashMap<usize, PaintedNode> = HashMap::new();
+        for item in &self.list {
+            let contentful = match *item {
+                DisplayItem::Text(_) | DisplayItem::Image(_) => true,
+                DisplayItem::Rectangle(_) |
+                DisplayItem::Border(_) |
+                DisplayItem::Gradient(_) |
+                DisplayItem::RadialGradient(_) |
+                DisplayItem::Line(_) |
+                DisplayItem::BoxShadow(_) |
+                DisplayItem::Iframe(_) => false,
+                _ => continue,
+            };
+
+            let base = item.base();
+            let bounds = base.bounds.to_untyped();
+            if bounds.is_empty_or_negative() {
+                continue;
+            }
+            let contentful_area = if contentful {
+                base.clip_rect
+                    .to_untyped()
+                    .intersection(&bounds)
+                    .map_or(0., |visible| visible.size.width * visible.size.height)
+            } else {
+                0.
+            };
+
+            painted_nodes
+                .entry(base.metadata.node.0)
+                .and_modify(|painted_node| {
+                    painted_node.bounds = painted_node.bounds.union(&bounds);
+                    painted_node.contentful_area += contentful_area;
+                })
+                .or_insert(PaintedNode {
+                    bounds,
+                    contentful_area,
+                });
+        }
+        painted_nodes
+    }
 }
 
 /// Display list sections that make up a stacking context. Each section  here refers
@@ -34,7 +34,8 @@ use range::*;
 use script_layout_interface::wrapper_traits::{
     PseudoElementType, ThreadSafeLayoutElement, ThreadSafeLayoutNode,
 };
-use script_layout_interface::{HTMLCanvasData, HTMLCanvasDataSource, HTMLMediaData, SVGSVGData};
+use script_layout_interface::SVGSVGData;
+use script_layout_interface::{HTMLCanvasData, HTMLCanvasDataSource, HTMLMediaCue, HTMLMediaData};
 use serde::ser::{Serialize, SerializeStruct, Serializer};
 use servo_url::ServoUrl;
 use std::borrow::ToOwned;
@@ -338,6 +339,7 @@ impl InlineAbsoluteFragmentInfo {
 pub enum CanvasFragmentSource {
     WebGL(webrender_api::ImageKey),
     Image(Option<Arc<Mutex<IpcSender<CanvasMsg>>>>),
+    Placeholder(webrender_api::ImageKey),
 }
 
 #[derive(Clone)]
@@ -355,6 +357,9 @@ impl CanvasFragmentInfo {
             HTMLCanvasDataSource::Image(ipc_sender) => CanvasFragmentSource::Image(
                 ipc_sender.map(|renderer| Arc::new(Mutex::new(renderer))),
             ),
+            HTMLCanvasDataSource::Placeholder(image_key) => {
+                CanvasFragmentSource::Placeholder(image_key)
+            },
         };
 
         CanvasFragmentInfo {
@@ -369,16 +374,77 @@ impl CanvasFragmentInfo {
 #[derive(Clone)]
 pub struct MediaFragmentInfo {
     pub current_frame: Option<(webrender_api::ImageKey, i32, i32)>,
+    /// The text track cues to render over the video.
+    pub cues: Vec<MediaCueInfo>,
 }
 
 impl MediaFragmentInfo {
-    pub fn new(data: HTMLMediaData) -> MediaFragmentInfo {
+    pub fn new<N: ThreadSafeLayoutNode>(
+        data: HTMLMediaData,
+        node: &N,
+        layout_context: &LayoutContext,
+    ) -> MediaFragmentInfo {
+        let cues = if data.cues.is_empty() {
+            vec![]
+        } else {
+            let cue_style = node
+                .as_element()
+                .unwrap()
+                .cue_style(layout_context.shared_context());
+            data.cues
+                .into_iter()
+                .map(|cue| MediaCueInfo::new(cue, node.opaque(), &cue_style, layout_context))
+                .collect()
+        };
         MediaFragmentInfo {
             current_frame: data.current_frame,
+            cues,
         }
     }
 }
 
+/// A text track cue rendered over a video, styled by its `::cue` pseudo-element.
+#[derive(Clone)]
+pub struct MediaCueInfo {
+    pub cue: HTMLMediaCue,
+    /// A scanned text fragment for each line of the cue text.
+    pub lines: Vec<Fragment>,
+}
+
+impl MediaCueInfo {
+    fn new(
+        cue: HTMLMediaCue,
+        node: OpaqueNode,
+        style: &ServoArc<ComputedValues>,
+        layout_context: &LayoutContext,
+    ) -> MediaCueInfo {
+        let lines =
+            cue.text
+                .split('\n')
+                .filter_map(|line| {
+                    let mut fragments = LinkedList::new();
+                    fragments.push_back(Fragment::from_opaque_node_and_style(
+                        node,
+                        PseudoElementType::Normal,
+                        style.clone(),
+                        style.clone(),
+                        RestyleDamage::rebuild_and_reflow(),
+                        SpecificFragmentInfo::UnscannedText(Box::new(
+                            UnscannedTextFragmentInfo::new(line.to_owned().into_boxed_str(), None),
+                        )),
+                    ));
+                    with_thread_local_font_context(layout_context, |font_context| {
+                        TextRunScanner::new().scan_for_runs(font_context, fragments)
+                    })
+                    .fragments
+                    .into_iter()
+                    .next()
+                })
+                .collect();
+        MediaCueInfo { cue, lines }
+    }
+}
+
 #[derive(Clone)]
 pub struct SvgFragmentInfo {
     pub dom_width: Au,
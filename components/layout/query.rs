@@ -800,6 +800,7 @@ where
     let layout_el = match *pseudo {
         Some(PseudoElement::Before) => layout_el.get_before_pseudo(),
         Some(PseudoElement::After) => layout_el.get_after_pseudo(),
+        Some(PseudoElement::Cue) |
         Some(PseudoElement::DetailsSummary) |
         Some(PseudoElement::DetailsContent) |
         Some(PseudoElement::Selection) => None,
@@ -915,7 +916,7 @@ where
                 .result
                 .map(|r| r.to_css_string())
                 .unwrap_or(String::new())
-        }
+        },
 
         LonghandId::Bottom | LonghandId::Top | LonghandId::Right | LonghandId::Left
             if applies && positioned && style.get_box().display != Display::None =>
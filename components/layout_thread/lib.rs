@@ -629,6 +629,7 @@ impl LayoutThread {
             },
             Msg::RegisterPaint(..) => LayoutHangAnnotation::RegisterPaint,
             Msg::SetNavigationStart(..) => LayoutHangAnnotation::SetNavigationStart,
+            Msg::SetObservedPaintMetrics(..) => LayoutHangAnnotation::SetObservedPaintMetrics,
             Msg::GetRunningAnimations(..) => LayoutHangAnnotation::GetRunningAnimations,
         };
         self.background_hang_monitor
@@ -802,6 +803,10 @@ impl LayoutThread {
             Msg::SetNavigationStart(time) => {
                 self.paint_time_metrics.set_navigation_start(time);
             },
+            Msg::SetObservedPaintMetrics(largest_contentful_paint, layout_shift) => {
+                self.paint_time_metrics
+                    .set_observed_metrics(largest_contentful_paint, layout_shift);
+            },
             Msg::GetRunningAnimations(sender) => {
                 let _ = sender.send(self.running_animations.read().len());
             },
@@ -1149,8 +1154,12 @@ impl LayoutThread {
                 // Observe notifications about rendered frames if needed right before
                 // sending the display list to WebRender in order to set time related
                 // Progressive Web Metrics.
-                self.paint_time_metrics
-                    .maybe_observe_paint_time(self, epoch, &*display_list);
+                self.paint_time_metrics.maybe_observe_paint_time(
+                    self,
+                    epoch,
+                    &*display_list,
+                    viewport_size.to_untyped(),
+                );
 
                 let mut txn = webrender_api::Transaction::new();
                 txn.set_display_list(
@@ -7,11 +7,13 @@ extern crate log;
 #[macro_use]
 extern crate malloc_size_of_derive;
 
-use gfx_traits::{DisplayList, Epoch};
+use euclid::{Point2D, Rect, Size2D};
+use gfx_traits::{DisplayList, Epoch, PaintedNode};
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
 use profile_traits::time::TimerMetadata;
 use profile_traits::time::{send_profile_data, ProfilerCategory, ProfilerChan};
+use script_traits::UntrustedNodeAddress;
 use script_traits::{ConstellationControlMsg, LayoutMsg, ProgressiveWebMetricType};
 use servo_config::opts;
 use servo_url::ServoUrl;
@@ -249,12 +251,37 @@ impl ProgressiveWebMetric for InteractiveMetrics {
     }
 }
 
+/// The information gathered about a display list until the frame that contains it
+/// is painted.
+struct PendingPaintMetric {
+    profiler_metadata: Option<TimerMetadata>,
+    is_contentful: bool,
+    /// The address of the node that became the largest contentful node with this display
+    /// list and its contentful area.
+    largest_contentful_paint: Option<(usize, f32)>,
+    /// The layout shift score of this display list, if any of its nodes moved.
+    layout_shift: Option<f64>,
+}
+
 // https://w3c.github.io/paint-timing/
+// https://wicg.github.io/largest-contentful-paint/
+// https://wicg.github.io/layout-instability/
 pub struct PaintTimeMetrics {
-    pending_metrics: RefCell<HashMap<Epoch, (Option<TimerMetadata>, bool)>>,
+    pending_metrics: RefCell<HashMap<Epoch, PendingPaintMetric>>,
     navigation_start: Option<u64>,
     first_paint: Cell<Option<u64>>,
     first_contentful_paint: Cell<Option<u64>>,
+    largest_contentful_paint: Cell<Option<u64>>,
+    /// The contentful area of the largest node observed so far.
+    largest_contentful_size: Cell<f32>,
+    /// The bounds of the nodes painted by the last observed display list.
+    previous_node_bounds: RefCell<HashMap<usize, Rect<f32>>>,
+    cumulative_layout_shift: Cell<f64>,
+    /// Whether script observes largest contentful paint entries. It stops doing so
+    /// after the first input.
+    observe_largest_contentful_paint: Cell<bool>,
+    /// Whether script observes layout shift entries.
+    observe_layout_shift: Cell<bool>,
     pipeline_id: PipelineId,
     time_profiler_chan: ProfilerChan,
     constellation_chan: IpcSender<LayoutMsg>,
@@ -262,6 +289,50 @@ pub struct PaintTimeMetrics {
     url: ServoUrl,
 }
 
+/// Computes the layout shift score of the nodes that moved between two layouts. The
+/// score is the product of the impact fraction, the part of the viewport covered by the
+/// moved nodes before and after the move, and the distance fraction, the largest
+/// distance any node moved relative to the largest dimension of the viewport.
+///
+/// The impact region is approximated by summing the areas of the unions of the old and
+/// new bounds of each moved node instead of computing the area of their union.
+pub fn layout_shift_score(
+    previous_node_bounds: &HashMap<usize, Rect<f32>>,
+    painted_nodes: &HashMap<usize, PaintedNode>,
+    viewport_size: Size2D<f32>,
+) -> f64 {
+    let viewport = Rect::new(Point2D::zero(), viewport_size);
+    let viewport_area = viewport_size.width * viewport_size.height;
+    let max_dimension = viewport_size.width.max(viewport_size.height);
+    if viewport_area <= 0. {
+        return 0.;
+    }
+
+    let mut impact_area = 0.;
+    let mut max_distance: f32 = 0.;
+    for (node, painted_node) in painted_nodes {
+        let previous_bounds = match previous_node_bounds.get(node) {
+            Some(bounds) => bounds,
+            None => continue,
+        };
+        let new_bounds = &painted_node.bounds;
+        if previous_bounds.origin == new_bounds.origin {
+            continue;
+        }
+        let impact_region = match previous_bounds.union(new_bounds).intersection(&viewport) {
+            Some(region) => region,
+            None => continue,
+        };
+        impact_area += impact_region.size.width * impact_region.size.height;
+        let distance = new_bounds.origin - previous_bounds.origin;
+        max_distance = max_distance.max(distance.x.abs().max(distance.y.abs()));
+    }
+
+    let impact_fraction = (impact_area / viewport_area).min(1.);
+    let distance_fraction = (max_distance / max_dimension).min(1.);
+    (impact_fraction * distance_fraction) as f64
+}
+
 impl PaintTimeMetrics {
     pub fn new(
         pipeline_id: PipelineId,
@@ -275,6 +346,12 @@ impl PaintTimeMetrics {
             navigation_start: None,
             first_paint: Cell::new(None),
             first_contentful_paint: Cell::new(None),
+            largest_contentful_paint: Cell::new(None),
+            largest_contentful_size: Cell::new(0.),
+            previous_node_bounds: RefCell::new(HashMap::new()),
+            cumulative_layout_shift: Cell::new(0.),
+            observe_largest_contentful_paint: Cell::new(false),
+            observe_layout_shift: Cell::new(false),
             pipeline_id,
             time_profiler_chan,
             constellation_chan,
@@ -302,25 +379,86 @@ impl PaintTimeMetrics {
         );
     }
 
+    /// Sets which of the metrics computed from the painted nodes of the display lists
+    /// are observed by script. The painted nodes are only gathered when one of them is.
+    pub fn set_observed_metrics(&self, largest_contentful_paint: bool, layout_shift: bool) {
+        self.observe_largest_contentful_paint
+            .set(largest_contentful_paint);
+        self.observe_layout_shift.set(layout_shift);
+        if !layout_shift {
+            // Shifts are only computed between display lists observed in a row.
+            self.previous_node_bounds.borrow_mut().clear();
+        }
+    }
+
     pub fn maybe_observe_paint_time<T>(
         &self,
         profiler_metadata_factory: &T,
         epoch: Epoch,
         display_list: &dyn DisplayList,
+        viewport_size: Size2D<f32>,
     ) where
         T: ProfilerMetadataFactory,
     {
-        if self.first_paint.get().is_some() && self.first_contentful_paint.get().is_some() {
-            // If we already set all paint metrics, we just bail out.
+        let observe_largest_contentful_paint = self.observe_largest_contentful_paint.get();
+        let observe_layout_shift = self.observe_layout_shift.get();
+        let painted_nodes = if observe_largest_contentful_paint || observe_layout_shift {
+            display_list.painted_nodes()
+        } else {
+            HashMap::new()
+        };
+
+        // A new largest contentful paint candidate is the node with the largest
+        // contentful area, if it is larger than any node we have seen before.
+        let largest_contentful_paint = if observe_largest_contentful_paint {
+            painted_nodes
+                .iter()
+                .map(|(node, painted_node)| (*node, painted_node.contentful_area))
+                .filter(|&(_, size)| size > self.largest_contentful_size.get())
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+        } else {
+            None
+        };
+        if let Some((_, size)) = largest_contentful_paint {
+            self.largest_contentful_size.set(size);
+        }
+
+        let layout_shift = if observe_layout_shift {
+            let score = layout_shift_score(
+                &self.previous_node_bounds.borrow(),
+                &painted_nodes,
+                viewport_size,
+            );
+            *self.previous_node_bounds.borrow_mut() = painted_nodes
+                .into_iter()
+                .map(|(node, painted_node)| (node, painted_node.bounds))
+                .collect();
+            if score > 0. {
+                Some(score)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        if self.first_paint.get().is_some() &&
+            self.first_contentful_paint.get().is_some() &&
+            largest_contentful_paint.is_none() &&
+            layout_shift.is_none()
+        {
+            // If there is nothing new to report about this display list, we just bail out.
             return;
         }
 
         self.pending_metrics.borrow_mut().insert(
             epoch,
-            (
-                profiler_metadata_factory.new_metadata(),
-                display_list.is_contentful(),
-            ),
+            PendingPaintMetric {
+                profiler_metadata: profiler_metadata_factory.new_metadata(),
+                is_contentful: display_list.is_contentful(),
+                largest_contentful_paint,
+                layout_shift,
+            },
         );
 
         // Send the pending metric information to the compositor thread.
@@ -333,35 +471,97 @@ impl PaintTimeMetrics {
     }
 
     pub fn maybe_set_metric(&self, epoch: Epoch, paint_time: u64) {
-        if self.first_paint.get().is_some() && self.first_contentful_paint.get().is_some() ||
-            self.navigation_start.is_none()
-        {
-            // If we already set all paint metrics or we have not set navigation start yet,
-            // we just bail out.
-            return;
-        }
+        let navigation_start = match self.navigation_start {
+            Some(time) => time,
+            // If we have not set navigation start yet, we just bail out.
+            None => return,
+        };
+
+        let pending_metric = match self.pending_metrics.borrow_mut().remove(&epoch) {
+            Some(pending_metric) => pending_metric,
+            None => return,
+        };
 
-        if let Some(pending_metric) = self.pending_metrics.borrow_mut().remove(&epoch) {
-            let profiler_metadata = pending_metric.0;
+        if self.first_paint.get().is_none() {
             set_metric(
                 self,
-                profiler_metadata.clone(),
+                pending_metric.profiler_metadata.clone(),
                 ProgressiveWebMetricType::FirstPaint,
                 ProfilerCategory::TimeToFirstPaint,
                 &self.first_paint,
                 Some(paint_time),
                 &self.url,
             );
+        }
 
-            if pending_metric.1 {
-                set_metric(
-                    self,
-                    profiler_metadata,
-                    ProgressiveWebMetricType::FirstContentfulPaint,
-                    ProfilerCategory::TimeToFirstContentfulPaint,
-                    &self.first_contentful_paint,
-                    Some(paint_time),
-                    &self.url,
+        if pending_metric.is_contentful && self.first_contentful_paint.get().is_none() {
+            set_metric(
+                self,
+                pending_metric.profiler_metadata.clone(),
+                ProgressiveWebMetricType::FirstContentfulPaint,
+                ProfilerCategory::TimeToFirstContentfulPaint,
+                &self.first_contentful_paint,
+                Some(paint_time),
+                &self.url,
+            );
+        }
+
+        let time = paint_time - navigation_start;
+
+        // Candidates painted after script stopped observing them are dropped.
+        let largest_contentful_paint = pending_metric
+            .largest_contentful_paint
+            .filter(|_| self.observe_largest_contentful_paint.get());
+        if let Some((node, size)) = largest_contentful_paint {
+            self.largest_contentful_paint.set(Some(time));
+            let msg = ConstellationControlMsg::LargestContentfulPaint(
+                self.pipeline_id,
+                time,
+                size as u64,
+                UntrustedNodeAddress::from_id(node),
+            );
+            if let Err(e) = self.script_chan.send(msg) {
+                warn!(
+                    "Sending largest contentful paint to script thread failed ({}).",
+                    e
+                );
+            }
+            send_profile_data(
+                ProfilerCategory::TimeToLargestContentfulPaint,
+                pending_metric.profiler_metadata.clone(),
+                &self.time_profiler_chan,
+                time,
+                time,
+                0,
+                0,
+            );
+            if opts::get().print_pwm {
+                println!("{:?} LargestContentfulPaint {:?}", self.url, time.to_ms());
+            }
+        }
+
+        if let Some(score) = pending_metric.layout_shift {
+            self.cumulative_layout_shift
+                .set(self.cumulative_layout_shift.get() + score);
+            let msg = ConstellationControlMsg::LayoutShift(self.pipeline_id, score, time);
+            if let Err(e) = self.script_chan.send(msg) {
+                warn!("Sending layout shift to script thread failed ({}).", e);
+            }
+            send_profile_data(
+                ProfilerCategory::LayoutShift,
+                pending_metric.profiler_metadata,
+                &self.time_profiler_chan,
+                time,
+                time,
+                0,
+                0,
+            );
+            if opts::get().print_pwm {
+                println!(
+                    "{:?} LayoutShift {:?} (cumulative {:?})",
+                    self.url,
+                    score,
+                    self.cumulative_layout_shift.get()
                 );
             }
         }
@@ -374,6 +574,14 @@ impl PaintTimeMetrics {
     pub fn get_first_contentful_paint(&self) -> Option<u64> {
         self.first_contentful_paint.get()
     }
+
+    pub fn get_largest_contentful_paint(&self) -> Option<u64> {
+        self.largest_contentful_paint.get()
+    }
+
+    pub fn get_cumulative_layout_shift(&self) -> f64 {
+        self.cumulative_layout_shift.get()
+    }
 }
 
 impl ProgressiveWebMetric for PaintTimeMetrics {
@@ -310,6 +310,7 @@ pub enum LayoutHangAnnotation {
     UpdateScrollStateFromScript,
     RegisterPaint,
     SetNavigationStart,
+    SetObservedPaintMetrics,
     GetRunningAnimations,
 }
 
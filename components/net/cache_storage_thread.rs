@@ -0,0 +1,466 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The [cache storage](https://w3c.github.io/ServiceWorker/#cache-storage) of every
+//! origin, persisted in one directory per origin under the config directory. The
+//! directory holds the list of caches of the origin, and one file per cache so that
+//! changing a cache doesn't rewrite the others.
+
+use crate::resource_thread;
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use net_traits::cache_storage_thread::{header_values, CacheId, CacheQueryOptions};
+use net_traits::cache_storage_thread::{CacheStorageThreadMsg, CachedRequest, CachedResponse};
+use servo_url::ImmutableOrigin;
+use std::borrow::ToOwned;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+
+pub trait CacheStorageThreadFactory {
+    fn new(config_dir: Option<PathBuf>) -> Self;
+}
+
+impl CacheStorageThreadFactory for IpcSender<CacheStorageThreadMsg> {
+    /// Create a cache storage thread
+    fn new(config_dir: Option<PathBuf>) -> IpcSender<CacheStorageThreadMsg> {
+        let (chan, port) = ipc::channel().unwrap();
+        thread::Builder::new()
+            .name("CacheStorageManager".to_owned())
+            .spawn(move || {
+                CacheStorageManager::new(port, config_dir).start();
+            })
+            .expect("Thread spawning failed");
+        chan
+    }
+}
+
+/// A [request response list](https://w3c.github.io/ServiceWorker/#request-response-list).
+pub type RequestResponseList = Vec<(CachedRequest, CachedResponse)>;
+
+/// The caches of an origin, without their entries.
+#[derive(Default, Deserialize, Serialize)]
+struct CacheIndex {
+    /// The name to cache map of the origin, in the order the caches were created.
+    names: Vec<(String, CacheId)>,
+    next_cache_id: CacheId,
+}
+
+#[derive(Default)]
+struct OriginCaches {
+    index: CacheIndex,
+    caches: HashMap<CacheId, RequestResponseList>,
+}
+
+impl OriginCaches {
+    fn id(&self, name: &str) -> Option<CacheId> {
+        self.index
+            .names
+            .iter()
+            .find(|&&(ref cache_name, _)| cache_name == name)
+            .map(|&(_, id)| id)
+    }
+
+    fn cache(&self, id: CacheId) -> &[(CachedRequest, CachedResponse)] {
+        self.caches.get(&id).map_or(&[], |entries| &entries[..])
+    }
+}
+
+struct CacheStorageManager {
+    port: IpcReceiver<CacheStorageThreadMsg>,
+    origins: HashMap<String, OriginCaches>,
+    config_dir: Option<PathBuf>,
+}
+
+impl CacheStorageManager {
+    fn new(
+        port: IpcReceiver<CacheStorageThreadMsg>,
+        config_dir: Option<PathBuf>,
+    ) -> CacheStorageManager {
+        CacheStorageManager {
+            port: port,
+            origins: HashMap::new(),
+            config_dir: config_dir.map(|config_dir| config_dir.join("cache_storage")),
+        }
+    }
+}
+
+impl CacheStorageManager {
+    fn start(&mut self) {
+        loop {
+            match self.port.recv().unwrap() {
+                CacheStorageThreadMsg::Has(sender, origin, name) => {
+                    let _ = sender.send(self.origin_caches(&origin).id(&name).is_some());
+                },
+                CacheStorageThreadMsg::Open(sender, origin, name) => {
+                    let _ = sender.send(self.open(&origin, name));
+                },
+                CacheStorageThreadMsg::Delete(sender, origin, name) => {
+                    let _ = sender.send(self.delete(&origin, &name));
+                },
+                CacheStorageThreadMsg::Keys(sender, origin) => {
+                    let names = self
+                        .origin_caches(&origin)
+                        .index
+                        .names
+                        .iter()
+                        .map(|&(ref name, _)| name.clone())
+                        .collect();
+                    let _ = sender.send(names);
+                },
+                CacheStorageThreadMsg::MatchAcrossCaches(
+                    sender,
+                    origin,
+                    name,
+                    request,
+                    options,
+                ) => {
+                    let _ = sender.send(self.match_across_caches(&origin, name, &request, options));
+                },
+                CacheStorageThreadMsg::MatchEntries(sender, origin, id, request, options) => {
+                    let cache = self.origin_caches(&origin).cache(id);
+                    let _ = sender.send(query_cache(request.as_ref(), options, cache));
+                },
+                CacheStorageThreadMsg::PutEntries(sender, origin, id, entries) => {
+                    let _ = sender.send(self.put_entries(&origin, id, entries));
+                },
+                CacheStorageThreadMsg::DeleteEntries(sender, origin, id, request, options) => {
+                    let _ = sender.send(self.delete_entries(&origin, id, &request, options));
+                },
+                CacheStorageThreadMsg::Exit(sender) => {
+                    // Nothing to do since we save the caches eagerly.
+                    let _ = sender.send(());
+                    break;
+                },
+            }
+        }
+    }
+
+    /// The directory holding the caches of `origin`, if they are persisted.
+    fn origin_dir(&self, origin: &ImmutableOrigin) -> Option<PathBuf> {
+        self.config_dir
+            .as_ref()
+            .map(|config_dir| config_dir.join(dir_name_for_origin(origin)))
+    }
+
+    /// The caches of `origin`, read from disk the first time they are needed.
+    fn origin_caches(&mut self, origin: &ImmutableOrigin) -> &mut OriginCaches {
+        let origin_dir = self.origin_dir(origin);
+        self.origins
+            .entry(origin.ascii_serialization())
+            .or_insert_with(|| {
+                let mut caches = OriginCaches::default();
+                let origin_dir = match origin_dir {
+                    Some(ref origin_dir) if origin_dir.join(INDEX_FILE_NAME).exists() => origin_dir,
+                    _ => return caches,
+                };
+                resource_thread::read_json_from_file(
+                    &mut caches.index,
+                    origin_dir,
+                    INDEX_FILE_NAME,
+                );
+                for &(_, id) in &caches.index.names {
+                    let mut entries = vec![];
+                    let filename = file_name_for_cache(id);
+                    if origin_dir.join(&filename).exists() {
+                        resource_thread::read_json_from_file(&mut entries, origin_dir, &filename);
+                    }
+                    caches.caches.insert(id, entries);
+                }
+                caches
+            })
+    }
+
+    /// Writes the list of caches of `origin` to disk.
+    fn save_index(&self, origin: &ImmutableOrigin) {
+        let origin_dir = match self.origin_dir(origin) {
+            Some(origin_dir) => origin_dir,
+            None => return,
+        };
+        let caches = match self.origins.get(&origin.ascii_serialization()) {
+            Some(caches) => caches,
+            None => return,
+        };
+        if let Err(e) = fs::create_dir_all(&origin_dir) {
+            return warn!("Couldn't create {}: {}", origin_dir.display(), e);
+        }
+        resource_thread::write_json_to_file(&caches.index, &origin_dir, INDEX_FILE_NAME);
+    }
+
+    /// Writes the entries of the cache `id` of `origin` to disk, or removes them
+    /// if the cache was deleted.
+    fn save_cache(&self, origin: &ImmutableOrigin, id: CacheId) {
+        let origin_dir = match self.origin_dir(origin) {
+            Some(origin_dir) => origin_dir,
+            None => return,
+        };
+        let path = origin_dir.join(file_name_for_cache(id));
+        let entries = match self
+            .origins
+            .get(&origin.ascii_serialization())
+            .and_then(|caches| caches.caches.get(&id))
+        {
+            Some(entries) => entries,
+            None => {
+                if path.exists() {
+                    if let Err(e) = fs::remove_file(&path) {
+                        warn!("Couldn't remove {}: {}", path.display(), e);
+                    }
+                }
+                return;
+            },
+        };
+        if let Err(e) = fs::create_dir_all(&origin_dir) {
+            return warn!("Couldn't create {}: {}", origin_dir.display(), e);
+        }
+        resource_thread::write_json_to_file(entries, &origin_dir, &file_name_for_cache(id));
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#cache-storage-open>
+    fn open(&mut self, origin: &ImmutableOrigin, name: String) -> CacheId {
+        let id = {
+            let caches = self.origin_caches(origin);
+            if let Some(id) = caches.id(&name) {
+                return id;
+            }
+            let id = caches.index.next_cache_id;
+            caches.index.next_cache_id += 1;
+            caches.index.names.push((name, id));
+            caches.caches.insert(id, vec![]);
+            id
+        };
+        self.save_index(origin);
+        id
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#cache-storage-delete>
+    fn delete(&mut self, origin: &ImmutableOrigin, name: &str) -> bool {
+        let id = {
+            let caches = self.origin_caches(origin);
+            let id = match caches.id(name) {
+                Some(id) => id,
+                None => return false,
+            };
+            caches.index.names.retain(|&(_, cache_id)| cache_id != id);
+            caches.caches.remove(&id);
+            id
+        };
+        self.save_index(origin);
+        self.save_cache(origin, id);
+        true
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#cache-storage-match>
+    fn match_across_caches(
+        &mut self,
+        origin: &ImmutableOrigin,
+        name: Option<String>,
+        request: &CachedRequest,
+        options: CacheQueryOptions,
+    ) -> Option<CachedResponse> {
+        let caches = self.origin_caches(origin);
+        let ids = match name {
+            Some(name) => caches.id(&name).into_iter().collect(),
+            None => caches
+                .index
+                .names
+                .iter()
+                .map(|&(_, id)| id)
+                .collect::<Vec<_>>(),
+        };
+        ids.into_iter()
+            .filter_map(|id| {
+                query_cache(Some(request), options, caches.cache(id))
+                    .into_iter()
+                    .next()
+            })
+            .map(|(_, response)| response)
+            .next()
+    }
+
+    /// The put operations of <https://w3c.github.io/ServiceWorker/#batch-cache-operations>.
+    fn put_entries(
+        &mut self,
+        origin: &ImmutableOrigin,
+        id: CacheId,
+        entries: RequestResponseList,
+    ) -> Result<(), ()> {
+        for (index, &(ref request, _)) in entries.iter().enumerate() {
+            let options = CacheQueryOptions::default();
+            if !query_cache(Some(request), options, &entries[..index]).is_empty() {
+                return Err(());
+            }
+        }
+        {
+            let cache = match self.origin_caches(origin).caches.get_mut(&id) {
+                Some(cache) => cache,
+                // The cache was deleted, nobody can see what is put in it anymore.
+                None => return Ok(()),
+            };
+            for (request, response) in entries {
+                cache.retain(|&(ref cached_request, ref cached_response)| {
+                    !request_matches_cached_item(
+                        &request,
+                        cached_request,
+                        Some(cached_response),
+                        CacheQueryOptions::default(),
+                    )
+                });
+                cache.push((request, response));
+            }
+        }
+        self.save_cache(origin, id);
+        Ok(())
+    }
+
+    /// The delete operations of <https://w3c.github.io/ServiceWorker/#batch-cache-operations>.
+    fn delete_entries(
+        &mut self,
+        origin: &ImmutableOrigin,
+        id: CacheId,
+        request: &CachedRequest,
+        options: CacheQueryOptions,
+    ) -> bool {
+        {
+            let cache = match self.origin_caches(origin).caches.get_mut(&id) {
+                Some(cache) => cache,
+                None => return false,
+            };
+            let len = cache.len();
+            cache.retain(|&(ref cached_request, ref cached_response)| {
+                !request_matches_cached_item(
+                    request,
+                    cached_request,
+                    Some(cached_response),
+                    options,
+                )
+            });
+            if cache.len() == len {
+                return false;
+            }
+        }
+        self.save_cache(origin, id);
+        true
+    }
+}
+
+/// The file holding the list of caches in the directory of an origin.
+const INDEX_FILE_NAME: &'static str = "index.json";
+
+/// A directory name that is unique to `origin` and safe to use on every platform.
+fn dir_name_for_origin(origin: &ImmutableOrigin) -> String {
+    origin
+        .ascii_serialization()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn file_name_for_cache(id: CacheId) -> String {
+    format!("cache_{}.json", id)
+}
+
+/// <https://w3c.github.io/ServiceWorker/#query-cache>
+pub fn query_cache(
+    request: Option<&CachedRequest>,
+    options: CacheQueryOptions,
+    cache: &[(CachedRequest, CachedResponse)],
+) -> RequestResponseList {
+    cache
+        .iter()
+        .filter(|&&(ref cached_request, ref cached_response)| {
+            request.map_or(true, |request| {
+                request_matches_cached_item(request, cached_request, Some(cached_response), options)
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// <https://w3c.github.io/ServiceWorker/#request-matches-cached-item>
+pub fn request_matches_cached_item(
+    request: &CachedRequest,
+    cached_request: &CachedRequest,
+    cached_response: Option<&CachedResponse>,
+    options: CacheQueryOptions,
+) -> bool {
+    // Step 1.
+    if !options.ignore_method && request.method != "GET" {
+        return false;
+    }
+
+    // Step 2-5.
+    let mut query_url = request.url.clone();
+    let mut cached_url = cached_request.url.clone();
+    query_url.as_mut_url().set_fragment(None);
+    cached_url.as_mut_url().set_fragment(None);
+    if options.ignore_search {
+        query_url.as_mut_url().set_query(None);
+        cached_url.as_mut_url().set_query(None);
+    }
+
+    // Step 6.
+    if query_url != cached_url {
+        return false;
+    }
+
+    // Step 7.
+    let cached_response = match cached_response {
+        Some(cached_response) if !options.ignore_vary => cached_response,
+        _ => return true,
+    };
+    let vary = header_values(&cached_response.headers, "vary");
+
+    // Step 8-9.
+    let field_values = vary
+        .iter()
+        .flat_map(|value| value.split(|&byte| byte == b','))
+        .map(|field_value| trim_http_whitespace(field_value))
+        .filter(|field_value| !field_value.is_empty());
+    for field_value in field_values {
+        // Step 9.1.
+        if field_value == b"*" {
+            return false;
+        }
+        // Step 9.2-9.3.
+        let name = String::from_utf8_lossy(field_value);
+        if combined_value(cached_request, &name) != combined_value(request, &name) {
+            return false;
+        }
+    }
+
+    // Step 10.
+    true
+}
+
+/// The [combined value](https://fetch.spec.whatwg.org/#concept-header-list-combine)
+/// of the headers of `request` named `name`.
+fn combined_value(request: &CachedRequest, name: &str) -> Option<Vec<u8>> {
+    let values = header_values(&request.headers, name);
+    if values.is_empty() {
+        return None;
+    }
+    let mut combined = values[0].to_vec();
+    for value in &values[1..] {
+        combined.extend_from_slice(b", ");
+        combined.extend_from_slice(value);
+    }
+    Some(combined)
+}
+
+fn trim_http_whitespace(mut value: &[u8]) -> &[u8] {
+    while let Some((first, rest)) = value.split_first() {
+        if !b" \t\r\n".contains(first) {
+            break;
+        }
+        value = rest;
+    }
+    while let Some((last, rest)) = value.split_last() {
+        if !b" \t\r\n".contains(last) {
+            break;
+        }
+        value = rest;
+    }
+    value
+}
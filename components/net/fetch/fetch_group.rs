@@ -0,0 +1,84 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! An implementation of [fetch groups](https://fetch.spec.whatwg.org/#concept-fetch-group),
+//! keyed by the pipeline that started the fetches.
+
+use msg::constellation_msg::PipelineId;
+use net_traits::request::Request;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+
+/// A [fetch record](https://fetch.spec.whatwg.org/#concept-fetch-record).
+///
+/// The record stays in its fetch group for as long as the fetch thread holds on to it.
+pub struct FetchRecord {
+    /// The size of the request body, if the request has its keepalive flag set.
+    keep_alive_bytes: Option<usize>,
+    /// Whether the fetch group this record belongs to has been terminated.
+    terminated: AtomicBool,
+}
+
+impl FetchRecord {
+    pub fn is_terminated(&self) -> bool {
+        self.terminated.load(Ordering::SeqCst)
+    }
+}
+
+pub struct FetchGroups {
+    groups: HashMap<PipelineId, Vec<Weak<FetchRecord>>>,
+}
+
+impl FetchGroups {
+    pub fn new() -> FetchGroups {
+        FetchGroups {
+            groups: HashMap::new(),
+        }
+    }
+
+    /// Append a record for `request` to the fetch group of its pipeline.
+    /// Returns `None` for requests that do not belong to a pipeline.
+    pub fn add_record(&mut self, request: &Request) -> Option<Arc<FetchRecord>> {
+        let pipeline_id = request.pipeline_id?;
+        let keep_alive_bytes = if request.keep_alive {
+            Some(request.body.as_ref().map_or(0, |body| body.len()))
+        } else {
+            None
+        };
+        let record = Arc::new(FetchRecord {
+            keep_alive_bytes,
+            terminated: AtomicBool::new(false),
+        });
+        let records = self.groups.entry(pipeline_id).or_insert_with(Vec::new);
+        records.retain(|record| record.upgrade().is_some());
+        records.push(Arc::downgrade(&record));
+        Some(record)
+    }
+
+    /// The sum of the body sizes of the keepalive requests still in flight in the
+    /// fetch group of `pipeline_id`.
+    pub fn inflight_keep_alive_bytes(&self, pipeline_id: PipelineId) -> usize {
+        self.groups.get(&pipeline_id).map_or(0, |records| {
+            records
+                .iter()
+                .filter_map(Weak::upgrade)
+                .filter_map(|record| record.keep_alive_bytes)
+                .sum()
+        })
+    }
+
+    /// <https://fetch.spec.whatwg.org/#concept-fetch-group-terminate>
+    pub fn terminate(&mut self, pipeline_id: PipelineId) {
+        let records = match self.groups.remove(&pipeline_id) {
+            Some(records) => records,
+            None => return,
+        };
+        for record in records.iter().filter_map(Weak::upgrade) {
+            if record.keep_alive_bytes.is_none() {
+                record.terminated.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+}
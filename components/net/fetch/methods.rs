@@ -4,6 +4,7 @@
 
 use crate::data_loader::decode;
 use crate::fetch::cors_cache::CorsCache;
+use crate::fetch::fetch_group::FetchRecord;
 use crate::filemanager_thread::{fetch_file_in_chunks, FileManager, FILE_CHUNK_SIZE};
 use crate::http_loader::{determine_request_referrer, http_fetch, HttpState};
 use crate::http_loader::{set_default_accept, set_default_accept_language};
@@ -15,7 +16,7 @@ use headers_ext::{AccessControlExposeHeaders, ContentType, Range};
 use http::header::{self, HeaderMap, HeaderName, HeaderValue};
 use hyper::Method;
 use hyper::StatusCode;
-use ipc_channel::ipc::IpcReceiver;
+use ipc_channel::ipc::{IpcReceiver, IpcSender};
 use mime::{self, Mime};
 use mime_guess::guess_mime_type;
 use net_traits::blob_url_store::{parse_blob_url, BlobURLStoreError};
@@ -23,8 +24,8 @@ use net_traits::filemanager_thread::RelativePos;
 use net_traits::request::{CredentialsMode, Destination, Referrer, Request, RequestMode};
 use net_traits::request::{Origin, ResponseTainting, Window};
 use net_traits::response::{Response, ResponseBody, ResponseType};
-use net_traits::ResourceAttribute;
-use net_traits::{FetchTaskTarget, NetworkError, ReferrerPolicy, ResourceFetchTiming};
+use net_traits::{CustomResponseMediator, FetchTaskTarget, NetworkError};
+use net_traits::{ReferrerPolicy, ResourceAttribute, ResourceFetchTiming};
 use servo_url::ServoUrl;
 use std::borrow::Cow;
 use std::fs::File;
@@ -55,34 +56,49 @@ pub struct FetchContext {
     pub filemanager: FileManager,
     pub cancellation_listener: Arc<Mutex<CancellationListener>>,
     pub timing: Arc<Mutex<ResourceFetchTiming>>,
+    /// Where to send the fetches service workers may handle.
+    pub swmanager_chan: Option<IpcSender<CustomResponseMediator>>,
 }
 
 pub struct CancellationListener {
     cancel_chan: Option<IpcReceiver<()>>,
+    fetch_record: Option<Arc<FetchRecord>>,
     cancelled: bool,
 }
 
 impl CancellationListener {
     pub fn new(cancel_chan: Option<IpcReceiver<()>>) -> Self {
+        Self::new_with_fetch_record(cancel_chan, None)
+    }
+
+    /// A listener that also reports the fetch as cancelled once its fetch group
+    /// has been terminated.
+    pub fn new_with_fetch_record(
+        cancel_chan: Option<IpcReceiver<()>>,
+        fetch_record: Option<Arc<FetchRecord>>,
+    ) -> Self {
         Self {
             cancel_chan: cancel_chan,
+            fetch_record: fetch_record,
             cancelled: false,
         }
     }
 
     pub fn cancelled(&mut self) -> bool {
+        if self.cancelled {
+            return true;
+        }
         if let Some(ref cancel_chan) = self.cancel_chan {
-            if self.cancelled {
-                true
-            } else if cancel_chan.try_recv().is_ok() {
+            if cancel_chan.try_recv().is_ok() {
+                self.cancelled = true;
+            }
+        }
+        if let Some(ref fetch_record) = self.fetch_record {
+            if fetch_record.is_terminated() {
                 self.cancelled = true;
-                true
-            } else {
-                false
             }
-        } else {
-            false
         }
+        self.cancelled
     }
 }
 pub type DoneChannel = Option<(Sender<Data>, Receiver<Data>)>;
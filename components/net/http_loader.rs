@@ -7,6 +7,7 @@ use crate::cookie;
 use crate::cookie_storage::CookieStorage;
 use crate::decoder::Decoder;
 use crate::fetch::cors_cache::CorsCache;
+use crate::fetch::fetch_group::FetchGroups;
 use crate::fetch::methods::{
     is_cors_safelisted_method, is_cors_safelisted_request_header, main_fetch,
 };
@@ -35,15 +36,19 @@ use http::uri::Authority;
 use http::{HeaderMap, Request as HyperRequest};
 use hyper::{Body, Client, Method, Response as HyperResponse, StatusCode};
 use hyper_serde::Serde;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
 use msg::constellation_msg::{HistoryStateId, PipelineId};
 use net_traits::quality::{quality_to_value, Quality, QualityItem};
+use net_traits::request::KEEPALIVE_INFLIGHT_QUOTA;
 use net_traits::request::{CacheMode, CredentialsMode, Destination, Origin};
 use net_traits::request::{RedirectMode, Referrer, Request, RequestMode};
 use net_traits::request::{ResponseTainting, ServiceWorkersMode};
 use net_traits::response::{HttpsState, Response, ResponseBody, ResponseType};
-use net_traits::{CookieSource, FetchMetadata, NetworkError, ReferrerPolicy};
-use net_traits::{RedirectStartValue, ResourceAttribute};
+use net_traits::{CookieSource, CustomResponseMediator, FetchMetadata, NetworkError};
+use net_traits::{RedirectStartValue, ReferrerPolicy, ResourceAttribute, ResourceFetchTiming};
 use openssl::ssl::SslConnectorBuilder;
+use servo_config::prefs::PREFS;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
@@ -51,6 +56,7 @@ use std::iter::FromIterator;
 use std::mem;
 use std::ops::Deref;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 use std::sync::RwLock;
 use std::time::{Duration, SystemTime};
@@ -68,6 +74,9 @@ pub struct HttpState {
     pub http_cache: RwLock<HttpCache>,
     pub auth_cache: RwLock<AuthCache>,
     pub history_states: RwLock<HashMap<HistoryStateId, Vec<u8>>>,
+    pub fetch_groups: RwLock<FetchGroups>,
+    /// Whether the embedder has put us in offline mode, where every network fetch fails.
+    pub offline: AtomicBool,
     pub client: Client<Connector, Body>,
 }
 
@@ -79,6 +88,8 @@ impl HttpState {
             auth_cache: RwLock::new(AuthCache::new()),
             history_states: RwLock::new(HashMap::new()),
             http_cache: RwLock::new(HttpCache::new()),
+            fetch_groups: RwLock::new(FetchGroups::new()),
+            offline: AtomicBool::new(PREFS.is_network_offline()),
             client: create_http_client(ssl_connector_builder, HANDLE.lock().unwrap().executor()),
         }
     }
@@ -142,11 +153,21 @@ pub fn set_default_accept_language(headers: &mut HeaderMap) {
         return;
     }
 
+    // Give each language a weight proportional to its position in the user's list,
+    // e.g. "en-US, en; q=0.5" for the default languages.
+    let languages = PREFS.languages();
+    let count = languages.len();
+    let languages = languages
+        .into_iter()
+        .enumerate()
+        .map(|(i, language)| {
+            let quality = 1000 - (1000 * i / count) as u16;
+            QualityItem::new(language, Quality::from_u16(quality))
+        })
+        .collect();
+
     // TODO(eijebong): Change this once typed headers are done
-    headers.insert(
-        header::ACCEPT_LANGUAGE,
-        HeaderValue::from_static("en-US, en; q=0.5"),
-    );
+    headers.insert(header::ACCEPT_LANGUAGE, quality_to_value(languages));
 }
 
 /// <https://w3c.github.io/webappsec-referrer-policy/#referrer-policy-state-no-referrer-when-downgrade>
@@ -450,6 +471,73 @@ fn obtain_response(
     // TODO(#21263) response_end (also needs to be set above if fetch is aborted due to an error)
 }
 
+/// [Handle fetch](https://w3c.github.io/ServiceWorker/#handle-fetch), the service worker
+/// manager looks for the service worker controlling the request and returns its answer
+/// to the fetch event, if it made one.
+fn handle_fetch(request: &Request, context: &FetchContext) -> Option<Response> {
+    let swmanager_chan = context.swmanager_chan.as_ref()?;
+    if !PREFS
+        .get("dom.serviceworker.enabled")
+        .as_boolean()
+        .unwrap_or(false)
+    {
+        return None;
+    }
+
+    // Step 4-5
+    if !request.is_navigation_request() && !request.is_subresource_request() {
+        return None;
+    }
+    let url = request.current_url();
+    match url.scheme() {
+        "http" | "https" => {},
+        _ => return None,
+    }
+
+    let (response_chan, response_port) = ipc::channel().unwrap();
+    let mediator = CustomResponseMediator {
+        response_chan: response_chan,
+        load_url: url.clone(),
+        method: request.method.clone(),
+        headers: request.headers.clone(),
+        body: request.body.clone(),
+        is_navigation: request.is_navigation_request(),
+        pipeline_id: request.pipeline_id,
+    };
+    swmanager_chan.send(mediator).ok()?;
+    // Don't let a service worker that never answers hang the fetch, fall back to the
+    // network instead.
+    let timeout = PREFS
+        .get("dom.serviceworker.timeout_seconds")
+        .as_u64()
+        .unwrap_or(60);
+    let response_port = ROUTER.route_ipc_receiver_to_new_crossbeam_receiver(response_port);
+    let answer = match response_port.recv_timeout(Duration::from_secs(timeout)) {
+        Ok(answer) => answer,
+        Err(_) => {
+            warn!("No answer from the service worker for {}", url);
+            return None;
+        },
+    };
+    match answer {
+        None => None,
+        Some(Err(error)) => Some(Response::network_error(error)),
+        Some(Ok(custom_response)) => {
+            let mut response =
+                Response::new(url.clone(), ResourceFetchTiming::new(request.timing_type()));
+            response.url_list = vec![url];
+            response.headers = custom_response.headers;
+            response.raw_status = Some((
+                custom_response.raw_status.0.as_u16(),
+                custom_response.raw_status.1.clone().into_bytes(),
+            ));
+            response.status = Some(custom_response.raw_status);
+            *response.body.lock().unwrap() = ResponseBody::Done(custom_response.body);
+            Some(response)
+        },
+    }
+}
+
 /// [HTTP fetch](https://fetch.spec.whatwg.org#http-fetch)
 pub fn http_fetch(
     request: &mut Request,
@@ -471,8 +559,8 @@ pub fn http_fetch(
 
     // Step 3
     if request.service_workers_mode == ServiceWorkersMode::All {
-        // TODO: Substep 1
-        // Set response to the result of invoking handle fetch for request.
+        // Substep 1
+        response = handle_fetch(request, context);
 
         // Substep 2
         if let Some(ref res) = response {
@@ -819,8 +907,22 @@ fn http_network_or_cache_fetch(
         http_request
             .headers
             .typed_insert(ContentLength(content_length_value));
+        // Step 9
         if http_request.keep_alive {
-            // Step 9 TODO: needs request's client object
+            if let Some(pipeline_id) = http_request.pipeline_id {
+                // The fetch group already accounts for this request's body.
+                let inflight_keep_alive_bytes = context
+                    .state
+                    .fetch_groups
+                    .read()
+                    .unwrap()
+                    .inflight_keep_alive_bytes(pipeline_id);
+                if inflight_keep_alive_bytes > KEEPALIVE_INFLIGHT_QUOTA {
+                    return Response::network_error(NetworkError::Internal(
+                        "Keepalive inflight quota exceeded".into(),
+                    ));
+                }
+            }
         }
     }
 
@@ -975,6 +1077,10 @@ fn http_network_or_cache_fetch(
                         response_from_cache.needs_validation,
                     ),
                 };
+            // There is nothing to revalidate a stale response against while offline,
+            // so serve it as it is.
+            let needs_revalidation =
+                needs_revalidation && !context.state.offline.load(Ordering::SeqCst);
             if needs_revalidation {
                 revalidating_flag = true;
                 // Substep 5
@@ -1151,6 +1257,10 @@ fn http_network_fetch(
     // Step 1
     // nothing to do here, since credentials_flag is already a boolean
 
+    if context.state.offline.load(Ordering::SeqCst) {
+        return Response::network_error(NetworkError::Internal("Network is offline".into()));
+    }
+
     // Step 2
     // TODO be able to create connection using current url's origin and credentials
 
@@ -17,6 +17,7 @@ extern crate profile_traits;
 #[macro_use]
 extern crate serde;
 
+pub mod cache_storage_thread;
 pub mod connector;
 pub mod cookie;
 pub mod cookie_storage;
@@ -36,6 +37,7 @@ mod websocket_loader;
 /// An implementation of the [Fetch specification](https://fetch.spec.whatwg.org/)
 pub mod fetch {
     pub mod cors_cache;
+    pub mod fetch_group;
     pub mod methods;
 }
 
@@ -4,10 +4,12 @@
 
 //! A thread that takes a URL and streams back the binary data.
 
+use crate::cache_storage_thread::CacheStorageThreadFactory;
 use crate::connector::{create_http_client, create_ssl_connector_builder};
 use crate::cookie;
 use crate::cookie_storage::CookieStorage;
 use crate::fetch::cors_cache::CorsCache;
+use crate::fetch::fetch_group::FetchGroups;
 use crate::fetch::methods::{fetch, CancellationListener, FetchContext};
 use crate::filemanager_thread::FileManager;
 use crate::hsts::HstsList;
@@ -22,6 +24,7 @@ use embedder_traits::EmbedderProxy;
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcReceiver, IpcReceiverSet, IpcSender};
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use net_traits::cache_storage_thread::CacheStorageThreadMsg;
 use net_traits::request::{Destination, Request, RequestInit};
 use net_traits::response::{Response, ResponseInit};
 use net_traits::storage_thread::StorageThreadMsg;
@@ -35,6 +38,7 @@ use profile_traits::mem::{Report, ReportKind, ReportsChan};
 use profile_traits::time::ProfilerChan;
 use serde::{Deserialize, Serialize};
 use servo_config::opts;
+use servo_config::prefs::PREFS;
 use servo_url::ServoUrl;
 use std::borrow::{Cow, ToOwned};
 use std::collections::HashMap;
@@ -43,6 +47,7 @@ use std::fs::{self, File};
 use std::io::prelude::*;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
 
@@ -63,10 +68,12 @@ pub fn new_resource_threads(
         embedder_proxy,
         config_dir.clone(),
     );
-    let storage: IpcSender<StorageThreadMsg> = StorageThreadFactory::new(config_dir);
+    let storage: IpcSender<StorageThreadMsg> = StorageThreadFactory::new(config_dir.clone());
+    let cache_storage: IpcSender<CacheStorageThreadMsg> =
+        CacheStorageThreadFactory::new(config_dir);
     (
-        ResourceThreads::new(public_core, storage.clone()),
-        ResourceThreads::new(private_core, storage),
+        ResourceThreads::new(public_core, storage.clone(), cache_storage.clone()),
+        ResourceThreads::new(private_core, storage, cache_storage),
     )
 }
 
@@ -137,6 +144,8 @@ fn create_http_states(config_dir: Option<&Path>) -> (Arc<HttpState>, Arc<HttpSta
         http_cache: RwLock::new(http_cache),
         hsts_list: RwLock::new(hsts_list),
         history_states: RwLock::new(HashMap::new()),
+        fetch_groups: RwLock::new(FetchGroups::new()),
+        offline: AtomicBool::new(PREFS.is_network_offline()),
         client: create_http_client(ssl_connector_builder, HANDLE.lock().unwrap().executor()),
     };
 
@@ -285,6 +294,16 @@ impl ResourceChannelManager {
                     history_states.remove(&history_state);
                 }
             },
+            CoreResourceMsg::TerminateFetchGroup(pipeline_id) => {
+                http_state
+                    .fetch_groups
+                    .write()
+                    .unwrap()
+                    .terminate(pipeline_id);
+            },
+            CoreResourceMsg::SetOffline(offline) => {
+                http_state.offline.store(offline, Ordering::SeqCst);
+            },
             CoreResourceMsg::Synchronize(sender) => {
                 let _ = sender.send(());
             },
@@ -440,6 +459,7 @@ impl CoreResourceManager {
         let ua = self.user_agent.clone();
         let dc = self.devtools_chan.clone();
         let filemanager = self.filemanager.clone();
+        let swmanager_chan = self.swmanager_chan.clone();
 
         let timing_type = match req_init.destination {
             Destination::Document => ResourceTimingType::Navigation,
@@ -450,19 +470,26 @@ impl CoreResourceManager {
             .name(format!("fetch thread for {}", req_init.url))
             .spawn(move || {
                 let mut request = Request::from_init(req_init);
+                // The fetch record leaves its fetch group once this thread is done with it,
+                // so keepalive requests outlive the pipeline that started them.
+                let fetch_record = http_state
+                    .fetch_groups
+                    .write()
+                    .unwrap()
+                    .add_record(&request);
                 // XXXManishearth: Check origin against pipeline id (also ensure that the mode is allowed)
                 // todo load context / mimesniff in fetch
                 // todo referrer policy?
-                // todo service worker stuff
                 let context = FetchContext {
                     state: http_state,
                     user_agent: ua,
                     devtools_chan: dc,
                     filemanager: filemanager,
-                    cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(
-                        cancel_chan,
-                    ))),
+                    cancellation_listener: Arc::new(Mutex::new(
+                        CancellationListener::new_with_fetch_record(cancel_chan, fetch_record),
+                    )),
                     timing: Arc::new(Mutex::new(ResourceFetchTiming::new(request.timing_type()))),
+                    swmanager_chan: swmanager_chan,
                 };
 
                 match res_init_ {
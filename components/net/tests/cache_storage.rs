@@ -0,0 +1,199 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use ipc_channel::ipc::{self, IpcSender};
+use net::cache_storage_thread::{request_matches_cached_item, CacheStorageThreadFactory};
+use net_traits::cache_storage_thread::{CacheQueryOptions, CacheStorageThreadMsg};
+use net_traits::cache_storage_thread::{CachedRequest, CachedResponse};
+use net_traits::response::ResponseType;
+use servo_url::ServoUrl;
+use std::env;
+use std::fs;
+use std::process;
+
+fn cached_request(url: &str, headers: &[(&str, &str)]) -> CachedRequest {
+    CachedRequest {
+        url: ServoUrl::parse(url).unwrap(),
+        method: "GET".to_owned(),
+        headers: headers
+            .iter()
+            .map(|&(name, value)| (name.to_owned(), value.as_bytes().to_vec()))
+            .collect(),
+    }
+}
+
+fn cached_response(headers: &[(&str, &str)], body: &[u8]) -> CachedResponse {
+    CachedResponse {
+        response_type: ResponseType::Basic,
+        url_list: vec![],
+        status: 200,
+        status_text: b"OK".to_vec(),
+        headers: headers
+            .iter()
+            .map(|&(name, value)| (name.to_owned(), value.as_bytes().to_vec()))
+            .collect(),
+        body: body.to_vec(),
+    }
+}
+
+fn matches(
+    request: &CachedRequest,
+    cached: &CachedRequest,
+    response: Option<&CachedResponse>,
+    options: CacheQueryOptions,
+) -> bool {
+    request_matches_cached_item(request, cached, response, options)
+}
+
+#[test]
+fn test_request_matches_cached_item_ignores_fragment_and_optionally_search() {
+    let cached = cached_request("https://servo.org/a?b=c", &[]);
+    let options = CacheQueryOptions::default();
+
+    let request = cached_request("https://servo.org/a?b=c#fragment", &[]);
+    assert!(matches(&request, &cached, None, options));
+
+    let request = cached_request("https://servo.org/a", &[]);
+    assert!(!matches(&request, &cached, None, options));
+    let options = CacheQueryOptions {
+        ignore_search: true,
+        ..options
+    };
+    assert!(matches(&request, &cached, None, options));
+}
+
+#[test]
+fn test_request_matches_cached_item_checks_method() {
+    let cached = cached_request("https://servo.org/", &[]);
+    let mut request = cached_request("https://servo.org/", &[]);
+    request.method = "POST".to_owned();
+
+    let options = CacheQueryOptions::default();
+    assert!(!matches(&request, &cached, None, options));
+    let options = CacheQueryOptions {
+        ignore_method: true,
+        ..options
+    };
+    assert!(matches(&request, &cached, None, options));
+}
+
+#[test]
+fn test_request_matches_cached_item_honors_vary() {
+    let cached = cached_request("https://servo.org/", &[("Accept-Language", "fr")]);
+    let response = cached_response(&[("Vary", "accept-language, Accept")], b"");
+    let options = CacheQueryOptions::default();
+
+    let request = cached_request("https://servo.org/", &[("accept-language", "fr")]);
+    assert!(matches(&request, &cached, Some(&response), options));
+
+    let request = cached_request("https://servo.org/", &[("accept-language", "en")]);
+    assert!(!matches(&request, &cached, Some(&response), options));
+    let options = CacheQueryOptions {
+        ignore_vary: true,
+        ..options
+    };
+    assert!(matches(&request, &cached, Some(&response), options));
+
+    let response = cached_response(&[("Vary", "*")], b"");
+    let request = cached_request("https://servo.org/", &[("accept-language", "fr")]);
+    let options = CacheQueryOptions::default();
+    assert!(!matches(&request, &cached, Some(&response), options));
+}
+
+#[test]
+fn test_cache_storage_put_match_and_delete() {
+    let cache_storage: IpcSender<CacheStorageThreadMsg> = CacheStorageThreadFactory::new(None);
+    let origin = ServoUrl::parse("https://servo.org").unwrap().origin();
+    let request = cached_request("https://servo.org/a", &[]);
+    let response = cached_response(&[], b"a");
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    let msg = CacheStorageThreadMsg::Open(sender, origin.clone(), "v1".to_owned());
+    cache_storage.send(msg).unwrap();
+    let id = receiver.recv().unwrap();
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    let entries = vec![(request.clone(), response.clone())];
+    let msg = CacheStorageThreadMsg::PutEntries(sender, origin.clone(), id, entries);
+    cache_storage.send(msg).unwrap();
+    assert_eq!(receiver.recv().unwrap(), Ok(()));
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    let msg = CacheStorageThreadMsg::MatchAcrossCaches(
+        sender,
+        origin.clone(),
+        None,
+        request.clone(),
+        CacheQueryOptions::default(),
+    );
+    cache_storage.send(msg).unwrap();
+    assert_eq!(receiver.recv().unwrap(), Some(response));
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    let msg = CacheStorageThreadMsg::Delete(sender, origin.clone(), "v1".to_owned());
+    cache_storage.send(msg).unwrap();
+    assert!(receiver.recv().unwrap());
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    let msg = CacheStorageThreadMsg::MatchAcrossCaches(
+        sender,
+        origin.clone(),
+        None,
+        request,
+        CacheQueryOptions::default(),
+    );
+    cache_storage.send(msg).unwrap();
+    assert_eq!(receiver.recv().unwrap(), None);
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    cache_storage
+        .send(CacheStorageThreadMsg::Exit(sender))
+        .unwrap();
+    receiver.recv().unwrap();
+}
+
+#[test]
+fn test_cache_storage_persists_caches() {
+    let config_dir = env::temp_dir().join(format!("servo_cache_storage_{}", process::id()));
+    let origin = ServoUrl::parse("https://servo.org").unwrap().origin();
+    let request = cached_request("https://servo.org/a", &[]);
+    let response = cached_response(&[], b"a");
+
+    let cache_storage: IpcSender<CacheStorageThreadMsg> =
+        CacheStorageThreadFactory::new(Some(config_dir.clone()));
+    let (sender, receiver) = ipc::channel().unwrap();
+    let msg = CacheStorageThreadMsg::Open(sender, origin.clone(), "v1".to_owned());
+    cache_storage.send(msg).unwrap();
+    let id = receiver.recv().unwrap();
+    let (sender, receiver) = ipc::channel().unwrap();
+    let entries = vec![(request.clone(), response.clone())];
+    let msg = CacheStorageThreadMsg::PutEntries(sender, origin.clone(), id, entries);
+    cache_storage.send(msg).unwrap();
+    assert_eq!(receiver.recv().unwrap(), Ok(()));
+    let (sender, receiver) = ipc::channel().unwrap();
+    cache_storage
+        .send(CacheStorageThreadMsg::Exit(sender))
+        .unwrap();
+    receiver.recv().unwrap();
+
+    let cache_storage: IpcSender<CacheStorageThreadMsg> =
+        CacheStorageThreadFactory::new(Some(config_dir.clone()));
+    let (sender, receiver) = ipc::channel().unwrap();
+    cache_storage
+        .send(CacheStorageThreadMsg::Keys(sender, origin.clone()))
+        .unwrap();
+    assert_eq!(receiver.recv().unwrap(), vec!["v1".to_owned()]);
+    let (sender, receiver) = ipc::channel().unwrap();
+    let msg =
+        CacheStorageThreadMsg::MatchEntries(sender, origin, id, None, CacheQueryOptions::default());
+    cache_storage.send(msg).unwrap();
+    assert_eq!(receiver.recv().unwrap(), vec![(request, response)]);
+    let (sender, receiver) = ipc::channel().unwrap();
+    cache_storage
+        .send(CacheStorageThreadMsg::Exit(sender))
+        .unwrap();
+    receiver.recv().unwrap();
+
+    let _ = fs::remove_dir_all(config_dir);
+}
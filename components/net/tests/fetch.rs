@@ -25,20 +25,25 @@ use http::uri::Authority;
 use http::{Method, StatusCode};
 use hyper::body::Body;
 use hyper::{Request as HyperRequest, Response as HyperResponse};
+use ipc_channel::ipc;
 use mime::{self, Mime};
 use msg::constellation_msg::TEST_PIPELINE_ID;
 use net::connector::create_ssl_connector_builder;
 use net::fetch::cors_cache::CorsCache;
+use net::fetch::fetch_group::FetchGroups;
 use net::fetch::methods::{self, CancellationListener, FetchContext};
 use net::filemanager_thread::FileManager;
 use net::hsts::HstsEntry;
 use net::test::HttpState;
+use net_traits::request::KEEPALIVE_INFLIGHT_QUOTA;
 use net_traits::request::{Destination, Origin, RedirectMode, Referrer, Request, RequestMode};
 use net_traits::response::{CacheState, Response, ResponseBody, ResponseType};
+use net_traits::{CustomResponse, CustomResponseMediator};
 use net_traits::{
     FetchTaskTarget, IncludeSubdomains, NetworkError, ReferrerPolicy, ResourceFetchTiming,
     ResourceTimingType,
 };
+use servo_config::prefs::{PrefValue, PREFS};
 use servo_url::{ImmutableOrigin, ServoUrl};
 use std::fs::File;
 use std::io::Read;
@@ -46,6 +51,7 @@ use std::iter::FromIterator;
 use std::path::Path;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::{Duration, SystemTime};
 
 // TODO write a struct that impls Handler for storing test values
@@ -109,6 +115,48 @@ fn test_fetch_response_body_matches_const_message() {
     };
 }
 
+#[test]
+fn test_fetch_response_from_service_worker() {
+    static MESSAGE: &'static [u8] = b"From the network";
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = MESSAGE.to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    PREFS.set("dom.serviceworker.enabled", PrefValue::Boolean(true));
+    let (swmanager_chan, swmanager_port) = ipc::channel::<CustomResponseMediator>().unwrap();
+    let mut context = new_fetch_context(None, None);
+    context.swmanager_chan = Some(swmanager_chan);
+    let load_url = url.clone();
+    let service_worker = thread::spawn(move || {
+        let mediator = swmanager_port.recv().unwrap();
+        assert_eq!(mediator.load_url, load_url);
+        assert!(!mediator.is_navigation);
+        let response = CustomResponse::new(
+            HeaderMap::new(),
+            (StatusCode::CREATED, "Created".to_owned()),
+            b"From the service worker".to_vec(),
+        );
+        mediator.response_chan.send(Some(Ok(response))).unwrap();
+    });
+
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(url, Some(origin), None);
+    request.referrer = Referrer::NoReferrer;
+    let fetch_response = fetch_with_context(&mut request, &mut context);
+    let _ = server.close();
+    service_worker.join().unwrap();
+
+    assert!(!fetch_response.is_network_error());
+    assert_eq!(fetch_response.raw_status, Some((201, b"Created".to_vec())));
+    match *fetch_response.body.lock().unwrap() {
+        ResponseBody::Done(ref body) => {
+            assert_eq!(&**body, b"From the service worker");
+        },
+        _ => panic!(),
+    };
+}
+
 #[test]
 fn test_fetch_aboutblank() {
     let url = ServoUrl::parse("about:blank").unwrap();
@@ -672,6 +720,7 @@ fn test_fetch_with_hsts() {
         timing: Arc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
         ))),
+        swmanager_chan: None,
     };
 
     {
@@ -1124,3 +1173,94 @@ fn test_fetch_with_devtools() {
     assert_eq!(devhttprequest, httprequest);
     assert_eq!(devhttpresponse, httpresponse);
 }
+
+#[test]
+fn test_fetch_keepalive_over_inflight_quota_is_network_error() {
+    static MESSAGE: &'static [u8] = b"";
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = MESSAGE.to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut context = new_fetch_context(None, None);
+    let origin = Origin::Origin(url.origin());
+
+    let mut inflight_request =
+        Request::new(url.clone(), Some(origin.clone()), Some(TEST_PIPELINE_ID));
+    inflight_request.keep_alive = true;
+    inflight_request.body = Some(vec![0; KEEPALIVE_INFLIGHT_QUOTA / 2]);
+    let _inflight_record = context
+        .state
+        .fetch_groups
+        .write()
+        .unwrap()
+        .add_record(&inflight_request);
+
+    let mut request = Request::new(url, Some(origin), Some(TEST_PIPELINE_ID));
+    request.referrer = Referrer::NoReferrer;
+    request.method = Method::POST;
+    request.keep_alive = true;
+    request.body = Some(vec![0; KEEPALIVE_INFLIGHT_QUOTA / 2 + 1]);
+    let _record = context
+        .state
+        .fetch_groups
+        .write()
+        .unwrap()
+        .add_record(&request);
+    let fetch_response = fetch_with_context(&mut request, &mut context);
+    let _ = server.close();
+
+    assert!(fetch_response.is_network_error());
+}
+
+#[test]
+fn test_terminated_fetch_group_cancels_fetch_but_not_keepalive() {
+    let url = ServoUrl::parse("http://example.com/").unwrap();
+    let origin = Origin::Origin(url.origin());
+    let mut fetch_groups = FetchGroups::new();
+
+    let request = Request::new(url.clone(), Some(origin.clone()), Some(TEST_PIPELINE_ID));
+    let record = fetch_groups.add_record(&request).unwrap();
+
+    let mut keep_alive_request = Request::new(url, Some(origin), Some(TEST_PIPELINE_ID));
+    keep_alive_request.keep_alive = true;
+    let keep_alive_record = fetch_groups.add_record(&keep_alive_request).unwrap();
+
+    fetch_groups.terminate(TEST_PIPELINE_ID);
+
+    let mut listener = CancellationListener::new_with_fetch_record(None, Some(record));
+    let mut keep_alive_listener =
+        CancellationListener::new_with_fetch_record(None, Some(keep_alive_record));
+    assert!(listener.cancelled());
+    assert!(!keep_alive_listener.cancelled());
+}
+
+#[test]
+fn test_fetch_while_offline() {
+    static MESSAGE: &'static [u8] = b"";
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = MESSAGE.to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut context = new_fetch_context(None, None);
+    context.state.offline.store(true, Ordering::SeqCst);
+
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(url, Some(origin), None);
+    request.referrer = Referrer::NoReferrer;
+    let fetch_response = fetch_with_context(&mut request, &mut context);
+    let _ = server.close();
+    assert!(fetch_response.is_network_error());
+
+    let url = ServoUrl::parse("data:text/plain,offline").unwrap();
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(url, Some(origin), None);
+    request.referrer = Referrer::NoReferrer;
+    let fetch_response = fetch_with_context(&mut request, &mut context);
+    assert!(!fetch_response.is_network_error());
+    assert_eq!(
+        *fetch_response.body.lock().unwrap(),
+        ResponseBody::Done(b"offline".to_vec())
+    );
+}
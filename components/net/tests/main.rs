@@ -7,6 +7,7 @@
 #[macro_use]
 extern crate lazy_static;
 
+mod cache_storage;
 mod cookie;
 mod cookie_http_state;
 mod data_loader;
@@ -96,6 +97,7 @@ fn new_fetch_context(
         timing: Arc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
         ))),
+        swmanager_chan: None,
     }
 }
 impl FetchTaskTarget for FetchResponseCollector {
@@ -195,6 +195,12 @@ pub fn init(
                 req_url.as_mut_url().set_scheme("https").unwrap();
             }
 
+            if http_state.offline.load(Ordering::SeqCst) {
+                debug!("Failed to establish a WebSocket connection: network is offline");
+                let _ = resource_event_sender.send(WebSocketNetworkEvent::Fail);
+                return;
+            }
+
             if should_be_blocked_due_to_bad_port(&req_url) {
                 debug!("Failed to establish a WebSocket connection: port blocked");
                 let _ = resource_event_sender.send(WebSocketNetworkEvent::Fail);
@@ -0,0 +1,105 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::response::ResponseType;
+use ipc_channel::ipc::IpcSender;
+use servo_url::{ImmutableOrigin, ServoUrl};
+
+/// Identifies a [cache](https://w3c.github.io/ServiceWorker/#cache-concept)
+/// among the caches of an origin. Ids stay valid after the cache is deleted
+/// from its cache storage, the cache is then just empty.
+pub type CacheId = u64;
+
+/// The parts of a request that are kept in a cache.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CachedRequest {
+    pub url: ServoUrl,
+    pub method: String,
+    pub headers: Vec<(String, Vec<u8>)>,
+}
+
+/// The parts of a response that are kept in a cache.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CachedResponse {
+    pub response_type: ResponseType,
+    pub url_list: Vec<ServoUrl>,
+    pub status: u16,
+    pub status_text: Vec<u8>,
+    pub headers: Vec<(String, Vec<u8>)>,
+    pub body: Vec<u8>,
+}
+
+/// The values of the headers named `name` in a cached header list, compared
+/// case-insensitively.
+pub fn header_values<'a>(headers: &'a [(String, Vec<u8>)], name: &str) -> Vec<&'a [u8]> {
+    headers
+        .iter()
+        .filter(|&&(ref header_name, _)| header_name.eq_ignore_ascii_case(name))
+        .map(|&(_, ref value)| &value[..])
+        .collect()
+}
+
+/// <https://w3c.github.io/ServiceWorker/#dictdef-cachequeryoptions>
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct CacheQueryOptions {
+    pub ignore_search: bool,
+    pub ignore_method: bool,
+    pub ignore_vary: bool,
+}
+
+/// Request operations on the caches of an origin.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum CacheStorageThreadMsg {
+    /// Whether a cache with the given name exists
+    Has(IpcSender<bool>, ImmutableOrigin, String),
+
+    /// Gets the cache with the given name, creating it if it does not exist yet
+    Open(IpcSender<CacheId>, ImmutableOrigin, String),
+
+    /// Deletes the cache with the given name, returns whether there was one
+    Delete(IpcSender<bool>, ImmutableOrigin, String),
+
+    /// Gets the names of the caches, in the order they were created
+    Keys(IpcSender<Vec<String>>, ImmutableOrigin),
+
+    /// Gets the first response matching the request, looking in the cache with
+    /// the given name, or in every cache if there is no name
+    MatchAcrossCaches(
+        IpcSender<Option<CachedResponse>>,
+        ImmutableOrigin,
+        Option<String>,
+        CachedRequest,
+        CacheQueryOptions,
+    ),
+
+    /// Gets the entries of a cache matching the request, or all of them if there is no request
+    MatchEntries(
+        IpcSender<Vec<(CachedRequest, CachedResponse)>>,
+        ImmutableOrigin,
+        CacheId,
+        Option<CachedRequest>,
+        CacheQueryOptions,
+    ),
+
+    /// Stores the entries in a cache, replacing the entries they match.
+    /// Sends Err(()) without storing anything if two of the new entries match each other
+    PutEntries(
+        IpcSender<Result<(), ()>>,
+        ImmutableOrigin,
+        CacheId,
+        Vec<(CachedRequest, CachedResponse)>,
+    ),
+
+    /// Removes the entries of a cache matching the request, returns whether there were any
+    DeleteEntries(
+        IpcSender<bool>,
+        ImmutableOrigin,
+        CacheId,
+        CachedRequest,
+        CacheQueryOptions,
+    ),
+
+    /// send a reply when done cleaning up thread resources and then shut it down
+    Exit(IpcSender<()>),
+}
@@ -17,6 +17,7 @@ extern crate serde;
 #[macro_use]
 extern crate url;
 
+use crate::cache_storage_thread::CacheStorageThreadMsg;
 use crate::filemanager_thread::FileManagerThreadMsg;
 use crate::request::{Request, RequestInit};
 use crate::response::{HttpsState, Response, ResponseInit};
@@ -24,7 +25,7 @@ use crate::storage_thread::StorageThreadMsg;
 use cookie::Cookie;
 use headers_core::HeaderMapExt;
 use headers_ext::{ContentType, ReferrerPolicy as ReferrerPolicyHeader};
-use http::{Error as HttpError, HeaderMap};
+use http::{Error as HttpError, HeaderMap, Method};
 use hyper::Error as HyperError;
 use hyper::StatusCode;
 use hyper_serde::Serde;
@@ -32,13 +33,14 @@ use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use ipc_channel::Error as IpcError;
 use mime::Mime;
-use msg::constellation_msg::HistoryStateId;
+use msg::constellation_msg::{HistoryStateId, PipelineId};
 use servo_url::ServoUrl;
 use std::error::Error;
 use time::precise_time_ns;
 use url::percent_encoding;
 
 pub mod blob_url_store;
+pub mod cache_storage_thread;
 pub mod filemanager_thread;
 pub mod image_cache;
 pub mod net_error_list;
@@ -103,10 +105,27 @@ impl CustomResponse {
     }
 }
 
+/// A fetch to be handled by the service worker controlling it, if any.
+/// The service worker answers with `None` to let the fetch go to the network.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct CustomResponseMediator {
-    pub response_chan: IpcSender<Option<CustomResponse>>,
+    pub response_chan: IpcSender<Option<Result<CustomResponse, NetworkError>>>,
     pub load_url: ServoUrl,
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub method: Method,
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub headers: HeaderMap,
+    pub body: Option<Vec<u8>>,
+    /// Whether this is a navigation request, whose controller is found from its url
+    /// rather than from the client that started it.
+    pub is_navigation: bool,
+    pub pipeline_id: Option<PipelineId>,
 }
 
 /// [Policies](https://w3c.github.io/webappsec-referrer-policy/#referrer-policy-states)
@@ -302,13 +321,19 @@ where
 pub struct ResourceThreads {
     core_thread: CoreResourceThread,
     storage_thread: IpcSender<StorageThreadMsg>,
+    cache_storage_thread: IpcSender<CacheStorageThreadMsg>,
 }
 
 impl ResourceThreads {
-    pub fn new(c: CoreResourceThread, s: IpcSender<StorageThreadMsg>) -> ResourceThreads {
+    pub fn new(
+        c: CoreResourceThread,
+        s: IpcSender<StorageThreadMsg>,
+        cs: IpcSender<CacheStorageThreadMsg>,
+    ) -> ResourceThreads {
         ResourceThreads {
             core_thread: c,
             storage_thread: s,
+            cache_storage_thread: cs,
         }
     }
 }
@@ -333,6 +358,16 @@ impl IpcSend<StorageThreadMsg> for ResourceThreads {
     }
 }
 
+impl IpcSend<CacheStorageThreadMsg> for ResourceThreads {
+    fn send(&self, msg: CacheStorageThreadMsg) -> IpcSendResult {
+        self.cache_storage_thread.send(msg)
+    }
+
+    fn sender(&self) -> IpcSender<CacheStorageThreadMsg> {
+        self.cache_storage_thread.clone()
+    }
+}
+
 // Ignore the sub-fields
 malloc_size_of_is_0!(ResourceThreads);
 
@@ -403,6 +438,12 @@ pub enum CoreResourceMsg {
     SetHistoryState(HistoryStateId, Vec<u8>),
     /// Removes history states for the given ids
     RemoveHistoryStates(Vec<HistoryStateId>),
+    /// Terminate the ongoing fetches of the fetch group belonging to a closed pipeline,
+    /// apart from those with the keepalive flag set
+    TerminateFetchGroup(PipelineId),
+    /// Make every network fetch fail, or stop doing so. Responses that do not need the
+    /// network, like data:, blob: and cached ones, are still served while offline.
+    SetOffline(bool),
     /// Synchronization message solely for knowing the state of the ResourceChannelManager loop
     Synchronize(IpcSender<()>),
     /// Send the network sender in constellation to CoreResourceThread
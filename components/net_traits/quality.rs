@@ -7,7 +7,6 @@
 // (I would probably also make some silly mistakes while migrating...)
 
 use http::header::HeaderValue;
-use mime::Mime;
 use std::{fmt, str};
 
 /// A quality value, as specified in [RFC7231].
@@ -75,7 +74,7 @@ where
     }
 }
 
-pub fn quality_to_value(q: Vec<QualityItem<Mime>>) -> HeaderValue {
+pub fn quality_to_value<T: fmt::Display>(q: Vec<QualityItem<T>>) -> HeaderValue {
     HeaderValue::from_str(
         &q.iter()
             .map(|q| q.to_string())
@@ -10,6 +10,10 @@ use msg::constellation_msg::PipelineId;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use std::default::Default;
 
+/// The maximum number of body bytes that keepalive requests of a single
+/// [fetch group](https://fetch.spec.whatwg.org/#concept-fetch-group) may have in flight.
+pub const KEEPALIVE_INFLIGHT_QUOTA: usize = 64 * 1024;
+
 /// An [initiator](https://fetch.spec.whatwg.org/#concept-request-initiator)
 #[derive(Clone, Copy, MallocSizeOf, PartialEq)]
 pub enum Initiator {
@@ -152,6 +156,7 @@ pub struct RequestInit {
     pub headers: HeaderMap,
     pub unsafe_request: bool,
     pub body: Option<Vec<u8>>,
+    pub keep_alive: bool,
     pub service_workers_mode: ServiceWorkersMode,
     // TODO: client object
     pub destination: Destination,
@@ -180,6 +185,7 @@ impl Default for RequestInit {
             headers: HeaderMap::new(),
             unsafe_request: false,
             body: None,
+            keep_alive: false,
             service_workers_mode: ServiceWorkersMode::All,
             destination: Destination::None,
             synchronous: false,
@@ -304,6 +310,7 @@ impl Request {
         req.headers = init.headers;
         req.unsafe_request = init.unsafe_request;
         req.body = init.body;
+        req.keep_alive = init.keep_alive;
         req.service_workers_mode = init.service_workers_mode;
         req.destination = init.destination;
         req.synchronous = init.synchronous;
@@ -156,6 +156,8 @@ impl Formattable for ProfilerCategory {
             ProfilerCategory::TimeToInteractive => "Time to Interactive",
             ProfilerCategory::IpcReceiver => "Blocked at IPC Receive",
             ProfilerCategory::IpcBytesReceiver => "Blocked at IPC Bytes Receive",
+            ProfilerCategory::TimeToLargestContentfulPaint => "Time To Largest Contentful Paint",
+            ProfilerCategory::LayoutShift => "Layout Shift",
             ProfilerCategory::ApplicationHeartbeat => "Application Heartbeat",
         };
         format!("{}{}", padding, name)
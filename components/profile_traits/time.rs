@@ -109,6 +109,8 @@ pub enum ProfilerCategory {
     TimeToInteractive = 0x82,
     IpcReceiver = 0x83,
     IpcBytesReceiver = 0x84,
+    TimeToLargestContentfulPaint = 0x85,
+    LayoutShift = 0x86,
     ApplicationHeartbeat = 0x90,
 }
 
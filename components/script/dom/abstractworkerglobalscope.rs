@@ -71,7 +71,11 @@ impl ScriptPort for Receiver<DedicatedWorkerScriptMsg> {
         let common_msg = match self.recv() {
             Ok(DedicatedWorkerScriptMsg::CommonWorker(_worker, common_msg)) => common_msg,
             Err(_) => return Err(()),
-            Ok(DedicatedWorkerScriptMsg::WakeUp) => panic!("unexpected worker event message!"),
+            Ok(DedicatedWorkerScriptMsg::WakeUp) |
+            Ok(DedicatedWorkerScriptMsg::NetworkStateChanged(_)) |
+            Ok(DedicatedWorkerScriptMsg::LanguagesChanged) => {
+                panic!("unexpected worker event message!")
+            },
         };
         match common_msg {
             WorkerScriptMsg::Common(script_msg) => Ok(script_msg),
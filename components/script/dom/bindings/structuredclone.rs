@@ -11,6 +11,7 @@ use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::blob::{Blob, BlobImpl};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::offscreencanvas::{OffscreenCanvas, TransferredOffscreenCanvas};
 use js::glue::CopyJSStructuredCloneData;
 use js::glue::DeleteJSAutoStructuredCloneBuffer;
 use js::glue::GetLengthOfJSStructuredCloneData;
@@ -43,6 +44,7 @@ enum StructuredCloneTags {
     /// To support additional types, add new tags with values incremented from the last one before Max.
     Min = 0xFFFF8000,
     DomBlob = 0xFFFF8001,
+    OffscreenCanvas = 0xFFFF8002,
     Max = 0xFFFFFFFF,
 }
 
@@ -188,26 +190,46 @@ unsafe extern "C" fn write_callback(
 }
 
 unsafe extern "C" fn read_transfer_callback(
-    _cx: *mut JSContext,
+    cx: *mut JSContext,
     _r: *mut JSStructuredCloneReader,
-    _tag: u32,
-    _content: *mut raw::c_void,
+    tag: u32,
+    content: *mut raw::c_void,
     _extra_data: u64,
-    _closure: *mut raw::c_void,
-    _return_object: RawMutableHandleObject,
+    closure: *mut raw::c_void,
+    return_object: RawMutableHandleObject,
 ) -> bool {
+    if tag == StructuredCloneTags::OffscreenCanvas as u32 {
+        let transferred = Box::from_raw(content as *mut TransferredOffscreenCanvas);
+        let target_global = GlobalScope::from_context(cx);
+        let canvas = OffscreenCanvas::new_from_transferred(&target_global, *transferred);
+        *return_object.ptr = canvas.reflector().get_jsobject().get();
+        let sc_holder = &mut *(closure as *mut StructuredCloneHolder);
+        sc_holder.offscreen_canvases.push(canvas);
+        return true;
+    }
     false
 }
 
 unsafe extern "C" fn write_transfer_callback(
     _cx: *mut JSContext,
-    _obj: RawHandleObject,
+    obj: RawHandleObject,
     _closure: *mut raw::c_void,
-    _tag: *mut u32,
-    _ownership: *mut TransferableOwnership,
-    _content: *mut *mut raw::c_void,
-    _extra_data: *mut u64,
+    tag: *mut u32,
+    ownership: *mut TransferableOwnership,
+    content: *mut *mut raw::c_void,
+    extra_data: *mut u64,
 ) -> bool {
+    if let Ok(canvas) = root_from_handleobject::<OffscreenCanvas>(Handle::from_raw(obj)) {
+        let transferred = match canvas.transfer() {
+            Ok(transferred) => transferred,
+            Err(_) => return false,
+        };
+        *tag = StructuredCloneTags::OffscreenCanvas as u32;
+        *ownership = TransferableOwnership::SCTAG_TMO_CUSTOM;
+        *content = Box::into_raw(Box::new(transferred)) as *mut raw::c_void;
+        *extra_data = 0;
+        return true;
+    }
     false
 }
 
@@ -218,6 +240,8 @@ unsafe extern "C" fn free_transfer_callback(
     _extra_data: u64,
     _closure: *mut raw::c_void,
 ) {
+    // The transferred content is owned by the serialized bytes once they
+    // are copied out of the writing buffer, the reading side takes it back.
 }
 
 unsafe extern "C" fn report_error_callback(_cx: *mut JSContext, _errorid: u32) {}
@@ -233,6 +257,7 @@ static STRUCTURED_CLONE_CALLBACKS: JSStructuredCloneCallbacks = JSStructuredClon
 
 struct StructuredCloneHolder {
     blob: Option<DomRoot<Blob>>,
+    offscreen_canvases: Vec<DomRoot<OffscreenCanvas>>,
 }
 
 /// A buffer for a structured clone.
@@ -247,6 +272,16 @@ impl StructuredCloneData {
     // TODO: should this be unsafe?
     /// Writes a structured clone. Returns a `DataClone` error if that fails.
     pub fn write(cx: *mut JSContext, message: HandleValue) -> Fallible<StructuredCloneData> {
+        StructuredCloneData::write_with_transfer(cx, message, HandleValue::undefined())
+    }
+
+    /// Writes a structured clone, moving the objects of the `transfer` array
+    /// instead of copying them. Returns a `DataClone` error if that fails.
+    pub fn write_with_transfer(
+        cx: *mut JSContext,
+        message: HandleValue,
+        transfer: HandleValue,
+    ) -> Fallible<StructuredCloneData> {
         unsafe {
             let scbuf = NewJSAutoStructuredCloneBuffer(
                 StructuredCloneScope::DifferentProcess,
@@ -265,7 +300,7 @@ impl StructuredCloneData {
                 policy,
                 &STRUCTURED_CLONE_CALLBACKS,
                 ptr::null_mut(),
-                HandleValue::undefined(),
+                transfer,
             );
             if !result {
                 JS_ClearPendingException(cx);
@@ -300,7 +335,10 @@ impl StructuredCloneData {
         let cx = global.get_cx();
         let globalhandle = global.reflector().get_jsobject();
         let _ac = JSAutoCompartment::new(cx, globalhandle.get());
-        let mut sc_holder = StructuredCloneHolder { blob: None };
+        let mut sc_holder = StructuredCloneHolder {
+            blob: None,
+            offscreen_canvases: vec![],
+        };
         let sc_holder_ptr = &mut sc_holder as *mut _;
         unsafe {
             let scbuf = NewJSAutoStructuredCloneBuffer(
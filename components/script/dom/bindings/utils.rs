@@ -7,6 +7,7 @@
 use crate::dom::bindings::codegen::InterfaceObjectMap;
 use crate::dom::bindings::codegen::PrototypeList;
 use crate::dom::bindings::codegen::PrototypeList::{MAX_PROTO_CHAIN_LENGTH, PROTO_OR_IFACE_LENGTH};
+use crate::dom::bindings::conversions::ToJSValConvertible;
 use crate::dom::bindings::conversions::{jsstring_to_str, private_from_proto_check};
 use crate::dom::bindings::error::throw_invalid_this;
 use crate::dom::bindings::inheritance::TopTypeId;
@@ -23,7 +24,7 @@ use js::jsapi::MutableHandleObject as RawMutableHandleObject;
 use js::jsapi::{CallArgs, DOMCallbacks, GetGlobalForObjectCrossCompartment};
 use js::jsapi::{Heap, JSAutoCompartment, JSContext};
 use js::jsapi::{JSJitInfo, JSObject, JSTracer, JSWrapObjectCallbacks};
-use js::jsapi::{JS_EnumerateStandardClasses, JS_GetLatin1StringCharsAndLength};
+use js::jsapi::{JS_EnumerateStandardClasses, JS_FreezeObject, JS_GetLatin1StringCharsAndLength};
 use js::jsapi::{JS_IsExceptionPending, JS_IsGlobalObject};
 use js::jsapi::{JS_ResolveStandardClass, JS_StringHasLatin1Chars, ObjectOpResult};
 use js::jsval::{JSVal, UndefinedValue};
@@ -315,6 +316,16 @@ pub unsafe fn has_property_on_prototype(
     JS_HasPropertyById(cx, proto.handle(), id, found)
 }
 
+/// Convert `values` into a frozen JS array. Used for attributes of type
+/// `FrozenArray<T>`, which the bindings do not support yet.
+pub unsafe fn to_frozen_array<T: ToJSValConvertible>(values: &[T], cx: *mut JSContext) -> JSVal {
+    rooted!(in(cx) let mut array = UndefinedValue());
+    values.to_jsval(cx, array.handle_mut());
+    rooted!(in(cx) let obj = array.to_object());
+    JS_FreezeObject(cx, obj.handle().into());
+    array.get()
+}
+
 /// Drop the resources held by reserved slots of a global object
 pub unsafe fn finalize_global(obj: *mut JSObject) {
     let protolist = get_proto_or_iface_array(obj);
@@ -0,0 +1,703 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::body::{consume_body, BodyType};
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::CacheBinding::CacheQueryOptions;
+use crate::dom::bindings::codegen::Bindings::CacheBinding::{self, CacheMethods};
+use crate::dom::bindings::codegen::Bindings::RequestBinding::{RequestInfo, RequestInit};
+use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performanceresourcetiming::InitiatorType;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::request::Request;
+use crate::dom::response::Response;
+use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
+use crate::fetch::request_init_from_request;
+use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
+use js::jsapi::{JSAutoCompartment, JSContext};
+use js::rust::HandleValue;
+use js::typedarray::ArrayBuffer;
+use net_traits::cache_storage_thread::CacheQueryOptions as NetTraitsCacheQueryOptions;
+use net_traits::cache_storage_thread::{header_values, CacheId, CacheStorageThreadMsg};
+use net_traits::cache_storage_thread::{CachedRequest, CachedResponse};
+use net_traits::request::ServiceWorkersMode;
+use net_traits::response::ResponseType;
+use net_traits::CoreResourceMsg::Fetch as NetTraitsFetch;
+use net_traits::{FetchChannels, FetchMetadata, FetchResponseListener, FilteredMetadata};
+use net_traits::{IpcSend, Metadata, NetworkError, ResourceFetchTiming, ResourceTimingType};
+use serde::{Deserialize, Serialize};
+use servo_url::{ImmutableOrigin, ServoUrl};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+// https://w3c.github.io/ServiceWorker/#cache-interface
+#[dom_struct]
+pub struct Cache {
+    reflector_: Reflector,
+    origin: ImmutableOrigin,
+    /// The cache of the cache storage thread this object stands for.
+    id: CacheId,
+}
+
+impl Cache {
+    fn new_inherited(origin: ImmutableOrigin, id: CacheId) -> Cache {
+        Cache {
+            reflector_: Reflector::new(),
+            origin: origin,
+            id: id,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, origin: ImmutableOrigin, id: CacheId) -> DomRoot<Cache> {
+        reflect_dom_object(
+            Box::new(Cache::new_inherited(origin, id)),
+            global,
+            CacheBinding::Wrap,
+        )
+    }
+
+    /// Looks up the entries matching `request`, and settles `promise` with
+    /// `on_reply` once they are known.
+    fn match_entries<F>(
+        &self,
+        promise: &Rc<Promise>,
+        request: Option<CachedRequest>,
+        options: NetTraitsCacheQueryOptions,
+        on_reply: F,
+    ) where
+        F: FnOnce(&Promise, Vec<(CachedRequest, CachedResponse)>) + Send + 'static,
+    {
+        let (origin, id) = (self.origin.clone(), self.id);
+        send_to_cache_storage(
+            &self.global(),
+            promise,
+            move |sender| CacheStorageThreadMsg::MatchEntries(sender, origin, id, request, options),
+            on_reply,
+        );
+    }
+}
+
+/// Sends the message built by `msg` to the cache storage thread, and settles
+/// `promise` with `on_reply` in a networking task once the reply arrives.
+/// `promise` is rejected if the cache storage thread can't be reached.
+pub fn send_to_cache_storage<T, M, F>(
+    global: &GlobalScope,
+    promise: &Rc<Promise>,
+    msg: M,
+    on_reply: F,
+) where
+    T: for<'de> Deserialize<'de> + Serialize + Send + 'static,
+    M: FnOnce(IpcSender<T>) -> CacheStorageThreadMsg,
+    F: FnOnce(&Promise, T) + Send + 'static,
+{
+    let (sender, receiver) = match ipc::channel() {
+        Ok(channel) => channel,
+        Err(_) => return promise.reject_error(Error::Operation),
+    };
+    if global.resource_threads().send(msg(sender)).is_err() {
+        return promise.reject_error(Error::Operation);
+    }
+    let task_source = global.networking_task_source();
+    let canceller = global.task_canceller(TaskSourceName::Networking);
+    let mut pending = Some((TrustedPromise::new(promise.clone()), on_reply));
+    ROUTER.add_route(
+        receiver.to_opaque(),
+        Box::new(move |message| {
+            let (promise, on_reply) = match pending.take() {
+                Some(pending) => pending,
+                None => return,
+            };
+            let reply = message.to::<T>().map_err(|_| ());
+            let _ = task_source.queue_with_canceller(
+                task!(cache_storage_reply: move || {
+                    let promise = promise.root();
+                    let _ac = JSAutoCompartment::new(
+                        promise.global().get_cx(),
+                        promise.reflector().get_jsobject().get(),
+                    );
+                    match reply {
+                        Ok(reply) => on_reply(&promise, reply),
+                        Err(()) => promise.reject_error(Error::Operation),
+                    }
+                }),
+                &canceller,
+            );
+        }),
+    );
+}
+
+/// The origin whose cache storage `global` can use.
+pub fn cache_storage_origin(global: &GlobalScope) -> Fallible<ImmutableOrigin> {
+    let origin = global.origin().immutable().clone();
+    if !origin.is_tuple() {
+        return Err(Error::Security);
+    }
+    Ok(origin)
+}
+
+pub fn query_options(options: &CacheQueryOptions) -> NetTraitsCacheQueryOptions {
+    NetTraitsCacheQueryOptions {
+        ignore_search: options.ignoreSearch,
+        ignore_method: options.ignoreMethod,
+        ignore_vary: options.ignoreVary,
+    }
+}
+
+/// The request `input` stands for, or `None` when it can not match anything
+/// because it is not a GET request.
+pub fn cached_request_from_info(
+    global: &GlobalScope,
+    input: RequestInfo,
+    ignore_method: bool,
+) -> Fallible<Option<CachedRequest>> {
+    let request = match input {
+        RequestInfo::Request(request) => request,
+        input @ RequestInfo::USVString(_) => {
+            Request::Constructor(global, input, RequestInit::empty())?
+        },
+    };
+    let request = request.to_cached_request();
+    if !ignore_method && request.method != "GET" {
+        return Ok(None);
+    }
+    Ok(Some(request))
+}
+
+/// Requests that can not be stored in a cache.
+fn check_request_for_put(request: &CachedRequest) -> Fallible<()> {
+    if request.url.scheme() != "http" && request.url.scheme() != "https" {
+        return Err(Error::Type(
+            "Only http and https requests can be cached".to_owned(),
+        ));
+    }
+    if request.method != "GET" {
+        return Err(Error::Type("Only GET requests can be cached".to_owned()));
+    }
+    Ok(())
+}
+
+fn vary_contains_star(response: &CachedResponse) -> bool {
+    header_values(&response.headers, "vary")
+        .iter()
+        .any(|value| {
+            value
+                .split(|&byte| byte == b',')
+                .any(|field_value| String::from_utf8_lossy(field_value).trim() == "*")
+        })
+}
+
+/// The put operations of <https://w3c.github.io/ServiceWorker/#batch-cache-operations>,
+/// `promise` is resolved once the entries are stored.
+fn put_entries(
+    global: &GlobalScope,
+    promise: &Rc<Promise>,
+    origin: ImmutableOrigin,
+    id: CacheId,
+    entries: Vec<(CachedRequest, CachedResponse)>,
+) {
+    send_to_cache_storage(
+        global,
+        promise,
+        move |sender| CacheStorageThreadMsg::PutEntries(sender, origin, id, entries),
+        |promise, result| match result {
+            Ok(()) => promise.resolve_native(&()),
+            Err(()) => promise.reject_error(Error::InvalidState),
+        },
+    );
+}
+
+impl CacheMethods for Cache {
+    // https://w3c.github.io/ServiceWorker/#cache-match
+    fn Match(&self, request: RequestInfo, options: &CacheQueryOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        let options = query_options(options);
+        let request = match cached_request_from_info(&global, request, options.ignore_method) {
+            Ok(Some(request)) => request,
+            Ok(None) => {
+                promise.resolve_native(&());
+                return promise;
+            },
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        self.match_entries(
+            &promise,
+            Some(request),
+            options,
+            |promise, entries| match entries.into_iter().next() {
+                Some((_, response)) => promise
+                    .resolve_native(&Response::from_cached_response(&promise.global(), response)),
+                None => promise.resolve_native(&()),
+            },
+        );
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-matchall
+    fn MatchAll(&self, request: Option<RequestInfo>, options: &CacheQueryOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        let options = query_options(options);
+        let request = match request {
+            Some(request) => {
+                match cached_request_from_info(&global, request, options.ignore_method) {
+                    Ok(Some(request)) => Some(request),
+                    Ok(None) => {
+                        promise.resolve_native(&Vec::<DomRoot<Response>>::new());
+                        return promise;
+                    },
+                    Err(error) => {
+                        promise.reject_error(error);
+                        return promise;
+                    },
+                }
+            },
+            None => None,
+        };
+        self.match_entries(&promise, request, options, |promise, entries| {
+            let global = promise.global();
+            let responses: Vec<_> = entries
+                .into_iter()
+                .map(|(_, response)| Response::from_cached_response(&global, response))
+                .collect();
+            promise.resolve_native(&responses);
+        });
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-add
+    fn Add(&self, request: RequestInfo) -> Rc<Promise> {
+        self.AddAll(vec![request])
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-addAll
+    fn AddAll(&self, requests: Vec<RequestInfo>) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+
+        // Steps 1-3.
+        let mut inner_requests = vec![];
+        for request in requests {
+            let request = match request {
+                RequestInfo::Request(request) => request,
+                input @ RequestInfo::USVString(_) => {
+                    match Request::Constructor(&global, input, RequestInit::empty()) {
+                        Ok(request) => request,
+                        Err(error) => {
+                            promise.reject_error(error);
+                            return promise;
+                        },
+                    }
+                },
+            };
+            if let Err(error) = check_request_for_put(&request.to_cached_request()) {
+                promise.reject_error(error);
+                return promise;
+            }
+            inner_requests.push(request);
+        }
+
+        if inner_requests.is_empty() {
+            promise.resolve_native(&());
+            return promise;
+        }
+
+        // Steps 4-7.
+        let state = Arc::new(Mutex::new(AddAllState {
+            promise: Some(TrustedPromise::new(promise.clone())),
+            cache: Trusted::new(self),
+            entries: inner_requests.iter().map(|_| None).collect(),
+        }));
+        for (index, request) in inner_requests.iter().enumerate() {
+            let cached_request = request.to_cached_request();
+            let net_request = request.get_request();
+            let timing_type = net_request.timing_type();
+            let mut request_init = request_init_from_request(net_request);
+            if global.downcast::<ServiceWorkerGlobalScope>().is_some() {
+                request_init.service_workers_mode = ServiceWorkersMode::None;
+            }
+
+            let (action_sender, action_receiver) = ipc::channel().unwrap();
+            let listener = NetworkListener {
+                context: Arc::new(Mutex::new(CacheAddContext {
+                    state: state.clone(),
+                    index: index,
+                    request: Some(cached_request),
+                    response: None,
+                    resource_timing: ResourceFetchTiming::new(timing_type),
+                })),
+                task_source: global.networking_task_source(),
+                canceller: Some(global.task_canceller(TaskSourceName::Networking)),
+            };
+            ROUTER.add_route(
+                action_receiver.to_opaque(),
+                Box::new(move |message| {
+                    listener.notify_fetch(message.to().unwrap());
+                }),
+            );
+            global
+                .core_resource_thread()
+                .send(NetTraitsFetch(
+                    request_init,
+                    FetchChannels::ResponseMsg(action_sender, None),
+                ))
+                .unwrap();
+        }
+
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-put
+    fn Put(&self, request: RequestInfo, response: &Response) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+
+        // Steps 1-3.
+        let request = match cached_request_from_info(&global, request, true) {
+            Ok(Some(request)) => request,
+            // `ignore_method` is set, so every request is returned.
+            Ok(None) => {
+                promise.reject_error(Error::Type("Only GET requests can be cached".to_owned()));
+                return promise;
+            },
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        if let Err(error) = check_request_for_put(&request) {
+            promise.reject_error(error);
+            return promise;
+        }
+
+        // Steps 4-6.
+        let cached_response = response.to_cached_response();
+        if cached_response.status == 206 {
+            promise.reject_error(Error::Type(
+                "Partial responses can not be cached".to_owned(),
+            ));
+            return promise;
+        }
+        if vary_contains_star(&cached_response) {
+            promise.reject_error(Error::Type(
+                "Responses varying on * can not be cached".to_owned(),
+            ));
+            return promise;
+        }
+        if response.BodyUsed() {
+            promise.reject_error(Error::Type("The response body was already used".to_owned()));
+            return promise;
+        }
+
+        // Steps 7-12. The body may still be loading, so put the response once it
+        // is fully read.
+        let body_promise = consume_body(response, BodyType::ArrayBuffer);
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(Box::new(PutBodyHandler {
+                promise: promise.clone(),
+                origin: self.origin.clone(),
+                id: self.id,
+                entry: DomRefCell::new(Some((request, cached_response))),
+            })),
+            Some(Box::new(RejectHandler {
+                promise: promise.clone(),
+            })),
+        );
+        body_promise.append_native_handler(&handler);
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-delete
+    fn Delete(&self, request: RequestInfo, options: &CacheQueryOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        let options = query_options(options);
+        let request = match cached_request_from_info(&global, request, options.ignore_method) {
+            Ok(Some(request)) => request,
+            Ok(None) => {
+                promise.resolve_native(&false);
+                return promise;
+            },
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let (origin, id) = (self.origin.clone(), self.id);
+        send_to_cache_storage(
+            &global,
+            &promise,
+            move |sender| {
+                CacheStorageThreadMsg::DeleteEntries(sender, origin, id, request, options)
+            },
+            |promise, deleted| promise.resolve_native(&deleted),
+        );
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-keys
+    fn Keys(&self, request: Option<RequestInfo>, options: &CacheQueryOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        let options = query_options(options);
+        let request = match request {
+            Some(request) => {
+                match cached_request_from_info(&global, request, options.ignore_method) {
+                    Ok(Some(request)) => Some(request),
+                    Ok(None) => {
+                        promise.resolve_native(&Vec::<DomRoot<Request>>::new());
+                        return promise;
+                    },
+                    Err(error) => {
+                        promise.reject_error(error);
+                        return promise;
+                    },
+                }
+            },
+            None => None,
+        };
+        self.match_entries(&promise, request, options, |promise, entries| {
+            let global = promise.global();
+            let requests: Vec<_> = entries
+                .iter()
+                .map(|&(ref request, _)| Request::from_cached_request(&global, request))
+                .collect();
+            promise.resolve_native(&requests);
+        });
+        promise
+    }
+}
+
+/// Stores the response to `Cache.put` once its body is read.
+#[derive(JSTraceable, MallocSizeOf)]
+struct PutBodyHandler {
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+    origin: ImmutableOrigin,
+    id: CacheId,
+    #[ignore_malloc_size_of = "Defined in net_traits"]
+    entry: DomRefCell<Option<(CachedRequest, CachedResponse)>>,
+}
+
+impl Callback for PutBodyHandler {
+    #[allow(unsafe_code)]
+    fn callback(&self, cx: *mut JSContext, v: HandleValue) {
+        let (request, mut response) = match self.entry.borrow_mut().take() {
+            Some(entry) => entry,
+            None => return,
+        };
+        response.body = unsafe {
+            typedarray!(in(cx) let body: ArrayBuffer = v.to_object());
+            match body {
+                Ok(body) => body.to_vec(),
+                Err(_) => {
+                    return self
+                        .promise
+                        .reject_error(Error::Type("Could not read the response body".to_owned()));
+                },
+            }
+        };
+        put_entries(
+            &self.promise.global(),
+            &self.promise,
+            self.origin.clone(),
+            self.id,
+            vec![(request, response)],
+        );
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+struct RejectHandler {
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+}
+
+impl Callback for RejectHandler {
+    #[allow(unsafe_code)]
+    fn callback(&self, cx: *mut JSContext, v: HandleValue) {
+        unsafe { self.promise.reject(cx, v) };
+    }
+}
+
+/// The fetches of a `Cache.addAll` call, the entries are put in the cache once
+/// all of them succeeded.
+struct AddAllState {
+    /// Taken once the promise is settled.
+    promise: Option<TrustedPromise>,
+    cache: Trusted<Cache>,
+    entries: Vec<Option<(CachedRequest, CachedResponse)>>,
+}
+
+impl AddAllState {
+    fn reject(&mut self, message: &str) {
+        let promise = match self.promise.take() {
+            Some(promise) => promise.root(),
+            None => return,
+        };
+        let _ac = JSAutoCompartment::new(
+            promise.global().get_cx(),
+            promise.reflector().get_jsobject().get(),
+        );
+        promise.reject_error(Error::Type(message.to_owned()));
+    }
+
+    fn finish_entry(&mut self, index: usize, request: CachedRequest, response: CachedResponse) {
+        self.entries[index] = Some((request, response));
+        if self.entries.iter().any(Option::is_none) {
+            return;
+        }
+        let promise = match self.promise.take() {
+            Some(promise) => promise.root(),
+            None => return,
+        };
+        let _ac = JSAutoCompartment::new(
+            promise.global().get_cx(),
+            promise.reflector().get_jsobject().get(),
+        );
+        let cache = self.cache.root();
+        let entries = self.entries.drain(..).filter_map(|entry| entry).collect();
+        put_entries(
+            &cache.global(),
+            &promise,
+            cache.origin.clone(),
+            cache.id,
+            entries,
+        );
+    }
+}
+
+struct CacheAddContext {
+    state: Arc<Mutex<AddAllState>>,
+    index: usize,
+    request: Option<CachedRequest>,
+    response: Option<CachedResponse>,
+    resource_timing: ResourceFetchTiming,
+}
+
+impl CacheAddContext {
+    fn response_from_metadata(response_type: ResponseType, metadata: Metadata) -> CachedResponse {
+        let (status, status_text) = metadata.status.unwrap_or((0, vec![]));
+        let headers = metadata.headers.map_or(vec![], |headers| {
+            headers
+                .into_inner()
+                .iter()
+                .map(|(name, value)| (name.as_str().to_owned(), value.as_bytes().to_vec()))
+                .collect()
+        });
+        CachedResponse {
+            response_type: response_type,
+            url_list: vec![metadata.final_url],
+            status: status,
+            status_text: status_text,
+            headers: headers,
+            body: vec![],
+        }
+    }
+}
+
+impl PreInvoke for CacheAddContext {}
+
+impl FetchResponseListener for CacheAddContext {
+    fn process_request_body(&mut self) {}
+
+    fn process_request_eof(&mut self) {}
+
+    fn process_response(&mut self, fetch_metadata: Result<FetchMetadata, NetworkError>) {
+        let response = match fetch_metadata {
+            Ok(FetchMetadata::Unfiltered(metadata)) => {
+                CacheAddContext::response_from_metadata(ResponseType::Default, metadata)
+            },
+            Ok(FetchMetadata::Filtered { filtered, .. }) => match filtered {
+                FilteredMetadata::Basic(metadata) => {
+                    CacheAddContext::response_from_metadata(ResponseType::Basic, metadata)
+                },
+                FilteredMetadata::Cors(metadata) => {
+                    CacheAddContext::response_from_metadata(ResponseType::Cors, metadata)
+                },
+                FilteredMetadata::Opaque | FilteredMetadata::OpaqueRedirect => {
+                    return self
+                        .state
+                        .lock()
+                        .unwrap()
+                        .reject("Opaque responses can not be added to a cache");
+                },
+            },
+            Err(_) => {
+                return self.state.lock().unwrap().reject("Network error occurred");
+            },
+        };
+
+        // Step 5.8.1 of addAll.
+        let ok = response.status >= 200 && response.status <= 299;
+        if !ok || response.status == 206 || vary_contains_star(&response) {
+            return self
+                .state
+                .lock()
+                .unwrap()
+                .reject("The response can not be added to a cache");
+        }
+        self.response = Some(response);
+    }
+
+    fn process_response_chunk(&mut self, mut chunk: Vec<u8>) {
+        if let Some(ref mut response) = self.response {
+            response.body.append(&mut chunk);
+        }
+    }
+
+    fn process_response_eof(&mut self, response: Result<ResourceFetchTiming, NetworkError>) {
+        let mut state = self.state.lock().unwrap();
+        if response.is_err() {
+            return state.reject("Network error occurred");
+        }
+        if let (Some(request), Some(response)) = (self.request.take(), self.response.take()) {
+            state.finish_entry(self.index, request, response);
+        }
+    }
+
+    fn resource_timing_mut(&mut self) -> &mut ResourceFetchTiming {
+        &mut self.resource_timing
+    }
+
+    fn resource_timing(&self) -> &ResourceFetchTiming {
+        &self.resource_timing
+    }
+
+    fn submit_resource_timing(&mut self) {
+        match self.resource_timing.timing_type {
+            ResourceTimingType::Resource => network_listener::submit_timing(self),
+            _ => {},
+        };
+    }
+}
+
+impl ResourceTimingListener for CacheAddContext {
+    fn resource_timing_information(&self) -> (InitiatorType, ServoUrl) {
+        let url = self.request.as_ref().map_or_else(
+            || self.resource_timing_global().get_url(),
+            |r| r.url.clone(),
+        );
+        (InitiatorType::Fetch, url)
+    }
+
+    fn resource_timing_global(&self) -> DomRoot<GlobalScope> {
+        self.state.lock().unwrap().cache.root().global()
+    }
+}
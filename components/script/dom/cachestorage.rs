@@ -0,0 +1,153 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CacheStorageBinding::MultiCacheQueryOptions;
+use crate::dom::bindings::codegen::Bindings::CacheStorageBinding::{self, CacheStorageMethods};
+use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInfo;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::cache::{cache_storage_origin, cached_request_from_info, query_options};
+use crate::dom::cache::{send_to_cache_storage, Cache};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::response::Response;
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use net_traits::cache_storage_thread::CacheStorageThreadMsg;
+use serde::{Deserialize, Serialize};
+use servo_url::ImmutableOrigin;
+use std::rc::Rc;
+
+// https://w3c.github.io/ServiceWorker/#cachestorage-interface
+#[dom_struct]
+pub struct CacheStorage {
+    reflector_: Reflector,
+}
+
+impl CacheStorage {
+    fn new_inherited() -> CacheStorage {
+        CacheStorage {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<CacheStorage> {
+        reflect_dom_object(
+            Box::new(CacheStorage::new_inherited()),
+            global,
+            CacheStorageBinding::Wrap,
+        )
+    }
+
+    /// Sends the message built by `msg` for the origin of this cache storage,
+    /// and settles `promise` with `on_reply` once the reply arrives. Rejects
+    /// `promise` if the origin can not use caches.
+    fn send<T, M, F>(&self, promise: &Rc<Promise>, msg: M, on_reply: F)
+    where
+        T: for<'de> Deserialize<'de> + Serialize + Send + 'static,
+        M: FnOnce(IpcSender<T>, ImmutableOrigin) -> CacheStorageThreadMsg,
+        F: FnOnce(&Promise, ImmutableOrigin, T) + Send + 'static,
+    {
+        let global = self.global();
+        let origin = match cache_storage_origin(&global) {
+            Ok(origin) => origin,
+            Err(error) => return promise.reject_error(error),
+        };
+        let reply_origin = origin.clone();
+        send_to_cache_storage(
+            &global,
+            promise,
+            move |sender| msg(sender, origin),
+            move |promise, reply| on_reply(promise, reply_origin, reply),
+        );
+    }
+}
+
+impl CacheStorageMethods for CacheStorage {
+    // https://w3c.github.io/ServiceWorker/#cache-storage-match
+    fn Match(&self, request: RequestInfo, options: &MultiCacheQueryOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        let cache_name = options
+            .cacheName
+            .as_ref()
+            .map(|name| String::from(name.clone()));
+        let options = query_options(&options.parent);
+        let request = match cached_request_from_info(&global, request, options.ignore_method) {
+            Ok(Some(request)) => request,
+            Ok(None) => {
+                promise.resolve_native(&());
+                return promise;
+            },
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        self.send(
+            &promise,
+            |sender, origin| {
+                CacheStorageThreadMsg::MatchAcrossCaches(
+                    sender, origin, cache_name, request, options,
+                )
+            },
+            |promise, _, response| match response {
+                Some(response) => promise
+                    .resolve_native(&Response::from_cached_response(&promise.global(), response)),
+                None => promise.resolve_native(&()),
+            },
+        );
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-storage-has
+    fn Has(&self, cache_name: DOMString) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+        self.send(
+            &promise,
+            |sender, origin| CacheStorageThreadMsg::Has(sender, origin, cache_name.into()),
+            |promise, _, has| promise.resolve_native(&has),
+        );
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-storage-open
+    fn Open(&self, cache_name: DOMString) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+        self.send(
+            &promise,
+            |sender, origin| CacheStorageThreadMsg::Open(sender, origin, cache_name.into()),
+            |promise, origin, id| {
+                promise.resolve_native(&Cache::new(&promise.global(), origin, id))
+            },
+        );
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-storage-delete
+    fn Delete(&self, cache_name: DOMString) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+        self.send(
+            &promise,
+            |sender, origin| CacheStorageThreadMsg::Delete(sender, origin, cache_name.into()),
+            |promise, _, deleted| promise.resolve_native(&deleted),
+        );
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-storage-keys
+    fn Keys(&self) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+        self.send(
+            &promise,
+            |sender, origin| CacheStorageThreadMsg::Keys(sender, origin),
+            |promise, _, names| {
+                let names: Vec<DOMString> = names.into_iter().map(DOMString::from).collect();
+                promise.resolve_native(&names);
+            },
+        );
+        promise
+    }
+}
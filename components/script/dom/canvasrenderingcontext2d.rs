@@ -9,6 +9,8 @@ use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::Ca
 use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::CanvasLineCap;
 use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::CanvasLineJoin;
 use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::CanvasRenderingContext2DMethods;
+use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::ImageSmoothingQuality;
+use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::DOMMatrixInit;
 use crate::dom::bindings::codegen::Bindings::ImageDataBinding::ImageDataMethods;
 use crate::dom::bindings::codegen::UnionTypes::StringOrCanvasGradientOrCanvasPattern;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
@@ -19,6 +21,8 @@ use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::canvasgradient::{CanvasGradient, CanvasGradientStyle, ToFillOrStrokeStyle};
 use crate::dom::canvaspattern::CanvasPattern;
+use crate::dom::dommatrix::DOMMatrix;
+use crate::dom::dommatrixreadonly::dommatrixinit_to_matrix;
 use crate::dom::element::Element;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlcanvaselement::{CanvasContext, HTMLCanvasElement};
@@ -27,12 +31,13 @@ use crate::dom::node::{window_from_node, Node, NodeDamage};
 use crate::unpremultiplytable::UNPREMULTIPLY_TABLE;
 use canvas_traits::canvas::{Canvas2dMsg, CanvasId, CanvasMsg};
 use canvas_traits::canvas::{CompositionOrBlending, FillOrStrokeStyle, FillRule};
+use canvas_traits::canvas::{FilterFunction, SmoothingQuality};
 use canvas_traits::canvas::{LineCapStyle, LineJoinStyle, LinearGradientStyle};
 use canvas_traits::canvas::{RadialGradientStyle, RepetitionStyle};
 use cssparser::Color as CSSColor;
 use cssparser::{Parser, ParserInput, RGBA};
 use dom_struct::dom_struct;
-use euclid::{vec2, Point2D, Rect, Size2D, Transform2D};
+use euclid::{vec2, Point2D, Rect, Size2D, Transform2D, Transform3D};
 use ipc_channel::ipc::{self, IpcSender};
 use net_traits::image_cache::CanRequestImages;
 use net_traits::image_cache::ImageCache;
@@ -48,6 +53,13 @@ use std::cell::Cell;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::{fmt, mem};
+use style::context::QuirksMode;
+use style::parser::{Parse, ParserContext};
+use style::stylesheets::{CssRuleType, Origin};
+use style::values::generics::effects::Filter as GenericFilter;
+use style::values::specified::effects::{Filter, SimpleShadow};
+use style::values::specified::length::Length;
+use style_traits::{ParseError, ParsingMode};
 
 #[must_root]
 #[derive(Clone, JSTraceable, MallocSizeOf)]
@@ -86,6 +98,7 @@ struct CanvasContextState {
     global_alpha: f64,
     global_composition: CompositionOrBlending,
     image_smoothing_enabled: bool,
+    image_smoothing_quality: SmoothingQuality,
     fill_style: CanvasFillOrStrokeStyle,
     stroke_style: CanvasFillOrStrokeStyle,
     line_width: f64,
@@ -97,6 +110,7 @@ struct CanvasContextState {
     shadow_offset_y: f64,
     shadow_blur: f64,
     shadow_color: RGBA,
+    filter: DOMString,
 }
 
 impl CanvasContextState {
@@ -106,6 +120,7 @@ impl CanvasContextState {
             global_alpha: 1.0,
             global_composition: CompositionOrBlending::default(),
             image_smoothing_enabled: true,
+            image_smoothing_quality: SmoothingQuality::Low,
             fill_style: CanvasFillOrStrokeStyle::Color(black),
             stroke_style: CanvasFillOrStrokeStyle::Color(black),
             line_width: 1.0,
@@ -117,6 +132,7 @@ impl CanvasContextState {
             shadow_offset_y: 0.0,
             shadow_blur: 0.0,
             shadow_color: RGBA::transparent(),
+            filter: DOMString::from("none"),
         }
     }
 }
@@ -497,29 +513,7 @@ impl CanvasRenderingContext2D {
         if parser.is_exhausted() {
             match color {
                 Ok(CSSColor::RGBA(rgba)) => Ok(rgba),
-                Ok(CSSColor::CurrentColor) => {
-                    // TODO: https://github.com/whatwg/html/issues/1099
-                    // Reconsider how to calculate currentColor in a display:none canvas
-
-                    // TODO: will need to check that the context bitmap mode is fixed
-                    // once we implement CanvasProxy
-                    let canvas = match self.canvas {
-                        // https://drafts.css-houdini.org/css-paint-api/#2d-rendering-context
-                        // Whenever "currentColor" is used as a color in the PaintRenderingContext2D API,
-                        // it is treated as opaque black.
-                        None => return Ok(RGBA::new(0, 0, 0, 255)),
-                        Some(ref canvas) => &**canvas,
-                    };
-
-                    let canvas_element = canvas.upcast::<Element>();
-
-                    match canvas_element.style() {
-                        Some(ref s) if canvas_element.has_css_layout_box() => {
-                            Ok(s.get_color().color)
-                        },
-                        _ => Ok(RGBA::new(0, 0, 0, 255)),
-                    }
-                },
+                Ok(CSSColor::CurrentColor) => Ok(self.current_color()),
                 _ => Err(()),
             }
         } else {
@@ -527,6 +521,95 @@ impl CanvasRenderingContext2D {
         }
     }
 
+    fn current_color(&self) -> RGBA {
+        // TODO: https://github.com/whatwg/html/issues/1099
+        // Reconsider how to calculate currentColor in a display:none canvas
+
+        // TODO: will need to check that the context bitmap mode is fixed
+        // once we implement CanvasProxy
+        let canvas = match self.canvas {
+            // https://drafts.css-houdini.org/css-paint-api/#2d-rendering-context
+            // Whenever "currentColor" is used as a color in the PaintRenderingContext2D API,
+            // it is treated as opaque black.
+            None => return RGBA::new(0, 0, 0, 255),
+            Some(ref canvas) => &**canvas,
+        };
+
+        let canvas_element = canvas.upcast::<Element>();
+
+        match canvas_element.style() {
+            Some(ref s) if canvas_element.has_css_layout_box() => s.get_color().color,
+            _ => RGBA::new(0, 0, 0, 255),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-filter
+    fn parse_filter(&self, string: &str) -> Result<Vec<FilterFunction>, ()> {
+        let mut input = ParserInput::new(string);
+        let mut parser = Parser::new(&mut input);
+        if parser
+            .r#try(|parser| parser.expect_ident_matching("none"))
+            .is_ok()
+        {
+            return if parser.is_exhausted() {
+                Ok(vec![])
+            } else {
+                Err(())
+            };
+        }
+
+        let context = ParserContext::new(
+            Origin::Author,
+            &self.base_url,
+            Some(CssRuleType::Style),
+            ParsingMode::DEFAULT,
+            QuirksMode::NoQuirks,
+            None,
+            None,
+        );
+        let mut functions = vec![];
+        while !parser.is_exhausted() {
+            // Servo's style system doesn't support drop-shadow() in the filter
+            // property, so it is parsed here.
+            let shadow = parser.r#try::<_, _, ParseError>(|parser| {
+                parser.expect_function_matching("drop-shadow")?;
+                parser.parse_nested_block(|parser| SimpleShadow::parse(&context, parser))
+            });
+            let function = match shadow {
+                Ok(shadow) => self.drop_shadow_to_filter_function(&shadow)?,
+                Err(_) => {
+                    let filter = Filter::parse(&context, &mut parser).map_err(|_| ())?;
+                    filter_to_filter_function(&filter)?
+                },
+            };
+            functions.push(function);
+        }
+
+        if functions.is_empty() {
+            return Err(());
+        }
+        Ok(functions)
+    }
+
+    fn drop_shadow_to_filter_function(&self, shadow: &SimpleShadow) -> Result<FilterFunction, ()> {
+        let offset = vec2(
+            length_to_px(&shadow.horizontal)?,
+            length_to_px(&shadow.vertical)?,
+        );
+        let blur = match shadow.blur {
+            Some(ref blur) => length_to_px(&blur.0)?,
+            None => 0.,
+        };
+        let color = match shadow.color {
+            Some(ref color) => color
+                .to_computed_color(None)
+                .ok_or(())?
+                .to_rgba(self.current_color()),
+            None => self.current_color(),
+        };
+        Ok(FilterFunction::DropShadow(offset, blur, color))
+    }
+
     pub fn get_canvas_id(&self) -> CanvasId {
         self.canvas_id.clone()
     }
@@ -689,8 +772,32 @@ impl CanvasRenderingContext2DMethods for CanvasRenderingContext2D {
         self.update_transform()
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-gettransform
+    fn GetTransform(&self) -> DomRoot<DOMMatrix> {
+        let transform = self.state.borrow().transform;
+        let matrix = Transform3D::row_major(
+            transform.m11 as f64,
+            transform.m12 as f64,
+            0.0,
+            0.0,
+            transform.m21 as f64,
+            transform.m22 as f64,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+            0.0,
+            transform.m31 as f64,
+            transform.m32 as f64,
+            0.0,
+            1.0,
+        );
+        DOMMatrix::new(&self.global(), true, matrix)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-settransform
-    fn SetTransform(&self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) {
+    fn SetTransform(&self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> ErrorResult {
         if !(a.is_finite() &&
             b.is_finite() &&
             c.is_finite() &&
@@ -698,12 +805,35 @@ impl CanvasRenderingContext2DMethods for CanvasRenderingContext2D {
             e.is_finite() &&
             f.is_finite())
         {
-            return;
+            return Ok(());
         }
 
         self.state.borrow_mut().transform =
             Transform2D::row_major(a as f32, b as f32, c as f32, d as f32, e as f32, f as f32);
-        self.update_transform()
+        self.update_transform();
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-settransform-matrix
+    fn SetTransform_(&self, transform: &DOMMatrixInit) -> ErrorResult {
+        // Step 1.
+        let (_, matrix) = dommatrixinit_to_matrix(transform)?;
+
+        // Step 2.
+        if !(matrix.m11.is_finite() &&
+            matrix.m12.is_finite() &&
+            matrix.m21.is_finite() &&
+            matrix.m22.is_finite() &&
+            matrix.m41.is_finite() &&
+            matrix.m42.is_finite())
+        {
+            return Ok(());
+        }
+
+        // Step 3.
+        self.SetTransform(
+            matrix.m11, matrix.m12, matrix.m21, matrix.m22, matrix.m41, matrix.m42,
+        )
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-resettransform
@@ -810,6 +940,52 @@ impl CanvasRenderingContext2DMethods for CanvasRenderingContext2D {
         receiver.recv().unwrap()
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-ispointinstroke
+    fn IsPointInStroke(&self, x: f64, y: f64) -> bool {
+        if !(x.is_finite() && y.is_finite()) {
+            return false;
+        }
+
+        let (sender, receiver) =
+            profiled_ipc::channel::<bool>(self.global().time_profiler_chan().clone()).unwrap();
+        self.send_canvas_2d_msg(Canvas2dMsg::IsPointInStroke(x, y, sender));
+        receiver.recv().unwrap()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-drawfocusifneeded
+    fn DrawFocusIfNeeded(&self, element: &Element) {
+        // Step 1.
+        let canvas = match self.canvas {
+            Some(ref canvas) => canvas,
+            None => return,
+        };
+        if !element.focus_state() ||
+            !canvas
+                .upcast::<Node>()
+                .is_ancestor_of(element.upcast::<Node>())
+        {
+            return;
+        }
+
+        // Step 2. Draw a focus ring around the current path, unaffected by
+        // shadows, filters, global alpha and compositing operators.
+        let focus_ring_color = RGBA::new(0x3b, 0x99, 0xfc, 255);
+        self.send_canvas_2d_msg(Canvas2dMsg::SaveContext);
+        self.send_canvas_2d_msg(Canvas2dMsg::SetShadowColor(RGBA::transparent()));
+        self.send_canvas_2d_msg(Canvas2dMsg::SetFilter(vec![]));
+        self.send_canvas_2d_msg(Canvas2dMsg::SetGlobalAlpha(1.0));
+        self.send_canvas_2d_msg(Canvas2dMsg::SetGlobalComposition(
+            CompositionOrBlending::default(),
+        ));
+        self.send_canvas_2d_msg(Canvas2dMsg::SetLineWidth(2.0));
+        self.send_canvas_2d_msg(Canvas2dMsg::SetStrokeStyle(FillOrStrokeStyle::Color(
+            focus_ring_color,
+        )));
+        self.send_canvas_2d_msg(Canvas2dMsg::Stroke);
+        self.send_canvas_2d_msg(Canvas2dMsg::RestoreContext);
+        self.mark_as_dirty();
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-filltext
     fn FillText(&self, text: DOMString, x: f64, y: f64, max_width: Option<f64>) {
         let parsed_text: String = text.into();
@@ -1018,6 +1194,26 @@ impl CanvasRenderingContext2DMethods for CanvasRenderingContext2D {
         self.state.borrow_mut().image_smoothing_enabled = value;
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-imagesmoothingquality
+    fn ImageSmoothingQuality(&self) -> ImageSmoothingQuality {
+        match self.state.borrow().image_smoothing_quality {
+            SmoothingQuality::Low => ImageSmoothingQuality::Low,
+            SmoothingQuality::Medium => ImageSmoothingQuality::Medium,
+            SmoothingQuality::High => ImageSmoothingQuality::High,
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-imagesmoothingquality
+    fn SetImageSmoothingQuality(&self, value: ImageSmoothingQuality) {
+        let quality = match value {
+            ImageSmoothingQuality::Low => SmoothingQuality::Low,
+            ImageSmoothingQuality::Medium => SmoothingQuality::Medium,
+            ImageSmoothingQuality::High => SmoothingQuality::High,
+        };
+        self.state.borrow_mut().image_smoothing_quality = quality;
+        self.send_canvas_2d_msg(Canvas2dMsg::SetImageSmoothingQuality(quality));
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-strokestyle
     fn StrokeStyle(&self) -> StringOrCanvasGradientOrCanvasPattern {
         match self.state.borrow().stroke_style {
@@ -1453,6 +1649,19 @@ impl CanvasRenderingContext2DMethods for CanvasRenderingContext2D {
             self.send_canvas_2d_msg(Canvas2dMsg::SetShadowColor(color))
         }
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-filter
+    fn Filter(&self) -> DOMString {
+        self.state.borrow().filter.clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-filter
+    fn SetFilter(&self, value: DOMString) {
+        if let Ok(filter) = self.parse_filter(&value) {
+            self.state.borrow_mut().filter = value;
+            self.send_canvas_2d_msg(Canvas2dMsg::SetFilter(filter))
+        }
+    }
 }
 
 impl Drop for CanvasRenderingContext2D {
@@ -1466,6 +1675,34 @@ impl Drop for CanvasRenderingContext2D {
     }
 }
 
+/// Resolves a filter function parsed by the style system. Lengths which
+/// can't be resolved without a computed style make the whole filter invalid.
+fn filter_to_filter_function(filter: &Filter) -> Result<FilterFunction, ()> {
+    Ok(match *filter {
+        GenericFilter::Blur(ref length) => FilterFunction::Blur(length_to_px(&length.0)?),
+        GenericFilter::Brightness(ref factor) => FilterFunction::Brightness(factor.to_number()),
+        GenericFilter::Contrast(ref factor) => FilterFunction::Contrast(factor.to_number()),
+        GenericFilter::Grayscale(ref factor) => FilterFunction::Grayscale(factor.to_number()),
+        GenericFilter::HueRotate(ref angle) => {
+            FilterFunction::HueRotate(angle.degrees().to_radians())
+        },
+        GenericFilter::Invert(ref factor) => FilterFunction::Invert(factor.to_number()),
+        GenericFilter::Opacity(ref factor) => FilterFunction::Opacity(factor.to_number()),
+        GenericFilter::Saturate(ref factor) => FilterFunction::Saturate(factor.to_number()),
+        GenericFilter::Sepia(ref factor) => FilterFunction::Sepia(factor.to_number()),
+        GenericFilter::DropShadow(ref shadow) => match *shadow {},
+        GenericFilter::Url(ref url) => match *url {},
+    })
+}
+
+fn length_to_px(length: &Length) -> Result<f32, ()> {
+    // TODO: Resolve font-relative lengths against the context's font.
+    match *length {
+        Length::NoCalc(ref length) => length.to_computed_pixel_length_without_context(),
+        Length::Calc(..) => Err(()),
+    }
+}
+
 pub fn parse_color(string: &str) -> Result<RGBA, ()> {
     let mut input = ParserInput::new(string);
     let mut parser = Parser::new(&mut input);
@@ -4,15 +4,17 @@
 
 use crate::dom::bindings::codegen::Bindings::ClientBinding::FrameType;
 use crate::dom::bindings::codegen::Bindings::ClientBinding::{ClientMethods, Wrap};
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::serviceworker::ServiceWorker;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
+use msg::constellation_msg::PipelineId;
 use servo_url::ServoUrl;
 use std::default::Default;
-use uuid::Uuid;
 
 #[dom_struct]
 pub struct Client {
@@ -20,24 +22,26 @@ pub struct Client {
     active_worker: MutNullableDom<ServiceWorker>,
     url: ServoUrl,
     frame_type: FrameType,
-    #[ignore_malloc_size_of = "Defined in uuid"]
-    id: Uuid,
+    pipeline_id: PipelineId,
 }
 
 impl Client {
-    fn new_inherited(url: ServoUrl) -> Client {
+    fn new_inherited(url: ServoUrl, pipeline_id: PipelineId) -> Client {
         Client {
             reflector_: Reflector::new(),
             active_worker: Default::default(),
             url: url,
             frame_type: FrameType::None,
-            id: Uuid::new_v4(),
+            pipeline_id: pipeline_id,
         }
     }
 
     pub fn new(window: &Window) -> DomRoot<Client> {
         reflect_dom_object(
-            Box::new(Client::new_inherited(window.get_url())),
+            Box::new(Client::new_inherited(
+                window.get_url(),
+                window.upcast::<GlobalScope>().pipeline_id(),
+            )),
             window,
             Wrap,
         )
@@ -69,7 +73,12 @@ impl ClientMethods for Client {
 
     // https://w3c.github.io/ServiceWorker/#client-id
     fn Id(&self) -> DOMString {
-        let uid_str = format!("{}", self.id);
-        DOMString::from_string(uid_str)
+        client_id(self.pipeline_id)
     }
 }
+
+/// The id of the client of a pipeline, as seen by its `Client` objects and by the
+/// fetch events of its requests.
+pub fn client_id(pipeline_id: PipelineId) -> DOMString {
+    DOMString::from_string(pipeline_id.to_string())
+}
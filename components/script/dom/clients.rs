@@ -0,0 +1,62 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::ClientsBinding::{self, ClientsMethods};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::promise::Promise;
+use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use script_traits::{ServiceWorkerId, ServiceWorkerMsg};
+use std::rc::Rc;
+
+// https://w3c.github.io/ServiceWorker/#clients-interface
+#[dom_struct]
+pub struct Clients {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    swmanager_sender: IpcSender<ServiceWorkerMsg>,
+    worker_id: ServiceWorkerId,
+}
+
+impl Clients {
+    fn new_inherited(
+        swmanager_sender: IpcSender<ServiceWorkerMsg>,
+        worker_id: ServiceWorkerId,
+    ) -> Clients {
+        Clients {
+            reflector_: Reflector::new(),
+            swmanager_sender: swmanager_sender,
+            worker_id: worker_id,
+        }
+    }
+
+    pub fn new(global: &ServiceWorkerGlobalScope) -> DomRoot<Clients> {
+        reflect_dom_object(
+            Box::new(Clients::new_inherited(
+                global.swmanager_sender(),
+                global.worker_id(),
+            )),
+            global,
+            ClientsBinding::Wrap,
+        )
+    }
+}
+
+impl ClientsMethods for Clients {
+    // https://w3c.github.io/ServiceWorker/#clients-claim
+    fn Claim(&self) -> Rc<Promise> {
+        // Step 1
+        let promise = Promise::new(&self.global());
+        // Step 2-3, the service worker manager ignores claims of workers that are not
+        // active, and makes the matching clients controlled by this worker
+        let _ = self
+            .swmanager_sender
+            .send(ServiceWorkerMsg::Claim(self.worker_id));
+        // Step 4
+        promise.resolve_native(&());
+        promise
+    }
+}
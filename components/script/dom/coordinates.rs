@@ -0,0 +1,73 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CoordinatesBinding::{self, CoordinatesMethods};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use embedder_traits::GeolocationPosition;
+
+// https://w3c.github.io/geolocation-api/#coordinates_interface
+#[dom_struct]
+pub struct Coordinates {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    position: GeolocationPosition,
+}
+
+impl Coordinates {
+    fn new_inherited(position: GeolocationPosition) -> Coordinates {
+        Coordinates {
+            reflector_: Reflector::new(),
+            position: position,
+        }
+    }
+
+    pub fn new(window: &Window, position: GeolocationPosition) -> DomRoot<Coordinates> {
+        reflect_dom_object(
+            Box::new(Coordinates::new_inherited(position)),
+            window,
+            CoordinatesBinding::Wrap,
+        )
+    }
+}
+
+impl CoordinatesMethods for Coordinates {
+    // https://w3c.github.io/geolocation-api/#lat
+    fn Latitude(&self) -> Finite<f64> {
+        Finite::wrap(self.position.latitude)
+    }
+
+    // https://w3c.github.io/geolocation-api/#lon
+    fn Longitude(&self) -> Finite<f64> {
+        Finite::wrap(self.position.longitude)
+    }
+
+    // https://w3c.github.io/geolocation-api/#altitude
+    fn GetAltitude(&self) -> Option<Finite<f64>> {
+        self.position.altitude.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/geolocation-api/#accuracy
+    fn Accuracy(&self) -> Finite<f64> {
+        Finite::wrap(self.position.accuracy)
+    }
+
+    // https://w3c.github.io/geolocation-api/#altitude-accuracy
+    fn GetAltitudeAccuracy(&self) -> Option<Finite<f64>> {
+        self.position.altitude_accuracy.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/geolocation-api/#heading
+    fn GetHeading(&self) -> Option<Finite<f64>> {
+        self.position.heading.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/geolocation-api/#speed
+    fn GetSpeed(&self) -> Option<Finite<f64>> {
+        self.position.speed.and_then(Finite::new)
+    }
+}
@@ -26,6 +26,7 @@ use crate::dom::domexception::{DOMErrorName, DOMException};
 use crate::dom::element::{CustomElementState, Element};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
+use crate::dom::htmlformelement::{FormControlElementHelpers, HTMLFormElement};
 use crate::dom::node::{document_from_node, window_from_node, Node};
 use crate::dom::promise::Promise;
 use crate::dom::window::Window;
@@ -37,7 +38,7 @@ use js::conversions::ToJSValConvertible;
 use js::glue::UnwrapObject;
 use js::jsapi::{HandleValueArray, Heap, IsCallable, IsConstructor};
 use js::jsapi::{JSAutoCompartment, JSContext, JSObject};
-use js::jsval::{JSVal, NullValue, ObjectValue, UndefinedValue};
+use js::jsval::{BooleanValue, JSVal, NullValue, ObjectValue, UndefinedValue};
 use js::rust::wrappers::{Construct1, JS_GetProperty, JS_SameValue};
 use js::rust::{HandleObject, HandleValue, MutableHandleValue};
 use std::cell::Cell;
@@ -158,9 +159,95 @@ impl CustomElementRegistry {
             disconnected_callback: get_callback(cx, prototype, b"disconnectedCallback\0")?,
             adopted_callback: get_callback(cx, prototype, b"adoptedCallback\0")?,
             attribute_changed_callback: get_callback(cx, prototype, b"attributeChangedCallback\0")?,
+            form_associated_callback: None,
+            form_reset_callback: None,
+            form_disabled_callback: None,
         })
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-customelementregistry-define>
+    /// Step 10.10
+    #[allow(unsafe_code)]
+    unsafe fn add_form_associated_callbacks(
+        &self,
+        prototype: HandleObject,
+        callbacks: &mut LifecycleCallbacks,
+    ) -> ErrorResult {
+        let cx = self.window.get_cx();
+
+        callbacks.form_associated_callback =
+            get_callback(cx, prototype, b"formAssociatedCallback\0")?;
+        callbacks.form_reset_callback = get_callback(cx, prototype, b"formResetCallback\0")?;
+        callbacks.form_disabled_callback = get_callback(cx, prototype, b"formDisabledCallback\0")?;
+
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-customelementregistry-define>
+    /// Steps 10.7 - 10.8
+    #[allow(unsafe_code)]
+    fn get_disabled_features(&self, constructor: HandleObject) -> Fallible<Vec<DOMString>> {
+        let cx = self.window.get_cx();
+        rooted!(in(cx) let mut disabled_features = UndefinedValue());
+        if unsafe {
+            !JS_GetProperty(
+                cx,
+                constructor,
+                b"disabledFeatures\0".as_ptr() as *const _,
+                disabled_features.handle_mut(),
+            )
+        } {
+            return Err(Error::JSFailed);
+        }
+
+        if disabled_features.is_undefined() {
+            return Ok(Vec::new());
+        }
+
+        let conversion = unsafe {
+            FromJSValConvertible::from_jsval(
+                cx,
+                disabled_features.handle(),
+                StringificationBehavior::Default,
+            )
+        };
+        match conversion {
+            Ok(ConversionResult::Success(features)) => Ok(features),
+            Ok(ConversionResult::Failure(error)) => Err(Error::Type(error.into())),
+            _ => Err(Error::JSFailed),
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-customelementregistry-define>
+    /// Step 10.9
+    #[allow(unsafe_code)]
+    fn get_form_associated_value(&self, constructor: HandleObject) -> Fallible<bool> {
+        let cx = self.window.get_cx();
+        rooted!(in(cx) let mut form_associated_value = UndefinedValue());
+        if unsafe {
+            !JS_GetProperty(
+                cx,
+                constructor,
+                b"formAssociated\0".as_ptr() as *const _,
+                form_associated_value.handle_mut(),
+            )
+        } {
+            return Err(Error::JSFailed);
+        }
+
+        if form_associated_value.is_undefined() {
+            return Ok(false);
+        }
+
+        let conversion =
+            unsafe { FromJSValConvertible::from_jsval(cx, form_associated_value.handle(), ()) };
+        match conversion {
+            Ok(ConversionResult::Success(flag)) => Ok(flag),
+            Ok(ConversionResult::Failure(error)) => Err(Error::Type(error.into())),
+            _ => Err(Error::JSFailed),
+        }
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#dom-customelementregistry-define>
     /// Step 10.6
     #[allow(unsafe_code)]
@@ -317,7 +404,7 @@ impl CustomElementRegistryMethods for CustomElementRegistry {
 
         // Steps 10.3 - 10.4
         rooted!(in(cx) let proto_object = prototype.to_object());
-        let callbacks = {
+        let mut callbacks = {
             let _ac = JSAutoCompartment::new(cx, proto_object.get());
             match unsafe { self.get_callbacks(proto_object.handle()) } {
                 Ok(callbacks) => callbacks,
@@ -342,6 +429,42 @@ impl CustomElementRegistryMethods for CustomElementRegistry {
             Vec::new()
         };
 
+        // Steps 10.7 - 10.9
+        let (disable_internals, form_associated) = {
+            let _ac = JSAutoCompartment::new(cx, constructor.get());
+            let disabled_features = match self.get_disabled_features(constructor.handle()) {
+                Ok(features) => features,
+                Err(error) => {
+                    self.element_definition_is_running.set(false);
+                    return Err(error);
+                },
+            };
+            let form_associated = match self.get_form_associated_value(constructor.handle()) {
+                Ok(flag) => flag,
+                Err(error) => {
+                    self.element_definition_is_running.set(false);
+                    return Err(error);
+                },
+            };
+            (
+                disabled_features
+                    .iter()
+                    .any(|feature| feature == "internals"),
+                form_associated,
+            )
+        };
+
+        // Step 10.10
+        if form_associated {
+            let _ac = JSAutoCompartment::new(cx, proto_object.get());
+            if let Err(error) =
+                unsafe { self.add_form_associated_callbacks(proto_object.handle(), &mut callbacks) }
+            {
+                self.element_definition_is_running.set(false);
+                return Err(error);
+            }
+        }
+
         self.element_definition_is_running.set(false);
 
         // Step 11
@@ -351,6 +474,8 @@ impl CustomElementRegistryMethods for CustomElementRegistry {
             constructor_,
             observed_attributes,
             callbacks,
+            form_associated,
+            disable_internals,
         ));
 
         // Step 12
@@ -445,6 +570,15 @@ pub struct LifecycleCallbacks {
 
     #[ignore_malloc_size_of = "Rc"]
     attribute_changed_callback: Option<Rc<Function>>,
+
+    #[ignore_malloc_size_of = "Rc"]
+    form_associated_callback: Option<Rc<Function>>,
+
+    #[ignore_malloc_size_of = "Rc"]
+    form_reset_callback: Option<Rc<Function>>,
+
+    #[ignore_malloc_size_of = "Rc"]
+    form_disabled_callback: Option<Rc<Function>>,
 }
 
 #[derive(Clone, JSTraceable, MallocSizeOf)]
@@ -468,6 +602,12 @@ pub struct CustomElementDefinition {
     pub callbacks: LifecycleCallbacks,
 
     pub construction_stack: DomRefCell<Vec<ConstructionStackEntry>>,
+
+    /// <https://html.spec.whatwg.org/multipage/#concept-custom-element-definition-form-associated>
+    pub form_associated: bool,
+
+    /// <https://html.spec.whatwg.org/multipage/#concept-custom-element-definition-disable-internals>
+    pub disable_internals: bool,
 }
 
 impl CustomElementDefinition {
@@ -477,6 +617,8 @@ impl CustomElementDefinition {
         constructor: Rc<CustomElementConstructor>,
         observed_attributes: Vec<DOMString>,
         callbacks: LifecycleCallbacks,
+        form_associated: bool,
+        disable_internals: bool,
     ) -> CustomElementDefinition {
         CustomElementDefinition {
             name: name,
@@ -485,6 +627,8 @@ impl CustomElementDefinition {
             observed_attributes: observed_attributes,
             callbacks: callbacks,
             construction_stack: Default::default(),
+            form_associated: form_associated,
+            disable_internals: disable_internals,
         }
     }
 
@@ -590,6 +734,9 @@ pub fn upgrade_element(definition: Rc<CustomElementDefinition>, element: &Elemen
         .borrow_mut()
         .push(ConstructionStackEntry::Element(DomRoot::from_ref(element)));
 
+    // The constructor is allowed to attach the element internals from now on.
+    element.set_custom_element_state(CustomElementState::Precustomized);
+
     // Step 7
     let result = run_upgrade_constructor(&definition.constructor, element);
 
@@ -617,7 +764,26 @@ pub fn upgrade_element(definition: Rc<CustomElementDefinition>, element: &Elemen
     element.set_custom_element_state(CustomElementState::Custom);
 
     // Step 9
-    element.set_custom_element_definition(definition);
+    element.set_custom_element_definition(definition.clone());
+
+    // Step 10
+    if definition.form_associated {
+        // Step 10.1
+        if let Some(control) = element.as_maybe_form_control() {
+            control.reset_form_owner();
+        }
+
+        // Step 10.2
+        element.check_disabled_attribute();
+        element.check_ancestors_disabled_state_for_form_control();
+        if element.disabled_state() {
+            ScriptThread::enqueue_callback_reaction(
+                element,
+                CallbackReaction::FormDisabled(true),
+                Some(definition),
+            );
+        }
+    }
 }
 
 /// <https://html.spec.whatwg.org/multipage/#concept-upgrade-an-element>
@@ -721,6 +887,9 @@ pub enum CallbackReaction {
     Disconnected,
     Adopted(DomRoot<Document>, DomRoot<Document>),
     AttributeChanged(LocalName, Option<DOMString>, Option<DOMString>, Namespace),
+    FormAssociated(Option<DomRoot<HTMLFormElement>>),
+    FormReset,
+    FormDisabled(bool),
 }
 
 /// <https://html.spec.whatwg.org/multipage/#processing-the-backup-element-queue>
@@ -884,6 +1053,21 @@ impl CustomElementReactionStack {
                     args,
                 )
             },
+            CallbackReaction::FormAssociated(ref form) => {
+                let args = vec![Heap::default()];
+                args[0].set(form.as_ref().map_or(NullValue(), |form| {
+                    ObjectValue(form.reflector().get_jsobject().get())
+                }));
+                (definition.callbacks.form_associated_callback.clone(), args)
+            },
+            CallbackReaction::FormReset => {
+                (definition.callbacks.form_reset_callback.clone(), Vec::new())
+            },
+            CallbackReaction::FormDisabled(disabled) => {
+                let args = vec![Heap::default()];
+                args[0].set(BooleanValue(disabled));
+                (definition.callbacks.form_disabled_callback.clone(), args)
+            },
         };
 
         // Step 3
@@ -0,0 +1,85 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::CustomStateSetBinding::{self, CustomStateSetMethods};
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+/// <https://wicg.github.io/custom-state-pseudo-class/#customstateset>
+/// The states are only exposed to script, the `:--state` pseudo-class is not supported
+/// by the style system yet.
+#[dom_struct]
+pub struct CustomStateSet {
+    reflector_: Reflector,
+    states: DomRefCell<Vec<DOMString>>,
+}
+
+impl CustomStateSet {
+    fn new_inherited() -> CustomStateSet {
+        CustomStateSet {
+            reflector_: Reflector::new(),
+            states: DomRefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<CustomStateSet> {
+        reflect_dom_object(
+            Box::new(CustomStateSet::new_inherited()),
+            window,
+            CustomStateSetBinding::Wrap,
+        )
+    }
+
+    pub fn contains(&self, state: &str) -> bool {
+        self.states.borrow().iter().any(|s| &**s == state)
+    }
+}
+
+impl CustomStateSetMethods for CustomStateSet {
+    // https://wicg.github.io/custom-state-pseudo-class/#customstateset
+    fn Size(&self) -> u32 {
+        self.states.borrow().len() as u32
+    }
+
+    // https://wicg.github.io/custom-state-pseudo-class/#customstateset
+    fn Has(&self, value: DOMString) -> bool {
+        self.contains(&value)
+    }
+
+    // https://wicg.github.io/custom-state-pseudo-class/#dom-customstateset-add
+    fn Add(&self, value: DOMString) -> ErrorResult {
+        // Step 1
+        if !value.starts_with("--") {
+            return Err(Error::Syntax);
+        }
+
+        // Step 2
+        if !self.contains(&value) {
+            self.states.borrow_mut().push(value);
+        }
+        Ok(())
+    }
+
+    // https://wicg.github.io/custom-state-pseudo-class/#customstateset
+    fn Delete(&self, value: DOMString) -> bool {
+        let position = self.states.borrow().iter().position(|s| *s == value);
+        match position {
+            Some(index) => {
+                self.states.borrow_mut().remove(index);
+                true
+            },
+            None => false,
+        }
+    }
+
+    // https://wicg.github.io/custom-state-pseudo-class/#customstateset
+    fn Clear(&self) {
+        self.states.borrow_mut().clear();
+    }
+}
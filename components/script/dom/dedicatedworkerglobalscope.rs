@@ -26,6 +26,7 @@ use crate::script_runtime::ScriptThreadEventCategory::WorkerEvent;
 use crate::script_runtime::{new_child_runtime, CommonScriptMsg, Runtime, ScriptChan, ScriptPort};
 use crate::task_queue::{QueuedTask, QueuedTaskConversion, TaskQueue};
 use crate::task_source::TaskSourceName;
+use canvas_traits::webgl::WebGLChan;
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::DevtoolScriptControlMsg;
 use dom_struct::dom_struct;
@@ -79,6 +80,10 @@ pub enum DedicatedWorkerScriptMsg {
     CommonWorker(TrustedWorkerAddress, WorkerScriptMsg),
     /// Wake-up call from the task queue.
     WakeUp,
+    /// The network went offline (`false`) or came back online (`true`).
+    NetworkStateChanged(bool),
+    /// The user's preferred languages changed.
+    LanguagesChanged,
 }
 
 pub enum MixedMessage {
@@ -171,6 +176,9 @@ pub struct DedicatedWorkerGlobalScope {
     #[ignore_malloc_size_of = "Can't measure trait objects"]
     /// Sender to the parent thread.
     parent_sender: Box<ScriptChan + Send>,
+    /// A handle for communicating messages to the WebGL thread, if available.
+    #[ignore_malloc_size_of = "channels are hard"]
+    webgl_chan: Option<WebGLChan>,
 }
 
 impl WorkerEventLoopMethods for DedicatedWorkerGlobalScope {
@@ -220,6 +228,7 @@ impl DedicatedWorkerGlobalScope {
         timer_event_chan: IpcSender<TimerEvent>,
         timer_event_port: Receiver<(TrustedWorkerAddress, TimerEvent)>,
         closing: Arc<AtomicBool>,
+        webgl_chan: Option<WebGLChan>,
     ) -> DedicatedWorkerGlobalScope {
         DedicatedWorkerGlobalScope {
             workerglobalscope: WorkerGlobalScope::new_inherited(
@@ -235,6 +244,7 @@ impl DedicatedWorkerGlobalScope {
             timer_event_port: timer_event_port,
             parent_sender: parent_sender,
             worker: DomRefCell::new(None),
+            webgl_chan,
         }
     }
 
@@ -250,6 +260,7 @@ impl DedicatedWorkerGlobalScope {
         timer_event_chan: IpcSender<TimerEvent>,
         timer_event_port: Receiver<(TrustedWorkerAddress, TimerEvent)>,
         closing: Arc<AtomicBool>,
+        webgl_chan: Option<WebGLChan>,
     ) -> DomRoot<DedicatedWorkerGlobalScope> {
         let cx = runtime.cx();
         let scope = Box::new(DedicatedWorkerGlobalScope::new_inherited(
@@ -263,6 +274,7 @@ impl DedicatedWorkerGlobalScope {
             timer_event_chan,
             timer_event_port,
             closing,
+            webgl_chan,
         ));
         unsafe { DedicatedWorkerGlobalScopeBinding::Wrap(cx, scope) }
     }
@@ -286,6 +298,7 @@ impl DedicatedWorkerGlobalScope {
         let current_global = GlobalScope::current().expect("No current global object");
         let origin = current_global.origin().immutable().clone();
         let parent = current_global.runtime_handle();
+        let webgl_chan = current_global.webgl_chan();
 
         thread::Builder::new()
             .name(name)
@@ -366,6 +379,7 @@ impl DedicatedWorkerGlobalScope {
                     timer_ipc_chan,
                     timer_rx,
                     closing,
+                    webgl_chan,
                 );
                 // FIXME(njn): workers currently don't have a unique ID suitable for using in reporter
                 // registration (#6631), so we instead use a random number and cross our fingers.
@@ -415,6 +429,10 @@ impl DedicatedWorkerGlobalScope {
         })
     }
 
+    pub fn webgl_chan(&self) -> Option<WebGLChan> {
+        self.webgl_chan.clone()
+    }
+
     pub fn new_script_pair(&self) -> (Box<dyn ScriptChan + Send>, Box<dyn ScriptPort + Send>) {
         let (tx, rx) = unbounded();
         let chan = Box::new(SendableWorkerScriptChan {
@@ -471,9 +489,35 @@ impl DedicatedWorkerGlobalScope {
                 self.handle_script_event(msg);
             },
             MixedMessage::FromWorker(DedicatedWorkerScriptMsg::WakeUp) => {},
+            MixedMessage::FromWorker(DedicatedWorkerScriptMsg::NetworkStateChanged(online)) => {
+                self.handle_network_state_changed(online);
+            },
+            MixedMessage::FromWorker(DedicatedWorkerScriptMsg::LanguagesChanged) => {
+                self.handle_languages_changed();
+            },
         }
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-navigator-online
+    fn handle_network_state_changed(&self, online: bool) {
+        let name = if online {
+            atom!("online")
+        } else {
+            atom!("offline")
+        };
+        self.upcast::<EventTarget>().fire_event(name);
+        self.upcast::<GlobalScope>()
+            .notify_workers_of_network_state(online);
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigator-languages
+    fn handle_languages_changed(&self) {
+        self.upcast::<EventTarget>()
+            .fire_event(atom!("languagechange"));
+        self.upcast::<GlobalScope>()
+            .notify_workers_of_language_change();
+    }
+
     // https://html.spec.whatwg.org/multipage/#runtime-script-errors-2
     #[allow(unsafe_code)]
     pub fn forward_error_to_worker_object(&self, error_info: ErrorInfo) {
@@ -170,6 +170,10 @@ const SPURIOUS_ANIMATION_FRAME_THRESHOLD: u8 = 5;
 /// The amount of time between fake `requestAnimationFrame()`s.
 const FAKE_REQUEST_ANIMATION_FRAME_DELAY: u64 = 16;
 
+/// The expected amount of time between two animation frames driven by the compositor, in
+/// nanoseconds.
+const ANIMATION_FRAME_INTERVAL_NS: u64 = 16_666_667;
+
 pub enum TouchEventResult {
     Processed(bool),
     Forwarded,
@@ -278,6 +282,9 @@ pub struct Document {
     url: DomRefCell<ServoUrl>,
     #[ignore_malloc_size_of = "defined in selectors"]
     quirks_mode: Cell<QuirksMode>,
+    /// The language matched by `:lang()` for elements without a `lang`
+    /// attribute: the user's most preferred language.
+    default_language: DomRefCell<String>,
     /// Caches for the getElement methods
     id_map: DomRefCell<HashMap<Atom, Vec<Dom<Element>>>>,
     tag_map: DomRefCell<HashMap<LocalName, Dom<HTMLCollection>>>,
@@ -324,6 +331,9 @@ pub struct Document {
     /// <https://html.spec.whatwg.org/multipage/#list-of-animation-frame-callbacks>
     /// List of animation frame callbacks
     animation_frame_list: DomRefCell<Vec<(u32, Option<AnimationFrameCallback>)>>,
+    /// The time at which the animation frame callbacks were last run, in the time base of
+    /// `time::precise_time_ns()`.
+    last_animation_frame_time: Cell<Option<u64>>,
     /// Whether we're in the process of running animation callbacks.
     ///
     /// Tracking this is not necessary for correctness. Instead, it is an optimization to avoid
@@ -979,6 +989,16 @@ impl Document {
         window.send_to_embedder(msg);
     }
 
+    /// Update the language matched by `:lang()` for elements without a
+    /// `lang` attribute, after the user's preferred languages changed.
+    pub fn set_default_language(&self, language: String) {
+        if *self.default_language.borrow() == language {
+            return;
+        }
+        *self.default_language.borrow_mut() = language;
+        self.dirty_all_nodes();
+    }
+
     pub fn dirty_all_nodes(&self) {
         let root = self.upcast::<Node>();
         for node in root.traverse_preorder() {
@@ -1464,7 +1484,7 @@ impl Document {
                             ActivationSource::NotFromClick,
                         )
                     }
-                }
+                },
                 Key::Enter if keyboard_event.state == KeyState::Up => {
                     let maybe_elem = target.downcast::<Element>();
                     if let Some(el) = maybe_elem {
@@ -1648,6 +1668,8 @@ impl Document {
         );
 
         self.running_animation_callbacks.set(true);
+        self.last_animation_frame_time
+            .set(Some(time::precise_time_ns()));
         let was_faking_animation_frames = self.is_faking_animation_frames();
         let timing = self.global().performance().Now();
 
@@ -1716,6 +1738,25 @@ impl Document {
         }
     }
 
+    /// The time, in the time base of `time::precise_time_ns()`, at which the next animation
+    /// frame is expected to run, if there are any pending animation frame callbacks.
+    pub fn next_animation_frame_deadline(&self) -> Option<u64> {
+        if self.animation_frame_list.borrow().is_empty() {
+            return None;
+        }
+        let interval = if self.is_faking_animation_frames() {
+            FAKE_REQUEST_ANIMATION_FRAME_DELAY * 1_000_000
+        } else {
+            ANIMATION_FRAME_INTERVAL_NS
+        };
+        let now = time::precise_time_ns();
+        let elapsed = self
+            .last_animation_frame_time
+            .get()
+            .map_or(0, |last| now.saturating_sub(last));
+        Some(now + interval - elapsed % interval)
+    }
+
     pub fn fetch_async(
         &self,
         load: LoadType,
@@ -2495,6 +2536,7 @@ pub trait LayoutDocumentHelpers {
     unsafe fn will_paint(&self);
     unsafe fn quirks_mode(&self) -> QuirksMode;
     unsafe fn style_shared_lock(&self) -> &StyleSharedRwLock;
+    unsafe fn default_language_for_layout(&self) -> &str;
 }
 
 #[allow(unsafe_code)]
@@ -2539,6 +2581,11 @@ impl LayoutDocumentHelpers for LayoutDom<Document> {
     unsafe fn style_shared_lock(&self) -> &StyleSharedRwLock {
         (*self.unsafe_get()).style_shared_lock()
     }
+
+    #[inline]
+    unsafe fn default_language_for_layout(&self) -> &str {
+        (*self.unsafe_get()).default_language.borrow_for_layout()
+    }
 }
 
 // https://html.spec.whatwg.org/multipage/#is-a-registrable-domain-suffix-of-or-is-equal-to
@@ -2657,6 +2704,7 @@ impl Document {
             url: DomRefCell::new(url),
             // https://dom.spec.whatwg.org/#concept-document-quirks
             quirks_mode: Cell::new(QuirksMode::NoQuirks),
+            default_language: DomRefCell::new(PREFS.languages().swap_remove(0)),
             // https://dom.spec.whatwg.org/#concept-document-encoding
             encoding: Cell::new(encoding),
             is_html_document: is_html_document == IsHTMLDocument::HTMLDocument,
@@ -2702,6 +2750,7 @@ impl Document {
             scripting_enabled: has_browsing_context == HasBrowsingContext::Yes,
             animation_frame_ident: Cell::new(0),
             animation_frame_list: DomRefCell::new(vec![]),
+            last_animation_frame_time: Cell::new(None),
             running_animation_callbacks: Cell::new(false),
             loader: DomRefCell::new(doc_loader),
             current_parser: Default::default(),
@@ -3736,7 +3785,7 @@ impl DocumentMethods for Document {
             ))),
             "uievent" | "uievents" => Ok(DomRoot::upcast(UIEvent::new_uninitialized(&self.window))),
             "webglcontextevent" => Ok(DomRoot::upcast(WebGLContextEvent::new_uninitialized(
-                &self.window,
+                self.window.upcast(),
             ))),
             _ => Err(Error::NotSupported),
         }
@@ -205,6 +205,7 @@ pub enum CustomElementState {
     Undefined,
     Failed,
     Uncustomized,
+    Precustomized,
     Custom,
 }
 
@@ -950,7 +951,11 @@ impl LayoutElementHelpers for LayoutDom<Element> {
             }
             // TODO: Check meta tags for a pragma-set default language
             // TODO: Check HTTP Content-Language header
-            String::new()
+            // Fall back to the user's preferred language.
+            self.upcast::<Node>()
+                .owner_doc_for_layout()
+                .default_language_for_layout()
+                .to_owned()
         }
     }
 
@@ -3033,6 +3038,14 @@ impl Element {
                 let element = self.downcast::<HTMLTextAreaElement>().unwrap();
                 Some(element as &dyn Validatable)
             },
+            NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLElement)) => {
+                let element = self.downcast::<HTMLElement>().unwrap();
+                if element.is_form_associated_custom_element() {
+                    Some(element as &dyn Validatable)
+                } else {
+                    None
+                }
+            },
             _ => None,
         };
         element
@@ -0,0 +1,310 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ElementInternalsBinding::{
+    self, ElementInternalsMethods, ValidityStateFlags,
+};
+use crate::dom::bindings::codegen::UnionTypes::FileOrUSVStringOrFormData;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::customstateset::CustomStateSet;
+use crate::dom::element::Element;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::file::File;
+use crate::dom::htmlelement::HTMLElement;
+use crate::dom::htmlformelement::{FormDatum, FormDatumValue, HTMLFormElement};
+use crate::dom::node::{window_from_node, Node};
+use crate::dom::nodelist::NodeList;
+use crate::dom::validation::Validatable;
+use crate::dom::validitystate::{ValidationFlags, ValidityState};
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+/// <https://html.spec.whatwg.org/multipage/#face-submission-value>
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+enum SubmissionValue {
+    None,
+    FormData(Vec<FormDatum>),
+    File(DomRoot<File>),
+    USVString(String),
+}
+
+impl From<Option<&FileOrUSVStringOrFormData>> for SubmissionValue {
+    fn from(value: Option<&FileOrUSVStringOrFormData>) -> Self {
+        match value {
+            None => SubmissionValue::None,
+            Some(FileOrUSVStringOrFormData::File(file)) => SubmissionValue::File(file.clone()),
+            Some(FileOrUSVStringOrFormData::USVString(string)) => {
+                SubmissionValue::USVString(string.0.clone())
+            },
+            Some(FileOrUSVStringOrFormData::FormData(form_data)) => {
+                SubmissionValue::FormData(form_data.datums())
+            },
+        }
+    }
+}
+
+/// <https://html.spec.whatwg.org/multipage/#elementinternals>
+///
+/// Every form-associated custom element has one of these to hold its form owner and
+/// form value, even before `attachInternals()` exposes it to script.
+#[dom_struct]
+pub struct ElementInternals {
+    reflector_: Reflector,
+    /// Whether `attachInternals()` was called on the target element.
+    attached: Cell<bool>,
+    target_element: Dom<HTMLElement>,
+    validity_flags: Cell<ValidationFlags>,
+    validation_message: DomRefCell<DOMString>,
+    validation_anchor: MutNullableDom<HTMLElement>,
+    submission_value: DomRefCell<SubmissionValue>,
+    state: DomRefCell<SubmissionValue>,
+    form_owner: MutNullableDom<HTMLFormElement>,
+    states: MutNullableDom<CustomStateSet>,
+}
+
+impl ElementInternals {
+    fn new_inherited(target_element: &HTMLElement) -> ElementInternals {
+        ElementInternals {
+            reflector_: Reflector::new(),
+            attached: Cell::new(false),
+            target_element: Dom::from_ref(target_element),
+            validity_flags: Cell::new(ValidationFlags::empty()),
+            validation_message: DomRefCell::new(DOMString::new()),
+            validation_anchor: MutNullableDom::new(None),
+            submission_value: DomRefCell::new(SubmissionValue::None),
+            state: DomRefCell::new(SubmissionValue::None),
+            form_owner: MutNullableDom::new(None),
+            states: MutNullableDom::new(None),
+        }
+    }
+
+    pub fn new(element: &HTMLElement) -> DomRoot<ElementInternals> {
+        let window = window_from_node(element);
+        reflect_dom_object(
+            Box::new(ElementInternals::new_inherited(element)),
+            &*window,
+            ElementInternalsBinding::Wrap,
+        )
+    }
+
+    pub fn attached(&self) -> bool {
+        self.attached.get()
+    }
+
+    pub fn set_attached(&self) {
+        self.attached.set(true);
+    }
+
+    pub fn form_owner(&self) -> Option<DomRoot<HTMLFormElement>> {
+        self.form_owner.get()
+    }
+
+    pub fn set_form_owner(&self, form: Option<&HTMLFormElement>) {
+        self.form_owner.set(form);
+    }
+
+    fn is_target_form_associated(&self) -> bool {
+        self.target_element.is_form_associated_custom_element()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#face-entry-construction>
+    pub fn form_datums(&self) -> Vec<FormDatum> {
+        let name = self
+            .target_element
+            .upcast::<Element>()
+            .get_string_attribute(&local_name!("name"));
+        match *self.submission_value.borrow() {
+            // Step 1
+            SubmissionValue::FormData(ref datums) => datums.clone(),
+            // Step 2
+            SubmissionValue::None => Vec::new(),
+            _ if name.is_empty() => Vec::new(),
+            // Step 3
+            SubmissionValue::File(ref file) => vec![FormDatum {
+                ty: DOMString::from("file"),
+                name: name,
+                value: FormDatumValue::File(file.clone()),
+            }],
+            SubmissionValue::USVString(ref string) => vec![FormDatum {
+                ty: DOMString::from("string"),
+                name: name,
+                value: FormDatumValue::String(DOMString::from(string.clone())),
+            }],
+        }
+    }
+
+    /// Whether the target element is a candidate for constraint validation that
+    /// suffers from none of the validity flags set with `setValidity()`.
+    pub fn satisfies_constraints(&self) -> bool {
+        self.validity_flags.get().is_empty()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#check-validity-steps>
+    fn check_validity(&self) -> bool {
+        // Step 1
+        if !self.target_element.is_instance_validatable() || self.satisfies_constraints() {
+            return true;
+        }
+
+        // Step 2
+        self.target_element
+            .upcast::<EventTarget>()
+            .fire_cancelable_event(atom!("invalid"));
+        false
+    }
+}
+
+impl ElementInternalsMethods for ElementInternals {
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-setformvalue
+    fn SetFormValue(
+        &self,
+        value: Option<FileOrUSVStringOrFormData>,
+        state: Option<Option<FileOrUSVStringOrFormData>>,
+    ) -> ErrorResult {
+        // Steps 1-2
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+
+        // Step 3
+        let value = SubmissionValue::from(value.as_ref());
+
+        // Steps 4-5
+        let state = match state {
+            Some(state) => SubmissionValue::from(state.as_ref()),
+            None => value.clone(),
+        };
+
+        *self.submission_value.borrow_mut() = value;
+        *self.state.borrow_mut() = state;
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-form
+    fn GetForm(&self) -> Fallible<Option<DomRoot<HTMLFormElement>>> {
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+        Ok(self.form_owner())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-setvalidity
+    fn SetValidity(
+        &self,
+        flags: &ValidityStateFlags,
+        message: Option<DOMString>,
+        anchor: Option<&HTMLElement>,
+    ) -> ErrorResult {
+        // Steps 1-2
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+
+        let mut validity_flags = ValidationFlags::empty();
+        validity_flags.set(ValidationFlags::VALUE_MISSING, flags.valueMissing);
+        validity_flags.set(ValidationFlags::TYPE_MISMATCH, flags.typeMismatch);
+        validity_flags.set(ValidationFlags::PATTERN_MISMATCH, flags.patternMismatch);
+        validity_flags.set(ValidationFlags::TOO_LONG, flags.tooLong);
+        validity_flags.set(ValidationFlags::TOO_SHORT, flags.tooShort);
+        validity_flags.set(ValidationFlags::RANGE_UNDERFLOW, flags.rangeUnderflow);
+        validity_flags.set(ValidationFlags::RANGE_OVERFLOW, flags.rangeOverflow);
+        validity_flags.set(ValidationFlags::STEP_MISMATCH, flags.stepMismatch);
+        validity_flags.set(ValidationFlags::BAD_INPUT, flags.badInput);
+        validity_flags.set(ValidationFlags::CUSTOM_ERROR, flags.customError);
+
+        // Step 3
+        let message = message.unwrap_or_default();
+        if !validity_flags.is_empty() && message.is_empty() {
+            return Err(Error::Type(
+                "A validation message is required when a validity flag is set".to_owned(),
+            ));
+        }
+
+        // Step 4
+        if let Some(anchor) = anchor {
+            let target = self.target_element.upcast::<Node>();
+            let anchor = anchor.upcast::<Node>();
+            if !target.is_ancestor_of(anchor) {
+                return Err(Error::NotFound);
+            }
+        }
+
+        // Step 5
+        self.validity_flags.set(validity_flags);
+
+        // Step 6
+        *self.validation_message.borrow_mut() = if validity_flags.is_empty() {
+            DOMString::new()
+        } else {
+            message
+        };
+
+        // Step 7
+        self.validation_anchor.set(anchor);
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-willvalidate
+    fn WillValidate(&self) -> Fallible<bool> {
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+        Ok(self.target_element.is_instance_validatable())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-validity
+    fn Validity(&self) -> Fallible<DomRoot<ValidityState>> {
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+        let window = window_from_node(&*self.target_element);
+        Ok(ValidityState::new(&window, self.target_element.upcast()))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-validationmessage
+    fn ValidationMessage(&self) -> Fallible<DOMString> {
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+        Ok(self.validation_message.borrow().clone())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-checkvalidity
+    fn CheckValidity(&self) -> Fallible<bool> {
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+        Ok(self.check_validity())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-reportvalidity
+    fn ReportValidity(&self) -> Fallible<bool> {
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+        // TODO: Report the problem to the user when the invalid event was not canceled.
+        Ok(self.check_validity())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-labels
+    fn Labels(&self) -> Fallible<DomRoot<NodeList>> {
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+        Ok(self.target_element.labels())
+    }
+
+    // https://wicg.github.io/custom-state-pseudo-class/#dom-elementinternals-states
+    fn States(&self) -> DomRoot<CustomStateSet> {
+        self.states.or_init(|| {
+            let window = window_from_node(&*self.target_element);
+            CustomStateSet::new(&window)
+        })
+    }
+}
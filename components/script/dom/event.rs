@@ -6,6 +6,7 @@ use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::EventBinding;
 use crate::dom::bindings::codegen::Bindings::EventBinding::{EventConstants, EventMethods};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::Trusted;
@@ -41,6 +42,8 @@ pub struct Event {
     dispatching: Cell<bool>,
     initialized: Cell<bool>,
     timestamp: u64,
+    /// When the event was created, used by the Event Timing API.
+    precise_time_ns: u64,
 }
 
 impl Event {
@@ -60,6 +63,7 @@ impl Event {
             dispatching: Cell::new(false),
             initialized: Cell::new(false),
             timestamp: time::get_time().sec as u64,
+            precise_time_ns: time::precise_time_ns(),
         }
     }
 
@@ -166,6 +170,13 @@ impl Event {
             return self.status();
         }
 
+        // https://w3c.github.io/event-timing/#sec-modifications-DOM
+        let processing_start = if self.trusted.get() && is_event_timing_type(&self.type_()) {
+            Some(time::precise_time_ns())
+        } else {
+            None
+        };
+
         // Step 3-4.
         let path = self.construct_event_path(&target);
         rooted_vec!(let event_path <- path.into_iter());
@@ -180,6 +191,16 @@ impl Event {
             }
         }
 
+        if let Some(processing_start) = processing_start {
+            if let Some(window) = DomRoot::downcast::<Window>(self.global()) {
+                window.Performance().record_event_timing(
+                    self,
+                    processing_start,
+                    time::precise_time_ns(),
+                );
+            }
+        }
+
         // Step 10-12.
         self.clear_dispatching_flags();
 
@@ -217,6 +238,11 @@ impl Event {
         self.initialized.get()
     }
 
+    /// When the event was created, in nanoseconds of `time::precise_time_ns`.
+    pub fn precise_time_ns(&self) -> u64 {
+        self.precise_time_ns
+    }
+
     #[inline]
     pub fn type_(&self) -> Atom {
         self.type_.borrow().clone()
@@ -455,6 +481,21 @@ impl TaskOnce for SimpleEventTask {
     }
 }
 
+/// Whether the events of the given type are measured by the Event Timing API.
+/// <https://w3c.github.io/event-timing/#sec-events-exposed>
+fn is_event_timing_type(type_: &Atom) -> bool {
+    match &**type_ {
+        "auxclick" | "click" | "contextmenu" | "dblclick" | "mousedown" | "mouseenter" |
+        "mouseleave" | "mouseout" | "mouseover" | "mouseup" | "pointerover" | "pointerenter" |
+        "pointerdown" | "pointerup" | "pointercancel" | "pointerout" | "pointerleave" |
+        "gotpointercapture" | "lostpointercapture" | "touchstart" | "touchend" |
+        "touchcancel" | "keydown" | "keypress" | "keyup" | "beforeinput" | "input" |
+        "compositionstart" | "compositionupdate" | "compositionend" | "dragstart" | "dragend" |
+        "dragenter" | "dragleave" | "dragover" | "drop" => true,
+        _ => false,
+    }
+}
+
 // See dispatch_event.
 // https://dom.spec.whatwg.org/#concept-event-dispatch
 fn dispatch_to_listeners(event: &Event, target: &EventTarget, event_path: &[&EventTarget]) {
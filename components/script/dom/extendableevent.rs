@@ -2,32 +2,78 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::EventBinding::{self, EventMethods};
 use crate::dom::bindings::codegen::Bindings::ExtendableEventBinding;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::event::Event;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
 use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
 use dom_struct::dom_struct;
 use js::jsapi::JSContext;
 use js::rust::HandleValue;
 use servo_atoms::Atom;
+use std::cell::Cell;
+use std::rc::Rc;
 
 // https://w3c.github.io/ServiceWorker/#extendable-event
 #[dom_struct]
 pub struct ExtendableEvent {
     event: Event,
-    extensions_allowed: bool,
+    #[ignore_malloc_size_of = "Rc"]
+    pending_promises: Rc<PendingPromises>,
+}
+
+/// The [extend lifetime promises](https://w3c.github.io/ServiceWorker/#extendableevent-extend-lifetime-promises)
+/// of an event that did not settle yet.
+#[derive(Default, JSTraceable, MallocSizeOf)]
+struct PendingPromises {
+    /// <https://w3c.github.io/ServiceWorker/#extendableevent-pending-promises-count>
+    count: Cell<usize>,
+    /// Whether one of the promises was rejected.
+    rejected: Cell<bool>,
+    /// Resolved once all the promises settled.
+    #[ignore_malloc_size_of = "Rc"]
+    all_settled: DomRefCell<Option<Rc<Promise>>>,
+}
+
+impl PendingPromises {
+    fn settle(&self, rejected: bool) {
+        if rejected {
+            self.rejected.set(true);
+        }
+        self.count.set(self.count.get() - 1);
+        if self.count.get() == 0 {
+            if let Some(promise) = self.all_settled.borrow_mut().take() {
+                promise.resolve_native(&!self.rejected.get());
+            }
+        }
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+struct SettleHandler {
+    #[ignore_malloc_size_of = "Rc"]
+    pending_promises: Rc<PendingPromises>,
+    rejected: bool,
+}
+
+impl Callback for SettleHandler {
+    fn callback(&self, _cx: *mut JSContext, _v: HandleValue) {
+        self.pending_promises.settle(self.rejected);
+    }
 }
 
 impl ExtendableEvent {
     pub fn new_inherited() -> ExtendableEvent {
         ExtendableEvent {
             event: Event::new_inherited(),
-            extensions_allowed: true,
+            pending_promises: Default::default(),
         }
     }
     pub fn new(
@@ -61,14 +107,59 @@ impl ExtendableEvent {
         ))
     }
 
+    // https://w3c.github.io/ServiceWorker/#extendableevent-active
+    pub fn is_active(&self) -> bool {
+        self.event.dispatching() || self.pending_promises.count.get() > 0
+    }
+
+    // https://w3c.github.io/ServiceWorker/#extendableevent-add-lifetime-promise
+    pub fn add_lifetime_promise(&self, promise: &Promise) {
+        // Step 2
+        self.pending_promises
+            .count
+            .set(self.pending_promises.count.get() + 1);
+
+        // Step 3
+        let handler = PromiseNativeHandler::new(
+            &self.global(),
+            Some(Box::new(SettleHandler {
+                pending_promises: self.pending_promises.clone(),
+                rejected: false,
+            })),
+            Some(Box::new(SettleHandler {
+                pending_promises: self.pending_promises.clone(),
+                rejected: true,
+            })),
+        );
+        promise.append_native_handler(&handler);
+    }
+
+    /// A promise resolved once all the extend lifetime promises of this event settled,
+    /// with whether all of them were fulfilled.
+    pub fn extended_lifetime_promise(&self) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+        if self.pending_promises.count.get() == 0 {
+            promise.resolve_native(&!self.pending_promises.rejected.get());
+        } else {
+            *self.pending_promises.all_settled.borrow_mut() = Some(promise.clone());
+        }
+        promise
+    }
+
+    #[allow(unsafe_code)]
     // https://w3c.github.io/ServiceWorker/#wait-until-method
-    pub fn WaitUntil(&self, _cx: *mut JSContext, _val: HandleValue) -> ErrorResult {
+    pub fn WaitUntil(&self, cx: *mut JSContext, val: HandleValue) -> ErrorResult {
         // Step 1
-        if !self.extensions_allowed {
+        if !self.IsTrusted() {
             return Err(Error::InvalidState);
         }
         // Step 2
-        // TODO add a extended_promises array to enqueue the `val`
+        if !self.is_active() {
+            return Err(Error::InvalidState);
+        }
+        // Step 3
+        let promise = unsafe { Promise::new_resolved(&self.global(), cx, val)? };
+        self.add_lifetime_promise(&promise);
         Ok(())
     }
 
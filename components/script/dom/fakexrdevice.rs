@@ -0,0 +1,247 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::FakeXRDeviceBinding::{
+    self, FakeXRBoundsPoint, FakeXRDeviceMethods, FakeXRRigidTransformInit, FakeXRViewInit,
+};
+use crate::dom::bindings::codegen::Bindings::FakeXRInputControllerBinding::FakeXRInputSourceInit;
+use crate::dom::bindings::codegen::Bindings::XRTestBinding::FakeXRDeviceInit;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::fakexrinputcontroller::FakeXRInputController;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::xrrigidtransform::RigidTransform;
+use crate::dom::xrsession::{XRButtonState, XRDeviceFrame, XRDeviceInput, XRDeviceView, XRSession};
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// A device simulated by the WebXR Test API, whose state is entirely
+/// controlled by the page.
+#[dom_struct]
+pub struct FakeXRDevice {
+    reflector_: Reflector,
+    supports_immersive: bool,
+    supports_unbounded: bool,
+    views: DomRefCell<Vec<XRDeviceView>>,
+    viewer: Cell<Option<RigidTransform>>,
+    emulated_position: Cell<bool>,
+    floor_origin: Cell<Option<RigidTransform>>,
+    bounds: DomRefCell<Option<Vec<(f64, f64)>>>,
+    input_controllers: DomRefCell<Vec<Dom<FakeXRInputController>>>,
+    next_input_id: Cell<u32>,
+    session: MutNullableDom<XRSession>,
+    connected: Cell<bool>,
+}
+
+impl FakeXRDevice {
+    fn new_inherited(
+        init: &FakeXRDeviceInit,
+        views: Vec<XRDeviceView>,
+        viewer: Option<RigidTransform>,
+        floor_origin: Option<RigidTransform>,
+    ) -> FakeXRDevice {
+        FakeXRDevice {
+            reflector_: Reflector::new(),
+            supports_immersive: init.supportsImmersive,
+            supports_unbounded: init.supportsUnbounded,
+            views: DomRefCell::new(views),
+            viewer: Cell::new(viewer),
+            emulated_position: Cell::new(false),
+            floor_origin: Cell::new(floor_origin),
+            bounds: DomRefCell::new(init.boundsCoordinates.as_ref().map(|b| get_bounds(b))),
+            input_controllers: DomRefCell::new(vec![]),
+            next_input_id: Cell::new(0),
+            session: Default::default(),
+            connected: Cell::new(true),
+        }
+    }
+
+    /// Creates a device from the options passed to `simulateDeviceConnection`.
+    pub fn new(global: &GlobalScope, init: &FakeXRDeviceInit) -> Fallible<DomRoot<FakeXRDevice>> {
+        let views = get_views(&init.views)?;
+        let viewer = match init.viewerOrigin {
+            Some(ref origin) => Some(get_origin(origin)?),
+            None => None,
+        };
+        let floor_origin = match init.floorOrigin {
+            Some(ref origin) => Some(get_origin(origin)?),
+            None => None,
+        };
+        Ok(reflect_dom_object(
+            Box::new(FakeXRDevice::new_inherited(
+                init,
+                views,
+                viewer,
+                floor_origin,
+            )),
+            global,
+            FakeXRDeviceBinding::Wrap,
+        ))
+    }
+
+    pub fn supports_immersive(&self) -> bool {
+        self.supports_immersive
+    }
+
+    pub fn supports_unbounded(&self) -> bool {
+        self.supports_unbounded
+    }
+
+    pub fn bounds(&self) -> Option<Vec<(f64, f64)>> {
+        self.bounds.borrow().clone()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.get()
+    }
+
+    pub fn set_session(&self, session: &XRSession) {
+        self.session.set(Some(session));
+    }
+
+    pub fn end_session(&self, session: &XRSession) {
+        if self.session == Some(session) {
+            self.session.set(None);
+        }
+    }
+
+    /// Samples the state of the device for the next frame.
+    pub fn frame(&self) -> XRDeviceFrame {
+        XRDeviceFrame {
+            viewer: self.viewer.get(),
+            emulated_position: self.emulated_position.get(),
+            views: self.views.borrow().clone(),
+            floor_origin: self.floor_origin.get(),
+            inputs: self
+                .input_controllers
+                .borrow()
+                .iter()
+                .filter_map(|controller| controller.take_input())
+                .collect(),
+        }
+    }
+}
+
+impl FakeXRDeviceMethods for FakeXRDevice {
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn SetViews(&self, views: Vec<FakeXRViewInit>) -> ErrorResult {
+        *self.views.borrow_mut() = get_views(&views)?;
+        Ok(())
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn Disconnect(&self) -> Rc<Promise> {
+        self.connected.set(false);
+        if let Some(session) = self.session.get() {
+            session.shut_down();
+        }
+        let p = Promise::new(&self.global());
+        p.resolve_native(&());
+        p
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn SetViewerOrigin(
+        &self,
+        origin: &FakeXRRigidTransformInit,
+        emulated_position: bool,
+    ) -> ErrorResult {
+        self.viewer.set(Some(get_origin(origin)?));
+        self.emulated_position.set(emulated_position);
+        Ok(())
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn ClearViewerOrigin(&self) {
+        self.viewer.set(None);
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn SetFloorOrigin(&self, origin: &FakeXRRigidTransformInit) -> ErrorResult {
+        self.floor_origin.set(Some(get_origin(origin)?));
+        Ok(())
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn ClearFloorOrigin(&self) {
+        self.floor_origin.set(None);
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn SetBoundsGeometry(&self, bounds: Vec<FakeXRBoundsPoint>) {
+        *self.bounds.borrow_mut() = Some(get_bounds(&bounds));
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn SimulateInputSourceConnection(
+        &self,
+        init: &FakeXRInputSourceInit,
+    ) -> Fallible<DomRoot<FakeXRInputController>> {
+        let id = self.next_input_id.get();
+        self.next_input_id.set(id + 1);
+        let grip = match init.gripOrigin {
+            Some(ref origin) => Some(get_origin(origin)?),
+            None => None,
+        };
+        let input = XRDeviceInput {
+            id,
+            handedness: init.handedness,
+            target_ray_mode: init.targetRayMode,
+            target_ray: Some(get_origin(&init.pointerOrigin)?),
+            grip,
+            emulated_position: false,
+            profiles: init.profiles.as_ref().map_or(vec![], |p| {
+                p.iter().map(|p| String::from(p.clone())).collect()
+            }),
+            select: XRButtonState {
+                pressed: init.selectionStarted,
+                clicked: init.selectionClicked,
+            },
+            squeeze: XRButtonState::default(),
+        };
+        let controller = FakeXRInputController::new(&self.global(), input);
+        self.input_controllers
+            .borrow_mut()
+            .push(Dom::from_ref(&*controller));
+        Ok(controller)
+    }
+}
+
+/// Converts a transform of the test API, rejecting arrays of the wrong size.
+pub fn get_origin(origin: &FakeXRRigidTransformInit) -> Fallible<RigidTransform> {
+    if origin.position.len() != 3 || origin.orientation.len() != 4 {
+        return Err(Error::Type("Incorrectly sized array".into()));
+    }
+    let p = &origin.position;
+    let o = &origin.orientation;
+    RigidTransform::new(
+        [*p[0] as f64, *p[1] as f64, *p[2] as f64],
+        [*o[0] as f64, *o[1] as f64, *o[2] as f64, *o[3] as f64],
+    )
+    .ok_or_else(|| Error::Type("Orientation must not be a zero quaternion".into()))
+}
+
+pub fn get_views(views: &[FakeXRViewInit]) -> Fallible<Vec<XRDeviceView>> {
+    views
+        .iter()
+        .map(|view| {
+            if view.projectionMatrix.len() != 16 {
+                return Err(Error::Type("Incorrectly sized array".into()));
+            }
+            Ok(XRDeviceView {
+                eye: view.eye,
+                offset: get_origin(&view.viewOffset)?,
+                projection: view.projectionMatrix.iter().map(|v| **v).collect(),
+            })
+        })
+        .collect()
+}
+
+fn get_bounds(bounds: &[FakeXRBoundsPoint]) -> Vec<(f64, f64)> {
+    bounds.iter().map(|point| (*point.x, *point.z)).collect()
+}
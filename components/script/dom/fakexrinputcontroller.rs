@@ -0,0 +1,146 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::FakeXRDeviceBinding::FakeXRRigidTransformInit;
+use crate::dom::bindings::codegen::Bindings::FakeXRInputControllerBinding::{
+    self, FakeXRButtonStateInit, FakeXRButtonType, FakeXRInputControllerMethods,
+};
+use crate::dom::bindings::codegen::Bindings::XRInputSourceBinding::{
+    XRHandedness, XRTargetRayMode,
+};
+use crate::dom::bindings::error::ErrorResult;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::fakexrdevice::get_origin;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrsession::{XRButtonState, XRDeviceInput};
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+/// An input source of a simulated device, driven by the WebXR Test API.
+#[dom_struct]
+pub struct FakeXRInputController {
+    reflector_: Reflector,
+    input: DomRefCell<XRDeviceInput>,
+    connected: Cell<bool>,
+}
+
+impl FakeXRInputController {
+    fn new_inherited(input: XRDeviceInput) -> FakeXRInputController {
+        FakeXRInputController {
+            reflector_: Reflector::new(),
+            input: DomRefCell::new(input),
+            connected: Cell::new(true),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, input: XRDeviceInput) -> DomRoot<FakeXRInputController> {
+        reflect_dom_object(
+            Box::new(FakeXRInputController::new_inherited(input)),
+            global,
+            FakeXRInputControllerBinding::Wrap,
+        )
+    }
+
+    /// Samples the state of the controller for the next frame, or `None` if
+    /// it is disconnected. Clicks are only reported once.
+    pub fn take_input(&self) -> Option<XRDeviceInput> {
+        if !self.connected.get() {
+            return None;
+        }
+        let mut input = self.input.borrow_mut();
+        let sampled = input.clone();
+        input.select.clicked = false;
+        input.squeeze.clicked = false;
+        Some(sampled)
+    }
+}
+
+/// Presses a button, or releases it and records a click if it was pressed.
+fn set_pressed(state: &mut XRButtonState, pressed: bool) {
+    if pressed {
+        state.pressed = true;
+    } else if state.pressed {
+        state.pressed = false;
+        state.clicked = true;
+    }
+}
+
+impl FakeXRInputControllerMethods for FakeXRInputController {
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn SetHandedness(&self, handedness: XRHandedness) {
+        self.input.borrow_mut().handedness = handedness;
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn SetTargetRayMode(&self, target_ray_mode: XRTargetRayMode) {
+        self.input.borrow_mut().target_ray_mode = target_ray_mode;
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn SetPointerOrigin(
+        &self,
+        origin: &FakeXRRigidTransformInit,
+        emulated_position: bool,
+    ) -> ErrorResult {
+        let origin = get_origin(origin)?;
+        let mut input = self.input.borrow_mut();
+        input.target_ray = Some(origin);
+        input.emulated_position = emulated_position;
+        Ok(())
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn SetGripOrigin(
+        &self,
+        origin: &FakeXRRigidTransformInit,
+        emulated_position: bool,
+    ) -> ErrorResult {
+        let origin = get_origin(origin)?;
+        let mut input = self.input.borrow_mut();
+        input.grip = Some(origin);
+        input.emulated_position = emulated_position;
+        Ok(())
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn ClearGripOrigin(&self) {
+        self.input.borrow_mut().grip = None;
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn Disconnect(&self) {
+        self.connected.set(false);
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn Reconnect(&self) {
+        self.connected.set(true);
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn StartSelection(&self) {
+        set_pressed(&mut self.input.borrow_mut().select, true);
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn EndSelection(&self) {
+        set_pressed(&mut self.input.borrow_mut().select, false);
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn SimulateSelect(&self) {
+        self.input.borrow_mut().select.clicked = true;
+    }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn UpdateButtonState(&self, state: &FakeXRButtonStateInit) {
+        // Only the grip button maps to an action; the remaining buttons are
+        // exposed through gamepads, which input sources don't support yet.
+        if state.buttonType == FakeXRButtonType::Grip {
+            set_pressed(&mut self.input.borrow_mut().squeeze, state.pressed);
+        }
+    }
+}
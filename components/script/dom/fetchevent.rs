@@ -0,0 +1,283 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::body::{consume_body, BodyType};
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::FetchEventBinding::{self, FetchEventMethods};
+use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseMethods;
+use crate::dom::bindings::conversions::root_from_handlevalue;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::extendableevent::ExtendableEvent;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::request::Request;
+use crate::dom::response::Response;
+use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
+use dom_struct::dom_struct;
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use hyper::StatusCode;
+use ipc_channel::ipc::IpcSender;
+use js::jsapi::JSContext;
+use js::rust::HandleValue;
+use js::typedarray::ArrayBuffer;
+use net_traits::cache_storage_thread::CachedResponse;
+use net_traits::response::ResponseType;
+use net_traits::{CustomResponse, NetworkError};
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Where the answer of a service worker to a fetch goes, `None` lets the fetch go to the
+/// network.
+pub type FetchResponseChan = IpcSender<Option<Result<CustomResponse, NetworkError>>>;
+
+// https://w3c.github.io/ServiceWorker/#fetchevent-interface
+#[dom_struct]
+pub struct FetchEvent {
+    event: ExtendableEvent,
+    request: Dom<Request>,
+    client_id: DOMString,
+    resulting_client_id: DOMString,
+    /// <https://w3c.github.io/ServiceWorker/#fetchevent-respond-with-entered-flag>
+    respond_with_entered: Cell<bool>,
+    /// The promise given to `respondWith`.
+    #[ignore_malloc_size_of = "Rc"]
+    response_promise: DomRefCell<Option<Rc<Promise>>>,
+}
+
+impl FetchEvent {
+    fn new_inherited(
+        request: &Request,
+        client_id: DOMString,
+        resulting_client_id: DOMString,
+    ) -> FetchEvent {
+        FetchEvent {
+            event: ExtendableEvent::new_inherited(),
+            request: Dom::from_ref(request),
+            client_id: client_id,
+            resulting_client_id: resulting_client_id,
+            respond_with_entered: Cell::new(false),
+            response_promise: DomRefCell::new(None),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        request: &Request,
+        client_id: DOMString,
+        resulting_client_id: DOMString,
+    ) -> DomRoot<FetchEvent> {
+        let ev = reflect_dom_object(
+            Box::new(FetchEvent::new_inherited(
+                request,
+                client_id,
+                resulting_client_id,
+            )),
+            global,
+            FetchEventBinding::Wrap,
+        );
+        ev.upcast::<Event>().init_event(type_, bubbles, cancelable);
+        ev
+    }
+
+    pub fn Constructor(
+        worker: &ServiceWorkerGlobalScope,
+        type_: DOMString,
+        init: &FetchEventBinding::FetchEventInit,
+    ) -> Fallible<DomRoot<FetchEvent>> {
+        Ok(FetchEvent::new(
+            worker.upcast(),
+            Atom::from(type_),
+            init.parent.parent.bubbles,
+            init.parent.parent.cancelable,
+            &init.request,
+            init.clientId.clone(),
+            init.resultingClientId.clone(),
+        ))
+    }
+
+    /// Sends the answer of the service worker once the event was dispatched: the response
+    /// it was responded with, or `None` to let the fetch go to the network.
+    // https://w3c.github.io/ServiceWorker/#handle-fetch Steps 23-24
+    pub fn send_response(&self, response_chan: FetchResponseChan) {
+        let promise = match self.response_promise.borrow_mut().take() {
+            Some(promise) => promise,
+            None => {
+                let _ = response_chan.send(None);
+                return;
+            },
+        };
+        let handler = PromiseNativeHandler::new(
+            &self.global(),
+            Some(Box::new(RespondWithHandler {
+                response_chan: response_chan.clone(),
+            })),
+            Some(Box::new(NetworkErrorHandler {
+                response_chan: response_chan,
+                reason: "The promise given to respondWith was rejected".to_owned(),
+            })),
+        );
+        promise.append_native_handler(&handler);
+    }
+}
+
+impl FetchEventMethods for FetchEvent {
+    // https://w3c.github.io/ServiceWorker/#fetch-event-request
+    fn Request(&self) -> DomRoot<Request> {
+        DomRoot::from_ref(&*self.request)
+    }
+
+    // https://w3c.github.io/ServiceWorker/#fetch-event-clientid
+    fn ClientId(&self) -> DOMString {
+        self.client_id.clone()
+    }
+
+    // https://w3c.github.io/ServiceWorker/#fetch-event-resultingclientid
+    fn ResultingClientId(&self) -> DOMString {
+        self.resulting_client_id.clone()
+    }
+
+    // https://w3c.github.io/ServiceWorker/#fetch-event-respondwith
+    fn RespondWith(&self, r: &Promise) -> ErrorResult {
+        let event = self.upcast::<Event>();
+        // Step 1
+        if !event.dispatching() {
+            return Err(Error::InvalidState);
+        }
+        // Step 2
+        if self.respond_with_entered.get() {
+            return Err(Error::InvalidState);
+        }
+        // Step 3
+        self.upcast::<ExtendableEvent>().add_lifetime_promise(r);
+        // Step 4
+        event.StopImmediatePropagation();
+        // Step 5-6
+        self.respond_with_entered.set(true);
+        // Step 7-8, see send_response
+        *self.response_promise.borrow_mut() = Some(r.duplicate());
+        Ok(())
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
+
+/// The answer to a fetch, made of the head of the response the fetch event was
+/// responded with and its body.
+fn custom_response(head: CachedResponse, body: Vec<u8>) -> Result<CustomResponse, NetworkError> {
+    if let ResponseType::Error(error) = head.response_type {
+        return Err(error);
+    }
+    let mut headers = HeaderMap::new();
+    for (name, value) in head.headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_bytes(&value),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    let status = StatusCode::from_u16(head.status)
+        .map_err(|_| NetworkError::Internal("Invalid response status".to_owned()))?;
+    let status_text = String::from_utf8_lossy(&head.status_text).into_owned();
+    Ok(CustomResponse::new(headers, (status, status_text), body))
+}
+
+// https://w3c.github.io/ServiceWorker/#fetch-event-respondwith Step 8.2
+#[derive(JSTraceable, MallocSizeOf)]
+struct RespondWithHandler {
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    response_chan: FetchResponseChan,
+}
+
+impl Callback for RespondWithHandler {
+    fn callback(&self, _cx: *mut JSContext, v: HandleValue) {
+        // Step 8.2.1
+        let response = match root_from_handlevalue::<Response>(v) {
+            Ok(response) => response,
+            Err(()) => {
+                let error = NetworkError::Internal("respondWith was not given a Response".into());
+                let _ = self.response_chan.send(Some(Err(error)));
+                return;
+            },
+        };
+        // Step 8.2.2
+        if response.BodyUsed() {
+            let error = NetworkError::Internal("The response body was already used".into());
+            let _ = self.response_chan.send(Some(Err(error)));
+            return;
+        }
+        // Step 8.2.3-8.2.5
+        let body_promise = consume_body(&*response, BodyType::ArrayBuffer);
+        let handler = PromiseNativeHandler::new(
+            &response.global(),
+            Some(Box::new(ResponseBodyHandler {
+                response_chan: self.response_chan.clone(),
+                head: DomRefCell::new(Some(response.to_cached_response())),
+            })),
+            Some(Box::new(NetworkErrorHandler {
+                response_chan: self.response_chan.clone(),
+                reason: "The response body could not be read".to_owned(),
+            })),
+        );
+        body_promise.append_native_handler(&handler);
+    }
+}
+
+/// Sends the response once its body was read.
+#[derive(JSTraceable, MallocSizeOf)]
+struct ResponseBodyHandler {
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    response_chan: FetchResponseChan,
+    #[ignore_malloc_size_of = "Defined in net_traits"]
+    head: DomRefCell<Option<CachedResponse>>,
+}
+
+impl Callback for ResponseBodyHandler {
+    #[allow(unsafe_code)]
+    fn callback(&self, cx: *mut JSContext, v: HandleValue) {
+        let head = match self.head.borrow_mut().take() {
+            Some(head) => head,
+            None => return,
+        };
+        let body = unsafe {
+            typedarray!(in(cx) let body: ArrayBuffer = v.to_object());
+            body.map(|body| body.to_vec())
+        };
+        let response = match body {
+            Ok(body) => custom_response(head, body),
+            Err(_) => Err(NetworkError::Internal(
+                "The response body could not be read".to_owned(),
+            )),
+        };
+        let _ = self.response_chan.send(Some(response));
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+struct NetworkErrorHandler {
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    response_chan: FetchResponseChan,
+    reason: String,
+}
+
+impl Callback for NetworkErrorHandler {
+    fn callback(&self, _cx: *mut JSContext, _v: HandleValue) {
+        let error = NetworkError::Internal(self.reason.clone());
+        let _ = self.response_chan.send(Some(Err(error)));
+    }
+}
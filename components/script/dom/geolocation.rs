@@ -0,0 +1,402 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GeolocationBinding::{
+    self, GeolocationMethods, PositionCallback, PositionErrorCallback, PositionOptions,
+};
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::codegen::Bindings::PositionErrorBinding::PositionErrorConstants;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::permissions::request_permission_to_use;
+use crate::dom::position::Position;
+use crate::dom::positionerror::PositionError;
+use crate::dom::window::Window;
+use crate::task_source::TaskSource;
+use crate::timers::{OneshotTimerCallback, OneshotTimerHandle};
+use dom_struct::dom_struct;
+use embedder_traits::GeolocationPosition;
+use euclid::Length;
+use servo_config::prefs::PREFS;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::u32;
+
+/// A pending call of `getCurrentPosition` or `watchPosition`.
+#[derive(JSTraceable, MallocSizeOf)]
+struct GeolocationRequest {
+    id: i32,
+    /// Whether the request keeps receiving positions until the watch is cleared.
+    watch: bool,
+    #[ignore_malloc_size_of = "can't measure Rc values"]
+    success_callback: Rc<PositionCallback>,
+    #[ignore_malloc_size_of = "can't measure Rc values"]
+    error_callback: Option<Rc<PositionErrorCallback>>,
+    timeout: u32,
+    maximum_age: u32,
+    /// The timer running while a position is acquired, if the timeout is not infinite.
+    timer: Option<OneshotTimerHandle>,
+}
+
+// https://w3c.github.io/geolocation-api/#geolocation_interface
+#[dom_struct]
+pub struct Geolocation {
+    reflector_: Reflector,
+    /// <https://w3c.github.io/geolocation-api/#dfn-cachedposition>
+    cached_position: MutNullableDom<Position>,
+    requests: DomRefCell<Vec<GeolocationRequest>>,
+    next_request_id: Cell<i32>,
+}
+
+impl Geolocation {
+    fn new_inherited() -> Geolocation {
+        Geolocation {
+            reflector_: Reflector::new(),
+            cached_position: Default::default(),
+            requests: DomRefCell::new(Vec::new()),
+            next_request_id: Cell::new(1),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<Geolocation> {
+        reflect_dom_object(
+            Box::new(Geolocation::new_inherited()),
+            window,
+            GeolocationBinding::Wrap,
+        )
+    }
+
+    fn window(&self) -> DomRoot<Window> {
+        DomRoot::downcast(self.global()).unwrap()
+    }
+
+    // https://w3c.github.io/geolocation-api/#dfn-request-a-position
+    fn request_position(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Rc<PositionErrorCallback>>,
+        options: &PositionOptions,
+        watch: bool,
+    ) -> i32 {
+        let id = self.next_request_id.get();
+        self.next_request_id.set(id + 1);
+        self.requests.borrow_mut().push(GeolocationRequest {
+            id: id,
+            watch: watch,
+            success_callback: success_callback,
+            error_callback: error_callback,
+            timeout: options.timeout,
+            maximum_age: options.maximumAge,
+            timer: None,
+        });
+
+        // The permission is checked and the position acquired in parallel.
+        let window = self.window();
+        let this = Trusted::new(self);
+        let _ = window.task_manager().dom_manipulation_task_source().queue(
+            task!(geolocation_acquire_position: move || {
+                this.root().acquire_position(id);
+            }),
+            window.upcast(),
+        );
+        id
+    }
+
+    // https://w3c.github.io/geolocation-api/#dfn-acquire-a-position
+    fn acquire_position(&self, id: i32) {
+        let (timeout, maximum_age) = match self.request(id) {
+            Some(request) => request,
+            // The watch was cleared meanwhile.
+            None => return,
+        };
+
+        // The mock location is meant for testing, and is granted without prompting.
+        let mock_position = mock_position();
+        if mock_position.is_none() {
+            let window = self.window();
+            let state = request_permission_to_use(PermissionName::Geolocation, window.upcast());
+            if state != PermissionState::Granted {
+                self.call_error_callback(
+                    id,
+                    PositionErrorConstants::PERMISSION_DENIED,
+                    "User denied Geolocation",
+                );
+                // A denied watch is not going to receive positions either.
+                self.remove_request(id);
+                return;
+            }
+        }
+
+        // Step 1-2
+        if maximum_age > 0 {
+            if let Some(cached_position) = self.cached_position.get() {
+                if now().saturating_sub(cached_position.timestamp()) <= maximum_age as u64 {
+                    return self.call_success_callbacks(&[id], &cached_position);
+                }
+            }
+        }
+
+        // Step 3
+        if timeout == 0 {
+            return self.handle_timeout(id);
+        }
+
+        if let Some(position) = mock_position {
+            let position = self.cache_position(position);
+            return self.call_success_callbacks(&[id], &position);
+        }
+
+        // Step 5, the embedder sends the next position with `WindowEvent::SetGeolocation`.
+        self.start_timer(id, timeout);
+    }
+
+    /// The timeout and maximum age of the request, if it was not cleared.
+    fn request(&self, id: i32) -> Option<(u32, u32)> {
+        self.requests
+            .borrow()
+            .iter()
+            .find(|request| request.id == id)
+            .map(|request| (request.timeout, request.maximum_age))
+    }
+
+    fn remove_request(&self, id: i32) -> Option<GeolocationRequest> {
+        let mut requests = self.requests.borrow_mut();
+        let index = requests.iter().position(|request| request.id == id)?;
+        let request = requests.remove(index);
+        if let Some(timer) = request.timer {
+            self.global().unschedule_callback(timer);
+        }
+        Some(request)
+    }
+
+    fn start_timer(&self, id: i32, timeout: u32) {
+        // The largest timeout is used for infinity.
+        if timeout == u32::MAX {
+            return;
+        }
+        let callback = OneshotTimerCallback::GeolocationTimeout(GeolocationTimeoutCallback {
+            geolocation: Trusted::new(self),
+            request_id: id,
+        });
+        let timer = self
+            .global()
+            .schedule_callback(callback, Length::new(timeout as u64));
+        let mut requests = self.requests.borrow_mut();
+        match requests.iter_mut().find(|request| request.id == id) {
+            Some(request) => {
+                if let Some(previous_timer) = request.timer.replace(timer) {
+                    self.global().unschedule_callback(previous_timer);
+                }
+            },
+            None => self.global().unschedule_callback(timer),
+        }
+    }
+
+    fn handle_timeout(&self, id: i32) {
+        let watch = {
+            let mut requests = self.requests.borrow_mut();
+            match requests.iter_mut().find(|request| request.id == id) {
+                Some(request) => {
+                    request.timer = None;
+                    request.watch
+                },
+                None => return,
+            }
+        };
+        self.call_error_callback(
+            id,
+            PositionErrorConstants::TIMEOUT,
+            "Position acquisition timed out",
+        );
+        if !watch {
+            self.remove_request(id);
+        }
+    }
+
+    fn cache_position(&self, position: GeolocationPosition) -> DomRoot<Position> {
+        let position = Position::new(&self.window(), position, now());
+        self.cached_position.set(Some(&position));
+        position
+    }
+
+    /// Calls the success callbacks of the given requests with the position. The requests
+    /// made with `getCurrentPosition` are done, the watches acquire the next position.
+    fn call_success_callbacks(&self, ids: &[i32], position: &Position) {
+        for &id in ids {
+            let request = match self.remove_request(id) {
+                Some(request) => request,
+                None => continue,
+            };
+            let callback = request.success_callback.clone();
+            if request.watch {
+                let timeout = request.timeout;
+                self.requests.borrow_mut().push(request);
+                self.start_timer(id, timeout);
+            }
+            let _ = callback.Call__(position, ExceptionHandling::Report);
+        }
+    }
+
+    // https://w3c.github.io/geolocation-api/#dfn-call-back-with-error
+    fn call_error_callback(&self, id: i32, code: u16, message: &str) {
+        let callback = self
+            .requests
+            .borrow()
+            .iter()
+            .find(|request| request.id == id)
+            .and_then(|request| request.error_callback.clone());
+        if let Some(callback) = callback {
+            let error = PositionError::new(&self.window(), code, DOMString::from(message));
+            let _ = callback.Call__(&error, ExceptionHandling::Report);
+        }
+    }
+
+    /// Handles a new position of the device, or the position becoming unavailable.
+    pub fn update_position(&self, position: Option<GeolocationPosition>) {
+        let ids: Vec<i32> = self
+            .requests
+            .borrow()
+            .iter()
+            .map(|request| request.id)
+            .collect();
+        let position = match position {
+            Some(ref position)
+                if position.latitude.is_finite() &&
+                    position.longitude.is_finite() &&
+                    position.accuracy.is_finite() =>
+            {
+                position.clone()
+            },
+            Some(_) => return warn!("Ignoring position with invalid coordinates."),
+            None => {
+                for id in ids {
+                    self.call_error_callback(
+                        id,
+                        PositionErrorConstants::POSITION_UNAVAILABLE,
+                        "Position unavailable",
+                    );
+                    let watch = self
+                        .requests
+                        .borrow()
+                        .iter()
+                        .any(|request| request.id == id && request.watch);
+                    if !watch {
+                        self.remove_request(id);
+                    }
+                }
+                return;
+            },
+        };
+        let position = self.cache_position(position);
+        self.call_success_callbacks(&ids, &position);
+    }
+}
+
+impl GeolocationMethods for Geolocation {
+    // https://w3c.github.io/geolocation-api/#getcurrentposition-method
+    fn GetCurrentPosition(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Rc<PositionErrorCallback>>,
+        options: &PositionOptions,
+    ) {
+        // Step 1
+        if !self.window().Document().is_fully_active() {
+            if let Some(error_callback) = error_callback {
+                let error = PositionError::new(
+                    &self.window(),
+                    PositionErrorConstants::POSITION_UNAVAILABLE,
+                    DOMString::from("Document is not fully active"),
+                );
+                let _ = error_callback.Call__(&error, ExceptionHandling::Report);
+            }
+            return;
+        }
+        // Step 2
+        self.request_position(success_callback, error_callback, options, false);
+    }
+
+    // https://w3c.github.io/geolocation-api/#watchposition-method
+    fn WatchPosition(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Rc<PositionErrorCallback>>,
+        options: &PositionOptions,
+    ) -> i32 {
+        // Step 1
+        if !self.window().Document().is_fully_active() {
+            if let Some(error_callback) = error_callback {
+                let error = PositionError::new(
+                    &self.window(),
+                    PositionErrorConstants::POSITION_UNAVAILABLE,
+                    DOMString::from("Document is not fully active"),
+                );
+                let _ = error_callback.Call__(&error, ExceptionHandling::Report);
+            }
+            return 0;
+        }
+        // Step 2-5
+        self.request_position(success_callback, error_callback, options, true)
+    }
+
+    // https://w3c.github.io/geolocation-api/#clearwatch-method
+    fn ClearWatch(&self, watch_id: i32) {
+        let watch = self
+            .requests
+            .borrow()
+            .iter()
+            .any(|request| request.id == watch_id && request.watch);
+        if watch {
+            self.remove_request(watch_id);
+        }
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct GeolocationTimeoutCallback {
+    #[ignore_malloc_size_of = "Because it is non-owning"]
+    geolocation: Trusted<Geolocation>,
+    request_id: i32,
+}
+
+impl GeolocationTimeoutCallback {
+    pub fn invoke(self) {
+        self.geolocation.root().handle_timeout(self.request_id);
+    }
+}
+
+/// The fixed position given by the `dom.geolocation.mock.*` preferences, if enabled.
+fn mock_position() -> Option<GeolocationPosition> {
+    if !PREFS
+        .get("dom.geolocation.mock.enabled")
+        .as_boolean()
+        .unwrap_or(false)
+    {
+        return None;
+    }
+    let pref = |name: &str| PREFS.get(name).as_f64().unwrap_or(0.);
+    Some(GeolocationPosition {
+        latitude: pref("dom.geolocation.mock.latitude"),
+        longitude: pref("dom.geolocation.mock.longitude"),
+        accuracy: pref("dom.geolocation.mock.accuracy"),
+        altitude: None,
+        altitude_accuracy: None,
+        heading: None,
+        speed: None,
+    })
+}
+
+/// The current time in milliseconds since the epoch, as a `DOMTimeStamp`.
+fn now() -> u64 {
+    let now = time::get_time();
+    now.sec as u64 * 1000 + now.nsec as u64 / 1_000_000
+}
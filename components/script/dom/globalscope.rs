@@ -15,7 +15,9 @@ use crate::dom::bindings::settings_stack::{entry_global, incumbent_global, AutoE
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::weakref::DOMTracker;
 use crate::dom::crypto::Crypto;
-use crate::dom::dedicatedworkerglobalscope::DedicatedWorkerGlobalScope;
+use crate::dom::dedicatedworkerglobalscope::{
+    DedicatedWorkerGlobalScope, DedicatedWorkerScriptMsg,
+};
 use crate::dom::errorevent::ErrorEvent;
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use crate::dom::eventsource::EventSource;
@@ -37,6 +39,8 @@ use crate::task_source::websocket::WebsocketTaskSource;
 use crate::task_source::TaskSourceName;
 use crate::timers::{IsInterval, OneshotTimerCallback, OneshotTimerHandle};
 use crate::timers::{OneshotTimers, TimerCallback};
+use canvas_traits::webgl::WebGLChan;
+use crossbeam_channel::Sender;
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
 use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcSender;
@@ -66,11 +70,16 @@ use std::sync::Arc;
 use time::{get_time, Timespec};
 
 #[derive(JSTraceable)]
-pub struct AutoCloseWorker(Arc<AtomicBool>);
+pub struct AutoCloseWorker {
+    /// https://html.spec.whatwg.org/multipage/#dom-workerglobalscope-closing
+    closing: Arc<AtomicBool>,
+    /// A sender to the event loop of the worker.
+    sender: Sender<DedicatedWorkerScriptMsg>,
+}
 
 impl Drop for AutoCloseWorker {
     fn drop(&mut self) {
-        self.0.store(true, Ordering::SeqCst);
+        self.closing.store(true, Ordering::SeqCst);
     }
 }
 
@@ -130,7 +139,7 @@ pub struct GlobalScope {
     #[ignore_malloc_size_of = "Rc<T> is hard"]
     microtask_queue: Rc<MicrotaskQueue>,
 
-    /// Vector storing closing references of all workers
+    /// Vector storing closing references and senders of all workers
     #[ignore_malloc_size_of = "Arc"]
     list_auto_close_worker: DomRefCell<Vec<AutoCloseWorker>>,
 
@@ -192,10 +201,43 @@ impl GlobalScope {
         }
     }
 
-    pub fn track_worker(&self, closing_worker: Arc<AtomicBool>) {
+    pub fn track_worker(
+        &self,
+        closing_worker: Arc<AtomicBool>,
+        worker_sender: Sender<DedicatedWorkerScriptMsg>,
+    ) {
         self.list_auto_close_worker
             .borrow_mut()
-            .push(AutoCloseWorker(closing_worker));
+            .push(AutoCloseWorker {
+                closing: closing_worker,
+                sender: worker_sender,
+            });
+    }
+
+    /// Let the dedicated workers started by this global know that the network went
+    /// offline or came back online, so that they can fire events of their own.
+    pub fn notify_workers_of_network_state(&self, online: bool) {
+        for worker in self.list_auto_close_worker.borrow().iter() {
+            if worker.closing.load(Ordering::SeqCst) {
+                continue;
+            }
+            let _ = worker
+                .sender
+                .send(DedicatedWorkerScriptMsg::NetworkStateChanged(online));
+        }
+    }
+
+    /// Let the dedicated workers started by this global know that the user's
+    /// preferred languages changed, so that they can fire `languagechange`.
+    pub fn notify_workers_of_language_change(&self) {
+        for worker in self.list_auto_close_worker.borrow().iter() {
+            if worker.closing.load(Ordering::SeqCst) {
+                continue;
+            }
+            let _ = worker
+                .sender
+                .send(DedicatedWorkerScriptMsg::LanguagesChanged);
+        }
     }
 
     pub fn track_event_source(&self, event_source: &EventSource) {
@@ -472,6 +514,18 @@ impl GlobalScope {
         unreachable!();
     }
 
+    /// A handle for communicating messages to the WebGL thread, if available
+    /// in this global scope.
+    pub fn webgl_chan(&self) -> Option<WebGLChan> {
+        if let Some(window) = self.downcast::<Window>() {
+            return window.webgl_chan();
+        }
+        if let Some(worker) = self.downcast::<DedicatedWorkerGlobalScope>() {
+            return worker.webgl_chan();
+        }
+        None
+    }
+
     /// `ScriptChan` to send messages to the networking task source of
     /// this global scope.
     pub fn networking_task_source(&self) -> NetworkingTaskSource {
@@ -601,6 +655,10 @@ impl GlobalScope {
         self.timers.clear_timeout_or_interval(self, handle)
     }
 
+    pub fn next_timer_deadline(&self) -> Option<MsDuration> {
+        self.timers.next_timer_deadline()
+    }
+
     pub fn fire_timer(&self, handle: TimerEventId) {
         self.timers.fire_timer(handle, self)
     }
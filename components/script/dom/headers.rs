@@ -280,6 +280,28 @@ impl Headers {
         header_vec.sort();
         header_vec
     }
+
+    /// The header list as name-value pairs, the way a cache stores it.
+    pub fn get_header_pairs(&self) -> Vec<(String, Vec<u8>)> {
+        self.header_list
+            .borrow()
+            .iter()
+            .map(|(name, value)| (name.as_str().to_owned(), value.as_bytes().to_vec()))
+            .collect()
+    }
+
+    /// Replace the header list with name-value pairs coming from a cache.
+    pub fn set_header_pairs(&self, pairs: &[(String, Vec<u8>)]) {
+        let mut header_list = HyperHeaders::new();
+        for &(ref name, ref value) in pairs {
+            let name = HeaderName::from_bytes(name.as_bytes());
+            let value = HeaderValue::from_bytes(value);
+            if let (Ok(name), Ok(value)) = (name, value) {
+                header_list.append(name, value);
+            }
+        }
+        *self.header_list.borrow_mut() = header_list;
+    }
 }
 
 impl Iterable for Headers {
@@ -303,7 +325,7 @@ impl Iterable for Headers {
     }
 }
 
-fn is_cors_safelisted_request_content_type(value: &[u8]) -> bool {
+pub fn is_cors_safelisted_request_content_type(value: &[u8]) -> bool {
     let value_string = if let Ok(s) = str::from_utf8(value) {
         s
     } else {
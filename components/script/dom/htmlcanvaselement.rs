@@ -3,18 +3,22 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::attr::Attr;
+use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HTMLCanvasElementBinding;
 use crate::dom::bindings::codegen::Bindings::HTMLCanvasElementBinding::{
-    HTMLCanvasElementMethods, RenderingContext,
+    BlobCallback, HTMLCanvasElementMethods, RenderingContext,
 };
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLContextAttributes;
+use crate::dom::bindings::codegen::UnionTypes::HTMLCanvasElementOrOffscreenCanvas;
 use crate::dom::bindings::conversions::ConversionResult;
-use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom};
 use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::blob::{Blob, BlobImpl};
 use crate::dom::canvasrenderingcontext2d::{
     CanvasRenderingContext2D, LayoutCanvasRenderingContext2DHelpers,
 };
@@ -22,20 +26,21 @@ use crate::dom::document::Document;
 use crate::dom::element::{AttributeMutation, Element, RawLayoutElementHelpers};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
-use crate::dom::node::{window_from_node, Node};
+use crate::dom::node::{window_from_node, Node, NodeDamage};
+use crate::dom::offscreencanvas::{OffscreenCanvas, OffscreenCanvasPlaceholder};
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::webgl2renderingcontext::WebGL2RenderingContext;
 use crate::dom::webglrenderingcontext::{
     LayoutCanvasWebGLRenderingContextHelpers, WebGLRenderingContext,
 };
+use crate::image_encoder::{self, ImageType};
+use crate::task_source::{TaskSource, TaskSourceName};
 use base64;
 use canvas_traits::canvas::{CanvasId, CanvasMsg, FromScriptMsg};
 use canvas_traits::webgl::{GLContextAttributes, WebGLVersion};
 use dom_struct::dom_struct;
 use euclid::{Rect, Size2D};
 use html5ever::{LocalName, Prefix};
-use image::png::PNGEncoder;
-use image::ColorType;
 use ipc_channel::ipc::IpcSharedMemory;
 use js::error::throw_type_error;
 use js::jsapi::JSContext;
@@ -43,8 +48,12 @@ use js::rust::HandleValue;
 use profile_traits::ipc;
 use script_layout_interface::{HTMLCanvasData, HTMLCanvasDataSource};
 use servo_config::prefs::PREFS;
-use std::cell::Ref;
+use std::cell::{Cell, Ref};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::thread;
 use style::attr::{AttrValue, LengthOrPercentageOrAuto};
+use webrender_api::ImageKey;
 
 const DEFAULT_WIDTH: u32 = 300;
 const DEFAULT_HEIGHT: u32 = 150;
@@ -61,6 +70,14 @@ pub enum CanvasContext {
 pub struct HTMLCanvasElement {
     htmlelement: HTMLElement,
     context: DomRefCell<Option<CanvasContext>>,
+    /// The image the `OffscreenCanvas` this canvas was transferred to
+    /// presents its frames in.
+    #[ignore_malloc_size_of = "Defined in webrender"]
+    placeholder_image: Cell<Option<ImageKey>>,
+    /// The callbacks of the `toBlob()` calls whose bitmap is being encoded.
+    #[ignore_malloc_size_of = "can't measure Rc values"]
+    blob_callbacks: DomRefCell<HashMap<u32, Rc<BlobCallback>>>,
+    next_blob_callback_id: Cell<u32>,
 }
 
 impl HTMLCanvasElement {
@@ -72,6 +89,9 @@ impl HTMLCanvasElement {
         HTMLCanvasElement {
             htmlelement: HTMLElement::new_inherited(local_name, prefix, document),
             context: DomRefCell::new(None),
+            placeholder_image: Cell::new(None),
+            blob_callbacks: DomRefCell::new(HashMap::new()),
+            next_blob_callback_id: Cell::new(0),
         }
     }
 
@@ -111,6 +131,50 @@ impl HTMLCanvasElement {
             _ => true,
         }
     }
+
+    /// Returns a copy of the bitmap as non-premultiplied RGBA pixels, or
+    /// `None` if it is empty or couldn't be read.
+    fn get_content(&self) -> Option<Vec<u8>> {
+        let size = self.get_size();
+        if size.width == 0 || size.height == 0 {
+            return None;
+        }
+        match *self.context.borrow() {
+            Some(CanvasContext::Context2d(ref context)) => {
+                Some(context.get_rect(Rect::from_size(size)))
+            },
+            Some(CanvasContext::WebGL(ref context)) => context.get_image_data(size),
+            Some(CanvasContext::WebGL2(ref context)) => context.base_context().get_image_data(size),
+            // Each pixel is fully-transparent black.
+            None => Some(vec![0; size.area() as usize * 4]),
+        }
+    }
+
+    /// Calls the callback of the `toBlob()` call `id` with the encoded bitmap.
+    fn invoke_blob_callback(&self, id: u32, encoded: Option<Vec<u8>>, image_type: ImageType) {
+        let callback = match self.blob_callbacks.borrow_mut().remove(&id) {
+            Some(callback) => callback,
+            None => return,
+        };
+        let blob = encoded.map(|bytes| {
+            Blob::new(
+                &self.global(),
+                BlobImpl::new_from_bytes(bytes),
+                image_type.mime_type().to_owned(),
+            )
+        });
+        let _ = callback.Call__(blob.as_ref().map(|blob| &**blob), ExceptionHandling::Report);
+    }
+}
+
+/// Returns the quality argument of `toDataURL()` and `toBlob()` if it is a
+/// number.
+fn quality_from_value(quality: HandleValue) -> Option<f64> {
+    if quality.is_number() {
+        Some(quality.to_number())
+    } else {
+        None
+    }
 }
 
 pub trait LayoutHTMLCanvasElementHelpers {
@@ -135,7 +199,10 @@ impl LayoutHTMLCanvasElementHelpers for LayoutDom<HTMLCanvasElement> {
                 Some(&CanvasContext::WebGL2(ref context)) => {
                     context.to_layout().canvas_data_source()
                 },
-                None => HTMLCanvasDataSource::Image(None),
+                None => match canvas.placeholder_image.get() {
+                    Some(image_key) => HTMLCanvasDataSource::Placeholder(image_key),
+                    None => HTMLCanvasDataSource::Image(None),
+                },
             };
 
             let width_attr = canvas
@@ -221,7 +288,14 @@ impl HTMLCanvasElement {
         let window = window_from_node(self);
         let size = self.get_size();
         let attrs = Self::get_gl_attributes(cx, options)?;
-        let context = WebGLRenderingContext::new(&window, self, WebGLVersion::WebGL1, size, attrs)?;
+        let canvas = HTMLCanvasElementOrOffscreenCanvas::HTMLCanvasElement(DomRoot::from_ref(self));
+        let context = WebGLRenderingContext::new(
+            window.upcast(),
+            &canvas,
+            WebGLVersion::WebGL1,
+            size,
+            attrs,
+        )?;
         *self.context.borrow_mut() = Some(CanvasContext::WebGL(Dom::from_ref(&*context)));
         Some(context)
     }
@@ -244,7 +318,8 @@ impl HTMLCanvasElement {
         let window = window_from_node(self);
         let size = self.get_size();
         let attrs = Self::get_gl_attributes(cx, options)?;
-        let context = WebGL2RenderingContext::new(&window, self, size, attrs)?;
+        let canvas = HTMLCanvasElementOrOffscreenCanvas::HTMLCanvasElement(DomRoot::from_ref(self));
+        let context = WebGL2RenderingContext::new(window.upcast(), &canvas, size, attrs)?;
         *self.context.borrow_mut() = Some(CanvasContext::WebGL2(Dom::from_ref(&*context)));
         Some(context)
     }
@@ -259,7 +334,7 @@ impl HTMLCanvasElement {
     }
 
     #[allow(unsafe_code)]
-    unsafe fn get_gl_attributes(
+    pub unsafe fn get_gl_attributes(
         cx: *mut JSContext,
         options: HandleValue,
     ) -> Option<GLContextAttributes> {
@@ -334,8 +409,11 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
         cx: *mut JSContext,
         id: DOMString,
         options: HandleValue,
-    ) -> Option<RenderingContext> {
-        match &*id {
+    ) -> Fallible<Option<RenderingContext>> {
+        if self.placeholder_image.get().is_some() {
+            return Err(Error::InvalidState);
+        }
+        Ok(match &*id {
             "2d" => self
                 .get_or_init_2d_context()
                 .map(RenderingContext::CanvasRenderingContext2D),
@@ -346,7 +424,7 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
                 .get_or_init_webgl2_context(cx, options)
                 .map(RenderingContext::WebGL2RenderingContext),
             _ => None,
-        }
+        })
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-canvas-todataurl
@@ -354,55 +432,97 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
     unsafe fn ToDataURL(
         &self,
         _context: *mut JSContext,
-        _mime_type: Option<DOMString>,
-        _quality: HandleValue,
+        mime_type: Option<DOMString>,
+        quality: HandleValue,
     ) -> Fallible<USVString> {
         // Step 1.
         if !self.origin_is_clean() {
             return Err(Error::Security);
         }
 
-        // Step 2.
-        if self.Width() == 0 || self.Height() == 0 {
-            return Ok(USVString("data:,".into()));
-        }
-
-        // Step 3.
-        let file = match *self.context.borrow() {
-            Some(CanvasContext::Context2d(ref context)) => {
-                context.get_rect(Rect::from_size(self.get_size()))
-            },
-            Some(CanvasContext::WebGL(ref context)) => {
-                match context.get_image_data(self.get_size()) {
-                    Some(data) => data,
-                    None => return Ok(USVString("data:,".into())),
-                }
-            },
-            Some(CanvasContext::WebGL2(ref context)) => {
-                match context.base_context().get_image_data(self.get_size()) {
-                    Some(data) => data,
-                    None => return Ok(USVString("data:,".into())),
-                }
-            },
-            None => {
-                // Each pixel is fully-transparent black.
-                vec![0; (self.Width() * self.Height() * 4) as usize]
-            },
+        // Steps 2-3.
+        let file = match self.get_content() {
+            Some(file) => file,
+            None => return Ok(USVString("data:,".into())),
         };
 
-        // FIXME: Only handle image/png for now.
-        let mut png = Vec::new();
-        // FIXME(nox): https://github.com/PistonDevelopers/image-png/issues/86
-        // FIXME(nox): https://github.com/PistonDevelopers/image-png/issues/87
-        PNGEncoder::new(&mut png)
-            .encode(&file, self.Width(), self.Height(), ColorType::RGBA(8))
-            .unwrap();
-        let mut url = "data:image/png;base64,".to_owned();
+        let size = self.get_size();
+        let image_type = ImageType::new(mime_type.as_ref().map(|t| &**t), size);
+        let encoded = image_encoder::encode(image_type, &file, size, quality_from_value(quality));
+        let mut url = format!("data:{};base64,", image_type.mime_type());
         // FIXME(nox): Should this use base64::URL_SAFE?
         // FIXME(nox): https://github.com/alicemaz/rust-base64/pull/56
-        base64::encode_config_buf(&png, base64::STANDARD, &mut url);
+        base64::encode_config_buf(&encoded, base64::STANDARD, &mut url);
         Ok(USVString(url))
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-canvas-toblob
+    #[allow(unsafe_code)]
+    unsafe fn ToBlob(
+        &self,
+        _context: *mut JSContext,
+        callback: Rc<BlobCallback>,
+        mime_type: Option<DOMString>,
+        quality: HandleValue,
+    ) -> ErrorResult {
+        // Step 1.
+        if !self.origin_is_clean() {
+            return Err(Error::Security);
+        }
+
+        // Steps 2-3.
+        let file = self.get_content();
+        let size = self.get_size();
+        let image_type = ImageType::new(mime_type.as_ref().map(|t| &**t), size);
+        let quality = quality_from_value(quality);
+
+        let id = self.next_blob_callback_id.get();
+        self.next_blob_callback_id.set(id.wrapping_add(1));
+        self.blob_callbacks.borrow_mut().insert(id, callback);
+
+        // Step 4, the bitmap is encoded off the script thread.
+        let canvas = Trusted::new(self);
+        let global = self.global();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let task_source = global.dom_manipulation_task_source();
+        thread::Builder::new()
+            .name("canvas blob encoder".to_owned())
+            .spawn(move || {
+                let encoded =
+                    file.map(|file| image_encoder::encode(image_type, &file, size, quality));
+                let task = task!(invoke_blob_callback: move || {
+                    canvas.root().invoke_blob_callback(id, encoded, image_type);
+                });
+                let _ = task_source.queue_with_canceller(task, &canceller);
+            })
+            .expect("Thread spawning failed");
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-canvas-transfercontroltooffscreen
+    fn TransferControlToOffscreen(&self) -> Fallible<DomRoot<OffscreenCanvas>> {
+        // Step 1.
+        if self.context.borrow().is_some() || self.placeholder_image.get().is_some() {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 2-4.
+        let window = window_from_node(self);
+        let placeholder = OffscreenCanvasPlaceholder::new(
+            window.get_webrender_api_sender(),
+            window.webrender_document(),
+        );
+        self.placeholder_image.set(Some(placeholder.image_key()));
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+
+        // Step 5.
+        Ok(OffscreenCanvas::new(
+            window.upcast(),
+            self.Width() as u64,
+            self.Height() as u64,
+            Some(placeholder),
+        ))
+    }
 }
 
 impl VirtualMethods for HTMLCanvasElement {
@@ -10,28 +10,34 @@ use crate::dom::bindings::codegen::Bindings::HTMLElementBinding;
 use crate::dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
-use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::inheritance::{ElementTypeId, HTMLElementTypeId, NodeTypeId};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
+use crate::dom::customelementregistry::CallbackReaction;
 use crate::dom::document::{Document, FocusType};
 use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::domstringmap::DOMStringMap;
-use crate::dom::element::{AttributeMutation, Element};
+use crate::dom::element::{AttributeMutation, CustomElementState, Element};
+use crate::dom::elementinternals::ElementInternals;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlbodyelement::HTMLBodyElement;
 use crate::dom::htmlbrelement::HTMLBRElement;
+use crate::dom::htmlformelement::{FormControl, FormDatum, HTMLFormElement};
 use crate::dom::htmlframesetelement::HTMLFrameSetElement;
 use crate::dom::htmlhtmlelement::HTMLHtmlElement;
 use crate::dom::htmlinputelement::{HTMLInputElement, InputType};
 use crate::dom::htmllabelelement::HTMLLabelElement;
 use crate::dom::node::{document_from_node, window_from_node};
-use crate::dom::node::{Node, NodeFlags};
+use crate::dom::node::{Node, NodeFlags, UnbindContext};
 use crate::dom::nodelist::NodeList;
 use crate::dom::text::Text;
+use crate::dom::validation::Validatable;
+use crate::dom::validitystate::ValidationFlags;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::script_thread::ScriptThread;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
 use script_layout_interface::message::QueryMsg;
@@ -46,6 +52,9 @@ pub struct HTMLElement {
     element: Element,
     style_decl: MutNullableDom<CSSStyleDeclaration>,
     dataset: MutNullableDom<DOMStringMap>,
+    /// <https://html.spec.whatwg.org/multipage/#attached-internals>, also created for
+    /// form-associated custom elements that did not attach their internals.
+    element_internals: MutNullableDom<ElementInternals>,
 }
 
 impl HTMLElement {
@@ -73,6 +82,7 @@ impl HTMLElement {
             ),
             style_decl: Default::default(),
             dataset: Default::default(),
+            element_internals: Default::default(),
         }
     }
 
@@ -517,6 +527,51 @@ impl HTMLElementMethods for HTMLElement {
         // Step 7.
         Node::replace_all(Some(fragment.upcast()), self.upcast::<Node>());
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-attachinternals
+    fn AttachInternals(&self) -> Fallible<DomRoot<ElementInternals>> {
+        let element = self.upcast::<Element>();
+
+        // Step 1
+        if element.get_is().is_some() {
+            return Err(Error::NotSupported);
+        }
+
+        // Step 2
+        let definition = match document_from_node(self).lookup_custom_element_definition(
+            &ns!(html),
+            element.local_name(),
+            None,
+        ) {
+            Some(definition) => definition,
+            None => return Err(Error::InvalidState),
+        };
+
+        // Step 3
+        if definition.disable_internals {
+            return Err(Error::NotSupported);
+        }
+
+        // Step 4
+        if self
+            .element_internals
+            .get()
+            .map_or(false, |internals| internals.attached())
+        {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 5
+        match element.get_custom_element_state() {
+            CustomElementState::Precustomized | CustomElementState::Custom => {},
+            _ => return Err(Error::NotSupported),
+        }
+
+        // Steps 6-7
+        let internals = self.ensure_element_internals();
+        internals.set_attached();
+        Ok(internals)
+    }
 }
 
 fn append_text_node_to_fragment(document: &Document, fragment: &DocumentFragment, text: String) {
@@ -635,12 +690,61 @@ impl HTMLElement {
                 HTMLElementTypeId::HTMLProgressElement |
                 HTMLElementTypeId::HTMLSelectElement |
                 HTMLElementTypeId::HTMLTextAreaElement => true,
+                HTMLElementTypeId::HTMLElement => self.is_form_associated_custom_element(),
                 _ => false,
             },
             _ => false,
         }
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#form-associated-custom-element>
+    pub fn is_form_associated_custom_element(&self) -> bool {
+        self.upcast::<Element>()
+            .get_custom_element_definition()
+            .map_or(false, |definition| definition.form_associated)
+    }
+
+    fn ensure_element_internals(&self) -> DomRoot<ElementInternals> {
+        self.element_internals
+            .or_init(|| ElementInternals::new(self))
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#face-entry-construction>
+    pub fn form_datums(&self) -> Vec<FormDatum> {
+        self.element_internals
+            .get()
+            .map_or(Vec::new(), |internals| internals.form_datums())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#concept-form-reset-control>
+    pub fn form_reset(&self) {
+        if self.is_form_associated_custom_element() {
+            ScriptThread::enqueue_callback_reaction(
+                self.upcast::<Element>(),
+                CallbackReaction::FormReset,
+                None,
+            );
+        }
+    }
+
+    /// Updates the disabled state of a form-associated custom element and enqueues a
+    /// formDisabledCallback reaction if it changed.
+    /// <https://html.spec.whatwg.org/multipage/#face-disabled>
+    pub fn update_form_associated_disabled_state(&self) {
+        let element = self.upcast::<Element>();
+        let was_disabled = element.disabled_state();
+        element.check_disabled_attribute();
+        element.check_ancestors_disabled_state_for_form_control();
+        let disabled = element.disabled_state();
+        if disabled != was_disabled {
+            ScriptThread::enqueue_callback_reaction(
+                element,
+                CallbackReaction::FormDisabled(disabled),
+                None,
+            );
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#category-listed
     pub fn is_listed_element(&self) -> bool {
         // Servo does not implement HTMLKeygenElement
@@ -658,6 +762,7 @@ impl HTMLElement {
                 HTMLElementTypeId::HTMLOutputElement |
                 HTMLElementTypeId::HTMLSelectElement |
                 HTMLElementTypeId::HTMLTextAreaElement => true,
+                HTMLElementTypeId::HTMLElement => self.is_form_associated_custom_element(),
                 _ => false,
             },
             _ => false,
@@ -736,6 +841,12 @@ impl VirtualMethods for HTMLElement {
                     DOMString::from(&**attr.value()),
                 );
             },
+            (&local_name!("disabled"), _) if self.is_form_associated_custom_element() => {
+                self.update_form_associated_disabled_state();
+            },
+            (&local_name!("form"), _) if self.is_form_associated_custom_element() => {
+                self.form_attribute_mutated(mutation);
+            },
             _ => {},
         }
     }
@@ -745,6 +856,18 @@ impl VirtualMethods for HTMLElement {
             s.bind_to_tree(tree_in_doc);
         }
         self.update_sequentially_focusable_status();
+
+        if self.is_form_associated_custom_element() {
+            self.update_form_associated_disabled_state();
+        }
+    }
+
+    fn unbind_from_tree(&self, context: &UnbindContext) {
+        self.super_type().unwrap().unbind_from_tree(context);
+
+        if self.is_form_associated_custom_element() {
+            self.update_form_associated_disabled_state();
+        }
     }
 
     fn parse_plain_attribute(&self, name: &LocalName, value: DOMString) -> AttrValue {
@@ -758,3 +881,43 @@ impl VirtualMethods for HTMLElement {
         }
     }
 }
+
+impl FormControl for HTMLElement {
+    fn form_owner(&self) -> Option<DomRoot<HTMLFormElement>> {
+        debug_assert!(self.is_form_associated_custom_element());
+        self.element_internals
+            .get()
+            .and_then(|internals| internals.form_owner())
+    }
+
+    fn set_form_owner(&self, form: Option<&HTMLFormElement>) {
+        debug_assert!(self.is_form_associated_custom_element());
+        self.ensure_element_internals().set_form_owner(form);
+
+        // https://html.spec.whatwg.org/multipage/#reset-the-form-owner
+        // Step 6
+        ScriptThread::enqueue_callback_reaction(
+            self.upcast::<Element>(),
+            CallbackReaction::FormAssociated(form.map(DomRoot::from_ref)),
+            None,
+        );
+    }
+
+    fn to_element<'a>(&'a self) -> &'a Element {
+        self.upcast::<Element>()
+    }
+}
+
+impl Validatable for HTMLElement {
+    // https://html.spec.whatwg.org/multipage/#candidate-for-constraint-validation
+    fn is_instance_validatable(&self) -> bool {
+        let element = self.upcast::<Element>();
+        !element.disabled_state() && !element.has_attribute(&local_name!("readonly"))
+    }
+
+    fn validate(&self, _validate_flags: ValidationFlags) -> bool {
+        self.element_internals
+            .get()
+            .map_or(true, |internals| internals.satisfies_constraints())
+    }
+}
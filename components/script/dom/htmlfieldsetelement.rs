@@ -142,17 +142,35 @@ impl VirtualMethods for HTMLFieldSetElement {
                             NodeTypeId::Element(ElementTypeId::HTMLElement(
                                 HTMLElementTypeId::HTMLTextAreaElement,
                             )) => true,
+                            NodeTypeId::Element(ElementTypeId::HTMLElement(
+                                HTMLElementTypeId::HTMLElement,
+                            )) => descendant
+                                .downcast::<HTMLElement>()
+                                .unwrap()
+                                .is_form_associated_custom_element(),
                             _ => false,
                         })
                 });
                 if disabled_state {
                     for field in fields {
+                        if let Some(custom) = field.downcast::<HTMLElement>() {
+                            if custom.is_form_associated_custom_element() {
+                                custom.update_form_associated_disabled_state();
+                                continue;
+                            }
+                        }
                         let el = field.downcast::<Element>().unwrap();
                         el.set_disabled_state(true);
                         el.set_enabled_state(false);
                     }
                 } else {
                     for field in fields {
+                        if let Some(custom) = field.downcast::<HTMLElement>() {
+                            if custom.is_form_associated_custom_element() {
+                                custom.update_form_associated_disabled_state();
+                                continue;
+                            }
+                        }
                         let el = field.downcast::<Element>().unwrap();
                         el.check_disabled_attribute();
                         el.check_ancestors_disabled_state_for_form_control();
@@ -217,6 +217,13 @@ impl HTMLFormElementMethods for HTMLFormElement {
                         HTMLElementTypeId::HTMLTextAreaElement => {
                             elem.downcast::<HTMLTextAreaElement>().unwrap().form_owner()
                         },
+                        HTMLElementTypeId::HTMLElement => {
+                            let html_elem = elem.downcast::<HTMLElement>().unwrap();
+                            if !html_elem.is_form_associated_custom_element() {
+                                return false;
+                            }
+                            html_elem.form_owner()
+                        },
                         _ => {
                             debug_assert!(
                                 !elem.downcast::<HTMLElement>().unwrap().is_listed_element() ||
@@ -676,6 +683,12 @@ impl HTMLFormElement {
                             });
                         }
                     },
+                    HTMLElementTypeId::HTMLElement => {
+                        let custom = child.downcast::<HTMLElement>().unwrap();
+                        if custom.is_form_associated_custom_element() {
+                            data_set.append(&mut custom.form_datums());
+                        }
+                    },
                     _ => (),
                 }
             }
@@ -809,6 +822,9 @@ impl HTMLFormElement {
                 )) => {
                     // Unimplemented
                 },
+                NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLElement)) => {
+                    child.downcast::<HTMLElement>().unwrap().form_reset();
+                },
                 _ => {},
             }
         }
@@ -1270,6 +1286,14 @@ impl FormControlElementHelpers for Element {
             NodeTypeId::Element(ElementTypeId::HTMLElement(
                 HTMLElementTypeId::HTMLTextAreaElement,
             )) => Some(self.downcast::<HTMLTextAreaElement>().unwrap() as &dyn FormControl),
+            NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLElement)) => {
+                let element = self.downcast::<HTMLElement>().unwrap();
+                if element.is_form_associated_custom_element() {
+                    Some(element as &dyn FormControl)
+                } else {
+                    None
+                }
+            },
             _ => None,
         }
     }
@@ -12,9 +12,13 @@ use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::CanPlayTyp
 use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementConstants;
 use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLSourceElementBinding::HTMLSourceElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLTrackElementBinding::HTMLTrackElementMethods;
 use crate::dom::bindings::codegen::Bindings::MediaErrorBinding::MediaErrorConstants::*;
 use crate::dom::bindings::codegen::Bindings::MediaErrorBinding::MediaErrorMethods;
-use crate::dom::bindings::codegen::Bindings::TextTrackBinding::{TextTrackKind, TextTrackMode};
+use crate::dom::bindings::codegen::Bindings::TextTrackBinding::{
+    TextTrackKind, TextTrackMethods, TextTrackMode,
+};
+use crate::dom::bindings::codegen::Bindings::TextTrackListBinding::TextTrackListMethods;
 use crate::dom::bindings::codegen::InheritTypes::{ElementTypeId, HTMLElementTypeId};
 use crate::dom::bindings::codegen::InheritTypes::{HTMLMediaElementTypeId, NodeTypeId};
 use crate::dom::bindings::codegen::UnionTypes::VideoTrackOrAudioTrackOrTextTrack;
@@ -33,8 +37,10 @@ use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlsourceelement::HTMLSourceElement;
+use crate::dom::htmltrackelement::HTMLTrackElement;
 use crate::dom::htmlvideoelement::HTMLVideoElement;
 use crate::dom::mediaerror::MediaError;
+use crate::dom::mediasource::MediaSource;
 use crate::dom::node::{document_from_node, window_from_node, Node, NodeDamage, UnbindContext};
 use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::promise::Promise;
@@ -45,11 +51,13 @@ use crate::dom::trackevent::TrackEvent;
 use crate::dom::videotrack::VideoTrack;
 use crate::dom::videotracklist::VideoTrackList;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::dom::vttcue::VTTCue;
 use crate::fetch::FetchCanceller;
 use crate::microtask::{Microtask, MicrotaskRunnable};
 use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
 use crate::script_thread::ScriptThread;
 use crate::task_source::TaskSource;
+use crate::webvtt::TextAlignment;
 use dom_struct::dom_struct;
 use headers_core::HeaderMapExt;
 use headers_ext::{ContentLength, ContentRange};
@@ -63,13 +71,14 @@ use net_traits::image_cache::ImageResponse;
 use net_traits::request::{CredentialsMode, Destination, RequestInit};
 use net_traits::{CoreResourceMsg, FetchChannels, FetchMetadata, FetchResponseListener, Metadata};
 use net_traits::{NetworkError, ResourceFetchTiming, ResourceTimingType};
-use script_layout_interface::HTMLMediaData;
+use script_layout_interface::{HTMLMediaCue, HTMLMediaCueAlign, HTMLMediaData};
 use servo_config::prefs::PREFS;
 use servo_media::player::frame::{Frame, FrameRenderer};
 use servo_media::player::{PlaybackState, Player, PlayerError, PlayerEvent, StreamType};
 use servo_media::ServoMedia;
 use servo_url::ServoUrl;
 use std::cell::Cell;
+use std::cmp::Ordering;
 use std::collections::VecDeque;
 use std::f64;
 use std::mem;
@@ -135,7 +144,7 @@ impl FrameRenderer for MediaFrameRenderer {
                 if let Some(old_image_key) = self.old_frame.take() {
                     txn.delete_image(old_image_key);
                 }
-            }
+            },
             Some((ref mut image_key, ref mut width, ref mut height)) => {
                 self.old_frame = Some(*image_key);
 
@@ -219,11 +228,16 @@ pub struct HTMLMediaElement {
     video_tracks_list: MutNullableDom<VideoTrackList>,
     /// https://html.spec.whatwg.org/multipage/#dom-media-texttracks
     text_tracks_list: MutNullableDom<TextTrackList>,
+    /// The active cues of the showing text tracks, as rendered by layout.
+    displayed_cues: DomRefCell<Vec<HTMLMediaCue>>,
     /// Time of last timeupdate notification.
     #[ignore_malloc_size_of = "Defined in time"]
     next_timeupdate_event: Cell<Timespec>,
     /// Latest fetch request context.
     current_fetch_context: DomRefCell<Option<HTMLMediaElementFetchContext>>,
+    /// The MediaSource object attached to this element, if any.
+    /// <https://w3c.github.io/media-source/#mediasource-attach>
+    media_source: MutNullableDom<MediaSource>,
 }
 
 /// <https://html.spec.whatwg.org/multipage/#dom-media-networkstate>
@@ -282,8 +296,10 @@ impl HTMLMediaElement {
             audio_tracks_list: Default::default(),
             video_tracks_list: Default::default(),
             text_tracks_list: Default::default(),
+            displayed_cues: DomRefCell::new(vec![]),
             next_timeupdate_event: Cell::new(time::get_time() + Duration::milliseconds(250)),
             current_fetch_context: DomRefCell::new(None),
+            media_source: Default::default(),
         }
     }
 
@@ -326,9 +342,35 @@ impl HTMLMediaElement {
 
     /// https://html.spec.whatwg.org/multipage/#time-marches-on
     fn time_marches_on(&self) {
+        let window = window_from_node(self);
+
+        // Steps 1-5.
+        let current_time = self.playback_position.get();
+        let mut cue_events = vec![];
+        let mut affected_tracks = vec![];
+        let mut pause_on_exit = false;
+        let text_tracks = self.TextTracks();
+        for index in 0..text_tracks.Length() {
+            let track = match text_tracks.item(index as usize) {
+                Some(track) => track,
+                None => continue,
+            };
+            let (entered_cues, exited_cues) = track.update_active_cues(current_time);
+            if entered_cues.is_empty() && exited_cues.is_empty() {
+                continue;
+            }
+            for cue in entered_cues {
+                cue_events.push((cue.start_time(), atom!("enter"), cue));
+            }
+            for cue in exited_cues {
+                pause_on_exit |= cue.pause_on_exit();
+                cue_events.push((cue.end_time(), atom!("exit"), cue));
+            }
+            affected_tracks.push(track);
+        }
+
         // Step 6.
         if time::get_time() > self.next_timeupdate_event.get() {
-            let window = window_from_node(self);
             window
                 .task_manager()
                 .media_element_task_source()
@@ -336,6 +378,86 @@ impl HTMLMediaElement {
             self.next_timeupdate_event
                 .set(time::get_time() + Duration::milliseconds(350));
         }
+
+        // Step 7.
+        if pause_on_exit && !self.Paused() {
+            self.internal_pause_steps();
+        }
+
+        // Steps 12-14.
+        cue_events.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        for (_, name, cue) in cue_events {
+            window
+                .task_manager()
+                .media_element_task_source()
+                .queue_simple_event(cue.upcast(), name, &window);
+        }
+
+        // Steps 15-16.
+        for track in &affected_tracks {
+            window
+                .task_manager()
+                .media_element_task_source()
+                .queue_simple_event(track.upcast(), atom!("cuechange"), &window);
+            let track_element = self
+                .upcast::<Node>()
+                .children()
+                .filter_map(DomRoot::downcast::<HTMLTrackElement>)
+                .find(|element| &*element.Track() == &**track);
+            if let Some(track_element) = track_element {
+                window
+                    .task_manager()
+                    .media_element_task_source()
+                    .queue_simple_event(track_element.upcast(), atom!("cuechange"), &window);
+            }
+        }
+
+        // Step 17.
+        let displayed_cues = self.showing_cues();
+        if *self.displayed_cues.borrow() != displayed_cues {
+            *self.displayed_cues.borrow_mut() = displayed_cues;
+            self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        }
+    }
+
+    /// The text of the cues of the showing text tracks that are currently
+    /// active, with their settings, for rendering over the video.
+    /// <https://w3c.github.io/webvtt/#rules-for-updating-the-display-of-webvtt-text-tracks>
+    fn showing_cues(&self) -> Vec<HTMLMediaCue> {
+        let text_tracks = match self.text_tracks_list.get() {
+            Some(text_tracks) => text_tracks,
+            None => return vec![],
+        };
+        let mut cues = vec![];
+        for index in 0..text_tracks.Length() {
+            let track = match text_tracks.item(index as usize) {
+                Some(track) => track,
+                None => continue,
+            };
+            if track.Mode() != TextTrackMode::Showing {
+                continue;
+            }
+            for cue in track.active_cues() {
+                let cue = match cue.downcast::<VTTCue>() {
+                    Some(cue) => cue,
+                    None => continue,
+                };
+                let settings = cue.settings();
+                cues.push(HTMLMediaCue {
+                    text: cue.text_content(),
+                    line: settings.line,
+                    snap_to_lines: settings.snap_to_lines,
+                    position: settings.position,
+                    size: settings.size,
+                    align: match settings.align {
+                        TextAlignment::Start | TextAlignment::Left => HTMLMediaCueAlign::Start,
+                        TextAlignment::Center => HTMLMediaCueAlign::Center,
+                        TextAlignment::End | TextAlignment::Right => HTMLMediaCueAlign::End,
+                    },
+                });
+            }
+        }
+        cues
     }
 
     /// <https://html.spec.whatwg.org/multipage/#internal-pause-steps>
@@ -745,6 +867,11 @@ impl HTMLMediaElement {
         // Step 4.
         match resource {
             Resource::Url(url) => {
+                if let Some(media_source) = window_from_node(self).media_source_for_url(&url) {
+                    self.attach_media_source(&media_source);
+                    return;
+                }
+
                 // Step 4.remote.1.
                 if self.Preload() == "none" && !self.autoplaying.get() {
                     // Step 4.remote.1.1.
@@ -792,6 +919,84 @@ impl HTMLMediaElement {
         }
     }
 
+    /// <https://w3c.github.io/media-source/#mediasource-attach>
+    fn attach_media_source(&self, media_source: &MediaSource) {
+        // If readyState is not "closed", run the dedicated media source failure steps.
+        if !media_source.is_closed() {
+            self.queue_dedicated_media_source_failure_steps();
+            return;
+        }
+
+        // Appended segments are pushed as they come, so the player cannot seek
+        // into the byte stream by itself.
+        if let Err(e) = self.player.set_stream_type(StreamType::Stream) {
+            eprintln!("Could not set stream type to Stream. {:?}", e);
+        }
+
+        self.media_source.set(Some(media_source));
+        media_source.attach(self);
+
+        // Continue the resource fetch algorithm as if the resource was local.
+        self.delay_load_event(false);
+    }
+
+    /// Pushes a media segment appended to a SourceBuffer of the attached
+    /// MediaSource to the player.
+    pub fn push_media_source_data(&self, data: Vec<u8>) -> Result<(), ()> {
+        self.player.push_data(data).map_err(|e| {
+            eprintln!("Could not push media source data to player {:?}", e);
+        })
+    }
+
+    /// <https://w3c.github.io/media-source/#end-of-stream-algorithm>
+    pub fn media_source_end_of_stream(&self) {
+        if let Err(e) = self.player.end_of_stream() {
+            eprintln!("Could not signal EOS to player {:?}", e);
+        }
+    }
+
+    /// Runs the error steps of the end of stream algorithm, `code` being either
+    /// `MEDIA_ERR_NETWORK` or `MEDIA_ERR_DECODE`.
+    /// <https://w3c.github.io/media-source/#end-of-stream-algorithm>
+    pub fn media_source_error(&self, code: u16) {
+        if self.ready_state.get() == ReadyState::HaveNothing {
+            self.queue_dedicated_media_source_failure_steps();
+            return;
+        }
+
+        let window = window_from_node(self);
+        self.error.set(Some(&*MediaError::new(&window, code)));
+        self.network_state.set(NetworkState::Idle);
+        window
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(self.upcast(), atom!("error"), &window);
+    }
+
+    /// <https://w3c.github.io/media-source/#duration-change-algorithm>
+    pub fn media_source_duration_changed(&self, duration: f64) {
+        if self.duration.get() == duration {
+            return;
+        }
+        self.duration.set(duration);
+        let window = window_from_node(self);
+        window
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(self.upcast(), atom!("durationchange"), &window);
+    }
+
+    /// The time ranges buffered by the player.
+    pub fn buffered_ranges(&self) -> TimeRangesContainer {
+        let mut buffered = TimeRangesContainer::new();
+        if let Ok(ranges) = self.player.buffered() {
+            for range in ranges {
+                let _ = buffered.add(range.start as f64, range.end as f64);
+            }
+        }
+        buffered
+    }
+
     /// Queues a task to run the [dedicated media source failure steps][steps].
     ///
     /// [steps]: https://html.spec.whatwg.org/multipage/#dedicated-media-source-failure-steps
@@ -915,7 +1120,9 @@ impl HTMLMediaElement {
             }
 
             // Step 6.3.
-            // FIXME(nox): Detach MediaSource media provider object.
+            if let Some(media_source) = self.media_source.take() {
+                media_source.detach();
+            }
 
             // Step 6.4.
             self.AudioTracks().clear();
@@ -1340,11 +1547,14 @@ impl HTMLMediaElement {
 
                 // Step 4.
                 let previous_duration = self.duration.get();
-                if let Some(duration) = metadata.duration {
-                    self.duration.set(duration.as_secs() as f64);
-                } else {
-                    self.duration.set(f64::INFINITY);
-                }
+                let duration = metadata
+                    .duration
+                    .map_or(f64::INFINITY, |duration| duration.as_secs() as f64);
+                let duration = match self.media_source.get() {
+                    Some(ref media_source) => media_source.init_segment_duration(duration),
+                    None => duration,
+                };
+                self.duration.set(duration);
                 if previous_duration != self.duration.get() {
                     let window = window_from_node(self);
                     let task_source = window.task_manager().media_element_task_source();
@@ -1794,13 +2004,7 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-media-buffered
     fn Buffered(&self) -> DomRoot<TimeRanges> {
-        let mut buffered = TimeRangesContainer::new();
-        if let Ok(ranges) = self.player.buffered() {
-            for range in ranges {
-                let _ = buffered.add(range.start as f64, range.end as f64);
-            }
-        }
-        TimeRanges::new(self.global().as_window(), buffered)
+        TimeRanges::new(self.global().as_window(), self.buffered_ranges())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-media-audiotracks
@@ -1926,6 +2130,7 @@ impl LayoutHTMLMediaElementHelpers for LayoutDom<HTMLMediaElement> {
         let media = unsafe { &*self.unsafe_get() };
         HTMLMediaData {
             current_frame: media.frame_renderer.lock().unwrap().current_frame.clone(),
+            cues: unsafe { media.displayed_cues.borrow_for_layout().clone() },
         }
     }
 }
@@ -2,23 +2,47 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::document_loader::{LoadBlocker, LoadType};
+use crate::dom::attr::Attr;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLTrackElementBinding::{
     self, HTMLTrackElementConstants, HTMLTrackElementMethods,
 };
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::TextTrackBinding::{TextTrackMethods, TextTrackMode};
+use crate::dom::bindings::codegen::Bindings::TextTrackListBinding::TextTrackListMethods;
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::document::Document;
-use crate::dom::element::Element;
+use crate::dom::element::{AttributeMutation, Element};
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
-use crate::dom::node::Node;
+use crate::dom::htmlmediaelement::HTMLMediaElement;
+use crate::dom::node::{document_from_node, window_from_node, Node, UnbindContext};
+use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::texttrack::TextTrack;
+use crate::dom::virtualmethods::VirtualMethods;
+use crate::dom::vttcue::VTTCue;
+use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
+use crate::webvtt;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use net_traits::request::{CorsSettings, CredentialsMode, Destination, RequestInit, RequestMode};
+use net_traits::{FetchMetadata, FetchResponseListener, NetworkError};
+use net_traits::{ResourceFetchTiming, ResourceTimingType};
+use servo_atoms::Atom;
+use servo_url::ServoUrl;
+use std::cell::Cell;
+use std::sync::{Arc, Mutex};
 
 #[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
 #[repr(u16)]
-#[allow(unused)]
 pub enum ReadyState {
     None = HTMLTrackElementConstants::NONE,
     Loading = HTMLTrackElementConstants::LOADING,
@@ -29,8 +53,13 @@ pub enum ReadyState {
 #[dom_struct]
 pub struct HTMLTrackElement {
     htmlelement: HTMLElement,
-    ready_state: ReadyState,
+    ready_state: Cell<ReadyState>,
     track: Dom<TextTrack>,
+    /// Incremented whenever the track URL changes, so that the responses of
+    /// outdated fetches are ignored.
+    generation_id: Cell<u32>,
+    /// <https://html.spec.whatwg.org/multipage/#delay-the-load-event>
+    delaying_the_load_event_flag: DomRefCell<Option<LoadBlocker>>,
 }
 
 impl HTMLTrackElement {
@@ -42,8 +71,10 @@ impl HTMLTrackElement {
     ) -> HTMLTrackElement {
         HTMLTrackElement {
             htmlelement: HTMLElement::new_inherited(local_name, prefix, document),
-            ready_state: ReadyState::None,
+            ready_state: Cell::new(ReadyState::None),
             track: Dom::from_ref(&track),
+            generation_id: Cell::new(0),
+            delaying_the_load_event_flag: Default::default(),
         }
     }
 
@@ -68,6 +99,278 @@ impl HTMLTrackElement {
             HTMLTrackElementBinding::Wrap,
         )
     }
+
+    fn media_element(&self) -> Option<DomRoot<HTMLMediaElement>> {
+        self.upcast::<Node>()
+            .GetParentNode()
+            .and_then(DomRoot::downcast::<HTMLMediaElement>)
+    }
+
+    fn delay_load_event(&self, delay: bool) {
+        let mut blocker = self.delaying_the_load_event_flag.borrow_mut();
+        if delay && blocker.is_none() {
+            *blocker = Some(LoadBlocker::new(&document_from_node(self), LoadType::Media));
+        } else if !delay && blocker.is_some() {
+            LoadBlocker::terminate(&mut *blocker);
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#honor-user-preferences-for-automatic-text-track-selection>
+    fn honor_user_preferences(&self, media_element: &HTMLMediaElement) {
+        if self.track.Mode() != TextTrackMode::Disabled {
+            return;
+        }
+        if !self
+            .upcast::<Element>()
+            .has_attribute(&local_name!("default"))
+        {
+            return;
+        }
+        let tracks = media_element.TextTracks();
+        let showing = (0..tracks.Length())
+            .filter_map(|index| tracks.item(index as usize))
+            .any(|track| track.Mode() == TextTrackMode::Showing);
+        match &*self.Kind() {
+            "subtitles" | "captions" if !showing => self.track.SetMode(TextTrackMode::Showing),
+            "chapters" | "metadata" => self.track.SetMode(TextTrackMode::Hidden),
+            _ => {},
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#start-the-track-processing-model>
+    fn start_track_processing(&self) {
+        let generation_id = self.generation_id.get().wrapping_add(1);
+        self.generation_id.set(generation_id);
+
+        // Steps 8-9.
+        let src = self
+            .upcast::<Element>()
+            .get_string_attribute(&local_name!("src"));
+        let url = match document_from_node(self).base_url().join(&src) {
+            Ok(url) if !src.is_empty() => url,
+            _ => {
+                self.ready_state.set(ReadyState::Error);
+                self.queue_event(atom!("error"));
+                return;
+            },
+        };
+
+        // Step 10.
+        self.ready_state.set(ReadyState::Loading);
+        self.delay_load_event(true);
+
+        let cors_setting = match self.media_element().and_then(|media| {
+            media
+                .upcast::<Element>()
+                .get_attribute(&ns!(), &local_name!("crossorigin"))
+        }) {
+            Some(ref attr) if &**attr.value() == "use-credentials" => CorsSettings::UseCredentials,
+            _ => CorsSettings::Anonymous,
+        };
+        let credentials_mode = match cors_setting {
+            CorsSettings::UseCredentials => CredentialsMode::Include,
+            CorsSettings::Anonymous => CredentialsMode::CredentialsSameOrigin,
+        };
+
+        let document = document_from_node(self);
+        let request = RequestInit {
+            url: url.clone(),
+            destination: Destination::Track,
+            mode: RequestMode::CorsMode,
+            credentials_mode,
+            origin: document.origin().immutable().clone(),
+            pipeline_id: Some(self.global().pipeline_id()),
+            ..RequestInit::default()
+        };
+
+        let context = Arc::new(Mutex::new(TrackContext {
+            elem: Trusted::new(self),
+            generation_id,
+            data: vec![],
+            status: Ok(()),
+            url,
+            resource_timing: ResourceFetchTiming::new(ResourceTimingType::Resource),
+        }));
+        let (action_sender, action_receiver) = ipc::channel().unwrap();
+        let (task_source, canceller) = window_from_node(self)
+            .task_manager()
+            .networking_task_source_with_canceller();
+        let listener = NetworkListener {
+            context,
+            task_source,
+            canceller: Some(canceller),
+        };
+        ROUTER.add_route(
+            action_receiver.to_opaque(),
+            Box::new(move |message| {
+                listener.notify_fetch(message.to().unwrap());
+            }),
+        );
+        document
+            .loader_mut()
+            .fetch_async_background(request, action_sender);
+    }
+
+    /// Parses the fetched track file and adds its cues to the text track.
+    /// <https://html.spec.whatwg.org/multipage/#start-the-track-processing-model>
+    fn process_track_data(&self, data: Result<Vec<u8>, NetworkError>) {
+        self.delay_load_event(false);
+
+        let cues = data
+            .map_err(|_| ())
+            .and_then(|data| webvtt::parse(&String::from_utf8_lossy(&data)));
+        match cues {
+            Ok(cues) => {
+                let window = window_from_node(self);
+                for cue in cues {
+                    let cue = VTTCue::from_parsed_cue(&window, cue);
+                    let _ = self.track.AddCue(cue.upcast());
+                }
+                self.ready_state.set(ReadyState::Loaded);
+                self.queue_event(atom!("load"));
+            },
+            Err(_) => {
+                self.ready_state.set(ReadyState::Error);
+                self.queue_event(atom!("error"));
+            },
+        }
+    }
+
+    fn queue_event(&self, name: Atom) {
+        let window = window_from_node(self);
+        window
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue_simple_event(self.upcast(), name, &window);
+    }
+}
+
+/// The context required for asynchronously loading a track file.
+struct TrackContext {
+    /// The element that initiated the request.
+    elem: Trusted<HTMLTrackElement>,
+    /// The generation of the element when the request was made.
+    generation_id: u32,
+    /// The response body received to date.
+    data: Vec<u8>,
+    /// Indicates whether the request failed, and why.
+    status: Result<(), NetworkError>,
+    /// The URL of the track file.
+    url: ServoUrl,
+    /// Timing data for this resource.
+    resource_timing: ResourceFetchTiming,
+}
+
+impl FetchResponseListener for TrackContext {
+    fn process_request_body(&mut self) {}
+
+    fn process_request_eof(&mut self) {}
+
+    fn process_response(&mut self, metadata: Result<FetchMetadata, NetworkError>) {
+        let metadata = metadata.ok().map(|meta| match meta {
+            FetchMetadata::Unfiltered(m) => m,
+            FetchMetadata::Filtered { unsafe_, .. } => unsafe_,
+        });
+
+        let status_code = metadata
+            .as_ref()
+            .and_then(|m| m.status.as_ref().map(|&(code, _)| code))
+            .unwrap_or(0);
+
+        self.status = match status_code {
+            0 => Err(NetworkError::Internal(
+                "No http status code received".to_owned(),
+            )),
+            200...299 => Ok(()), // HTTP ok status codes
+            _ => Err(NetworkError::Internal(format!(
+                "HTTP error code {}",
+                status_code
+            ))),
+        };
+    }
+
+    fn process_response_chunk(&mut self, mut chunk: Vec<u8>) {
+        if self.status.is_ok() {
+            self.data.append(&mut chunk);
+        }
+    }
+
+    fn process_response_eof(&mut self, response: Result<ResourceFetchTiming, NetworkError>) {
+        let data = response
+            .and(self.status.clone())
+            .map(|_| self.data.drain(..).collect());
+        self.elem.root().process_track_data(data);
+    }
+
+    fn resource_timing_mut(&mut self) -> &mut ResourceFetchTiming {
+        &mut self.resource_timing
+    }
+
+    fn resource_timing(&self) -> &ResourceFetchTiming {
+        &self.resource_timing
+    }
+
+    fn submit_resource_timing(&mut self) {
+        network_listener::submit_timing(self)
+    }
+}
+
+impl ResourceTimingListener for TrackContext {
+    fn resource_timing_information(&self) -> (InitiatorType, ServoUrl) {
+        (
+            InitiatorType::LocalName("track".to_string()),
+            self.url.clone(),
+        )
+    }
+
+    fn resource_timing_global(&self) -> DomRoot<GlobalScope> {
+        self.elem.root().global()
+    }
+}
+
+impl PreInvoke for TrackContext {
+    fn should_invoke(&self) -> bool {
+        // Ignore the responses of a fetch for a previous URL.
+        self.elem.root().generation_id.get() == self.generation_id
+    }
+}
+
+impl VirtualMethods for HTMLTrackElement {
+    fn super_type(&self) -> Option<&dyn VirtualMethods> {
+        Some(self.upcast::<HTMLElement>() as &dyn VirtualMethods)
+    }
+
+    fn attribute_mutated(&self, attr: &Attr, mutation: AttributeMutation) {
+        self.super_type().unwrap().attribute_mutated(attr, mutation);
+        if attr.local_name() == &local_name!("src") && self.media_element().is_some() {
+            self.start_track_processing();
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#sourcing-out-of-band-text-tracks>
+    fn bind_to_tree(&self, tree_in_doc: bool) {
+        self.super_type().unwrap().bind_to_tree(tree_in_doc);
+        if let Some(media_element) = self.media_element() {
+            media_element.TextTracks().add(&self.track);
+            self.honor_user_preferences(&media_element);
+            if self.ready_state.get() == ReadyState::None {
+                self.start_track_processing();
+            }
+        }
+    }
+
+    fn unbind_from_tree(&self, context: &UnbindContext) {
+        self.super_type().unwrap().unbind_from_tree(context);
+        if self.upcast::<Node>().GetParentNode().is_some() {
+            return;
+        }
+        if let Some(media_element) = context.parent.downcast::<HTMLMediaElement>() {
+            let tracks = media_element.TextTracks();
+            if let Some(index) = tracks.find(&self.track) {
+                tracks.remove(index);
+            }
+        }
+    }
 }
 
 impl HTMLTrackElementMethods for HTMLTrackElement {
@@ -125,7 +428,7 @@ impl HTMLTrackElementMethods for HTMLTrackElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-track-readystate
     fn ReadyState(&self) -> u16 {
-        self.ready_state as u16
+        self.ready_state.get() as u16
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-track-track
@@ -0,0 +1,72 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding;
+use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::ImageBitmapMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use euclid::Size2D;
+
+#[dom_struct]
+pub struct ImageBitmap {
+    reflector_: Reflector,
+    width: u32,
+    height: u32,
+    /// The premultiplied BGRA pixels of the bitmap, `None` once the bitmap
+    /// is closed.
+    bitmap_data: DomRefCell<Option<Vec<u8>>>,
+}
+
+impl ImageBitmap {
+    fn new_inherited(size: Size2D<u32>, bitmap_data: Vec<u8>) -> ImageBitmap {
+        ImageBitmap {
+            reflector_: Reflector::new(),
+            width: size.width,
+            height: size.height,
+            bitmap_data: DomRefCell::new(Some(bitmap_data)),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        size: Size2D<u32>,
+        bitmap_data: Vec<u8>,
+    ) -> DomRoot<ImageBitmap> {
+        reflect_dom_object(
+            Box::new(ImageBitmap::new_inherited(size, bitmap_data)),
+            global,
+            ImageBitmapBinding::Wrap,
+        )
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.bitmap_data.borrow().is_none()
+    }
+}
+
+impl ImageBitmapMethods for ImageBitmap {
+    // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-width
+    fn Width(&self) -> u32 {
+        if self.is_closed() {
+            return 0;
+        }
+        self.width
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-height
+    fn Height(&self) -> u32 {
+        if self.is_closed() {
+            return 0;
+        }
+        self.height
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-close
+    fn Close(&self) {
+        *self.bitmap_data.borrow_mut() = None;
+    }
+}
@@ -0,0 +1,97 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::LargestContentfulPaintBinding::{
+    self, LargestContentfulPaintMethods,
+};
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performanceentry::PerformanceEntry;
+use dom_struct::dom_struct;
+
+// https://wicg.github.io/largest-contentful-paint/#sec-largest-contentful-paint-interface
+#[dom_struct]
+pub struct LargestContentfulPaint {
+    entry: PerformanceEntry,
+    render_time: f64,
+    size: u32,
+    id: DOMString,
+    url: DOMString,
+    element: MutNullableDom<Element>,
+}
+
+impl LargestContentfulPaint {
+    fn new_inherited(
+        render_time: f64,
+        size: u32,
+        id: DOMString,
+        url: DOMString,
+        element: Option<&Element>,
+    ) -> LargestContentfulPaint {
+        LargestContentfulPaint {
+            entry: PerformanceEntry::new_inherited(
+                DOMString::new(),
+                DOMString::from("largest-contentful-paint"),
+                render_time,
+                0.,
+            ),
+            render_time,
+            size,
+            id,
+            url,
+            element: MutNullableDom::new(element),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        render_time: f64,
+        size: u32,
+        id: DOMString,
+        url: DOMString,
+        element: Option<&Element>,
+    ) -> DomRoot<LargestContentfulPaint> {
+        let entry = LargestContentfulPaint::new_inherited(render_time, size, id, url, element);
+        reflect_dom_object(Box::new(entry), global, LargestContentfulPaintBinding::Wrap)
+    }
+}
+
+impl LargestContentfulPaintMethods for LargestContentfulPaint {
+    // https://wicg.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-rendertime
+    fn RenderTime(&self) -> DOMHighResTimeStamp {
+        Finite::wrap(self.render_time)
+    }
+
+    // https://wicg.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-loadtime
+    fn LoadTime(&self) -> DOMHighResTimeStamp {
+        // Image load times are not tracked yet.
+        Finite::wrap(0.)
+    }
+
+    // https://wicg.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-size
+    fn Size(&self) -> u32 {
+        self.size
+    }
+
+    // https://wicg.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-id
+    fn Id(&self) -> DOMString {
+        self.id.clone()
+    }
+
+    // https://wicg.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-url
+    fn Url(&self) -> DOMString {
+        self.url.clone()
+    }
+
+    // https://wicg.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-element
+    fn GetElement(&self) -> Option<DomRoot<Element>> {
+        self.element.get()
+    }
+}
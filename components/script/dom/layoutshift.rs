@@ -0,0 +1,73 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::LayoutShiftBinding::{self, LayoutShiftMethods};
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performanceentry::PerformanceEntry;
+use dom_struct::dom_struct;
+
+// https://wicg.github.io/layout-instability/#sec-layout-shift
+#[dom_struct]
+pub struct LayoutShift {
+    entry: PerformanceEntry,
+    value: f64,
+    had_recent_input: bool,
+    last_input_time: f64,
+}
+
+impl LayoutShift {
+    fn new_inherited(
+        start_time: f64,
+        value: f64,
+        had_recent_input: bool,
+        last_input_time: f64,
+    ) -> LayoutShift {
+        LayoutShift {
+            entry: PerformanceEntry::new_inherited(
+                DOMString::new(),
+                DOMString::from("layout-shift"),
+                start_time,
+                0.,
+            ),
+            value,
+            had_recent_input,
+            last_input_time,
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        start_time: f64,
+        value: f64,
+        had_recent_input: bool,
+        last_input_time: f64,
+    ) -> DomRoot<LayoutShift> {
+        let entry =
+            LayoutShift::new_inherited(start_time, value, had_recent_input, last_input_time);
+        reflect_dom_object(Box::new(entry), global, LayoutShiftBinding::Wrap)
+    }
+}
+
+impl LayoutShiftMethods for LayoutShift {
+    // https://wicg.github.io/layout-instability/#dom-layoutshift-value
+    fn Value(&self) -> Finite<f64> {
+        Finite::wrap(self.value)
+    }
+
+    // https://wicg.github.io/layout-instability/#dom-layoutshift-hadrecentinput
+    fn HadRecentInput(&self) -> bool {
+        self.had_recent_input
+    }
+
+    // https://wicg.github.io/layout-instability/#dom-layoutshift-lastinputtime
+    fn LastInputTime(&self) -> DOMHighResTimeStamp {
+        Finite::wrap(self.last_input_time)
+    }
+}
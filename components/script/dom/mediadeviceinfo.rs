@@ -0,0 +1,76 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::MediaDeviceInfoBinding::{
+    self, MediaDeviceInfoMethods, MediaDeviceKind,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct MediaDeviceInfo {
+    reflector_: Reflector,
+    device_id: DOMString,
+    kind: MediaDeviceKind,
+    label: DOMString,
+    group_id: DOMString,
+}
+
+impl MediaDeviceInfo {
+    fn new_inherited(
+        device_id: DOMString,
+        kind: MediaDeviceKind,
+        label: DOMString,
+        group_id: DOMString,
+    ) -> MediaDeviceInfo {
+        MediaDeviceInfo {
+            reflector_: Reflector::new(),
+            device_id,
+            kind,
+            label,
+            group_id,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        device_id: DOMString,
+        kind: MediaDeviceKind,
+        label: DOMString,
+        group_id: DOMString,
+    ) -> DomRoot<MediaDeviceInfo> {
+        reflect_dom_object(
+            Box::new(MediaDeviceInfo::new_inherited(
+                device_id, kind, label, group_id,
+            )),
+            global,
+            MediaDeviceInfoBinding::Wrap,
+        )
+    }
+}
+
+impl MediaDeviceInfoMethods for MediaDeviceInfo {
+    /// https://w3c.github.io/mediacapture-main/#dom-mediadeviceinfo-deviceid
+    fn DeviceId(&self) -> DOMString {
+        self.device_id.clone()
+    }
+
+    /// https://w3c.github.io/mediacapture-main/#dom-mediadeviceinfo-kind
+    fn Kind(&self) -> MediaDeviceKind {
+        self.kind
+    }
+
+    /// https://w3c.github.io/mediacapture-main/#dom-mediadeviceinfo-label
+    fn Label(&self) -> DOMString {
+        self.label.clone()
+    }
+
+    /// https://w3c.github.io/mediacapture-main/#dom-mediadeviceinfo-groupid
+    fn GroupId(&self) -> DOMString {
+        self.group_id.clone()
+    }
+}
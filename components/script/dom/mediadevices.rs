@@ -2,32 +2,54 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use crate::dom::bindings::codegen::Bindings::MediaDevicesBinding::MediaStreamConstraints;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::MediaDeviceInfoBinding::MediaDeviceKind;
 use crate::dom::bindings::codegen::Bindings::MediaDevicesBinding::{self, MediaDevicesMethods};
+use crate::dom::bindings::codegen::Bindings::MediaDevicesBinding::{
+    MediaStreamConstraints, MediaTrackConstraintSet as JSMediaTrackConstraintSet,
+    MediaTrackConstraints, MediaTrackSupportedConstraints,
+};
 use crate::dom::bindings::codegen::UnionTypes::BooleanOrMediaTrackConstraints;
 use crate::dom::bindings::codegen::UnionTypes::ClampedUnsignedLongOrConstrainULongRange as ConstrainULong;
 use crate::dom::bindings::codegen::UnionTypes::DoubleOrConstrainDoubleRange as ConstrainDouble;
+use crate::dom::bindings::codegen::UnionTypes::StringOrStringSequence;
+use crate::dom::bindings::codegen::UnionTypes::StringOrStringSequenceOrConstrainDOMStringParameters as ConstrainDOMString;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::mediadeviceinfo::MediaDeviceInfo;
 use crate::dom::mediastream::MediaStream;
+use crate::dom::overconstrainederror::OverconstrainedError;
 use crate::dom::promise::Promise;
 use dom_struct::dom_struct;
+use servo_config::prefs::PREFS;
 use servo_media::streams::capture::{Constrain, ConstrainRange, MediaTrackConstraintSet};
+use servo_media::streams::MediaStream as BackendMediaStream;
 use servo_media::ServoMedia;
+use std::cell::Cell;
 use std::rc::Rc;
 
 #[dom_struct]
 pub struct MediaDevices {
     eventtarget: EventTarget,
+    /// Device labels are only exposed once capture has been allowed.
+    labels_exposed: Cell<bool>,
+    /// The ids of the devices found by the last enumeration, to notice
+    /// changes in the set of available devices.
+    known_devices: DomRefCell<Option<Vec<String>>>,
 }
 
 impl MediaDevices {
     pub fn new_inherited() -> MediaDevices {
         MediaDevices {
             eventtarget: EventTarget::new_inherited(),
+            labels_exposed: Cell::new(false),
+            known_devices: DomRefCell::new(None),
         }
     }
 
@@ -38,43 +60,498 @@ impl MediaDevices {
             MediaDevicesBinding::Wrap,
         )
     }
+
+    /// Enumerate the available capture devices, firing `devicechange`
+    /// if they differ from the ones found last time.
+    fn capture_devices(&self) -> Vec<CaptureDevice> {
+        let devices = if fake_devices_enabled() {
+            fake_devices()
+        } else {
+            default_devices()
+        };
+
+        // https://w3c.github.io/mediacapture-main/#mediadevices
+        let ids: Vec<String> = devices.iter().map(|device| device.id.clone()).collect();
+        let previous = self.known_devices.borrow_mut().replace(ids.clone());
+        if previous.map_or(false, |previous| previous != ids) {
+            let global = self.global();
+            let window = global.as_window();
+            window
+                .task_manager()
+                .dom_manipulation_task_source()
+                .queue_simple_event(self.upcast(), atom!("devicechange"), window);
+        }
+        devices
+    }
 }
 
 impl MediaDevicesMethods for MediaDevices {
+    /// https://w3c.github.io/mediacapture-main/#dom-mediadevices-ondevicechange
+    event_handler!(devicechange, GetOndevicechange, SetOndevicechange);
+
+    /// https://w3c.github.io/mediacapture-main/#dom-mediadevices-enumeratedevices
+    fn EnumerateDevices(&self) -> Rc<Promise> {
+        let global = self.global();
+        let p = Promise::new(&global);
+        let labels_exposed = self.labels_exposed.get();
+        let devices: Vec<DomRoot<MediaDeviceInfo>> = self
+            .capture_devices()
+            .into_iter()
+            .map(|device| {
+                let label = if labels_exposed {
+                    device.label
+                } else {
+                    String::new()
+                };
+                MediaDeviceInfo::new(
+                    &global,
+                    DOMString::from(device.id),
+                    device.kind,
+                    DOMString::from(label),
+                    DOMString::from(device.group_id),
+                )
+            })
+            .collect();
+        p.resolve_native(&devices);
+        p
+    }
+
+    /// https://w3c.github.io/mediacapture-main/#dom-mediadevices-getsupportedconstraints
+    fn GetSupportedConstraints(&self) -> MediaTrackSupportedConstraints {
+        MediaTrackSupportedConstraints::empty()
+    }
+
     /// https://w3c.github.io/mediacapture-main/#dom-mediadevices-getusermedia
     fn GetUserMedia(&self, constraints: &MediaStreamConstraints) -> Rc<Promise> {
-        let p = Promise::new(&self.global());
-        let media = ServoMedia::get().unwrap();
-        let mut tracks = vec![];
-        if let Some(constraints) = convert_constraints(&constraints.audio) {
-            if let Some(audio) = media.create_audioinput_stream(constraints) {
-                tracks.push(audio)
-            }
+        let global = self.global();
+        let p = Promise::new(&global);
+
+        // Steps 2-3.
+        let requests = [
+            (MediaDeviceKind::Audioinput, requested(&constraints.audio)),
+            (MediaDeviceKind::Videoinput, requested(&constraints.video)),
+        ];
+        if requests.iter().all(|&(_, ref request)| request.is_none()) {
+            p.reject_error(Error::Type(
+                "At least one of audio and video must be requested.".to_owned(),
+            ));
+            return p;
         }
-        if let Some(constraints) = convert_constraints(&constraints.video) {
-            if let Some(video) = media.create_videoinput_stream(constraints) {
-                tracks.push(video)
+
+        // Steps 8.4-8.6.
+        let devices = self.capture_devices();
+        let mut tracks = vec![];
+        for &(kind, request) in requests.iter() {
+            let constraints = match request {
+                Some(constraints) => constraints,
+                None => continue,
+            };
+            let device = match select_device(&devices, kind, constraints) {
+                Ok(device) => device,
+                Err(SelectionError::NotFound) => {
+                    p.reject_error(Error::NotFound);
+                    return p;
+                },
+                Err(SelectionError::Overconstrained(constraint)) => {
+                    let error = OverconstrainedError::new(
+                        &global,
+                        DOMString::from(constraint),
+                        DOMString::from("No device satisfies the constraints."),
+                    );
+                    p.reject_native(&error);
+                    return p;
+                },
+            };
+            match device.create_track(constraints) {
+                Some(track) => tracks.push(track),
+                None => {
+                    p.reject_error(Error::NotReadable);
+                    return p;
+                },
             }
         }
-        let stream = MediaStream::new(&self.global(), tracks);
+
+        self.labels_exposed.set(true);
+        let stream = MediaStream::new(&global, tracks);
         p.resolve_native(&stream);
         p
     }
 }
 
-fn convert_constraints(js: &BooleanOrMediaTrackConstraints) -> Option<MediaTrackConstraintSet> {
+/// Whether the `dom.webrtc.fake_devices.enabled` preference replaces the
+/// system's devices with a fake camera and microphone.
+fn fake_devices_enabled() -> bool {
+    PREFS
+        .get("dom.webrtc.fake_devices.enabled")
+        .as_boolean()
+        .unwrap_or(false)
+}
+
+/// A capture device, and the settings it is known to support.
+struct CaptureDevice {
+    id: String,
+    kind: MediaDeviceKind,
+    label: String,
+    group_id: String,
+    /// The ranges of settings of the device, if they are known.
+    capabilities: Option<Capabilities>,
+    fake: bool,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Capabilities {
+    width: Option<(f64, f64)>,
+    height: Option<(f64, f64)>,
+    aspect_ratio: Option<(f64, f64)>,
+    frame_rate: Option<(f64, f64)>,
+    sample_rate: Option<(f64, f64)>,
+}
+
+impl CaptureDevice {
+    fn create_track(
+        &self,
+        constraints: Option<&MediaTrackConstraints>,
+    ) -> Option<Box<BackendMediaStream>> {
+        let media = ServoMedia::get().unwrap();
+        if self.fake {
+            // Test sources: a video test pattern, and a sine tone.
+            return match self.kind {
+                MediaDeviceKind::Audioinput => Some(media.create_audiostream()),
+                MediaDeviceKind::Videoinput => Some(media.create_videostream()),
+                MediaDeviceKind::Audiooutput => None,
+            };
+        }
+        let constraints = constraints
+            .map(|constraints| convert_constraints(&constraints.parent))
+            .unwrap_or_default();
+        match self.kind {
+            MediaDeviceKind::Audioinput => media.create_audioinput_stream(constraints),
+            MediaDeviceKind::Videoinput => media.create_videoinput_stream(constraints),
+            MediaDeviceKind::Audiooutput => None,
+        }
+    }
+}
+
+fn fake_devices() -> Vec<CaptureDevice> {
+    vec![
+        CaptureDevice {
+            id: "fake-audio-input".to_owned(),
+            kind: MediaDeviceKind::Audioinput,
+            label: "Fake microphone (sine tone)".to_owned(),
+            group_id: "fake-devices".to_owned(),
+            capabilities: Some(Capabilities {
+                sample_rate: Some((8000., 48000.)),
+                ..Default::default()
+            }),
+            fake: true,
+        },
+        CaptureDevice {
+            id: "fake-video-input".to_owned(),
+            kind: MediaDeviceKind::Videoinput,
+            label: "Fake camera (test pattern)".to_owned(),
+            group_id: "fake-devices".to_owned(),
+            capabilities: Some(Capabilities {
+                width: Some((1., 1920.)),
+                height: Some((1., 1080.)),
+                aspect_ratio: Some((1. / 1080., 1920.)),
+                frame_rate: Some((1., 60.)),
+                ..Default::default()
+            }),
+            fake: true,
+        },
+    ]
+}
+
+/// servo-media cannot enumerate the devices of the system yet, so the default
+/// microphone and camera of the backend stand for them.
+fn default_devices() -> Vec<CaptureDevice> {
+    vec![
+        CaptureDevice {
+            id: "default-audio-input".to_owned(),
+            kind: MediaDeviceKind::Audioinput,
+            label: "Default microphone".to_owned(),
+            group_id: String::new(),
+            // The backend applies the constraints itself when capturing.
+            capabilities: None,
+            fake: false,
+        },
+        CaptureDevice {
+            id: "default-video-input".to_owned(),
+            kind: MediaDeviceKind::Videoinput,
+            label: "Default camera".to_owned(),
+            group_id: String::new(),
+            capabilities: None,
+            fake: false,
+        },
+    ]
+}
+
+/// The constraints of a requested kind of track, or `None` if the kind
+/// wasn't requested at all.
+fn requested(js: &BooleanOrMediaTrackConstraints) -> Option<Option<&MediaTrackConstraints>> {
     match js {
         BooleanOrMediaTrackConstraints::Boolean(false) => None,
-        BooleanOrMediaTrackConstraints::Boolean(true) => Some(Default::default()),
-        BooleanOrMediaTrackConstraints::MediaTrackConstraints(ref c) => {
-            Some(MediaTrackConstraintSet {
-                height: convert_culong(&c.parent.height),
-                width: convert_culong(&c.parent.width),
-                aspect: convert_cdouble(&c.parent.aspectRatio),
-                frame_rate: convert_cdouble(&c.parent.frameRate),
-                sample_rate: convert_culong(&c.parent.sampleRate),
-            })
+        BooleanOrMediaTrackConstraints::Boolean(true) => Some(None),
+        BooleanOrMediaTrackConstraints::MediaTrackConstraints(ref c) => Some(Some(c)),
+    }
+}
+
+enum SelectionError {
+    NotFound,
+    Overconstrained(&'static str),
+}
+
+/// https://w3c.github.io/mediacapture-main/#dfn-selectsettings
+fn select_device<'a>(
+    devices: &'a [CaptureDevice],
+    kind: MediaDeviceKind,
+    constraints: Option<&MediaTrackConstraints>,
+) -> Result<&'a CaptureDevice, SelectionError> {
+    let candidates = devices.iter().filter(|device| device.kind == kind);
+
+    // Step 3.
+    let mut failed_constraint = None;
+    let mut candidates: Vec<(&CaptureDevice, f64)> = candidates
+        .filter_map(|device| {
+            let distance = match constraints {
+                Some(constraints) => fitness_distance(device, &constraints.parent, false),
+                None => Ok(0.),
+            };
+            match distance {
+                Ok(distance) => Some((device, distance)),
+                Err(constraint) => {
+                    failed_constraint = Some(constraint);
+                    None
+                },
+            }
+        })
+        .collect();
+
+    // Step 4.
+    if candidates.is_empty() {
+        return Err(match failed_constraint {
+            Some(constraint) => SelectionError::Overconstrained(constraint),
+            None => SelectionError::NotFound,
+        });
+    }
+
+    // Step 5. Advanced constraint sets narrow the candidates down, unless
+    // none of them satisfies the set.
+    if let Some(advanced) = constraints.and_then(|c| c.advanced.as_ref()) {
+        for set in advanced {
+            if candidates
+                .iter()
+                .any(|&(device, _)| fitness_distance(device, set, true).is_ok())
+            {
+                candidates.retain(|&(device, _)| fitness_distance(device, set, true).is_ok());
+            }
+        }
+    }
+
+    // Step 6.
+    let best =
+        candidates
+            .into_iter()
+            .fold(
+                None,
+                |best: Option<(&CaptureDevice, f64)>, candidate| match best {
+                    Some(best) if best.1 <= candidate.1 => Some(best),
+                    _ => Some(candidate),
+                },
+            );
+    Ok(best.unwrap().0)
+}
+
+/// https://w3c.github.io/mediacapture-main/#dfn-fitness-distance
+///
+/// Returns the name of the first constraint that the device can't satisfy,
+/// if any. Bare values are exact in advanced constraint sets, and ideal
+/// otherwise.
+fn fitness_distance(
+    device: &CaptureDevice,
+    set: &JSMediaTrackConstraintSet,
+    advanced: bool,
+) -> Result<f64, &'static str> {
+    let mut distance = 0.;
+    if let Some(ref constraint) = set.deviceId {
+        distance += device_id_distance(constraint, &device.id, advanced).ok_or("deviceId")?;
+    }
+
+    let capabilities = match device.capabilities {
+        Some(capabilities) => capabilities,
+        None => return Ok(distance),
+    };
+    let numeric = [
+        (
+            "width",
+            set.width
+                .as_ref()
+                .map(|c| NumericConstraint::ulong(c, advanced)),
+            capabilities.width,
+        ),
+        (
+            "height",
+            set.height
+                .as_ref()
+                .map(|c| NumericConstraint::ulong(c, advanced)),
+            capabilities.height,
+        ),
+        (
+            "aspectRatio",
+            set.aspectRatio
+                .as_ref()
+                .map(|c| NumericConstraint::double(c, advanced)),
+            capabilities.aspect_ratio,
+        ),
+        (
+            "frameRate",
+            set.frameRate
+                .as_ref()
+                .map(|c| NumericConstraint::double(c, advanced)),
+            capabilities.frame_rate,
+        ),
+        (
+            "sampleRate",
+            set.sampleRate
+                .as_ref()
+                .map(|c| NumericConstraint::ulong(c, advanced)),
+            capabilities.sample_rate,
+        ),
+    ];
+    for &(name, ref constraint, range) in numeric.iter() {
+        if let Some(ref constraint) = *constraint {
+            distance += constraint.fitness_distance(range).ok_or(name)?;
+        }
+    }
+    Ok(distance)
+}
+
+fn device_id_distance(js: &ConstrainDOMString, id: &str, advanced: bool) -> Option<f64> {
+    fn contains(ids: &StringOrStringSequence, id: &str) -> bool {
+        match ids {
+            StringOrStringSequence::String(ref s) => &**s == id,
+            StringOrStringSequence::StringSequence(ref v) => v.iter().any(|s| &**s == id),
+        }
+    }
+
+    let (exact, ideal) = match js {
+        ConstrainDOMString::String(ref s) => {
+            let matches = &**s == id;
+            if advanced {
+                (Some(matches), None)
+            } else {
+                (None, Some(matches))
+            }
+        },
+        ConstrainDOMString::StringSequence(ref v) => {
+            let matches = v.iter().any(|s| &**s == id);
+            if advanced {
+                (Some(matches), None)
+            } else {
+                (None, Some(matches))
+            }
         },
+        ConstrainDOMString::ConstrainDOMStringParameters(ref params) => (
+            params.exact.as_ref().map(|ids| contains(ids, id)),
+            params.ideal.as_ref().map(|ids| contains(ids, id)),
+        ),
+    };
+    if exact == Some(false) {
+        return None;
+    }
+    Some(if ideal == Some(false) { 1. } else { 0. })
+}
+
+/// The required and ideal parts of a numeric constraint.
+struct NumericConstraint {
+    exact: Option<f64>,
+    min: Option<f64>,
+    max: Option<f64>,
+    ideal: Option<f64>,
+}
+
+impl NumericConstraint {
+    fn ulong(js: &ConstrainULong, advanced: bool) -> NumericConstraint {
+        match js {
+            ConstrainULong::ClampedUnsignedLong(val) => {
+                NumericConstraint::bare(*val as f64, advanced)
+            },
+            ConstrainULong::ConstrainULongRange(ref range) => NumericConstraint {
+                exact: range.exact.map(|x| x as f64),
+                min: range.parent.min.map(|x| x as f64),
+                max: range.parent.max.map(|x| x as f64),
+                ideal: range.ideal.map(|x| x as f64),
+            },
+        }
+    }
+
+    fn double(js: &ConstrainDouble, advanced: bool) -> NumericConstraint {
+        match js {
+            ConstrainDouble::Double(val) => NumericConstraint::bare(**val, advanced),
+            ConstrainDouble::ConstrainDoubleRange(ref range) => NumericConstraint {
+                exact: range.exact.map(|x| *x),
+                min: range.parent.min.map(|x| *x),
+                max: range.parent.max.map(|x| *x),
+                ideal: range.ideal.map(|x| *x),
+            },
+        }
+    }
+
+    fn bare(value: f64, advanced: bool) -> NumericConstraint {
+        NumericConstraint {
+            exact: if advanced { Some(value) } else { None },
+            min: None,
+            max: None,
+            ideal: if advanced { None } else { Some(value) },
+        }
+    }
+
+    fn is_required(&self) -> bool {
+        self.exact.is_some() || self.min.is_some() || self.max.is_some()
+    }
+
+    /// The distance between this constraint and the range of values a
+    /// device supports, or `None` if the device can't satisfy it.
+    fn fitness_distance(&self, range: Option<(f64, f64)>) -> Option<f64> {
+        // Step 2.
+        let (low, high) = match range {
+            Some(range) => range,
+            None if self.is_required() => return None,
+            None => return Some(if self.ideal.is_some() { 1. } else { 0. }),
+        };
+
+        // Step 3.
+        if self
+            .exact
+            .map_or(false, |exact| exact < low || exact > high) ||
+            self.min.map_or(false, |min| min > high) ||
+            self.max.map_or(false, |max| max < low) ||
+            self.min
+                .map_or(false, |min| self.max.map_or(false, |max| min > max))
+        {
+            return None;
+        }
+
+        // Steps 4-7.
+        let ideal = match self.ideal {
+            Some(ideal) => ideal,
+            None => return Some(0.),
+        };
+        let actual = ideal.max(low).min(high);
+        if actual == ideal {
+            return Some(0.);
+        }
+        Some((actual - ideal).abs() / actual.abs().max(ideal.abs()))
+    }
+}
+
+fn convert_constraints(js: &JSMediaTrackConstraintSet) -> MediaTrackConstraintSet {
+    MediaTrackConstraintSet {
+        height: js.height.as_ref().and_then(convert_culong),
+        width: js.width.as_ref().and_then(convert_culong),
+        aspect: js.aspectRatio.as_ref().and_then(convert_cdouble),
+        frame_rate: js.frameRate.as_ref().and_then(convert_cdouble),
+        sample_rate: js.sampleRate.as_ref().and_then(convert_culong),
     }
 }
 
@@ -0,0 +1,372 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::MediaErrorBinding::MediaErrorConstants::*;
+use crate::dom::bindings::codegen::Bindings::MediaSourceBinding::{
+    self, EndOfStreamError, MediaSourceMethods, ReadyState,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::htmlmediaelement::HTMLMediaElement;
+use crate::dom::sourcebuffer::SourceBuffer;
+use crate::dom::sourcebufferlist::SourceBufferList;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use mime::{self, Mime};
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::f64;
+
+/// <https://w3c.github.io/media-source/#mediasource>
+///
+/// Segments appended to the source buffers are pushed, in append order, to the
+/// servo-media player of the attached media element, which demuxes them as a
+/// single byte stream. This limits a MediaSource to a single SourceBuffer, so
+/// players that append audio and video to separate buffers, as most DASH and HLS
+/// players do, are not supported yet.
+#[dom_struct]
+pub struct MediaSource {
+    eventtarget: EventTarget,
+    /// <https://w3c.github.io/media-source/#dom-mediasource-readystate>
+    ready_state: Cell<ReadyState>,
+    /// <https://w3c.github.io/media-source/#dom-mediasource-duration>
+    duration: Cell<f64>,
+    /// <https://w3c.github.io/media-source/#dom-mediasource-sourcebuffers>
+    source_buffers: Dom<SourceBufferList>,
+    /// <https://w3c.github.io/media-source/#dom-mediasource-activesourcebuffers>
+    active_source_buffers: Dom<SourceBufferList>,
+    /// The media element this object is attached to, if any.
+    media_element: MutNullableDom<HTMLMediaElement>,
+}
+
+impl MediaSource {
+    fn new_inherited(
+        source_buffers: &SourceBufferList,
+        active_source_buffers: &SourceBufferList,
+    ) -> MediaSource {
+        MediaSource {
+            eventtarget: EventTarget::new_inherited(),
+            ready_state: Cell::new(ReadyState::Closed),
+            duration: Cell::new(f64::NAN),
+            source_buffers: Dom::from_ref(source_buffers),
+            active_source_buffers: Dom::from_ref(active_source_buffers),
+            media_element: Default::default(),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<MediaSource> {
+        let source_buffers = SourceBufferList::new(window);
+        let active_source_buffers = SourceBufferList::new(window);
+        reflect_dom_object(
+            Box::new(MediaSource::new_inherited(
+                &source_buffers,
+                &active_source_buffers,
+            )),
+            window,
+            MediaSourceBinding::Wrap,
+        )
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-constructor
+    pub fn Constructor(window: &Window) -> Fallible<DomRoot<MediaSource>> {
+        Ok(MediaSource::new(window))
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-istypesupported
+    pub fn IsTypeSupported(_window: &Window, type_: DOMString) -> bool {
+        MediaSource::is_type_supported(&type_)
+    }
+
+    /// Whether a SourceBuffer can be created for the `type_` MIME type, which
+    /// has to be one of the byte stream formats servo-media is able to demux.
+    fn is_type_supported(type_: &str) -> bool {
+        match type_.parse::<Mime>() {
+            Ok(ref mime) if mime.type_() == mime::AUDIO || mime.type_() == mime::VIDEO => {
+                match mime.subtype().as_str() {
+                    "mp4" | "webm" | "mpeg" | "mp2t" | "aac" => true,
+                    _ => false,
+                }
+            },
+            _ => false,
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.ready_state.get() == ReadyState::Closed
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.ready_state.get() == ReadyState::Open
+    }
+
+    pub fn media_element(&self) -> Option<DomRoot<HTMLMediaElement>> {
+        self.media_element.get()
+    }
+
+    /// <https://w3c.github.io/media-source/#mediasource-attach>
+    pub fn attach(&self, media_element: &HTMLMediaElement) {
+        self.media_element.set(Some(media_element));
+        self.ready_state.set(ReadyState::Open);
+        self.queue_event(atom!("sourceopen"));
+    }
+
+    /// <https://w3c.github.io/media-source/#mediasource-detach>
+    pub fn detach(&self) {
+        self.media_element.set(None);
+
+        // Step 1.
+        self.ready_state.set(ReadyState::Closed);
+
+        // Step 2.
+        self.duration.set(f64::NAN);
+
+        // Steps 3-4.
+        for source_buffer in self.active_source_buffers.buffers() {
+            self.active_source_buffers.remove(&source_buffer);
+        }
+
+        // Steps 5-6.
+        for source_buffer in self.source_buffers.buffers() {
+            source_buffer.set_removed();
+            self.source_buffers.remove(&source_buffer);
+        }
+
+        // Step 7.
+        self.queue_event(atom!("sourceclose"));
+    }
+
+    /// Sets the state back to "open" if the stream had ended, as done when a
+    /// source buffer is about to be modified.
+    pub fn reopen_if_ended(&self) {
+        if self.ready_state.get() == ReadyState::Ended {
+            self.ready_state.set(ReadyState::Open);
+            self.queue_event(atom!("sourceopen"));
+        }
+    }
+
+    /// Whether the `updating` attribute of any source buffer is true.
+    fn is_updating(&self) -> bool {
+        self.source_buffers
+            .buffers()
+            .iter()
+            .any(|source_buffer| source_buffer.is_updating())
+    }
+
+    /// Marks `source_buffer` as active once its first segment was appended.
+    pub fn activate(&self, source_buffer: &SourceBuffer) {
+        if !self.active_source_buffers.contains(source_buffer) {
+            self.active_source_buffers.add(source_buffer);
+        }
+    }
+
+    /// Returns the duration to use once the player demuxed an initialization
+    /// segment lasting `duration` seconds. The duration set by script wins over
+    /// the one from the byte stream.
+    /// <https://w3c.github.io/media-source/#sourcebuffer-init-segment-received>
+    pub fn init_segment_duration(&self, duration: f64) -> f64 {
+        if self.duration.get().is_nan() {
+            self.duration.set(duration);
+        }
+        self.duration.get()
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.duration.get()
+    }
+
+    /// <https://w3c.github.io/media-source/#duration-change-algorithm>
+    fn change_duration(&self, duration: f64) {
+        // Step 1.
+        if self.duration.get() == duration {
+            return;
+        }
+
+        // Steps 2-4.
+        self.duration.set(duration);
+
+        // Step 5.
+        if let Some(media_element) = self.media_element.get() {
+            media_element.media_source_duration_changed(duration);
+        }
+    }
+
+    /// <https://w3c.github.io/media-source/#end-of-stream-algorithm>
+    pub fn end_of_stream(&self, error: Option<EndOfStreamError>) {
+        // Step 1.
+        self.ready_state.set(ReadyState::Ended);
+
+        // Step 2.
+        self.queue_event(atom!("sourceended"));
+
+        // Step 3.
+        let media_element = match self.media_element.get() {
+            Some(media_element) => media_element,
+            None => return,
+        };
+        match error {
+            None => {
+                // Step 3.no_error.1.
+                let buffered = media_element.buffered_ranges();
+                if buffered.len() > 0 {
+                    if let Ok(end) = buffered.end(buffered.len() - 1) {
+                        self.change_duration(end);
+                    }
+                }
+
+                // Step 3.no_error.2.
+                media_element.media_source_end_of_stream();
+            },
+            Some(EndOfStreamError::Network) => media_element.media_source_error(MEDIA_ERR_NETWORK),
+            Some(EndOfStreamError::Decode) => media_element.media_source_error(MEDIA_ERR_DECODE),
+        }
+    }
+
+    fn queue_event(&self, name: Atom) {
+        let global = self.global();
+        let window = global.as_window();
+        window
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(self.upcast(), name, window);
+    }
+}
+
+impl MediaSourceMethods for MediaSource {
+    // https://w3c.github.io/media-source/#dom-mediasource-sourcebuffers
+    fn SourceBuffers(&self) -> DomRoot<SourceBufferList> {
+        DomRoot::from_ref(&*self.source_buffers)
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-activesourcebuffers
+    fn ActiveSourceBuffers(&self) -> DomRoot<SourceBufferList> {
+        DomRoot::from_ref(&*self.active_source_buffers)
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-readystate
+    fn ReadyState(&self) -> ReadyState {
+        self.ready_state.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-duration
+    fn Duration(&self) -> f64 {
+        if self.is_closed() {
+            return f64::NAN;
+        }
+        self.duration.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-duration
+    fn SetDuration(&self, value: f64) -> ErrorResult {
+        // Step 1.
+        if value.is_nan() || value < 0. {
+            return Err(Error::Type("Invalid duration".to_owned()));
+        }
+
+        // Step 2.
+        if !self.is_open() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3.
+        if self.is_updating() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 4.
+        self.change_duration(value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceopen
+    event_handler!(sourceopen, GetOnsourceopen, SetOnsourceopen);
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceended
+    event_handler!(sourceended, GetOnsourceended, SetOnsourceended);
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceclose
+    event_handler!(sourceclose, GetOnsourceclose, SetOnsourceclose);
+
+    // https://w3c.github.io/media-source/#dom-mediasource-addsourcebuffer
+    fn AddSourceBuffer(&self, type_: DOMString) -> Fallible<DomRoot<SourceBuffer>> {
+        // Step 1.
+        if type_.is_empty() {
+            return Err(Error::Type("Empty MIME type".to_owned()));
+        }
+
+        // Step 2.
+        if !MediaSource::is_type_supported(&type_) {
+            return Err(Error::NotSupported);
+        }
+
+        // Step 3.
+        // FIXME: The player is fed with a single byte stream, so separate audio
+        // and video source buffers cannot be demuxed together yet and a second
+        // buffer is rejected as allowed when the user agent cannot handle more.
+        if self.source_buffers.buffers().len() > 0 {
+            return Err(Error::QuotaExceeded);
+        }
+
+        // Step 4.
+        if !self.is_open() {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 5-7.
+        let source_buffer = SourceBuffer::new(self.global().as_window(), self);
+
+        // Step 8.
+        self.source_buffers.add(&source_buffer);
+
+        // Step 9.
+        Ok(source_buffer)
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-removesourcebuffer
+    fn RemoveSourceBuffer(&self, source_buffer: &SourceBuffer) -> ErrorResult {
+        // Step 1.
+        if !self.source_buffers.contains(source_buffer) {
+            return Err(Error::NotFound);
+        }
+
+        // Step 2.
+        source_buffer.abort_buffer_append();
+
+        // Steps 3-7.
+        // FIXME: Remove the audio, video and text tracks of the source buffer.
+
+        // Step 8.
+        if self.active_source_buffers.contains(source_buffer) {
+            self.active_source_buffers.remove(source_buffer);
+        }
+
+        // Steps 9-10.
+        source_buffer.set_removed();
+        self.source_buffers.remove(source_buffer);
+
+        // Step 11.
+        // Nothing to destroy, the player keeps the data it was pushed.
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-endofstream
+    fn EndOfStream(&self, error: Option<EndOfStreamError>) -> ErrorResult {
+        // Step 1.
+        if !self.is_open() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 2.
+        if self.is_updating() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3.
+        self.end_of_stream(error);
+        Ok(())
+    }
+}
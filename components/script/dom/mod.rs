@@ -242,6 +242,8 @@ pub mod bluetoothremotegattdescriptor;
 pub mod bluetoothremotegattserver;
 pub mod bluetoothremotegattservice;
 pub mod bluetoothuuid;
+pub mod cache;
+pub mod cachestorage;
 pub mod canvasgradient;
 pub mod canvaspattern;
 pub mod canvasrenderingcontext2d;
@@ -250,10 +252,12 @@ pub mod channelmergernode;
 pub mod channelsplitternode;
 pub mod characterdata;
 pub mod client;
+pub mod clients;
 pub mod closeevent;
 pub mod comment;
 pub mod compositionevent;
 pub mod console;
+pub mod coordinates;
 mod create;
 pub mod crypto;
 pub mod css;
@@ -275,6 +279,7 @@ pub mod csssupportsrule;
 pub mod cssviewportrule;
 pub mod customelementregistry;
 pub mod customevent;
+pub mod customstateset;
 pub mod dedicatedworkerglobalscope;
 pub mod dissimilaroriginlocation;
 pub mod dissimilaroriginwindow;
@@ -294,12 +299,16 @@ pub mod domrectreadonly;
 pub mod domstringmap;
 pub mod domtokenlist;
 pub mod element;
+pub mod elementinternals;
 pub mod errorevent;
 pub mod event;
 pub mod eventsource;
 pub mod eventtarget;
 pub mod extendableevent;
 pub mod extendablemessageevent;
+pub mod fakexrdevice;
+pub mod fakexrinputcontroller;
+pub mod fetchevent;
 pub mod file;
 pub mod filelist;
 pub mod filereader;
@@ -313,6 +322,7 @@ pub mod gamepadbutton;
 pub mod gamepadbuttonlist;
 pub mod gamepadevent;
 pub mod gamepadlist;
+pub mod geolocation;
 pub mod globalscope;
 pub mod hashchangeevent;
 pub mod headers;
@@ -388,15 +398,21 @@ pub mod htmltrackelement;
 pub mod htmlulistelement;
 pub mod htmlunknownelement;
 pub mod htmlvideoelement;
+pub mod idledeadline;
+pub mod imagebitmap;
 pub mod imagedata;
 pub mod inputevent;
 pub mod keyboardevent;
+pub mod largestcontentfulpaint;
+pub mod layoutshift;
 pub mod location;
+pub mod mediadeviceinfo;
 pub mod mediadevices;
 pub mod mediaerror;
 pub mod medialist;
 pub mod mediaquerylist;
 pub mod mediaquerylistevent;
+pub mod mediasource;
 pub mod mediastream;
 pub mod messageevent;
 pub mod mimetype;
@@ -411,11 +427,14 @@ pub mod navigatorinfo;
 pub mod node;
 pub mod nodeiterator;
 pub mod nodelist;
+pub mod notification;
+pub mod notificationevent;
 pub mod offlineaudiocompletionevent;
 pub mod offlineaudiocontext;
 pub mod offscreencanvas;
 pub mod offscreencanvasrenderingcontext2d;
 pub mod oscillatornode;
+pub mod overconstrainederror;
 pub mod pagetransitionevent;
 pub mod paintrenderingcontext2d;
 pub mod paintsize;
@@ -423,6 +442,8 @@ pub mod paintworkletglobalscope;
 pub mod pannernode;
 pub mod performance;
 pub mod performanceentry;
+pub mod performanceeventtiming;
+pub mod performancelongtasktiming;
 pub mod performancemark;
 pub mod performancemeasure;
 pub mod performancenavigationtiming;
@@ -435,6 +456,8 @@ pub mod permissionstatus;
 pub mod plugin;
 pub mod pluginarray;
 pub mod popstateevent;
+pub mod position;
+pub mod positionerror;
 pub mod processinginstruction;
 pub mod progressevent;
 pub mod promise;
@@ -448,12 +471,15 @@ pub mod rtcicecandidate;
 pub mod rtcpeerconnection;
 pub mod rtcpeerconnectioniceevent;
 pub mod rtcsessiondescription;
+pub mod rtcstatsreport;
 pub mod screen;
 pub mod serviceworker;
 pub mod serviceworkercontainer;
 pub mod serviceworkerglobalscope;
 pub mod serviceworkerregistration;
 pub mod servoparser;
+pub mod sourcebuffer;
+pub mod sourcebufferlist;
 pub mod storage;
 pub mod storageevent;
 pub mod stylepropertymapreadonly;
@@ -462,6 +488,7 @@ pub mod stylesheetlist;
 pub mod svgelement;
 pub mod svggraphicselement;
 pub mod svgsvgelement;
+pub mod taskattributiontiming;
 pub mod testbinding;
 pub mod testbindingiterable;
 pub mod testbindingpairiterable;
@@ -503,6 +530,7 @@ pub mod vrfieldofview;
 pub mod vrframedata;
 pub mod vrpose;
 pub mod vrstageparameters;
+pub mod vttcue;
 pub mod webgl_extensions;
 pub use self::webgl_extensions::ext::*;
 pub mod webgl2renderingcontext;
@@ -513,11 +541,15 @@ pub mod webglcontextevent;
 pub mod webglframebuffer;
 pub mod webglobject;
 pub mod webglprogram;
+pub mod webglquery;
 pub mod webglrenderbuffer;
 pub mod webglrenderingcontext;
+pub mod webglsampler;
 pub mod webglshader;
 pub mod webglshaderprecisionformat;
+pub mod webglsync;
 pub mod webgltexture;
+pub mod webgltransformfeedback;
 pub mod webgluniformlocation;
 pub mod webglvertexarrayobjectoes;
 pub mod websocket;
@@ -534,13 +566,18 @@ pub mod xmlhttprequest;
 pub mod xmlhttprequesteventtarget;
 pub mod xmlhttprequestupload;
 pub mod xr;
+pub mod xrboundedreferencespace;
 pub mod xrframe;
+pub mod xrinputsource;
+pub mod xrinputsourceevent;
+pub mod xrinputsourceschangeevent;
 pub mod xrlayer;
+pub mod xrpose;
 pub mod xrreferencespace;
 pub mod xrrigidtransform;
 pub mod xrsession;
 pub mod xrspace;
-pub mod xrstationaryreferencespace;
+pub mod xrtest;
 pub mod xrview;
 pub mod xrviewerpose;
 pub mod xrviewport;
@@ -2,14 +2,19 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
-use crate::dom::bindings::error::Error;
+use crate::dom::bindings::codegen::Bindings::XMLHttpRequestBinding::BodyInit;
+use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::bindings::utils::to_frozen_array;
 use crate::dom::bluetooth::Bluetooth;
 use crate::dom::gamepadlist::GamepadList;
+use crate::dom::geolocation::Geolocation;
+use crate::dom::headers::is_cors_safelisted_request_content_type;
 use crate::dom::mediadevices::MediaDevices;
 use crate::dom::mimetypearray::MimeTypeArray;
 use crate::dom::navigatorinfo;
@@ -18,8 +23,17 @@ use crate::dom::pluginarray::PluginArray;
 use crate::dom::promise::Promise;
 use crate::dom::serviceworkercontainer::ServiceWorkerContainer;
 use crate::dom::window::Window;
+use crate::dom::xmlhttprequest::Extractable;
 use crate::dom::xr::XR;
 use dom_struct::dom_struct;
+use http::header::{self, HeaderValue};
+use hyper::Method;
+use ipc_channel::ipc;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::JSVal;
+use net_traits::request::KEEPALIVE_INFLIGHT_QUOTA;
+use net_traits::request::{CredentialsMode, RequestInit, RequestMode};
+use net_traits::{CoreResourceMsg, FetchChannels};
 use std::rc::Rc;
 
 #[dom_struct]
@@ -32,7 +46,11 @@ pub struct Navigator {
     xr: MutNullableDom<XR>,
     mediadevices: MutNullableDom<MediaDevices>,
     gamepads: MutNullableDom<GamepadList>,
+    geolocation: MutNullableDom<Geolocation>,
     permissions: MutNullableDom<Permissions>,
+    languages: DomRefCell<Vec<DOMString>>,
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    languages_array: Heap<JSVal>,
 }
 
 impl Navigator {
@@ -46,7 +64,10 @@ impl Navigator {
             xr: Default::default(),
             mediadevices: Default::default(),
             gamepads: Default::default(),
+            geolocation: Default::default(),
             permissions: Default::default(),
+            languages: DomRefCell::new(Vec::new()),
+            languages_array: Heap::default(),
         }
     }
 
@@ -57,6 +78,11 @@ impl Navigator {
             NavigatorBinding::Wrap,
         )
     }
+
+    /// The geolocation object, if the page ever used it.
+    pub fn geolocation(&self) -> Option<DomRoot<Geolocation>> {
+        self.geolocation.get()
+    }
 }
 
 impl NavigatorMethods for Navigator {
@@ -105,6 +131,23 @@ impl NavigatorMethods for Navigator {
         navigatorinfo::Language()
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-navigator-languages
+    #[allow(unsafe_code)]
+    unsafe fn Languages(&self, cx: *mut JSContext) -> JSVal {
+        // The same array is returned until the user's languages change.
+        let languages = navigatorinfo::Languages();
+        if self.languages_array.get().is_undefined() || *self.languages.borrow() != languages {
+            self.languages_array.set(to_frozen_array(&languages, cx));
+            *self.languages.borrow_mut() = languages;
+        }
+        self.languages_array.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigator-online
+    fn OnLine(&self) -> bool {
+        navigatorinfo::OnLine()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-navigator-plugins
     fn Plugins(&self) -> DomRoot<PluginArray> {
         self.plugins.or_init(|| PluginArray::new(&self.global()))
@@ -143,6 +186,12 @@ impl NavigatorMethods for Navigator {
         // TODO: Add not VR related gamepads
         root
     }
+    // https://w3c.github.io/geolocation-api/#navi-geo
+    fn Geolocation(&self) -> DomRoot<Geolocation> {
+        self.geolocation
+            .or_init(|| Geolocation::new(self.global().as_window()))
+    }
+
     // https://w3c.github.io/permissions/#navigator-and-workernavigator-extension
     fn Permissions(&self) -> DomRoot<Permissions> {
         self.permissions
@@ -170,4 +219,65 @@ impl NavigatorMethods for Navigator {
         self.mediadevices
             .or_init(|| MediaDevices::new(&self.global()))
     }
+
+    // https://w3c.github.io/beacon/#sec-processing-model
+    fn SendBeacon(&self, url: USVString, data: Option<BodyInit>) -> Fallible<bool> {
+        let global = self.global();
+
+        // Steps 1-3
+        let url = match global.api_base_url().join(&url.0) {
+            Ok(url) => url,
+            Err(_) => return Err(Error::Type("Invalid beacon URL".to_owned())),
+        };
+
+        // Step 4
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(Error::Type("Beacon URL must be http or https".to_owned()));
+        }
+
+        let mut request = RequestInit {
+            method: Method::POST,
+            url: url,
+            origin: global.origin().immutable().clone(),
+            referrer_url: Some(global.get_url()),
+            pipeline_id: Some(global.pipeline_id()),
+            keep_alive: true,
+            mode: RequestMode::NoCors,
+            credentials_mode: CredentialsMode::Include,
+            ..RequestInit::default()
+        };
+
+        // Step 5
+        if let Some(data) = data {
+            let (bytes, content_type) = data.extract();
+
+            // Step 5.2. The resource thread enforces the quota across all of the
+            // inflight keepalive requests of this document.
+            if bytes.len() > KEEPALIVE_INFLIGHT_QUOTA {
+                return Ok(false);
+            }
+
+            // Step 5.3
+            if let Some(content_type) = content_type {
+                if !is_cors_safelisted_request_content_type(content_type.as_bytes()) {
+                    request.mode = RequestMode::CorsMode;
+                }
+                if let Ok(value) = HeaderValue::from_str(&content_type) {
+                    request.headers.insert(header::CONTENT_TYPE, value);
+                }
+            }
+            request.body = Some(bytes);
+        }
+
+        // Steps 6-7. Nobody listens to the response, the resource thread keeps
+        // the keepalive request alive even if this document goes away.
+        let (action_sender, _) = ipc::channel().unwrap();
+        let _ = global.core_resource_thread().send(CoreResourceMsg::Fetch(
+            request,
+            FetchChannels::ResponseMsg(action_sender, None),
+        ));
+
+        // Step 8
+        Ok(true)
+    }
 }
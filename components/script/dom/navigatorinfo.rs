@@ -4,6 +4,7 @@
 
 use crate::dom::bindings::str::DOMString;
 use servo_config::opts;
+use servo_config::prefs::PREFS;
 
 pub fn Product() -> DOMString {
     DOMString::from("Gecko")
@@ -50,5 +51,13 @@ pub fn AppVersion() -> DOMString {
 }
 
 pub fn Language() -> DOMString {
-    DOMString::from("en-US")
+    Languages().swap_remove(0)
+}
+
+pub fn Languages() -> Vec<DOMString> {
+    PREFS.languages().into_iter().map(DOMString::from).collect()
+}
+
+pub fn OnLine() -> bool {
+    !PREFS.is_network_offline()
 }
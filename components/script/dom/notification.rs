@@ -0,0 +1,354 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::codegen::Bindings::NotificationBinding::NotificationDirectionValues;
+use crate::dom::bindings::codegen::Bindings::NotificationBinding::{
+    self, NotificationDirection, NotificationMethods, NotificationOptions, NotificationPermission,
+    NotificationPermissionCallback,
+};
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::stored_permission_state;
+use crate::dom::permissions::{get_descriptor_permission_state, request_permission_to_use};
+use crate::dom::promise::Promise;
+use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
+use crate::dom::window::Window;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use embedder_traits::{EmbedderMsg, Notification as EmbedderNotification, NotificationEvent};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use script_traits::{ScriptMsg, ServiceWorkerMsg};
+use servo_url::ServoUrl;
+use std::cell::Cell;
+use std::rc::Rc;
+use uuid::Uuid;
+
+// https://notifications.spec.whatwg.org/#notification
+#[dom_struct]
+pub struct Notification {
+    eventtarget: EventTarget,
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    data: EmbedderNotification,
+    /// Whether the close steps ran, after which events from the embedder are ignored
+    closed: Cell<bool>,
+}
+
+impl Notification {
+    fn new_inherited(data: EmbedderNotification) -> Notification {
+        Notification {
+            eventtarget: EventTarget::new_inherited(),
+            data: data,
+            closed: Cell::new(false),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, data: EmbedderNotification) -> DomRoot<Notification> {
+        reflect_dom_object(
+            Box::new(Notification::new_inherited(data)),
+            global,
+            NotificationBinding::Wrap,
+        )
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-notification
+    pub fn Constructor(
+        global: &GlobalScope,
+        title: DOMString,
+        options: &NotificationOptions,
+    ) -> Fallible<DomRoot<Notification>> {
+        // Step 1
+        if global.is::<ServiceWorkerGlobalScope>() {
+            return Err(Error::Type(
+                "Service workers can only show persistent notifications".to_owned(),
+            ));
+        }
+        // Step 2
+        let data = notification_data(global, title, options)?;
+        // Step 3
+        let notification = Notification::new(global, data);
+        // Step 4, in parallel in the embedder
+        notification.show();
+        // Step 5
+        Ok(notification)
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-permission
+    pub fn Permission(global: &GlobalScope) -> NotificationPermission {
+        notification_permission(permission_state(global))
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-requestpermission
+    pub fn RequestPermission(
+        global: &GlobalScope,
+        deprecated_callback: Option<Rc<NotificationPermissionCallback>>,
+    ) -> Rc<Promise> {
+        // Step 1-2
+        let promise = Promise::new(global);
+        // Step 3.1, only windows can ask the user.
+        let state = if global.is::<Window>() {
+            request_permission_to_use(PermissionName::Notifications, global)
+        } else {
+            permission_state(global)
+        };
+        let permission = notification_permission(state);
+        // Step 3.2.1
+        if let Some(callback) = deprecated_callback {
+            let _ = callback.Call__(permission, ExceptionHandling::Report);
+        }
+        // Step 3.2.2
+        promise.resolve_native(&permission);
+        // Step 4
+        promise
+    }
+
+    // https://notifications.spec.whatwg.org/#show-steps
+    fn show(&self) {
+        let global = self.global();
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let this = Trusted::new(self);
+
+        // Step 1-2
+        if permission_state(&global) != PermissionState::Granted {
+            let _ = task_source.queue_with_canceller(
+                task!(notification_error: move || {
+                    this.root().upcast::<EventTarget>().fire_event(atom!("error"));
+                }),
+                &canceller,
+            );
+            return;
+        }
+
+        // Step 3-8, the embedder replaces the notification with the same tag if any, and
+        // reports the show, click and close events.
+        // The route goes away once the embedder drops the sender, which it can do after
+        // `Closed`. Don't keep the notification alive until then.
+        let mut listener = Some((this, task_source, canceller));
+        let (sender, receiver) = ipc::channel().unwrap();
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let event = match message.to() {
+                    Ok(event) => event,
+                    Err(_) => NotificationEvent::Closed,
+                };
+                if let Some((ref this, ref task_source, ref canceller)) = listener {
+                    let this = this.clone();
+                    let _ = task_source.queue_with_canceller(
+                        task!(handle_notification_event: move || {
+                            this.root().handle_notification_event(event);
+                        }),
+                        canceller,
+                    );
+                }
+                if event == NotificationEvent::Closed {
+                    listener = None;
+                }
+            }),
+        );
+        send_to_embedder(
+            &global,
+            EmbedderMsg::ShowNotification(self.data.clone(), Some(sender)),
+        );
+    }
+
+    fn handle_notification_event(&self, event: NotificationEvent) {
+        if self.closed.get() {
+            return;
+        }
+        let target = self.upcast::<EventTarget>();
+        match event {
+            // https://notifications.spec.whatwg.org/#show-steps Step 9
+            NotificationEvent::Shown => {
+                target.fire_event(atom!("show"));
+            },
+            // https://notifications.spec.whatwg.org/#activating-a-notification
+            NotificationEvent::Clicked => {
+                target.fire_cancelable_event(atom!("click"));
+            },
+            // https://notifications.spec.whatwg.org/#close-steps
+            NotificationEvent::Closed => {
+                self.closed.set(true);
+                target.fire_event(atom!("close"));
+            },
+            NotificationEvent::Error => {
+                target.fire_event(atom!("error"));
+            },
+        }
+    }
+}
+
+impl NotificationMethods for Notification {
+    // https://notifications.spec.whatwg.org/#dom-notification-onclick
+    event_handler!(click, GetOnclick, SetOnclick);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-onshow
+    event_handler!(show, GetOnshow, SetOnshow);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-onclose
+    event_handler!(close, GetOnclose, SetOnclose);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-title
+    fn Title(&self) -> DOMString {
+        DOMString::from(self.data.title.clone())
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-dir
+    fn Dir(&self) -> NotificationDirection {
+        NotificationDirectionValues::pairs
+            .iter()
+            .find(|&&(value, _)| value == self.data.dir)
+            .map_or(NotificationDirection::Auto, |&(_, dir)| dir)
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-lang
+    fn Lang(&self) -> DOMString {
+        DOMString::from(self.data.lang.clone())
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-body
+    fn Body(&self) -> DOMString {
+        DOMString::from(self.data.body.clone())
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-tag
+    fn Tag(&self) -> DOMString {
+        DOMString::from(self.data.tag.clone())
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-icon
+    fn Icon(&self) -> USVString {
+        USVString(
+            self.data
+                .icon
+                .as_ref()
+                .map_or(String::new(), |icon| icon.to_string()),
+        )
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-silent
+    fn Silent(&self) -> bool {
+        self.data.silent
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-requireinteraction
+    fn RequireInteraction(&self) -> bool {
+        self.data.require_interaction
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-close
+    fn Close(&self) {
+        if self.closed.get() {
+            return;
+        }
+        let global = self.global();
+        send_to_embedder(
+            &global,
+            EmbedderMsg::CloseNotification(self.data.id.clone()),
+        );
+        // https://notifications.spec.whatwg.org/#close-steps
+        self.closed.set(true);
+        let this = Trusted::new(self);
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(notification_close: move || {
+                this.root().upcast::<EventTarget>().fire_event(atom!("close"));
+            }),
+            &global,
+        );
+    }
+}
+
+/// The notification described by the given title and options, as displayed by the embedder.
+// https://notifications.spec.whatwg.org/#create-a-notification
+pub fn notification_data(
+    global: &GlobalScope,
+    title: DOMString,
+    options: &NotificationOptions,
+) -> Fallible<EmbedderNotification> {
+    // Step 13
+    let icon = match options.icon {
+        Some(USVString(ref icon)) => match global.api_base_url().join(icon) {
+            Ok(icon) => Some(icon),
+            Err(_) => return Err(Error::Type("Invalid icon URL".to_owned())),
+        },
+        None => None,
+    };
+    Ok(EmbedderNotification {
+        id: Uuid::new_v4().to_simple().to_string(),
+        title: String::from(title),
+        body: String::from(options.body.clone()),
+        tag: String::from(options.tag.clone()),
+        origin: global.origin().immutable().ascii_serialization(),
+        icon: icon,
+        lang: String::from(options.lang.clone()),
+        dir: options.dir.as_str().to_owned(),
+        require_interaction: options.requireInteraction,
+        silent: options.silent,
+    })
+}
+
+/// Displays a persistent notification, whose click and close events are fired at the
+/// active worker of the registration for `scope`.
+// https://notifications.spec.whatwg.org/#dom-serviceworkerregistration-shownotification
+pub fn show_persistent_notification(
+    global: &GlobalScope,
+    scope: ServoUrl,
+    data: EmbedderNotification,
+) {
+    match global.downcast::<ServiceWorkerGlobalScope>() {
+        // The page which registered the worker may be gone, don't go through it.
+        Some(worker) => {
+            let _ = worker
+                .swmanager_sender()
+                .send(ServiceWorkerMsg::ShowNotification(scope, data));
+        },
+        None => {
+            let _ = global
+                .script_to_constellation_chan()
+                .send(ScriptMsg::ShowPersistentNotification(scope, data));
+        },
+    }
+}
+
+/// The permission to show notifications of the given global.
+// https://notifications.spec.whatwg.org/#permission-model
+pub fn permission_state(global: &GlobalScope) -> PermissionState {
+    if global.is::<Window>() {
+        let state = get_descriptor_permission_state(PermissionName::Notifications, Some(global));
+        if state != PermissionState::Prompt {
+            return state;
+        }
+    }
+    // Workers can not ask the user, they use what the user answered to a window of
+    // their origin.
+    stored_permission_state(PermissionName::Notifications, global)
+}
+
+fn send_to_embedder(global: &GlobalScope, msg: EmbedderMsg) {
+    let _ = global
+        .script_to_constellation_chan()
+        .send(ScriptMsg::ForwardToEmbedder(msg));
+}
+
+fn notification_permission(state: PermissionState) -> NotificationPermission {
+    match state {
+        PermissionState::Granted => NotificationPermission::Granted,
+        PermissionState::Denied => NotificationPermission::Denied,
+        PermissionState::Prompt => NotificationPermission::Default,
+    }
+}
@@ -0,0 +1,86 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::NotificationEventBinding::{
+    self, NotificationEventMethods,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::extendableevent::ExtendableEvent;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::notification::Notification;
+use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+
+// https://notifications.spec.whatwg.org/#notificationevent
+#[dom_struct]
+pub struct NotificationEvent {
+    event: ExtendableEvent,
+    notification: Dom<Notification>,
+    action: DOMString,
+}
+
+impl NotificationEvent {
+    fn new_inherited(notification: &Notification, action: DOMString) -> NotificationEvent {
+        NotificationEvent {
+            event: ExtendableEvent::new_inherited(),
+            notification: Dom::from_ref(notification),
+            action: action,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        notification: &Notification,
+        action: DOMString,
+    ) -> DomRoot<NotificationEvent> {
+        let ev = reflect_dom_object(
+            Box::new(NotificationEvent::new_inherited(notification, action)),
+            global,
+            NotificationEventBinding::Wrap,
+        );
+        ev.upcast::<Event>().init_event(type_, bubbles, cancelable);
+        ev
+    }
+
+    pub fn Constructor(
+        worker: &ServiceWorkerGlobalScope,
+        type_: DOMString,
+        init: &NotificationEventBinding::NotificationEventInit,
+    ) -> Fallible<DomRoot<NotificationEvent>> {
+        Ok(NotificationEvent::new(
+            worker.upcast(),
+            Atom::from(type_),
+            init.parent.parent.bubbles,
+            init.parent.parent.cancelable,
+            &init.notification,
+            init.action.clone(),
+        ))
+    }
+}
+
+impl NotificationEventMethods for NotificationEvent {
+    // https://notifications.spec.whatwg.org/#dom-notificationevent-notification
+    fn Notification(&self) -> DomRoot<Notification> {
+        DomRoot::from_ref(&*self.notification)
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notificationevent-action
+    fn Action(&self) -> DOMString {
+        self.action.clone()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
@@ -4,31 +4,115 @@
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::OffscreenCanvasBinding::{
-    OffscreenCanvasMethods, OffscreenRenderingContext, Wrap as OffscreenCanvasWrap,
+    ImageEncodeOptions, OffscreenCanvasMethods, OffscreenRenderingContext,
+    Wrap as OffscreenCanvasWrap,
 };
-use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::codegen::UnionTypes::HTMLCanvasElementOrOffscreenCanvas;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::TrustedPromise;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::blob::{Blob, BlobImpl};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlcanvaselement::HTMLCanvasElement;
+use crate::dom::imagebitmap::ImageBitmap;
 use crate::dom::offscreencanvasrenderingcontext2d::OffscreenCanvasRenderingContext2D;
+use crate::dom::promise::Promise;
+use crate::dom::webgl2renderingcontext::WebGL2RenderingContext;
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use crate::image_encoder::{self, ImageType};
+use crate::task_source::{TaskSource, TaskSourceName};
+use canvas_traits::webgl::WebGLVersion;
 use dom_struct::dom_struct;
 use euclid::Size2D;
 use js::jsapi::JSContext;
 use js::rust::HandleValue;
 use ref_filter_map;
+use servo_config::prefs::PREFS;
 use std::cell::Cell;
 use std::cell::Ref;
+use std::rc::Rc;
+use std::thread;
+use webrender_api::{DeviceIntSize, RenderApi, RenderApiSender, Transaction};
+use webrender_api::{DirtyRect, DocumentId, ImageData, ImageDescriptor, ImageFormat, ImageKey};
 
 #[must_root]
 #[derive(Clone, JSTraceable, MallocSizeOf)]
 pub enum OffscreenCanvasContext {
     OffscreenContext2d(Dom<OffscreenCanvasRenderingContext2D>),
-    //WebGL(Dom<WebGLRenderingContext>),
-    //WebGL2(Dom<WebGL2RenderingContext>),
+    WebGL(Dom<WebGLRenderingContext>),
+    WebGL2(Dom<WebGL2RenderingContext>),
+}
+
+/// The image of the canvas element an `OffscreenCanvas` was created from with
+/// `transferControlToOffscreen()`, frames are presented by updating it.
+#[derive(JSTraceable)]
+pub struct OffscreenCanvasPlaceholder {
+    image_key: ImageKey,
+    document: DocumentId,
+    webrender_api: RenderApi,
+}
+
+impl OffscreenCanvasPlaceholder {
+    /// Creates the transparent image the placeholder canvas shows until the
+    /// first frame is committed.
+    pub fn new(api_sender: RenderApiSender, document: DocumentId) -> Self {
+        let webrender_api = api_sender.create_api();
+        let image_key = webrender_api.generate_image_key();
+        let size = Size2D::new(1, 1);
+        let mut txn = Transaction::new();
+        txn.add_image(
+            image_key,
+            Self::image_descriptor(size),
+            ImageData::new(vec![0; 4]),
+            None,
+        );
+        webrender_api.update_resources(txn.resource_updates);
+        Self {
+            image_key,
+            document,
+            webrender_api,
+        }
+    }
+
+    pub fn image_key(&self) -> ImageKey {
+        self.image_key
+    }
+
+    /// Shows a frame of premultiplied BGRA pixels in the placeholder canvas.
+    fn present(&self, pixels: Vec<u8>, size: Size2D<u32>) {
+        let mut txn = Transaction::new();
+        txn.update_image(
+            self.image_key,
+            Self::image_descriptor(size),
+            ImageData::new(pixels),
+            &DirtyRect::All,
+        );
+        txn.generate_frame();
+        self.webrender_api.send_transaction(self.document, txn);
+    }
+
+    fn image_descriptor(size: Size2D<u32>) -> ImageDescriptor {
+        ImageDescriptor {
+            size: DeviceIntSize::new(size.width as i32, size.height as i32),
+            stride: None,
+            format: ImageFormat::BGRA8,
+            offset: 0,
+            is_opaque: false,
+            allow_mipmaps: false,
+        }
+    }
+}
+
+/// What is left of an `OffscreenCanvas` after transferring it, used to
+/// recreate it in the global it is transferred to.
+pub struct TransferredOffscreenCanvas {
+    width: u64,
+    height: u64,
+    placeholder: Option<OffscreenCanvasPlaceholder>,
 }
 
 #[dom_struct]
@@ -37,32 +121,36 @@ pub struct OffscreenCanvas {
     height: Cell<u64>,
     width: Cell<u64>,
     context: DomRefCell<Option<OffscreenCanvasContext>>,
-    placeholder: Option<Dom<HTMLCanvasElement>>,
+    #[ignore_malloc_size_of = "Defined in webrender"]
+    placeholder: DomRefCell<Option<OffscreenCanvasPlaceholder>>,
+    /// Whether this canvas was transferred to another global.
+    detached: Cell<bool>,
 }
 
 impl OffscreenCanvas {
     pub fn new_inherited(
-        height: u64,
         width: u64,
-        placeholder: Option<&HTMLCanvasElement>,
+        height: u64,
+        placeholder: Option<OffscreenCanvasPlaceholder>,
     ) -> OffscreenCanvas {
         OffscreenCanvas {
             eventtarget: EventTarget::new_inherited(),
             height: Cell::new(height),
             width: Cell::new(width),
             context: DomRefCell::new(None),
-            placeholder: placeholder.map(Dom::from_ref),
+            placeholder: DomRefCell::new(placeholder),
+            detached: Cell::new(false),
         }
     }
 
     pub fn new(
         global: &GlobalScope,
-        height: u64,
         width: u64,
-        placeholder: Option<&HTMLCanvasElement>,
+        height: u64,
+        placeholder: Option<OffscreenCanvasPlaceholder>,
     ) -> DomRoot<OffscreenCanvas> {
         reflect_dom_object(
-            Box::new(OffscreenCanvas::new_inherited(height, width, placeholder)),
+            Box::new(OffscreenCanvas::new_inherited(width, height, placeholder)),
             global,
             OffscreenCanvasWrap,
         )
@@ -70,10 +158,10 @@ impl OffscreenCanvas {
 
     pub fn Constructor(
         global: &GlobalScope,
-        height: u64,
         width: u64,
+        height: u64,
     ) -> Fallible<DomRoot<OffscreenCanvas>> {
-        let offscreencanvas = OffscreenCanvas::new(global, height, width, None);
+        let offscreencanvas = OffscreenCanvas::new(global, width, height, None);
         Ok(offscreencanvas)
     }
 
@@ -81,15 +169,31 @@ impl OffscreenCanvas {
         Size2D::new(self.Width(), self.Height())
     }
 
+    fn get_webgl_size(&self) -> Size2D<u32> {
+        Size2D::new(self.Width() as u32, self.Height() as u32)
+    }
+
     pub fn context(&self) -> Option<Ref<OffscreenCanvasContext>> {
         ref_filter_map::ref_filter_map(self.context.borrow(), |ctx| ctx.as_ref())
     }
 
+    fn recreate_contexts(&self) {
+        let size = self.get_webgl_size();
+        if let Some(ref context) = *self.context.borrow() {
+            match *context {
+                OffscreenCanvasContext::OffscreenContext2d(_) => {},
+                OffscreenCanvasContext::WebGL(ref context) => context.recreate(size),
+                OffscreenCanvasContext::WebGL2(ref context) => context.recreate(size),
+            }
+        }
+    }
+
     #[allow(unsafe_code)]
     fn get_or_init_2d_context(&self) -> Option<DomRoot<OffscreenCanvasRenderingContext2D>> {
         if let Some(ctx) = self.context() {
             return match *ctx {
                 OffscreenCanvasContext::OffscreenContext2d(ref ctx) => Some(DomRoot::from_ref(ctx)),
+                _ => None,
             };
         }
         let size = self.get_size();
@@ -99,6 +203,120 @@ impl OffscreenCanvas {
         ));
         Some(context)
     }
+
+    #[allow(unsafe_code)]
+    unsafe fn get_or_init_webgl_context(
+        &self,
+        cx: *mut JSContext,
+        options: HandleValue,
+    ) -> Option<DomRoot<WebGLRenderingContext>> {
+        if let Some(ctx) = self.context() {
+            return match *ctx {
+                OffscreenCanvasContext::WebGL(ref ctx) => Some(DomRoot::from_ref(ctx)),
+                _ => None,
+            };
+        }
+        let size = self.get_webgl_size();
+        let attrs = HTMLCanvasElement::get_gl_attributes(cx, options)?;
+        let canvas = HTMLCanvasElementOrOffscreenCanvas::OffscreenCanvas(DomRoot::from_ref(self));
+        let context =
+            WebGLRenderingContext::new(&self.global(), &canvas, WebGLVersion::WebGL1, size, attrs)?;
+        *self.context.borrow_mut() = Some(OffscreenCanvasContext::WebGL(Dom::from_ref(&*context)));
+        Some(context)
+    }
+
+    #[allow(unsafe_code)]
+    unsafe fn get_or_init_webgl2_context(
+        &self,
+        cx: *mut JSContext,
+        options: HandleValue,
+    ) -> Option<DomRoot<WebGL2RenderingContext>> {
+        if !PREFS.is_webgl2_enabled() {
+            return None;
+        }
+        if let Some(ctx) = self.context() {
+            return match *ctx {
+                OffscreenCanvasContext::WebGL2(ref ctx) => Some(DomRoot::from_ref(ctx)),
+                _ => None,
+            };
+        }
+        let size = self.get_webgl_size();
+        let attrs = HTMLCanvasElement::get_gl_attributes(cx, options)?;
+        let canvas = HTMLCanvasElementOrOffscreenCanvas::OffscreenCanvas(DomRoot::from_ref(self));
+        let context = WebGL2RenderingContext::new(&self.global(), &canvas, size, attrs)?;
+        *self.context.borrow_mut() = Some(OffscreenCanvasContext::WebGL2(Dom::from_ref(&*context)));
+        Some(context)
+    }
+
+    /// Reads back the current frame of the context as premultiplied BGRA
+    /// pixels.
+    fn fetch_frame(&self) -> Option<(Vec<u8>, Size2D<u32>)> {
+        match *self.context()? {
+            // TODO: the 2D context doesn't have a bitmap yet.
+            OffscreenCanvasContext::OffscreenContext2d(_) => None,
+            OffscreenCanvasContext::WebGL(ref context) => context.get_frame_pixels(),
+            OffscreenCanvasContext::WebGL2(ref context) => {
+                context.base_context().get_frame_pixels()
+            },
+        }
+    }
+
+    /// Returns a copy of the bitmap as non-premultiplied RGBA pixels.
+    fn get_content(&self) -> Option<Vec<u8>> {
+        let size = self.get_webgl_size();
+        match self.context.borrow().as_ref() {
+            Some(&OffscreenCanvasContext::WebGL(ref context)) => context.get_image_data(size),
+            Some(&OffscreenCanvasContext::WebGL2(ref context)) => {
+                context.base_context().get_image_data(size)
+            },
+            // TODO: the 2D context doesn't have a bitmap yet.
+            Some(&OffscreenCanvasContext::OffscreenContext2d(_)) | None => {
+                Some(vec![0; size.area() as usize * 4])
+            },
+        }
+    }
+
+    /// Presents the current frame in the placeholder canvas, if any.
+    ///
+    /// https://html.spec.whatwg.org/multipage/#dom-offscreencanvasrenderingcontext2d-commit
+    pub fn commit(&self) {
+        if let Some(ref placeholder) = *self.placeholder.borrow() {
+            if let Some((pixels, size)) = self.fetch_frame() {
+                placeholder.present(pixels, size);
+            }
+        }
+    }
+
+    /// Detaches this canvas so that it can be recreated in another global.
+    ///
+    /// https://html.spec.whatwg.org/multipage/#offscreencanvas-transfer-steps
+    pub fn transfer(&self) -> Fallible<TransferredOffscreenCanvas> {
+        if self.detached.get() {
+            return Err(Error::DataClone);
+        }
+        if self.context.borrow().is_some() {
+            return Err(Error::InvalidState);
+        }
+        self.detached.set(true);
+        Ok(TransferredOffscreenCanvas {
+            width: self.width.get(),
+            height: self.height.get(),
+            placeholder: self.placeholder.borrow_mut().take(),
+        })
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#offscreencanvas-transfer-receiving-steps
+    pub fn new_from_transferred(
+        global: &GlobalScope,
+        transferred: TransferredOffscreenCanvas,
+    ) -> DomRoot<OffscreenCanvas> {
+        OffscreenCanvas::new(
+            global,
+            transferred.width,
+            transferred.height,
+            transferred.placeholder,
+        )
+    }
 }
 
 impl OffscreenCanvasMethods for OffscreenCanvas {
@@ -106,22 +324,93 @@ impl OffscreenCanvasMethods for OffscreenCanvas {
     #[allow(unsafe_code)]
     unsafe fn GetContext(
         &self,
-        _cx: *mut JSContext,
+        cx: *mut JSContext,
         id: DOMString,
-        _options: HandleValue,
-    ) -> Option<OffscreenRenderingContext> {
-        match &*id {
+        options: HandleValue,
+    ) -> Fallible<Option<OffscreenRenderingContext>> {
+        if self.detached.get() {
+            return Err(Error::InvalidState);
+        }
+        Ok(match &*id {
             "2d" => self
                 .get_or_init_2d_context()
                 .map(OffscreenRenderingContext::OffscreenCanvasRenderingContext2D),
-            /*"webgl" | "experimental-webgl" => self
+            "webgl" | "experimental-webgl" => self
                 .get_or_init_webgl_context(cx, options)
                 .map(OffscreenRenderingContext::WebGLRenderingContext),
             "webgl2" | "experimental-webgl2" => self
                 .get_or_init_webgl2_context(cx, options)
-                .map(OffscreenRenderingContext::WebGL2RenderingContext),*/
+                .map(OffscreenRenderingContext::WebGL2RenderingContext),
             _ => None,
+        })
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-transfertoimagebitmap
+    fn TransferToImageBitmap(&self) -> Fallible<DomRoot<ImageBitmap>> {
+        if self.detached.get() || self.context.borrow().is_none() {
+            return Err(Error::InvalidState);
         }
+        let (pixels, size) = self.fetch_frame().unwrap_or_else(|| {
+            let size = self.get_webgl_size();
+            (vec![0; size.area() as usize * 4], size)
+        });
+        // TODO: reset the bitmap of the context to transparent black.
+        Ok(ImageBitmap::new(&self.global(), size, pixels))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-converttoblob
+    fn ConvertToBlob(&self, options: &ImageEncodeOptions) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+
+        // Step 1.
+        if self.detached.get() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Step 2.
+        let size = self.get_webgl_size();
+        if size.width == 0 || size.height == 0 {
+            promise.reject_error(Error::IndexSize);
+            return promise;
+        }
+
+        // Step 3.
+        let file = self.get_content();
+        let image_type = ImageType::new(Some(&*options.type_), size);
+        let quality = options.quality;
+
+        // Step 5, the bitmap is encoded off the script thread.
+        let trusted_promise = TrustedPromise::new(promise.clone());
+        let global = self.global();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let task_source = global.dom_manipulation_task_source();
+        thread::Builder::new()
+            .name("canvas blob encoder".to_owned())
+            .spawn(move || {
+                let encoded =
+                    file.map(|file| image_encoder::encode(image_type, &file, size, quality));
+                let task = task!(resolve_blob_promise: move || {
+                    let promise = trusted_promise.root();
+                    match encoded {
+                        Some(bytes) => {
+                            let blob = Blob::new(
+                                &promise.global(),
+                                BlobImpl::new_from_bytes(bytes),
+                                image_type.mime_type().to_owned(),
+                            );
+                            promise.resolve_native(&blob);
+                        },
+                        // FIXME: this should be an "EncodingError" DOMException.
+                        None => promise.reject_error(Error::InvalidState),
+                    }
+                });
+                let _ = task_source.queue_with_canceller(task, &canceller);
+            })
+            .expect("Thread spawning failed");
+
+        // Step 6.
+        promise
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-width
@@ -132,6 +421,7 @@ impl OffscreenCanvasMethods for OffscreenCanvas {
     // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-width
     fn SetWidth(&self, value: u64) {
         self.width.set(value);
+        self.recreate_contexts();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-height
@@ -142,5 +432,6 @@ impl OffscreenCanvasMethods for OffscreenCanvas {
     // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-height
     fn SetHeight(&self, value: u64) {
         self.height.set(value);
+        self.recreate_contexts();
     }
 }
@@ -0,0 +1,59 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::OverconstrainedErrorBinding::{
+    self, OverconstrainedErrorMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+
+// https://w3c.github.io/mediacapture-main/#overconstrainederror-interface
+#[dom_struct]
+pub struct OverconstrainedError {
+    reflector_: Reflector,
+    constraint: DOMString,
+    message: DOMString,
+}
+
+impl OverconstrainedError {
+    fn new_inherited(constraint: DOMString, message: DOMString) -> OverconstrainedError {
+        OverconstrainedError {
+            reflector_: Reflector::new(),
+            constraint,
+            message,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        constraint: DOMString,
+        message: DOMString,
+    ) -> DomRoot<OverconstrainedError> {
+        reflect_dom_object(
+            Box::new(OverconstrainedError::new_inherited(constraint, message)),
+            global,
+            OverconstrainedErrorBinding::Wrap,
+        )
+    }
+}
+
+impl OverconstrainedErrorMethods for OverconstrainedError {
+    // https://w3c.github.io/mediacapture-main/#overconstrainederror-interface
+    fn Name(&self) -> DOMString {
+        DOMString::from("OverconstrainedError")
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-overconstrainederror-constraint
+    fn Constraint(&self) -> DOMString {
+        self.constraint.clone()
+    }
+
+    // https://w3c.github.io/mediacapture-main/#overconstrainederror-interface
+    fn Message(&self) -> DOMString {
+        self.message.clone()
+    }
+}
@@ -3,6 +3,8 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLImageElementBinding::HTMLImageElementMethods;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceEntryList as DOMPerformanceEntryList;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::{
@@ -14,9 +16,16 @@ use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmlimageelement::HTMLImageElement;
+use crate::dom::largestcontentfulpaint::LargestContentfulPaint;
+use crate::dom::layoutshift::LayoutShift;
 use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::performanceeventtiming::PerformanceEventTiming;
+use crate::dom::performancelongtasktiming::PerformanceLongTaskTiming;
 use crate::dom::performancemark::PerformanceMark;
 use crate::dom::performancemeasure::PerformanceMeasure;
 use crate::dom::performancenavigationtiming::PerformanceNavigationTiming;
@@ -24,6 +33,7 @@ use crate::dom::performanceobserver::PerformanceObserver as DOMPerformanceObserv
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
 use metrics::ToMs;
+use script_layout_interface::message::Msg;
 use std::cell::Cell;
 use std::cmp::Ordering;
 use std::collections::VecDeque;
@@ -52,6 +62,15 @@ const INVALID_ENTRY_NAMES: &'static [&'static str] = &[
     "loadEventEnd",
 ];
 
+/// Events processed faster than this, in milliseconds, do not get an `event` entry.
+/// <https://w3c.github.io/event-timing/#sec-fini-event-timing>
+const EVENT_TIMING_DURATION_THRESHOLD: f64 = 104.;
+
+/// Layout shifts within this time, in milliseconds, after an input are flagged as
+/// having recent input.
+/// <https://wicg.github.io/layout-instability/#recent-input>
+const RECENT_INPUT_THRESHOLD: f64 = 500.;
+
 /// Implementation of a list of PerformanceEntry items shared by the
 /// Performance and PerformanceObserverEntryList interfaces implementations.
 #[derive(JSTraceable, MallocSizeOf)]
@@ -144,6 +163,13 @@ pub struct Performance {
     resource_timing_buffer_current_size: Cell<usize>,
     resource_timing_buffer_pending_full_event: Cell<bool>,
     resource_timing_secondary_entries: DomRefCell<VecDeque<DomRoot<PerformanceEntry>>>,
+    /// <https://w3c.github.io/event-timing/#has-dispatched-input-event>
+    has_dispatched_input_event: Cell<bool>,
+    /// The time of the last input event, used to flag layout shifts that follow input.
+    last_input_time: Cell<Option<f64>>,
+    /// Whether layout was last told that largest contentful paint and layout shift
+    /// entries are observed.
+    observed_paint_metrics: Cell<(bool, bool)>,
 }
 
 impl Performance {
@@ -158,6 +184,9 @@ impl Performance {
             resource_timing_buffer_current_size: Cell::new(0),
             resource_timing_buffer_pending_full_event: Cell::new(false),
             resource_timing_secondary_entries: DomRefCell::new(VecDeque::new()),
+            has_dispatched_input_event: Cell::new(false),
+            last_input_time: Cell::new(None),
+            observed_paint_metrics: Cell::new((false, false)),
         }
     }
 
@@ -198,6 +227,8 @@ impl Performance {
                 entry_types,
             }),
         };
+        drop(observers);
+        self.update_observed_paint_metrics();
     }
 
     /// Remove a PerformanceObserver from the list of observers.
@@ -209,6 +240,35 @@ impl Performance {
         };
 
         observers.remove(index);
+        drop(observers);
+        self.update_observed_paint_metrics();
+    }
+
+    /// Layout only computes the largest contentful paint and layout shift entries of
+    /// a window while they are observed. The largest contentful paint is not observed
+    /// anymore after the first input.
+    fn update_observed_paint_metrics(&self) {
+        let is_observed = |entry_type: &str| {
+            self.observers
+                .borrow()
+                .iter()
+                .any(|o| o.entry_types.iter().any(|t| &**t == entry_type))
+        };
+        let observed_paint_metrics = (
+            !self.has_dispatched_input_event.get() && is_observed("largest-contentful-paint"),
+            is_observed("layout-shift"),
+        );
+        if observed_paint_metrics == self.observed_paint_metrics.get() {
+            return;
+        }
+        self.observed_paint_metrics.set(observed_paint_metrics);
+        if let Some(window) = self.global().downcast::<Window>() {
+            let (largest_contentful_paint, layout_shift) = observed_paint_metrics;
+            let msg = Msg::SetObservedPaintMetrics(largest_contentful_paint, layout_shift);
+            if window.layout_chan().send(msg).is_err() {
+                warn!("Layout thread failed to receive SetObservedPaintMetrics");
+            }
+        }
     }
 
     /// Queue a notification for each performance observer interested in
@@ -295,6 +355,135 @@ impl Performance {
         (time::precise_time_ns() - self.navigation_start_precise).to_ms()
     }
 
+    /// The time relative to the time origin, in milliseconds, of a time given by
+    /// `time::precise_time_ns`.
+    fn to_relative_time(&self, precise_time_ns: u64) -> f64 {
+        precise_time_ns
+            .saturating_sub(self.navigation_start_precise)
+            .to_ms()
+    }
+
+    /// Reports a task of the event loop that took longer than 50ms.
+    /// <https://w3c.github.io/longtasks/#report-long-tasks>
+    pub fn queue_long_task(&self, start: u64, end: u64) {
+        let start_time = self.to_relative_time(start);
+        let entry = PerformanceLongTaskTiming::new(
+            &self.global(),
+            start_time,
+            self.to_relative_time(end) - start_time,
+        );
+        self.queue_entry(
+            &entry.upcast::<PerformanceEntry>(),
+            false, /* buffer performance entry */
+        );
+    }
+
+    /// Reports the processing of a trusted input event.
+    /// <https://w3c.github.io/event-timing/#sec-fini-event-timing>
+    pub fn record_event_timing(&self, event: &Event, processing_start: u64, processing_end: u64) {
+        let start_time = self.to_relative_time(event.precise_time_ns());
+        let processing_start = self.to_relative_time(processing_start);
+        let processing_end = self.to_relative_time(processing_end);
+
+        // https://w3c.github.io/event-timing/#sec-init-event-timing
+        // Pointer events are not supported, so a pointerdown is never the first input.
+        let type_ = event.type_();
+        let is_input = &*type_ == "keydown" || &*type_ == "mousedown" || &*type_ == "click";
+        if is_input {
+            self.last_input_time.set(Some(start_time));
+        }
+        if !self.has_dispatched_input_event.get() {
+            if is_input {
+                self.has_dispatched_input_event.set(true);
+                self.update_observed_paint_metrics();
+                let entry = PerformanceEventTiming::new(
+                    &self.global(),
+                    DOMString::from("first-input"),
+                    event,
+                    start_time,
+                    processing_start,
+                    processing_end,
+                );
+                self.queue_entry(
+                    &entry.upcast::<PerformanceEntry>(),
+                    true, /* buffer performance entry */
+                );
+            }
+        }
+
+        if processing_end - start_time < EVENT_TIMING_DURATION_THRESHOLD {
+            return;
+        }
+        let entry = PerformanceEventTiming::new(
+            &self.global(),
+            DOMString::from("event"),
+            event,
+            start_time,
+            processing_start,
+            processing_end,
+        );
+        // The event entries are only reported to the observers.
+        self.queue_entry(
+            &entry.upcast::<PerformanceEntry>(),
+            false, /* buffer performance entry */
+        );
+    }
+
+    /// Reports a new largest contentful paint candidate painted at `render_time`, in
+    /// nanoseconds since navigation start.
+    /// <https://wicg.github.io/largest-contentful-paint/#sec-report-largest-contentful-paint>
+    pub fn queue_largest_contentful_paint(
+        &self,
+        render_time: u64,
+        size: u64,
+        element: Option<&Element>,
+    ) {
+        // The largest contentful paint is not updated anymore after the user interacted
+        // with the page.
+        if self.has_dispatched_input_event.get() {
+            return;
+        }
+        let id = element.map_or(DOMString::new(), |element| element.Id());
+        let url = element
+            .and_then(|element| element.downcast::<HTMLImageElement>())
+            .map_or(DOMString::new(), |image| {
+                DOMString::from(image.CurrentSrc().0)
+            });
+        let entry = LargestContentfulPaint::new(
+            &self.global(),
+            render_time.to_ms(),
+            size.min(u32::max_value() as u64) as u32,
+            id,
+            url,
+            element,
+        );
+        self.queue_entry(
+            &entry.upcast::<PerformanceEntry>(),
+            true, /* buffer performance entry */
+        );
+    }
+
+    /// Reports a layout shift painted at `time`, in nanoseconds since navigation start.
+    /// <https://wicg.github.io/layout-instability/#sec-report-layout-shift>
+    pub fn queue_layout_shift(&self, value: f64, time: u64) {
+        let start_time = time.to_ms();
+        let last_input_time = self.last_input_time.get();
+        let had_recent_input = last_input_time.map_or(false, |last_input_time| {
+            start_time - last_input_time <= RECENT_INPUT_THRESHOLD
+        });
+        let entry = LayoutShift::new(
+            &self.global(),
+            start_time,
+            value,
+            had_recent_input,
+            last_input_time.unwrap_or(0.),
+        );
+        self.queue_entry(
+            &entry.upcast::<PerformanceEntry>(),
+            true, /* buffer performance entry */
+        );
+    }
+
     fn can_add_resource_timing_entry(&self) -> bool {
         self.resource_timing_buffer_current_size.get() <=
             self.resource_timing_buffer_size_limit.get()
@@ -0,0 +1,97 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
+use crate::dom::bindings::codegen::Bindings::PerformanceEventTimingBinding::{
+    self, PerformanceEventTimingMethods,
+};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::node::Node;
+use crate::dom::performanceentry::PerformanceEntry;
+use dom_struct::dom_struct;
+
+// https://w3c.github.io/event-timing/#sec-performance-event-timing
+#[dom_struct]
+pub struct PerformanceEventTiming {
+    entry: PerformanceEntry,
+    processing_start: f64,
+    processing_end: f64,
+    cancelable: bool,
+    target: MutNullableDom<Node>,
+}
+
+impl PerformanceEventTiming {
+    fn new_inherited(
+        entry_type: DOMString,
+        event: &Event,
+        start_time: f64,
+        processing_start: f64,
+        processing_end: f64,
+    ) -> PerformanceEventTiming {
+        // The duration is rounded to 8ms to not expose precise rendering times.
+        let duration = ((processing_end - start_time) / 8.).round() * 8.;
+        let target = event
+            .GetTarget()
+            .and_then(|target| DomRoot::downcast::<Node>(target));
+        PerformanceEventTiming {
+            entry: PerformanceEntry::new_inherited(
+                DOMString::from(&*event.type_()),
+                entry_type,
+                start_time,
+                duration,
+            ),
+            processing_start,
+            processing_end,
+            cancelable: event.Cancelable(),
+            target: MutNullableDom::new(target.as_ref().map(|target| &**target)),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        entry_type: DOMString,
+        event: &Event,
+        start_time: f64,
+        processing_start: f64,
+        processing_end: f64,
+    ) -> DomRoot<PerformanceEventTiming> {
+        let entry = PerformanceEventTiming::new_inherited(
+            entry_type,
+            event,
+            start_time,
+            processing_start,
+            processing_end,
+        );
+        reflect_dom_object(Box::new(entry), global, PerformanceEventTimingBinding::Wrap)
+    }
+}
+
+impl PerformanceEventTimingMethods for PerformanceEventTiming {
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-processingstart
+    fn ProcessingStart(&self) -> DOMHighResTimeStamp {
+        Finite::wrap(self.processing_start)
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-processingend
+    fn ProcessingEnd(&self) -> DOMHighResTimeStamp {
+        Finite::wrap(self.processing_end)
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-cancelable
+    fn Cancelable(&self) -> bool {
+        self.cancelable
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-target
+    fn GetTarget(&self) -> Option<DomRoot<Node>> {
+        self.target.get()
+    }
+}
@@ -0,0 +1,78 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::PerformanceLongTaskTimingBinding::{
+    self, PerformanceLongTaskTimingMethods,
+};
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::taskattributiontiming::TaskAttributionTiming;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::JSVal;
+
+// https://w3c.github.io/longtasks/#sec-PerformanceLongTaskTiming
+#[dom_struct]
+pub struct PerformanceLongTaskTiming {
+    entry: PerformanceEntry,
+    attribution: Vec<Dom<TaskAttributionTiming>>,
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    attribution_array: Heap<JSVal>,
+}
+
+impl PerformanceLongTaskTiming {
+    fn new_inherited(
+        start_time: f64,
+        duration: f64,
+        attribution: &TaskAttributionTiming,
+    ) -> PerformanceLongTaskTiming {
+        PerformanceLongTaskTiming {
+            // Tasks are only attributed to the browsing context that ran them.
+            entry: PerformanceEntry::new_inherited(
+                DOMString::from("self"),
+                DOMString::from("longtask"),
+                start_time,
+                duration,
+            ),
+            attribution: vec![Dom::from_ref(attribution)],
+            attribution_array: Heap::default(),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        start_time: f64,
+        duration: f64,
+    ) -> DomRoot<PerformanceLongTaskTiming> {
+        let attribution = TaskAttributionTiming::new(global, DOMString::from("window"));
+        let entry = PerformanceLongTaskTiming::new_inherited(start_time, duration, &attribution);
+        reflect_dom_object(
+            Box::new(entry),
+            global,
+            PerformanceLongTaskTimingBinding::Wrap,
+        )
+    }
+}
+
+impl PerformanceLongTaskTimingMethods for PerformanceLongTaskTiming {
+    // https://w3c.github.io/longtasks/#dom-performancelongtasktiming-attribution
+    #[allow(unsafe_code)]
+    unsafe fn Attribution(&self, cx: *mut JSContext) -> JSVal {
+        if self.attribution_array.get().is_undefined() {
+            let attribution: Vec<DomRoot<TaskAttributionTiming>> = self
+                .attribution
+                .iter()
+                .map(|entry| DomRoot::from_ref(&**entry))
+                .collect();
+            self.attribution_array
+                .set(to_frozen_array(&attribution, cx));
+        }
+        self.attribution_array.get()
+    }
+}
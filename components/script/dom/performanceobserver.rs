@@ -28,7 +28,12 @@ const VALID_ENTRY_TYPES: &'static [&'static str] = &[
     "navigation", // Navigation Timing API
     // "frame", //TODO Frame Timing API
     // "server", XXX Server Timing API
-    "paint", // Paint Timing API
+    "paint",                    // Paint Timing API
+    "longtask",                 // Long Tasks API
+    "event",                    // Event Timing API
+    "first-input",              // Event Timing API
+    "largest-contentful-paint", // Largest Contentful Paint API
+    "layout-shift",             // Layout Instability API
 ];
 
 #[dom_struct]
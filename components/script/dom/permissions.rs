@@ -9,6 +9,7 @@ use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
 };
 use crate::dom::bindings::codegen::Bindings::PermissionsBinding::{self, PermissionsMethods};
 use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bluetooth::Bluetooth;
@@ -16,10 +17,13 @@ use crate::dom::bluetoothpermissionresult::BluetoothPermissionResult;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::permissionstatus::PermissionStatus;
 use crate::dom::promise::Promise;
+use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
 use dom_struct::dom_struct;
+use ipc_channel::ipc;
 use js::conversions::ConversionResult;
 use js::jsapi::{JSContext, JSObject};
 use js::jsval::{ObjectValue, UndefinedValue};
+use script_traits::{ScriptMsg, ServiceWorkerMsg};
 #[cfg(target_os = "linux")]
 use servo_config::opts;
 use servo_config::prefs::PREFS;
@@ -347,6 +351,72 @@ pub fn get_descriptor_permission_state(
     state
 }
 
+// https://w3c.github.io/permissions/#request-permission-to-use
+pub fn request_permission_to_use(
+    permission_name: PermissionName,
+    env_settings_obj: &GlobalScope,
+) -> PermissionState {
+    // Step 1-2.
+    let state = get_descriptor_permission_state(permission_name, Some(env_settings_obj));
+    if state != PermissionState::Prompt {
+        return state;
+    }
+
+    // Step 3-4.
+    let state = if PREFS
+        .get("dom.permissions.testing.grant_requests")
+        .as_boolean()
+        .unwrap_or(false)
+    {
+        PermissionState::Granted
+    } else {
+        prompt_user(&format!("{} {} ?", REQUEST_DIALOG_MESSAGE, permission_name))
+    };
+    env_settings_obj
+        .as_window()
+        .permission_state_invocation_results()
+        .borrow_mut()
+        .insert(permission_name.to_string(), state);
+    // Keep the answer for the other globals of the origin.
+    let _ = env_settings_obj
+        .script_to_constellation_chan()
+        .send(ScriptMsg::RecordPermission(
+            env_settings_obj.origin().immutable().clone(),
+            permission_name.to_string(),
+            state == PermissionState::Granted,
+        ));
+    state
+}
+
+/// The answer of the user to a request to use the permission of the given name by the
+/// origin of the given global, `Prompt` if they were not asked.
+pub fn stored_permission_state(
+    permission_name: PermissionName,
+    global: &GlobalScope,
+) -> PermissionState {
+    let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+    let origin = global.origin().immutable().clone();
+    let name = permission_name.to_string();
+    match global.downcast::<ServiceWorkerGlobalScope>() {
+        // The page which registered the worker may be gone, don't go through it.
+        Some(worker) => {
+            let _ = worker
+                .swmanager_sender()
+                .send(ServiceWorkerMsg::GetPermission(origin, name, sender));
+        },
+        None => {
+            let _ = global
+                .script_to_constellation_chan()
+                .send(ScriptMsg::GetPermission(origin, name, sender));
+        },
+    }
+    match receiver.recv() {
+        Ok(Some(true)) => PermissionState::Granted,
+        Ok(Some(false)) => PermissionState::Denied,
+        Ok(None) | Err(_) => PermissionState::Prompt,
+    }
+}
+
 #[cfg(target_os = "linux")]
 fn prompt_user(message: &str) -> PermissionState {
     if opts::get().headless {
@@ -0,0 +1,59 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::PositionBinding::{self, PositionMethods};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::coordinates::Coordinates;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use embedder_traits::GeolocationPosition;
+
+// https://w3c.github.io/geolocation-api/#position_interface
+#[dom_struct]
+pub struct Position {
+    reflector_: Reflector,
+    coords: Dom<Coordinates>,
+    /// When the position was acquired, in milliseconds since the epoch.
+    timestamp: u64,
+}
+
+impl Position {
+    fn new_inherited(coords: &Coordinates, timestamp: u64) -> Position {
+        Position {
+            reflector_: Reflector::new(),
+            coords: Dom::from_ref(coords),
+            timestamp: timestamp,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        position: GeolocationPosition,
+        timestamp: u64,
+    ) -> DomRoot<Position> {
+        let coords = Coordinates::new(window, position);
+        reflect_dom_object(
+            Box::new(Position::new_inherited(&coords, timestamp)),
+            window,
+            PositionBinding::Wrap,
+        )
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+impl PositionMethods for Position {
+    // https://w3c.github.io/geolocation-api/#coords
+    fn Coords(&self) -> DomRoot<Coordinates> {
+        DomRoot::from_ref(&*self.coords)
+    }
+
+    // https://w3c.github.io/geolocation-api/#timestamp
+    fn Timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
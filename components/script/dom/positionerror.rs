@@ -0,0 +1,48 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::PositionErrorBinding::{self, PositionErrorMethods};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+// https://w3c.github.io/geolocation-api/#position_error_interface
+#[dom_struct]
+pub struct PositionError {
+    reflector_: Reflector,
+    code: u16,
+    message: DOMString,
+}
+
+impl PositionError {
+    fn new_inherited(code: u16, message: DOMString) -> PositionError {
+        PositionError {
+            reflector_: Reflector::new(),
+            code: code,
+            message: message,
+        }
+    }
+
+    pub fn new(window: &Window, code: u16, message: DOMString) -> DomRoot<PositionError> {
+        reflect_dom_object(
+            Box::new(PositionError::new_inherited(code, message)),
+            window,
+            PositionErrorBinding::Wrap,
+        )
+    }
+}
+
+impl PositionErrorMethods for PositionError {
+    // https://w3c.github.io/geolocation-api/#code
+    fn Code(&self) -> u16 {
+        self.code
+    }
+
+    // https://w3c.github.io/geolocation-api/#message
+    fn Message(&self) -> DOMString {
+        self.message.clone()
+    }
+}
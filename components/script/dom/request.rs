@@ -27,6 +27,7 @@ use crate::dom::xmlhttprequest::Extractable;
 use dom_struct::dom_struct;
 use http::method::InvalidMethod;
 use http::Method as HttpMethod;
+use net_traits::cache_storage_thread::CachedRequest;
 use net_traits::request::CacheMode as NetTraitsRequestCache;
 use net_traits::request::CredentialsMode as NetTraitsRequestCredentials;
 use net_traits::request::Destination as NetTraitsRequestDestination;
@@ -35,6 +36,7 @@ use net_traits::request::Referrer as NetTraitsRequestReferrer;
 use net_traits::request::Request as NetTraitsRequest;
 use net_traits::request::RequestMode as NetTraitsRequestMode;
 use net_traits::request::{Origin, Window};
+use net_traits::CustomResponseMediator;
 use net_traits::ReferrerPolicy as MsgReferrerPolicy;
 use servo_url::ServoUrl;
 use std::cell::{Cell, Ref};
@@ -158,12 +160,14 @@ impl Request {
         request.cache_mode = temporary_request.cache_mode;
         request.redirect_mode = temporary_request.redirect_mode;
         request.integrity_metadata = temporary_request.integrity_metadata;
+        request.keep_alive = temporary_request.keep_alive;
 
         // Step 13
         if init.body.is_some() ||
             init.cache.is_some() ||
             init.credentials.is_some() ||
             init.integrity.is_some() ||
+            init.keepalive.is_some() ||
             init.headers.is_some() ||
             init.method.is_some() ||
             init.mode.is_some() ||
@@ -274,6 +278,11 @@ impl Request {
             request.integrity_metadata = integrity;
         }
 
+        // Step 24.1
+        if let Some(init_keepalive) = init.keepalive {
+            request.keep_alive = init_keepalive;
+        }
+
         // Step 25
         if let Some(init_method) = init.method.as_ref() {
             // Step 25.1
@@ -467,6 +476,52 @@ impl Request {
     pub fn get_request(&self) -> NetTraitsRequest {
         self.request.borrow().clone()
     }
+
+    /// The parts of this request that are kept in a cache.
+    pub fn to_cached_request(&self) -> CachedRequest {
+        let request = self.request.borrow();
+        CachedRequest {
+            url: request.url(),
+            method: request.method.as_str().to_owned(),
+            headers: self.Headers().get_header_pairs(),
+        }
+    }
+
+    /// A request for an entry of a cache, its headers are immutable.
+    pub fn from_cached_request(global: &GlobalScope, cached: &CachedRequest) -> DomRoot<Request> {
+        let r = Request::new(global, cached.url.clone());
+        r.Headers().set_header_pairs(&cached.headers);
+        r.Headers().set_guard(Guard::Immutable);
+        {
+            let mut request = r.request.borrow_mut();
+            if let Ok(method) = HttpMethod::from_bytes(cached.method.as_bytes()) {
+                request.method = method;
+            }
+            request.headers = r.Headers().get_headers_list();
+        }
+        r
+    }
+
+    /// The request of a fetch event, for a fetch a service worker was asked to handle.
+    pub fn for_fetch_event(
+        global: &GlobalScope,
+        mediator: &CustomResponseMediator,
+    ) -> DomRoot<Request> {
+        let r = Request::new(global, mediator.load_url.clone());
+        r.Headers().set_headers(mediator.headers.clone());
+        r.Headers().set_guard(Guard::Immutable);
+        {
+            let mut request = r.request.borrow_mut();
+            request.method = mediator.method.clone();
+            request.headers = mediator.headers.clone();
+            request.body = mediator.body.clone();
+            if mediator.is_navigation {
+                request.mode = NetTraitsRequestMode::Navigate;
+                request.destination = NetTraitsRequestDestination::Document;
+            }
+        }
+        r
+    }
 }
 
 fn net_request_from_global(global: &GlobalScope, url: ServoUrl) -> NetTraitsRequest {
@@ -601,6 +656,11 @@ impl RequestMethods for Request {
         DOMString::from_string(r.integrity_metadata.clone())
     }
 
+    // https://fetch.spec.whatwg.org/#dom-request-keepalive
+    fn Keepalive(&self) -> bool {
+        self.request.borrow().keep_alive
+    }
+
     // https://fetch.spec.whatwg.org/#dom-body-bodyused
     fn BodyUsed(&self) -> bool {
         self.body_used.get()
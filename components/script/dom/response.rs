@@ -23,7 +23,10 @@ use dom_struct::dom_struct;
 use http::header::HeaderMap as HyperHeaders;
 use hyper::StatusCode;
 use hyper_serde::Serde;
+use net_traits::cache_storage_thread::CachedResponse;
 use net_traits::response::ResponseBody as NetTraitsResponseBody;
+use net_traits::response::ResponseType as NetTraitsResponseType;
+use net_traits::NetworkError;
 use servo_url::ServoUrl;
 use std::cell::{Cell, Ref};
 use std::mem;
@@ -412,6 +415,55 @@ impl Response {
         *self.url.borrow_mut() = Some(final_url);
     }
 
+    /// The parts of this response that are kept in a cache, except for the body.
+    pub fn to_cached_response(&self) -> CachedResponse {
+        let response_type = match *self.response_type.borrow() {
+            DOMResponseType::Basic => NetTraitsResponseType::Basic,
+            DOMResponseType::Cors => NetTraitsResponseType::Cors,
+            DOMResponseType::Default => NetTraitsResponseType::Default,
+            DOMResponseType::Error => {
+                NetTraitsResponseType::Error(NetworkError::Internal("Error response".to_owned()))
+            },
+            DOMResponseType::Opaque => NetTraitsResponseType::Opaque,
+            DOMResponseType::Opaqueredirect => NetTraitsResponseType::OpaqueRedirect,
+        };
+        let (status, status_text) = self.raw_status.borrow().clone().unwrap_or((0, vec![]));
+        let mut url_list = self.url_list.borrow().clone();
+        if url_list.is_empty() {
+            url_list.extend(self.url.borrow().clone());
+        }
+        CachedResponse {
+            response_type,
+            url_list,
+            status,
+            status_text,
+            headers: self.Headers().get_header_pairs(),
+            body: vec![],
+        }
+    }
+
+    /// A response for an entry of a cache, its headers are immutable.
+    pub fn from_cached_response(global: &GlobalScope, cached: CachedResponse) -> DomRoot<Response> {
+        let r = Response::new(global);
+        r.set_type(match cached.response_type {
+            NetTraitsResponseType::Basic => DOMResponseType::Basic,
+            NetTraitsResponseType::Cors => DOMResponseType::Cors,
+            NetTraitsResponseType::Default => DOMResponseType::Default,
+            NetTraitsResponseType::Error(_) => DOMResponseType::Error,
+            NetTraitsResponseType::Opaque => DOMResponseType::Opaque,
+            NetTraitsResponseType::OpaqueRedirect => DOMResponseType::Opaqueredirect,
+        });
+        r.Headers().set_header_pairs(&cached.headers);
+        r.Headers().set_guard(Guard::Immutable);
+        *r.mime_type.borrow_mut() = r.Headers().extract_mime_type();
+        *r.status.borrow_mut() = StatusCode::from_u16(cached.status).ok();
+        r.set_raw_status(Some((cached.status, cached.status_text)));
+        *r.url.borrow_mut() = cached.url_list.last().cloned();
+        *r.url_list.borrow_mut() = cached.url_list;
+        r.finish(cached.body);
+        r
+    }
+
     #[allow(unrooted_must_root)]
     pub fn finish(&self, body: Vec<u8>) {
         *self.body.borrow_mut() = NetTraitsResponseBody::Done(body);
@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
 use crate::dom::bindings::codegen::Bindings::RTCIceCandidateBinding::RTCIceCandidateInit;
 use crate::dom::bindings::codegen::Bindings::RTCPeerConnectionBinding;
 use crate::dom::bindings::codegen::Bindings::RTCPeerConnectionBinding::RTCPeerConnectionMethods;
@@ -12,6 +13,10 @@ use crate::dom::bindings::codegen::Bindings::RTCPeerConnectionBinding::{
 use crate::dom::bindings::codegen::Bindings::RTCSessionDescriptionBinding::{
     RTCSdpType, RTCSessionDescriptionInit,
 };
+use crate::dom::bindings::codegen::Bindings::RTCStatsReportBinding::{
+    RTCPeerConnectionStats, RTCStats, RTCStatsType,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::UnionTypes::StringOrStringSequence;
 use crate::dom::bindings::error::Error;
 use crate::dom::bindings::error::Fallible;
@@ -20,6 +25,7 @@ use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
@@ -28,6 +34,7 @@ use crate::dom::promise::Promise;
 use crate::dom::rtcicecandidate::RTCIceCandidate;
 use crate::dom::rtcpeerconnectioniceevent::RTCPeerConnectionIceEvent;
 use crate::dom::rtcsessiondescription::RTCSessionDescription;
+use crate::dom::rtcstatsreport::RTCStatsReport;
 use crate::dom::window::Window;
 use crate::task::TaskCanceller;
 use crate::task_source::networking::NetworkingTaskSource;
@@ -409,6 +416,31 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
         p
     }
 
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-getstats
+    fn GetStats(&self) -> Rc<Promise> {
+        let global = self.global();
+        let p = Promise::new(&global);
+        if self.closed.get() {
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+
+        // FIXME: Gather the transport and media stats of the backend once it
+        // exposes them, only the peer connection itself is reported for now.
+        let timestamp = global.as_window().Performance().Now();
+        let report = RTCStatsReport::new(&global);
+        let stats = RTCPeerConnectionStats {
+            parent: RTCStats {
+                id: DOMString::from("RTCPeerConnection"),
+                timestamp,
+                type_: RTCStatsType::Peer_connection,
+            },
+        };
+        report.add(stats.parent.id.clone(), &stats);
+        p.resolve_native(&*report);
+        p
+    }
+
     // https://w3c.github.io/webrtc-pc/#legacy-interface-extensions
     fn AddStream(&self, stream: &MediaStream) {
         let mut tracks = stream.get_tracks();
@@ -0,0 +1,95 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::RTCStatsReportBinding::{self, RTCStatsReportMethods};
+use crate::dom::bindings::iterable::Iterable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSAutoCompartment, JSContext, JSObject};
+use js::jsval::UndefinedValue;
+use std::ptr::NonNull;
+
+/// <https://w3c.github.io/webrtc-pc/#rtcstatsreport-object>
+#[dom_struct]
+pub struct RTCStatsReport {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "mozjs"]
+    stats: DomRefCell<Vec<(DOMString, Box<Heap<*mut JSObject>>)>>,
+}
+
+impl RTCStatsReport {
+    fn new_inherited() -> RTCStatsReport {
+        RTCStatsReport {
+            reflector_: Reflector::new(),
+            stats: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<RTCStatsReport> {
+        reflect_dom_object(
+            Box::new(RTCStatsReport::new_inherited()),
+            global,
+            RTCStatsReportBinding::Wrap,
+        )
+    }
+
+    /// Add a stats dictionary to the report, keyed by its id.
+    #[allow(unsafe_code)]
+    pub fn add<T: ToJSValConvertible>(&self, id: DOMString, stats: &T) {
+        let cx = self.global().get_cx();
+        let _ac = JSAutoCompartment::new(cx, self.reflector().get_jsobject().get());
+        rooted!(in(cx) let mut value = UndefinedValue());
+        unsafe { stats.to_jsval(cx, value.handle_mut()) };
+        self.stats
+            .borrow_mut()
+            .push((id, Heap::boxed(value.to_object())));
+    }
+}
+
+impl RTCStatsReportMethods for RTCStatsReport {
+    /// <https://w3c.github.io/webrtc-pc/#rtcstatsreport-object>
+    fn Size(&self) -> u32 {
+        self.stats.borrow().len() as u32
+    }
+
+    #[allow(unsafe_code)]
+    /// <https://w3c.github.io/webrtc-pc/#rtcstatsreport-object>
+    unsafe fn Get(&self, _cx: *mut JSContext, id: DOMString) -> Option<NonNull<JSObject>> {
+        self.stats
+            .borrow()
+            .iter()
+            .find(|&&(ref stats_id, _)| *stats_id == id)
+            .and_then(|&(_, ref stats)| NonNull::new(stats.get()))
+    }
+
+    /// <https://w3c.github.io/webrtc-pc/#rtcstatsreport-object>
+    fn Has(&self, id: DOMString) -> bool {
+        self.stats
+            .borrow()
+            .iter()
+            .any(|&(ref stats_id, _)| *stats_id == id)
+    }
+}
+
+impl Iterable for RTCStatsReport {
+    type Key = DOMString;
+    type Value = *mut JSObject;
+
+    fn get_iterable_length(&self) -> u32 {
+        self.stats.borrow().len() as u32
+    }
+
+    fn get_value_at_index(&self, n: u32) -> *mut JSObject {
+        self.stats.borrow()[n as usize].1.get()
+    }
+
+    fn get_key_at_index(&self, n: u32) -> DOMString {
+        self.stats.borrow()[n as usize].0.clone()
+    }
+}
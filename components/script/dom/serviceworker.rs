@@ -20,7 +20,7 @@ use crate::task::TaskOnce;
 use dom_struct::dom_struct;
 use js::jsapi::JSContext;
 use js::rust::HandleValue;
-use script_traits::{DOMMessage, ScriptMsg};
+use script_traits::{DOMMessage, ScriptMsg, ServiceWorkerId, ServiceWorkerLifecycleState};
 use servo_url::ServoUrl;
 use std::cell::Cell;
 
@@ -32,17 +32,18 @@ pub struct ServiceWorker {
     script_url: DomRefCell<String>,
     scope_url: ServoUrl,
     state: Cell<ServiceWorkerState>,
-    skip_waiting: Cell<bool>,
+    /// The id of this worker in the service worker manager
+    id: ServiceWorkerId,
 }
 
 impl ServiceWorker {
-    fn new_inherited(script_url: &str, skip_waiting: bool, scope_url: ServoUrl) -> ServiceWorker {
+    fn new_inherited(script_url: &str, id: ServiceWorkerId, scope_url: ServoUrl) -> ServiceWorker {
         ServiceWorker {
             eventtarget: EventTarget::new_inherited(),
             script_url: DomRefCell::new(String::from(script_url)),
             state: Cell::new(ServiceWorkerState::Installing),
             scope_url: scope_url,
-            skip_waiting: Cell::new(skip_waiting),
+            id: id,
         }
     }
 
@@ -50,12 +51,12 @@ impl ServiceWorker {
         global: &GlobalScope,
         script_url: ServoUrl,
         scope_url: ServoUrl,
-        skip_waiting: bool,
+        id: ServiceWorkerId,
     ) -> DomRoot<ServiceWorker> {
         reflect_dom_object(
             Box::new(ServiceWorker::new_inherited(
                 script_url.as_str(),
-                skip_waiting,
+                id,
                 scope_url,
             )),
             global,
@@ -63,17 +64,35 @@ impl ServiceWorker {
         )
     }
 
+    pub fn id(&self) -> ServiceWorkerId {
+        self.id
+    }
+
     pub fn dispatch_simple_error(address: TrustedServiceWorkerAddress) {
         let service_worker = address.root();
         service_worker.upcast().fire_event(atom!("error"));
     }
 
+    // https://w3c.github.io/ServiceWorker/#update-state-algorithm
     pub fn set_transition_state(&self, state: ServiceWorkerState) {
+        if self.state.get() == state {
+            return;
+        }
         self.state.set(state);
         self.upcast::<EventTarget>()
             .fire_event(atom!("statechange"));
     }
 
+    pub fn set_lifecycle_state(&self, state: ServiceWorkerLifecycleState) {
+        self.set_transition_state(match state {
+            ServiceWorkerLifecycleState::Installing => ServiceWorkerState::Installing,
+            ServiceWorkerLifecycleState::Installed => ServiceWorkerState::Installed,
+            ServiceWorkerLifecycleState::Activating => ServiceWorkerState::Activating,
+            ServiceWorkerLifecycleState::Activated => ServiceWorkerState::Activated,
+            ServiceWorkerLifecycleState::Redundant => ServiceWorkerState::Redundant,
+        });
+    }
+
     pub fn get_script_url(&self) -> ServoUrl {
         ServoUrl::parse(&self.script_url.borrow().clone()).unwrap()
     }
@@ -7,6 +7,7 @@ use crate::dom::bindings::codegen::Bindings::ServiceWorkerContainerBinding::{
     ServiceWorkerContainerMethods, Wrap,
 };
 use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::USVString;
@@ -40,9 +41,32 @@ impl ServiceWorkerContainer {
     #[allow(unrooted_must_root)]
     pub fn new(global: &GlobalScope) -> DomRoot<ServiceWorkerContainer> {
         let client = Client::new(&global.as_window());
+        // A page loaded while a registration of its scope was active is controlled by it
+        if let Some(controller) = ScriptThread::controller_for(&client.creation_url()) {
+            client.set_controller(&*controller);
+        }
         let container = ServiceWorkerContainer::new_inherited(&*client);
         reflect_dom_object(Box::new(container), global, Wrap)
     }
+
+    pub fn client(&self) -> DomRoot<Client> {
+        DomRoot::from_ref(&*self.client)
+    }
+
+    /// Makes the given worker control the client of this container.
+    pub fn set_controller(&self, worker: &ServiceWorker) {
+        if self
+            .client
+            .get_controller()
+            .map_or(false, |controller| controller.id() == worker.id())
+        {
+            return;
+        }
+        self.client.set_controller(worker);
+        // https://w3c.github.io/ServiceWorker/#notify-controller-change-algorithm
+        self.upcast::<EventTarget>()
+            .fire_event(atom!("controllerchange"));
+    }
 }
 
 impl ServiceWorkerContainerMethods for ServiceWorkerContainer {
@@ -128,4 +152,11 @@ impl ServiceWorkerContainerMethods for ServiceWorkerContainer {
         ScriptThread::schedule_job(job);
         promise
     }
+
+    // https://w3c.github.io/ServiceWorker/#dom-serviceworkercontainer-oncontrollerchange
+    event_handler!(
+        controllerchange,
+        GetOncontrollerchange,
+        SetOncontrollerchange
+    );
 }
@@ -8,36 +8,51 @@ use crate::dom::abstractworkerglobalscope::{run_worker_event_loop, WorkerEventLo
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerGlobalScopeBinding;
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerGlobalScopeBinding::ServiceWorkerGlobalScopeMethods;
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::TrustedPromise;
 use crate::dom::bindings::reflector::DomObject;
-use crate::dom::bindings::root::{DomRoot, RootCollection, ThreadLocalStackRoots};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom, RootCollection, ThreadLocalStackRoots};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::cachestorage::CacheStorage;
+use crate::dom::client::client_id;
+use crate::dom::clients::Clients;
 use crate::dom::dedicatedworkerglobalscope::AutoWorkerReset;
 use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::extendableevent::ExtendableEvent;
 use crate::dom::extendablemessageevent::ExtendableMessageEvent;
+use crate::dom::fetchevent::FetchEvent;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::notification::Notification;
+use crate::dom::notificationevent::NotificationEvent;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::request::Request;
+use crate::dom::serviceworkerregistration::ServiceWorkerRegistration;
 use crate::dom::worker::TrustedWorkerAddress;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::script_runtime::{new_rt_and_cx, CommonScriptMsg, Runtime, ScriptChan};
 use crate::task_queue::{QueuedTask, QueuedTaskConversion, TaskQueue};
-use crate::task_source::TaskSourceName;
+use crate::task_source::{TaskSource, TaskSourceName};
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::DevtoolScriptControlMsg;
 use dom_struct::dom_struct;
+use embedder_traits::Notification as EmbedderNotification;
+use embedder_traits::NotificationEvent as EmbedderNotificationEvent;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use js::jsapi::{JSAutoCompartment, JSContext, JS_AddInterruptCallback};
 use js::jsval::UndefinedValue;
+use js::rust::HandleValue;
 use msg::constellation_msg::PipelineId;
-use net_traits::request::{CredentialsMode, Destination, RequestInit};
-use net_traits::{load_whole_resource, CustomResponseMediator, IpcSend};
-use script_traits::{
-    ScopeThings, ServiceWorkerMsg, TimerEvent, WorkerGlobalScopeInit, WorkerScriptLoadOrigin,
-};
+use net_traits::CustomResponseMediator;
+use script_traits::WorkerGlobalScopeInit;
+use script_traits::{ScopeThings, ServiceWorkerId, ServiceWorkerLifecycleState};
+use script_traits::{ServiceWorkerMsg, TimerEvent};
 use servo_config::prefs::PREFS;
 use servo_rand::random;
 use servo_url::ServoUrl;
+use std::cell::Cell;
+use std::rc::Rc;
 use std::thread;
 use std::time::Duration;
 use style::thread_state::{self, ThreadState};
@@ -48,8 +63,16 @@ pub enum ServiceWorkerScriptMsg {
     CommonWorker(WorkerScriptMsg),
     /// Message to request a custom response by the service worker
     Response(CustomResponseMediator),
+    /// Message to fire the install event
+    Install,
+    /// Message to fire the activate event
+    Activate,
     /// Wake-up call from the task queue.
     WakeUp,
+    /// The network went offline (`false`) or came back online (`true`).
+    NetworkStateChanged(bool),
+    /// A persistent notification of the registration was clicked or closed.
+    NotificationEvent(EmbedderNotification, EmbedderNotificationEvent),
 }
 
 impl QueuedTaskConversion for ServiceWorkerScriptMsg {
@@ -150,6 +173,12 @@ pub struct ServiceWorkerGlobalScope {
     #[ignore_malloc_size_of = "Defined in std"]
     swmanager_sender: IpcSender<ServiceWorkerMsg>,
     scope_url: ServoUrl,
+    worker_id: ServiceWorkerId,
+    caches: MutNullableDom<CacheStorage>,
+    clients: MutNullableDom<Clients>,
+    registration: MutNullableDom<ServiceWorkerRegistration>,
+    /// Whether this worker is the active worker of its registration
+    active: Cell<bool>,
 }
 
 impl WorkerEventLoopMethods for ServiceWorkerGlobalScope {
@@ -198,6 +227,8 @@ impl ServiceWorkerGlobalScope {
         timer_event_port: Receiver<()>,
         swmanager_sender: IpcSender<ServiceWorkerMsg>,
         scope_url: ServoUrl,
+        worker_id: ServiceWorkerId,
+        active: bool,
     ) -> ServiceWorkerGlobalScope {
         ServiceWorkerGlobalScope {
             workerglobalscope: WorkerGlobalScope::new_inherited(
@@ -213,6 +244,11 @@ impl ServiceWorkerGlobalScope {
             own_sender: own_sender,
             swmanager_sender: swmanager_sender,
             scope_url: scope_url,
+            worker_id: worker_id,
+            caches: Default::default(),
+            clients: Default::default(),
+            registration: Default::default(),
+            active: Cell::new(active),
         }
     }
 
@@ -228,6 +264,8 @@ impl ServiceWorkerGlobalScope {
         timer_event_port: Receiver<()>,
         swmanager_sender: IpcSender<ServiceWorkerMsg>,
         scope_url: ServoUrl,
+        worker_id: ServiceWorkerId,
+        active: bool,
     ) -> DomRoot<ServiceWorkerGlobalScope> {
         let cx = runtime.cx();
         let scope = Box::new(ServiceWorkerGlobalScope::new_inherited(
@@ -241,6 +279,8 @@ impl ServiceWorkerGlobalScope {
             timer_event_port,
             swmanager_sender,
             scope_url,
+            worker_id,
+            active,
         ));
         unsafe { ServiceWorkerGlobalScopeBinding::Wrap(cx, scope) }
     }
@@ -249,61 +289,25 @@ impl ServiceWorkerGlobalScope {
     // https://html.spec.whatwg.org/multipage/#run-a-worker
     pub fn run_serviceworker_scope(
         scope_things: ScopeThings,
+        worker_id: ServiceWorkerId,
+        active: bool,
+        script_url: ServoUrl,
+        source: String,
         own_sender: Sender<ServiceWorkerScriptMsg>,
         receiver: Receiver<ServiceWorkerScriptMsg>,
         devtools_receiver: IpcReceiver<DevtoolScriptControlMsg>,
         swmanager_sender: IpcSender<ServiceWorkerMsg>,
         scope_url: ServoUrl,
     ) {
-        let ScopeThings {
-            script_url,
-            init,
-            worker_load_origin,
-            ..
-        } = scope_things;
-
-        let serialized_worker_url = script_url.to_string();
-        let origin = GlobalScope::current()
-            .expect("No current global object")
-            .origin()
-            .immutable()
-            .clone();
+        let ScopeThings { init, .. } = scope_things;
+
         thread::Builder::new()
-            .name(format!("ServiceWorker for {}", serialized_worker_url))
+            .name(format!("ServiceWorker for {}", script_url))
             .spawn(move || {
                 thread_state::initialize(ThreadState::SCRIPT | ThreadState::IN_WORKER);
                 let roots = RootCollection::new();
                 let _stack_roots = ThreadLocalStackRoots::new(&roots);
 
-                let WorkerScriptLoadOrigin {
-                    referrer_url,
-                    referrer_policy,
-                    pipeline_id,
-                } = worker_load_origin;
-
-                let request = RequestInit {
-                    url: script_url.clone(),
-                    destination: Destination::ServiceWorker,
-                    credentials_mode: CredentialsMode::Include,
-                    use_url_credentials: true,
-                    pipeline_id: pipeline_id,
-                    referrer_url: referrer_url,
-                    referrer_policy: referrer_policy,
-                    origin,
-                    ..RequestInit::default()
-                };
-
-                let (url, source) =
-                    match load_whole_resource(request, &init.resource_threads.sender()) {
-                        Err(_) => {
-                            println!("error loading script {}", serialized_worker_url);
-                            return;
-                        },
-                        Ok((metadata, bytes)) => {
-                            (metadata.final_url, String::from_utf8(bytes).unwrap())
-                        },
-                    };
-
                 let runtime = new_rt_and_cx();
 
                 let (devtools_mpsc_chan, devtools_mpsc_port) = unbounded();
@@ -314,7 +318,7 @@ impl ServiceWorkerGlobalScope {
                 let (timer_chan, timer_port) = unbounded();
                 let global = ServiceWorkerGlobalScope::new(
                     init,
-                    url,
+                    script_url,
                     devtools_mpsc_port,
                     runtime,
                     own_sender,
@@ -323,6 +327,8 @@ impl ServiceWorkerGlobalScope {
                     timer_port,
                     swmanager_sender,
                     scope_url,
+                    worker_id,
+                    active,
                 );
                 let scope = global.upcast::<WorkerGlobalScope>();
 
@@ -344,8 +350,6 @@ impl ServiceWorkerGlobalScope {
                         let _ = timer_chan.send(());
                     })
                     .expect("Thread spawning failed");
-
-                global.dispatch_activate();
                 let reporter_name = format!("service-worker-reporter-{}", random::<u64>());
                 scope
                     .upcast::<GlobalScope>()
@@ -393,7 +397,7 @@ impl ServiceWorkerGlobalScope {
             MixedMessage::FromTimeoutThread(_) => {
                 let _ = self
                     .swmanager_sender
-                    .send(ServiceWorkerMsg::Timeout(self.scope_url.clone()));
+                    .send(ServiceWorkerMsg::Timeout(self.worker_id));
                 false
             },
         }
@@ -416,26 +420,161 @@ impl ServiceWorkerGlobalScope {
                 self.upcast::<WorkerGlobalScope>().process_event(msg);
             },
             Response(mediator) => {
-                // TODO XXXcreativcoder This will eventually use a FetchEvent interface to fire event
-                // when we have the Request and Response dom api's implemented
-                // https://w3c.github.io/ServiceWorker/#fetchevent-interface
-                self.upcast::<EventTarget>().fire_event(atom!("fetch"));
-                let _ = mediator.response_chan.send(None);
+                // https://w3c.github.io/ServiceWorker/#handle-fetch Steps 18-24
+                let scope = self.upcast::<WorkerGlobalScope>();
+                let _ac =
+                    JSAutoCompartment::new(scope.get_cx(), scope.reflector().get_jsobject().get());
+                let request = Request::for_fetch_event(self.upcast(), &mediator);
+                // A navigation creates a new client, other requests come from an
+                // existing one.
+                let client_id = mediator.pipeline_id.map_or_else(DOMString::new, client_id);
+                let (client_id, resulting_client_id) = if mediator.is_navigation {
+                    (DOMString::new(), client_id)
+                } else {
+                    (client_id, DOMString::new())
+                };
+                let event = FetchEvent::new(
+                    self.upcast(),
+                    atom!("fetch"),
+                    false,
+                    true,
+                    &request,
+                    client_id,
+                    resulting_client_id,
+                );
+                event.upcast::<Event>().fire(self.upcast());
+                event.send_response(mediator.response_chan);
+            },
+            Install => self.dispatch_lifecycle_event(LifecycleEvent::Install),
+            Activate => {
+                self.set_active();
+                self.dispatch_lifecycle_event(LifecycleEvent::Activate);
             },
             WakeUp => {},
+            NetworkStateChanged(online) => {
+                // https://html.spec.whatwg.org/multipage/#dom-navigator-online
+                let name = if online {
+                    atom!("online")
+                } else {
+                    atom!("offline")
+                };
+                self.upcast::<EventTarget>().fire_event(name);
+            },
+            ServiceWorkerScriptMsg::NotificationEvent(data, event) => {
+                self.dispatch_notification_event(data, event)
+            },
+        }
+    }
+
+    /// Records that this worker became the active worker of its registration.
+    fn set_active(&self) {
+        self.active.set(true);
+        if let Some(registration) = self.registration.get() {
+            self.activate_in(&registration);
         }
     }
 
+    /// Makes this worker the active worker of the `registration` object of this global.
+    fn activate_in(&self, registration: &ServiceWorkerRegistration) {
+        let script_url = self.upcast::<WorkerGlobalScope>().get_url().clone();
+        registration.handle_state_change(
+            self.worker_id,
+            script_url,
+            ServiceWorkerLifecycleState::Activating,
+        );
+    }
+
+    // https://notifications.spec.whatwg.org/#activating-a-notification
+    // https://notifications.spec.whatwg.org/#close-steps
+    fn dispatch_notification_event(
+        &self,
+        data: EmbedderNotification,
+        event: EmbedderNotificationEvent,
+    ) {
+        let type_ = match event {
+            EmbedderNotificationEvent::Clicked => atom!("notificationclick"),
+            EmbedderNotificationEvent::Closed => atom!("notificationclose"),
+            _ => return,
+        };
+        let scope = self.upcast::<WorkerGlobalScope>();
+        let _ac = JSAutoCompartment::new(scope.get_cx(), scope.reflector().get_jsobject().get());
+        let notification = Notification::new(self.upcast(), data);
+        let event = NotificationEvent::new(
+            self.upcast(),
+            type_,
+            false,
+            false,
+            &notification,
+            DOMString::new(),
+        );
+        event.upcast::<Event>().fire(self.upcast());
+    }
+
     pub fn script_chan(&self) -> Box<dyn ScriptChan + Send> {
         Box::new(ServiceWorkerChan {
             sender: self.own_sender.clone(),
         })
     }
 
-    fn dispatch_activate(&self) {
-        let event = ExtendableEvent::new(self, atom!("activate"), false, false);
-        let event = (&*event).upcast::<Event>();
-        self.upcast::<EventTarget>().dispatch_event(event);
+    pub fn worker_id(&self) -> ServiceWorkerId {
+        self.worker_id
+    }
+
+    pub fn swmanager_sender(&self) -> IpcSender<ServiceWorkerMsg> {
+        self.swmanager_sender.clone()
+    }
+
+    /// Fires the install or activate event, and tells the service worker manager once the
+    /// promises extending it settled.
+    // https://w3c.github.io/ServiceWorker/#installation-algorithm Step 11
+    // https://w3c.github.io/ServiceWorker/#activation-algorithm Step 12
+    fn dispatch_lifecycle_event(&self, lifecycle_event: LifecycleEvent) {
+        let scope = self.upcast::<WorkerGlobalScope>();
+        let _ac = JSAutoCompartment::new(scope.get_cx(), scope.reflector().get_jsobject().get());
+        let type_ = match lifecycle_event {
+            LifecycleEvent::Install => atom!("install"),
+            LifecycleEvent::Activate => atom!("activate"),
+        };
+        let event = ExtendableEvent::new(self, type_, false, false);
+        event.upcast::<Event>().fire(self.upcast::<EventTarget>());
+        let handler = PromiseNativeHandler::new(
+            self.upcast(),
+            Some(Box::new(LifecycleEventHandler {
+                swmanager_sender: self.swmanager_sender.clone(),
+                worker_id: self.worker_id,
+                event: lifecycle_event,
+            })),
+            None,
+        );
+        event
+            .extended_lifetime_promise()
+            .append_native_handler(&handler);
+    }
+}
+
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
+enum LifecycleEvent {
+    Install,
+    Activate,
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+struct LifecycleEventHandler {
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    swmanager_sender: IpcSender<ServiceWorkerMsg>,
+    worker_id: ServiceWorkerId,
+    event: LifecycleEvent,
+}
+
+impl Callback for LifecycleEventHandler {
+    fn callback(&self, _cx: *mut JSContext, v: HandleValue) {
+        let msg = match self.event {
+            LifecycleEvent::Install => {
+                ServiceWorkerMsg::InstallFinished(self.worker_id, v.get().to_boolean())
+            },
+            LifecycleEvent::Activate => ServiceWorkerMsg::ActivateFinished(self.worker_id),
+        };
+        let _ = self.swmanager_sender.send(msg);
     }
 }
 
@@ -455,4 +594,81 @@ impl ServiceWorkerGlobalScopeMethods for ServiceWorkerGlobalScope {
 
     // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-onmessageerror
     event_handler!(messageerror, GetOnmessageerror, SetOnmessageerror);
+
+    // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-oninstall
+    event_handler!(install, GetOninstall, SetOninstall);
+
+    // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-onactivate
+    event_handler!(activate, GetOnactivate, SetOnactivate);
+
+    // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-onfetch
+    event_handler!(fetch, GetOnfetch, SetOnfetch);
+
+    // https://notifications.spec.whatwg.org/#dom-serviceworkerglobalscope-onnotificationclick
+    event_handler!(
+        notificationclick,
+        GetOnnotificationclick,
+        SetOnnotificationclick
+    );
+
+    // https://notifications.spec.whatwg.org/#dom-serviceworkerglobalscope-onnotificationclose
+    event_handler!(
+        notificationclose,
+        GetOnnotificationclose,
+        SetOnnotificationclose
+    );
+
+    // https://w3c.github.io/ServiceWorker/#dom-windoworworkerglobalscope-caches
+    fn Caches(&self) -> DomRoot<CacheStorage> {
+        self.caches.or_init(|| CacheStorage::new(self.upcast()))
+    }
+
+    // https://w3c.github.io/ServiceWorker/#service-worker-global-scope-clients
+    fn Clients(&self) -> DomRoot<Clients> {
+        self.clients.or_init(|| Clients::new(self))
+    }
+
+    // https://w3c.github.io/ServiceWorker/#service-worker-global-scope-registration
+    fn Registration(&self) -> DomRoot<ServiceWorkerRegistration> {
+        self.registration.or_init(|| {
+            let registration =
+                ServiceWorkerRegistration::new(self.upcast(), self.scope_url.clone());
+            if self.active.get() {
+                self.activate_in(&registration);
+            }
+            registration
+        })
+    }
+
+    // https://w3c.github.io/ServiceWorker/#service-worker-global-scope-skipwaiting
+    fn SkipWaiting(&self) -> Rc<Promise> {
+        // Step 1
+        let promise = Promise::new(self.upcast());
+        let global = self.upcast::<GlobalScope>();
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let mut trusted = Some(TrustedPromise::new(promise.clone()));
+        let (sender, receiver) = ipc::channel().unwrap();
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |_| {
+                let promise = match trusted.take() {
+                    Some(promise) => promise,
+                    None => return,
+                };
+                // Step 2.3
+                let _ = task_source.queue_with_canceller(
+                    task!(resolve_skip_waiting: move || {
+                        promise.root().resolve_native(&());
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        // Step 2.1-2.2
+        let _ = self
+            .swmanager_sender
+            .send(ServiceWorkerMsg::SkipWaiting(self.worker_id, sender));
+        promise
+    }
 }
@@ -3,30 +3,42 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
-use crate::dom::bindings::codegen::Bindings::ServiceWorkerBinding::ServiceWorkerState;
+use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
+use crate::dom::bindings::codegen::Bindings::NotificationBinding::NotificationOptions;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionState;
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerRegistrationBinding::ServiceWorkerUpdateViaCache;
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerRegistrationBinding::{
     ServiceWorkerRegistrationMethods, Wrap,
 };
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
-use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
-use crate::dom::bindings::str::{ByteString, USVString};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::{ByteString, DOMString, USVString};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::navigationpreloadmanager::NavigationPreloadManager;
+use crate::dom::notification::{self, show_persistent_notification};
+use crate::dom::promise::Promise;
 use crate::dom::serviceworker::ServiceWorker;
+use crate::dom::window::Window;
 use crate::dom::workerglobalscope::prepare_workerscope_init;
+use crate::script_thread::ScriptThread;
+use crate::serviceworkerjob::{Job, JobType};
 use dom_struct::dom_struct;
-use script_traits::{ScopeThings, WorkerScriptLoadOrigin};
+use script_traits::WorkerScriptLoadOrigin;
+use script_traits::{ScopeThings, ServiceWorkerId, ServiceWorkerLifecycleState};
 use servo_url::ServoUrl;
 use std::cell::Cell;
+use std::rc::Rc;
 
 #[dom_struct]
 pub struct ServiceWorkerRegistration {
     eventtarget: EventTarget,
-    active: Option<Dom<ServiceWorker>>,
-    installing: Option<Dom<ServiceWorker>>,
-    waiting: Option<Dom<ServiceWorker>>,
+    active: MutNullableDom<ServiceWorker>,
+    installing: MutNullableDom<ServiceWorker>,
+    waiting: MutNullableDom<ServiceWorker>,
     navigation_preload: MutNullableDom<NavigationPreloadManager>,
     scope: ServoUrl,
     navigation_preload_enabled: Cell<bool>,
@@ -36,12 +48,12 @@ pub struct ServiceWorkerRegistration {
 }
 
 impl ServiceWorkerRegistration {
-    fn new_inherited(active_sw: &ServiceWorker, scope: ServoUrl) -> ServiceWorkerRegistration {
+    fn new_inherited(scope: ServoUrl) -> ServiceWorkerRegistration {
         ServiceWorkerRegistration {
             eventtarget: EventTarget::new_inherited(),
-            active: Some(Dom::from_ref(active_sw)),
-            installing: None,
-            waiting: None,
+            active: Default::default(),
+            installing: Default::default(),
+            waiting: Default::default(),
             navigation_preload: MutNullableDom::new(None),
             scope: scope,
             navigation_preload_enabled: Cell::new(false),
@@ -51,32 +63,92 @@ impl ServiceWorkerRegistration {
         }
     }
 
-    #[allow(unrooted_must_root)]
-    pub fn new(
-        global: &GlobalScope,
-        script_url: &ServoUrl,
-        scope: ServoUrl,
-    ) -> DomRoot<ServiceWorkerRegistration> {
-        let active_worker =
-            ServiceWorker::install_serviceworker(global, script_url.clone(), scope.clone(), true);
-        active_worker.set_transition_state(ServiceWorkerState::Installed);
-
+    pub fn new(global: &GlobalScope, scope: ServoUrl) -> DomRoot<ServiceWorkerRegistration> {
         reflect_dom_object(
-            Box::new(ServiceWorkerRegistration::new_inherited(
-                &*active_worker,
-                scope,
-            )),
+            Box::new(ServiceWorkerRegistration::new_inherited(scope)),
             global,
             Wrap,
         )
     }
 
-    pub fn active(&self) -> Option<&ServiceWorker> {
-        self.active.as_ref().map(|sw| &**sw)
+    pub fn active(&self) -> Option<DomRoot<ServiceWorker>> {
+        self.active.get()
+    }
+
+    pub fn scope(&self) -> ServoUrl {
+        self.scope.clone()
+    }
+
+    /// The worker of this registration with the given id, created if this registration
+    /// does not know about it yet.
+    pub fn worker_for(&self, id: ServiceWorkerId, script_url: ServoUrl) -> DomRoot<ServiceWorker> {
+        let known = [&self.installing, &self.waiting, &self.active]
+            .iter()
+            .filter_map(|slot| slot.get())
+            .find(|worker| worker.id() == id);
+        known.unwrap_or_else(|| {
+            ServiceWorker::install_serviceworker(&self.global(), script_url, self.scope(), id)
+        })
+    }
+
+    // https://w3c.github.io/ServiceWorker/#update-registration-state-algorithm
+    pub fn set_installing(&self, worker: Option<&ServiceWorker>) {
+        let changed = self.installing.get().map(|w| w.id()) != worker.map(|w| w.id());
+        self.installing.set(worker);
+        // https://w3c.github.io/ServiceWorker/#installation-algorithm Step 6
+        if changed && worker.is_some() {
+            self.upcast::<EventTarget>()
+                .fire_event(atom!("updatefound"));
+        }
+    }
+
+    pub fn set_waiting(&self, worker: Option<&ServiceWorker>) {
+        self.waiting.set(worker);
+    }
+
+    pub fn set_active(&self, worker: Option<&ServiceWorker>) {
+        self.active.set(worker);
     }
 
-    pub fn get_installed(&self) -> &ServiceWorker {
-        self.active.as_ref().unwrap()
+    /// Moves the worker with the given id to the slot matching its new state, then updates
+    /// its state.
+    pub fn handle_state_change(
+        &self,
+        id: ServiceWorkerId,
+        script_url: ServoUrl,
+        state: ServiceWorkerLifecycleState,
+    ) {
+        let worker = self.worker_for(id, script_url);
+        let holds =
+            |slot: &MutNullableDom<ServiceWorker>| slot.get().map_or(false, |w| w.id() == id);
+        match state {
+            ServiceWorkerLifecycleState::Installing => self.set_installing(Some(&*worker)),
+            ServiceWorkerLifecycleState::Installed => {
+                if holds(&self.installing) {
+                    self.set_installing(None);
+                }
+                self.set_waiting(Some(&*worker));
+            },
+            ServiceWorkerLifecycleState::Activating => {
+                if holds(&self.waiting) {
+                    self.set_waiting(None);
+                }
+                self.set_active(Some(&*worker));
+            },
+            ServiceWorkerLifecycleState::Activated => {},
+            ServiceWorkerLifecycleState::Redundant => {
+                if holds(&self.installing) {
+                    self.set_installing(None);
+                }
+                if holds(&self.waiting) {
+                    self.set_waiting(None);
+                }
+                if holds(&self.active) {
+                    self.set_active(None);
+                }
+            },
+        }
+        worker.set_lifecycle_state(state);
     }
 
     pub fn get_navigation_preload_header_value(&self) -> Option<ByteString> {
@@ -125,13 +197,10 @@ impl ServiceWorkerRegistration {
 
     // https://w3c.github.io/ServiceWorker/#get-newest-worker-algorithm
     pub fn get_newest_worker(&self) -> Option<DomRoot<ServiceWorker>> {
-        if self.installing.as_ref().is_some() {
-            self.installing.as_ref().map(|sw| DomRoot::from_ref(&**sw))
-        } else if self.waiting.as_ref().is_some() {
-            self.waiting.as_ref().map(|sw| DomRoot::from_ref(&**sw))
-        } else {
-            self.active.as_ref().map(|sw| DomRoot::from_ref(&**sw))
-        }
+        self.installing
+            .get()
+            .or_else(|| self.waiting.get())
+            .or_else(|| self.active.get())
     }
 }
 
@@ -155,17 +224,17 @@ pub fn longest_prefix_match(stored_scope: &ServoUrl, potential_match: &ServoUrl)
 impl ServiceWorkerRegistrationMethods for ServiceWorkerRegistration {
     // https://w3c.github.io/ServiceWorker/#service-worker-registration-installing-attribute
     fn GetInstalling(&self) -> Option<DomRoot<ServiceWorker>> {
-        self.installing.as_ref().map(|sw| DomRoot::from_ref(&**sw))
+        self.installing.get()
     }
 
     // https://w3c.github.io/ServiceWorker/#service-worker-registration-active-attribute
     fn GetActive(&self) -> Option<DomRoot<ServiceWorker>> {
-        self.active.as_ref().map(|sw| DomRoot::from_ref(&**sw))
+        self.active.get()
     }
 
     // https://w3c.github.io/ServiceWorker/#service-worker-registration-waiting-attribute
     fn GetWaiting(&self) -> Option<DomRoot<ServiceWorker>> {
-        self.waiting.as_ref().map(|sw| DomRoot::from_ref(&**sw))
+        self.waiting.get()
     }
 
     // https://w3c.github.io/ServiceWorker/#service-worker-registration-scope-attribute
@@ -183,4 +252,74 @@ impl ServiceWorkerRegistrationMethods for ServiceWorkerRegistration {
         self.navigation_preload
             .or_init(|| NavigationPreloadManager::new(&self.global(), &self))
     }
+
+    #[allow(unrooted_must_root)] // Job is unrooted
+                                 // https://w3c.github.io/ServiceWorker/#service-worker-registration-update
+    fn Update(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        // Step 1-2
+        let newest_worker = match self.get_newest_worker() {
+            Some(worker) => worker,
+            None => {
+                promise.reject_error(Error::InvalidState);
+                return promise;
+            },
+        };
+        // Update jobs are scheduled by the client of the window, workers can not run them yet
+        let window = match global.downcast::<Window>() {
+            Some(window) => window,
+            None => {
+                promise.reject_error(Error::NotSupported);
+                return promise;
+            },
+        };
+        let container = window.Navigator().ServiceWorker();
+        // Step 3-4
+        let job = Job::create_job(
+            JobType::Update,
+            self.scope(),
+            newest_worker.get_script_url(),
+            promise.clone(),
+            &*container.client(),
+        );
+        // Job is unrooted here, do not do anything other than immediately scheduling
+        ScriptThread::schedule_job(job);
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#service-worker-registration-onupdatefound
+    event_handler!(updatefound, GetOnupdatefound, SetOnupdatefound);
+
+    // https://notifications.spec.whatwg.org/#dom-serviceworkerregistration-shownotification
+    fn ShowNotification(&self, title: DOMString, options: &NotificationOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        // Step 3
+        if self.active.get().is_none() {
+            promise.reject_error(Error::Type(
+                "The registration has no active worker".to_owned(),
+            ));
+            return promise;
+        }
+        // Step 4-5
+        let data = match notification::notification_data(&global, title, options) {
+            Ok(data) => data,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        // Step 6
+        if notification::permission_state(&global) != PermissionState::Granted {
+            promise.reject_error(Error::Type(
+                "The permission to show notifications was not granted".to_owned(),
+            ));
+            return promise;
+        }
+        // Step 7-8
+        show_persistent_notification(&global, self.scope.clone(), data);
+        promise.resolve_native(&());
+        promise
+    }
 }
@@ -0,0 +1,407 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::MediaSourceBinding::EndOfStreamError;
+use crate::dom::bindings::codegen::Bindings::SourceBufferBinding::{
+    self, AppendMode, SourceBufferMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::mediasource::MediaSource;
+use crate::dom::timeranges::{TimeRanges, TimeRangesContainer};
+use crate::dom::window::Window;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::f64;
+
+/// <https://w3c.github.io/media-source/#sourcebuffer>
+///
+/// There is no segment parser on the DOM side: appended bytes go straight to
+/// the player of the media element and the buffered ranges are the ones the
+/// player reports.
+#[dom_struct]
+pub struct SourceBuffer {
+    eventtarget: EventTarget,
+    /// The MediaSource this buffer was created by, or None once it was removed
+    /// from its `sourceBuffers` list.
+    parent_media_source: MutNullableDom<MediaSource>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-mode>
+    mode: Cell<AppendMode>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-updating>
+    updating: Cell<bool>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-timestampoffset>
+    timestamp_offset: Cell<f64>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowstart>
+    append_window_start: Cell<f64>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowend>
+    append_window_end: Cell<f64>,
+    /// Whether the range removal algorithm is running.
+    removing: Cell<bool>,
+    /// Ranges removed by `remove()`. The player cannot evict data it was pushed,
+    /// so these are hidden from the buffered ranges instead, until a segment
+    /// covering them is appended again.
+    removed_ranges: DomRefCell<TimeRangesContainer>,
+    /// Incremented whenever a pending append or removal is aborted.
+    generation_id: Cell<u32>,
+}
+
+impl SourceBuffer {
+    fn new_inherited(parent_media_source: &MediaSource) -> SourceBuffer {
+        SourceBuffer {
+            eventtarget: EventTarget::new_inherited(),
+            parent_media_source: MutNullableDom::new(Some(parent_media_source)),
+            mode: Cell::new(AppendMode::Segments),
+            updating: Cell::new(false),
+            timestamp_offset: Cell::new(0.),
+            append_window_start: Cell::new(0.),
+            append_window_end: Cell::new(f64::INFINITY),
+            removing: Cell::new(false),
+            removed_ranges: DomRefCell::new(TimeRangesContainer::new()),
+            generation_id: Cell::new(0),
+        }
+    }
+
+    pub fn new(window: &Window, parent_media_source: &MediaSource) -> DomRoot<SourceBuffer> {
+        reflect_dom_object(
+            Box::new(SourceBuffer::new_inherited(parent_media_source)),
+            window,
+            SourceBufferBinding::Wrap,
+        )
+    }
+
+    pub fn is_updating(&self) -> bool {
+        self.updating.get()
+    }
+
+    /// Called once this buffer is removed from the `sourceBuffers` list of its
+    /// parent.
+    pub fn set_removed(&self) {
+        self.parent_media_source.set(None);
+    }
+
+    /// Returns the parent media source, or an InvalidStateError if this buffer
+    /// was removed from it or if it is updating.
+    fn parent_if_idle(&self) -> Fallible<DomRoot<MediaSource>> {
+        let parent = self.parent_media_source.get().ok_or(Error::InvalidState)?;
+        if self.updating.get() {
+            return Err(Error::InvalidState);
+        }
+        Ok(parent)
+    }
+
+    /// Steps 3-5 of the abort() method, also used by removeSourceBuffer().
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-abort>
+    pub fn abort_buffer_append(&self) {
+        if !self.updating.get() {
+            return;
+        }
+        self.generation_id.set(self.generation_id.get() + 1);
+        self.updating.set(false);
+        self.removing.set(false);
+        self.queue_event(atom!("abort"));
+        self.queue_event(atom!("updateend"));
+    }
+
+    /// Sets `updating`, queues `updatestart` and then runs `steps` in a task
+    /// unless the operation gets aborted in the meantime.
+    fn start_update<F>(&self, steps: F)
+    where
+        F: FnOnce(&SourceBuffer) + Send + 'static,
+    {
+        self.updating.set(true);
+        self.queue_event(atom!("updatestart"));
+
+        let this = Trusted::new(self);
+        let generation_id = self.generation_id.get();
+        let global = self.global();
+        let window = global.as_window();
+        let _ = window.task_manager().media_element_task_source().queue(
+            task!(source_buffer_update: move || {
+                let this = this.root();
+                if generation_id != this.generation_id.get() {
+                    return;
+                }
+                steps(&this);
+            }),
+            window.upcast(),
+        );
+    }
+
+    /// Fires `update` and `updateend` once an append or removal completed.
+    fn finish_update(&self) {
+        self.updating.set(false);
+        self.removing.set(false);
+        self.upcast::<EventTarget>().fire_event(atom!("update"));
+        self.upcast::<EventTarget>().fire_event(atom!("updateend"));
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-buffer-append>
+    fn buffer_append(&self, data: Vec<u8>) {
+        let parent = match self.parent_media_source.get() {
+            Some(parent) => parent,
+            None => return,
+        };
+        let pushed = parent.media_element().map_or(Err(()), |media_element| {
+            media_element.push_media_source_data(data)
+        });
+        if pushed.is_err() {
+            self.append_error(&parent);
+            return;
+        }
+
+        // The player does not tell which presentation times a segment covers, so
+        // every removed range it reports as buffered again is taken as re-appended.
+        if let Some(media_element) = parent.media_element() {
+            self.removed_ranges
+                .borrow_mut()
+                .remove_ranges(&media_element.buffered_ranges());
+        }
+
+        // https://w3c.github.io/media-source/#sourcebuffer-init-segment-received
+        // Step 6.
+        parent.activate(self);
+
+        self.finish_update();
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-append-error>
+    fn append_error(&self, parent: &MediaSource) {
+        // Step 2.
+        self.updating.set(false);
+
+        // Steps 3-4.
+        self.upcast::<EventTarget>().fire_event(atom!("error"));
+        self.upcast::<EventTarget>().fire_event(atom!("updateend"));
+
+        // Step 5.
+        parent.end_of_stream(Some(EndOfStreamError::Decode));
+    }
+
+    fn queue_event(&self, name: Atom) {
+        let global = self.global();
+        let window = global.as_window();
+        window
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(self.upcast(), name, window);
+    }
+}
+
+impl SourceBufferMethods for SourceBuffer {
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-mode
+    fn Mode(&self) -> AppendMode {
+        self.mode.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-mode
+    fn SetMode(&self, mode: AppendMode) -> ErrorResult {
+        // Steps 1-2.
+        let parent = self.parent_if_idle()?;
+
+        // Step 3.
+        // The generate timestamps flag is never set, segments carry their own.
+
+        // Step 4.
+        parent.reopen_if_ended();
+
+        // Steps 5-6.
+        // FIXME: The group start timestamp is owned by the player.
+
+        // Step 7.
+        self.mode.set(mode);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-updating
+    fn Updating(&self) -> bool {
+        self.updating.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-buffered
+    fn GetBuffered(&self) -> Fallible<DomRoot<TimeRanges>> {
+        // Step 1.
+        let parent = self.parent_media_source.get().ok_or(Error::InvalidState)?;
+
+        // Steps 2-5.
+        let mut buffered = parent
+            .media_element()
+            .map_or_else(TimeRangesContainer::new, |media_element| {
+                media_element.buffered_ranges()
+            });
+        buffered.remove_ranges(&self.removed_ranges.borrow());
+
+        // Step 6.
+        Ok(TimeRanges::new(self.global().as_window(), buffered))
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-timestampoffset
+    fn TimestampOffset(&self) -> Finite<f64> {
+        Finite::wrap(self.timestamp_offset.get())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-timestampoffset
+    fn SetTimestampOffset(&self, value: Finite<f64>) -> ErrorResult {
+        // Steps 2-3.
+        let parent = self.parent_if_idle()?;
+
+        // Step 4.
+        parent.reopen_if_ended();
+
+        // Steps 5-6.
+        // The append state is never PARSING_MEDIA_SEGMENT outside of a buffer append.
+
+        // Step 7.
+        // FIXME: servo-media does not let us shift the presentation timestamps of
+        // the pushed stream yet, so only a zero offset is supported.
+        if *value != 0. {
+            return Err(Error::NotSupported);
+        }
+        self.timestamp_offset.set(*value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowstart
+    fn AppendWindowStart(&self) -> Finite<f64> {
+        Finite::wrap(self.append_window_start.get())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowstart
+    fn SetAppendWindowStart(&self, value: Finite<f64>) -> ErrorResult {
+        // Steps 1-2.
+        self.parent_if_idle()?;
+
+        // Step 3.
+        if *value < 0. || *value >= self.append_window_end.get() {
+            return Err(Error::Type("Invalid append window start".to_owned()));
+        }
+
+        // Step 4.
+        self.append_window_start.set(*value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowend
+    fn AppendWindowEnd(&self) -> f64 {
+        self.append_window_end.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowend
+    fn SetAppendWindowEnd(&self, value: f64) -> ErrorResult {
+        // Steps 1-2.
+        self.parent_if_idle()?;
+
+        // Steps 3-4.
+        if value.is_nan() || value <= self.append_window_start.get() {
+            return Err(Error::Type("Invalid append window end".to_owned()));
+        }
+
+        // Step 5.
+        self.append_window_end.set(value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdatestart
+    event_handler!(updatestart, GetOnupdatestart, SetOnupdatestart);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdate
+    event_handler!(update, GetOnupdate, SetOnupdate);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdateend
+    event_handler!(updateend, GetOnupdateend, SetOnupdateend);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onabort
+    event_handler!(abort, GetOnabort, SetOnabort);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendbuffer
+    fn AppendBuffer(&self, data: ArrayBufferViewOrArrayBuffer) -> ErrorResult {
+        // Step 1.
+        // https://w3c.github.io/media-source/#sourcebuffer-prepare-append
+        let parent = self.parent_if_idle()?;
+        parent.reopen_if_ended();
+
+        // Step 2.
+        let data = match data {
+            ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref view) => view.to_vec(),
+            ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref buffer) => buffer.to_vec(),
+        };
+
+        // Steps 3-5.
+        self.start_update(move |this| this.buffer_append(data));
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-abort
+    fn Abort(&self) -> ErrorResult {
+        // Step 1.
+        let parent = self.parent_media_source.get().ok_or(Error::InvalidState)?;
+
+        // Step 2.
+        if !parent.is_open() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3.
+        if self.removing.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 4.
+        self.abort_buffer_append();
+
+        // Step 5.
+        // There is no segment parser state to reset.
+
+        // Steps 6-7.
+        self.append_window_start.set(0.);
+        self.append_window_end.set(f64::INFINITY);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-remove
+    fn Remove(&self, start: Finite<f64>, end: f64) -> ErrorResult {
+        // Steps 1-2.
+        let parent = self.parent_if_idle()?;
+
+        // Step 3.
+        let duration = parent.duration();
+        if duration.is_nan() {
+            return Err(Error::Type("Duration is not set".to_owned()));
+        }
+
+        // Step 4.
+        if *start < 0. || *start > duration {
+            return Err(Error::Type("Invalid start".to_owned()));
+        }
+
+        // Step 5.
+        if end.is_nan() || end <= *start {
+            return Err(Error::Type("Invalid end".to_owned()));
+        }
+
+        // Step 6.
+        parent.reopen_if_ended();
+
+        // Step 7.
+        // https://w3c.github.io/media-source/#sourcebuffer-range-removal
+        let start = *start;
+        self.removing.set(true);
+        self.start_update(move |this| {
+            let _ = this.removed_ranges.borrow_mut().add(start, end);
+            this.finish_update();
+        });
+        Ok(())
+    }
+}
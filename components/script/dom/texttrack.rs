@@ -2,12 +2,14 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::TextTrackBinding::{
     self, TextTrackKind, TextTrackMethods, TextTrackMode,
 };
+use crate::dom::bindings::codegen::Bindings::TextTrackCueListBinding::TextTrackCueListMethods;
 use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::texttrackcue::TextTrackCue;
@@ -25,6 +27,8 @@ pub struct TextTrack {
     id: String,
     mode: Cell<TextTrackMode>,
     cue_list: MutNullableDom<TextTrackCueList>,
+    /// <https://html.spec.whatwg.org/multipage/#text-track-cue-active-flag>
+    active_cues: DomRefCell<Vec<Dom<TextTrackCue>>>,
 }
 
 impl TextTrack {
@@ -43,6 +47,7 @@ impl TextTrack {
             id: id.into(),
             mode: Cell::new(mode),
             cue_list: Default::default(),
+            active_cues: DomRefCell::new(vec![]),
         }
     }
 
@@ -69,6 +74,52 @@ impl TextTrack {
     pub fn id(&self) -> &str {
         &self.id
     }
+
+    pub fn active_cues(&self) -> Vec<DomRoot<TextTrackCue>> {
+        self.active_cues
+            .borrow()
+            .iter()
+            .map(|cue| DomRoot::from_ref(&**cue))
+            .collect()
+    }
+
+    /// Updates the active flag of the cues of this track for `current_time`,
+    /// returning the cues that became active and the ones that stopped
+    /// being active, in that order.
+    /// <https://html.spec.whatwg.org/multipage/#time-marches-on>
+    pub fn update_active_cues(
+        &self,
+        current_time: f64,
+    ) -> (Vec<DomRoot<TextTrackCue>>, Vec<DomRoot<TextTrackCue>>) {
+        let mut current_cues = vec![];
+        if self.mode.get() != TextTrackMode::Disabled {
+            let cues = self.get_cues();
+            for index in 0..cues.Length() {
+                if let Some(cue) = cues.item(index as usize) {
+                    if cue.start_time() <= current_time && current_time < cue.end_time() {
+                        current_cues.push(cue);
+                    }
+                }
+            }
+        }
+
+        let mut active_cues = self.active_cues.borrow_mut();
+        let entered = current_cues
+            .iter()
+            .filter(|cue| !active_cues.iter().any(|active| &**active == &***cue))
+            .cloned()
+            .collect();
+        let exited = active_cues
+            .iter()
+            .filter(|active| !current_cues.iter().any(|cue| &***active == &**cue))
+            .map(|cue| DomRoot::from_ref(&**cue))
+            .collect();
+        *active_cues = current_cues
+            .iter()
+            .map(|cue| Dom::from_ref(&**cue))
+            .collect();
+        (entered, exited)
+    }
 }
 
 impl TextTrackMethods for TextTrack {
@@ -112,9 +163,14 @@ impl TextTrackMethods for TextTrack {
 
     // https://html.spec.whatwg.org/multipage/#dom-texttrack-activecues
     fn GetActiveCues(&self) -> Option<DomRoot<TextTrackCueList>> {
-        // XXX implement active cues logic
-        //      https://github.com/servo/servo/issues/22314
-        Some(TextTrackCueList::new(&self.global().as_window(), &[]))
+        match self.Mode() {
+            TextTrackMode::Disabled => None,
+            _ => {
+                let active_cues = self.active_cues.borrow();
+                let cues = active_cues.iter().map(|cue| &**cue).collect::<Vec<_>>();
+                Some(TextTrackCueList::new(&self.global().as_window(), &cues))
+            },
+        }
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-texttrack-addcue
@@ -130,6 +186,7 @@ impl TextTrackMethods for TextTrack {
             }
         }
         // Step 4
+        cue.set_track(Some(self));
         self.get_cues().add(cue);
         Ok(())
     }
@@ -144,6 +201,10 @@ impl TextTrackMethods for TextTrack {
         }?;
         // Step 2
         cues.remove(index);
+        cue.set_track(None);
+        self.active_cues
+            .borrow_mut()
+            .retain(|active| &**active != cue);
         Ok(())
     }
 
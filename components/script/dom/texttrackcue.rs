@@ -3,14 +3,12 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
-use crate::dom::bindings::codegen::Bindings::TextTrackCueBinding::{self, TextTrackCueMethods};
+use crate::dom::bindings::codegen::Bindings::TextTrackCueBinding::TextTrackCueMethods;
 use crate::dom::bindings::num::Finite;
-use crate::dom::bindings::reflector::reflect_dom_object;
-use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::texttrack::TextTrack;
-use crate::dom::window::Window;
 use dom_struct::dom_struct;
 use std::cell::Cell;
 
@@ -18,42 +16,46 @@ use std::cell::Cell;
 pub struct TextTrackCue {
     eventtarget: EventTarget,
     id: DomRefCell<DOMString>,
-    track: Option<Dom<TextTrack>>,
+    track: MutNullableDom<TextTrack>,
     start_time: Cell<f64>,
     end_time: Cell<f64>,
     pause_on_exit: Cell<bool>,
 }
 
 impl TextTrackCue {
-    // FIXME(#22314, dlrobertson) implement VTTCue.
-    #[allow(dead_code)]
-    pub fn new_inherited(id: DOMString, track: Option<&TextTrack>) -> TextTrackCue {
+    pub fn new_inherited(id: DOMString, start_time: f64, end_time: f64) -> TextTrackCue {
         TextTrackCue {
             eventtarget: EventTarget::new_inherited(),
             id: DomRefCell::new(id),
-            track: track.map(Dom::from_ref),
-            start_time: Cell::new(0.),
-            end_time: Cell::new(0.),
+            track: Default::default(),
+            start_time: Cell::new(start_time),
+            end_time: Cell::new(end_time),
             pause_on_exit: Cell::new(false),
         }
     }
 
-    // FIXME(#22314, dlrobertson) implement VTTCue.
-    #[allow(dead_code)]
-    pub fn new(window: &Window, id: DOMString, track: Option<&TextTrack>) -> DomRoot<TextTrackCue> {
-        reflect_dom_object(
-            Box::new(TextTrackCue::new_inherited(id, track)),
-            window,
-            TextTrackCueBinding::Wrap,
-        )
-    }
-
     pub fn id(&self) -> DOMString {
         self.id.borrow().clone()
     }
 
     pub fn get_track(&self) -> Option<DomRoot<TextTrack>> {
-        self.track.as_ref().map(|t| DomRoot::from_ref(&**t))
+        self.track.get()
+    }
+
+    pub fn set_track(&self, track: Option<&TextTrack>) {
+        self.track.set(track);
+    }
+
+    pub fn start_time(&self) -> f64 {
+        self.start_time.get()
+    }
+
+    pub fn end_time(&self) -> f64 {
+        self.end_time.get()
+    }
+
+    pub fn pause_on_exit(&self) -> bool {
+        self.pause_on_exit.get()
     }
 }
 
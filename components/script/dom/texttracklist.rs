@@ -97,9 +97,6 @@ impl TextTrackList {
         }
     }
 
-    // FIXME(#22314, dlrobertson) allow TextTracks to be
-    // removed from the TextTrackList.
-    #[allow(dead_code)]
     pub fn remove(&self, idx: usize) {
         self.dom_tracks.borrow_mut().remove(idx);
         self.upcast::<EventTarget>()
@@ -121,6 +121,44 @@ impl TimeRangesContainer {
 
         Ok(())
     }
+
+    /// Removes the [start, end) range from the ranges, splitting the range that
+    /// contains it if needed.
+    pub fn remove(&mut self, start: f64, end: f64) -> Result<(), TimeRangesError> {
+        if start > end {
+            return Err(TimeRangesError::EndOlderThanStart);
+        }
+
+        let mut remaining = Vec::with_capacity(self.ranges.len() + 1);
+        for range in self.ranges.drain(..) {
+            if range.end <= start || range.start >= end {
+                remaining.push(range);
+                continue;
+            }
+            if range.start < start {
+                remaining.push(TimeRange {
+                    start: range.start,
+                    end: start,
+                });
+            }
+            if range.end > end {
+                remaining.push(TimeRange {
+                    start: end,
+                    end: range.end,
+                });
+            }
+        }
+        self.ranges = remaining;
+
+        Ok(())
+    }
+
+    /// Removes every range of `other` from the ranges.
+    pub fn remove_ranges(&mut self, other: &TimeRangesContainer) {
+        for range in &other.ranges {
+            let _ = self.remove(range.start, range.end);
+        }
+    }
 }
 
 #[dom_struct]
@@ -4,14 +4,16 @@
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::URLBinding::{self, URLMethods};
+use crate::dom::bindings::codegen::UnionTypes::BlobOrMediaSource;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
-use crate::dom::blob::Blob;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::urlhelper::UrlHelper;
 use crate::dom::urlsearchparams::URLSearchParams;
+use crate::dom::window::Window;
 use dom_struct::dom_struct;
 use net_traits::blob_url_store::{get_blob_origin, parse_blob_url};
 use net_traits::filemanager_thread::FileManagerThreadMsg;
@@ -91,7 +93,7 @@ impl URL {
                         return Err(Error::Type(format!("could not parse base: {}", error)));
                     },
                 }
-            }
+            },
         };
         // Step 3.
         let parsed_url = match ServoUrl::parse_with_base(parsed_base.as_ref(), &url.0) {
@@ -111,14 +113,28 @@ impl URL {
     }
 
     // https://w3c.github.io/FileAPI/#dfn-createObjectURL
-    pub fn CreateObjectURL(global: &GlobalScope, blob: &Blob) -> DOMString {
+    // https://w3c.github.io/media-source/#dom-url-createobjecturl
+    pub fn CreateObjectURL(global: &GlobalScope, object: BlobOrMediaSource) -> DOMString {
         // XXX: Second field is an unicode-serialized Origin, it is a temporary workaround
         //      and should not be trusted. See issue https://github.com/servo/servo/issues/11722
         let origin = get_blob_origin(&global.get_url());
 
-        let id = blob.get_blob_url_id();
+        match object {
+            BlobOrMediaSource::Blob(blob) => {
+                let id = blob.get_blob_url_id();
 
-        DOMString::from(URL::unicode_serialization_blob_url(&origin, &id))
+                DOMString::from(URL::unicode_serialization_blob_url(&origin, &id))
+            },
+            BlobOrMediaSource::MediaSource(media_source) => {
+                // MediaSource objects are never handed to the file manager, the URL
+                // is only resolved by media elements of the same window.
+                let url = URL::unicode_serialization_blob_url(&origin, &Uuid::new_v4());
+                if let Some(window) = global.downcast::<Window>() {
+                    window.register_media_source_url(url.clone(), &media_source);
+                }
+                DOMString::from(url)
+            },
+        }
     }
 
     // https://w3c.github.io/FileAPI/#dfn-revokeObjectURL
@@ -129,6 +145,11 @@ impl URL {
         let origin = get_blob_origin(&global.get_url());
 
         if let Ok(url) = ServoUrl::parse(&url) {
+            if let Some(window) = global.downcast::<Window>() {
+                if window.revoke_media_source_url(url.as_str()) {
+                    return;
+                }
+            }
             if let Ok((id, _)) = parse_blob_url(&url) {
                 let resource_threads = global.resource_threads();
                 let (tx, rx) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
@@ -13,7 +13,11 @@ use crate::dom::bindings::codegen::Bindings::VRLayerBinding::VRLayer;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::FrameRequestCallback;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
-use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XRFrameRequestCallback;
+use crate::dom::bindings::codegen::Bindings::XRInputSourceBinding::{
+    XRHandedness, XRTargetRayMode,
+};
+use crate::dom::bindings::codegen::Bindings::XRViewBinding::XREye;
+use crate::dom::bindings::codegen::UnionTypes::HTMLCanvasElementOrOffscreenCanvas;
 use crate::dom::bindings::error::Error;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
@@ -32,8 +36,8 @@ use crate::dom::vrframedata::VRFrameData;
 use crate::dom::vrpose::VRPose;
 use crate::dom::vrstageparameters::VRStageParameters;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
-use crate::dom::xrframe::XRFrame;
-use crate::dom::xrsession::XRSession;
+use crate::dom::xrrigidtransform::RigidTransform;
+use crate::dom::xrsession::{XRButtonState, XRDeviceFrame, XRDeviceInput, XRDeviceView, XRSession};
 use crate::script_runtime::CommonScriptMsg;
 use crate::script_runtime::ScriptThreadEventCategory::WebVREvent;
 use crate::task_source::{TaskSource, TaskSourceName};
@@ -48,7 +52,7 @@ use std::ops::Deref;
 use std::rc::Rc;
 use std::thread;
 use webvr_traits::{WebVRDisplayData, WebVRDisplayEvent, WebVRFrameData, WebVRFutureFrameData};
-use webvr_traits::{WebVRLayer, WebVRMsg};
+use webvr_traits::{WebVRGamepadHand, WebVRLayer, WebVRMsg, WebVRResult};
 
 #[dom_struct]
 pub struct VRDisplay {
@@ -72,8 +76,6 @@ pub struct VRDisplay {
     /// List of request animation frame callbacks
     #[ignore_malloc_size_of = "closures are hard"]
     raf_callback_list: DomRefCell<Vec<(u32, Option<Rc<FrameRequestCallback>>)>>,
-    #[ignore_malloc_size_of = "closures are hard"]
-    xr_raf_callback_list: DomRefCell<Vec<(u32, Option<Rc<XRFrameRequestCallback>>)>>,
     // Compositor VRFrameData synchonization
     frame_data_status: Cell<VRFrameDataStatus>,
     #[ignore_malloc_size_of = "closures are hard"]
@@ -129,7 +131,6 @@ impl VRDisplay {
             layer_ctx: MutNullableDom::default(),
             next_raf_id: Cell::new(1),
             raf_callback_list: DomRefCell::new(vec![]),
-            xr_raf_callback_list: DomRefCell::new(vec![]),
             frame_data_status: Cell::new(VRFrameDataStatus::Waiting),
             frame_data_receiver: DomRefCell::new(None),
             running_display_raf: Cell::new(false),
@@ -384,23 +385,9 @@ impl VRDisplayMethods for VRDisplay {
             return promise;
         }
 
-        // Exit present
-        let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
-        self.webvr_thread()
-            .send(WebVRMsg::ExitPresent(
-                self.global().pipeline_id(),
-                self.display.borrow().display_id,
-                Some(sender),
-            ))
-            .unwrap();
-        match receiver.recv().unwrap() {
-            Ok(()) => {
-                self.stop_present();
-                promise.resolve_native(&());
-            },
-            Err(e) => {
-                promise.reject_native(&e);
-            },
+        match self.exit_present() {
+            Ok(()) => promise.resolve_native(&()),
+            Err(e) => promise.reject_native(&e),
         }
 
         promise
@@ -431,7 +418,10 @@ impl VRDisplayMethods for VRDisplay {
         vec![VRLayer {
             leftBounds: Some(bounds_to_vec(&layer.left_bounds)),
             rightBounds: Some(bounds_to_vec(&layer.right_bounds)),
-            source: self.layer_ctx.get().map(|ctx| ctx.Canvas()),
+            source: self.layer_ctx.get().and_then(|ctx| match ctx.Canvas() {
+                HTMLCanvasElementOrOffscreenCanvas::HTMLCanvasElement(canvas) => Some(canvas),
+                HTMLCanvasElementOrOffscreenCanvas::OffscreenCanvas(_) => None,
+            }),
         }]
     }
 }
@@ -570,7 +560,7 @@ impl VRDisplay {
     fn init_present(&self) {
         self.presenting.set(true);
         let xr = self.global().as_window().Navigator().Xr();
-        xr.set_active_immersive_session(&self);
+        xr.set_active_immersive_session();
         let (sync_sender, sync_receiver) = webgl_channel().unwrap();
         *self.frame_data_receiver.borrow_mut() = Some(sync_receiver);
 
@@ -635,6 +625,22 @@ impl VRDisplay {
             .expect("Thread spawning failed");
     }
 
+    fn exit_present(&self) -> WebVRResult<()> {
+        let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
+        self.webvr_thread()
+            .send(WebVRMsg::ExitPresent(
+                self.global().pipeline_id(),
+                self.display.borrow().display_id,
+                Some(sender),
+            ))
+            .unwrap();
+        let result = receiver.recv().unwrap();
+        if result.is_ok() {
+            self.stop_present();
+        }
+        result
+    }
+
     fn stop_present(&self) {
         self.presenting.set(false);
         let xr = self.global().as_window().Navigator().Xr();
@@ -683,18 +689,8 @@ impl VRDisplay {
         let now = self.global().as_window().Performance().Now();
 
         if let Some(session) = self.xr_session.get() {
-            let mut callbacks = mem::replace(&mut *self.xr_raf_callback_list.borrow_mut(), vec![]);
-            if callbacks.is_empty() {
-                return;
-            }
             self.sync_frame_data();
-            let frame = XRFrame::new(&self.global(), &session, self.frame_data.borrow().clone());
-
-            for (_, callback) in callbacks.drain(..) {
-                if let Some(callback) = callback {
-                    let _ = callback.Call__(Finite::wrap(*now), &frame, ExceptionHandling::Report);
-                }
-            }
+            session.run_frame(*now, self.xr_frame());
             // frame submission is automatic in XR
             self.SubmitFrame();
         } else {
@@ -751,21 +747,114 @@ impl VRDisplay {
         });
     }
 
-    pub fn xr_raf(&self, callback: Rc<XRFrameRequestCallback>) -> u32 {
-        let raf_id = self.next_raf_id.get();
-        self.next_raf_id.set(raf_id + 1);
-        self.xr_raf_callback_list
-            .borrow_mut()
-            .push((raf_id, Some(callback)));
-        raf_id
+    pub fn xr_exit_present(&self) {
+        if self.presenting.get() {
+            if let Err(e) = self.exit_present() {
+                warn!("WebVR: failed to exit presentation: {}", e);
+            }
+        }
+        self.xr_session.set(None);
+    }
+
+    /// The bounds of the play area as `(x, z)` pairs, centered on the
+    /// floor origin.
+    pub fn xr_bounds(&self) -> Option<Vec<(f64, f64)>> {
+        let display = self.display.borrow();
+        let stage = display.stage_parameters.as_ref()?;
+        let x = stage.size_x as f64 / 2.;
+        let z = stage.size_z as f64 / 2.;
+        if x <= 0. || z <= 0. {
+            return None;
+        }
+        Some(vec![(-x, -z), (x, -z), (x, z), (-x, z)])
     }
 
-    pub fn xr_cancel_raf(&self, handle: i32) {
-        let mut list = self.xr_raf_callback_list.borrow_mut();
-        if let Some(pair) = list.iter_mut().find(|pair| pair.0 == handle as u32) {
-            pair.1 = None;
+    /// Converts the last synced frame data to the format used by sessions.
+    fn xr_frame(&self) -> XRDeviceFrame {
+        let display = self.display.borrow();
+        let data = self.frame_data.borrow();
+        let viewer = xr_pose(&data.pose.position, &data.pose.orientation);
+        let eye_offset = |offset: &[f32; 3]| {
+            RigidTransform::translation(offset[0] as f64, offset[1] as f64, offset[2] as f64)
+        };
+        let views = vec![
+            XRDeviceView {
+                eye: XREye::Left,
+                offset: eye_offset(&display.left_eye_parameters.offset),
+                projection: data.left_projection_matrix.to_vec(),
+            },
+            XRDeviceView {
+                eye: XREye::Right,
+                offset: eye_offset(&display.right_eye_parameters.offset),
+                projection: data.right_projection_matrix.to_vec(),
+            },
+        ];
+        // The sitting to standing transform maps the native origin to the
+        // floor, so the floor origin is its translation inverted.
+        let floor_origin = display.stage_parameters.as_ref().map(|stage| {
+            let m = &stage.sitting_to_standing_transform;
+            RigidTransform::translation(-m[12] as f64, -m[13] as f64, -m[14] as f64)
+        });
+        XRDeviceFrame {
+            viewer,
+            emulated_position: data.pose.position.is_none(),
+            views,
+            floor_origin,
+            inputs: self.xr_inputs(display.display_id),
         }
     }
+
+    /// Samples the controllers attached to this display.
+    fn xr_inputs(&self, display_id: u32) -> Vec<XRDeviceInput> {
+        let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
+        self.webvr_thread()
+            .send(WebVRMsg::GetGamepads(vec![], sender))
+            .unwrap();
+        let gamepads = match receiver.recv().unwrap() {
+            Ok(gamepads) => gamepads,
+            Err(_) => return vec![],
+        };
+        gamepads
+            .into_iter()
+            .filter_map(|(data, state)| {
+                let data = data?;
+                if data.display_id != display_id || !state.connected {
+                    return None;
+                }
+                let pose = xr_pose(&state.pose.position, &state.pose.orientation)?;
+                let handedness = match data.hand {
+                    WebVRGamepadHand::Unknown => XRHandedness::None,
+                    WebVRGamepadHand::Left => XRHandedness::Left,
+                    WebVRGamepadHand::Right => XRHandedness::Right,
+                };
+                let button = |index: usize| XRButtonState {
+                    pressed: state.buttons.get(index).map_or(false, |b| b.pressed),
+                    clicked: false,
+                };
+                Some(XRDeviceInput {
+                    id: state.gamepad_id,
+                    handedness,
+                    target_ray_mode: XRTargetRayMode::Tracked_pointer,
+                    target_ray: Some(pose),
+                    grip: Some(pose),
+                    emulated_position: state.pose.position.is_none(),
+                    profiles: vec![],
+                    select: button(0),
+                    squeeze: button(1),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Converts a WebVR pose, which may lack a position on 3DOF devices.
+fn xr_pose(position: &Option<[f32; 3]>, orientation: &Option<[f32; 4]>) -> Option<RigidTransform> {
+    let o = (*orientation)?;
+    let p = position.unwrap_or([0., 0., 0.]);
+    RigidTransform::new(
+        [p[0] as f64, p[1] as f64, p[2] as f64],
+        [o[0] as f64, o[1] as f64, o[2] as f64, o[3] as f64],
+    )
 }
 
 // WebVR Spec: If the number of values in the leftBounds/rightBounds arrays
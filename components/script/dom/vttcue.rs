@@ -0,0 +1,339 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::VTTCueBinding::{
+    self, AlignSetting, AutoKeyword, DirectionSetting, LineAlignSetting, PositionAlignSetting,
+    VTTCueMethods,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::DoubleOrAutoKeyword;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::documentfragment::DocumentFragment;
+use crate::dom::element::{CustomElementCreationMode, Element, ElementCreator};
+use crate::dom::node::Node;
+use crate::dom::text::Text;
+use crate::dom::texttrackcue::TextTrackCue;
+use crate::dom::window::Window;
+use crate::webvtt::{
+    self, CueNode, CueSettings, LineAlignment, PositionAlignment, TextAlignment, WritingDirection,
+};
+use dom_struct::dom_struct;
+use html5ever::{LocalName, QualName};
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct VTTCue {
+    texttrackcue: TextTrackCue,
+    settings: Cell<CueSettings>,
+    text: DomRefCell<DOMString>,
+}
+
+impl VTTCue {
+    fn new_inherited(
+        id: DOMString,
+        start_time: f64,
+        end_time: f64,
+        settings: CueSettings,
+        text: DOMString,
+    ) -> VTTCue {
+        VTTCue {
+            texttrackcue: TextTrackCue::new_inherited(id, start_time, end_time),
+            settings: Cell::new(settings),
+            text: DomRefCell::new(text),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        id: DOMString,
+        start_time: f64,
+        end_time: f64,
+        settings: CueSettings,
+        text: DOMString,
+    ) -> DomRoot<VTTCue> {
+        reflect_dom_object(
+            Box::new(VTTCue::new_inherited(
+                id, start_time, end_time, settings, text,
+            )),
+            window,
+            VTTCueBinding::Wrap,
+        )
+    }
+
+    /// Creates the object for a cue parsed from a WebVTT file.
+    pub fn from_parsed_cue(window: &Window, cue: webvtt::Cue) -> DomRoot<VTTCue> {
+        VTTCue::new(
+            window,
+            DOMString::from(cue.id),
+            cue.start_time,
+            cue.end_time,
+            cue.settings,
+            DOMString::from(cue.text),
+        )
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-vttcue
+    pub fn Constructor(
+        window: &Window,
+        start_time: Finite<f64>,
+        end_time: Finite<f64>,
+        text: DOMString,
+    ) -> Fallible<DomRoot<VTTCue>> {
+        Ok(VTTCue::new(
+            window,
+            DOMString::new(),
+            *start_time,
+            *end_time,
+            CueSettings::default(),
+            text,
+        ))
+    }
+
+    pub fn settings(&self) -> CueSettings {
+        self.settings.get()
+    }
+
+    /// The cue text with its markup stripped, as rendered over the video.
+    pub fn text_content(&self) -> String {
+        webvtt::cue_text_content(&self.text.borrow())
+    }
+
+    fn update_settings<F: FnOnce(&mut CueSettings)>(&self, f: F) {
+        let mut settings = self.settings.get();
+        f(&mut settings);
+        self.settings.set(settings);
+    }
+
+    /// <https://w3c.github.io/webvtt/#webvtt-cue-text-dom-construction-rules>
+    fn append_cue_nodes(document: &Document, parent: &Node, nodes: &[CueNode]) {
+        for cue_node in nodes {
+            match *cue_node {
+                CueNode::Text(ref text) => {
+                    let text = Text::new(DOMString::from(text.clone()), document);
+                    let _ = parent.AppendChild(text.upcast());
+                },
+                CueNode::Element {
+                    ref name,
+                    ref classes,
+                    ref annotation,
+                    ref children,
+                } => {
+                    let local_name = match &**name {
+                        "c" | "v" | "lang" => local_name!("span"),
+                        name => LocalName::from(name),
+                    };
+                    let element = Element::create(
+                        QualName::new(None, ns!(html), local_name),
+                        None,
+                        document,
+                        ElementCreator::ScriptCreated,
+                        CustomElementCreationMode::Synchronous,
+                    );
+                    if !classes.is_empty() {
+                        element.set_tokenlist_attribute(
+                            &local_name!("class"),
+                            DOMString::from(classes.join(" ")),
+                        );
+                    }
+                    if let Some(ref annotation) = *annotation {
+                        match &**name {
+                            "v" => element.set_string_attribute(
+                                &local_name!("title"),
+                                DOMString::from(annotation.clone()),
+                            ),
+                            "lang" => element.set_string_attribute(
+                                &local_name!("lang"),
+                                DOMString::from(annotation.clone()),
+                            ),
+                            _ => {},
+                        }
+                    }
+                    VTTCue::append_cue_nodes(document, element.upcast(), children);
+                    let _ = parent.AppendChild(element.upcast());
+                },
+            }
+        }
+    }
+}
+
+impl VTTCueMethods for VTTCue {
+    // https://w3c.github.io/webvtt/#dom-vttcue-vertical
+    fn Vertical(&self) -> DirectionSetting {
+        match self.settings.get().vertical {
+            WritingDirection::Horizontal => DirectionSetting::_empty,
+            WritingDirection::VerticalGrowingLeft => DirectionSetting::Rl,
+            WritingDirection::VerticalGrowingRight => DirectionSetting::Lr,
+        }
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-vertical
+    fn SetVertical(&self, value: DirectionSetting) {
+        self.update_settings(|settings| {
+            settings.vertical = match value {
+                DirectionSetting::_empty => WritingDirection::Horizontal,
+                DirectionSetting::Rl => WritingDirection::VerticalGrowingLeft,
+                DirectionSetting::Lr => WritingDirection::VerticalGrowingRight,
+            }
+        });
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-snaptolines
+    fn SnapToLines(&self) -> bool {
+        self.settings.get().snap_to_lines
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-snaptolines
+    fn SetSnapToLines(&self, value: bool) {
+        self.update_settings(|settings| settings.snap_to_lines = value);
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-line
+    fn Line(&self) -> DoubleOrAutoKeyword {
+        match self.settings.get().line {
+            Some(line) => DoubleOrAutoKeyword::Double(Finite::wrap(line)),
+            None => DoubleOrAutoKeyword::AutoKeyword(AutoKeyword::Auto),
+        }
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-line
+    fn SetLine(&self, value: DoubleOrAutoKeyword) {
+        self.update_settings(|settings| {
+            settings.line = match value {
+                DoubleOrAutoKeyword::Double(line) => Some(*line),
+                DoubleOrAutoKeyword::AutoKeyword(_) => None,
+            }
+        });
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-linealign
+    fn LineAlign(&self) -> LineAlignSetting {
+        match self.settings.get().line_align {
+            LineAlignment::Start => LineAlignSetting::Start,
+            LineAlignment::Center => LineAlignSetting::Center,
+            LineAlignment::End => LineAlignSetting::End,
+        }
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-linealign
+    fn SetLineAlign(&self, value: LineAlignSetting) {
+        self.update_settings(|settings| {
+            settings.line_align = match value {
+                LineAlignSetting::Start => LineAlignment::Start,
+                LineAlignSetting::Center => LineAlignment::Center,
+                LineAlignSetting::End => LineAlignment::End,
+            }
+        });
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-position
+    fn Position(&self) -> DoubleOrAutoKeyword {
+        match self.settings.get().position {
+            Some(position) => DoubleOrAutoKeyword::Double(Finite::wrap(position)),
+            None => DoubleOrAutoKeyword::AutoKeyword(AutoKeyword::Auto),
+        }
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-position
+    fn SetPosition(&self, value: DoubleOrAutoKeyword) -> ErrorResult {
+        let position = match value {
+            DoubleOrAutoKeyword::Double(position) => {
+                if *position < 0. || *position > 100. {
+                    return Err(Error::IndexSize);
+                }
+                Some(*position)
+            },
+            DoubleOrAutoKeyword::AutoKeyword(_) => None,
+        };
+        self.update_settings(|settings| settings.position = position);
+        Ok(())
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-positionalign
+    fn PositionAlign(&self) -> PositionAlignSetting {
+        match self.settings.get().position_align {
+            PositionAlignment::LineLeft => PositionAlignSetting::Line_left,
+            PositionAlignment::Center => PositionAlignSetting::Center,
+            PositionAlignment::LineRight => PositionAlignSetting::Line_right,
+            PositionAlignment::Auto => PositionAlignSetting::Auto,
+        }
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-positionalign
+    fn SetPositionAlign(&self, value: PositionAlignSetting) {
+        self.update_settings(|settings| {
+            settings.position_align = match value {
+                PositionAlignSetting::Line_left => PositionAlignment::LineLeft,
+                PositionAlignSetting::Center => PositionAlignment::Center,
+                PositionAlignSetting::Line_right => PositionAlignment::LineRight,
+                PositionAlignSetting::Auto => PositionAlignment::Auto,
+            }
+        });
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-size
+    fn Size(&self) -> Finite<f64> {
+        Finite::wrap(self.settings.get().size)
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-size
+    fn SetSize(&self, value: Finite<f64>) -> ErrorResult {
+        if *value < 0. || *value > 100. {
+            return Err(Error::IndexSize);
+        }
+        self.update_settings(|settings| settings.size = *value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-align
+    fn Align(&self) -> AlignSetting {
+        match self.settings.get().align {
+            TextAlignment::Start => AlignSetting::Start,
+            TextAlignment::Center => AlignSetting::Center,
+            TextAlignment::End => AlignSetting::End,
+            TextAlignment::Left => AlignSetting::Left,
+            TextAlignment::Right => AlignSetting::Right,
+        }
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-align
+    fn SetAlign(&self, value: AlignSetting) {
+        self.update_settings(|settings| {
+            settings.align = match value {
+                AlignSetting::Start => TextAlignment::Start,
+                AlignSetting::Center => TextAlignment::Center,
+                AlignSetting::End => TextAlignment::End,
+                AlignSetting::Left => TextAlignment::Left,
+                AlignSetting::Right => TextAlignment::Right,
+            }
+        });
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-text
+    fn Text(&self) -> DOMString {
+        self.text.borrow().clone()
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-text
+    fn SetText(&self, value: DOMString) {
+        *self.text.borrow_mut() = value;
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-getcueashtml
+    fn GetCueAsHTML(&self) -> DomRoot<DocumentFragment> {
+        let global = self.global();
+        let document = global.as_window().Document();
+        let fragment = DocumentFragment::new(&document);
+        let nodes = webvtt::parse_cue_text(&self.text.borrow());
+        VTTCue::append_cue_nodes(&document, fragment.upcast(), &nodes);
+        fragment
+    }
+}
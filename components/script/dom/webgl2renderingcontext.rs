@@ -3,72 +3,111 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding;
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants as constants;
 use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextMethods;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLContextAttributes;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextMethods;
 use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
 use crate::dom::bindings::codegen::UnionTypes::Float32ArrayOrUnrestrictedFloatSequence;
+use crate::dom::bindings::codegen::UnionTypes::HTMLCanvasElementOrOffscreenCanvas;
 use crate::dom::bindings::codegen::UnionTypes::ImageDataOrHTMLImageElementOrHTMLCanvasElementOrHTMLVideoElement;
 use crate::dom::bindings::codegen::UnionTypes::Int32ArrayOrLongSequence;
 use crate::dom::bindings::error::{ErrorResult, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
-use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom};
+use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom, MutDom, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
-use crate::dom::htmlcanvaselement::HTMLCanvasElement;
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmliframeelement::HTMLIFrameElement;
+use crate::dom::webgl_validations::types::ext_disjoint_timer_query::TIME_ELAPSED_EXT;
+use crate::dom::webgl_validations::types::{IndexedBufferTarget, QueryTarget};
+use crate::dom::webgl_validations::webgl2::{self, SamplerParameterValue};
 use crate::dom::webglactiveinfo::WebGLActiveInfo;
-use crate::dom::webglbuffer::WebGLBuffer;
+use crate::dom::webglbuffer::{IndexedBufferBinding, WebGLBuffer};
 use crate::dom::webglframebuffer::WebGLFramebuffer;
 use crate::dom::webglprogram::WebGLProgram;
+use crate::dom::webglquery::WebGLQuery;
 use crate::dom::webglrenderbuffer::WebGLRenderbuffer;
 use crate::dom::webglrenderingcontext::{
     LayoutCanvasWebGLRenderingContextHelpers, WebGLRenderingContext,
 };
+use crate::dom::webglsampler::WebGLSampler;
 use crate::dom::webglshader::WebGLShader;
 use crate::dom::webglshaderprecisionformat::WebGLShaderPrecisionFormat;
+use crate::dom::webglsync::WebGLSync;
 use crate::dom::webgltexture::WebGLTexture;
+use crate::dom::webgltransformfeedback::WebGLTransformFeedback;
 use crate::dom::webgluniformlocation::WebGLUniformLocation;
-use crate::dom::window::Window;
 /// https://www.khronos.org/registry/webgl/specs/latest/2.0/webgl.idl
-use canvas_traits::webgl::{GLContextAttributes, WebGLVersion};
+use canvas_traits::webgl::WebGLError::*;
+use canvas_traits::webgl::WebGLVersion;
+use canvas_traits::webgl::{webgl_channel, GLContextAttributes, WebGLCommand, WebGLResult};
 use dom_struct::dom_struct;
 use euclid::Size2D;
-use js::jsapi::{JSContext, JSObject};
-use js::jsval::JSVal;
+use ipc_channel::ipc;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{JSContext, JSObject, Type};
+use js::jsval::{BooleanValue, DoubleValue, Int32Value, JSVal, NullValue, ObjectValue};
+use js::jsval::{UInt32Value, UndefinedValue};
 use js::rust::CustomAutoRooterGuard;
-use js::typedarray::ArrayBufferView;
+use js::typedarray::{ArrayBufferView, CreateWith, Uint32Array};
 use script_layout_interface::HTMLCanvasDataSource;
-use std::ptr::NonNull;
+use std::ptr::{self, NonNull};
+
+/// The largest timeout accepted by `clientWaitSync`, waiting would block the
+/// script thread on the WebGL thread so it is never allowed.
+const MAX_CLIENT_WAIT_TIMEOUT: u64 = 0;
 
 #[dom_struct]
 pub struct WebGL2RenderingContext {
     reflector_: Reflector,
     base: Dom<WebGLRenderingContext>,
+    /// The active query of each query target, occlusion queries share a slot.
+    occlusion_query: MutNullableDom<WebGLQuery>,
+    primitives_query: MutNullableDom<WebGLQuery>,
+    timer_query: MutNullableDom<WebGLQuery>,
+    samplers: Box<[MutNullableDom<WebGLSampler>]>,
+    default_transform_feedback: Dom<WebGLTransformFeedback>,
+    current_transform_feedback: MutDom<WebGLTransformFeedback>,
+    uniform_buffer_bindings: Box<[IndexedBufferBinding]>,
 }
 
 impl WebGL2RenderingContext {
+    #[allow(unrooted_must_root)]
     fn new_inherited(
-        window: &Window,
-        canvas: &HTMLCanvasElement,
+        global: &GlobalScope,
+        canvas: &HTMLCanvasElementOrOffscreenCanvas,
         size: Size2D<u32>,
         attrs: GLContextAttributes,
     ) -> Option<WebGL2RenderingContext> {
-        let base = WebGLRenderingContext::new(window, canvas, WebGLVersion::WebGL2, size, attrs)?;
+        let base = WebGLRenderingContext::new(global, canvas, WebGLVersion::WebGL2, size, attrs)?;
+        let default_transform_feedback = WebGLTransformFeedback::new(&base, None);
+        let limits = base.limits();
         Some(WebGL2RenderingContext {
             reflector_: Reflector::new(),
+            occlusion_query: MutNullableDom::new(None),
+            primitives_query: MutNullableDom::new(None),
+            timer_query: MutNullableDom::new(None),
+            samplers: (0..limits.max_combined_texture_image_units)
+                .map(|_| Default::default())
+                .collect(),
+            default_transform_feedback: Dom::from_ref(&*default_transform_feedback),
+            current_transform_feedback: MutDom::new(&*default_transform_feedback),
+            uniform_buffer_bindings: (0..limits.max_uniform_buffer_bindings)
+                .map(|_| Default::default())
+                .collect(),
             base: Dom::from_ref(&*base),
         })
     }
 
     #[allow(unrooted_must_root)]
     pub fn new(
-        window: &Window,
-        canvas: &HTMLCanvasElement,
+        global: &GlobalScope,
+        canvas: &HTMLCanvasElementOrOffscreenCanvas,
         size: Size2D<u32>,
         attrs: GLContextAttributes,
     ) -> Option<DomRoot<WebGL2RenderingContext>> {
-        WebGL2RenderingContext::new_inherited(window, canvas, size, attrs).map(|ctx| {
-            reflect_dom_object(Box::new(ctx), window, WebGL2RenderingContextBinding::Wrap)
+        WebGL2RenderingContext::new_inherited(global, canvas, size, attrs).map(|ctx| {
+            reflect_dom_object(Box::new(ctx), global, WebGL2RenderingContextBinding::Wrap)
         })
     }
 }
@@ -81,11 +120,235 @@ impl WebGL2RenderingContext {
     pub fn base_context(&self) -> DomRoot<WebGLRenderingContext> {
         DomRoot::from_ref(&*self.base)
     }
+
+    /// Resets the WebGL 2 specific state when the context is restored, the
+    /// base context already reset everything else.
+    pub fn reset(&self) {
+        for (target, slot) in &[
+            (constants::ANY_SAMPLES_PASSED, &self.occlusion_query),
+            (
+                constants::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN,
+                &self.primitives_query,
+            ),
+            (TIME_ELAPSED_EXT, &self.timer_query),
+        ] {
+            if slot.get().is_some() {
+                self.base.send_command(WebGLCommand::EndQuery(*target));
+                slot.set(None);
+            }
+        }
+        for (unit, slot) in self.samplers.iter().enumerate() {
+            if slot.get().is_some() {
+                self.base
+                    .send_command(WebGLCommand::BindSampler(unit as u32, None));
+                slot.set(None);
+            }
+        }
+        if self.current_transform_feedback.get().is_active() {
+            self.base.send_command(WebGLCommand::EndTransformFeedback);
+        }
+        self.base.send_command(WebGLCommand::BindTransformFeedback(
+            constants::TRANSFORM_FEEDBACK,
+            None,
+        ));
+        self.current_transform_feedback
+            .set(&*self.default_transform_feedback);
+        self.default_transform_feedback.reset();
+        for index in 0..self.base.limits().max_transform_feedback_separate_attribs {
+            self.base.send_command(WebGLCommand::BindBufferBase(
+                constants::TRANSFORM_FEEDBACK_BUFFER,
+                index,
+                None,
+            ));
+        }
+        for (index, binding) in self.uniform_buffer_bindings.iter().enumerate() {
+            if binding.buffer().is_some() {
+                self.base.send_command(WebGLCommand::BindBufferBase(
+                    constants::UNIFORM_BUFFER,
+                    index as u32,
+                    None,
+                ));
+                binding.set(None, 0, 0);
+            }
+        }
+        for &target in &[
+            constants::UNIFORM_BUFFER,
+            constants::TRANSFORM_FEEDBACK_BUFFER,
+        ] {
+            self.base
+                .send_command(WebGLCommand::BindBuffer(target, None));
+        }
+    }
+
+    fn query_slot(&self, target: QueryTarget) -> &MutNullableDom<WebGLQuery> {
+        match target {
+            QueryTarget::AnySamplesPassed | QueryTarget::AnySamplesPassedConservative => {
+                &self.occlusion_query
+            },
+            QueryTarget::TransformFeedbackPrimitivesWritten => &self.primitives_query,
+            QueryTarget::TimeElapsed => &self.timer_query,
+        }
+    }
+
+    fn is_query_active(&self, query: &WebGLQuery) -> bool {
+        [
+            &self.occlusion_query,
+            &self.primitives_query,
+            &self.timer_query,
+        ]
+        .iter()
+        .any(|slot| slot.get().map_or(false, |q| query == &*q))
+    }
+
+    fn validate_query_target(&self, target: u32) -> WebGLResult<QueryTarget> {
+        webgl2::validate_query_target(
+            target,
+            self.base
+                .supports_gl_extension("GL_EXT_disjoint_timer_query"),
+        )
+    }
+
+    /// Runs `f` with the indexed binding point `index` of `target`, which must
+    /// have been validated with `validate_indexed_buffer_binding` already.
+    fn with_indexed_buffer_binding<R>(
+        &self,
+        target: IndexedBufferTarget,
+        index: u32,
+        f: impl FnOnce(&IndexedBufferBinding) -> R,
+    ) -> R {
+        match target {
+            IndexedBufferTarget::UniformBuffer => f(&self.uniform_buffer_bindings[index as usize]),
+            IndexedBufferTarget::TransformFeedbackBuffer => {
+                let transform_feedback = self.current_transform_feedback.get();
+                f(transform_feedback.buffer_binding(index).unwrap())
+            },
+        }
+    }
+
+    /// Checks a draw call against the transform feedback state, returning the
+    /// number of vertices it would capture.
+    fn validate_transform_feedback_draw(
+        &self,
+        mode: u32,
+        count: i32,
+        instances: i32,
+    ) -> WebGLResult<usize> {
+        self.current_transform_feedback
+            .get()
+            .validate_draw(mode, count, instances)
+    }
+
+    fn draw_arrays_instanced(&self, mode: u32, first: i32, count: i32, primcount: i32) {
+        let vertices = handle_potential_webgl_error!(
+            self.base,
+            self.validate_transform_feedback_draw(mode, count, primcount),
+            return
+        );
+        handle_potential_webgl_error!(
+            self.base,
+            self.base
+                .draw_arrays_instanced(mode, first, count, primcount),
+            return
+        );
+        self.current_transform_feedback
+            .get()
+            .record_captured_vertices(vertices);
+    }
+
+    fn draw_elements_instanced(
+        &self,
+        mode: u32,
+        count: i32,
+        type_: u32,
+        offset: i64,
+        primcount: i32,
+    ) {
+        // https://www.khronos.org/registry/webgl/specs/latest/2.0/#5.24
+        if self.current_transform_feedback.get().is_capturing() {
+            return self.base.webgl_error(InvalidOperation);
+        }
+        handle_potential_webgl_error!(
+            self.base,
+            self.base
+                .draw_elements_instanced(mode, count, type_, offset, primcount)
+        )
+    }
+
+    fn bind_indexed_buffer(
+        &self,
+        target: u32,
+        index: u32,
+        buffer: Option<&WebGLBuffer>,
+        range: Option<(i64, i64)>,
+    ) -> WebGLResult<()> {
+        let indexed_target =
+            webgl2::validate_indexed_buffer_binding(target, index, self.base.limits())?;
+        if let Some(buffer) = buffer {
+            self.base.validate_ownership(buffer)?;
+            if buffer.is_marked_for_deletion() {
+                return Err(InvalidOperation);
+            }
+            if let Some((offset, size)) = range {
+                webgl2::validate_buffer_range(indexed_target, offset, size, self.base.limits())?;
+            }
+        }
+        if indexed_target == IndexedBufferTarget::TransformFeedbackBuffer &&
+            self.current_transform_feedback.get().is_active()
+        {
+            return Err(InvalidOperation);
+        }
+        if let Some(buffer) = buffer {
+            buffer.set_target(target)?;
+        }
+
+        let id = buffer.map(|buffer| buffer.id());
+        let (start, size) = match range {
+            Some((offset, size)) if buffer.is_some() => {
+                self.base.send_command(WebGLCommand::BindBufferRange(
+                    target, index, id, offset, size,
+                ));
+                (offset, size)
+            },
+            _ => {
+                self.base
+                    .send_command(WebGLCommand::BindBufferBase(target, index, id));
+                (0, 0)
+            },
+        };
+        self.with_indexed_buffer_binding(indexed_target, index, |binding| {
+            binding.set(buffer, start, size)
+        });
+        // Binding to an indexed binding point also binds to the generic one.
+        self.base.set_generic_buffer_binding(target, buffer)
+    }
+
+    fn validate_uniform_block_program(&self, program: &WebGLProgram) -> WebGLResult<()> {
+        self.base.validate_ownership(program)?;
+        if program.is_deleted() {
+            return Err(InvalidValue);
+        }
+        Ok(())
+    }
+
+    fn validate_uniform_block_index(&self, program: &WebGLProgram, index: u32) -> WebGLResult<()> {
+        self.validate_uniform_block_program(program)?;
+        if index >= program.active_uniform_blocks() as u32 {
+            return Err(InvalidValue);
+        }
+        Ok(())
+    }
+
+    fn unbind_buffer_everywhere(&self, buffer: &WebGLBuffer) {
+        for binding in &*self.uniform_buffer_bindings {
+            binding.unbind(buffer);
+        }
+        self.current_transform_feedback.get().unbind_buffer(buffer);
+    }
 }
 
 impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.1
-    fn Canvas(&self) -> DomRoot<HTMLCanvasElement> {
+    fn Canvas(&self) -> HTMLCanvasElementOrOffscreenCanvas {
         self.base.Canvas()
     }
 
@@ -94,6 +357,11 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
         self.base.Flush()
     }
 
+    /// https://html.spec.whatwg.org/multipage/#dom-offscreencanvasrenderingcontext2d-commit
+    fn Commit(&self) {
+        self.base.Commit()
+    }
+
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.11
     fn Finish(&self) {
         self.base.Finish()
@@ -118,7 +386,49 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
     #[allow(unsafe_code)]
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.3
     unsafe fn GetParameter(&self, cx: *mut JSContext, parameter: u32) -> JSVal {
-        self.base.GetParameter(cx, parameter)
+        if self.base.is_context_lost() {
+            return NullValue();
+        }
+        let limits = self.base.limits();
+        match parameter {
+            constants::SAMPLER_BINDING => {
+                let unit = self.base.textures().active_unit_index();
+                let sampler = self.samplers[unit as usize].get();
+                optional_root_object_to_js_or_null!(cx, sampler)
+            },
+            constants::UNIFORM_BUFFER_BINDING | constants::TRANSFORM_FEEDBACK_BUFFER_BINDING => {
+                let target = if parameter == constants::UNIFORM_BUFFER_BINDING {
+                    constants::UNIFORM_BUFFER
+                } else {
+                    constants::TRANSFORM_FEEDBACK_BUFFER
+                };
+                let buffer = self.base.bound_buffer(target).unwrap();
+                optional_root_object_to_js_or_null!(cx, buffer)
+            },
+            constants::TRANSFORM_FEEDBACK_BINDING => {
+                let transform_feedback =
+                    Some(self.current_transform_feedback.get()).filter(|tf| tf.id().is_some());
+                optional_root_object_to_js_or_null!(cx, transform_feedback)
+            },
+            constants::TRANSFORM_FEEDBACK_ACTIVE => {
+                BooleanValue(self.current_transform_feedback.get().is_active())
+            },
+            constants::TRANSFORM_FEEDBACK_PAUSED => {
+                BooleanValue(self.current_transform_feedback.get().is_paused())
+            },
+            constants::MAX_UNIFORM_BUFFER_BINDINGS => {
+                Int32Value(limits.max_uniform_buffer_bindings as i32)
+            },
+            constants::UNIFORM_BUFFER_OFFSET_ALIGNMENT => {
+                Int32Value(limits.uniform_buffer_offset_alignment as i32)
+            },
+            constants::MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS => {
+                Int32Value(limits.max_transform_feedback_separate_attribs as i32)
+            },
+            // https://www.khronos.org/registry/webgl/specs/latest/2.0/#5.15
+            constants::MAX_CLIENT_WAIT_TIMEOUT_WEBGL => DoubleValue(MAX_CLIENT_WAIT_TIMEOUT as f64),
+            _ => self.base.GetParameter(cx, parameter),
+        }
     }
 
     #[allow(unsafe_code)]
@@ -132,6 +442,11 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
         self.base.GetError()
     }
 
+    /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.13
+    fn IsContextLost(&self) -> bool {
+        self.base.IsContextLost()
+    }
+
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.2
     fn GetContextAttributes(&self) -> Option<WebGLContextAttributes> {
         self.base.GetContextAttributes()
@@ -429,7 +744,16 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
     fn DeleteBuffer(&self, buffer: Option<&WebGLBuffer>) {
-        self.base.DeleteBuffer(buffer)
+        let buffer = match buffer {
+            Some(buffer) => buffer,
+            None => return,
+        };
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(buffer), return);
+        if buffer.is_marked_for_deletion() {
+            return;
+        }
+        self.unbind_buffer_everywhere(buffer);
+        self.base.DeleteBuffer(Some(buffer))
     }
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.6
@@ -459,12 +783,12 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.11
     fn DrawArrays(&self, mode: u32, first: i32, count: i32) {
-        self.base.DrawArrays(mode, first, count)
+        self.draw_arrays_instanced(mode, first, count, 1)
     }
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.11
     fn DrawElements(&self, mode: u32, count: i32, type_: u32, offset: i64) {
-        self.base.DrawElements(mode, count, type_, offset)
+        self.draw_elements_instanced(mode, count, type_, offset, 1)
     }
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.10
@@ -513,7 +837,30 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
         program: &WebGLProgram,
         param_id: u32,
     ) -> JSVal {
-        self.base.GetProgramParameter(cx, program, param_id)
+        match param_id {
+            constants::TRANSFORM_FEEDBACK_VARYINGS |
+            constants::TRANSFORM_FEEDBACK_BUFFER_MODE |
+            constants::ACTIVE_UNIFORM_BLOCKS => {},
+            _ => return self.base.GetProgramParameter(cx, program, param_id),
+        }
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(program),
+            return NullValue()
+        );
+        if program.is_deleted() {
+            self.base.webgl_error(InvalidOperation);
+            return NullValue();
+        }
+        match param_id {
+            constants::TRANSFORM_FEEDBACK_VARYINGS => {
+                Int32Value(program.transform_feedback_varyings().len() as i32)
+            },
+            constants::TRANSFORM_FEEDBACK_BUFFER_MODE => {
+                Int32Value(program.transform_feedback_buffer_mode() as i32)
+            },
+            _ => Int32Value(program.active_uniform_blocks()),
+        }
     }
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.9
@@ -675,6 +1022,13 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.9
     fn LinkProgram(&self, program: &WebGLProgram) {
+        let transform_feedback = self.current_transform_feedback.get();
+        if transform_feedback
+            .program()
+            .map_or(false, |p| program == &*p)
+        {
+            return self.base.webgl_error(InvalidOperation);
+        }
         self.base.LinkProgram(program)
     }
 
@@ -827,6 +1181,9 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.9
     fn UseProgram(&self, program: Option<&WebGLProgram>) {
+        if self.current_transform_feedback.get().is_capturing() {
+            return self.base.webgl_error(InvalidOperation);
+        }
         self.base.UseProgram(program)
     }
 
@@ -1044,11 +1401,7 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.9
     fn DrawArraysInstanced(&self, mode: u32, first: i32, count: i32, primcount: i32) {
-        handle_potential_webgl_error!(
-            self.base,
-            self.base
-                .draw_arrays_instanced(mode, first, count, primcount)
-        )
+        self.draw_arrays_instanced(mode, first, count, primcount)
     }
 
     /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.9
@@ -1060,17 +1413,763 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
         offset: i64,
         primcount: i32,
     ) {
-        handle_potential_webgl_error!(
-            self.base,
-            self.base
-                .draw_elements_instanced(mode, count, type_, offset, primcount)
-        )
+        self.draw_elements_instanced(mode, count, type_, offset, primcount)
     }
 
     /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.9
     fn VertexAttribDivisor(&self, index: u32, divisor: u32) {
         self.base.vertex_attrib_divisor(index, divisor);
     }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.11
+    fn DrawBuffers(&self, buffers: Vec<u32>) {
+        self.base.draw_buffers(buffers)
+    }
+
+    #[allow(unsafe_code)]
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.3
+    fn GetBufferSubData(
+        &self,
+        target: u32,
+        src_byte_offset: i64,
+        mut dst_buffer: CustomAutoRooterGuard<ArrayBufferView>,
+        dst_offset: u32,
+        length: u32,
+    ) {
+        if self.base.is_context_lost() {
+            return;
+        }
+        let buffer = handle_potential_webgl_error!(
+            self.base,
+            self.base
+                .bound_buffer(target)
+                .and_then(|buffer| buffer.ok_or(InvalidOperation)),
+            return
+        );
+        if target == constants::TRANSFORM_FEEDBACK_BUFFER &&
+            self.current_transform_feedback.get().is_active()
+        {
+            return self.base.webgl_error(InvalidOperation);
+        }
+
+        let element_size = match dst_buffer.get_array_type() {
+            Type::Int16 | Type::Uint16 => 2,
+            Type::Int32 | Type::Uint32 | Type::Float32 => 4,
+            Type::Float64 => 8,
+            _ => 1,
+        };
+        let dst = unsafe { dst_buffer.as_mut_slice() };
+        let (dst_offset, copy_length) = handle_potential_webgl_error!(
+            self.base,
+            webgl2::validate_get_buffer_sub_data(
+                src_byte_offset,
+                buffer.capacity(),
+                dst.len() / element_size,
+                dst_offset,
+                length,
+                element_size,
+            ),
+            return
+        );
+        if copy_length == 0 {
+            return;
+        }
+
+        let start = dst_offset * element_size;
+        let end = start + copy_length * element_size;
+        let (sender, receiver) = ipc::bytes_channel().unwrap();
+        self.base.send_command(WebGLCommand::GetBufferSubData(
+            target,
+            src_byte_offset as usize,
+            end - start,
+            sender,
+        ));
+        let data = receiver.recv().unwrap();
+        dst[start..end].copy_from_slice(&data);
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.12
+    fn CreateQuery(&self) -> Option<DomRoot<WebGLQuery>> {
+        if self.base.is_context_lost() {
+            return None;
+        }
+        WebGLQuery::maybe_new(&self.base)
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.12
+    fn DeleteQuery(&self, query: Option<&WebGLQuery>) {
+        let query = match query {
+            Some(query) => query,
+            None => return,
+        };
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(query), return);
+        if query.is_deleted() {
+            return;
+        }
+        if self.is_query_active(query) {
+            let target = query.target().unwrap();
+            query.end(target);
+            let target = QueryTarget::from_gl_constant(target).unwrap();
+            self.query_slot(target).set(None);
+        }
+        query.delete();
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.12
+    fn IsQuery(&self, query: Option<&WebGLQuery>) -> bool {
+        query.map_or(false, |query| {
+            self.base.validate_ownership(query).is_ok() &&
+                !query.is_deleted() &&
+                query.target().is_some()
+        })
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.12
+    fn BeginQuery(&self, target: u32, query: &WebGLQuery) {
+        let query_target =
+            handle_potential_webgl_error!(self.base, self.validate_query_target(target), return);
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(query), return);
+        let slot = self.query_slot(query_target);
+        if slot.get().is_some() || self.is_query_active(query) {
+            return self.base.webgl_error(InvalidOperation);
+        }
+        handle_potential_webgl_error!(self.base, query.begin(target), return);
+        slot.set(Some(query));
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.12
+    fn EndQuery(&self, target: u32) {
+        let query_target =
+            handle_potential_webgl_error!(self.base, self.validate_query_target(target), return);
+        let slot = self.query_slot(query_target);
+        match slot.get() {
+            Some(ref query) if query.target() == Some(target) => {
+                query.end(target);
+                slot.set(None);
+            },
+            _ => self.base.webgl_error(InvalidOperation),
+        }
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.12
+    fn GetQuery(&self, target: u32, pname: u32) -> Option<DomRoot<WebGLQuery>> {
+        let query_target = handle_potential_webgl_error!(
+            self.base,
+            self.validate_query_target(target),
+            return None
+        );
+        if pname != constants::CURRENT_QUERY {
+            self.base.webgl_error(InvalidEnum);
+            return None;
+        }
+        self.query_slot(query_target)
+            .get()
+            .filter(|query| query.target() == Some(target))
+    }
+
+    #[allow(unsafe_code)]
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.12
+    unsafe fn GetQueryParameter(
+        &self,
+        _cx: *mut JSContext,
+        query: &WebGLQuery,
+        pname: u32,
+    ) -> JSVal {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(query),
+            return NullValue()
+        );
+        if self.is_query_active(query) {
+            self.base.webgl_error(InvalidOperation);
+            return NullValue();
+        }
+        let (available, result) = handle_potential_webgl_error!(
+            self.base,
+            query.get_parameter(pname),
+            return NullValue()
+        );
+        match pname {
+            constants::QUERY_RESULT_AVAILABLE => BooleanValue(available),
+            _ if query.target() == Some(TIME_ELAPSED_EXT) => DoubleValue(result as f64),
+            _ => UInt32Value(result as u32),
+        }
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn CreateSampler(&self) -> Option<DomRoot<WebGLSampler>> {
+        if self.base.is_context_lost() {
+            return None;
+        }
+        WebGLSampler::maybe_new(&self.base)
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn DeleteSampler(&self, sampler: Option<&WebGLSampler>) {
+        let sampler = match sampler {
+            Some(sampler) => sampler,
+            None => return,
+        };
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(sampler), return);
+        if sampler.is_deleted() {
+            return;
+        }
+        // Deleting a sampler unbinds it from every texture unit.
+        for slot in &*self.samplers {
+            if slot.get().map_or(false, |s| sampler == &*s) {
+                slot.set(None);
+            }
+        }
+        sampler.delete();
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn IsSampler(&self, sampler: Option<&WebGLSampler>) -> bool {
+        sampler.map_or(false, |sampler| {
+            self.base.validate_ownership(sampler).is_ok() && !sampler.is_deleted()
+        })
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn BindSampler(&self, unit: u32, sampler: Option<&WebGLSampler>) {
+        if let Some(sampler) = sampler {
+            handle_potential_webgl_error!(self.base, self.base.validate_ownership(sampler), return);
+            if sampler.is_deleted() {
+                return self.base.webgl_error(InvalidOperation);
+            }
+        }
+        let slot = match self.samplers.get(unit as usize) {
+            Some(slot) => slot,
+            None => return self.base.webgl_error(InvalidValue),
+        };
+        self.base
+            .send_command(WebGLCommand::BindSampler(unit, sampler.map(|s| s.id())));
+        slot.set(sampler);
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn SamplerParameteri(&self, sampler: &WebGLSampler, pname: u32, param: i32) {
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(sampler), return);
+        handle_potential_webgl_error!(
+            self.base,
+            sampler.set_parameter(pname, SamplerParameterValue::Int(param))
+        )
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn SamplerParameterf(&self, sampler: &WebGLSampler, pname: u32, param: f32) {
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(sampler), return);
+        handle_potential_webgl_error!(
+            self.base,
+            sampler.set_parameter(pname, SamplerParameterValue::Float(param))
+        )
+    }
+
+    #[allow(unsafe_code)]
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    unsafe fn GetSamplerParameter(
+        &self,
+        _cx: *mut JSContext,
+        sampler: &WebGLSampler,
+        pname: u32,
+    ) -> JSVal {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(sampler),
+            return NullValue()
+        );
+        match handle_potential_webgl_error!(
+            self.base,
+            sampler.get_parameter(pname),
+            return NullValue()
+        ) {
+            SamplerParameterValue::Int(value) => Int32Value(value),
+            SamplerParameterValue::Float(value) => DoubleValue(value as f64),
+        }
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.14
+    fn FenceSync(&self, condition: u32, flags: u32) -> Option<DomRoot<WebGLSync>> {
+        if self.base.is_context_lost() {
+            return None;
+        }
+        handle_potential_webgl_error!(
+            self.base,
+            webgl2::validate_fence_sync(condition, flags),
+            return None
+        );
+        WebGLSync::maybe_new(&self.base)
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.14
+    fn IsSync(&self, sync: Option<&WebGLSync>) -> bool {
+        sync.map_or(false, |sync| {
+            self.base.validate_ownership(sync).is_ok() && !sync.is_deleted()
+        })
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.14
+    fn DeleteSync(&self, sync: Option<&WebGLSync>) {
+        if let Some(sync) = sync {
+            handle_potential_webgl_error!(self.base, self.base.validate_ownership(sync), return);
+            sync.delete();
+        }
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.14
+    fn ClientWaitSync(&self, sync: &WebGLSync, flags: u32, timeout: u64) -> u32 {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(sync),
+            return constants::WAIT_FAILED
+        );
+        handle_potential_webgl_error!(
+            self.base,
+            webgl2::validate_client_wait_sync(flags, timeout, MAX_CLIENT_WAIT_TIMEOUT),
+            return constants::WAIT_FAILED
+        );
+        handle_potential_webgl_error!(
+            self.base,
+            sync.client_wait(flags, timeout),
+            constants::WAIT_FAILED
+        )
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.14
+    fn WaitSync(&self, sync: &WebGLSync, flags: u32, timeout: i64) {
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(sync), return);
+        handle_potential_webgl_error!(
+            self.base,
+            webgl2::validate_wait_sync(flags, timeout),
+            return
+        );
+        handle_potential_webgl_error!(self.base, sync.wait(flags, timeout))
+    }
+
+    #[allow(unsafe_code)]
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.14
+    unsafe fn GetSyncParameter(&self, _cx: *mut JSContext, sync: &WebGLSync, pname: u32) -> JSVal {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(sync),
+            return NullValue()
+        );
+        let value =
+            handle_potential_webgl_error!(self.base, sync.get_parameter(pname), return NullValue());
+        UInt32Value(value)
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn CreateTransformFeedback(&self) -> Option<DomRoot<WebGLTransformFeedback>> {
+        if self.base.is_context_lost() {
+            return None;
+        }
+        WebGLTransformFeedback::maybe_new(&self.base)
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn DeleteTransformFeedback(&self, transform_feedback: Option<&WebGLTransformFeedback>) {
+        let transform_feedback = match transform_feedback {
+            Some(transform_feedback) => transform_feedback,
+            None => return,
+        };
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(transform_feedback),
+            return
+        );
+        if transform_feedback.id().is_none() || transform_feedback.is_deleted() {
+            return;
+        }
+        if transform_feedback.is_active() {
+            return self.base.webgl_error(InvalidOperation);
+        }
+        if &*self.current_transform_feedback.get() == transform_feedback {
+            self.current_transform_feedback
+                .set(&self.default_transform_feedback);
+        }
+        transform_feedback.delete();
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn IsTransformFeedback(&self, transform_feedback: Option<&WebGLTransformFeedback>) -> bool {
+        transform_feedback.map_or(false, |transform_feedback| {
+            self.base.validate_ownership(transform_feedback).is_ok() &&
+                !transform_feedback.is_deleted() &&
+                transform_feedback.ever_bound()
+        })
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn BindTransformFeedback(
+        &self,
+        target: u32,
+        transform_feedback: Option<&WebGLTransformFeedback>,
+    ) {
+        if target != constants::TRANSFORM_FEEDBACK {
+            return self.base.webgl_error(InvalidEnum);
+        }
+        if let Some(transform_feedback) = transform_feedback {
+            handle_potential_webgl_error!(
+                self.base,
+                self.base.validate_ownership(transform_feedback),
+                return
+            );
+            if transform_feedback.is_deleted() {
+                return self.base.webgl_error(InvalidOperation);
+            }
+        }
+        if self.current_transform_feedback.get().is_capturing() {
+            return self.base.webgl_error(InvalidOperation);
+        }
+        let transform_feedback = transform_feedback.unwrap_or(&*self.default_transform_feedback);
+        transform_feedback.set_ever_bound();
+        self.base.send_command(WebGLCommand::BindTransformFeedback(
+            target,
+            transform_feedback.id(),
+        ));
+        self.current_transform_feedback.set(transform_feedback);
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn BeginTransformFeedback(&self, primitive_mode: u32) {
+        handle_potential_webgl_error!(
+            self.base,
+            webgl2::validate_transform_feedback_primitive_mode(primitive_mode),
+            return
+        );
+        let program = match self.base.current_program() {
+            Some(program) => program,
+            None => return self.base.webgl_error(InvalidOperation),
+        };
+        handle_potential_webgl_error!(
+            self.base,
+            self.current_transform_feedback
+                .get()
+                .begin(primitive_mode, &program)
+        )
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn EndTransformFeedback(&self) {
+        let transform_feedback = self.current_transform_feedback.get();
+        if !transform_feedback.is_active() {
+            return self.base.webgl_error(InvalidOperation);
+        }
+        transform_feedback.end();
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn PauseTransformFeedback(&self) {
+        let transform_feedback = self.current_transform_feedback.get();
+        if !transform_feedback.is_capturing() {
+            return self.base.webgl_error(InvalidOperation);
+        }
+        transform_feedback.pause();
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn ResumeTransformFeedback(&self) {
+        let transform_feedback = self.current_transform_feedback.get();
+        if !transform_feedback.is_active() || !transform_feedback.is_paused() {
+            return self.base.webgl_error(InvalidOperation);
+        }
+        if transform_feedback.program() != self.base.current_program() {
+            return self.base.webgl_error(InvalidOperation);
+        }
+        transform_feedback.resume();
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn TransformFeedbackVaryings(
+        &self,
+        program: &WebGLProgram,
+        varyings: Vec<DOMString>,
+        buffer_mode: u32,
+    ) {
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(program), return);
+        if program.is_deleted() {
+            return self.base.webgl_error(InvalidValue);
+        }
+        handle_potential_webgl_error!(
+            self.base,
+            webgl2::validate_transform_feedback_buffer_mode(
+                buffer_mode,
+                varyings.len(),
+                self.base.limits(),
+            ),
+            return
+        );
+        program.transform_feedback_varyings(
+            varyings.into_iter().map(String::from).collect(),
+            buffer_mode,
+        );
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn GetTransformFeedbackVarying(
+        &self,
+        program: &WebGLProgram,
+        index: u32,
+    ) -> Option<DomRoot<WebGLActiveInfo>> {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(program),
+            return None
+        );
+        handle_potential_webgl_error!(
+            self.base,
+            program.get_transform_feedback_varying(index).map(Some),
+            None
+        )
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn BindBufferBase(&self, target: u32, index: u32, buffer: Option<&WebGLBuffer>) {
+        handle_potential_webgl_error!(
+            self.base,
+            self.bind_indexed_buffer(target, index, buffer, None)
+        )
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn BindBufferRange(
+        &self,
+        target: u32,
+        index: u32,
+        buffer: Option<&WebGLBuffer>,
+        offset: i64,
+        size: i64,
+    ) {
+        handle_potential_webgl_error!(
+            self.base,
+            self.bind_indexed_buffer(target, index, buffer, Some((offset, size)))
+        )
+    }
+
+    #[allow(unsafe_code)]
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    unsafe fn GetIndexedParameter(&self, cx: *mut JSContext, target: u32, index: u32) -> JSVal {
+        if self.base.is_context_lost() {
+            return NullValue();
+        }
+        let indexed_target = match target {
+            constants::TRANSFORM_FEEDBACK_BUFFER_BINDING |
+            constants::TRANSFORM_FEEDBACK_BUFFER_START |
+            constants::TRANSFORM_FEEDBACK_BUFFER_SIZE => {
+                IndexedBufferTarget::TransformFeedbackBuffer
+            },
+            constants::UNIFORM_BUFFER_BINDING |
+            constants::UNIFORM_BUFFER_START |
+            constants::UNIFORM_BUFFER_SIZE => IndexedBufferTarget::UniformBuffer,
+            _ => {
+                self.base.webgl_error(InvalidEnum);
+                return NullValue();
+            },
+        };
+        if index >= webgl2::indexed_binding_count(indexed_target, self.base.limits()) {
+            self.base.webgl_error(InvalidValue);
+            return NullValue();
+        }
+        self.with_indexed_buffer_binding(indexed_target, index, |binding| match target {
+            constants::TRANSFORM_FEEDBACK_BUFFER_BINDING | constants::UNIFORM_BUFFER_BINDING => {
+                optional_root_object_to_js_or_null!(cx, binding.buffer())
+            },
+            constants::TRANSFORM_FEEDBACK_BUFFER_START | constants::UNIFORM_BUFFER_START => {
+                DoubleValue(binding.start() as f64)
+            },
+            _ => DoubleValue(binding.size() as f64),
+        })
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn GetUniformIndices(
+        &self,
+        program: &WebGLProgram,
+        uniform_names: Vec<DOMString>,
+    ) -> Option<Vec<u32>> {
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_uniform_block_program(program),
+            return None
+        );
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.base.send_command(WebGLCommand::GetUniformIndices(
+            program.id(),
+            uniform_names.into_iter().map(String::from).collect(),
+            sender,
+        ));
+        Some(receiver.recv().unwrap())
+    }
+
+    #[allow(unsafe_code)]
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    unsafe fn GetActiveUniforms(
+        &self,
+        cx: *mut JSContext,
+        program: &WebGLProgram,
+        uniform_indices: Vec<u32>,
+        pname: u32,
+    ) -> JSVal {
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_uniform_block_program(program),
+            return NullValue()
+        );
+        match pname {
+            constants::UNIFORM_TYPE |
+            constants::UNIFORM_SIZE |
+            constants::UNIFORM_BLOCK_INDEX |
+            constants::UNIFORM_OFFSET |
+            constants::UNIFORM_ARRAY_STRIDE |
+            constants::UNIFORM_MATRIX_STRIDE |
+            constants::UNIFORM_IS_ROW_MAJOR => {},
+            _ => {
+                self.base.webgl_error(InvalidEnum);
+                return NullValue();
+            },
+        }
+        let active_uniforms = program.active_uniforms().len();
+        if uniform_indices
+            .iter()
+            .any(|&index| index as usize >= active_uniforms)
+        {
+            self.base.webgl_error(InvalidValue);
+            return NullValue();
+        }
+
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.base.send_command(WebGLCommand::GetActiveUniforms(
+            program.id(),
+            uniform_indices,
+            pname,
+            sender,
+        ));
+        let values = receiver.recv().unwrap();
+        rooted!(in(cx) let mut rval = UndefinedValue());
+        match pname {
+            constants::UNIFORM_TYPE => values
+                .into_iter()
+                .map(|value| value as u32)
+                .collect::<Vec<_>>()
+                .to_jsval(cx, rval.handle_mut()),
+            constants::UNIFORM_IS_ROW_MAJOR => values
+                .into_iter()
+                .map(|value| value != 0)
+                .collect::<Vec<_>>()
+                .to_jsval(cx, rval.handle_mut()),
+            _ => values.to_jsval(cx, rval.handle_mut()),
+        }
+        rval.get()
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn GetUniformBlockIndex(&self, program: &WebGLProgram, uniform_block_name: DOMString) -> u32 {
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_uniform_block_program(program),
+            return constants::INVALID_INDEX
+        );
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.base.send_command(WebGLCommand::GetUniformBlockIndex(
+            program.id(),
+            uniform_block_name.into(),
+            sender,
+        ));
+        receiver.recv().unwrap()
+    }
+
+    #[allow(unsafe_code)]
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    unsafe fn GetActiveUniformBlockParameter(
+        &self,
+        cx: *mut JSContext,
+        program: &WebGLProgram,
+        uniform_block_index: u32,
+        pname: u32,
+    ) -> JSVal {
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_uniform_block_index(program, uniform_block_index),
+            return NullValue()
+        );
+        match pname {
+            constants::UNIFORM_BLOCK_BINDING |
+            constants::UNIFORM_BLOCK_DATA_SIZE |
+            constants::UNIFORM_BLOCK_ACTIVE_UNIFORMS |
+            constants::UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES |
+            constants::UNIFORM_BLOCK_REFERENCED_BY_VERTEX_SHADER |
+            constants::UNIFORM_BLOCK_REFERENCED_BY_FRAGMENT_SHADER => {},
+            _ => {
+                self.base.webgl_error(InvalidEnum);
+                return NullValue();
+            },
+        }
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.base
+            .send_command(WebGLCommand::GetActiveUniformBlockParameter(
+                program.id(),
+                uniform_block_index,
+                pname,
+                sender,
+            ));
+        let values = receiver.recv().unwrap();
+        match pname {
+            constants::UNIFORM_BLOCK_REFERENCED_BY_VERTEX_SHADER |
+            constants::UNIFORM_BLOCK_REFERENCED_BY_FRAGMENT_SHADER => BooleanValue(values[0] != 0),
+            constants::UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES => {
+                let indices = values.iter().map(|&index| index as u32).collect::<Vec<_>>();
+                rooted!(in(cx) let mut rval = ptr::null_mut::<JSObject>());
+                let _ = Uint32Array::create(cx, CreateWith::Slice(&indices), rval.handle_mut())
+                    .unwrap();
+                ObjectValue(rval.get())
+            },
+            _ => Int32Value(values[0]),
+        }
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn GetActiveUniformBlockName(
+        &self,
+        program: &WebGLProgram,
+        uniform_block_index: u32,
+    ) -> Option<DOMString> {
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_uniform_block_index(program, uniform_block_index),
+            return None
+        );
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.base
+            .send_command(WebGLCommand::GetActiveUniformBlockName(
+                program.id(),
+                uniform_block_index,
+                sender,
+            ));
+        Some(receiver.recv().unwrap().into())
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn UniformBlockBinding(
+        &self,
+        program: &WebGLProgram,
+        uniform_block_index: u32,
+        uniform_block_binding: u32,
+    ) {
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_uniform_block_index(program, uniform_block_index),
+            return
+        );
+        if uniform_block_binding >= self.base.limits().max_uniform_buffer_bindings {
+            return self.base.webgl_error(InvalidValue);
+        }
+        self.base.send_command(WebGLCommand::UniformBlockBinding(
+            program.id(),
+            uniform_block_index,
+            uniform_block_binding,
+        ));
+    }
 }
 
 impl LayoutCanvasWebGLRenderingContextHelpers for LayoutDom<WebGL2RenderingContext> {
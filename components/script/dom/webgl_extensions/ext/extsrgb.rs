@@ -0,0 +1,53 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use super::{WebGLExtension, WebGLExtensionSpec, WebGLExtensions};
+use crate::dom::bindings::codegen::Bindings::EXTsRGBBinding::{self, EXTsRGBConstants};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use canvas_traits::webgl::{is_gles, WebGLVersion};
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct EXTsRGB {
+    reflector_: Reflector,
+}
+
+impl EXTsRGB {
+    fn new_inherited() -> Self {
+        Self {
+            reflector_: Reflector::new(),
+        }
+    }
+}
+
+impl WebGLExtension for EXTsRGB {
+    type Extension = Self;
+
+    fn new(ctx: &WebGLRenderingContext) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(Self::new_inherited()),
+            &*ctx.global(),
+            EXTsRGBBinding::Wrap,
+        )
+    }
+
+    fn spec() -> WebGLExtensionSpec {
+        WebGLExtensionSpec::Specific(WebGLVersion::WebGL1)
+    }
+
+    fn is_supported(ext: &WebGLExtensions) -> bool {
+        !is_gles() || ext.supports_any_gl_extension(&["GL_EXT_sRGB", "GL_EXT_texture_sRGB"])
+    }
+
+    fn enable(ext: &WebGLExtensions) {
+        ext.enable_tex_format(EXTsRGBConstants::SRGB_EXT);
+        ext.enable_tex_format(EXTsRGBConstants::SRGB_ALPHA_EXT);
+    }
+
+    fn name() -> &'static str {
+        "EXT_sRGB"
+    }
+}
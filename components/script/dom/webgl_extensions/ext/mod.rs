@@ -8,7 +8,9 @@ use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGL
 pub mod angleinstancedarrays;
 pub mod extblendminmax;
 pub mod extcolorbufferhalffloat;
+pub mod extfragdepth;
 pub mod extshadertexturelod;
+pub mod extsrgb;
 pub mod exttexturefilteranisotropic;
 pub mod oeselementindexuint;
 pub mod oesstandardderivatives;
@@ -18,3 +20,9 @@ pub mod oestexturehalffloat;
 pub mod oestexturehalffloatlinear;
 pub mod oesvertexarrayobject;
 pub mod webglcolorbufferfloat;
+pub mod webglcompressedtextureetc;
+pub mod webglcompressedtextures3tc;
+pub mod webgldebugrendererinfo;
+pub mod webgldepthtexture;
+pub mod webgldrawbuffers;
+pub mod webgllosecontext;
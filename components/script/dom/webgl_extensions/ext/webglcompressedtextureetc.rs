@@ -0,0 +1,127 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use super::{WebGLExtension, WebGLExtensionSpec, WebGLExtensions};
+use crate::dom::bindings::codegen::Bindings::WEBGLCompressedTextureETCBinding;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::webgl_validations::types::{TexCompression, TexCompressionValidation};
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use canvas_traits::webgl::TexFormat;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct WEBGLCompressedTextureETC {
+    reflector_: Reflector,
+}
+
+impl WEBGLCompressedTextureETC {
+    fn new_inherited() -> Self {
+        Self {
+            reflector_: Reflector::new(),
+        }
+    }
+}
+
+impl WebGLExtension for WEBGLCompressedTextureETC {
+    type Extension = Self;
+
+    fn new(ctx: &WebGLRenderingContext) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(Self::new_inherited()),
+            &*ctx.global(),
+            WEBGLCompressedTextureETCBinding::Wrap,
+        )
+    }
+
+    fn spec() -> WebGLExtensionSpec {
+        WebGLExtensionSpec::All
+    }
+
+    fn is_supported(ext: &WebGLExtensions) -> bool {
+        ext.supports_any_gl_extension(&[
+            "GL_ARB_ES3_compatibility",
+            "GL_OES_compressed_ETC2_RGB8_texture",
+        ])
+    }
+
+    fn enable(ext: &WebGLExtensions) {
+        ext.add_tex_compression_formats(&[
+            TexCompression {
+                format: TexFormat::CompressedR11Eac,
+                bytes_per_block: 8,
+                block_width: 4,
+                block_height: 4,
+                validation: TexCompressionValidation::None,
+            },
+            TexCompression {
+                format: TexFormat::CompressedSignedR11Eac,
+                bytes_per_block: 8,
+                block_width: 4,
+                block_height: 4,
+                validation: TexCompressionValidation::None,
+            },
+            TexCompression {
+                format: TexFormat::CompressedRg11Eac,
+                bytes_per_block: 16,
+                block_width: 4,
+                block_height: 4,
+                validation: TexCompressionValidation::None,
+            },
+            TexCompression {
+                format: TexFormat::CompressedSignedRg11Eac,
+                bytes_per_block: 16,
+                block_width: 4,
+                block_height: 4,
+                validation: TexCompressionValidation::None,
+            },
+            TexCompression {
+                format: TexFormat::CompressedRgb8Etc2,
+                bytes_per_block: 8,
+                block_width: 4,
+                block_height: 4,
+                validation: TexCompressionValidation::None,
+            },
+            TexCompression {
+                format: TexFormat::CompressedSrgb8Etc2,
+                bytes_per_block: 8,
+                block_width: 4,
+                block_height: 4,
+                validation: TexCompressionValidation::None,
+            },
+            TexCompression {
+                format: TexFormat::CompressedRgb8PunchthroughAlpha1Etc2,
+                bytes_per_block: 8,
+                block_width: 4,
+                block_height: 4,
+                validation: TexCompressionValidation::None,
+            },
+            TexCompression {
+                format: TexFormat::CompressedSrgb8PunchthroughAlpha1Etc2,
+                bytes_per_block: 8,
+                block_width: 4,
+                block_height: 4,
+                validation: TexCompressionValidation::None,
+            },
+            TexCompression {
+                format: TexFormat::CompressedRgba8Etc2Eac,
+                bytes_per_block: 16,
+                block_width: 4,
+                block_height: 4,
+                validation: TexCompressionValidation::None,
+            },
+            TexCompression {
+                format: TexFormat::CompressedSrgb8Alpha8Etc2Eac,
+                bytes_per_block: 16,
+                block_width: 4,
+                block_height: 4,
+                validation: TexCompressionValidation::None,
+            },
+        ]);
+    }
+
+    fn name() -> &'static str {
+        "WEBGL_compressed_texture_etc"
+    }
+}
@@ -0,0 +1,63 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use super::{constants as webgl, WebGLExtension, WebGLExtensionSpec, WebGLExtensions};
+use crate::dom::bindings::codegen::Bindings::WEBGLDepthTextureBinding::{
+    self, WEBGLDepthTextureConstants,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use canvas_traits::webgl::{is_gles, WebGLVersion};
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct WEBGLDepthTexture {
+    reflector_: Reflector,
+}
+
+impl WEBGLDepthTexture {
+    fn new_inherited() -> Self {
+        Self {
+            reflector_: Reflector::new(),
+        }
+    }
+}
+
+impl WebGLExtension for WEBGLDepthTexture {
+    type Extension = Self;
+
+    fn new(ctx: &WebGLRenderingContext) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(Self::new_inherited()),
+            &*ctx.global(),
+            WEBGLDepthTextureBinding::Wrap,
+        )
+    }
+
+    fn spec() -> WebGLExtensionSpec {
+        WebGLExtensionSpec::Specific(WebGLVersion::WebGL1)
+    }
+
+    fn is_supported(ext: &WebGLExtensions) -> bool {
+        !is_gles() ||
+            ext.supports_any_gl_extension(&[
+                "GL_OES_depth_texture",
+                "GL_ARB_depth_texture",
+                "GL_ANGLE_depth_texture",
+            ])
+    }
+
+    fn enable(ext: &WebGLExtensions) {
+        ext.enable_tex_type(webgl::UNSIGNED_SHORT);
+        ext.enable_tex_type(webgl::UNSIGNED_INT);
+        ext.enable_tex_type(WEBGLDepthTextureConstants::UNSIGNED_INT_24_8_WEBGL);
+        ext.enable_tex_format(webgl::DEPTH_COMPONENT);
+        ext.enable_tex_format(webgl::DEPTH_STENCIL);
+    }
+
+    fn name() -> &'static str {
+        "WEBGL_depth_texture"
+    }
+}
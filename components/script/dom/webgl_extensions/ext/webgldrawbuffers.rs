@@ -0,0 +1,65 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use super::{WebGLExtension, WebGLExtensionSpec, WebGLExtensions};
+use crate::dom::bindings::codegen::Bindings::WEBGLDrawBuffersBinding::{
+    self, WEBGLDrawBuffersConstants, WEBGLDrawBuffersMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use canvas_traits::webgl::{is_gles, WebGLVersion};
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct WEBGLDrawBuffers {
+    reflector_: Reflector,
+    ctx: Dom<WebGLRenderingContext>,
+}
+
+impl WEBGLDrawBuffers {
+    fn new_inherited(ctx: &WebGLRenderingContext) -> Self {
+        Self {
+            reflector_: Reflector::new(),
+            ctx: Dom::from_ref(ctx),
+        }
+    }
+}
+
+impl WEBGLDrawBuffersMethods for WEBGLDrawBuffers {
+    // https://www.khronos.org/registry/webgl/extensions/WEBGL_draw_buffers/
+    fn DrawBuffersWEBGL(&self, buffers: Vec<u32>) {
+        self.ctx.draw_buffers(buffers);
+    }
+}
+
+impl WebGLExtension for WEBGLDrawBuffers {
+    type Extension = Self;
+
+    fn new(ctx: &WebGLRenderingContext) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(Self::new_inherited(ctx)),
+            &*ctx.global(),
+            WEBGLDrawBuffersBinding::Wrap,
+        )
+    }
+
+    fn spec() -> WebGLExtensionSpec {
+        WebGLExtensionSpec::Specific(WebGLVersion::WebGL1)
+    }
+
+    fn is_supported(ext: &WebGLExtensions) -> bool {
+        !is_gles() || ext.supports_any_gl_extension(&["GL_EXT_draw_buffers", "GL_NV_draw_buffers"])
+    }
+
+    fn enable(ext: &WebGLExtensions) {
+        ext.enable_draw_buffers();
+        ext.enable_get_parameter_name(WEBGLDrawBuffersConstants::MAX_COLOR_ATTACHMENTS_WEBGL);
+        ext.enable_get_parameter_name(WEBGLDrawBuffersConstants::MAX_DRAW_BUFFERS_WEBGL);
+    }
+
+    fn name() -> &'static str {
+        "WEBGL_draw_buffers"
+    }
+}
@@ -0,0 +1,66 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use super::{WebGLExtension, WebGLExtensionSpec, WebGLExtensions};
+use crate::dom::bindings::codegen::Bindings::WEBGLLoseContextBinding::{
+    self, WEBGLLoseContextMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct WEBGLLoseContext {
+    reflector_: Reflector,
+    ctx: Dom<WebGLRenderingContext>,
+}
+
+impl WEBGLLoseContext {
+    fn new_inherited(ctx: &WebGLRenderingContext) -> Self {
+        Self {
+            reflector_: Reflector::new(),
+            ctx: Dom::from_ref(ctx),
+        }
+    }
+}
+
+impl WEBGLLoseContextMethods for WEBGLLoseContext {
+    // https://www.khronos.org/registry/webgl/extensions/WEBGL_lose_context/
+    fn LoseContext(&self) {
+        self.ctx.lose_context();
+    }
+
+    // https://www.khronos.org/registry/webgl/extensions/WEBGL_lose_context/
+    fn RestoreContext(&self) {
+        handle_potential_webgl_error!(self.ctx, self.ctx.restore_context());
+    }
+}
+
+impl WebGLExtension for WEBGLLoseContext {
+    type Extension = Self;
+
+    fn new(ctx: &WebGLRenderingContext) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(Self::new_inherited(ctx)),
+            &*ctx.global(),
+            WEBGLLoseContextBinding::Wrap,
+        )
+    }
+
+    fn spec() -> WebGLExtensionSpec {
+        WebGLExtensionSpec::All
+    }
+
+    fn is_supported(_ext: &WebGLExtensions) -> bool {
+        // Context loss is simulated on the script side, so this works everywhere.
+        true
+    }
+
+    fn enable(_ext: &WebGLExtensions) {}
+
+    fn name() -> &'static str {
+        "WEBGL_lose_context"
+    }
+}
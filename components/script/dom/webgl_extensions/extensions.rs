@@ -7,14 +7,19 @@ use super::{ext, WebGLExtension, WebGLExtensionSpec};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::ANGLEInstancedArraysBinding::ANGLEInstancedArraysConstants;
 use crate::dom::bindings::codegen::Bindings::EXTTextureFilterAnisotropicBinding::EXTTextureFilterAnisotropicConstants;
+use crate::dom::bindings::codegen::Bindings::EXTsRGBBinding::EXTsRGBConstants;
 use crate::dom::bindings::codegen::Bindings::OESStandardDerivativesBinding::OESStandardDerivativesConstants;
 use crate::dom::bindings::codegen::Bindings::OESTextureHalfFloatBinding::OESTextureHalfFloatConstants;
 use crate::dom::bindings::codegen::Bindings::OESVertexArrayObjectBinding::OESVertexArrayObjectConstants;
+use crate::dom::bindings::codegen::Bindings::WEBGLDebugRendererInfoBinding::WEBGLDebugRendererInfoConstants;
+use crate::dom::bindings::codegen::Bindings::WEBGLDepthTextureBinding::WEBGLDepthTextureConstants;
+use crate::dom::bindings::codegen::Bindings::WEBGLDrawBuffersBinding::WEBGLDrawBuffersConstants;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextConstants as constants;
 use crate::dom::bindings::trace::JSTraceable;
 use crate::dom::extcolorbufferhalffloat::EXTColorBufferHalfFloat;
 use crate::dom::oestexturefloat::OESTextureFloat;
 use crate::dom::oestexturehalffloat::OESTextureHalfFloat;
+use crate::dom::webgl_validations::types::TexCompression;
 use crate::dom::webglcolorbufferfloat::WEBGLColorBufferFloat;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use canvas_traits::webgl::WebGLVersion;
@@ -29,11 +34,29 @@ use std::ptr::NonNull;
 // Data types that are implemented for texImage2D and texSubImage2D in a WebGL 1.0 context
 // but must trigger a InvalidValue error until the related WebGL Extensions are enabled.
 // Example: https://www.khronos.org/registry/webgl/extensions/OES_texture_float/
-const DEFAULT_DISABLED_TEX_TYPES_WEBGL1: [GLenum; 2] = [
+const DEFAULT_DISABLED_TEX_TYPES_WEBGL1: [GLenum; 5] = [
     constants::FLOAT,
     OESTextureHalfFloatConstants::HALF_FLOAT_OES,
+    constants::UNSIGNED_SHORT,
+    constants::UNSIGNED_INT,
+    WEBGLDepthTextureConstants::UNSIGNED_INT_24_8_WEBGL,
 ];
 
+// Formats that are implemented for texImage2D in a WebGL 1.0 context
+// but must trigger a InvalidEnum error until the related WebGL Extensions are enabled.
+// Example: https://www.khronos.org/registry/webgl/extensions/WEBGL_depth_texture/
+const DEFAULT_DISABLED_TEX_FORMATS_WEBGL1: [GLenum; 4] = [
+    constants::DEPTH_COMPONENT,
+    constants::DEPTH_STENCIL,
+    EXTsRGBConstants::SRGB_EXT,
+    EXTsRGBConstants::SRGB_ALPHA_EXT,
+];
+
+// The unsized sRGB formats of EXT_sRGB are never accepted by a WebGL 2.0 context,
+// it has sized sRGB formats instead.
+const DEFAULT_DISABLED_TEX_FORMATS_WEBGL2: [GLenum; 2] =
+    [EXTsRGBConstants::SRGB_EXT, EXTsRGBConstants::SRGB_ALPHA_EXT];
+
 // Data types that are implemented for textures in WebGLRenderingContext
 // but not allowed to use with linear filtering until the related WebGL Extensions are enabled.
 // Example: https://www.khronos.org/registry/webgl/extensions/OES_texture_float_linear/
@@ -45,10 +68,22 @@ const DEFAULT_NOT_FILTERABLE_TEX_TYPES: [GLenum; 2] = [
 // Param names that are implemented for glGetParameter in a WebGL 1.0 context
 // but must trigger a InvalidEnum error until the related WebGL Extensions are enabled.
 // Example: https://www.khronos.org/registry/webgl/extensions/OES_standard_derivatives/
-const DEFAULT_DISABLED_GET_PARAMETER_NAMES_WEBGL1: [GLenum; 3] = [
+const DEFAULT_DISABLED_GET_PARAMETER_NAMES_WEBGL1: [GLenum; 7] = [
     EXTTextureFilterAnisotropicConstants::MAX_TEXTURE_MAX_ANISOTROPY_EXT,
     OESStandardDerivativesConstants::FRAGMENT_SHADER_DERIVATIVE_HINT_OES,
     OESVertexArrayObjectConstants::VERTEX_ARRAY_BINDING_OES,
+    WEBGLDebugRendererInfoConstants::UNMASKED_VENDOR_WEBGL,
+    WEBGLDebugRendererInfoConstants::UNMASKED_RENDERER_WEBGL,
+    WEBGLDrawBuffersConstants::MAX_COLOR_ATTACHMENTS_WEBGL,
+    WEBGLDrawBuffersConstants::MAX_DRAW_BUFFERS_WEBGL,
+];
+
+// Param names that are implemented for glGetParameter in a WebGL 2.0 context
+// but must trigger a InvalidEnum error until the related WebGL Extensions are enabled.
+// Example: https://www.khronos.org/registry/webgl/extensions/WEBGL_debug_renderer_info/
+const DEFAULT_DISABLED_GET_PARAMETER_NAMES_WEBGL2: [GLenum; 2] = [
+    WEBGLDebugRendererInfoConstants::UNMASKED_VENDOR_WEBGL,
+    WEBGLDebugRendererInfoConstants::UNMASKED_RENDERER_WEBGL,
 ];
 
 // Param names that are implemented for glGetTexParameter in a WebGL 1.0 context
@@ -68,8 +103,11 @@ const DEFAULT_DISABLED_GET_VERTEX_ATTRIB_NAMES_WEBGL1: [GLenum; 1] =
 struct WebGLExtensionFeatures {
     gl_extensions: FnvHashSet<String>,
     disabled_tex_types: FnvHashSet<GLenum>,
+    disabled_tex_formats: FnvHashSet<GLenum>,
     not_filterable_tex_types: FnvHashSet<GLenum>,
     effective_tex_internal_formats: FnvHashMap<TexFormatType, u32>,
+    /// WebGL compressed texture formats enabled by extensions.
+    tex_compression_formats: FnvHashMap<GLenum, TexCompression>,
     /// WebGL Hint() targets enabled by extensions.
     hint_targets: FnvHashSet<GLenum>,
     /// WebGL GetParameter() names enabled by extensions.
@@ -82,20 +120,28 @@ struct WebGLExtensionFeatures {
     element_index_uint_enabled: bool,
     /// WebGL EXT_blend_minmax extension.
     blend_minmax_enabled: bool,
+    /// WebGL WEBGL_draw_buffers extension.
+    draw_buffers_enabled: bool,
 }
 
 impl WebGLExtensionFeatures {
     fn new(webgl_version: WebGLVersion) -> Self {
         let (
             disabled_tex_types,
+            disabled_tex_formats,
             disabled_get_parameter_names,
             disabled_get_tex_parameter_names,
             disabled_get_vertex_attrib_names,
             element_index_uint_enabled,
             blend_minmax_enabled,
+            draw_buffers_enabled,
         ) = match webgl_version {
             WebGLVersion::WebGL1 => (
                 DEFAULT_DISABLED_TEX_TYPES_WEBGL1.iter().cloned().collect(),
+                DEFAULT_DISABLED_TEX_FORMATS_WEBGL1
+                    .iter()
+                    .cloned()
+                    .collect(),
                 DEFAULT_DISABLED_GET_PARAMETER_NAMES_WEBGL1
                     .iter()
                     .cloned()
@@ -110,27 +156,39 @@ impl WebGLExtensionFeatures {
                     .collect(),
                 false,
                 false,
+                false,
             ),
             WebGLVersion::WebGL2 => (
                 Default::default(),
-                Default::default(),
+                DEFAULT_DISABLED_TEX_FORMATS_WEBGL2
+                    .iter()
+                    .cloned()
+                    .collect(),
+                DEFAULT_DISABLED_GET_PARAMETER_NAMES_WEBGL2
+                    .iter()
+                    .cloned()
+                    .collect(),
                 Default::default(),
                 Default::default(),
                 true,
                 true,
+                true,
             ),
         };
         Self {
             gl_extensions: Default::default(),
             disabled_tex_types,
+            disabled_tex_formats,
             not_filterable_tex_types: DEFAULT_NOT_FILTERABLE_TEX_TYPES.iter().cloned().collect(),
             effective_tex_internal_formats: Default::default(),
+            tex_compression_formats: Default::default(),
             hint_targets: Default::default(),
             disabled_get_parameter_names,
             disabled_get_tex_parameter_names,
             disabled_get_vertex_attrib_names,
             element_index_uint_enabled,
             blend_minmax_enabled,
+            draw_buffers_enabled,
         }
     }
 }
@@ -240,6 +298,48 @@ impl WebGLExtensions {
             .is_none()
     }
 
+    pub fn enable_tex_format(&self, format: GLenum) {
+        self.features
+            .borrow_mut()
+            .disabled_tex_formats
+            .remove(&format);
+    }
+
+    pub fn is_tex_format_enabled(&self, format: GLenum) -> bool {
+        !self
+            .features
+            .borrow()
+            .disabled_tex_formats
+            .contains(&format)
+    }
+
+    pub fn add_tex_compression_formats(&self, formats: &[TexCompression]) {
+        let formats = formats
+            .iter()
+            .map(|&compression| (compression.format.as_gl_constant(), compression));
+        self.features
+            .borrow_mut()
+            .tex_compression_formats
+            .extend(formats);
+    }
+
+    pub fn get_tex_compression_format(&self, format: GLenum) -> Option<TexCompression> {
+        self.features
+            .borrow()
+            .tex_compression_formats
+            .get(&format)
+            .cloned()
+    }
+
+    pub fn get_tex_compression_ids(&self) -> Vec<GLenum> {
+        self.features
+            .borrow()
+            .tex_compression_formats
+            .keys()
+            .cloned()
+            .collect()
+    }
+
     pub fn add_effective_tex_internal_format(
         &self,
         source_internal_format: u32,
@@ -339,7 +439,9 @@ impl WebGLExtensions {
         self.register::<ext::angleinstancedarrays::ANGLEInstancedArrays>();
         self.register::<ext::extblendminmax::EXTBlendMinmax>();
         self.register::<ext::extcolorbufferhalffloat::EXTColorBufferHalfFloat>();
+        self.register::<ext::extfragdepth::EXTFragDepth>();
         self.register::<ext::extshadertexturelod::EXTShaderTextureLod>();
+        self.register::<ext::extsrgb::EXTsRGB>();
         self.register::<ext::exttexturefilteranisotropic::EXTTextureFilterAnisotropic>();
         self.register::<ext::oeselementindexuint::OESElementIndexUint>();
         self.register::<ext::oesstandardderivatives::OESStandardDerivatives>();
@@ -349,6 +451,12 @@ impl WebGLExtensions {
         self.register::<ext::oestexturehalffloatlinear::OESTextureHalfFloatLinear>();
         self.register::<ext::oesvertexarrayobject::OESVertexArrayObject>();
         self.register::<ext::webglcolorbufferfloat::WEBGLColorBufferFloat>();
+        self.register::<ext::webglcompressedtextureetc::WEBGLCompressedTextureETC>();
+        self.register::<ext::webglcompressedtextures3tc::WEBGLCompressedTextureS3TC>();
+        self.register::<ext::webgldebugrendererinfo::WEBGLDebugRendererInfo>();
+        self.register::<ext::webgldepthtexture::WEBGLDepthTexture>();
+        self.register::<ext::webgldrawbuffers::WEBGLDrawBuffers>();
+        self.register::<ext::webgllosecontext::WEBGLLoseContext>();
     }
 
     pub fn enable_element_index_uint(&self) {
@@ -367,6 +475,14 @@ impl WebGLExtensions {
         self.features.borrow().blend_minmax_enabled
     }
 
+    pub fn enable_draw_buffers(&self) {
+        self.features.borrow_mut().draw_buffers_enabled = true;
+    }
+
+    pub fn is_draw_buffers_enabled(&self) -> bool {
+        self.features.borrow().draw_buffers_enabled
+    }
+
     pub fn is_float_buffer_renderable(&self) -> bool {
         self.is_enabled::<WEBGLColorBufferFloat>() || self.is_enabled::<OESTextureFloat>()
     }
@@ -11,3 +11,4 @@ pub trait WebGLValidator {
 
 pub mod tex_image_2d;
 pub mod types;
+pub mod webgl2;
@@ -2,12 +2,12 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use super::types::TexImageTarget;
+use super::types::{TexCompression, TexCompressionValidation, TexImageTarget};
 use super::WebGLValidator;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
-use crate::dom::webgltexture::WebGLTexture;
-use canvas_traits::webgl::{TexDataType, TexFormat, WebGLError::*};
+use crate::dom::webgltexture::{ImageInfo, WebGLTexture};
+use canvas_traits::webgl::{TexDataType, TexFormat, WebGLError::*, WebGLVersion};
 use std::{self, fmt};
 
 /// The errors that the texImage* family of functions can generate.
@@ -40,6 +40,18 @@ pub enum TexImageValidationError {
     InvalidBorder,
     /// Expected a power of two texture.
     NonPotTexture,
+    /// The format is not supported for this function.
+    UnsupportedFormatForFunction,
+    /// The target and level don't allow a depth texture.
+    InvalidTargetForDepthTexture,
+    /// The data length doesn't match the size of the compressed image.
+    InvalidCompressedDataLength,
+    /// The dimensions of the compressed image are not block aligned.
+    InvalidCompressedTextureDimensions,
+    /// The region of a compressed sub-image is not block aligned.
+    InvalidCompressedSubImageRegion,
+    /// An invalid offset was passed for a sub-image.
+    InvalidOffsets,
 }
 
 impl std::error::Error for TexImageValidationError {
@@ -61,6 +73,14 @@ impl std::error::Error for TexImageValidationError {
             InvalidTypeForFormat => "Invalid type for the given format",
             InvalidBorder => "Invalid border",
             NonPotTexture => "Expected a power of two texture",
+            UnsupportedFormatForFunction => "Format not supported for this function",
+            InvalidTargetForDepthTexture => "Invalid target or level for a depth texture",
+            InvalidCompressedDataLength => "Invalid data length for the compressed image",
+            InvalidCompressedTextureDimensions => {
+                "Invalid dimensions were given for a compressed texture"
+            },
+            InvalidCompressedSubImageRegion => "Compressed sub-image region is not block aligned",
+            InvalidOffsets => "Invalid offsets for the sub-image",
         }
     }
 }
@@ -147,6 +167,24 @@ impl<'a> WebGLValidator for CommonTexImage2DValidator<'a> {
             },
         };
 
+        // Depth, sRGB and compressed formats are only accepted once the
+        // related extension is enabled.
+        if !self
+            .context
+            .extension_manager()
+            .is_tex_format_enabled(internal_format.as_gl_constant())
+        {
+            self.context.webgl_error(InvalidEnum);
+            return Err(TexImageValidationError::InvalidTextureFormat);
+        }
+
+        // Compressed formats must go through compressedTexImage2D.
+        // https://www.khronos.org/registry/webgl/specs/latest/1.0/#COMPRESSED_TEXTURE_SUPPORT
+        if internal_format.is_compressed() {
+            self.context.webgl_error(InvalidEnum);
+            return Err(TexImageValidationError::UnsupportedFormatForFunction);
+        }
+
         // GL_INVALID_VALUE is generated if target is one of the six cube map 2D
         // image targets and the width and height parameters are not equal.
         if target.is_cubic() && self.width != self.height {
@@ -337,7 +375,7 @@ impl<'a> WebGLValidator for TexImage2DValidator<'a> {
             {
                 context.webgl_error(InvalidOperation);
                 return Err(TexImageValidationError::InvalidTypeForFormat);
-            }
+            },
             TexDataType::UnsignedShort565 if format != TexFormat::RGB => {
                 context.webgl_error(InvalidOperation);
                 return Err(TexImageValidationError::InvalidTypeForFormat);
@@ -345,6 +383,39 @@ impl<'a> WebGLValidator for TexImage2DValidator<'a> {
             _ => {},
         }
 
+        // https://www.khronos.org/registry/webgl/extensions/WEBGL_depth_texture/
+        let type_matches_depth_format = match (format, data_type) {
+            (TexFormat::DepthComponent, TexDataType::UnsignedShort) |
+            (TexFormat::DepthComponent, TexDataType::UnsignedInt) |
+            (TexFormat::DepthStencil, TexDataType::UnsignedInt248) => true,
+            (TexFormat::DepthComponent, _) | (TexFormat::DepthStencil, _) => false,
+            (_, TexDataType::UnsignedShort) |
+            (_, TexDataType::UnsignedInt) |
+            (_, TexDataType::UnsignedInt248) => false,
+            _ => true,
+        };
+        if !type_matches_depth_format {
+            context.webgl_error(InvalidOperation);
+            return Err(TexImageValidationError::InvalidTypeForFormat);
+        }
+
+        if format.is_depth() &&
+            context.webgl_version() == WebGLVersion::WebGL1 &&
+            (target != TexImageTarget::Texture2D || level != 0)
+        {
+            context.webgl_error(InvalidOperation);
+            return Err(TexImageValidationError::InvalidTargetForDepthTexture);
+        }
+
+        // https://www.khronos.org/registry/webgl/extensions/EXT_sRGB/
+        match format {
+            TexFormat::SRGB | TexFormat::SRGBAlpha if data_type != TexDataType::UnsignedByte => {
+                context.webgl_error(InvalidOperation);
+                return Err(TexImageValidationError::InvalidTypeForFormat);
+            },
+            _ => {},
+        }
+
         Ok(TexImage2DValidatorResult {
             width: width,
             height: height,
@@ -357,3 +428,276 @@ impl<'a> WebGLValidator for TexImage2DValidator<'a> {
         })
     }
 }
+
+pub struct CommonCompressedTexImage2DValidator<'a> {
+    common_validator: CommonTexImage2DValidator<'a>,
+    compression_format: u32,
+    data_len: usize,
+}
+
+impl<'a> CommonCompressedTexImage2DValidator<'a> {
+    pub fn new(
+        context: &'a WebGLRenderingContext,
+        target: u32,
+        level: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        compression_format: u32,
+        data_len: usize,
+    ) -> Self {
+        // NB: We use a dummy (valid) format in order to reuse the common
+        // validations, the compression format is checked separately.
+        CommonCompressedTexImage2DValidator {
+            common_validator: CommonTexImage2DValidator::new(
+                context,
+                target,
+                level,
+                TexFormat::RGBA.as_gl_constant(),
+                width,
+                height,
+                border,
+            ),
+            compression_format,
+            data_len,
+        }
+    }
+}
+
+pub struct CommonCompressedTexImage2DValidatorResult {
+    pub texture: DomRoot<WebGLTexture>,
+    pub target: TexImageTarget,
+    pub level: u32,
+    pub width: u32,
+    pub height: u32,
+    pub compression: TexCompression,
+}
+
+fn valid_s3tc_dimension(level: u32, side_length: u32, block_size: u32) -> bool {
+    (side_length % block_size == 0) || (level > 0 && [0, 1, 2].contains(&side_length))
+}
+
+fn valid_compressed_data_len(
+    data_len: usize,
+    width: u32,
+    height: u32,
+    compression: &TexCompression,
+) -> bool {
+    let block_width = compression.block_width as u32;
+    let block_height = compression.block_height as u32;
+
+    let required_blocks_hor = (width + block_width - 1) / block_width;
+    let required_blocks_ver = (height + block_height - 1) / block_height;
+    let required_blocks = required_blocks_hor * required_blocks_ver;
+
+    let required_bytes = required_blocks * compression.bytes_per_block as u32;
+    data_len == required_bytes as usize
+}
+
+fn is_subimage_blockaligned(
+    xoffset: u32,
+    yoffset: u32,
+    width: u32,
+    height: u32,
+    compression: &TexCompression,
+    tex_info: &ImageInfo,
+) -> bool {
+    let block_width = compression.block_width as u32;
+    let block_height = compression.block_height as u32;
+
+    (xoffset % block_width == 0 && yoffset % block_height == 0) &&
+        (width % block_width == 0 || xoffset + width == tex_info.width()) &&
+        (height % block_height == 0 || yoffset + height == tex_info.height())
+}
+
+impl<'a> WebGLValidator for CommonCompressedTexImage2DValidator<'a> {
+    type Error = TexImageValidationError;
+    type ValidatedOutput = CommonCompressedTexImage2DValidatorResult;
+
+    fn validate(self) -> Result<Self::ValidatedOutput, TexImageValidationError> {
+        let context = self.common_validator.context;
+        let compression_format = self.compression_format;
+
+        // GL_INVALID_ENUM is generated if the format is not one of the
+        // compressed formats enabled by an extension.
+        let compression = match context
+            .extension_manager()
+            .get_tex_compression_format(compression_format)
+        {
+            Some(compression) => compression,
+            None => {
+                context.webgl_error(InvalidEnum);
+                return Err(TexImageValidationError::InvalidTextureFormat);
+            },
+        };
+
+        let CommonTexImage2DValidatorResult {
+            texture,
+            target,
+            level,
+            width,
+            height,
+            ..
+        } = self.common_validator.validate()?;
+
+        // GL_INVALID_VALUE is generated if the data length doesn't match the
+        // size of the image.
+        if !valid_compressed_data_len(self.data_len, width, height, &compression) {
+            context.webgl_error(InvalidValue);
+            return Err(TexImageValidationError::InvalidCompressedDataLength);
+        }
+
+        Ok(CommonCompressedTexImage2DValidatorResult {
+            texture,
+            target,
+            level,
+            width,
+            height,
+            compression,
+        })
+    }
+}
+
+pub struct CompressedTexImage2DValidator<'a> {
+    compression_validator: CommonCompressedTexImage2DValidator<'a>,
+}
+
+impl<'a> CompressedTexImage2DValidator<'a> {
+    pub fn new(
+        context: &'a WebGLRenderingContext,
+        target: u32,
+        level: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        compression_format: u32,
+        data_len: usize,
+    ) -> Self {
+        CompressedTexImage2DValidator {
+            compression_validator: CommonCompressedTexImage2DValidator::new(
+                context,
+                target,
+                level,
+                width,
+                height,
+                border,
+                compression_format,
+                data_len,
+            ),
+        }
+    }
+}
+
+/// CompressedTexImage2D validator as per
+/// <https://www.khronos.org/registry/webgl/specs/latest/1.0/#COMPRESSED_TEXTURE_SUPPORT>
+impl<'a> WebGLValidator for CompressedTexImage2DValidator<'a> {
+    type Error = TexImageValidationError;
+    type ValidatedOutput = CommonCompressedTexImage2DValidatorResult;
+
+    fn validate(self) -> Result<Self::ValidatedOutput, TexImageValidationError> {
+        let context = self.compression_validator.common_validator.context;
+        let result = self.compression_validator.validate()?;
+
+        // https://www.khronos.org/registry/webgl/extensions/WEBGL_compressed_texture_s3tc/
+        if result.compression.validation == TexCompressionValidation::S3TC {
+            let block_width = result.compression.block_width as u32;
+            let block_height = result.compression.block_height as u32;
+            if !valid_s3tc_dimension(result.level, result.width, block_width) ||
+                !valid_s3tc_dimension(result.level, result.height, block_height)
+            {
+                context.webgl_error(InvalidOperation);
+                return Err(TexImageValidationError::InvalidCompressedTextureDimensions);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+pub struct CompressedTexSubImage2DValidator<'a> {
+    compression_validator: CommonCompressedTexImage2DValidator<'a>,
+    xoffset: i32,
+    yoffset: i32,
+}
+
+impl<'a> CompressedTexSubImage2DValidator<'a> {
+    pub fn new(
+        context: &'a WebGLRenderingContext,
+        target: u32,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        width: i32,
+        height: i32,
+        compression_format: u32,
+        data_len: usize,
+    ) -> Self {
+        CompressedTexSubImage2DValidator {
+            compression_validator: CommonCompressedTexImage2DValidator::new(
+                context,
+                target,
+                level,
+                width,
+                height,
+                0,
+                compression_format,
+                data_len,
+            ),
+            xoffset,
+            yoffset,
+        }
+    }
+}
+
+/// CompressedTexSubImage2D validator as per
+/// <https://www.khronos.org/registry/webgl/specs/latest/1.0/#COMPRESSED_TEXTURE_SUPPORT>
+impl<'a> WebGLValidator for CompressedTexSubImage2DValidator<'a> {
+    type Error = TexImageValidationError;
+    type ValidatedOutput = CommonCompressedTexImage2DValidatorResult;
+
+    fn validate(self) -> Result<Self::ValidatedOutput, TexImageValidationError> {
+        let context = self.compression_validator.common_validator.context;
+        let result = self.compression_validator.validate()?;
+
+        let tex_info = result
+            .texture
+            .image_info_for_target(&result.target, result.level);
+
+        // GL_INVALID_VALUE is generated if:
+        //   - xoffset or yoffset is less than 0
+        //   - x offset plus the width is greater than the texture width
+        //   - y offset plus the height is greater than the texture height
+        if self.xoffset < 0 ||
+            (self.xoffset as u32 + result.width) > tex_info.width() ||
+            self.yoffset < 0 ||
+            (self.yoffset as u32 + result.height) > tex_info.height()
+        {
+            context.webgl_error(InvalidValue);
+            return Err(TexImageValidationError::InvalidOffsets);
+        }
+
+        // GL_INVALID_OPERATION is generated if format does not match the
+        // format of the texture image.
+        if tex_info.internal_format() != Some(result.compression.format) {
+            context.webgl_error(InvalidOperation);
+            return Err(TexImageValidationError::TextureFormatMismatch);
+        }
+
+        // https://www.khronos.org/registry/webgl/extensions/WEBGL_compressed_texture_s3tc/
+        if result.compression.validation == TexCompressionValidation::S3TC {
+            if !is_subimage_blockaligned(
+                self.xoffset as u32,
+                self.yoffset as u32,
+                result.width,
+                result.height,
+                &result.compression,
+                &tex_info,
+            ) {
+                context.webgl_error(InvalidOperation);
+                return Err(TexImageValidationError::InvalidCompressedSubImageRegion);
+            }
+        }
+
+        Ok(result)
+    }
+}
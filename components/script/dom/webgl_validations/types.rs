@@ -2,8 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextConstants;
 use canvas_traits::gl_enums;
+use canvas_traits::webgl::TexFormat;
 
 gl_enums! {
     pub enum TexImageTarget {
@@ -25,3 +27,44 @@ impl TexImageTarget {
         }
     }
 }
+
+/// The block layout of a compressed texture format.
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf)]
+pub struct TexCompression {
+    pub format: TexFormat,
+    pub bytes_per_block: u8,
+    pub block_width: u8,
+    pub block_height: u8,
+    pub validation: TexCompressionValidation,
+}
+
+/// Additional constraints some compressed formats put on the dimensions of
+/// the uploaded images.
+#[derive(Clone, Copy, Debug, Eq, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum TexCompressionValidation {
+    None,
+    /// https://www.khronos.org/registry/webgl/extensions/WEBGL_compressed_texture_s3tc/
+    S3TC,
+}
+
+/// The `EXT_disjoint_timer_query_webgl2` query target, which doesn't have an
+/// IDL constant we could refer to.
+pub mod ext_disjoint_timer_query {
+    pub const TIME_ELAPSED_EXT: u32 = 0x88BF;
+}
+
+gl_enums! {
+    pub enum QueryTarget {
+        AnySamplesPassed = WebGL2RenderingContextConstants::ANY_SAMPLES_PASSED,
+        AnySamplesPassedConservative =
+            WebGL2RenderingContextConstants::ANY_SAMPLES_PASSED_CONSERVATIVE,
+        TransformFeedbackPrimitivesWritten =
+            WebGL2RenderingContextConstants::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN,
+        TimeElapsed = ext_disjoint_timer_query::TIME_ELAPSED_EXT,
+    }
+
+    pub enum IndexedBufferTarget {
+        TransformFeedbackBuffer = WebGL2RenderingContextConstants::TRANSFORM_FEEDBACK_BUFFER,
+        UniformBuffer = WebGL2RenderingContextConstants::UNIFORM_BUFFER,
+    }
+}
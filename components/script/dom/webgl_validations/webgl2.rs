@@ -0,0 +1,234 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Argument validation for the WebGL 2 sampler, sync, query, transform
+//! feedback and uniform buffer entry points.
+
+use super::types::{IndexedBufferTarget, QueryTarget};
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants as constants;
+use canvas_traits::webgl::WebGLError::*;
+use canvas_traits::webgl::{GLLimits, WebGLResult};
+
+/// The value passed to `samplerParameter{i,f}`.
+#[derive(Clone, Copy, Debug)]
+pub enum SamplerParameterValue {
+    Int(i32),
+    Float(f32),
+}
+
+impl SamplerParameterValue {
+    fn as_enum(self) -> u32 {
+        match self {
+            SamplerParameterValue::Int(value) => value as u32,
+            SamplerParameterValue::Float(value) => value as u32,
+        }
+    }
+}
+
+/// Whether `pname` is a sampler parameter whose value is a float.
+pub fn is_float_sampler_parameter(pname: u32) -> WebGLResult<bool> {
+    match pname {
+        constants::TEXTURE_MIN_LOD | constants::TEXTURE_MAX_LOD => Ok(true),
+        constants::TEXTURE_MAG_FILTER |
+        constants::TEXTURE_MIN_FILTER |
+        constants::TEXTURE_WRAP_S |
+        constants::TEXTURE_WRAP_T |
+        constants::TEXTURE_WRAP_R |
+        constants::TEXTURE_COMPARE_MODE |
+        constants::TEXTURE_COMPARE_FUNC => Ok(false),
+        _ => Err(InvalidEnum),
+    }
+}
+
+/// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+pub fn validate_sampler_parameter(pname: u32, value: SamplerParameterValue) -> WebGLResult<()> {
+    if is_float_sampler_parameter(pname)? {
+        return Ok(());
+    }
+    let valid = match (pname, value.as_enum()) {
+        (constants::TEXTURE_MAG_FILTER, constants::NEAREST) |
+        (constants::TEXTURE_MAG_FILTER, constants::LINEAR) => true,
+        (constants::TEXTURE_MIN_FILTER, constants::NEAREST) |
+        (constants::TEXTURE_MIN_FILTER, constants::LINEAR) |
+        (constants::TEXTURE_MIN_FILTER, constants::NEAREST_MIPMAP_NEAREST) |
+        (constants::TEXTURE_MIN_FILTER, constants::LINEAR_MIPMAP_NEAREST) |
+        (constants::TEXTURE_MIN_FILTER, constants::NEAREST_MIPMAP_LINEAR) |
+        (constants::TEXTURE_MIN_FILTER, constants::LINEAR_MIPMAP_LINEAR) => true,
+        (constants::TEXTURE_WRAP_S, value) |
+        (constants::TEXTURE_WRAP_T, value) |
+        (constants::TEXTURE_WRAP_R, value) => match value {
+            constants::CLAMP_TO_EDGE | constants::MIRRORED_REPEAT | constants::REPEAT => true,
+            _ => false,
+        },
+        (constants::TEXTURE_COMPARE_MODE, constants::NONE) |
+        (constants::TEXTURE_COMPARE_MODE, constants::COMPARE_REF_TO_TEXTURE) => true,
+        (constants::TEXTURE_COMPARE_FUNC, value) => match value {
+            constants::LEQUAL |
+            constants::GEQUAL |
+            constants::LESS |
+            constants::GREATER |
+            constants::EQUAL |
+            constants::NOTEQUAL |
+            constants::ALWAYS |
+            constants::NEVER => true,
+            _ => false,
+        },
+        _ => false,
+    };
+    if !valid {
+        return Err(InvalidEnum);
+    }
+    Ok(())
+}
+
+/// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.12
+pub fn validate_query_target(
+    target: u32,
+    timer_queries_supported: bool,
+) -> WebGLResult<QueryTarget> {
+    let target = QueryTarget::from_gl_constant(target).ok_or(InvalidEnum)?;
+    if target == QueryTarget::TimeElapsed && !timer_queries_supported {
+        return Err(InvalidEnum);
+    }
+    Ok(target)
+}
+
+/// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.14
+pub fn validate_fence_sync(condition: u32, flags: u32) -> WebGLResult<()> {
+    if condition != constants::SYNC_GPU_COMMANDS_COMPLETE {
+        return Err(InvalidEnum);
+    }
+    if flags != 0 {
+        return Err(InvalidValue);
+    }
+    Ok(())
+}
+
+/// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.14
+pub fn validate_client_wait_sync(flags: u32, timeout: u64, max_timeout: u64) -> WebGLResult<()> {
+    if flags & !constants::SYNC_FLUSH_COMMANDS_BIT != 0 {
+        return Err(InvalidValue);
+    }
+    if timeout > max_timeout {
+        return Err(InvalidOperation);
+    }
+    Ok(())
+}
+
+/// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.14
+pub fn validate_wait_sync(flags: u32, timeout: i64) -> WebGLResult<()> {
+    if flags != 0 || timeout != constants::TIMEOUT_IGNORED {
+        return Err(InvalidValue);
+    }
+    Ok(())
+}
+
+/// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+pub fn validate_transform_feedback_primitive_mode(mode: u32) -> WebGLResult<()> {
+    match mode {
+        constants::POINTS | constants::LINES | constants::TRIANGLES => Ok(()),
+        _ => Err(InvalidEnum),
+    }
+}
+
+/// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+pub fn validate_transform_feedback_buffer_mode(
+    buffer_mode: u32,
+    varyings: usize,
+    limits: &GLLimits,
+) -> WebGLResult<()> {
+    match buffer_mode {
+        constants::INTERLEAVED_ATTRIBS => Ok(()),
+        constants::SEPARATE_ATTRIBS
+            if varyings <= limits.max_transform_feedback_separate_attribs as usize =>
+        {
+            Ok(())
+        },
+        constants::SEPARATE_ATTRIBS => Err(InvalidValue),
+        _ => Err(InvalidEnum),
+    }
+}
+
+/// Returns the number of indexed binding points `target` has.
+pub fn indexed_binding_count(target: IndexedBufferTarget, limits: &GLLimits) -> u32 {
+    match target {
+        IndexedBufferTarget::TransformFeedbackBuffer => {
+            limits.max_transform_feedback_separate_attribs
+        },
+        IndexedBufferTarget::UniformBuffer => limits.max_uniform_buffer_bindings,
+    }
+}
+
+/// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+pub fn validate_indexed_buffer_binding(
+    target: u32,
+    index: u32,
+    limits: &GLLimits,
+) -> WebGLResult<IndexedBufferTarget> {
+    let target = IndexedBufferTarget::from_gl_constant(target).ok_or(InvalidEnum)?;
+    if index >= indexed_binding_count(target, limits) {
+        return Err(InvalidValue);
+    }
+    Ok(target)
+}
+
+/// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+pub fn validate_buffer_range(
+    target: IndexedBufferTarget,
+    offset: i64,
+    size: i64,
+    limits: &GLLimits,
+) -> WebGLResult<()> {
+    if offset < 0 || size <= 0 {
+        return Err(InvalidValue);
+    }
+    let alignment = match target {
+        IndexedBufferTarget::TransformFeedbackBuffer => {
+            if size % 4 != 0 {
+                return Err(InvalidValue);
+            }
+            4
+        },
+        IndexedBufferTarget::UniformBuffer => limits.uniform_buffer_offset_alignment.max(1) as i64,
+    };
+    if offset % alignment != 0 {
+        return Err(InvalidValue);
+    }
+    Ok(())
+}
+
+/// Validates the arguments of `getBufferSubData` and returns the range of
+/// the destination view, in elements, which will receive the data.
+///
+/// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.3
+pub fn validate_get_buffer_sub_data(
+    src_byte_offset: i64,
+    buffer_capacity: usize,
+    dst_len: usize,
+    dst_offset: u32,
+    length: u32,
+    element_size: usize,
+) -> WebGLResult<(usize, usize)> {
+    if src_byte_offset < 0 {
+        return Err(InvalidValue);
+    }
+    let dst_offset = dst_offset as usize;
+    if dst_offset > dst_len {
+        return Err(InvalidValue);
+    }
+    let copy_length = if length == 0 {
+        dst_len - dst_offset
+    } else {
+        length as usize
+    };
+    if dst_offset + copy_length > dst_len {
+        return Err(InvalidValue);
+    }
+    let src_byte_offset = src_byte_offset as u64;
+    let byte_length = (copy_length * element_size) as u64;
+    if src_byte_offset + byte_length > buffer_capacity as u64 {
+        return Err(InvalidValue);
+    }
+    Ok((dst_offset, copy_length))
+}
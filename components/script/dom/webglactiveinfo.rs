@@ -8,7 +8,7 @@ use crate::dom::bindings::codegen::Bindings::WebGLActiveInfoBinding::WebGLActive
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
-use crate::dom::window::Window;
+use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
 
 #[dom_struct]
@@ -30,10 +30,15 @@ impl WebGLActiveInfo {
         }
     }
 
-    pub fn new(window: &Window, size: i32, ty: u32, name: DOMString) -> DomRoot<WebGLActiveInfo> {
+    pub fn new(
+        global: &GlobalScope,
+        size: i32,
+        ty: u32,
+        name: DOMString,
+    ) -> DomRoot<WebGLActiveInfo> {
         reflect_dom_object(
             Box::new(WebGLActiveInfo::new_inherited(size, ty, name)),
-            window,
+            global,
             WebGLActiveInfoBinding::Wrap,
         )
     }
@@ -3,15 +3,16 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 // https://www.khronos.org/registry/webgl/specs/latest/1.0/webgl.idl
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants;
 use crate::dom::bindings::codegen::Bindings::WebGLBufferBinding;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextConstants;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::webglobject::WebGLObject;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use canvas_traits::webgl::webgl_channel;
-use canvas_traits::webgl::{WebGLBufferId, WebGLCommand, WebGLError, WebGLResult};
+use canvas_traits::webgl::{WebGLBufferId, WebGLCommand, WebGLError, WebGLResult, WebGLVersion};
 use dom_struct::dom_struct;
 use ipc_channel::ipc;
 use std::cell::Cell;
@@ -65,11 +66,18 @@ impl WebGLBuffer {
         self.id
     }
 
-    pub fn buffer_data(&self, data: &[u8], usage: u32) -> WebGLResult<()> {
+    pub fn buffer_data(&self, target: u32, data: &[u8], usage: u32) -> WebGLResult<()> {
         match usage {
             WebGLRenderingContextConstants::STREAM_DRAW |
             WebGLRenderingContextConstants::STATIC_DRAW |
             WebGLRenderingContextConstants::DYNAMIC_DRAW => (),
+            WebGL2RenderingContextConstants::STREAM_READ |
+            WebGL2RenderingContextConstants::STREAM_COPY |
+            WebGL2RenderingContextConstants::STATIC_READ |
+            WebGL2RenderingContextConstants::STATIC_COPY |
+            WebGL2RenderingContextConstants::DYNAMIC_READ |
+            WebGL2RenderingContextConstants::DYNAMIC_COPY
+                if self.is_webgl2() => {},
             _ => return Err(WebGLError::InvalidEnum),
         }
 
@@ -78,11 +86,7 @@ impl WebGLBuffer {
         let (sender, receiver) = ipc::bytes_channel().unwrap();
         self.upcast::<WebGLObject>()
             .context()
-            .send_command(WebGLCommand::BufferData(
-                self.target.get().unwrap(),
-                receiver,
-                usage,
-            ));
+            .send_command(WebGLCommand::BufferData(target, receiver, usage));
         sender.send(data).unwrap();
         Ok(())
     }
@@ -121,13 +125,31 @@ impl WebGLBuffer {
     }
 
     pub fn set_target(&self, target: u32) -> WebGLResult<()> {
-        if self.target.get().map_or(false, |t| t != target) {
+        let previous = match self.target.get() {
+            Some(previous) => previous,
+            None => {
+                self.target.set(Some(target));
+                return Ok(());
+            },
+        };
+        if previous == target {
+            return Ok(());
+        }
+        // https://www.khronos.org/registry/webgl/specs/latest/2.0/#5.1
+        //
+        // WebGL 2 only forbids mixing element array buffers with the other
+        // buffer targets.
+        let element_array = WebGLRenderingContextConstants::ELEMENT_ARRAY_BUFFER;
+        if !self.is_webgl2() || (previous == element_array) != (target == element_array) {
             return Err(WebGLError::InvalidOperation);
         }
-        self.target.set(Some(target));
         Ok(())
     }
 
+    fn is_webgl2(&self) -> bool {
+        self.upcast::<WebGLObject>().context().webgl_version() == WebGLVersion::WebGL2
+    }
+
     pub fn is_attached(&self) -> bool {
         self.attached_counter.get() != 0
     }
@@ -163,3 +185,60 @@ impl Drop for WebGLBuffer {
         self.mark_for_deletion();
     }
 }
+
+/// An indexed buffer binding point, as used by `bindBufferBase` and
+/// `bindBufferRange`.
+#[must_root]
+#[derive(Default, JSTraceable, MallocSizeOf)]
+pub struct IndexedBufferBinding {
+    buffer: MutNullableDom<WebGLBuffer>,
+    start: Cell<i64>,
+    /// Zero when the whole buffer is bound.
+    size: Cell<i64>,
+}
+
+impl IndexedBufferBinding {
+    pub fn buffer(&self) -> Option<DomRoot<WebGLBuffer>> {
+        self.buffer.get()
+    }
+
+    pub fn start(&self) -> i64 {
+        self.start.get()
+    }
+
+    pub fn size(&self) -> i64 {
+        self.size.get()
+    }
+
+    /// The number of bytes available through this binding point.
+    pub fn available_bytes(&self) -> usize {
+        let capacity = match self.buffer.get() {
+            Some(buffer) => buffer.capacity() as i64,
+            None => return 0,
+        };
+        let end = if self.size.get() == 0 {
+            capacity
+        } else {
+            capacity.min(self.start.get() + self.size.get())
+        };
+        (end - self.start.get()).max(0) as usize
+    }
+
+    pub fn set(&self, buffer: Option<&WebGLBuffer>, start: i64, size: i64) {
+        if let Some(buffer) = buffer {
+            buffer.increment_attached_counter();
+        }
+        if let Some(old) = self.buffer.get() {
+            old.decrement_attached_counter();
+        }
+        self.buffer.set(buffer);
+        self.start.set(start);
+        self.size.set(size);
+    }
+
+    pub fn unbind(&self, buffer: &WebGLBuffer) {
+        if self.buffer.get().map_or(false, |b| buffer == &*b) {
+            self.set(None, 0, 0);
+        }
+    }
+}
@@ -12,7 +12,7 @@ use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
-use crate::dom::window::Window;
+use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
 use servo_atoms::Atom;
 
@@ -42,20 +42,20 @@ impl WebGLContextEvent {
         }
     }
 
-    pub fn new_uninitialized(window: &Window) -> DomRoot<WebGLContextEvent> {
+    pub fn new_uninitialized(global: &GlobalScope) -> DomRoot<WebGLContextEvent> {
         // according to https://www.khronos.org/registry/webgl/specs/1.0/#5.15 this is
         // additional information or the empty string if no additional information is
         // available.
         let status_message = DOMString::new();
         reflect_dom_object(
             Box::new(WebGLContextEvent::new_inherited(status_message)),
-            window,
+            global,
             WebGLContextEventBinding::Wrap,
         )
     }
 
     pub fn new(
-        window: &Window,
+        global: &GlobalScope,
         type_: Atom,
         bubbles: EventBubbles,
         cancelable: EventCancelable,
@@ -63,7 +63,7 @@ impl WebGLContextEvent {
     ) -> DomRoot<WebGLContextEvent> {
         let event = reflect_dom_object(
             Box::new(WebGLContextEvent::new_inherited(status_message)),
-            window,
+            global,
             WebGLContextEventBinding::Wrap,
         );
 
@@ -76,7 +76,7 @@ impl WebGLContextEvent {
     }
 
     pub fn Constructor(
-        window: &Window,
+        global: &GlobalScope,
         type_: DOMString,
         init: &WebGLContextEventInit,
     ) -> Fallible<DomRoot<WebGLContextEvent>> {
@@ -90,7 +90,7 @@ impl WebGLContextEvent {
         let cancelable = EventCancelable::from(init.parent.cancelable);
 
         Ok(WebGLContextEvent::new(
-            window,
+            global,
             Atom::from(type_),
             bubbles,
             cancelable,
@@ -4,6 +4,8 @@
 
 // https://www.khronos.org/registry/webgl/specs/latest/1.0/webgl.idl
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EXTsRGBBinding::EXTsRGBConstants;
+use crate::dom::bindings::codegen::Bindings::WEBGLDrawBuffersBinding::WEBGLDrawBuffersConstants;
 use crate::dom::bindings::codegen::Bindings::WebGLFramebufferBinding;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextConstants as constants;
 use crate::dom::bindings::inheritance::Castable;
@@ -78,11 +80,13 @@ pub struct WebGLFramebuffer {
     status: Cell<u32>,
     // The attachment points for textures and renderbuffers on this
     // FBO.
-    color: DomRefCell<Option<WebGLFramebufferAttachment>>,
+    colors: Box<[DomRefCell<Option<WebGLFramebufferAttachment>>]>,
     depth: DomRefCell<Option<WebGLFramebufferAttachment>>,
     stencil: DomRefCell<Option<WebGLFramebufferAttachment>>,
     depthstencil: DomRefCell<Option<WebGLFramebufferAttachment>>,
     is_initialized: Cell<bool>,
+    /// The color attachments selected by `WEBGL_draw_buffers`, one per draw buffer.
+    draw_buffers: DomRefCell<Vec<u32>>,
 }
 
 impl WebGLFramebuffer {
@@ -94,11 +98,24 @@ impl WebGLFramebuffer {
             is_deleted: Cell::new(false),
             size: Cell::new(None),
             status: Cell::new(constants::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT),
-            color: DomRefCell::new(None),
+            colors: (0..context.limits().max_color_attachments)
+                .map(|_| DomRefCell::new(None))
+                .collect(),
             depth: DomRefCell::new(None),
             stencil: DomRefCell::new(None),
             depthstencil: DomRefCell::new(None),
             is_initialized: Cell::new(false),
+            draw_buffers: DomRefCell::new(
+                (0..context.limits().max_draw_buffers)
+                    .map(|i| {
+                        if i == 0 {
+                            constants::COLOR_ATTACHMENT0
+                        } else {
+                            constants::NONE
+                        }
+                    })
+                    .collect(),
+            ),
         }
     }
 
@@ -157,27 +174,42 @@ impl WebGLFramebuffer {
         self.size.get()
     }
 
+    pub fn draw_buffer(&self, index: usize) -> Option<u32> {
+        self.draw_buffers.borrow().get(index).cloned()
+    }
+
+    pub fn set_draw_buffers(&self, buffers: &[u32]) {
+        let mut draw_buffers = self.draw_buffers.borrow_mut();
+        for (i, draw_buffer) in draw_buffers.iter_mut().enumerate() {
+            *draw_buffer = buffers.get(i).cloned().unwrap_or(constants::NONE);
+        }
+    }
+
     fn update_status(&self) {
-        let c = self.color.borrow();
+        let c = self.colors.iter().map(|c| c.borrow()).collect::<Vec<_>>();
         let z = self.depth.borrow();
         let s = self.stencil.borrow();
         let zs = self.depthstencil.borrow();
-        let has_c = c.is_some();
+        let has_c = c.iter().any(|c| c.is_some());
         let has_z = z.is_some();
         let has_s = s.is_some();
         let has_zs = zs.is_some();
-        let attachments = [&*c, &*z, &*s, &*zs];
-        let attachment_constraints = [
-            &[
-                constants::RGBA4,
-                constants::RGB5_A1,
-                constants::RGB565,
-                constants::RGBA,
-            ][..],
-            &[constants::DEPTH_COMPONENT16][..],
-            &[constants::STENCIL_INDEX8][..],
-            &[constants::DEPTH_STENCIL][..],
-        ];
+        let color_constraints = &[
+            constants::RGBA4,
+            constants::RGB5_A1,
+            constants::RGB565,
+            constants::RGBA,
+            EXTsRGBConstants::SRGB8_ALPHA8_EXT,
+            EXTsRGBConstants::SRGB_ALPHA_EXT,
+        ][..];
+        let attachments = c.iter().map(|c| (&**c, color_constraints)).chain(vec![
+            (
+                &*z,
+                &[constants::DEPTH_COMPONENT16, constants::DEPTH_COMPONENT][..],
+            ),
+            (&*s, &[constants::STENCIL_INDEX8][..]),
+            (&*zs, &[constants::DEPTH_STENCIL][..]),
+        ]);
 
         // From the WebGL spec, 6.6 ("Framebuffer Object Attachments"):
         //
@@ -198,9 +230,9 @@ impl WebGLFramebuffer {
         }
 
         let mut fb_size = None;
-        for (attachment, constraints) in attachments.iter().zip(&attachment_constraints) {
+        for (attachment, constraints) in attachments {
             // Get the size of this attachment.
-            let (format, size) = match **attachment {
+            let (format, size) = match *attachment {
                 Some(WebGLFramebufferAttachment::Renderbuffer(ref att_rb)) => {
                     (Some(att_rb.internal_format()), att_rb.size())
                 },
@@ -262,22 +294,25 @@ impl WebGLFramebuffer {
             return CompleteForRendering::Incomplete;
         }
 
-        if self.color.borrow().is_none() {
+        if self.colors.iter().all(|c| c.borrow().is_none()) {
             return CompleteForRendering::MissingColorAttachment;
         }
 
         if !self.is_initialized.get() {
-            let attachments = [
-                (&self.color, constants::COLOR_BUFFER_BIT),
-                (&self.depth, constants::DEPTH_BUFFER_BIT),
-                (&self.stencil, constants::STENCIL_BUFFER_BIT),
-                (
-                    &self.depthstencil,
-                    constants::DEPTH_BUFFER_BIT | constants::STENCIL_BUFFER_BIT,
-                ),
-            ];
+            let attachments = self
+                .colors
+                .iter()
+                .map(|c| (c, constants::COLOR_BUFFER_BIT))
+                .chain(vec![
+                    (&self.depth, constants::DEPTH_BUFFER_BIT),
+                    (&self.stencil, constants::STENCIL_BUFFER_BIT),
+                    (
+                        &self.depthstencil,
+                        constants::DEPTH_BUFFER_BIT | constants::STENCIL_BUFFER_BIT,
+                    ),
+                ]);
             let mut clear_bits = 0;
-            for &(attachment, bits) in &attachments {
+            for (attachment, bits) in attachments {
                 if let Some(ref att) = *attachment.borrow() {
                     if att.needs_initialization() {
                         att.mark_initialized();
@@ -346,7 +381,14 @@ impl WebGLFramebuffer {
         attachment: u32,
     ) -> Option<&DomRefCell<Option<WebGLFramebufferAttachment>>> {
         match attachment {
-            constants::COLOR_ATTACHMENT0 => Some(&self.color),
+            constants::COLOR_ATTACHMENT0..=WEBGLDrawBuffersConstants::COLOR_ATTACHMENT15_WEBGL => {
+                let idx = attachment - constants::COLOR_ATTACHMENT0;
+                let context = self.upcast::<WebGLObject>().context();
+                if idx > 0 && !context.extension_manager().is_draw_buffers_enabled() {
+                    return None;
+                }
+                self.colors.get(idx as usize)
+            },
             constants::DEPTH_ATTACHMENT => Some(&self.depth),
             constants::STENCIL_ATTACHMENT => Some(&self.stencil),
             constants::DEPTH_STENCIL_ATTACHMENT => Some(&self.depthstencil),
@@ -485,18 +527,24 @@ impl WebGLFramebuffer {
         Ok(())
     }
 
+    fn attachments(&self) -> Vec<(&DomRefCell<Option<WebGLFramebufferAttachment>>, u32)> {
+        self.colors
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c, constants::COLOR_ATTACHMENT0 + i as u32))
+            .chain(vec![
+                (&self.depth, constants::DEPTH_ATTACHMENT),
+                (&self.stencil, constants::STENCIL_ATTACHMENT),
+                (&self.depthstencil, constants::DEPTH_STENCIL_ATTACHMENT),
+            ])
+            .collect()
+    }
+
     fn with_matching_renderbuffers<F>(&self, rb: &WebGLRenderbuffer, mut closure: F)
     where
         F: FnMut(&DomRefCell<Option<WebGLFramebufferAttachment>>, u32),
     {
-        let attachments = [
-            (&self.color, constants::COLOR_ATTACHMENT0),
-            (&self.depth, constants::DEPTH_ATTACHMENT),
-            (&self.stencil, constants::STENCIL_ATTACHMENT),
-            (&self.depthstencil, constants::DEPTH_STENCIL_ATTACHMENT),
-        ];
-
-        for (attachment, name) in &attachments {
+        for (attachment, name) in self.attachments() {
             let matched = {
                 match *attachment.borrow() {
                     Some(WebGLFramebufferAttachment::Renderbuffer(ref att_rb))
@@ -509,7 +557,7 @@ impl WebGLFramebuffer {
             };
 
             if matched {
-                closure(attachment, *name);
+                closure(attachment, name);
             }
         }
     }
@@ -518,14 +566,7 @@ impl WebGLFramebuffer {
     where
         F: FnMut(&DomRefCell<Option<WebGLFramebufferAttachment>>, u32),
     {
-        let attachments = [
-            (&self.color, constants::COLOR_ATTACHMENT0),
-            (&self.depth, constants::DEPTH_ATTACHMENT),
-            (&self.stencil, constants::STENCIL_ATTACHMENT),
-            (&self.depthstencil, constants::DEPTH_STENCIL_ATTACHMENT),
-        ];
-
-        for (attachment, name) in &attachments {
+        for (attachment, name) in self.attachments() {
             let matched = {
                 match *attachment.borrow() {
                     Some(WebGLFramebufferAttachment::Texture {
@@ -537,7 +578,7 @@ impl WebGLFramebuffer {
             };
 
             if matched {
-                closure(attachment, *name);
+                closure(attachment, name);
             }
         }
     }
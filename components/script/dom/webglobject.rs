@@ -12,6 +12,9 @@ use dom_struct::dom_struct;
 pub struct WebGLObject {
     reflector_: Reflector,
     context: Dom<WebGLRenderingContext>,
+    /// The generation of the context when this object was created, objects
+    /// from before a context loss are invalid in the restored context.
+    context_generation: u32,
 }
 
 impl WebGLObject {
@@ -19,10 +22,15 @@ impl WebGLObject {
         WebGLObject {
             reflector_: Reflector::new(),
             context: Dom::from_ref(context),
+            context_generation: context.generation(),
         }
     }
 
     pub fn context(&self) -> &WebGLRenderingContext {
         &self.context
     }
+
+    pub fn context_generation(&self) -> u32 {
+        self.context_generation
+    }
 }
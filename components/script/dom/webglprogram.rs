@@ -4,6 +4,7 @@
 
 // https://www.khronos.org/registry/webgl/specs/latest/1.0/webgl.idl
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants;
 use crate::dom::bindings::codegen::Bindings::WebGLProgramBinding;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextConstants as constants;
 use crate::dom::bindings::inheritance::Castable;
@@ -15,6 +16,7 @@ use crate::dom::webglobject::WebGLObject;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::dom::webglshader::WebGLShader;
 use crate::dom::webgluniformlocation::WebGLUniformLocation;
+use canvas_traits::webgl::ActiveTransformFeedbackVaryingInfo;
 use canvas_traits::webgl::{webgl_channel, WebGLProgramId, WebGLResult};
 use canvas_traits::webgl::{ActiveAttribInfo, ActiveUniformInfo, WebGLCommand, WebGLError};
 use dom_struct::dom_struct;
@@ -34,6 +36,11 @@ pub struct WebGLProgram {
     vertex_shader: MutNullableDom<WebGLShader>,
     active_attribs: DomRefCell<Box<[ActiveAttribInfo]>>,
     active_uniforms: DomRefCell<Box<[ActiveUniformInfo]>>,
+    /// The number of varyings and the buffer mode passed to
+    /// transformFeedbackVaryings, which only take effect on the next link.
+    pending_transform_feedback_varyings: Cell<(usize, u32)>,
+    transform_feedback_buffer_mode: Cell<u32>,
+    transform_feedback_varyings: DomRefCell<Box<[ActiveTransformFeedbackVaryingInfo]>>,
 }
 
 impl WebGLProgram {
@@ -50,6 +57,14 @@ impl WebGLProgram {
             vertex_shader: Default::default(),
             active_attribs: DomRefCell::new(vec![].into()),
             active_uniforms: DomRefCell::new(vec![].into()),
+            pending_transform_feedback_varyings: Cell::new((
+                0,
+                WebGL2RenderingContextConstants::INTERLEAVED_ATTRIBS,
+            )),
+            transform_feedback_buffer_mode: Cell::new(
+                WebGL2RenderingContextConstants::INTERLEAVED_ATTRIBS,
+            ),
+            transform_feedback_varyings: DomRefCell::new(vec![].into()),
         }
     }
 
@@ -135,6 +150,7 @@ impl WebGLProgram {
             .set(self.link_generation.get().checked_add(1).unwrap());
         *self.active_attribs.borrow_mut() = Box::new([]);
         *self.active_uniforms.borrow_mut() = Box::new([]);
+        *self.transform_feedback_varyings.borrow_mut() = Box::new([]);
 
         match self.fragment_shader.get() {
             Some(ref shader) if shader.successfully_compiled() => {},
@@ -184,9 +200,77 @@ impl WebGLProgram {
         self.linked.set(link_info.linked);
         *self.active_attribs.borrow_mut() = link_info.active_attribs;
         *self.active_uniforms.borrow_mut() = link_info.active_uniforms;
+        if link_info.linked {
+            let (count, buffer_mode) = self.pending_transform_feedback_varyings.get();
+            self.transform_feedback_buffer_mode.set(buffer_mode);
+            let context = self.upcast::<WebGLObject>().context();
+            *self.transform_feedback_varyings.borrow_mut() = (0..count as u32)
+                .filter_map(|index| {
+                    let (sender, receiver) = webgl_channel().unwrap();
+                    context.send_command(WebGLCommand::GetTransformFeedbackVarying(
+                        self.id, index, sender,
+                    ));
+                    receiver.recv().unwrap()
+                })
+                .collect();
+        }
         Ok(())
     }
 
+    /// glTransformFeedbackVaryings
+    pub fn transform_feedback_varyings(&self, varyings: Vec<String>, buffer_mode: u32) {
+        self.pending_transform_feedback_varyings
+            .set((varyings.len(), buffer_mode));
+        self.upcast::<WebGLObject>().context().send_command(
+            WebGLCommand::TransformFeedbackVaryings(self.id, varyings, buffer_mode),
+        );
+    }
+
+    /// The varyings captured in transform feedback as of the last link.
+    pub fn transform_feedback_varyings(&self) -> Ref<[ActiveTransformFeedbackVaryingInfo]> {
+        Ref::map(self.transform_feedback_varyings.borrow(), |varyings| {
+            &**varyings
+        })
+    }
+
+    /// The transform feedback buffer mode as of the last link.
+    pub fn transform_feedback_buffer_mode(&self) -> u32 {
+        self.transform_feedback_buffer_mode.get()
+    }
+
+    /// The number of active uniform blocks, which is only non-zero for
+    /// programs linked in a WebGL 2 context.
+    pub fn active_uniform_blocks(&self) -> i32 {
+        if !self.is_linked() {
+            return 0;
+        }
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::GetProgramActiveUniformBlocks(self.id, sender));
+        receiver.recv().unwrap()
+    }
+
+    /// glGetTransformFeedbackVarying
+    pub fn get_transform_feedback_varying(
+        &self,
+        index: u32,
+    ) -> WebGLResult<DomRoot<WebGLActiveInfo>> {
+        if self.is_deleted() {
+            return Err(WebGLError::InvalidValue);
+        }
+        let varyings = self.transform_feedback_varyings.borrow();
+        let data = varyings
+            .get(index as usize)
+            .ok_or(WebGLError::InvalidValue)?;
+        Ok(WebGLActiveInfo::new(
+            &self.global(),
+            data.size,
+            data.type_,
+            data.name.clone().into(),
+        ))
+    }
+
     pub fn active_attribs(&self) -> Ref<[ActiveAttribInfo]> {
         Ref::map(self.active_attribs.borrow(), |attribs| &**attribs)
     }
@@ -295,7 +379,7 @@ impl WebGLProgram {
             .get(index as usize)
             .ok_or(WebGLError::InvalidValue)?;
         Ok(WebGLActiveInfo::new(
-            self.global().as_window(),
+            &self.global(),
             data.size.unwrap_or(1),
             data.type_,
             data.name().into(),
@@ -312,7 +396,7 @@ impl WebGLProgram {
             .get(index as usize)
             .ok_or(WebGLError::InvalidValue)?;
         Ok(WebGLActiveInfo::new(
-            self.global().as_window(),
+            &self.global(),
             data.size,
             data.type_,
             data.name.clone().into(),
@@ -389,7 +473,7 @@ impl WebGLProgram {
         let location = receiver.recv().unwrap();
 
         Ok(Some(WebGLUniformLocation::new(
-            self.global().as_window(),
+            &self.global(),
             location,
             self.id,
             self.link_generation.get(),
@@ -0,0 +1,165 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+// https://www.khronos.org/registry/webgl/specs/latest/2.0/webgl2.idl
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants as constants;
+use crate::dom::bindings::codegen::Bindings::WebGLQueryBinding;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::webglobject::WebGLObject;
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use crate::task_source::TaskSource;
+use canvas_traits::webgl::WebGLError::*;
+use canvas_traits::webgl::{webgl_channel, WebGLCommand, WebGLQueryId, WebGLResult};
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct WebGLQuery {
+    webgl_object: WebGLObject,
+    id: WebGLQueryId,
+    /// The target this query was first used with, a query object can't be
+    /// reused for another target.
+    target: Cell<Option<u32>>,
+    marked_for_deletion: Cell<bool>,
+    /// Whether control returned to the event loop since the query ended, the
+    /// result must not be made available before that.
+    ready: Cell<bool>,
+    /// The result of the last query once it was read back from the GL.
+    result: Cell<Option<u64>>,
+}
+
+impl WebGLQuery {
+    fn new_inherited(context: &WebGLRenderingContext, id: WebGLQueryId) -> Self {
+        Self {
+            webgl_object: WebGLObject::new_inherited(context),
+            id,
+            target: Default::default(),
+            marked_for_deletion: Default::default(),
+            ready: Default::default(),
+            result: Default::default(),
+        }
+    }
+
+    pub fn maybe_new(context: &WebGLRenderingContext) -> Option<DomRoot<Self>> {
+        let (sender, receiver) = webgl_channel().unwrap();
+        context.send_command(WebGLCommand::CreateQuery(sender));
+        receiver
+            .recv()
+            .unwrap()
+            .map(|id| WebGLQuery::new(context, id))
+    }
+
+    pub fn new(context: &WebGLRenderingContext, id: WebGLQueryId) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(WebGLQuery::new_inherited(context, id)),
+            &*context.global(),
+            WebGLQueryBinding::Wrap,
+        )
+    }
+}
+
+impl WebGLQuery {
+    pub fn id(&self) -> WebGLQueryId {
+        self.id
+    }
+
+    pub fn target(&self) -> Option<u32> {
+        self.target.get()
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.marked_for_deletion.get()
+    }
+
+    pub fn delete(&self) {
+        if self.marked_for_deletion.get() {
+            return;
+        }
+        self.marked_for_deletion.set(true);
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::DeleteQuery(self.id));
+    }
+
+    pub fn begin(&self, target: u32) -> WebGLResult<()> {
+        if self.marked_for_deletion.get() {
+            return Err(InvalidOperation);
+        }
+        if self.target.get().map_or(false, |t| t != target) {
+            return Err(InvalidOperation);
+        }
+        self.target.set(Some(target));
+        self.ready.set(false);
+        self.result.set(None);
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::BeginQuery(target, self.id));
+        Ok(())
+    }
+
+    pub fn end(&self, target: u32) {
+        let context = self.upcast::<WebGLObject>().context();
+        context.send_command(WebGLCommand::EndQuery(target));
+
+        let global = context.global();
+        let this = Trusted::new(self);
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(query_result_ready: move || {
+                this.root().ready.set(true);
+            }),
+            &global,
+        );
+    }
+
+    fn fetch_result(&self) -> Option<u64> {
+        if let Some(result) = self.result.get() {
+            return Some(result);
+        }
+        if !self.ready.get() || self.marked_for_deletion.get() {
+            return None;
+        }
+        let context = self.upcast::<WebGLObject>().context();
+        let (sender, receiver) = webgl_channel().unwrap();
+        context.send_command(WebGLCommand::GetQueryParameter(
+            self.id,
+            constants::QUERY_RESULT_AVAILABLE,
+            sender,
+        ));
+        if receiver.recv().unwrap() == 0 {
+            return None;
+        }
+        let (sender, receiver) = webgl_channel().unwrap();
+        context.send_command(WebGLCommand::GetQueryParameter(
+            self.id,
+            constants::QUERY_RESULT,
+            sender,
+        ));
+        let result = receiver.recv().unwrap();
+        self.result.set(Some(result));
+        Some(result)
+    }
+
+    /// Returns whether the result is available, and the result itself, which
+    /// is zero while it is not.
+    pub fn get_parameter(&self, pname: u32) -> WebGLResult<(bool, u64)> {
+        match pname {
+            constants::QUERY_RESULT_AVAILABLE | constants::QUERY_RESULT => {},
+            _ => return Err(InvalidEnum),
+        }
+        if self.marked_for_deletion.get() || self.target.get().is_none() {
+            return Err(InvalidOperation);
+        }
+        let result = self.fetch_result();
+        Ok((result.is_some(), result.unwrap_or(0)))
+    }
+}
+
+impl Drop for WebGLQuery {
+    fn drop(&mut self) {
+        self.delete();
+    }
+}
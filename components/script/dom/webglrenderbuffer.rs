@@ -4,6 +4,7 @@
 
 // https://www.khronos.org/registry/webgl/specs/latest/1.0/webgl.idl
 use crate::dom::bindings::codegen::Bindings::EXTColorBufferHalfFloatBinding::EXTColorBufferHalfFloatConstants;
+use crate::dom::bindings::codegen::Bindings::EXTsRGBBinding::EXTsRGBConstants;
 use crate::dom::bindings::codegen::Bindings::WEBGLColorBufferFloatBinding::WEBGLColorBufferFloatConstants;
 use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderbufferBinding;
@@ -11,6 +12,7 @@ use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGL
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::extsrgb::EXTsRGB;
 use crate::dom::webglobject::WebGLObject;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use canvas_traits::webgl::{
@@ -168,6 +170,17 @@ impl WebGLRenderbuffer {
                 }
                 internal_format
             },
+            EXTsRGBConstants::SRGB8_ALPHA8_EXT => {
+                if !self
+                    .upcast()
+                    .context()
+                    .extension_manager()
+                    .is_enabled::<EXTsRGB>()
+                {
+                    return Err(WebGLError::InvalidEnum);
+                }
+                internal_format
+            },
             _ => return Err(WebGLError::InvalidEnum),
         };
 
@@ -4,7 +4,11 @@
 
 use crate::dom::bindings::codegen::Bindings::ANGLEInstancedArraysBinding::ANGLEInstancedArraysConstants;
 use crate::dom::bindings::codegen::Bindings::EXTBlendMinmaxBinding::EXTBlendMinmaxConstants;
+use crate::dom::bindings::codegen::Bindings::EXTsRGBBinding::EXTsRGBConstants;
 use crate::dom::bindings::codegen::Bindings::OESVertexArrayObjectBinding::OESVertexArrayObjectConstants;
+use crate::dom::bindings::codegen::Bindings::WEBGLDebugRendererInfoBinding::WEBGLDebugRendererInfoConstants;
+use crate::dom::bindings::codegen::Bindings::WEBGLDrawBuffersBinding::WEBGLDrawBuffersConstants;
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::TexImageSource;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLContextAttributes;
@@ -12,21 +16,29 @@ use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGL
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextMethods;
 use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
 use crate::dom::bindings::codegen::UnionTypes::Float32ArrayOrUnrestrictedFloatSequence;
+use crate::dom::bindings::codegen::UnionTypes::HTMLCanvasElementOrOffscreenCanvas;
 use crate::dom::bindings::codegen::UnionTypes::Int32ArrayOrLongSequence;
 use crate::dom::bindings::conversions::{DerivedFrom, ToJSValConvertible};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
-use crate::dom::bindings::root::{Dom, DomOnceCell, DomRoot, LayoutDom, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
-use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::extsrgb::EXTsRGB;
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlcanvaselement::utils as canvas_utils;
-use crate::dom::htmlcanvaselement::HTMLCanvasElement;
+use crate::dom::htmlcanvaselement::{CanvasContext, HTMLCanvasElement};
 use crate::dom::htmliframeelement::HTMLIFrameElement;
-use crate::dom::node::{document_from_node, window_from_node, Node, NodeDamage};
+use crate::dom::node::{window_from_node, Node, NodeDamage};
+use crate::dom::offscreencanvas::{OffscreenCanvas, OffscreenCanvasContext};
 use crate::dom::webgl_extensions::WebGLExtensions;
 use crate::dom::webgl_validations::tex_image_2d::{
-    CommonTexImage2DValidator, CommonTexImage2DValidatorResult,
+    CommonCompressedTexImage2DValidatorResult, CommonTexImage2DValidator,
+    CommonTexImage2DValidatorResult, CompressedTexImage2DValidator,
+    CompressedTexSubImage2DValidator,
 };
 use crate::dom::webgl_validations::tex_image_2d::{TexImage2DValidator, TexImage2DValidatorResult};
 use crate::dom::webgl_validations::types::TexImageTarget;
@@ -46,6 +58,7 @@ use crate::dom::webgltexture::{TexParameterValue, WebGLTexture};
 use crate::dom::webgluniformlocation::WebGLUniformLocation;
 use crate::dom::webglvertexarrayobjectoes::WebGLVertexArrayObjectOES;
 use crate::dom::window::Window;
+use crate::task_source::TaskSource;
 #[cfg(feature = "webgl_backtrace")]
 use backtrace::Backtrace;
 use canvas_traits::webgl::WebGLError::*;
@@ -97,16 +110,6 @@ macro_rules! handle_object_deletion {
     };
 }
 
-macro_rules! optional_root_object_to_js_or_null {
-    ($cx: expr, $binding:expr) => {{
-        rooted!(in($cx) let mut rval = NullValue());
-        if let Some(object) = $binding {
-            object.to_jsval($cx, rval.handle_mut());
-        }
-        rval.get()
-    }};
-}
-
 fn has_invalid_blend_constants(arg1: u32, arg2: u32) -> bool {
     match (arg1, arg2) {
         (constants::CONSTANT_COLOR, constants::CONSTANT_ALPHA) => true,
@@ -118,6 +121,55 @@ fn has_invalid_blend_constants(arg1: u32, arg2: u32) -> bool {
 }
 
 /// Set of bitflags for texture unpacking (texImage2d, etc...)
+/// The canvas a WebGL context renders into, either a canvas element or an
+/// `OffscreenCanvas`, possibly living in a worker.
+#[must_root]
+#[derive(JSTraceable, MallocSizeOf)]
+pub enum WebGLCanvas {
+    HTMLCanvasElement(Dom<HTMLCanvasElement>),
+    OffscreenCanvas(Dom<OffscreenCanvas>),
+}
+
+impl WebGLCanvas {
+    #[allow(unrooted_must_root)]
+    fn from_ref(canvas: &HTMLCanvasElementOrOffscreenCanvas) -> Self {
+        match *canvas {
+            HTMLCanvasElementOrOffscreenCanvas::HTMLCanvasElement(ref canvas) => {
+                WebGLCanvas::HTMLCanvasElement(Dom::from_ref(&**canvas))
+            },
+            HTMLCanvasElementOrOffscreenCanvas::OffscreenCanvas(ref canvas) => {
+                WebGLCanvas::OffscreenCanvas(Dom::from_ref(&**canvas))
+            },
+        }
+    }
+
+    fn upcast(&self) -> &EventTarget {
+        match *self {
+            WebGLCanvas::HTMLCanvasElement(ref canvas) => canvas.upcast(),
+            WebGLCanvas::OffscreenCanvas(ref canvas) => canvas.upcast(),
+        }
+    }
+
+    fn to_union(&self) -> HTMLCanvasElementOrOffscreenCanvas {
+        match *self {
+            WebGLCanvas::HTMLCanvasElement(ref canvas) => {
+                HTMLCanvasElementOrOffscreenCanvas::HTMLCanvasElement(DomRoot::from_ref(&**canvas))
+            },
+            WebGLCanvas::OffscreenCanvas(ref canvas) => {
+                HTMLCanvasElementOrOffscreenCanvas::OffscreenCanvas(DomRoot::from_ref(&**canvas))
+            },
+        }
+    }
+
+    /// Marks the canvas element for repaint. Offscreen canvases only show
+    /// new frames when they are committed.
+    fn dirty(&self) {
+        if let WebGLCanvas::HTMLCanvasElement(ref canvas) = *self {
+            canvas.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        }
+    }
+}
+
 bitflags! {
     #[derive(JSTraceable, MallocSizeOf)]
     struct TextureUnpacking: u8 {
@@ -139,7 +191,7 @@ pub struct WebGLRenderingContext {
     glsl_version: WebGLSLVersion,
     #[ignore_malloc_size_of = "Defined in offscreen_gl_context"]
     limits: GLLimits,
-    canvas: Dom<HTMLCanvasElement>,
+    canvas: WebGLCanvas,
     #[ignore_malloc_size_of = "Defined in canvas_traits"]
     last_error: Cell<Option<WebGLError>>,
     texture_packing_alignment: Cell<u8>,
@@ -149,6 +201,10 @@ pub struct WebGLRenderingContext {
     bound_framebuffer: MutNullableDom<WebGLFramebuffer>,
     bound_renderbuffer: MutNullableDom<WebGLRenderbuffer>,
     bound_buffer_array: MutNullableDom<WebGLBuffer>,
+    /// The generic `UNIFORM_BUFFER` binding, WebGL 2 only.
+    bound_buffer_uniform: MutNullableDom<WebGLBuffer>,
+    /// The generic `TRANSFORM_FEEDBACK_BUFFER` binding, WebGL 2 only.
+    bound_buffer_transform_feedback: MutNullableDom<WebGLBuffer>,
     current_program: MutNullableDom<WebGLProgram>,
     /// https://www.khronos.org/webgl/wiki/WebGL_and_OpenGL_Differences#Vertex_Attribute_0
     #[ignore_malloc_size_of = "Because it's small"]
@@ -160,15 +216,25 @@ pub struct WebGLRenderingContext {
     size: Cell<Size2D<u32>>,
     extension_manager: WebGLExtensions,
     capabilities: Capabilities,
-    default_vao: DomOnceCell<WebGLVertexArrayObjectOES>,
+    default_vao: MutNullableDom<WebGLVertexArrayObjectOES>,
     current_vao: MutNullableDom<WebGLVertexArrayObjectOES>,
     textures: Textures,
+    /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#webgl-context-lost-flag
+    context_lost: Cell<bool>,
+    /// Whether the `webglcontextlost` event was cancelled, a lost context
+    /// can only be restored if it was.
+    restore_allowed: Cell<bool>,
+    /// Bumped every time the context is restored, objects created with an
+    /// older generation can't be used anymore.
+    generation: Cell<u32>,
+    /// The draw buffer of the default framebuffer, either BACK or NONE.
+    default_draw_buffer: Cell<u32>,
 }
 
 impl WebGLRenderingContext {
     pub fn new_inherited(
-        window: &Window,
-        canvas: &HTMLCanvasElement,
+        global: &GlobalScope,
+        canvas: &HTMLCanvasElementOrOffscreenCanvas,
         webgl_version: WebGLVersion,
         size: Size2D<u32>,
         attrs: GLContextAttributes,
@@ -180,7 +246,7 @@ impl WebGLRenderingContext {
             return Err("WebGL context creation error forced by pref `webgl.testing.context_creation_error`".into());
         }
 
-        let webgl_chan = match window.webgl_chan() {
+        let webgl_chan = match global.webgl_chan() {
             Some(chan) => chan,
             None => return Err("WebGL initialization failed early on".into()),
         };
@@ -201,13 +267,15 @@ impl WebGLRenderingContext {
                 webgl_version,
                 glsl_version: ctx_data.glsl_version,
                 limits: ctx_data.limits,
-                canvas: Dom::from_ref(canvas),
+                canvas: WebGLCanvas::from_ref(canvas),
                 last_error: Cell::new(None),
                 texture_packing_alignment: Cell::new(4),
                 texture_unpacking_settings: Cell::new(TextureUnpacking::CONVERT_COLORSPACE),
                 texture_unpacking_alignment: Cell::new(4),
                 bound_framebuffer: MutNullableDom::new(None),
                 bound_buffer_array: MutNullableDom::new(None),
+                bound_buffer_uniform: MutNullableDom::new(None),
+                bound_buffer_transform_feedback: MutNullableDom::new(None),
                 bound_renderbuffer: MutNullableDom::new(None),
                 current_program: MutNullableDom::new(None),
                 current_vertex_attrib_0: Cell::new((0f32, 0f32, 0f32, 1f32)),
@@ -221,34 +289,46 @@ impl WebGLRenderingContext {
                 default_vao: Default::default(),
                 current_vao: Default::default(),
                 textures: Textures::new(max_combined_texture_image_units),
+                context_lost: Cell::new(false),
+                restore_allowed: Cell::new(false),
+                generation: Cell::new(0),
+                default_draw_buffer: Cell::new(constants::BACK),
             }
         })
     }
 
     #[allow(unrooted_must_root)]
     pub fn new(
-        window: &Window,
-        canvas: &HTMLCanvasElement,
+        global: &GlobalScope,
+        canvas: &HTMLCanvasElementOrOffscreenCanvas,
         webgl_version: WebGLVersion,
         size: Size2D<u32>,
         attrs: GLContextAttributes,
     ) -> Option<DomRoot<WebGLRenderingContext>> {
-        match WebGLRenderingContext::new_inherited(window, canvas, webgl_version, size, attrs) {
+        match WebGLRenderingContext::new_inherited(global, canvas, webgl_version, size, attrs) {
             Ok(ctx) => Some(reflect_dom_object(
                 Box::new(ctx),
-                window,
+                global,
                 WebGLRenderingContextBinding::Wrap,
             )),
             Err(msg) => {
                 error!("Couldn't create WebGLRenderingContext: {}", msg);
                 let event = WebGLContextEvent::new(
-                    window,
+                    global,
                     atom!("webglcontextcreationerror"),
                     EventBubbles::DoesNotBubble,
                     EventCancelable::Cancelable,
                     DOMString::from(msg),
                 );
-                event.upcast::<Event>().fire(canvas.upcast());
+                let target = match *canvas {
+                    HTMLCanvasElementOrOffscreenCanvas::HTMLCanvasElement(ref canvas) => {
+                        canvas.upcast::<EventTarget>()
+                    },
+                    HTMLCanvasElementOrOffscreenCanvas::OffscreenCanvas(ref canvas) => {
+                        canvas.upcast::<EventTarget>()
+                    },
+                };
+                event.upcast::<Event>().fire(target);
                 None
             },
         }
@@ -258,12 +338,14 @@ impl WebGLRenderingContext {
         &self.limits
     }
 
+    pub fn webgl_version(&self) -> WebGLVersion {
+        self.webgl_version
+    }
+
     fn current_vao(&self) -> DomRoot<WebGLVertexArrayObjectOES> {
         self.current_vao.or_init(|| {
-            DomRoot::from_ref(
-                self.default_vao
-                    .init_once(|| WebGLVertexArrayObjectOES::new(self, None)),
-            )
+            self.default_vao
+                .or_init(|| WebGLVertexArrayObjectOES::new(self, None))
         })
     }
 
@@ -320,8 +402,14 @@ impl WebGLRenderingContext {
         self.webgl_sender.clone()
     }
 
+    /// Sends a command to the WebGL thread. Commands are dropped while the
+    /// context is lost, so callers expecting a reply must check for the loss
+    /// first.
     #[inline]
     pub fn send_command(&self, command: WebGLCommand) {
+        if self.context_lost.get() {
+            return;
+        }
         self.webgl_sender
             .send(command, capture_webgl_backtrace(self))
             .unwrap();
@@ -333,6 +421,12 @@ impl WebGLRenderingContext {
     }
 
     pub fn webgl_error(&self, err: WebGLError) {
+        // No errors are generated while the context is lost, `getError`
+        // only reports the loss itself.
+        if self.context_lost.get() {
+            return;
+        }
+
         // TODO(emilio): Add useful debug messages to this
         warn!(
             "WebGL error: {:?}, previous error was {:?}",
@@ -380,11 +474,17 @@ impl WebGLRenderingContext {
         }
     }
 
-    fn validate_ownership<T>(&self, object: &T) -> WebGLResult<()>
+    pub fn validate_ownership<T>(&self, object: &T) -> WebGLResult<()>
     where
         T: DerivedFrom<WebGLObject>,
     {
-        if self != object.upcast().context() {
+        // Every call taking an object fails silently while the context is
+        // lost, see `webgl_error`.
+        if self.context_lost.get() {
+            return Err(WebGLError::ContextLost);
+        }
+        let object = object.upcast();
+        if self != object.context() || self.generation.get() != object.context_generation() {
             return Err(InvalidOperation);
         }
         Ok(())
@@ -449,9 +549,7 @@ impl WebGLRenderingContext {
         // If we don't have a bound framebuffer, then don't mark the canvas
         // as dirty.
         if self.bound_framebuffer.get().is_none() {
-            self.canvas
-                .upcast::<Node>()
-                .dirty(NodeDamage::OtherNodeDamage);
+            self.canvas.dirty();
         }
     }
 
@@ -549,8 +647,7 @@ impl WebGLRenderingContext {
                 false,
             ),
             TexImageSource::HTMLImageElement(image) => {
-                let document = document_from_node(&*self.canvas);
-                if !image.same_origin(document.origin()) {
+                if !image.same_origin(self.global().origin()) {
                     return Err(Error::Security);
                 }
 
@@ -559,7 +656,7 @@ impl WebGLRenderingContext {
                     None => return Ok(None),
                 };
 
-                let window = window_from_node(&*self.canvas);
+                let window = window_from_node(&*image);
 
                 let img = match canvas_utils::request_image_from_cache(&window, img_url) {
                     ImageResponse::Loaded(img, _) => img,
@@ -824,6 +921,9 @@ impl WebGLRenderingContext {
         count: i32,
         primcount: i32,
     ) -> WebGLResult<()> {
+        if self.context_lost.get() {
+            return Ok(());
+        }
         match mode {
             constants::POINTS |
             constants::LINE_STRIP |
@@ -886,6 +986,9 @@ impl WebGLRenderingContext {
         offset: i64,
         primcount: i32,
     ) -> WebGLResult<()> {
+        if self.context_lost.get() {
+            return Ok(());
+        }
         match mode {
             constants::POINTS |
             constants::LINE_STRIP |
@@ -976,6 +1079,9 @@ impl WebGLRenderingContext {
     //
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#2.2
     pub fn get_image_data(&self, mut size: Size2D<u32>) -> Option<Vec<u8>> {
+        if self.context_lost.get() {
+            return None;
+        }
         handle_potential_webgl_error!(self, self.validate_framebuffer(), return None);
 
         let (fb_width, fb_height) = handle_potential_webgl_error!(
@@ -996,6 +1102,55 @@ impl WebGLRenderingContext {
         Some(receiver.recv().unwrap())
     }
 
+    /// Reads back the default framebuffer as top-down, premultiplied BGRA
+    /// pixels, which is what WebRender expects for a frame.
+    pub fn get_frame_pixels(&self) -> Option<(Vec<u8>, Size2D<u32>)> {
+        if self.context_lost.get() {
+            return None;
+        }
+        let size = self.size.get();
+        if size.width == 0 || size.height == 0 {
+            return None;
+        }
+
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.send_command(WebGLCommand::GetContextAttributes(sender));
+        let premultiplied = receiver.recv().unwrap().premultiplied_alpha;
+
+        let bound_framebuffer = self.bound_framebuffer.get();
+        if bound_framebuffer.is_some() {
+            self.send_command(WebGLCommand::BindFramebuffer(
+                constants::FRAMEBUFFER,
+                WebGLFramebufferBindingRequest::Default,
+            ));
+        }
+        let (sender, receiver) = ipc::bytes_channel().unwrap();
+        self.send_command(WebGLCommand::ReadPixels(
+            Rect::from_size(size),
+            constants::RGBA,
+            constants::UNSIGNED_BYTE,
+            sender,
+        ));
+        let pixels = receiver.recv().unwrap();
+        if let Some(fbo) = bound_framebuffer {
+            let id = WebGLFramebufferBindingRequest::Explicit(fbo.id());
+            self.send_command(WebGLCommand::BindFramebuffer(constants::FRAMEBUFFER, id));
+        }
+
+        // GL returns the rows bottom-up.
+        let stride = size.width as usize * 4;
+        let mut frame = Vec::with_capacity(pixels.len());
+        for row in pixels.chunks(stride).rev() {
+            frame.extend_from_slice(row);
+        }
+        if premultiplied {
+            pixels::rgba8_byte_swap_colors_inplace(&mut frame);
+        } else {
+            pixels::rgba8_byte_swap_and_premultiply_inplace(&mut frame);
+        }
+        Some((frame, size))
+    }
+
     pub fn array_buffer(&self) -> Option<DomRoot<WebGLBuffer>> {
         self.bound_buffer_array.get()
     }
@@ -1004,11 +1159,51 @@ impl WebGLRenderingContext {
         match target {
             constants::ARRAY_BUFFER => Ok(self.bound_buffer_array.get()),
             constants::ELEMENT_ARRAY_BUFFER => Ok(self.current_vao().element_array_buffer().get()),
-            _ => Err(WebGLError::InvalidEnum),
+            _ => Ok(self.webgl2_buffer_slot(target)?.get()),
+        }
+    }
+
+    /// The generic binding points which only exist in WebGL 2.
+    fn webgl2_buffer_slot(&self, target: u32) -> WebGLResult<&MutNullableDom<WebGLBuffer>> {
+        if self.webgl_version != WebGLVersion::WebGL2 {
+            return Err(InvalidEnum);
+        }
+        match target {
+            WebGL2RenderingContextConstants::UNIFORM_BUFFER => Ok(&self.bound_buffer_uniform),
+            WebGL2RenderingContextConstants::TRANSFORM_FEEDBACK_BUFFER => {
+                Ok(&self.bound_buffer_transform_feedback)
+            },
+            _ => Err(InvalidEnum),
+        }
+    }
+
+    /// Updates a generic binding point without telling the GL about it, for
+    /// the entry points such as bindBufferBase which also bind the buffer to
+    /// the generic binding point on their own.
+    pub fn set_generic_buffer_binding(
+        &self,
+        target: u32,
+        buffer: Option<&WebGLBuffer>,
+    ) -> WebGLResult<()> {
+        let slot = self.webgl2_buffer_slot(target)?;
+        if let Some(buffer) = buffer {
+            buffer.increment_attached_counter();
         }
+        if let Some(old) = slot.get() {
+            old.decrement_attached_counter();
+        }
+        slot.set(buffer);
+        Ok(())
+    }
+
+    pub fn current_program(&self) -> Option<DomRoot<WebGLProgram>> {
+        self.current_program.get()
     }
 
     pub fn create_vertex_array(&self) -> Option<DomRoot<WebGLVertexArrayObjectOES>> {
+        if self.context_lost.get() {
+            return None;
+        }
         let (sender, receiver) = webgl_channel().unwrap();
         self.send_command(WebGLCommand::CreateVertexArray(sender));
         receiver
@@ -1091,6 +1286,179 @@ impl WebGLRenderingContext {
     pub fn extension_manager(&self) -> &WebGLExtensions {
         &self.extension_manager
     }
+
+    pub fn supports_gl_extension(&self, name: &str) -> bool {
+        self.extension_manager
+            .init_once(|| self.get_gl_extensions());
+        self.extension_manager.supports_gl_extension(name)
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation.get()
+    }
+
+    pub fn is_context_lost(&self) -> bool {
+        self.context_lost.get()
+    }
+
+    /// Queries one of the strings which are only exposed through
+    /// `WEBGL_debug_renderer_info`.
+    fn get_gl_string(&self, name: u32) -> String {
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.send_command(WebGLCommand::GetString(name, sender));
+        receiver.recv().unwrap()
+    }
+
+    // https://www.khronos.org/registry/webgl/extensions/WEBGL_draw_buffers/
+    pub fn draw_buffers(&self, buffers: Vec<u32>) {
+        if buffers.len() as u32 > self.limits.max_draw_buffers {
+            return self.webgl_error(InvalidValue);
+        }
+
+        match self.bound_framebuffer.get() {
+            Some(fb) => {
+                let max_attachment =
+                    constants::COLOR_ATTACHMENT0 + self.limits.max_color_attachments;
+                for (i, &buffer) in buffers.iter().enumerate() {
+                    // The i-th draw buffer can only be NONE or COLOR_ATTACHMENTi.
+                    if buffer == constants::NONE ||
+                        buffer == constants::COLOR_ATTACHMENT0 + i as u32
+                    {
+                        continue;
+                    }
+                    if buffer == constants::BACK ||
+                        (buffer >= constants::COLOR_ATTACHMENT0 && buffer < max_attachment)
+                    {
+                        return self.webgl_error(InvalidOperation);
+                    }
+                    return self.webgl_error(InvalidEnum);
+                }
+                fb.set_draw_buffers(&buffers);
+            },
+            None => {
+                // The default framebuffer only has a single draw buffer.
+                if buffers.len() != 1 ||
+                    (buffers[0] != constants::BACK && buffers[0] != constants::NONE)
+                {
+                    return self.webgl_error(InvalidOperation);
+                }
+                self.default_draw_buffer.set(buffers[0]);
+            },
+        }
+
+        self.send_command(WebGLCommand::DrawBuffers(buffers));
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#CONTEXT_LOST
+    pub fn lose_context(&self) {
+        if self.context_lost.get() {
+            return;
+        }
+        self.context_lost.set(true);
+        self.restore_allowed.set(false);
+        // The next call to `getError` reports the loss of the context.
+        self.last_error.set(Some(WebGLError::ContextLost));
+
+        let global = self.global();
+        let this = Trusted::new(self);
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(webgl_context_lost: move || {
+                let context = this.root();
+                let event = WebGLContextEvent::new(
+                    &context.global(),
+                    atom!("webglcontextlost"),
+                    EventBubbles::DoesNotBubble,
+                    EventCancelable::Cancelable,
+                    DOMString::new(),
+                );
+                let status = event.upcast::<Event>().fire(context.canvas.upcast());
+                context.restore_allowed.set(status == EventStatus::Canceled);
+            }),
+            &global,
+        );
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#CONTEXT_RESTORED
+    pub fn restore_context(&self) -> WebGLResult<()> {
+        if !self.context_lost.get() {
+            return Err(InvalidOperation);
+        }
+
+        let global = self.global();
+        let this = Trusted::new(self);
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(webgl_context_restored: move || {
+                let context = this.root();
+                if !context.context_lost.get() || !context.restore_allowed.get() {
+                    return;
+                }
+                context.reset();
+                let event = WebGLContextEvent::new(
+                    &context.global(),
+                    atom!("webglcontextrestored"),
+                    EventBubbles::DoesNotBubble,
+                    EventCancelable::NotCancelable,
+                    DOMString::new(),
+                );
+                event.upcast::<Event>().fire(context.canvas.upcast());
+            }),
+            &global,
+        );
+        Ok(())
+    }
+
+    /// Puts the context back into the state of a newly created one, all
+    /// objects created before are invalidated.
+    fn reset(&self) {
+        self.context_lost.set(false);
+        self.restore_allowed.set(false);
+        self.generation.set(self.generation.get() + 1);
+        self.last_error.set(None);
+
+        self.texture_packing_alignment.set(4);
+        self.texture_unpacking_settings
+            .set(TextureUnpacking::CONVERT_COLORSPACE);
+        self.texture_unpacking_alignment.set(4);
+        self.bound_framebuffer.set(None);
+        self.bound_renderbuffer.set(None);
+        self.bound_buffer_array.set(None);
+        self.bound_buffer_uniform.set(None);
+        self.bound_buffer_transform_feedback.set(None);
+        self.current_program.set(None);
+        self.current_vertex_attrib_0.set((0., 0., 0., 1.));
+        let size = self.size.get();
+        self.current_scissor.set((0, 0, size.width, size.height));
+        self.current_clear_color.set((0., 0., 0., 0.));
+        self.default_draw_buffer.set(constants::BACK);
+        self.capabilities.reset();
+        self.default_vao.set(None);
+        self.current_vao.set(None);
+        self.textures.reset();
+
+        self.send_command(WebGLCommand::ResetState);
+        if self.extension_manager.is_draw_buffers_enabled() {
+            self.send_command(WebGLCommand::DrawBuffers(vec![constants::BACK]));
+        }
+
+        match self.canvas {
+            WebGLCanvas::HTMLCanvasElement(ref canvas) => {
+                if let Some(context) = canvas.context() {
+                    if let CanvasContext::WebGL2(ref context) = *context {
+                        context.reset();
+                    }
+                }
+            },
+            WebGLCanvas::OffscreenCanvas(ref canvas) => {
+                if let Some(context) = canvas.context() {
+                    if let OffscreenCanvasContext::WebGL2(ref context) = *context {
+                        context.reset();
+                    }
+                }
+            },
+        }
+
+        self.canvas.dirty();
+    }
 }
 
 #[cfg(not(feature = "webgl_backtrace"))]
@@ -1120,8 +1488,8 @@ impl Drop for WebGLRenderingContext {
 
 impl WebGLRenderingContextMethods for WebGLRenderingContext {
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.1
-    fn Canvas(&self) -> DomRoot<HTMLCanvasElement> {
-        DomRoot::from_ref(&*self.canvas)
+    fn Canvas(&self) -> HTMLCanvasElementOrOffscreenCanvas {
+        self.canvas.to_union()
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.11
@@ -1129,8 +1497,18 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
         self.send_command(WebGLCommand::Flush);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-offscreencanvasrenderingcontext2d-commit
+    fn Commit(&self) {
+        if let WebGLCanvas::OffscreenCanvas(ref canvas) = self.canvas {
+            canvas.commit();
+        }
+    }
+
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.11
     fn Finish(&self) {
+        if self.context_lost.get() {
+            return;
+        }
         let (sender, receiver) = webgl_channel().unwrap();
         self.send_command(WebGLCommand::Finish(sender));
         receiver.recv().unwrap()
@@ -1138,6 +1516,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.1
     fn DrawingBufferWidth(&self) -> i32 {
+        if self.context_lost.get() {
+            return self.size.get().width as i32;
+        }
         let (sender, receiver) = webgl_channel().unwrap();
         self.send_command(WebGLCommand::DrawingBufferWidth(sender));
         receiver.recv().unwrap()
@@ -1145,6 +1526,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.1
     fn DrawingBufferHeight(&self) -> i32 {
+        if self.context_lost.get() {
+            return self.size.get().height as i32;
+        }
         let (sender, receiver) = webgl_channel().unwrap();
         self.send_command(WebGLCommand::DrawingBufferHeight(sender));
         receiver.recv().unwrap()
@@ -1173,6 +1557,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
     #[allow(unsafe_code)]
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.3
     unsafe fn GetParameter(&self, cx: *mut JSContext, parameter: u32) -> JSVal {
+        if self.context_lost.get() {
+            return NullValue();
+        }
         if !self
             .extension_manager
             .is_get_parameter_name_enabled(parameter)
@@ -1230,9 +1617,11 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
                 return Int32Value(constants::UNSIGNED_BYTE as i32);
             },
             constants::COMPRESSED_TEXTURE_FORMATS => {
-                // FIXME(nox): https://github.com/servo/servo/issues/20594
+                let format_ids = self.extension_manager.get_tex_compression_ids();
+
                 rooted!(in(cx) let mut rval = ptr::null_mut::<JSObject>());
-                let _ = Uint32Array::create(cx, CreateWith::Slice(&[]), rval.handle_mut()).unwrap();
+                let _ = Uint32Array::create(cx, CreateWith::Slice(&format_ids), rval.handle_mut())
+                    .unwrap();
                 return ObjectValue(rval.get());
             },
             constants::VERSION => {
@@ -1245,6 +1634,33 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
                 "Mozilla/Servo".to_jsval(cx, rval.handle_mut());
                 return rval.get();
             },
+            WEBGLDebugRendererInfoConstants::UNMASKED_VENDOR_WEBGL |
+            WEBGLDebugRendererInfoConstants::UNMASKED_RENDERER_WEBGL => {
+                let name = if parameter == WEBGLDebugRendererInfoConstants::UNMASKED_VENDOR_WEBGL {
+                    constants::VENDOR
+                } else {
+                    constants::RENDERER
+                };
+                rooted!(in(cx) let mut rval = UndefinedValue());
+                self.get_gl_string(name).to_jsval(cx, rval.handle_mut());
+                return rval.get();
+            },
+            WEBGLDrawBuffersConstants::DRAW_BUFFER0_WEBGL
+                ..=WEBGLDrawBuffersConstants::DRAW_BUFFER15_WEBGL => {
+                let index = parameter - WEBGLDrawBuffersConstants::DRAW_BUFFER0_WEBGL;
+                if !self.extension_manager.is_draw_buffers_enabled() ||
+                    index >= self.limits.max_draw_buffers
+                {
+                    self.webgl_error(InvalidEnum);
+                    return NullValue();
+                }
+                let draw_buffer = match self.bound_framebuffer.get() {
+                    Some(fb) => fb.draw_buffer(index as usize).unwrap_or(constants::NONE),
+                    None if index == 0 => self.default_draw_buffer.get(),
+                    None => constants::NONE,
+                };
+                return Int32Value(draw_buffer as i32);
+            },
             constants::SHADING_LANGUAGE_VERSION => {
                 rooted!(in(cx) let mut rval = UndefinedValue());
                 "WebGL GLSL ES 1.0".to_jsval(cx, rval.handle_mut());
@@ -1294,6 +1710,10 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
                 Some(self.limits.max_vertex_texture_image_units)
             },
             constants::MAX_VERTEX_UNIFORM_VECTORS => Some(self.limits.max_vertex_uniform_vectors),
+            WEBGLDrawBuffersConstants::MAX_COLOR_ATTACHMENTS_WEBGL => {
+                Some(self.limits.max_color_attachments)
+            },
+            WEBGLDrawBuffersConstants::MAX_DRAW_BUFFERS_WEBGL => Some(self.limits.max_draw_buffers),
             _ => None,
         };
         if let Some(limit) = limit {
@@ -1385,6 +1805,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
     #[allow(unsafe_code)]
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.8
     unsafe fn GetTexParameter(&self, _cx: *mut JSContext, target: u32, pname: u32) -> JSVal {
+        if self.context_lost.get() {
+            return NullValue();
+        }
         let texture_slot = handle_potential_webgl_error!(
             self,
             self.textures.active_texture_slot(target),
@@ -1443,8 +1866,16 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
         error_code
     }
 
+    // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.13
+    fn IsContextLost(&self) -> bool {
+        self.context_lost.get()
+    }
+
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.2
     fn GetContextAttributes(&self) -> Option<WebGLContextAttributes> {
+        if self.context_lost.get() {
+            return None;
+        }
         let (sender, receiver) = webgl_channel().unwrap();
 
         // If the send does not succeed, assume context lost
@@ -1473,6 +1904,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.14
     fn GetSupportedExtensions(&self) -> Option<Vec<DOMString>> {
+        if self.context_lost.get() {
+            return None;
+        }
         self.extension_manager
             .init_once(|| self.get_gl_extensions());
         let extensions = self.extension_manager.get_suported_extensions();
@@ -1491,6 +1925,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
         _cx: *mut JSContext,
         name: DOMString,
     ) -> Option<NonNull<JSObject>> {
+        if self.context_lost.get() {
+            return None;
+        }
         self.extension_manager
             .init_once(|| self.get_gl_extensions());
         self.extension_manager.get_or_init_extension(&name, self)
@@ -1589,7 +2026,7 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
                 current_vao = self.current_vao();
                 current_vao.element_array_buffer()
             },
-            _ => return self.webgl_error(InvalidEnum),
+            _ => handle_potential_webgl_error!(self, self.webgl2_buffer_slot(target), return),
         };
 
         if let Some(buffer) = buffer {
@@ -1709,7 +2146,7 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
                 ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref data) => data.as_slice(),
             }
         };
-        handle_potential_webgl_error!(self, bound_buffer.buffer_data(data, usage));
+        handle_potential_webgl_error!(self, bound_buffer.buffer_data(target, data, usage));
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
@@ -1725,7 +2162,7 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
         // FIXME: Allocating a buffer based on user-requested size is
         // not great, but we don't have a fallible allocation to try.
         let data = vec![0u8; size as usize];
-        handle_potential_webgl_error!(self, bound_buffer.buffer_data(&data, usage));
+        handle_potential_webgl_error!(self, bound_buffer.buffer_data(target, &data, usage));
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
@@ -1759,36 +2196,104 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.8
+    #[allow(unsafe_code)]
     fn CompressedTexImage2D(
         &self,
-        _target: u32,
-        _level: i32,
-        _internal_format: u32,
-        _width: i32,
-        _height: i32,
-        _border: i32,
-        _data: CustomAutoRooterGuard<ArrayBufferView>,
+        target: u32,
+        level: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        border: i32,
+        data: CustomAutoRooterGuard<ArrayBufferView>,
     ) {
-        // FIXME: No compressed texture format is currently supported, so error out as per
-        // https://www.khronos.org/registry/webgl/specs/latest/1.0/#COMPRESSED_TEXTURE_SUPPORT
-        self.webgl_error(InvalidEnum);
+        let validator = CompressedTexImage2DValidator::new(
+            self,
+            target,
+            level,
+            width,
+            height,
+            border,
+            internal_format,
+            data.len(),
+        );
+        let CommonCompressedTexImage2DValidatorResult {
+            texture,
+            target,
+            level,
+            width,
+            height,
+            compression,
+        } = match validator.validate() {
+            Ok(result) => result,
+            Err(_) => return,
+        };
+
+        // NB: TexImage2D depth is always equal to 1
+        handle_potential_webgl_error!(
+            self,
+            texture.initialize(target, width, height, 1, compression.format, level, None)
+        );
+
+        let buff = IpcSharedMemory::from_bytes(unsafe { data.as_slice() });
+        self.send_command(WebGLCommand::CompressedTexImage2D {
+            target: target.as_gl_constant(),
+            level,
+            internal_format,
+            size: Size2D::new(width, height),
+            data: buff.into(),
+        });
+
+        if let Some(fb) = self.bound_framebuffer.get() {
+            fb.invalidate_texture(&*texture);
+        }
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.8
+    #[allow(unsafe_code)]
     fn CompressedTexSubImage2D(
         &self,
-        _target: u32,
-        _level: i32,
-        _xoffset: i32,
-        _yoffset: i32,
-        _width: i32,
-        _height: i32,
-        _format: u32,
-        _data: CustomAutoRooterGuard<ArrayBufferView>,
+        target: u32,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        data: CustomAutoRooterGuard<ArrayBufferView>,
     ) {
-        // FIXME: No compressed texture format is currently supported, so error out as per
-        // https://www.khronos.org/registry/webgl/specs/latest/1.0/#COMPRESSED_TEXTURE_SUPPORT
-        self.webgl_error(InvalidEnum);
+        let validator = CompressedTexSubImage2DValidator::new(
+            self,
+            target,
+            level,
+            xoffset,
+            yoffset,
+            width,
+            height,
+            format,
+            data.len(),
+        );
+        let CommonCompressedTexImage2DValidatorResult {
+            target,
+            level,
+            width,
+            height,
+            ..
+        } = match validator.validate() {
+            Ok(result) => result,
+            Err(_) => return,
+        };
+
+        let buff = IpcSharedMemory::from_bytes(unsafe { data.as_slice() });
+        self.send_command(WebGLCommand::CompressedTexSubImage2D {
+            target: target.as_gl_constant(),
+            level: level as i32,
+            xoffset,
+            yoffset,
+            size: Size2D::new(width, height),
+            format,
+            data: buff.into(),
+        });
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.8
@@ -1827,6 +2332,11 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
             Err(_) => return,
         };
 
+        // https://www.khronos.org/registry/webgl/extensions/WEBGL_depth_texture/
+        if internal_format.is_depth() {
+            return self.webgl_error(InvalidOperation);
+        }
+
         // NB: TexImage2D depth is always equal to 1
         handle_potential_webgl_error!(
             self,
@@ -1894,6 +2404,14 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
         let image_info = texture.image_info_for_target(&target, level);
 
+        // https://www.khronos.org/registry/webgl/extensions/WEBGL_depth_texture/
+        if image_info
+            .internal_format()
+            .map_or(false, |format| format.is_depth() || format.is_compressed())
+        {
+            return self.webgl_error(InvalidOperation);
+        }
+
         // GL_INVALID_VALUE is generated if:
         //   - xoffset or yoffset is less than 0
         //   - x offset plus the width is greater than the texture width
@@ -1923,6 +2441,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.11
     fn Clear(&self, mask: u32) {
+        if self.context_lost.get() {
+            return;
+        }
         handle_potential_webgl_error!(self, self.validate_framebuffer(), return);
         if mask &
             !(constants::DEPTH_BUFFER_BIT |
@@ -2034,31 +2555,49 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
     fn CreateBuffer(&self) -> Option<DomRoot<WebGLBuffer>> {
+        if self.context_lost.get() {
+            return None;
+        }
         WebGLBuffer::maybe_new(self)
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.6
     fn CreateFramebuffer(&self) -> Option<DomRoot<WebGLFramebuffer>> {
+        if self.context_lost.get() {
+            return None;
+        }
         WebGLFramebuffer::maybe_new(self)
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.7
     fn CreateRenderbuffer(&self) -> Option<DomRoot<WebGLRenderbuffer>> {
+        if self.context_lost.get() {
+            return None;
+        }
         WebGLRenderbuffer::maybe_new(self)
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.8
     fn CreateTexture(&self) -> Option<DomRoot<WebGLTexture>> {
+        if self.context_lost.get() {
+            return None;
+        }
         WebGLTexture::maybe_new(self)
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.9
     fn CreateProgram(&self) -> Option<DomRoot<WebGLProgram>> {
+        if self.context_lost.get() {
+            return None;
+        }
         WebGLProgram::maybe_new(self)
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.9
     fn CreateShader(&self, shader_type: u32) -> Option<DomRoot<WebGLShader>> {
+        if self.context_lost.get() {
+            return None;
+        }
         match shader_type {
             constants::VERTEX_SHADER | constants::FRAGMENT_SHADER => {},
             _ => {
@@ -2088,6 +2627,15 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
             self.bound_buffer_array.set(None);
             buffer.decrement_attached_counter();
         }
+        for slot in &[
+            &self.bound_buffer_uniform,
+            &self.bound_buffer_transform_feedback,
+        ] {
+            if slot.get().map_or(false, |b| buffer == &*b) {
+                slot.set(None);
+                buffer.decrement_attached_counter();
+            }
+        }
         buffer.mark_for_deletion();
     }
 
@@ -2252,6 +2800,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
         attachment: u32,
         pname: u32,
     ) -> JSVal {
+        if self.context_lost.get() {
+            return NullValue();
+        }
         // Check if currently bound framebuffer is non-zero as per spec.
         if self.bound_framebuffer.get().is_none() {
             self.webgl_error(InvalidOperation);
@@ -2272,7 +2823,12 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
             constants::DEPTH_STENCIL_ATTACHMENT |
             constants::DEPTH_ATTACHMENT |
             constants::STENCIL_ATTACHMENT => true,
-            _ => false,
+            // https://www.khronos.org/registry/webgl/extensions/WEBGL_draw_buffers/
+            _ => {
+                self.extension_manager.is_draw_buffers_enabled() &&
+                    attachment > constants::COLOR_ATTACHMENT0 &&
+                    attachment < constants::COLOR_ATTACHMENT0 + self.limits.max_color_attachments
+            },
         };
         let pname_matches = match pname {
             // constants::FRAMEBUFFER_ATTACHMENT_ALPHA_SIZE |
@@ -2288,6 +2844,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
             constants::FRAMEBUFFER_ATTACHMENT_OBJECT_TYPE |
             constants::FRAMEBUFFER_ATTACHMENT_TEXTURE_CUBE_MAP_FACE |
             constants::FRAMEBUFFER_ATTACHMENT_TEXTURE_LEVEL => true,
+            EXTsRGBConstants::FRAMEBUFFER_ATTACHMENT_COLOR_ENCODING_EXT => {
+                self.extension_manager.is_enabled::<EXTsRGB>()
+            },
             _ => false,
         };
 
@@ -2296,14 +2855,16 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
                 Some(attachment_root) => match attachment_root {
                     WebGLFramebufferAttachmentRoot::Renderbuffer(_) => match pname {
                         constants::FRAMEBUFFER_ATTACHMENT_OBJECT_TYPE |
-                        constants::FRAMEBUFFER_ATTACHMENT_OBJECT_NAME => true,
+                        constants::FRAMEBUFFER_ATTACHMENT_OBJECT_NAME |
+                        EXTsRGBConstants::FRAMEBUFFER_ATTACHMENT_COLOR_ENCODING_EXT => true,
                         _ => false,
                     },
                     WebGLFramebufferAttachmentRoot::Texture(_) => match pname {
                         constants::FRAMEBUFFER_ATTACHMENT_OBJECT_TYPE |
                         constants::FRAMEBUFFER_ATTACHMENT_OBJECT_NAME |
                         constants::FRAMEBUFFER_ATTACHMENT_TEXTURE_LEVEL |
-                        constants::FRAMEBUFFER_ATTACHMENT_TEXTURE_CUBE_MAP_FACE => true,
+                        constants::FRAMEBUFFER_ATTACHMENT_TEXTURE_CUBE_MAP_FACE |
+                        EXTsRGBConstants::FRAMEBUFFER_ATTACHMENT_COLOR_ENCODING_EXT => true,
                         _ => false,
                     },
                 },
@@ -2362,6 +2923,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
         target: u32,
         pname: u32,
     ) -> JSVal {
+        if self.context_lost.get() {
+            return NullValue();
+        }
         let target_matches = target == constants::RENDERBUFFER;
 
         let pname_matches = match pname {
@@ -2490,6 +3054,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
         shader_type: u32,
         precision_type: u32,
     ) -> Option<DomRoot<WebGLShaderPrecisionFormat>> {
+        if self.context_lost.get() {
+            return None;
+        }
         match shader_type {
             constants::FRAGMENT_SHADER | constants::VERTEX_SHADER => (),
             _ => {
@@ -2520,7 +3087,7 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
         let (range_min, range_max, precision) = receiver.recv().unwrap();
         Some(WebGLShaderPrecisionFormat::new(
-            self.global().as_window(),
+            &self.global(),
             range_min,
             range_max,
             precision,
@@ -2540,6 +3107,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
     #[allow(unsafe_code)]
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.9
     unsafe fn GetVertexAttrib(&self, cx: *mut JSContext, index: u32, param: u32) -> JSVal {
+        if self.context_lost.get() {
+            return NullValue();
+        }
         let current_vao = self.current_vao();
         let data = handle_potential_webgl_error!(
             self,
@@ -2741,6 +3311,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
         pixel_type: u32,
         mut pixels: CustomAutoRooterGuard<Option<ArrayBufferView>>,
     ) {
+        if self.context_lost.get() {
+            return;
+        }
         let pixels =
             handle_potential_webgl_error!(self, pixels.as_mut().ok_or(InvalidValue), return);
 
@@ -3607,6 +4180,11 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
             Err(_) => return Ok(()), // NB: The validator sets the correct error for us.
         };
 
+        // https://www.khronos.org/registry/webgl/extensions/WEBGL_depth_texture/
+        if format.is_depth() && pixels.is_some() && self.webgl_version() == WebGLVersion::WebGL1 {
+            return Ok(self.webgl_error(InvalidOperation));
+        }
+
         let unpacking_alignment = self.texture_unpacking_alignment.get();
 
         let expected_byte_length = match {
@@ -3706,6 +4284,11 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
             Err(_) => return Ok(()), // NB: The validator sets the correct error for us.
         };
 
+        // https://www.khronos.org/registry/webgl/extensions/WEBGL_depth_texture/
+        if format.is_depth() {
+            return Ok(self.webgl_error(InvalidOperation));
+        }
+
         if !self.validate_filterable_texture(
             &texture,
             target,
@@ -3810,6 +4393,11 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
             Err(_) => return Ok(()), // NB: The validator sets the correct error for us.
         };
 
+        // https://www.khronos.org/registry/webgl/extensions/WEBGL_depth_texture/
+        if format.is_depth() && self.webgl_version() == WebGLVersion::WebGL1 {
+            return Ok(self.webgl_error(InvalidOperation));
+        }
+
         let unpacking_alignment = self.texture_unpacking_alignment.get();
 
         let expected_byte_length = match {
@@ -3898,6 +4486,11 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
             Err(_) => return Ok(()), // NB: The validator sets the correct error for us.
         };
 
+        // https://www.khronos.org/registry/webgl/extensions/WEBGL_depth_texture/
+        if format.is_depth() {
+            return Ok(self.webgl_error(InvalidOperation));
+        }
+
         self.tex_sub_image_2d(
             texture, target, level, xoffset, yoffset, format, data_type, 1, pixels,
         );
@@ -4044,6 +4637,10 @@ impl Capabilities {
     fn is_enabled(&self, cap: u32) -> WebGLResult<bool> {
         Ok(self.value.get().contains(CapFlags::from_enum(cap)?))
     }
+
+    fn reset(&self) {
+        self.value.set(Default::default());
+    }
 }
 
 impl Default for CapFlags {
@@ -4108,6 +4705,10 @@ impl Textures {
         }
     }
 
+    pub fn active_unit_index(&self) -> u32 {
+        self.active_unit.get()
+    }
+
     fn active_unit_enum(&self) -> u32 {
         self.active_unit.get() + constants::TEXTURE0
     }
@@ -4150,6 +4751,14 @@ impl Textures {
         &self.units[self.active_unit.get() as usize]
     }
 
+    fn reset(&self) {
+        self.active_unit.set(0);
+        for unit in &*self.units {
+            unit.tex_2d.set(None);
+            unit.tex_cube_map.set(None);
+        }
+    }
+
     fn iter(&self) -> impl Iterator<Item = (u32, &TextureUnit)> {
         self.units
             .iter()
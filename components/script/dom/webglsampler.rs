@@ -0,0 +1,116 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+// https://www.khronos.org/registry/webgl/specs/latest/2.0/webgl2.idl
+use crate::dom::bindings::codegen::Bindings::WebGLSamplerBinding;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::webgl_validations::webgl2::{self, SamplerParameterValue};
+use crate::dom::webglobject::WebGLObject;
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use canvas_traits::webgl::WebGLError::*;
+use canvas_traits::webgl::{webgl_channel, WebGLCommand, WebGLResult, WebGLSamplerId};
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct WebGLSampler {
+    webgl_object: WebGLObject,
+    id: WebGLSamplerId,
+    marked_for_deletion: Cell<bool>,
+}
+
+impl WebGLSampler {
+    fn new_inherited(context: &WebGLRenderingContext, id: WebGLSamplerId) -> Self {
+        Self {
+            webgl_object: WebGLObject::new_inherited(context),
+            id,
+            marked_for_deletion: Default::default(),
+        }
+    }
+
+    pub fn maybe_new(context: &WebGLRenderingContext) -> Option<DomRoot<Self>> {
+        let (sender, receiver) = webgl_channel().unwrap();
+        context.send_command(WebGLCommand::CreateSampler(sender));
+        receiver
+            .recv()
+            .unwrap()
+            .map(|id| WebGLSampler::new(context, id))
+    }
+
+    pub fn new(context: &WebGLRenderingContext, id: WebGLSamplerId) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(WebGLSampler::new_inherited(context, id)),
+            &*context.global(),
+            WebGLSamplerBinding::Wrap,
+        )
+    }
+}
+
+impl WebGLSampler {
+    pub fn id(&self) -> WebGLSamplerId {
+        self.id
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.marked_for_deletion.get()
+    }
+
+    pub fn delete(&self) {
+        if self.marked_for_deletion.get() {
+            return;
+        }
+        self.marked_for_deletion.set(true);
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::DeleteSampler(self.id));
+    }
+
+    pub fn set_parameter(&self, pname: u32, value: SamplerParameterValue) -> WebGLResult<()> {
+        if self.marked_for_deletion.get() {
+            return Err(InvalidOperation);
+        }
+        webgl2::validate_sampler_parameter(pname, value)?;
+        let command = if webgl2::is_float_sampler_parameter(pname)? {
+            let value = match value {
+                SamplerParameterValue::Int(value) => value as f32,
+                SamplerParameterValue::Float(value) => value,
+            };
+            WebGLCommand::SamplerParameterf(self.id, pname, value)
+        } else {
+            let value = match value {
+                SamplerParameterValue::Int(value) => value,
+                SamplerParameterValue::Float(value) => value as i32,
+            };
+            WebGLCommand::SamplerParameteri(self.id, pname, value)
+        };
+        self.upcast::<WebGLObject>().context().send_command(command);
+        Ok(())
+    }
+
+    pub fn get_parameter(&self, pname: u32) -> WebGLResult<SamplerParameterValue> {
+        if self.marked_for_deletion.get() {
+            return Err(InvalidOperation);
+        }
+        let context = self.upcast::<WebGLObject>().context();
+        if webgl2::is_float_sampler_parameter(pname)? {
+            let (sender, receiver) = webgl_channel().unwrap();
+            context.send_command(WebGLCommand::GetSamplerParameterFloat(
+                self.id, pname, sender,
+            ));
+            Ok(SamplerParameterValue::Float(receiver.recv().unwrap()))
+        } else {
+            let (sender, receiver) = webgl_channel().unwrap();
+            context.send_command(WebGLCommand::GetSamplerParameterInt(self.id, pname, sender));
+            Ok(SamplerParameterValue::Int(receiver.recv().unwrap()))
+        }
+    }
+}
+
+impl Drop for WebGLSampler {
+    fn drop(&mut self) {
+        self.delete();
+    }
+}
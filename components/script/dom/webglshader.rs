@@ -9,8 +9,10 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::webgl_extensions::ext::extfragdepth::EXTFragDepth;
 use crate::dom::webgl_extensions::ext::extshadertexturelod::EXTShaderTextureLod;
 use crate::dom::webgl_extensions::ext::oesstandardderivatives::OESStandardDerivatives;
+use crate::dom::webgl_extensions::ext::webgldrawbuffers::WEBGLDrawBuffers;
 use crate::dom::webgl_extensions::WebGLExtensions;
 use crate::dom::webglobject::WebGLObject;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
@@ -107,6 +109,11 @@ impl WebGLShader {
 
         let source = self.source.borrow();
 
+        let max_draw_buffers = if ext.is_draw_buffers_enabled() {
+            limits.max_draw_buffers
+        } else {
+            1
+        };
         let params = BuiltInResources {
             MaxVertexAttribs: limits.max_vertex_attribs as c_int,
             MaxVertexUniformVectors: limits.max_vertex_uniform_vectors as c_int,
@@ -117,6 +124,9 @@ impl WebGLShader {
             MaxFragmentUniformVectors: limits.max_fragment_uniform_vectors as c_int,
             OES_standard_derivatives: ext.is_enabled::<OESStandardDerivatives>() as c_int,
             EXT_shader_texture_lod: ext.is_enabled::<EXTShaderTextureLod>() as c_int,
+            EXT_frag_depth: ext.is_enabled::<EXTFragDepth>() as c_int,
+            EXT_draw_buffers: ext.is_enabled::<WEBGLDrawBuffers>() as c_int,
+            MaxDrawBuffers: max_draw_buffers as c_int,
             FragmentPrecisionHigh: 1,
             ..BuiltInResources::default()
         };
@@ -0,0 +1,158 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+// https://www.khronos.org/registry/webgl/specs/latest/2.0/webgl2.idl
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants as constants;
+use crate::dom::bindings::codegen::Bindings::WebGLSyncBinding;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::webglobject::WebGLObject;
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use crate::task_source::TaskSource;
+use canvas_traits::webgl::WebGLError::*;
+use canvas_traits::webgl::{webgl_channel, WebGLCommand, WebGLResult, WebGLSyncId};
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct WebGLSync {
+    webgl_object: WebGLObject,
+    id: WebGLSyncId,
+    marked_for_deletion: Cell<bool>,
+    /// Whether control returned to the event loop since the fence was
+    /// inserted, its status must not change before that.
+    ready: Cell<bool>,
+    /// Set once the fence was observed as signaled.
+    signaled: Cell<bool>,
+}
+
+impl WebGLSync {
+    fn new_inherited(context: &WebGLRenderingContext, id: WebGLSyncId) -> Self {
+        Self {
+            webgl_object: WebGLObject::new_inherited(context),
+            id,
+            marked_for_deletion: Default::default(),
+            ready: Default::default(),
+            signaled: Default::default(),
+        }
+    }
+
+    pub fn maybe_new(context: &WebGLRenderingContext) -> Option<DomRoot<Self>> {
+        let (sender, receiver) = webgl_channel().unwrap();
+        context.send_command(WebGLCommand::FenceSync(sender));
+        let sync = receiver
+            .recv()
+            .unwrap()
+            .map(|id| WebGLSync::new(context, id))?;
+
+        let global = context.global();
+        let this = Trusted::new(&*sync);
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(sync_ready: move || {
+                this.root().ready.set(true);
+            }),
+            &global,
+        );
+        Some(sync)
+    }
+
+    pub fn new(context: &WebGLRenderingContext, id: WebGLSyncId) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(WebGLSync::new_inherited(context, id)),
+            &*context.global(),
+            WebGLSyncBinding::Wrap,
+        )
+    }
+}
+
+impl WebGLSync {
+    pub fn id(&self) -> WebGLSyncId {
+        self.id
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.marked_for_deletion.get()
+    }
+
+    pub fn delete(&self) {
+        if self.marked_for_deletion.get() {
+            return;
+        }
+        self.marked_for_deletion.set(true);
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::DeleteSync(self.id));
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.14
+    pub fn client_wait(&self, flags: u32, timeout: u64) -> WebGLResult<u32> {
+        if self.marked_for_deletion.get() {
+            return Err(InvalidOperation);
+        }
+        if self.signaled.get() {
+            return Ok(constants::ALREADY_SIGNALED);
+        }
+        if !self.ready.get() {
+            return Ok(constants::TIMEOUT_EXPIRED);
+        }
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::ClientWaitSync(
+                self.id, flags, timeout, sender,
+            ));
+        let status = receiver.recv().unwrap();
+        if status == constants::ALREADY_SIGNALED || status == constants::CONDITION_SATISFIED {
+            self.signaled.set(true);
+        }
+        Ok(status)
+    }
+
+    pub fn wait(&self, flags: u32, timeout: i64) -> WebGLResult<()> {
+        if self.marked_for_deletion.get() {
+            return Err(InvalidOperation);
+        }
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::WaitSync(self.id, flags, timeout));
+        Ok(())
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.14
+    pub fn get_parameter(&self, pname: u32) -> WebGLResult<u32> {
+        if self.marked_for_deletion.get() {
+            return Err(InvalidOperation);
+        }
+        match pname {
+            constants::OBJECT_TYPE => Ok(constants::SYNC_FENCE),
+            constants::SYNC_CONDITION => Ok(constants::SYNC_GPU_COMMANDS_COMPLETE),
+            constants::SYNC_FLAGS => Ok(0),
+            constants::SYNC_STATUS => {
+                if !self.signaled.get() && self.ready.get() {
+                    let (sender, receiver) = webgl_channel().unwrap();
+                    self.upcast::<WebGLObject>().context().send_command(
+                        WebGLCommand::GetSyncParameter(self.id, constants::SYNC_STATUS, sender),
+                    );
+                    if receiver.recv().unwrap() == constants::SIGNALED {
+                        self.signaled.set(true);
+                    }
+                }
+                if self.signaled.get() {
+                    Ok(constants::SIGNALED)
+                } else {
+                    Ok(constants::UNSIGNALED)
+                }
+            },
+            _ => Err(InvalidEnum),
+        }
+    }
+}
+
+impl Drop for WebGLSync {
+    fn drop(&mut self) {
+        self.delete();
+    }
+}
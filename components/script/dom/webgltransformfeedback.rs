@@ -0,0 +1,271 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+// https://www.khronos.org/registry/webgl/specs/latest/2.0/webgl2.idl
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants as constants;
+use crate::dom::bindings::codegen::Bindings::WebGLTransformFeedbackBinding;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::webglbuffer::{IndexedBufferBinding, WebGLBuffer};
+use crate::dom::webglobject::WebGLObject;
+use crate::dom::webglprogram::WebGLProgram;
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use canvas_traits::webgl::WebGLError::*;
+use canvas_traits::webgl::{webgl_channel, WebGLCommand, WebGLResult, WebGLTransformFeedbackId};
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct WebGLTransformFeedback {
+    webgl_object: WebGLObject,
+    /// `None` for the default transform feedback object of the context.
+    id: Option<WebGLTransformFeedbackId>,
+    ever_bound: Cell<bool>,
+    is_deleted: Cell<bool>,
+    /// The primitive mode passed to `beginTransformFeedback`, if active.
+    primitive_mode: Cell<Option<u32>>,
+    paused: Cell<bool>,
+    /// The program which was current when transform feedback began.
+    program: MutNullableDom<WebGLProgram>,
+    /// The number of vertices which can still be captured before one of the
+    /// bound buffers overflows.
+    remaining_vertices: Cell<usize>,
+    buffer_bindings: Box<[IndexedBufferBinding]>,
+}
+
+impl WebGLTransformFeedback {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(
+        context: &WebGLRenderingContext,
+        id: Option<WebGLTransformFeedbackId>,
+    ) -> Self {
+        let max_bindings = context.limits().max_transform_feedback_separate_attribs;
+        Self {
+            webgl_object: WebGLObject::new_inherited(context),
+            id,
+            ever_bound: Default::default(),
+            is_deleted: Default::default(),
+            primitive_mode: Default::default(),
+            paused: Default::default(),
+            program: Default::default(),
+            remaining_vertices: Default::default(),
+            buffer_bindings: (0..max_bindings).map(|_| Default::default()).collect(),
+        }
+    }
+
+    pub fn maybe_new(context: &WebGLRenderingContext) -> Option<DomRoot<Self>> {
+        let (sender, receiver) = webgl_channel().unwrap();
+        context.send_command(WebGLCommand::CreateTransformFeedback(sender));
+        receiver
+            .recv()
+            .unwrap()
+            .map(|id| WebGLTransformFeedback::new(context, Some(id)))
+    }
+
+    pub fn new(
+        context: &WebGLRenderingContext,
+        id: Option<WebGLTransformFeedbackId>,
+    ) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(WebGLTransformFeedback::new_inherited(context, id)),
+            &*context.global(),
+            WebGLTransformFeedbackBinding::Wrap,
+        )
+    }
+}
+
+impl WebGLTransformFeedback {
+    pub fn id(&self) -> Option<WebGLTransformFeedbackId> {
+        self.id
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.is_deleted.get()
+    }
+
+    pub fn delete(&self) {
+        assert!(self.id.is_some());
+        if self.is_deleted.get() {
+            return;
+        }
+        self.is_deleted.set(true);
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::DeleteTransformFeedback(self.id.unwrap()));
+        for binding in &*self.buffer_bindings {
+            binding.set(None, 0, 0);
+        }
+    }
+
+    pub fn buffer_binding(&self, index: u32) -> Option<&IndexedBufferBinding> {
+        self.buffer_bindings.get(index as usize)
+    }
+
+    pub fn unbind_buffer(&self, buffer: &WebGLBuffer) {
+        for binding in &*self.buffer_bindings {
+            binding.unbind(buffer);
+        }
+    }
+
+    pub fn ever_bound(&self) -> bool {
+        self.ever_bound.get()
+    }
+
+    pub fn set_ever_bound(&self) {
+        self.ever_bound.set(true);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.primitive_mode.get().is_some()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.get()
+    }
+
+    /// Whether transform feedback is active and not paused, i.e. whether
+    /// draw calls currently capture vertices.
+    pub fn is_capturing(&self) -> bool {
+        self.is_active() && !self.paused.get()
+    }
+
+    pub fn primitive_mode(&self) -> Option<u32> {
+        self.primitive_mode.get()
+    }
+
+    pub fn program(&self) -> Option<DomRoot<WebGLProgram>> {
+        self.program.get()
+    }
+
+    /// https://www.khronos.org/registry/OpenGL/specs/es/3.0/es_spec_3.0.pdf#nameddest=section-2.15.2
+    pub fn begin(&self, primitive_mode: u32, program: &WebGLProgram) -> WebGLResult<()> {
+        if self.is_active() {
+            return Err(InvalidOperation);
+        }
+        let varyings = program.transform_feedback_varyings();
+        if varyings.is_empty() {
+            return Err(InvalidOperation);
+        }
+        let sizes = varyings
+            .iter()
+            .map(|varying| varying_byte_size(varying.type_) * varying.size.max(0) as usize);
+        let per_buffer: Vec<usize> =
+            if program.transform_feedback_buffer_mode() == constants::INTERLEAVED_ATTRIBS {
+                vec![sizes.sum()]
+            } else {
+                sizes.collect()
+            };
+        let mut remaining_vertices = usize::max_value();
+        for (index, bytes_per_vertex) in per_buffer.iter().enumerate() {
+            let binding = self.buffer_bindings.get(index).ok_or(InvalidOperation)?;
+            if binding.buffer().is_none() {
+                return Err(InvalidOperation);
+            }
+            if *bytes_per_vertex > 0 {
+                remaining_vertices =
+                    remaining_vertices.min(binding.available_bytes() / bytes_per_vertex);
+            }
+        }
+        drop(varyings);
+
+        self.remaining_vertices.set(remaining_vertices);
+        self.primitive_mode.set(Some(primitive_mode));
+        self.paused.set(false);
+        self.program.set(Some(program));
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::BeginTransformFeedback(primitive_mode));
+        Ok(())
+    }
+
+    /// Checks a draw call against the capture state and returns the number of
+    /// vertices it would capture, which must not overflow the bound buffers.
+    pub fn validate_draw(&self, mode: u32, count: i32, instances: i32) -> WebGLResult<usize> {
+        if !self.is_capturing() {
+            return Ok(0);
+        }
+        if Some(mode) != self.primitive_mode.get() {
+            return Err(InvalidOperation);
+        }
+        let per_primitive = match mode {
+            constants::LINES => 2,
+            constants::TRIANGLES => 3,
+            _ => 1,
+        };
+        let count = count.max(0) as usize;
+        let vertices = (count - count % per_primitive) * instances.max(0) as usize;
+        if vertices > self.remaining_vertices.get() {
+            return Err(InvalidOperation);
+        }
+        Ok(vertices)
+    }
+
+    pub fn record_captured_vertices(&self, vertices: usize) {
+        self.remaining_vertices
+            .set(self.remaining_vertices.get() - vertices);
+    }
+
+    pub fn end(&self) {
+        self.primitive_mode.set(None);
+        self.paused.set(false);
+        self.program.set(None);
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::EndTransformFeedback);
+    }
+
+    pub fn pause(&self) {
+        self.paused.set(true);
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::PauseTransformFeedback);
+    }
+
+    /// Forgets about the capture state and buffer bindings, used for the
+    /// default transform feedback object when its context is restored.
+    pub fn reset(&self) {
+        self.primitive_mode.set(None);
+        self.paused.set(false);
+        self.program.set(None);
+        for binding in &*self.buffer_bindings {
+            binding.set(None, 0, 0);
+        }
+    }
+
+    pub fn resume(&self) {
+        self.paused.set(false);
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::ResumeTransformFeedback);
+    }
+}
+
+/// The number of bytes captured for a single element of a varying of the
+/// given type.
+fn varying_byte_size(type_: u32) -> usize {
+    let components = match type_ {
+        constants::FLOAT_VEC2 | constants::INT_VEC2 | constants::UNSIGNED_INT_VEC2 => 2,
+        constants::FLOAT_VEC3 | constants::INT_VEC3 | constants::UNSIGNED_INT_VEC3 => 3,
+        constants::FLOAT_VEC4 |
+        constants::INT_VEC4 |
+        constants::UNSIGNED_INT_VEC4 |
+        constants::FLOAT_MAT2 => 4,
+        constants::FLOAT_MAT2x3 | constants::FLOAT_MAT3x2 => 6,
+        constants::FLOAT_MAT2x4 | constants::FLOAT_MAT4x2 => 8,
+        constants::FLOAT_MAT3 => 9,
+        constants::FLOAT_MAT3x4 | constants::FLOAT_MAT4x3 => 12,
+        constants::FLOAT_MAT4 => 16,
+        _ => 1,
+    };
+    components * 4
+}
+
+impl Drop for WebGLTransformFeedback {
+    fn drop(&mut self) {
+        if self.id.is_some() {
+            self.delete();
+        }
+    }
+}
@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
     DocumentMethods, DocumentReadyState,
@@ -12,7 +13,7 @@ use crate::dom::bindings::codegen::Bindings::MediaQueryListBinding::MediaQueryLi
 use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionState;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
-    self, FrameRequestCallback, WindowMethods,
+    self, FrameRequestCallback, IdleRequestCallback, IdleRequestOptions, WindowMethods,
 };
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, ScrollToOptions};
 use crate::dom::bindings::codegen::UnionTypes::RequestOrUSVString;
@@ -28,6 +29,7 @@ use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::bindings::utils::{GlobalStaticData, WindowProxyHandler};
 use crate::dom::bindings::weakref::DOMTracker;
 use crate::dom::bluetooth::BluetoothExtraPermissionData;
+use crate::dom::cachestorage::CacheStorage;
 use crate::dom::crypto::Crypto;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::customelementregistry::CustomElementRegistry;
@@ -38,9 +40,11 @@ use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::hashchangeevent::HashChangeEvent;
 use crate::dom::history::History;
+use crate::dom::idledeadline::IdleDeadline;
 use crate::dom::location::Location;
 use crate::dom::mediaquerylist::{MediaQueryList, MediaQueryListMatchState};
 use crate::dom::mediaquerylistevent::MediaQueryListEvent;
+use crate::dom::mediasource::MediaSource;
 use crate::dom::messageevent::MessageEvent;
 use crate::dom::navigator::Navigator;
 use crate::dom::node::{document_from_node, from_untrusted_node_address, Node, NodeDamage};
@@ -62,7 +66,7 @@ use crate::script_thread::{ImageCacheMsg, MainThreadScriptChan, MainThreadScript
 use crate::script_thread::{ScriptThread, SendableMainThreadScriptChan};
 use crate::task_manager::TaskManager;
 use crate::task_source::TaskSourceName;
-use crate::timers::{IsInterval, TimerCallback};
+use crate::timers::{IsInterval, OneshotTimerCallback, OneshotTimerHandle, TimerCallback};
 use crate::webdriver_handlers::jsval_to_webdriver;
 use app_units::Au;
 use base64;
@@ -100,17 +104,18 @@ use script_layout_interface::rpc::{
 };
 use script_layout_interface::{PendingImageState, TrustedNodeAddress};
 use script_traits::webdriver_msg::{WebDriverJSError, WebDriverJSResult};
-use script_traits::{ConstellationControlMsg, DocumentState, LoadData};
+use script_traits::{ConstellationControlMsg, DocumentState, LoadData, MsDuration};
 use script_traits::{ScriptMsg, ScriptToConstellationChan, ScrollState, TimerEvent, TimerEventId};
 use script_traits::{TimerSchedulerMsg, WindowSizeData, WindowSizeType};
 use selectors::attr::CaseSensitivity;
 use servo_config::opts;
+use servo_config::prefs::PREFS;
 use servo_geometry::{f32_rect_to_au_rect, MaxRect};
 use servo_url::{Host, ImmutableOrigin, MutableOrigin, ServoUrl};
 use std::borrow::ToOwned;
 use std::cell::Cell;
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::default::Default;
 use std::env;
 use std::fs;
@@ -128,6 +133,7 @@ use style::selector_parser::PseudoElement;
 use style::str::HTML_SPACE_CHARACTERS;
 use style::stylesheets::CssRuleType;
 use style_traits::{CSSPixel, DevicePixel, ParsingMode};
+use time::precise_time_ns;
 use url::Position;
 use webrender_api::{DeviceIntPoint, DeviceIntSize, DocumentId, ExternalScrollId, RenderApiSender};
 use webvr_traits::WebVRMsg;
@@ -186,6 +192,7 @@ pub struct Window {
     screen: MutNullableDom<Screen>,
     session_storage: MutNullableDom<Storage>,
     local_storage: MutNullableDom<Storage>,
+    caches: MutNullableDom<CacheStorage>,
     status: DomRefCell<DOMString>,
 
     /// For sending timeline markers. Will be ignored if
@@ -272,6 +279,10 @@ pub struct Window {
     /// available at some point in the future.
     pending_layout_images: DomRefCell<HashMap<PendingImageId, Vec<Dom<Node>>>>,
 
+    /// MediaSource objects which were given an object URL by `URL.createObjectURL`.
+    /// <https://w3c.github.io/media-source/#mediasource-object-url>
+    media_source_urls: DomRefCell<HashMap<String, Dom<MediaSource>>>,
+
     /// Directory to store unminified scripts for this window if unminify-js
     /// opt is enabled.
     unminified_js_dir: DomRefCell<Option<String>>,
@@ -293,6 +304,21 @@ pub struct Window {
     /// Indicate whether a SetDocumentStatus message has been sent after a reflow is complete.
     /// It is used to avoid sending idle message more than once, which is unneccessary.
     has_sent_idle_message: Cell<bool>,
+
+    /// <https://w3c.github.io/requestidlecallback/#dfn-idle-callback-identifier>
+    idle_callback_identifier: Cell<u32>,
+
+    /// <https://w3c.github.io/requestidlecallback/#dfn-list-of-idle-request-callbacks>
+    idle_request_callbacks: DomRefCell<VecDeque<IdleRequest>>,
+
+    /// <https://w3c.github.io/requestidlecallback/#dfn-list-of-runnable-idle-callbacks>
+    runnable_idle_callbacks: DomRefCell<VecDeque<IdleRequest>>,
+
+    /// Whether an idle period has been started, and its callbacks are still being invoked.
+    in_idle_period: Cell<bool>,
+
+    /// The network state last reported to this window.
+    offline: Cell<bool>,
 }
 
 impl Window {
@@ -417,6 +443,26 @@ impl Window {
         &self.permission_state_invocation_results
     }
 
+    pub fn register_media_source_url(&self, url: String, media_source: &MediaSource) {
+        self.media_source_urls
+            .borrow_mut()
+            .insert(url, Dom::from_ref(media_source));
+    }
+
+    /// Returns the MediaSource object `url` was created for, if any.
+    pub fn media_source_for_url(&self, url: &ServoUrl) -> Option<DomRoot<MediaSource>> {
+        self.media_source_urls
+            .borrow()
+            .get(url.as_str())
+            .map(|media_source| DomRoot::from_ref(&**media_source))
+    }
+
+    /// Removes `url` from the MediaSource object URLs. Returns false if it
+    /// was not a MediaSource object URL.
+    pub fn revoke_media_source_url(&self, url: &str) -> bool {
+        self.media_source_urls.borrow_mut().remove(url).is_some()
+    }
+
     pub fn pending_image_notification(&self, response: PendingImageResponse) {
         //XXXjdm could be more efficient to send the responses to the layout thread,
         //       rather than making the layout thread talk to the image cache to
@@ -650,6 +696,11 @@ impl WindowMethods for Window {
             .or_init(|| Storage::new(self, StorageType::Local))
     }
 
+    // https://w3c.github.io/ServiceWorker/#dom-windoworworkerglobalscope-caches
+    fn Caches(&self) -> DomRoot<CacheStorage> {
+        self.caches.or_init(|| CacheStorage::new(self.upcast()))
+    }
+
     // https://dvcs.w3.org/hg/webcrypto-api/raw-file/tip/spec/Overview.html#dfn-GlobalCrypto
     fn Crypto(&self) -> DomRoot<Crypto> {
         self.upcast::<GlobalScope>().crypto()
@@ -848,6 +899,53 @@ impl WindowMethods for Window {
         doc.cancel_animation_frame(ident);
     }
 
+    /// <https://w3c.github.io/requestidlecallback/#the-requestidlecallback-method>
+    fn RequestIdleCallback(
+        &self,
+        callback: Rc<IdleRequestCallback>,
+        options: &IdleRequestOptions,
+    ) -> u32 {
+        // Steps 1-3.
+        let handle = self.idle_callback_identifier.get() + 1;
+        self.idle_callback_identifier.set(handle);
+
+        // Step 5.
+        let timeout = match options.timeout {
+            Some(timeout) if timeout > 0 => {
+                let callback = IdleRequestTimeoutCallback {
+                    window: Trusted::new(self),
+                    handle,
+                };
+                Some(self.upcast::<GlobalScope>().schedule_callback(
+                    OneshotTimerCallback::IdleRequestTimeout(callback),
+                    MsDuration::new(timeout as u64),
+                ))
+            },
+            _ => None,
+        };
+
+        // Step 4.
+        self.idle_request_callbacks
+            .borrow_mut()
+            .push_back(IdleRequest {
+                handle,
+                callback,
+                timeout,
+            });
+
+        // Step 6.
+        handle
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#the-cancelidlecallback-method>
+    fn CancelIdleCallback(&self, handle: u32) {
+        if let Some(request) = self.take_idle_request(handle) {
+            if let Some(timeout) = request.timeout {
+                self.upcast::<GlobalScope>().unschedule_callback(timeout);
+            }
+        }
+    }
+
     #[allow(unsafe_code)]
     // https://html.spec.whatwg.org/multipage/#dom-window-postmessage
     unsafe fn PostMessage(
@@ -1183,6 +1281,109 @@ impl Window {
         self.document.get().is_some()
     }
 
+    /// Records the network state, returning whether it changed since it was last reported.
+    pub fn set_offline(&self, offline: bool) -> bool {
+        self.offline.replace(offline) != offline
+    }
+
+    /// Whether this window has idle callbacks waiting for an idle period to be started.
+    pub fn needs_idle_period(&self) -> bool {
+        !self.in_idle_period.get() &&
+            !(self.idle_request_callbacks.borrow().is_empty() &&
+                self.runnable_idle_callbacks.borrow().is_empty())
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#start-an-idle-period-algorithm>
+    ///
+    /// Steps 1 to 3, computing the deadline, are performed by the script thread.
+    pub fn start_an_idle_period(&self, deadline: u64) {
+        // Step 4.
+        {
+            let mut pending = self.idle_request_callbacks.borrow_mut();
+            self.runnable_idle_callbacks
+                .borrow_mut()
+                .extend(pending.drain(..));
+        }
+
+        if self.runnable_idle_callbacks.borrow().is_empty() {
+            return;
+        }
+
+        // Step 5.
+        self.in_idle_period.set(true);
+        self.queue_invoke_idle_callbacks(deadline);
+    }
+
+    fn queue_invoke_idle_callbacks(&self, deadline: u64) {
+        let result = self
+            .task_manager
+            .idle_task_source()
+            .queue_invoke_idle_callbacks(self, deadline);
+        if result.is_err() {
+            // The event loop is going away. End the idle period rather than
+            // leaving the window waiting on a task that will never run.
+            self.in_idle_period.set(false);
+        }
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#invoke-idle-callbacks-algorithm>
+    pub fn invoke_idle_callbacks(&self, deadline: u64) {
+        // Step 2.
+        let now = precise_time_ns();
+
+        // Step 3.
+        if now >= deadline {
+            // The idle period is over, any remaining callbacks will be run
+            // during the next one.
+            self.in_idle_period.set(false);
+            return;
+        }
+
+        // Step 3.1.
+        let request = self.runnable_idle_callbacks.borrow_mut().pop_front();
+        if let Some(request) = request {
+            if let Some(timeout) = request.timeout {
+                self.upcast::<GlobalScope>().unschedule_callback(timeout);
+            }
+
+            // Steps 3.2-3.3.
+            let deadline = IdleDeadline::new(self, deadline, false);
+            let _ = request
+                .callback
+                .Call__(&deadline, ExceptionHandling::Report);
+        }
+
+        // Step 3.4.
+        if self.runnable_idle_callbacks.borrow().is_empty() {
+            self.in_idle_period.set(false);
+        } else {
+            self.queue_invoke_idle_callbacks(deadline);
+        }
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#invoke-idle-callback-timeout-algorithm>
+    fn invoke_idle_callback_timeout(&self, handle: u32) {
+        // Steps 1-2.
+        if let Some(request) = self.take_idle_request(handle) {
+            // Steps 2.1-2.3.
+            let deadline = IdleDeadline::new(self, precise_time_ns(), true);
+            let _ = request
+                .callback
+                .Call__(&deadline, ExceptionHandling::Report);
+        }
+    }
+
+    /// Removes the idle request with the given handle from whichever list it is in.
+    fn take_idle_request(&self, handle: u32) -> Option<IdleRequest> {
+        for list in &[&self.idle_request_callbacks, &self.runnable_idle_callbacks] {
+            let mut list = list.borrow_mut();
+            if let Some(index) = list.iter().position(|request| request.handle == handle) {
+                return list.remove(index);
+            }
+        }
+        None
+    }
+
     /// Cancels all the tasks associated with that window.
     ///
     /// This sets the current `task_manager.task_cancellers` sentinel value to
@@ -2062,6 +2263,7 @@ impl Window {
             screen: Default::default(),
             session_storage: Default::default(),
             local_storage: Default::default(),
+            caches: Default::default(),
             status: DomRefCell::new(DOMString::new()),
             parent_info,
             dom_static: GlobalStaticData::new(),
@@ -2087,6 +2289,7 @@ impl Window {
             webgl_chan,
             webvr_chan,
             permission_state_invocation_results: Default::default(),
+            media_source_urls: Default::default(),
             pending_layout_images: Default::default(),
             unminified_js_dir: Default::default(),
             test_worklet: Default::default(),
@@ -2095,6 +2298,11 @@ impl Window {
             exists_mut_observer: Cell::new(false),
             webrender_api_sender,
             has_sent_idle_message: Cell::new(false),
+            idle_callback_identifier: Cell::new(0),
+            idle_request_callbacks: Default::default(),
+            runnable_idle_callbacks: Default::default(),
+            in_idle_period: Cell::new(false),
+            offline: Cell::new(PREFS.is_network_offline()),
         });
 
         unsafe { WindowBinding::Wrap(runtime.cx(), win) }
@@ -2235,6 +2443,30 @@ impl Window {
     }
 }
 
+/// An entry in the lists of idle request callbacks.
+#[derive(JSTraceable, MallocSizeOf)]
+struct IdleRequest {
+    handle: u32,
+    #[ignore_malloc_size_of = "Rc is hard"]
+    callback: Rc<IdleRequestCallback>,
+    /// The timer scheduled for the `timeout` option, if one was given.
+    timeout: Option<OneshotTimerHandle>,
+}
+
+/// The timer callback used to run an idle callback once its `timeout` has elapsed.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct IdleRequestTimeoutCallback {
+    #[ignore_malloc_size_of = "non-owning"]
+    window: Trusted<Window>,
+    handle: u32,
+}
+
+impl IdleRequestTimeoutCallback {
+    pub fn invoke(self) {
+        self.window.root().invoke_idle_callback_timeout(self.handle);
+    }
+}
+
 #[derive(Clone, MallocSizeOf)]
 pub struct CSSErrorReporter {
     pub pipelineid: PipelineId,
@@ -193,6 +193,15 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
     // https://html.spec.whatwg.org/multipage/#handler-workerglobalscope-onerror
     error_event_handler!(error, GetOnerror, SetOnerror);
 
+    // https://html.spec.whatwg.org/multipage/#handler-workerglobalscope-onlanguagechange
+    event_handler!(languagechange, GetOnlanguagechange, SetOnlanguagechange);
+
+    // https://html.spec.whatwg.org/multipage/#handler-workerglobalscope-onoffline
+    event_handler!(offline, GetOnoffline, SetOnoffline);
+
+    // https://html.spec.whatwg.org/multipage/#handler-workerglobalscope-ononline
+    event_handler!(online, GetOnonline, SetOnonline);
+
     // https://html.spec.whatwg.org/multipage/#dom-workerglobalscope-importscripts
     fn ImportScripts(&self, url_strings: Vec<DOMString>) -> ErrorResult {
         let mut urls = Vec::with_capacity(url_strings.len());
@@ -2,21 +2,28 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::WorkerNavigatorBinding;
 use crate::dom::bindings::codegen::Bindings::WorkerNavigatorBinding::WorkerNavigatorMethods;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
 use crate::dom::navigatorinfo;
 use crate::dom::permissions::Permissions;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::JSVal;
 
 // https://html.spec.whatwg.org/multipage/#workernavigator
 #[dom_struct]
 pub struct WorkerNavigator {
     reflector_: Reflector,
     permissions: MutNullableDom<Permissions>,
+    languages: DomRefCell<Vec<DOMString>>,
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    languages_array: Heap<JSVal>,
 }
 
 impl WorkerNavigator {
@@ -24,6 +31,8 @@ impl WorkerNavigator {
         WorkerNavigator {
             reflector_: Reflector::new(),
             permissions: Default::default(),
+            languages: DomRefCell::new(Vec::new()),
+            languages_array: Heap::default(),
         }
     }
 
@@ -77,6 +86,23 @@ impl WorkerNavigatorMethods for WorkerNavigator {
         navigatorinfo::Language()
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-navigator-languages
+    #[allow(unsafe_code)]
+    unsafe fn Languages(&self, cx: *mut JSContext) -> JSVal {
+        // The same array is returned until the user's languages change.
+        let languages = navigatorinfo::Languages();
+        if self.languages_array.get().is_undefined() || *self.languages.borrow() != languages {
+            self.languages_array.set(to_frozen_array(&languages, cx));
+            *self.languages.borrow_mut() = languages;
+        }
+        self.languages_array.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigator-online
+    fn OnLine(&self) -> bool {
+        navigatorinfo::OnLine()
+    }
+
     // https://w3c.github.io/permissions/#navigator-and-workernavigator-extension
     fn Permissions(&self) -> DomRoot<Permissions> {
         self.permissions
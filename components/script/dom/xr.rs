@@ -13,6 +13,7 @@ use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
+use crate::dom::fakexrdevice::FakeXRDevice;
 use crate::dom::gamepad::Gamepad;
 use crate::dom::gamepadevent::GamepadEventType;
 use crate::dom::globalscope::GlobalScope;
@@ -20,6 +21,7 @@ use crate::dom::promise::Promise;
 use crate::dom::vrdisplay::VRDisplay;
 use crate::dom::vrdisplayevent::VRDisplayEvent;
 use crate::dom::xrsession::XRSession;
+use crate::dom::xrtest::XRTest;
 use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcSender;
 use profile_traits::ipc;
@@ -34,7 +36,8 @@ pub struct XR {
     displays: DomRefCell<Vec<Dom<VRDisplay>>>,
     gamepads: DomRefCell<Vec<Dom<Gamepad>>>,
     pending_immersive_session: Cell<bool>,
-    active_immersive_session: MutNullableDom<VRDisplay>,
+    active_immersive_session: Cell<bool>,
+    test: MutNullableDom<XRTest>,
 }
 
 impl XR {
@@ -44,7 +47,8 @@ impl XR {
             displays: DomRefCell::new(Vec::new()),
             gamepads: DomRefCell::new(Vec::new()),
             pending_immersive_session: Cell::new(false),
-            active_immersive_session: Default::default(),
+            active_immersive_session: Cell::new(false),
+            test: Default::default(),
         }
     }
 
@@ -55,23 +59,23 @@ impl XR {
     }
 
     pub fn pending_or_active_session(&self) -> bool {
-        self.pending_immersive_session.get() || self.active_immersive_session.get().is_some()
+        self.pending_immersive_session.get() || self.active_immersive_session.get()
     }
 
     pub fn set_pending(&self) {
         self.pending_immersive_session.set(true)
     }
 
-    pub fn set_active_immersive_session(&self, session: &VRDisplay) {
+    pub fn set_active_immersive_session(&self) {
         // XXXManishearth when we support non-immersive (inline) sessions we should
         // ensure they never reach these codepaths
         self.pending_immersive_session.set(false);
-        self.active_immersive_session.set(Some(session))
+        self.active_immersive_session.set(true)
     }
 
     pub fn deactivate_session(&self) {
         self.pending_immersive_session.set(false);
-        self.active_immersive_session.set(None)
+        self.active_immersive_session.set(false)
     }
 }
 
@@ -87,7 +91,12 @@ impl XRMethods for XR {
         // XXXManishearth this should select an XR device first
         let promise = Promise::new(&self.global());
         if mode == XRSessionMode::Immersive_vr {
-            promise.resolve_native(&());
+            match self.simulated_device() {
+                Some(ref device) if !device.supports_immersive() => {
+                    promise.reject_error(Error::NotSupported)
+                },
+                _ => promise.resolve_native(&()),
+            }
         } else {
             // XXXManishearth support other modes
             promise.reject_error(Error::NotSupported);
@@ -111,6 +120,20 @@ impl XRMethods for XR {
         // we set pending immersive session to true further down
         // to handle rejections in a cleaner way
 
+        // Devices simulated through the test API take precedence over real ones.
+        if let Some(device) = self.simulated_device() {
+            if !device.supports_immersive() {
+                promise.reject_error(Error::NotSupported);
+                return promise;
+            }
+            let session = XRSession::new_simulated(&self.global(), &device);
+            device.set_session(&session);
+            self.set_active_immersive_session();
+            session.schedule_simulated_frame();
+            promise.resolve_native(&session);
+            return promise;
+        }
+
         let displays = self.get_displays();
 
         let displays = match displays {
@@ -124,6 +147,7 @@ impl XRMethods for XR {
         // XXXManishearth filter for displays which can_present
         if displays.is_empty() {
             promise.reject_error(Error::Security);
+            return promise;
         }
 
         self.set_pending();
@@ -132,9 +156,19 @@ impl XRMethods for XR {
         session.xr_present(promise.clone());
         promise
     }
+
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/webxr-test-api.md
+    fn Test(&self) -> DomRoot<XRTest> {
+        self.test.or_init(|| XRTest::new(&self.global()))
+    }
 }
 
 impl XR {
+    /// The device simulated by the test API, if the page connected one.
+    fn simulated_device(&self) -> Option<DomRoot<FakeXRDevice>> {
+        self.test.get().and_then(|test| test.connected_device())
+    }
+
     pub fn get_displays(&self) -> Result<Vec<DomRoot<VRDisplay>>, ()> {
         if let Some(webvr_thread) = self.webvr_thread() {
             let (sender, receiver) =
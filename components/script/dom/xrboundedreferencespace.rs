@@ -0,0 +1,87 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::DOMPointReadOnlyBinding::DOMPointReadOnlyMethods;
+use crate::dom::bindings::codegen::Bindings::XRBoundedReferenceSpaceBinding;
+use crate::dom::bindings::codegen::Bindings::XRBoundedReferenceSpaceBinding::XRBoundedReferenceSpaceMethods;
+use crate::dom::bindings::codegen::Bindings::XRReferenceSpaceBinding::XRReferenceSpaceType;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::dompointreadonly::DOMPointReadOnly;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrreferencespace::XRReferenceSpace;
+use crate::dom::xrrigidtransform::RigidTransform;
+use crate::dom::xrsession::XRSession;
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::{JSVal, UndefinedValue};
+
+#[dom_struct]
+pub struct XRBoundedReferenceSpace {
+    xrreferencespace: XRReferenceSpace,
+    points: Vec<Dom<DOMPointReadOnly>>,
+    bounds_geometry: Heap<JSVal>,
+}
+
+impl XRBoundedReferenceSpace {
+    fn new_inherited(
+        session: &XRSession,
+        offset: RigidTransform,
+        points: &[DomRoot<DOMPointReadOnly>],
+    ) -> XRBoundedReferenceSpace {
+        XRBoundedReferenceSpace {
+            xrreferencespace: XRReferenceSpace::new_inherited(
+                session,
+                XRReferenceSpaceType::Bounded_floor,
+                offset,
+            ),
+            points: points.iter().map(|p| Dom::from_ref(&**p)).collect(),
+            bounds_geometry: Heap::default(),
+        }
+    }
+
+    /// Creates a `bounded-floor` space whose bounds are given by `points`,
+    /// relative to the origin of the new space.
+    #[allow(unsafe_code)]
+    pub fn new(
+        global: &GlobalScope,
+        session: &XRSession,
+        offset: RigidTransform,
+        points: &[[f64; 3]],
+    ) -> DomRoot<XRBoundedReferenceSpace> {
+        let points: Vec<_> = points
+            .iter()
+            .map(|p| DOMPointReadOnly::new(global, p[0], p[1], p[2], 1.))
+            .collect();
+        let space = reflect_dom_object(
+            Box::new(XRBoundedReferenceSpace::new_inherited(
+                session, offset, &points,
+            )),
+            global,
+            XRBoundedReferenceSpaceBinding::Wrap,
+        );
+
+        unsafe {
+            let cx = global.get_cx();
+            rooted!(in(cx) let mut jsval = UndefinedValue());
+            points.to_jsval(cx, jsval.handle_mut());
+            space.bounds_geometry.set(jsval.get());
+        }
+
+        space
+    }
+
+    pub fn points(&self) -> Vec<[f64; 3]> {
+        self.points.iter().map(|p| [p.X(), p.Y(), p.Z()]).collect()
+    }
+}
+
+impl XRBoundedReferenceSpaceMethods for XRBoundedReferenceSpace {
+    /// https://immersive-web.github.io/webxr/#dom-xrboundedreferencespace-boundsgeometry
+    #[allow(unsafe_code)]
+    unsafe fn BoundsGeometry(&self, _cx: *mut JSContext) -> JSVal {
+        self.bounds_geometry.get()
+    }
+}
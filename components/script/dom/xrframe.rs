@@ -4,45 +4,54 @@
 
 use crate::dom::bindings::codegen::Bindings::XRFrameBinding;
 use crate::dom::bindings::codegen::Bindings::XRFrameBinding::XRFrameMethods;
-use crate::dom::bindings::codegen::Bindings::XRViewBinding::XREye;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrpose::XRPose;
 use crate::dom::xrreferencespace::XRReferenceSpace;
-use crate::dom::xrsession::XRSession;
+use crate::dom::xrsession::{XRDeviceFrame, XRSession};
+use crate::dom::xrspace::XRSpace;
 use crate::dom::xrview::XRView;
 use crate::dom::xrviewerpose::XRViewerPose;
 use dom_struct::dom_struct;
-use webvr_traits::WebVRFrameData;
+use std::cell::Cell;
 
 #[dom_struct]
 pub struct XRFrame {
     reflector_: Reflector,
     session: Dom<XRSession>,
-    #[ignore_malloc_size_of = "defined in rust-webvr"]
-    data: WebVRFrameData,
+    data: XRDeviceFrame,
+    /// Frames can only be queried while their callbacks and events run.
+    active: Cell<bool>,
 }
 
 impl XRFrame {
-    fn new_inherited(session: &XRSession, data: WebVRFrameData) -> XRFrame {
+    fn new_inherited(session: &XRSession, data: XRDeviceFrame) -> XRFrame {
         XRFrame {
             reflector_: Reflector::new(),
             session: Dom::from_ref(session),
             data,
+            active: Cell::new(false),
         }
     }
 
-    pub fn new(
-        global: &GlobalScope,
-        session: &XRSession,
-        data: WebVRFrameData,
-    ) -> DomRoot<XRFrame> {
+    pub fn new(global: &GlobalScope, session: &XRSession, data: XRDeviceFrame) -> DomRoot<XRFrame> {
         reflect_dom_object(
             Box::new(XRFrame::new_inherited(session, data)),
             global,
             XRFrameBinding::Wrap,
         )
     }
+
+    pub fn data(&self) -> &XRDeviceFrame {
+        &self.data
+    }
+
+    pub fn set_active(&self, active: bool) {
+        self.active.set(active);
+    }
 }
 
 impl XRFrameMethods for XRFrame {
@@ -52,16 +61,65 @@ impl XRFrameMethods for XRFrame {
     }
 
     /// https://immersive-web.github.io/webxr/#dom-xrframe-getviewerpose
-    fn GetViewerPose(&self, reference: Option<&XRReferenceSpace>) -> Option<DomRoot<XRViewerPose>> {
-        // We assume the reference space is eye level for now
-        // since it's the only one 3DOF devices support
-        if reference.is_some() {
-            // it's not possible to obtain a reference
-            // space at all yet
-            return None;
+    fn GetViewerPose(
+        &self,
+        reference: &XRReferenceSpace,
+    ) -> Fallible<Option<DomRoot<XRViewerPose>>> {
+        let space = reference.upcast::<XRSpace>();
+        if !self.active.get() || self.session != space.session() {
+            return Err(Error::InvalidState);
+        }
+        let viewer = match self.data.viewer {
+            Some(viewer) => viewer,
+            None => return Ok(None),
+        };
+        let base = match space.get_pose(&self.data) {
+            Some(base) => base,
+            None => return Ok(None),
+        };
+        let pose = viewer.then(&base.inverse());
+        let views: Vec<_> = self
+            .data
+            .views
+            .iter()
+            .map(|view| {
+                XRView::new(
+                    &self.global(),
+                    &self.session,
+                    view.eye,
+                    view.offset.then(&pose),
+                    &view.projection,
+                )
+            })
+            .collect();
+        let emulated = self.data.emulated_position || space.emulated_position(&self.data);
+        Ok(Some(XRViewerPose::new(
+            &self.global(),
+            pose,
+            emulated,
+            &views,
+        )))
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrframe-getpose
+    fn GetPose(&self, space: &XRSpace, relative_to: &XRSpace) -> Fallible<Option<DomRoot<XRPose>>> {
+        if !self.active.get() ||
+            self.session != space.session() ||
+            self.session != relative_to.session()
+        {
+            return Err(Error::InvalidState);
         }
-        let left = XRView::new(&self.global(), &self.session, XREye::Left, &self.data);
-        let right = XRView::new(&self.global(), &self.session, XREye::Right, &self.data);
-        Some(XRViewerPose::new(&self.global(), &left, &right))
+        let space_pose = match space.get_pose(&self.data) {
+            Some(pose) => pose,
+            None => return Ok(None),
+        };
+        let base = match relative_to.get_pose(&self.data) {
+            Some(base) => base,
+            None => return Ok(None),
+        };
+        let pose = space_pose.then(&base.inverse());
+        let emulated =
+            space.emulated_position(&self.data) || relative_to.emulated_position(&self.data);
+        Ok(Some(XRPose::new(&self.global(), pose, emulated)))
     }
 }
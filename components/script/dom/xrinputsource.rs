@@ -0,0 +1,169 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XRInputSourceBinding;
+use crate::dom::bindings::codegen::Bindings::XRInputSourceBinding::{
+    XRHandedness, XRInputSourceMethods, XRTargetRayMode,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrsession::{XRDeviceInput, XRSession};
+use crate::dom::xrspace::XRSpace;
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::{JSVal, UndefinedValue};
+use servo_atoms::Atom;
+use std::cell::Cell;
+
+/// The actions an input source can perform.
+#[derive(Clone, Copy, PartialEq)]
+pub enum XRInputAction {
+    /// https://immersive-web.github.io/webxr/#primary-action
+    Select,
+    /// https://immersive-web.github.io/webxr/#primary-squeeze-action
+    Squeeze,
+}
+
+impl XRInputAction {
+    pub fn start_event(self) -> Atom {
+        match self {
+            XRInputAction::Select => atom!("selectstart"),
+            XRInputAction::Squeeze => atom!("squeezestart"),
+        }
+    }
+
+    pub fn event(self) -> Atom {
+        match self {
+            XRInputAction::Select => atom!("select"),
+            XRInputAction::Squeeze => atom!("squeeze"),
+        }
+    }
+
+    pub fn end_event(self) -> Atom {
+        match self {
+            XRInputAction::Select => atom!("selectend"),
+            XRInputAction::Squeeze => atom!("squeezeend"),
+        }
+    }
+}
+
+#[dom_struct]
+pub struct XRInputSource {
+    reflector_: Reflector,
+    session: Dom<XRSession>,
+    id: u32,
+    handedness: XRHandedness,
+    target_ray_mode: XRTargetRayMode,
+    has_grip: bool,
+    target_ray_space: MutNullableDom<XRSpace>,
+    grip_space: MutNullableDom<XRSpace>,
+    profiles: Heap<JSVal>,
+    selecting: Cell<bool>,
+    squeezing: Cell<bool>,
+}
+
+impl XRInputSource {
+    fn new_inherited(session: &XRSession, input: &XRDeviceInput) -> XRInputSource {
+        XRInputSource {
+            reflector_: Reflector::new(),
+            session: Dom::from_ref(session),
+            id: input.id,
+            handedness: input.handedness,
+            target_ray_mode: input.target_ray_mode,
+            has_grip: input.grip.is_some(),
+            target_ray_space: Default::default(),
+            grip_space: Default::default(),
+            profiles: Heap::default(),
+            selecting: Cell::new(false),
+            squeezing: Cell::new(false),
+        }
+    }
+
+    #[allow(unsafe_code)]
+    pub fn new(
+        global: &GlobalScope,
+        session: &XRSession,
+        input: &XRDeviceInput,
+    ) -> DomRoot<XRInputSource> {
+        let source = reflect_dom_object(
+            Box::new(XRInputSource::new_inherited(session, input)),
+            global,
+            XRInputSourceBinding::Wrap,
+        );
+
+        unsafe {
+            let cx = global.get_cx();
+            rooted!(in(cx) let mut jsval = UndefinedValue());
+            input.profiles.to_jsval(cx, jsval.handle_mut());
+            source.profiles.set(jsval.get());
+        }
+
+        source
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Whether this source still represents the given input. Inputs which
+    /// changed their handedness, target ray mode or gained or lost their
+    /// grip get a new source.
+    pub fn matches(&self, input: &XRDeviceInput) -> bool {
+        self.id == input.id &&
+            self.handedness == input.handedness &&
+            self.target_ray_mode == input.target_ray_mode &&
+            self.has_grip == input.grip.is_some()
+    }
+
+    pub fn action_in_progress(&self, action: XRInputAction) -> bool {
+        match action {
+            XRInputAction::Select => self.selecting.get(),
+            XRInputAction::Squeeze => self.squeezing.get(),
+        }
+    }
+
+    pub fn set_action_in_progress(&self, action: XRInputAction, in_progress: bool) {
+        match action {
+            XRInputAction::Select => self.selecting.set(in_progress),
+            XRInputAction::Squeeze => self.squeezing.set(in_progress),
+        }
+    }
+}
+
+impl XRInputSourceMethods for XRInputSource {
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsource-handedness
+    fn Handedness(&self) -> XRHandedness {
+        self.handedness
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsource-targetraymode
+    fn TargetRayMode(&self) -> XRTargetRayMode {
+        self.target_ray_mode
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsource-targetrayspace
+    fn TargetRaySpace(&self) -> DomRoot<XRSpace> {
+        self.target_ray_space
+            .or_init(|| XRSpace::new_input_space(&self.global(), &self.session, self, false))
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsource-gripspace
+    fn GetGripSpace(&self) -> Option<DomRoot<XRSpace>> {
+        if !self.has_grip {
+            return None;
+        }
+        Some(
+            self.grip_space
+                .or_init(|| XRSpace::new_input_space(&self.global(), &self.session, self, true)),
+        )
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsource-profiles
+    #[allow(unsafe_code)]
+    unsafe fn Profiles(&self, _cx: *mut JSContext) -> JSVal {
+        self.profiles.get()
+    }
+}
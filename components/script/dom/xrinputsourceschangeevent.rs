@@ -0,0 +1,113 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::XRInputSourcesChangeEventBinding;
+use crate::dom::bindings::codegen::Bindings::XRInputSourcesChangeEventBinding::XRInputSourcesChangeEventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use crate::dom::xrinputsource::XRInputSource;
+use crate::dom::xrsession::XRSession;
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::{JSVal, UndefinedValue};
+use servo_atoms::Atom;
+
+#[dom_struct]
+pub struct XRInputSourcesChangeEvent {
+    event: Event,
+    session: Dom<XRSession>,
+    added: Heap<JSVal>,
+    removed: Heap<JSVal>,
+}
+
+impl XRInputSourcesChangeEvent {
+    fn new_inherited(session: &XRSession) -> XRInputSourcesChangeEvent {
+        XRInputSourcesChangeEvent {
+            event: Event::new_inherited(),
+            session: Dom::from_ref(session),
+            added: Heap::default(),
+            removed: Heap::default(),
+        }
+    }
+
+    #[allow(unsafe_code)]
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        session: &XRSession,
+        added: &[DomRoot<XRInputSource>],
+        removed: &[DomRoot<XRInputSource>],
+    ) -> DomRoot<XRInputSourcesChangeEvent> {
+        let ev = reflect_dom_object(
+            Box::new(XRInputSourcesChangeEvent::new_inherited(session)),
+            global,
+            XRInputSourcesChangeEventBinding::Wrap,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        unsafe {
+            let cx = global.get_cx();
+            rooted!(in(cx) let mut added_val = UndefinedValue());
+            added.to_jsval(cx, added_val.handle_mut());
+            ev.added.set(added_val.get());
+            rooted!(in(cx) let mut removed_val = UndefinedValue());
+            removed.to_jsval(cx, removed_val.handle_mut());
+            ev.removed.set(removed_val.get());
+        }
+        ev
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrinputsourceschangeevent-xrinputsourceschangeevent
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &XRInputSourcesChangeEventBinding::XRInputSourcesChangeEventInit,
+    ) -> Fallible<DomRoot<XRInputSourcesChangeEvent>> {
+        Ok(XRInputSourcesChangeEvent::new(
+            &window.global(),
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            &init.session,
+            &init.added,
+            &init.removed,
+        ))
+    }
+}
+
+impl XRInputSourcesChangeEventMethods for XRInputSourcesChangeEvent {
+    // https://immersive-web.github.io/webxr/#dom-xrinputsourceschangeevent-session
+    fn Session(&self) -> DomRoot<XRSession> {
+        DomRoot::from_ref(&*self.session)
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrinputsourceschangeevent-added
+    #[allow(unsafe_code)]
+    unsafe fn Added(&self, _cx: *mut JSContext) -> JSVal {
+        self.added.get()
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrinputsourceschangeevent-removed
+    #[allow(unsafe_code)]
+    unsafe fn Removed(&self, _cx: *mut JSContext) -> JSVal {
+        self.removed.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
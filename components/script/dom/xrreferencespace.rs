@@ -3,30 +3,107 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::codegen::Bindings::XRReferenceSpaceBinding;
-use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::codegen::Bindings::XRReferenceSpaceBinding::XRReferenceSpaceMethods;
+use crate::dom::bindings::codegen::Bindings::XRReferenceSpaceBinding::XRReferenceSpaceType;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrboundedreferencespace::XRBoundedReferenceSpace;
+use crate::dom::xrrigidtransform::{RigidTransform, XRRigidTransform};
+use crate::dom::xrsession::{XRDeviceFrame, XRSession};
 use crate::dom::xrspace::XRSpace;
 use dom_struct::dom_struct;
 
+/// The height of the eyes above the floor assumed by `local-floor` spaces
+/// of devices which don't know where the floor is.
+const EMULATED_EYE_HEIGHT: f64 = 1.6;
+
 #[dom_struct]
 pub struct XRReferenceSpace {
     xrspace: XRSpace,
+    ty: XRReferenceSpaceType,
+    /// The pose of the origin of this space relative to the origin of the
+    /// space it was created from.
+    offset: RigidTransform,
 }
 
 impl XRReferenceSpace {
-    pub fn new_inherited() -> XRReferenceSpace {
+    pub fn new_inherited(
+        session: &XRSession,
+        ty: XRReferenceSpaceType,
+        offset: RigidTransform,
+    ) -> XRReferenceSpace {
         XRReferenceSpace {
-            xrspace: XRSpace::new_inherited(),
+            xrspace: XRSpace::new_inherited(session),
+            ty,
+            offset,
         }
     }
 
-    #[allow(unused)]
-    pub fn new(global: &GlobalScope) -> DomRoot<XRReferenceSpace> {
+    pub fn new(
+        global: &GlobalScope,
+        session: &XRSession,
+        ty: XRReferenceSpaceType,
+    ) -> DomRoot<XRReferenceSpace> {
+        XRReferenceSpace::new_with_offset(global, session, ty, RigidTransform::identity())
+    }
+
+    fn new_with_offset(
+        global: &GlobalScope,
+        session: &XRSession,
+        ty: XRReferenceSpaceType,
+        offset: RigidTransform,
+    ) -> DomRoot<XRReferenceSpace> {
         reflect_dom_object(
-            Box::new(XRReferenceSpace::new_inherited()),
+            Box::new(XRReferenceSpace::new_inherited(session, ty, offset)),
             global,
             XRReferenceSpaceBinding::Wrap,
         )
     }
+
+    /// Returns the pose of the origin of this space relative to the native
+    /// origin of the device, or `None` if it isn't tracked in this frame.
+    pub fn get_pose(&self, data: &XRDeviceFrame) -> Option<RigidTransform> {
+        let origin = match self.ty {
+            XRReferenceSpaceType::Viewer => data.viewer?,
+            XRReferenceSpaceType::Local | XRReferenceSpaceType::Unbounded => {
+                RigidTransform::identity()
+            },
+            XRReferenceSpaceType::Local_floor | XRReferenceSpaceType::Bounded_floor => data
+                .floor_origin
+                .unwrap_or(RigidTransform::translation(0., -EMULATED_EYE_HEIGHT, 0.)),
+        };
+        Some(self.offset.then(&origin))
+    }
+
+    /// Whether the position of this space is an estimate in this frame.
+    pub fn emulated_position(&self, data: &XRDeviceFrame) -> bool {
+        match self.ty {
+            XRReferenceSpaceType::Viewer => data.emulated_position,
+            XRReferenceSpaceType::Local_floor => data.floor_origin.is_none(),
+            _ => false,
+        }
+    }
+}
+
+impl XRReferenceSpaceMethods for XRReferenceSpace {
+    /// https://immersive-web.github.io/webxr/#dom-xrreferencespace-getoffsetreferencespace
+    fn GetOffsetReferenceSpace(&self, origin_offset: &XRRigidTransform) -> DomRoot<Self> {
+        let global = self.global();
+        let session = self.upcast::<XRSpace>().session();
+        let offset = origin_offset.transform().then(&self.offset);
+        if let Some(bounded) = self.downcast::<XRBoundedReferenceSpace>() {
+            // The bounds are expressed relative to the origin of the new space.
+            let inverse = origin_offset.transform().inverse();
+            let points: Vec<_> = bounded
+                .points()
+                .iter()
+                .map(|point| inverse.transform_point(*point))
+                .collect();
+            let space = XRBoundedReferenceSpace::new(&global, session, offset, &points);
+            return DomRoot::from_ref(space.upcast());
+        }
+        XRReferenceSpace::new_with_offset(&global, session, self.ty, offset)
+    }
 }
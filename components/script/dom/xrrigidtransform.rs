@@ -2,30 +2,251 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::DOMPointBinding::DOMPointInit;
 use crate::dom::bindings::codegen::Bindings::XRRigidTransformBinding;
-use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::codegen::Bindings::XRRigidTransformBinding::XRRigidTransformMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::dompointreadonly::DOMPointReadOnly;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::vrframedata::create_typed_array;
+use crate::dom::window::Window;
 use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSContext, JSObject};
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use std::ptr::NonNull;
+
+/// A rotation followed by a translation, mapping points from a space into
+/// its parent space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RigidTransform {
+    /// The translation, as `[x, y, z]`.
+    pub position: [f64; 3],
+    /// The rotation, as a unit quaternion `[x, y, z, w]`.
+    pub orientation: [f64; 4],
+}
+
+unsafe_no_jsmanaged_fields!(RigidTransform);
+
+impl MallocSizeOf for RigidTransform {
+    fn size_of(&self, _ops: &mut MallocSizeOfOps) -> usize {
+        0
+    }
+}
+
+impl Default for RigidTransform {
+    fn default() -> RigidTransform {
+        RigidTransform::identity()
+    }
+}
+
+impl RigidTransform {
+    pub fn identity() -> RigidTransform {
+        RigidTransform {
+            position: [0., 0., 0.],
+            orientation: [0., 0., 0., 1.],
+        }
+    }
+
+    pub fn translation(x: f64, y: f64, z: f64) -> RigidTransform {
+        RigidTransform {
+            position: [x, y, z],
+            orientation: [0., 0., 0., 1.],
+        }
+    }
+
+    /// Creates a transform from a position and a not necessarily normalized
+    /// orientation, returning `None` if the orientation has no direction.
+    pub fn new(position: [f64; 3], orientation: [f64; 4]) -> Option<RigidTransform> {
+        let norm = orientation.iter().map(|c| c * c).sum::<f64>().sqrt();
+        if !norm.is_finite() || norm == 0. || position.iter().any(|c| !c.is_finite()) {
+            return None;
+        }
+        Some(RigidTransform {
+            position,
+            orientation: [
+                orientation[0] / norm,
+                orientation[1] / norm,
+                orientation[2] / norm,
+                orientation[3] / norm,
+            ],
+        })
+    }
+
+    /// Rotates a vector by this transform's orientation.
+    fn rotate(&self, v: [f64; 3]) -> [f64; 3] {
+        // v' = v + 2w (u × v) + 2 u × (u × v)
+        let [x, y, z, w] = self.orientation;
+        let uv = cross([x, y, z], v);
+        let uuv = cross([x, y, z], uv);
+        [
+            v[0] + 2. * (w * uv[0] + uuv[0]),
+            v[1] + 2. * (w * uv[1] + uuv[1]),
+            v[2] + 2. * (w * uv[2] + uuv[2]),
+        ]
+    }
+
+    /// Maps a point from this transform's space into its parent space.
+    pub fn transform_point(&self, p: [f64; 3]) -> [f64; 3] {
+        let r = self.rotate(p);
+        [
+            r[0] + self.position[0],
+            r[1] + self.position[1],
+            r[2] + self.position[2],
+        ]
+    }
+
+    /// Returns the transform that applies `self` and then `other`.
+    pub fn then(&self, other: &RigidTransform) -> RigidTransform {
+        let [x1, y1, z1, w1] = other.orientation;
+        let [x2, y2, z2, w2] = self.orientation;
+        RigidTransform {
+            position: other.transform_point(self.position),
+            orientation: [
+                w1 * x2 + x1 * w2 + y1 * z2 - z1 * y2,
+                w1 * y2 - x1 * z2 + y1 * w2 + z1 * x2,
+                w1 * z2 + x1 * y2 - y1 * x2 + z1 * w2,
+                w1 * w2 - x1 * x2 - y1 * y2 - z1 * z2,
+            ],
+        }
+    }
+
+    pub fn inverse(&self) -> RigidTransform {
+        let [x, y, z, w] = self.orientation;
+        let rotation = RigidTransform {
+            position: [0., 0., 0.],
+            orientation: [-x, -y, -z, w],
+        };
+        let [px, py, pz] = rotation.rotate(self.position);
+        RigidTransform {
+            position: [-px, -py, -pz],
+            orientation: rotation.orientation,
+        }
+    }
+
+    /// Returns the column-major 4x4 matrix of this transform.
+    pub fn to_matrix(&self) -> [f32; 16] {
+        let [x, y, z, w] = self.orientation;
+        let [tx, ty, tz] = self.position;
+        let m = [
+            1. - 2. * (y * y + z * z),
+            2. * (x * y + z * w),
+            2. * (x * z - y * w),
+            0.,
+            2. * (x * y - z * w),
+            1. - 2. * (x * x + z * z),
+            2. * (y * z + x * w),
+            0.,
+            2. * (x * z + y * w),
+            2. * (y * z - x * w),
+            1. - 2. * (x * x + y * y),
+            0.,
+            tx,
+            ty,
+            tz,
+            1.,
+        ];
+        let mut matrix = [0.; 16];
+        for (dst, src) in matrix.iter_mut().zip(m.iter()) {
+            *dst = *src as f32;
+        }
+        matrix
+    }
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
 
 #[dom_struct]
 pub struct XRRigidTransform {
     reflector_: Reflector,
+    transform: RigidTransform,
+    position: MutNullableDom<DOMPointReadOnly>,
+    orientation: MutNullableDom<DOMPointReadOnly>,
+    matrix: Heap<*mut JSObject>,
+    inverse: MutNullableDom<XRRigidTransform>,
 }
 
 impl XRRigidTransform {
-    fn new_inherited() -> XRRigidTransform {
+    fn new_inherited(transform: RigidTransform) -> XRRigidTransform {
         XRRigidTransform {
             reflector_: Reflector::new(),
+            transform,
+            position: Default::default(),
+            orientation: Default::default(),
+            matrix: Heap::default(),
+            inverse: Default::default(),
         }
     }
 
-    #[allow(unused)]
-    pub fn new(global: &GlobalScope) -> DomRoot<XRRigidTransform> {
+    pub fn new(global: &GlobalScope, transform: RigidTransform) -> DomRoot<XRRigidTransform> {
         reflect_dom_object(
-            Box::new(XRRigidTransform::new_inherited()),
+            Box::new(XRRigidTransform::new_inherited(transform)),
             global,
             XRRigidTransformBinding::Wrap,
         )
     }
+
+    // https://immersive-web.github.io/webxr/#dom-xrrigidtransform-xrrigidtransform
+    pub fn Constructor(
+        window: &Window,
+        position: &DOMPointInit,
+        orientation: &DOMPointInit,
+    ) -> Fallible<DomRoot<Self>> {
+        if position.w != 1.0 {
+            return Err(Error::Type(format!(
+                "XRRigidTransform must have a position with a w value of 1.0, found {}",
+                position.w
+            )));
+        }
+        let transform = RigidTransform::new(
+            [position.x, position.y, position.z],
+            [orientation.x, orientation.y, orientation.z, orientation.w],
+        )
+        .ok_or(Error::InvalidState)?;
+        Ok(XRRigidTransform::new(&window.global(), transform))
+    }
+
+    pub fn transform(&self) -> RigidTransform {
+        self.transform
+    }
+}
+
+impl XRRigidTransformMethods for XRRigidTransform {
+    // https://immersive-web.github.io/webxr/#dom-xrrigidtransform-position
+    fn Position(&self) -> DomRoot<DOMPointReadOnly> {
+        self.position.or_init(|| {
+            let [x, y, z] = self.transform.position;
+            DOMPointReadOnly::new(&self.global(), x, y, z, 1.0)
+        })
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrrigidtransform-orientation
+    fn Orientation(&self) -> DomRoot<DOMPointReadOnly> {
+        self.orientation.or_init(|| {
+            let [x, y, z, w] = self.transform.orientation;
+            DOMPointReadOnly::new(&self.global(), x, y, z, w)
+        })
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrrigidtransform-inverse
+    fn Inverse(&self) -> DomRoot<XRRigidTransform> {
+        self.inverse
+            .or_init(|| XRRigidTransform::new(&self.global(), self.transform.inverse()))
+    }
+
+    #[allow(unsafe_code)]
+    // https://immersive-web.github.io/webxr/#dom-xrrigidtransform-matrix
+    unsafe fn Matrix(&self, cx: *mut JSContext) -> NonNull<JSObject> {
+        if self.matrix.get().is_null() {
+            create_typed_array(cx, &self.transform.to_matrix(), &self.matrix);
+        }
+        NonNull::new(self.matrix.get()).unwrap()
+    }
 }
@@ -2,77 +2,391 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
 use crate::dom::bindings::codegen::Bindings::VRDisplayBinding::VRDisplayMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::XRBinding::XRSessionMode;
+use crate::dom::bindings::codegen::Bindings::XRInputSourceBinding::{
+    XRHandedness, XRTargetRayMode,
+};
+use crate::dom::bindings::codegen::Bindings::XRReferenceSpaceBinding::XRReferenceSpaceType;
 use crate::dom::bindings::codegen::Bindings::XRSessionBinding;
 use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XREnvironmentBlendMode;
 use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XRFrameRequestCallback;
 use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XRSessionMethods;
+use crate::dom::bindings::codegen::Bindings::XRViewBinding::XREye;
 use crate::dom::bindings::codegen::Bindings::XRWebGLLayerBinding::XRWebGLLayerMethods;
+use crate::dom::bindings::error::Error;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
-use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
+use crate::dom::fakexrdevice::FakeXRDevice;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
 use crate::dom::vrdisplay::VRDisplay;
+use crate::dom::xrboundedreferencespace::XRBoundedReferenceSpace;
+use crate::dom::xrframe::XRFrame;
+use crate::dom::xrinputsource::{XRInputAction, XRInputSource};
+use crate::dom::xrinputsourceevent::XRInputSourceEvent;
+use crate::dom::xrinputsourceschangeevent::XRInputSourcesChangeEvent;
 use crate::dom::xrlayer::XRLayer;
+use crate::dom::xrreferencespace::XRReferenceSpace;
+use crate::dom::xrrigidtransform::RigidTransform;
 use crate::dom::xrwebgllayer::XRWebGLLayer;
+use crate::timers::OneshotTimerCallback;
 use dom_struct::dom_struct;
+use script_traits::MsDuration;
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::mem;
 use std::rc::Rc;
 
+/// The delay between two frames of a simulated device.
+const SIMULATED_FRAME_INTERVAL: u64 = 16;
+
+/// The device backing a session.
+#[must_root]
+#[derive(JSTraceable, MallocSizeOf)]
+pub enum XRDevice {
+    /// A WebVR display driven by the WebVR thread.
+    Display(Dom<VRDisplay>),
+    /// A device created with `navigator.xr.test.simulateDeviceConnection`.
+    Simulated(Dom<FakeXRDevice>),
+}
+
+/// The tracking state reported by a device for a single frame. All poses
+/// are relative to the native origin of the device.
+#[derive(Clone, Default, JSTraceable, MallocSizeOf)]
+pub struct XRDeviceFrame {
+    /// The pose of the viewer, or `None` if the viewer isn't tracked.
+    pub viewer: Option<RigidTransform>,
+    /// Whether the position of the viewer is an estimate.
+    pub emulated_position: bool,
+    pub views: Vec<XRDeviceView>,
+    /// The origin of `local-floor` and `bounded-floor` spaces, if the device
+    /// knows where the floor is.
+    pub floor_origin: Option<RigidTransform>,
+    pub inputs: Vec<XRDeviceInput>,
+}
+
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct XRDeviceView {
+    pub eye: XREye,
+    /// The pose of the view relative to the viewer.
+    pub offset: RigidTransform,
+    /// The column-major projection matrix.
+    pub projection: Vec<f32>,
+}
+
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct XRDeviceInput {
+    /// Identifies the input across frames.
+    pub id: u32,
+    pub handedness: XRHandedness,
+    pub target_ray_mode: XRTargetRayMode,
+    pub target_ray: Option<RigidTransform>,
+    pub grip: Option<RigidTransform>,
+    pub emulated_position: bool,
+    pub profiles: Vec<String>,
+    pub select: XRButtonState,
+    pub squeeze: XRButtonState,
+}
+
+#[derive(Clone, Copy, Default, JSTraceable, MallocSizeOf)]
+pub struct XRButtonState {
+    pub pressed: bool,
+    /// Whether the button was released since the previous frame, which
+    /// completes an action even if no frame saw the button pressed.
+    pub clicked: bool,
+}
+
 #[dom_struct]
 pub struct XRSession {
     eventtarget: EventTarget,
-    display: Dom<VRDisplay>,
+    device: XRDevice,
     base_layer: MutNullableDom<XRLayer>,
     blend_mode: XREnvironmentBlendMode,
+    /// List of request animation frame callbacks
+    #[ignore_malloc_size_of = "closures are hard"]
+    raf_callback_list: DomRefCell<Vec<(i32, Option<Rc<XRFrameRequestCallback>>)>>,
+    next_raf_id: Cell<i32>,
+    input_sources: DomRefCell<Vec<Dom<XRInputSource>>>,
+    /// Depth values of sessions without a display, which don't need to be
+    /// passed to a compositor.
+    depth_near: Cell<f64>,
+    depth_far: Cell<f64>,
+    ended: Cell<bool>,
 }
 
 impl XRSession {
-    fn new_inherited(display: &VRDisplay) -> XRSession {
+    fn new_inherited(device: XRDevice) -> XRSession {
         XRSession {
             eventtarget: EventTarget::new_inherited(),
-            display: Dom::from_ref(display),
+            device,
             base_layer: Default::default(),
             // we don't yet support any AR devices
             blend_mode: XREnvironmentBlendMode::Opaque,
+            raf_callback_list: DomRefCell::new(vec![]),
+            next_raf_id: Cell::new(1),
+            input_sources: DomRefCell::new(vec![]),
+            depth_near: Cell::new(0.1),
+            depth_far: Cell::new(1000.),
+            ended: Cell::new(false),
         }
     }
 
     pub fn new(global: &GlobalScope, display: &VRDisplay) -> DomRoot<XRSession> {
         reflect_dom_object(
-            Box::new(XRSession::new_inherited(display)),
+            Box::new(XRSession::new_inherited(XRDevice::Display(Dom::from_ref(
+                display,
+            )))),
+            global,
+            XRSessionBinding::Wrap,
+        )
+    }
+
+    pub fn new_simulated(global: &GlobalScope, device: &FakeXRDevice) -> DomRoot<XRSession> {
+        reflect_dom_object(
+            Box::new(XRSession::new_inherited(XRDevice::Simulated(
+                Dom::from_ref(device),
+            ))),
             global,
             XRSessionBinding::Wrap,
         )
     }
 
     pub fn xr_present(&self, p: Rc<Promise>) {
-        self.display.xr_present(self, None, Some(p));
+        if let XRDevice::Display(ref display) = self.device {
+            display.xr_present(self, None, Some(p));
+        }
+    }
+
+    pub fn has_ended(&self) -> bool {
+        self.ended.get()
+    }
+
+    /// The bounds of `bounded-floor` spaces on the floor as `(x, z)` pairs,
+    /// or `None` if the device doesn't support them.
+    fn bounds(&self) -> Option<Vec<(f64, f64)>> {
+        match self.device {
+            XRDevice::Display(ref display) => display.xr_bounds(),
+            XRDevice::Simulated(ref device) => device.bounds(),
+        }
+    }
+
+    fn supports_unbounded(&self) -> bool {
+        match self.device {
+            XRDevice::Display(_) => false,
+            XRDevice::Simulated(ref device) => device.supports_unbounded(),
+        }
+    }
+
+    /// Schedules the next frame of a simulated device, which has no
+    /// display loop of its own.
+    pub fn schedule_simulated_frame(&self) {
+        let callback = XRSimulatedFrameCallback {
+            session: Trusted::new(self),
+        };
+        self.global().schedule_callback(
+            OneshotTimerCallback::XRSimulatedFrame(callback),
+            MsDuration::new(SIMULATED_FRAME_INTERVAL),
+        );
+    }
+
+    fn run_simulated_frame(&self) {
+        if self.ended.get() {
+            return;
+        }
+        if let XRDevice::Simulated(ref device) = self.device {
+            let now = self.global().as_window().Performance().Now();
+            self.run_frame(*now, device.frame());
+            self.schedule_simulated_frame();
+        }
+    }
+
+    /// Runs a frame of the session: updates the input sources, fires their
+    /// events and runs the animation frame callbacks.
+    ///
+    /// https://immersive-web.github.io/webxr/#xr-animation-frame
+    pub fn run_frame(&self, now: f64, data: XRDeviceFrame) {
+        if self.ended.get() {
+            return;
+        }
+        let frame = XRFrame::new(&self.global(), self, data);
+        frame.set_active(true);
+
+        self.update_input_sources(&frame);
+
+        let mut callbacks = mem::replace(&mut *self.raf_callback_list.borrow_mut(), vec![]);
+        for (_, callback) in callbacks.drain(..) {
+            if let Some(callback) = callback {
+                let _ = callback.Call__(Finite::wrap(now), &frame, ExceptionHandling::Report);
+            }
+        }
+
+        frame.set_active(false);
+    }
+
+    /// https://immersive-web.github.io/webxr/#list-of-active-input-sources
+    fn update_input_sources(&self, frame: &XRFrame) {
+        let global = self.global();
+        let inputs = &frame.data().inputs;
+        let mut added = vec![];
+        let mut removed = vec![];
+        {
+            let mut sources = self.input_sources.borrow_mut();
+            // A source whose handedness or target ray mode changed is
+            // replaced by a new one.
+            sources.retain(|source| {
+                let present = inputs.iter().any(|input| source.matches(input));
+                if !present {
+                    removed.push(DomRoot::from_ref(&**source));
+                }
+                present
+            });
+            for input in inputs {
+                if !sources.iter().any(|source| source.id() == input.id) {
+                    let source = XRInputSource::new(&global, self, input);
+                    sources.push(Dom::from_ref(&*source));
+                    added.push(source);
+                }
+            }
+        }
+
+        // Actions of removed sources are cancelled, without a select event.
+        for source in &removed {
+            for &action in &[XRInputAction::Select, XRInputAction::Squeeze] {
+                if source.action_in_progress(action) {
+                    source.set_action_in_progress(action, false);
+                    self.fire_input_source_event(frame, source, action.end_event());
+                }
+            }
+        }
+
+        if !added.is_empty() || !removed.is_empty() {
+            let event = XRInputSourcesChangeEvent::new(
+                &global,
+                atom!("inputsourceschange"),
+                false,
+                false,
+                self,
+                &added,
+                &removed,
+            );
+            event.upcast::<Event>().fire(self.upcast());
+        }
+
+        let sources: Vec<_> = self
+            .input_sources
+            .borrow()
+            .iter()
+            .map(|source| DomRoot::from_ref(&**source))
+            .collect();
+        for source in sources {
+            let input = match inputs.iter().find(|input| input.id == source.id()) {
+                Some(input) => input,
+                None => continue,
+            };
+            self.update_action(frame, &source, XRInputAction::Select, input.select);
+            self.update_action(frame, &source, XRInputAction::Squeeze, input.squeeze);
+        }
+    }
+
+    /// https://immersive-web.github.io/webxr/#primary-action
+    fn update_action(
+        &self,
+        frame: &XRFrame,
+        source: &XRInputSource,
+        action: XRInputAction,
+        state: XRButtonState,
+    ) {
+        if state.clicked {
+            if !source.action_in_progress(action) {
+                self.fire_input_source_event(frame, source, action.start_event());
+            }
+            self.fire_input_source_event(frame, source, action.event());
+            self.fire_input_source_event(frame, source, action.end_event());
+            source.set_action_in_progress(action, false);
+        }
+        if state.pressed && !source.action_in_progress(action) {
+            source.set_action_in_progress(action, true);
+            self.fire_input_source_event(frame, source, action.start_event());
+        } else if !state.pressed && source.action_in_progress(action) {
+            source.set_action_in_progress(action, false);
+            self.fire_input_source_event(frame, source, action.event());
+            self.fire_input_source_event(frame, source, action.end_event());
+        }
+    }
+
+    fn fire_input_source_event(&self, frame: &XRFrame, source: &XRInputSource, name: Atom) {
+        let event = XRInputSourceEvent::new(&self.global(), name, false, false, frame, source);
+        event.upcast::<Event>().fire(self.upcast());
+    }
+
+    /// Shuts the session down, either because the page ended it or because
+    /// its device went away.
+    ///
+    /// https://immersive-web.github.io/webxr/#shut-down-the-session
+    pub fn shut_down(&self) {
+        if self.ended.get() {
+            return;
+        }
+        self.ended.set(true);
+        self.raf_callback_list.borrow_mut().clear();
+        self.input_sources.borrow_mut().clear();
+        match self.device {
+            XRDevice::Display(ref display) => display.xr_exit_present(),
+            XRDevice::Simulated(ref device) => {
+                device.end_session(self);
+                self.global()
+                    .as_window()
+                    .Navigator()
+                    .Xr()
+                    .deactivate_session();
+            },
+        }
+        self.upcast::<EventTarget>().fire_event(atom!("end"));
     }
 }
 
 impl XRSessionMethods for XRSession {
     /// https://immersive-web.github.io/webxr/#dom-xrsession-depthnear
     fn DepthNear(&self) -> Finite<f64> {
-        self.display.DepthNear()
+        match self.device {
+            XRDevice::Display(ref display) => display.DepthNear(),
+            XRDevice::Simulated(_) => Finite::wrap(self.depth_near.get()),
+        }
     }
 
     /// https://immersive-web.github.io/webxr/#dom-xrsession-depthfar
     fn DepthFar(&self) -> Finite<f64> {
-        self.display.DepthFar()
+        match self.device {
+            XRDevice::Display(ref display) => display.DepthFar(),
+            XRDevice::Simulated(_) => Finite::wrap(self.depth_far.get()),
+        }
     }
 
     /// https://immersive-web.github.io/webxr/#dom-xrsession-depthnear
     fn SetDepthNear(&self, d: Finite<f64>) {
-        self.display.SetDepthNear(d)
+        match self.device {
+            XRDevice::Display(ref display) => display.SetDepthNear(d),
+            XRDevice::Simulated(_) => self.depth_near.set(*d),
+        }
     }
 
     /// https://immersive-web.github.io/webxr/#dom-xrsession-depthfar
     fn SetDepthFar(&self, d: Finite<f64>) {
-        self.display.SetDepthFar(d)
+        match self.device {
+            XRDevice::Display(ref display) => display.SetDepthFar(d),
+            XRDevice::Simulated(_) => self.depth_far.set(*d),
+        }
     }
 
     /// https://immersive-web.github.io/webxr/#dom-xrsession-mode
@@ -84,8 +398,11 @@ impl XRSessionMethods for XRSession {
     fn SetBaseLayer(&self, layer: Option<&XRLayer>) {
         self.base_layer.set(layer);
         if let Some(layer) = layer {
-            let layer = layer.downcast::<XRWebGLLayer>().unwrap();
-            self.display.xr_present(&self, Some(&layer.Context()), None);
+            // Simulated devices don't composite the layer anywhere.
+            if let XRDevice::Display(ref display) = self.device {
+                let layer = layer.downcast::<XRWebGLLayer>().unwrap();
+                display.xr_present(&self, Some(&layer.Context()), None);
+            }
         } else {
             // steps unknown
             // https://github.com/immersive-web/webxr/issues/453
@@ -99,16 +416,122 @@ impl XRSessionMethods for XRSession {
 
     /// https://immersive-web.github.io/webxr/#dom-xrsession-requestanimationframe
     fn RequestAnimationFrame(&self, callback: Rc<XRFrameRequestCallback>) -> i32 {
-        self.display.xr_raf(callback) as i32
+        let raf_id = self.next_raf_id.get();
+        self.next_raf_id.set(raf_id + 1);
+        self.raf_callback_list
+            .borrow_mut()
+            .push((raf_id, Some(callback)));
+        raf_id
     }
 
     /// https://immersive-web.github.io/webxr/#dom-xrsession-cancelanimationframe
     fn CancelAnimationFrame(&self, frame: i32) {
-        self.display.xr_cancel_raf(frame)
+        let mut list = self.raf_callback_list.borrow_mut();
+        if let Some(pair) = list.iter_mut().find(|pair| pair.0 == frame) {
+            pair.1 = None;
+        }
     }
 
     /// https://immersive-web.github.io/webxr/#dom-xrsession-environmentblendmode
     fn EnvironmentBlendMode(&self) -> XREnvironmentBlendMode {
         self.blend_mode
     }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrsession-requestreferencespace
+    fn RequestReferenceSpace(&self, ty: XRReferenceSpaceType) -> Rc<Promise> {
+        let global = self.global();
+        let p = Promise::new(&global);
+        if self.ended.get() {
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+        match ty {
+            XRReferenceSpaceType::Viewer |
+            XRReferenceSpaceType::Local |
+            XRReferenceSpaceType::Local_floor => {
+                p.resolve_native(&XRReferenceSpace::new(&global, self, ty));
+            },
+            XRReferenceSpaceType::Bounded_floor => match self.bounds() {
+                Some(ref bounds) if bounds.len() >= 3 => {
+                    let points: Vec<_> = bounds.iter().map(|&(x, z)| [x, 0., z]).collect();
+                    p.resolve_native(&XRBoundedReferenceSpace::new(
+                        &global,
+                        self,
+                        RigidTransform::identity(),
+                        &points,
+                    ));
+                },
+                _ => p.reject_error(Error::NotSupported),
+            },
+            XRReferenceSpaceType::Unbounded => {
+                if self.supports_unbounded() {
+                    p.resolve_native(&XRReferenceSpace::new(&global, self, ty));
+                } else {
+                    p.reject_error(Error::NotSupported);
+                }
+            },
+        }
+        p
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrsession-getinputsources
+    fn GetInputSources(&self) -> Vec<DomRoot<XRInputSource>> {
+        self.input_sources
+            .borrow()
+            .iter()
+            .map(|source| DomRoot::from_ref(&**source))
+            .collect()
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrsession-end
+    fn End(&self) -> Rc<Promise> {
+        let p = Promise::new(&self.global());
+        if self.ended.get() {
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+        self.shut_down();
+        p.resolve_native(&());
+        p
+    }
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-end
+    event_handler!(end, GetOnend, SetOnend);
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-select
+    event_handler!(select, GetOnselect, SetOnselect);
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-inputsourceschange
+    event_handler!(
+        inputsourceschange,
+        GetOninputsourceschange,
+        SetOninputsourceschange
+    );
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-selectstart
+    event_handler!(selectstart, GetOnselectstart, SetOnselectstart);
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-selectend
+    event_handler!(selectend, GetOnselectend, SetOnselectend);
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-squeeze
+    event_handler!(squeeze, GetOnsqueeze, SetOnsqueeze);
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-squeezestart
+    event_handler!(squeezestart, GetOnsqueezestart, SetOnsqueezestart);
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-squeezeend
+    event_handler!(squeezeend, GetOnsqueezeend, SetOnsqueezeend);
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct XRSimulatedFrameCallback {
+    #[ignore_malloc_size_of = "non-owning"]
+    session: Trusted<XRSession>,
+}
+
+impl XRSimulatedFrameCallback {
+    pub fn invoke(self) {
+        self.session.root().run_simulated_frame();
+    }
 }
@@ -3,30 +3,94 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::codegen::Bindings::XRSpaceBinding;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrinputsource::XRInputSource;
+use crate::dom::xrreferencespace::XRReferenceSpace;
+use crate::dom::xrrigidtransform::RigidTransform;
+use crate::dom::xrsession::{XRDeviceFrame, XRSession};
 use dom_struct::dom_struct;
 
 #[dom_struct]
 pub struct XRSpace {
     eventtarget: EventTarget,
+    session: Dom<XRSession>,
+    /// The input source whose target ray or grip this space tracks, unless
+    /// this is a reference space.
+    input_source: Option<Dom<XRInputSource>>,
+    is_grip_space: bool,
 }
 
 impl XRSpace {
-    pub fn new_inherited() -> XRSpace {
+    pub fn new_inherited(session: &XRSession) -> XRSpace {
         XRSpace {
             eventtarget: EventTarget::new_inherited(),
+            session: Dom::from_ref(session),
+            input_source: None,
+            is_grip_space: false,
         }
     }
 
-    #[allow(unused)]
-    pub fn new(global: &GlobalScope) -> DomRoot<XRSpace> {
+    fn new_input_space_inherited(
+        session: &XRSession,
+        input_source: &XRInputSource,
+        is_grip_space: bool,
+    ) -> XRSpace {
+        XRSpace {
+            eventtarget: EventTarget::new_inherited(),
+            session: Dom::from_ref(session),
+            input_source: Some(Dom::from_ref(input_source)),
+            is_grip_space,
+        }
+    }
+
+    pub fn new_input_space(
+        global: &GlobalScope,
+        session: &XRSession,
+        input_source: &XRInputSource,
+        is_grip_space: bool,
+    ) -> DomRoot<XRSpace> {
         reflect_dom_object(
-            Box::new(XRSpace::new_inherited()),
+            Box::new(XRSpace::new_input_space_inherited(
+                session,
+                input_source,
+                is_grip_space,
+            )),
             global,
             XRSpaceBinding::Wrap,
         )
     }
+
+    pub fn session(&self) -> &XRSession {
+        &self.session
+    }
+
+    /// Returns the pose of the origin of this space relative to the native
+    /// origin of the device, or `None` if it isn't tracked in this frame.
+    pub fn get_pose(&self, data: &XRDeviceFrame) -> Option<RigidTransform> {
+        if let Some(reference) = self.downcast::<XRReferenceSpace>() {
+            return reference.get_pose(data);
+        }
+        let source = self.input_source.as_ref()?;
+        let input = data.inputs.iter().find(|input| input.id == source.id())?;
+        if self.is_grip_space {
+            input.grip
+        } else {
+            input.target_ray
+        }
+    }
+
+    /// Whether the position of this space is an estimate in this frame.
+    pub fn emulated_position(&self, data: &XRDeviceFrame) -> bool {
+        if let Some(reference) = self.downcast::<XRReferenceSpace>() {
+            return reference.emulated_position(data);
+        }
+        self.input_source
+            .as_ref()
+            .and_then(|source| data.inputs.iter().find(|input| input.id == source.id()))
+            .map_or(false, |input| input.emulated_position)
+    }
 }
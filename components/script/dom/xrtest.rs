@@ -0,0 +1,65 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::XRTestBinding::{
+    self, FakeXRDeviceInit, XRTestMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::fakexrdevice::FakeXRDevice;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use dom_struct::dom_struct;
+use std::rc::Rc;
+
+#[dom_struct]
+pub struct XRTest {
+    reflector_: Reflector,
+    devices_connected: DomRefCell<Vec<Dom<FakeXRDevice>>>,
+}
+
+impl XRTest {
+    fn new_inherited() -> XRTest {
+        XRTest {
+            reflector_: Reflector::new(),
+            devices_connected: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<XRTest> {
+        reflect_dom_object(
+            Box::new(XRTest::new_inherited()),
+            global,
+            XRTestBinding::Wrap,
+        )
+    }
+
+    /// The most recently connected device which hasn't been disconnected.
+    pub fn connected_device(&self) -> Option<DomRoot<FakeXRDevice>> {
+        self.devices_connected
+            .borrow()
+            .iter()
+            .rev()
+            .find(|d| d.is_connected())
+            .map(|d| DomRoot::from_ref(&**d))
+    }
+}
+
+impl XRTestMethods for XRTest {
+    /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
+    fn SimulateDeviceConnection(&self, init: &FakeXRDeviceInit) -> Rc<Promise> {
+        let p = Promise::new(&self.global());
+        match FakeXRDevice::new(&self.global(), init) {
+            Ok(device) => {
+                self.devices_connected
+                    .borrow_mut()
+                    .push(Dom::from_ref(&*device));
+                p.resolve_native(&device);
+            },
+            Err(error) => p.reject_error(error),
+        }
+        p
+    }
+}
@@ -8,11 +8,11 @@ use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::vrframedata::create_typed_array;
+use crate::dom::xrrigidtransform::{RigidTransform, XRRigidTransform};
 use crate::dom::xrsession::XRSession;
 use dom_struct::dom_struct;
 use js::jsapi::{Heap, JSContext, JSObject};
 use std::ptr::NonNull;
-use webvr_traits::WebVRFrameData;
 
 #[dom_struct]
 pub struct XRView {
@@ -21,42 +21,42 @@ pub struct XRView {
     eye: XREye,
     proj: Heap<*mut JSObject>,
     view: Heap<*mut JSObject>,
+    transform: Dom<XRRigidTransform>,
 }
 
 impl XRView {
-    fn new_inherited(session: &XRSession, eye: XREye) -> XRView {
+    fn new_inherited(session: &XRSession, eye: XREye, transform: &XRRigidTransform) -> XRView {
         XRView {
             reflector_: Reflector::new(),
             session: Dom::from_ref(session),
             eye,
             proj: Heap::default(),
             view: Heap::default(),
+            transform: Dom::from_ref(transform),
         }
     }
 
+    /// Creates a view from its pose in the reference space and its
+    /// projection matrix.
     #[allow(unsafe_code)]
     pub fn new(
         global: &GlobalScope,
         session: &XRSession,
         eye: XREye,
-        data: &WebVRFrameData,
+        pose: RigidTransform,
+        projection: &[f32],
     ) -> DomRoot<XRView> {
+        let transform = XRRigidTransform::new(global, pose);
         let ret = reflect_dom_object(
-            Box::new(XRView::new_inherited(session, eye)),
+            Box::new(XRView::new_inherited(session, eye, &transform)),
             global,
             XRViewBinding::Wrap,
         );
 
-        let (proj, view) = if eye == XREye::Left {
-            (&data.left_projection_matrix, &data.left_view_matrix)
-        } else {
-            (&data.right_projection_matrix, &data.right_view_matrix)
-        };
-
         let cx = global.get_cx();
         unsafe {
-            create_typed_array(cx, proj, &ret.proj);
-            create_typed_array(cx, view, &ret.view);
+            create_typed_array(cx, projection, &ret.proj);
+            create_typed_array(cx, &pose.inverse().to_matrix(), &ret.view);
         }
         ret
     }
@@ -83,4 +83,9 @@ impl XRViewMethods for XRView {
     unsafe fn ViewMatrix(&self, _cx: *mut JSContext) -> NonNull<JSObject> {
         NonNull::new(self.view.get()).unwrap()
     }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrview-transform
+    fn Transform(&self) -> DomRoot<XRRigidTransform> {
+        DomRoot::from_ref(&self.transform)
+    }
 }
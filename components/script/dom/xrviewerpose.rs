@@ -4,9 +4,11 @@
 
 use crate::dom::bindings::codegen::Bindings::XRViewerPoseBinding;
 use crate::dom::bindings::codegen::Bindings::XRViewerPoseBinding::XRViewerPoseMethods;
-use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrpose::XRPose;
+use crate::dom::xrrigidtransform::{RigidTransform, XRRigidTransform};
 use crate::dom::xrview::XRView;
 use dom_struct::dom_struct;
 use js::conversions::ToJSValConvertible;
@@ -15,22 +17,28 @@ use js::jsval::{JSVal, UndefinedValue};
 
 #[dom_struct]
 pub struct XRViewerPose {
-    reflector_: Reflector,
+    pose: XRPose,
     views: Heap<JSVal>,
 }
 
 impl XRViewerPose {
-    fn new_inherited() -> XRViewerPose {
+    fn new_inherited(transform: &XRRigidTransform, emulated_position: bool) -> XRViewerPose {
         XRViewerPose {
-            reflector_: Reflector::new(),
+            pose: XRPose::new_inherited(transform, emulated_position),
             views: Heap::default(),
         }
     }
 
     #[allow(unsafe_code)]
-    pub fn new(global: &GlobalScope, left: &XRView, right: &XRView) -> DomRoot<XRViewerPose> {
+    pub fn new(
+        global: &GlobalScope,
+        pose: RigidTransform,
+        emulated_position: bool,
+        views: &[DomRoot<XRView>],
+    ) -> DomRoot<XRViewerPose> {
+        let transform = XRRigidTransform::new(global, pose);
         let pose = reflect_dom_object(
-            Box::new(XRViewerPose::new_inherited()),
+            Box::new(XRViewerPose::new_inherited(&transform, emulated_position)),
             global,
             XRViewerPoseBinding::Wrap,
         );
@@ -38,8 +46,7 @@ impl XRViewerPose {
         unsafe {
             let cx = global.get_cx();
             rooted!(in(cx) let mut jsval = UndefinedValue());
-            let vec = vec![DomRoot::from_ref(left), DomRoot::from_ref(right)];
-            vec.to_jsval(cx, jsval.handle_mut());
+            views.to_jsval(cx, jsval.handle_mut());
             pose.views.set(jsval.get());
         }
 
@@ -0,0 +1,371 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Serialisation of canvas bitmaps for `toDataURL()`, `toBlob()` and
+//! `convertToBlob()`.
+//!
+//! https://html.spec.whatwg.org/multipage/#a-serialisation-of-the-bitmap-as-a-file
+
+use euclid::Size2D;
+use image::jpeg::JPEGEncoder;
+use image::png::PNGEncoder;
+use image::ColorType;
+use std::cmp::{self, Reverse};
+use std::collections::BinaryHeap;
+
+/// The quality used for lossy formats when none or an invalid one is given.
+const DEFAULT_QUALITY: f64 = 0.92;
+
+/// The largest dimension a WebP image can have.
+const MAX_WEBP_DIMENSION: u32 = 1 << 14;
+
+/// The largest dimension a JPEG image can have.
+const MAX_JPEG_DIMENSION: u32 = 65535;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageType {
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl ImageType {
+    /// Returns the type to serialise a bitmap of the given size as when
+    /// `mime_type` is requested, falling back to PNG for types we don't
+    /// support or can't encode an image that large as.
+    pub fn new(mime_type: Option<&str>, size: Size2D<u32>) -> ImageType {
+        let mime_type = match mime_type {
+            Some(mime_type) => mime_type.to_ascii_lowercase(),
+            None => return ImageType::Png,
+        };
+        let fits = |max| size.width <= max && size.height <= max;
+        match &*mime_type {
+            "image/jpeg" if fits(MAX_JPEG_DIMENSION) => ImageType::Jpeg,
+            "image/webp" if fits(MAX_WEBP_DIMENSION) => ImageType::Webp,
+            _ => ImageType::Png,
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match *self {
+            ImageType::Png => "image/png",
+            ImageType::Jpeg => "image/jpeg",
+            ImageType::Webp => "image/webp",
+        }
+    }
+}
+
+/// Encodes non-premultiplied RGBA pixels as an image of the given type.
+///
+/// `quality` is only used by lossy formats and is ignored unless it is
+/// between 0 and 1.
+pub fn encode(
+    image_type: ImageType,
+    pixels: &[u8],
+    size: Size2D<u32>,
+    quality: Option<f64>,
+) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    match image_type {
+        ImageType::Png => {
+            // FIXME(nox): https://github.com/PistonDevelopers/image-png/issues/86
+            // FIXME(nox): https://github.com/PistonDevelopers/image-png/issues/87
+            PNGEncoder::new(&mut encoded)
+                .encode(pixels, size.width, size.height, ColorType::RGBA(8))
+                .unwrap();
+        },
+        ImageType::Jpeg => {
+            let quality = quality
+                .filter(|quality| *quality >= 0. && *quality <= 1.)
+                .unwrap_or(DEFAULT_QUALITY);
+            let quality = cmp::max((quality * 100.).round() as u8, 1);
+            // JPEG has no alpha channel, the pixels are composited onto
+            // opaque black.
+            let rgb = pixels
+                .chunks(4)
+                .flat_map(|pixel| {
+                    let alpha = pixel[3] as u32;
+                    (0..3).map(move |i| ((pixel[i] as u32 * alpha + 127) / 255) as u8)
+                })
+                .collect::<Vec<u8>>();
+            JPEGEncoder::new_with_quality(&mut encoded, quality)
+                .encode(&rgb, size.width, size.height, ColorType::RGB(8))
+                .unwrap();
+        },
+        ImageType::Webp => encode_webp(pixels, size, &mut encoded),
+    }
+    encoded
+}
+
+/// Encodes the pixels as a lossless (VP8L) WebP image, the size must not be
+/// empty.
+///
+/// https://developers.google.com/speed/webp/docs/webp_lossless_bitstream_specification
+fn encode_webp(pixels: &[u8], size: Size2D<u32>, encoded: &mut Vec<u8>) {
+    // Apply the subtract green transform, it usually makes the red and blue
+    // channels much more compressible. The channels are kept in the order
+    // their prefix codes are written: green, red, blue and alpha.
+    let transformed = pixels
+        .chunks(4)
+        .map(|pixel| {
+            [
+                pixel[1],
+                pixel[0].wrapping_sub(pixel[1]),
+                pixel[2].wrapping_sub(pixel[1]),
+                pixel[3],
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let mut histograms = [vec![0; 280], vec![0; 256], vec![0; 256], vec![0; 256]];
+    for pixel in &transformed {
+        for (histogram, &value) in histograms.iter_mut().zip(pixel) {
+            histogram[value as usize] += 1;
+        }
+    }
+
+    let mut writer = BitWriter::new();
+    writer.write(0x2f, 8);
+    writer.write(size.width - 1, 14);
+    writer.write(size.height - 1, 14);
+    let alpha_is_used = transformed.iter().any(|pixel| pixel[3] != 0xff);
+    writer.write(alpha_is_used as u32, 1);
+    // Version.
+    writer.write(0, 3);
+    // A subtract green transform, and no other transform.
+    writer.write(1, 1);
+    writer.write(2, 2);
+    writer.write(0, 1);
+    // No color cache.
+    writer.write(0, 1);
+    // A single group of prefix codes for the whole image.
+    writer.write(0, 1);
+
+    // The green, red, blue and alpha codes, we only emit literals so the
+    // distance code is always empty.
+    let codes = histograms
+        .iter()
+        .map(|histogram| PrefixCode::write(&mut writer, histogram))
+        .collect::<Vec<_>>();
+    PrefixCode::write(&mut writer, &[0; 40]);
+
+    for pixel in &transformed {
+        for (code, &value) in codes.iter().zip(pixel) {
+            code.write_symbol(&mut writer, value as usize);
+        }
+    }
+
+    let data = writer.finish();
+    let chunk_size = data.len() as u32;
+    let padded_size = (chunk_size + 1) & !1;
+    encoded.extend_from_slice(b"RIFF");
+    encoded.extend_from_slice(&(4 + 8 + padded_size).to_le_bytes());
+    encoded.extend_from_slice(b"WEBP");
+    encoded.extend_from_slice(b"VP8L");
+    encoded.extend_from_slice(&chunk_size.to_le_bytes());
+    encoded.extend_from_slice(&data);
+    if padded_size != chunk_size {
+        encoded.push(0);
+    }
+}
+
+/// The order in which the code lengths of the code length code are written.
+const CODE_LENGTH_CODE_ORDER: [usize; 19] = [
+    17, 18, 0, 1, 2, 3, 4, 5, 16, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+];
+
+/// A canonical prefix code, with the codes already bit-reversed for the
+/// LSB-first `BitWriter`.
+struct PrefixCode {
+    lengths: Vec<u8>,
+    codes: Vec<u16>,
+}
+
+impl PrefixCode {
+    fn new(lengths: &[u8]) -> PrefixCode {
+        let codes = canonical_codes(lengths);
+        let mut lengths = lengths.to_vec();
+        // A code with a single symbol uses zero bits per symbol.
+        if lengths.iter().filter(|&&length| length != 0).count() == 1 {
+            lengths.iter_mut().for_each(|length| *length = 0);
+        }
+        PrefixCode { lengths, codes }
+    }
+
+    /// Builds the code for `histogram` and writes it to the bitstream.
+    fn write(writer: &mut BitWriter, histogram: &[u32]) -> PrefixCode {
+        let lengths = huffman_code_lengths(histogram, 15);
+        let symbols = (0..histogram.len())
+            .filter(|&symbol| histogram[symbol] != 0)
+            .collect::<Vec<_>>();
+
+        if symbols.len() <= 2 && symbols.iter().all(|&symbol| symbol < 256) {
+            // Simple code.
+            writer.write(1, 1);
+            if symbols.len() < 2 {
+                let symbol = symbols.first().map_or(0, |&symbol| symbol as u32);
+                writer.write(0, 1);
+                if symbol < 2 {
+                    writer.write(0, 1);
+                    writer.write(symbol, 1);
+                } else {
+                    writer.write(1, 1);
+                    writer.write(symbol, 8);
+                }
+            } else {
+                writer.write(1, 1);
+                writer.write(1, 1);
+                writer.write(symbols[0] as u32, 8);
+                writer.write(symbols[1] as u32, 8);
+            }
+            return PrefixCode::new(&lengths);
+        }
+
+        // Normal code, the code lengths are themselves prefix coded.
+        writer.write(0, 1);
+        let mut length_histogram = [0; 19];
+        for &length in &lengths {
+            length_histogram[length as usize] += 1;
+        }
+        let length_code_lengths = huffman_code_lengths(&length_histogram, 7);
+        let count = CODE_LENGTH_CODE_ORDER
+            .iter()
+            .rposition(|&symbol| length_code_lengths[symbol] != 0)
+            .map_or(0, |position| position + 1);
+        let count = cmp::max(count, 4);
+        writer.write(count as u32 - 4, 4);
+        for &symbol in &CODE_LENGTH_CODE_ORDER[..count] {
+            writer.write(length_code_lengths[symbol] as u32, 3);
+        }
+        // The lengths of all the symbols follow.
+        writer.write(0, 1);
+        let length_code = PrefixCode::new(&length_code_lengths);
+        for &length in &lengths {
+            length_code.write_symbol(writer, length as usize);
+        }
+        PrefixCode::new(&lengths)
+    }
+
+    fn write_symbol(&self, writer: &mut BitWriter, symbol: usize) {
+        writer.write(self.codes[symbol] as u32, self.lengths[symbol] as u32);
+    }
+}
+
+/// Computes the lengths of a Huffman code for `histogram` no longer than
+/// `max_length`, by flattening the histogram until the tree is shallow
+/// enough.
+fn huffman_code_lengths(histogram: &[u32], max_length: u8) -> Vec<u8> {
+    let mut lengths = vec![0; histogram.len()];
+    let symbols = (0..histogram.len())
+        .filter(|&symbol| histogram[symbol] != 0)
+        .collect::<Vec<_>>();
+    match symbols.len() {
+        0 => return lengths,
+        1 => {
+            lengths[symbols[0]] = 1;
+            return lengths;
+        },
+        _ => {},
+    }
+
+    let mut min_count = 1;
+    loop {
+        // The leaves come first, internal nodes are always created after
+        // their children.
+        let mut parents = vec![0; symbols.len() * 2 - 1];
+        let mut heap = symbols
+            .iter()
+            .enumerate()
+            .map(|(node, &symbol)| Reverse((cmp::max(histogram[symbol] as u64, min_count), node)))
+            .collect::<BinaryHeap<_>>();
+        let mut next_node = symbols.len();
+        while heap.len() > 1 {
+            let Reverse((first_count, first)) = heap.pop().unwrap();
+            let Reverse((second_count, second)) = heap.pop().unwrap();
+            parents[first] = next_node;
+            parents[second] = next_node;
+            heap.push(Reverse((first_count + second_count, next_node)));
+            next_node += 1;
+        }
+
+        let root = next_node - 1;
+        let mut depths = vec![0u32; next_node];
+        for node in (0..root).rev() {
+            depths[node] = depths[parents[node]] + 1;
+        }
+        if depths[..symbols.len()]
+            .iter()
+            .all(|&depth| depth <= max_length as u32)
+        {
+            for (node, &symbol) in symbols.iter().enumerate() {
+                lengths[symbol] = depths[node] as u8;
+            }
+            return lengths;
+        }
+        min_count *= 2;
+    }
+}
+
+/// Assigns the canonical codes for the given code lengths, bit-reversed.
+fn canonical_codes(lengths: &[u8]) -> Vec<u16> {
+    let mut length_counts = [0u16; 16];
+    for &length in lengths.iter().filter(|&&length| length != 0) {
+        length_counts[length as usize] += 1;
+    }
+    let mut next_codes = [0u16; 16];
+    let mut code = 0;
+    for length in 1..16 {
+        code = (code + length_counts[length - 1]) << 1;
+        next_codes[length] = code;
+    }
+    lengths
+        .iter()
+        .map(|&length| {
+            if length == 0 {
+                return 0;
+            }
+            let code = next_codes[length as usize];
+            next_codes[length as usize] += 1;
+            reverse_bits(code, length)
+        })
+        .collect()
+}
+
+fn reverse_bits(code: u16, length: u8) -> u16 {
+    (0..length).fold(0, |reversed, bit| (reversed << 1) | ((code >> bit) & 1))
+}
+
+/// Writes values least significant bit first.
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter {
+            bytes: Vec::new(),
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    fn write(&mut self, value: u32, bits: u32) {
+        self.buffer |= (value as u64) << self.count;
+        self.count += bits;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
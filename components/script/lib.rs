@@ -57,6 +57,7 @@ pub mod document_loader;
 #[macro_use]
 mod dom;
 pub mod fetch;
+mod image_encoder;
 mod image_listener;
 mod layout_image;
 mod mem;
@@ -76,6 +77,7 @@ pub mod textinput;
 mod timers;
 mod unpremultiplytable;
 mod webdriver_handlers;
+mod webvtt;
 
 /// A module with everything layout can use from script.
 ///
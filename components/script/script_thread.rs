@@ -25,6 +25,7 @@ use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
 };
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventInit;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::TransitionEventBinding::TransitionEventInit;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::conversions::{
@@ -54,8 +55,8 @@ use crate::dom::mutationobserver::MutationObserver;
 use crate::dom::node::{from_untrusted_node_address, window_from_node, Node, NodeDamage};
 use crate::dom::performanceentry::PerformanceEntry;
 use crate::dom::performancepainttiming::PerformancePaintTiming;
-use crate::dom::serviceworker::TrustedServiceWorkerAddress;
-use crate::dom::serviceworkerregistration::ServiceWorkerRegistration;
+use crate::dom::serviceworker::{ServiceWorker, TrustedServiceWorkerAddress};
+use crate::dom::serviceworkerregistration::{longest_prefix_match, ServiceWorkerRegistration};
 use crate::dom::servoparser::{ParserContext, ServoParser};
 use crate::dom::transitionevent::TransitionEvent;
 use crate::dom::uievent::UIEvent;
@@ -74,6 +75,7 @@ use crate::task_queue::{QueuedTask, QueuedTaskConversion, TaskQueue};
 use crate::task_source::dom_manipulation::DOMManipulationTaskSource;
 use crate::task_source::file_reading::FileReadingTaskSource;
 use crate::task_source::history_traversal::HistoryTraversalTaskSource;
+use crate::task_source::idle::IdleTaskSource;
 use crate::task_source::media_element::MediaElementTaskSource;
 use crate::task_source::networking::NetworkingTaskSource;
 use crate::task_source::performance_timeline::PerformanceTimelineTaskSource;
@@ -88,7 +90,7 @@ use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::CSSError;
 use devtools_traits::{DevtoolScriptControlMsg, DevtoolsPageInfo};
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{EmbedderMsg, GeolocationPosition};
 use euclid::{Point2D, Rect, Vector2D};
 use headers_core::HeaderMapExt;
 use headers_ext::LastModified;
@@ -130,13 +132,16 @@ use script_traits::{InitialScriptState, JsEvalResult, LayoutMsg, LoadData};
 use script_traits::{MouseButton, MouseEventType, NewLayoutInfo};
 use script_traits::{Painter, ProgressiveWebMetricType, ScriptMsg, ScriptThreadFactory};
 use script_traits::{ScriptToConstellationChan, TimerEvent, TimerSchedulerMsg};
+use script_traits::{ServiceWorkerId, ServiceWorkerLifecycleState};
 use script_traits::{TimerSource, TouchEventType, TouchId, UntrustedNodeAddress};
 use script_traits::{UpdatePipelineIdReason, WindowSizeData, WindowSizeType};
 use servo_atoms::Atom;
 use servo_config::opts;
+use servo_config::prefs::{PrefValue, PREFS};
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use std::cell::Cell;
 use std::cell::RefCell;
+use std::cmp;
 use std::collections::{hash_map, HashMap, HashSet};
 use std::default::Default;
 use std::ops::Deref;
@@ -157,6 +162,10 @@ use webvr_traits::{WebVREvent, WebVRMsg};
 
 pub type ImageCacheMsg = (PipelineId, PendingImageResponse);
 
+/// The maximum duration of an idle period, in nanoseconds.
+/// <https://w3c.github.io/requestidlecallback/#why50>
+const MAX_IDLE_PERIOD_NS: u64 = 50_000_000;
+
 thread_local!(static SCRIPT_THREAD_ROOT: Cell<Option<*const ScriptThread>> = Cell::new(None));
 
 pub unsafe fn trace_thread(tr: *mut JSTracer) {
@@ -561,6 +570,8 @@ pub struct ScriptThread {
 
     file_reading_task_sender: Box<dyn ScriptChan>,
 
+    idle_task_sender: Box<dyn ScriptChan>,
+
     performance_timeline_task_sender: Box<dyn ScriptChan>,
 
     remote_event_task_sender: Box<dyn ScriptChan>,
@@ -1118,6 +1129,7 @@ impl ScriptThread {
             user_interaction_task_sender: chan.clone(),
             networking_task_sender: boxed_script_sender.clone(),
             file_reading_task_sender: boxed_script_sender.clone(),
+            idle_task_sender: boxed_script_sender.clone(),
             performance_timeline_task_sender: boxed_script_sender.clone(),
             remote_event_task_sender: boxed_script_sender.clone(),
 
@@ -1395,9 +1407,57 @@ impl ScriptThread {
             }
         }
 
+        self.start_idle_periods();
+
         true
     }
 
+    /// Whether the event-loop has no pending messages from any of its sources.
+    fn event_loop_is_idle(&self) -> bool {
+        self.task_queue.is_empty() &&
+            self.control_port.is_empty() &&
+            self.timer_event_port.is_empty() &&
+            self.devtools_port.is_empty() &&
+            self.image_cache_port.is_empty()
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#start-an-idle-period-algorithm>
+    fn start_idle_periods(&self) {
+        if !self.event_loop_is_idle() {
+            return;
+        }
+
+        for (_, document) in self.documents.borrow().iter() {
+            if !document.is_fully_active() {
+                continue;
+            }
+            let window = document.window();
+            if !window.needs_idle_period() {
+                continue;
+            }
+
+            // Step 1.
+            let now = precise_time_ns();
+
+            // Steps 2-3: the idle period lasts at most 50ms, and must end
+            // before the next pending timer or animation frame is due.
+            let mut deadline = now + MAX_IDLE_PERIOD_NS;
+            if let Some(timer) = window.upcast::<GlobalScope>().next_timer_deadline() {
+                deadline = cmp::min(deadline, timer.get() * 1_000_000);
+            }
+            if let Some(frame) = document.next_animation_frame_deadline() {
+                deadline = cmp::min(deadline, frame);
+            }
+            if deadline <= now {
+                // Something else is due right away; try again once it has run.
+                continue;
+            }
+
+            // Steps 4-5.
+            window.start_an_idle_period(deadline);
+        }
+    }
+
     fn categorize_msg(&self, msg: &MixedMessage) -> ScriptThreadEventCategory {
         match *msg {
             MixedMessage::FromConstellation(ref inner_msg) => match *inner_msg {
@@ -1500,6 +1560,14 @@ impl ScriptThread {
                     Reload(id, ..) => Some(id),
                     WebVREvents(id, ..) => Some(id),
                     PaintMetric(..) => None,
+                    LargestContentfulPaint(..) => None,
+                    LayoutShift(..) => None,
+                    SetLanguages(id, ..) => Some(id),
+                    SetOffline(id, ..) => Some(id),
+                    SetGeolocation(id, ..) => Some(id),
+                    ServiceWorkerUpdateFinished(id, ..) => Some(id),
+                    ServiceWorkerStateChanged(id, ..) => Some(id),
+                    SetServiceWorkerController(id, ..) => Some(id),
                 }
             },
             MixedMessage::FromDevtools(_) => None,
@@ -1594,6 +1662,7 @@ impl ScriptThread {
                         );
                     }
                     doc.start_tti();
+                    doc.window().Performance().queue_long_task(start, end);
                 }
             }
             doc.record_tti_if_necessary();
@@ -1727,6 +1796,37 @@ impl ScriptThread {
             ConstellationControlMsg::PaintMetric(pipeline_id, metric_type, metric_value) => {
                 self.handle_paint_metric(pipeline_id, metric_type, metric_value)
             },
+            ConstellationControlMsg::LargestContentfulPaint(
+                pipeline_id,
+                render_time,
+                size,
+                node,
+            ) => self.handle_largest_contentful_paint(pipeline_id, render_time, size, node),
+            ConstellationControlMsg::LayoutShift(pipeline_id, value, time) => {
+                self.handle_layout_shift(pipeline_id, value, time)
+            },
+            ConstellationControlMsg::SetLanguages(pipeline_id, languages) => {
+                self.handle_set_languages(pipeline_id, languages)
+            },
+            ConstellationControlMsg::SetOffline(pipeline_id, offline) => {
+                self.handle_set_offline(pipeline_id, offline)
+            },
+            ConstellationControlMsg::SetGeolocation(pipeline_id, position) => {
+                self.handle_set_geolocation(pipeline_id, position)
+            },
+            ConstellationControlMsg::ServiceWorkerUpdateFinished(_, scope, result) => self
+                .job_queue_map
+                .handle_update_finished(scope, result, self),
+            ConstellationControlMsg::ServiceWorkerStateChanged(
+                _,
+                scope,
+                worker_id,
+                script_url,
+                state,
+            ) => self.handle_serviceworker_state_changed(scope, worker_id, script_url, state),
+            ConstellationControlMsg::SetServiceWorkerController(pipeline_id, scope) => {
+                self.handle_set_serviceworker_controller(pipeline_id, scope)
+            },
             msg @ ConstellationControlMsg::AttachLayout(..) |
             msg @ ConstellationControlMsg::Viewport(..) |
             msg @ ConstellationControlMsg::SetScrollState(..) |
@@ -2355,34 +2455,52 @@ impl ScriptThread {
         &self,
         scope: &ServoUrl,
         registration: &ServiceWorkerRegistration,
-        pipeline_id: PipelineId,
     ) {
-        {
-            let ref mut reg_ref = *self.registration_map.borrow_mut();
-            // according to spec we should replace if an older registration exists for
-            // same scope otherwise just insert the new one
-            let _ = reg_ref.remove(scope);
-            reg_ref.insert(scope.clone(), Dom::from_ref(registration));
+        let ref mut reg_ref = *self.registration_map.borrow_mut();
+        // according to spec we should replace if an older registration exists for
+        // same scope otherwise just insert the new one
+        let _ = reg_ref.remove(scope);
+        reg_ref.insert(scope.clone(), Dom::from_ref(registration));
+    }
+
+    /// The active worker of the registration whose scope is the longest to match the
+    /// given url, if any.
+    pub fn controller_for(url: &ServoUrl) -> Option<DomRoot<ServiceWorker>> {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            let script_thread = unsafe { &*root.get()? };
+            let registrations = script_thread.registration_map.borrow();
+            registrations
+                .iter()
+                .filter(|&(scope, _)| longest_prefix_match(scope, url))
+                .max_by_key(|&(scope, _)| scope.as_str().len())
+                .and_then(|(_, registration)| registration.active())
+        })
+    }
+
+    fn handle_serviceworker_state_changed(
+        &self,
+        scope: ServoUrl,
+        worker_id: ServiceWorkerId,
+        script_url: ServoUrl,
+        state: ServiceWorkerLifecycleState,
+    ) {
+        // Every page of the scope's origin gets told, the registration may not live here.
+        if let Some(registration) = self.handle_get_registration(&scope) {
+            registration.handle_state_change(worker_id, script_url, state);
         }
+    }
 
-        // send ScopeThings to sw-manager
-        let ref maybe_registration_ref = *self.registration_map.borrow();
-        let maybe_registration = match maybe_registration_ref.get(scope) {
-            Some(r) => r,
-            None => return,
-        };
+    fn handle_set_serviceworker_controller(&self, pipeline_id: PipelineId, scope: ServoUrl) {
         let window = match { self.documents.borrow().find_window(pipeline_id) } {
             Some(window) => window,
-            None => return warn!("Registration failed for {}", scope),
+            None => return warn!("Controller set for closed pipeline {}.", pipeline_id),
         };
-
-        let script_url = maybe_registration.get_installed().get_script_url();
-        let scope_things =
-            ServiceWorkerRegistration::create_scope_things(window.upcast(), script_url);
-        let _ = self.script_sender.send((
-            pipeline_id,
-            ScriptMsg::RegisterServiceWorker(scope_things, scope.clone()),
-        ));
+        let active = self
+            .handle_get_registration(&scope)
+            .and_then(|registration| registration.active());
+        if let Some(active) = active {
+            window.Navigator().ServiceWorker().set_controller(&*active);
+        }
     }
 
     pub fn schedule_job_queue(&self, scope_url: ServoUrl) {
@@ -2399,6 +2517,10 @@ impl ScriptThread {
         DOMManipulationTaskSource(self.dom_manipulation_task_sender.clone(), pipeline_id)
     }
 
+    pub fn idle_task_source(&self, pipeline_id: PipelineId) -> IdleTaskSource {
+        IdleTaskSource(self.idle_task_sender.clone(), pipeline_id)
+    }
+
     pub fn media_element_task_source(&self, pipeline_id: PipelineId) -> MediaElementTaskSource {
         MediaElementTaskSource(self.media_element_task_sender.clone(), pipeline_id)
     }
@@ -2821,6 +2943,7 @@ impl ScriptThread {
             self.dom_manipulation_task_source(incomplete.pipeline_id),
             self.file_reading_task_source(incomplete.pipeline_id),
             self.history_traversal_task_source(incomplete.pipeline_id),
+            self.idle_task_source(incomplete.pipeline_id),
             self.media_element_task_source(incomplete.pipeline_id),
             self.networking_task_source(incomplete.pipeline_id),
             self.performance_timeline_task_source(incomplete.pipeline_id)
@@ -3463,6 +3586,73 @@ impl ScriptThread {
         }
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#event-languagechange>
+    fn handle_set_languages(&self, pipeline_id: PipelineId, languages: Vec<String>) {
+        // Workers and layout of this process read the languages from here.
+        PREFS.set_languages(&languages);
+
+        let window = match { self.documents.borrow().find_window(pipeline_id) } {
+            None => return warn!("Languages sent to closed pipeline {}.", pipeline_id),
+            Some(window) => window,
+        };
+
+        // The default language used by `:lang()` may have changed.
+        window
+            .Document()
+            .set_default_language(PREFS.languages().swap_remove(0));
+
+        window
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue_simple_event(window.upcast(), atom!("languagechange"), &window);
+        window
+            .upcast::<GlobalScope>()
+            .notify_workers_of_language_change();
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-navigator-online>
+    fn handle_set_offline(&self, pipeline_id: PipelineId, offline: bool) {
+        // `navigator.onLine` reads the network state from here.
+        PREFS.set("network.offline", PrefValue::Boolean(offline));
+
+        let window = match { self.documents.borrow().find_window(pipeline_id) } {
+            None => return warn!("Offline state sent to closed pipeline {}.", pipeline_id),
+            Some(window) => window,
+        };
+        if !window.set_offline(offline) {
+            return;
+        }
+
+        let name = if offline {
+            atom!("offline")
+        } else {
+            atom!("online")
+        };
+        window
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue_simple_event(window.upcast(), name, &window);
+        window
+            .upcast::<GlobalScope>()
+            .notify_workers_of_network_state(!offline);
+    }
+
+    /// <https://w3c.github.io/geolocation-api/#dfn-acquire-a-position>
+    fn handle_set_geolocation(
+        &self,
+        pipeline_id: PipelineId,
+        position: Option<GeolocationPosition>,
+    ) {
+        let window = match { self.documents.borrow().find_window(pipeline_id) } {
+            None => return warn!("Position sent to closed pipeline {}.", pipeline_id),
+            Some(window) => window,
+        };
+        // Nobody is interested in the position if the geolocation object was never used.
+        if let Some(geolocation) = window.Navigator().geolocation() {
+            geolocation.update_position(position);
+        }
+    }
+
     fn handle_paint_metric(
         &self,
         pipeline_id: PipelineId,
@@ -3483,6 +3673,37 @@ impl ScriptThread {
         }
     }
 
+    fn handle_largest_contentful_paint(
+        &self,
+        pipeline_id: PipelineId,
+        render_time: u64,
+        size: u64,
+        node_address: UntrustedNodeAddress,
+    ) {
+        let document = match self.documents.borrow().find_document(pipeline_id) {
+            Some(document) => document,
+            None => return,
+        };
+        let js_runtime = self.js_runtime.rt();
+        let node = unsafe { from_untrusted_node_address(js_runtime, node_address) };
+        // Text is reported for its parent element.
+        let element = match node.downcast::<Element>() {
+            Some(element) => Some(DomRoot::from_ref(element)),
+            None => node.GetParentElement(),
+        };
+        document
+            .window()
+            .Performance()
+            .queue_largest_contentful_paint(render_time, size, element.as_ref().map(|e| &**e));
+    }
+
+    fn handle_layout_shift(&self, pipeline_id: PipelineId, value: f64, time: u64) {
+        let window = self.documents.borrow().find_window(pipeline_id);
+        if let Some(window) = window {
+            window.Performance().queue_layout_shift(value, time);
+        }
+    }
+
     pub fn enqueue_microtask(job: Microtask) {
         SCRIPT_THREAD_ROOT.with(|root| {
             let script_thread = unsafe { &*root.get().unwrap() };
@@ -2,10 +2,12 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-//! The service worker manager persists the descriptor of any registered service workers.
-//! It also stores an active workers map, which holds descriptors of running service workers.
-//! If an active service worker timeouts, then it removes the descriptor entry from its
-//! active_workers map
+//! The service worker manager keeps the registrations of all the service workers, and runs
+//! their lifecycle: it fetches their scripts, starts them, installs and activates them and
+//! tracks which clients they control.
+//! It also stores a running workers map, which holds the senders of running service workers.
+//! If a running service worker timeouts, then it removes the entry from its
+//! running_workers map, and the worker is started again the next time it is needed.
 
 use crate::dom::abstractworker::WorkerScriptMsg;
 use crate::dom::bindings::structuredclone::StructuredCloneData;
@@ -13,31 +15,95 @@ use crate::dom::serviceworkerglobalscope::{ServiceWorkerGlobalScope, ServiceWork
 use crate::dom::serviceworkerregistration::longest_prefix_match;
 use crossbeam_channel::{unbounded, Receiver, RecvError, Sender};
 use devtools_traits::{DevtoolsPageInfo, ScriptToDevtoolsControlMsg};
+use embedder_traits::{EmbedderMsg, Notification, NotificationEvent};
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
-use net_traits::{CoreResourceMsg, CustomResponseMediator};
+use msg::constellation_msg::PipelineId;
+use net_traits::request::{CredentialsMode, Destination, RequestInit};
+use net_traits::{CoreResourceMsg, CustomResponseMediator, FetchChannels, IpcSend};
+use net_traits::{FetchMetadata, FetchResponseMsg};
+use script_traits::WorkerScriptLoadOrigin;
 use script_traits::{DOMMessage, SWManagerMsg, SWManagerSenders, ScopeThings, ServiceWorkerMsg};
-use servo_config::prefs::PREFS;
+use script_traits::{ServiceWorkerId, ServiceWorkerLifecycleState, ServiceWorkerUpdateResult};
+use servo_config::prefs::{PrefValue, PREFS};
 use servo_url::ServoUrl;
 use std::collections::HashMap;
+use std::mem;
 use std::thread;
+use std::time::{Duration, Instant};
 
 enum Message {
     FromResource(CustomResponseMediator),
     FromConstellation(ServiceWorkerMsg),
+    FromFetch(ScriptFetch),
+}
+
+/// A fetch of the script of a registration started by the update algorithm, along with
+/// its result once it is done.
+struct ScriptFetch {
+    scope: ServoUrl,
+    scope_things: ScopeThings,
+    /// The pipeline whose register or update job waits for the result, if any.
+    job_pipeline: Option<PipelineId>,
+    /// The final url of the script and its source.
+    result: Result<(ServoUrl, String), ()>,
+}
+
+/// A service worker of a registration, along with what is needed to start it.
+#[derive(Clone)]
+struct ServiceWorkerRecord {
+    id: ServiceWorkerId,
+    scope_things: ScopeThings,
+    /// The final url of the script, after redirects
+    script_url: ServoUrl,
+    source: String,
+    /// <https://w3c.github.io/ServiceWorker/#dfn-skip-waiting-flag>
+    skip_waiting: bool,
+}
+
+/// <https://w3c.github.io/ServiceWorker/#dfn-service-worker-registration>
+#[derive(Default)]
+struct Registration {
+    installing: Option<ServiceWorkerRecord>,
+    waiting: Option<ServiceWorkerRecord>,
+    active: Option<ServiceWorkerRecord>,
+    /// <https://w3c.github.io/ServiceWorker/#dfn-last-update-check-time>
+    last_update_check: Option<Instant>,
+}
+
+impl Registration {
+    /// <https://w3c.github.io/ServiceWorker/#get-newest-worker>
+    fn newest_worker(&self) -> Option<&ServiceWorkerRecord> {
+        self.installing
+            .as_ref()
+            .or(self.waiting.as_ref())
+            .or(self.active.as_ref())
+    }
 }
 
 pub struct ServiceWorkerManager {
-    // map of registered service worker descriptors
-    registered_workers: HashMap<ServoUrl, ScopeThings>,
-    // map of active service worker descriptors
-    active_workers: HashMap<ServoUrl, Sender<ServiceWorkerScriptMsg>>,
+    // map of registrations, by scope
+    registrations: HashMap<ServoUrl, Registration>,
+    // map of running service workers
+    running_workers: HashMap<ServiceWorkerId, Sender<ServiceWorkerScriptMsg>>,
+    // the scope of the registration controlling a pipeline
+    controlled_clients: HashMap<PipelineId, ServoUrl>,
+    // the url of the documents navigated to, for `clients.claim()`
+    clients: HashMap<PipelineId, ServoUrl>,
+    next_worker_id: u64,
     // own sender to send messages here
     own_sender: IpcSender<ServiceWorkerMsg>,
     // receiver to receive messages from constellation
     own_port: Receiver<ServiceWorkerMsg>,
     // to receive resource messages
     resource_receiver: Receiver<CustomResponseMediator>,
+    // to send messages to the constellation
+    constellation_sender: IpcSender<SWManagerMsg>,
+    // to receive the scripts fetched by the update algorithm
+    fetch_sender: Sender<ScriptFetch>,
+    fetch_receiver: Receiver<ScriptFetch>,
+    // the network state last reported to the running workers
+    offline: bool,
 }
 
 impl ServiceWorkerManager {
@@ -45,13 +111,22 @@ impl ServiceWorkerManager {
         own_sender: IpcSender<ServiceWorkerMsg>,
         from_constellation_receiver: Receiver<ServiceWorkerMsg>,
         resource_port: Receiver<CustomResponseMediator>,
+        constellation_sender: IpcSender<SWManagerMsg>,
     ) -> ServiceWorkerManager {
+        let (fetch_sender, fetch_receiver) = unbounded();
         ServiceWorkerManager {
-            registered_workers: HashMap::new(),
-            active_workers: HashMap::new(),
+            registrations: HashMap::new(),
+            running_workers: HashMap::new(),
+            controlled_clients: HashMap::new(),
+            clients: HashMap::new(),
+            next_worker_id: 0,
             own_sender: own_sender,
             own_port: from_constellation_receiver,
             resource_receiver: resource_port,
+            constellation_sender: constellation_sender,
+            fetch_sender: fetch_sender,
+            fetch_receiver: fetch_receiver,
+            offline: PREFS.is_network_offline(),
         }
     }
 
@@ -67,59 +142,419 @@ impl ServiceWorkerManager {
         let _ = sw_senders
             .swmanager_sender
             .send(SWManagerMsg::OwnSender(own_sender.clone()));
+        let constellation_sender = sw_senders.swmanager_sender;
         thread::Builder::new()
             .name("ServiceWorkerManager".to_owned())
             .spawn(move || {
-                ServiceWorkerManager::new(own_sender, from_constellation, resource_port)
-                    .handle_message();
+                ServiceWorkerManager::new(
+                    own_sender,
+                    from_constellation,
+                    resource_port,
+                    constellation_sender,
+                )
+                .handle_message();
             })
             .expect("Thread spawning failed");
     }
 
-    pub fn get_matching_scope(&self, load_url: &ServoUrl) -> Option<ServoUrl> {
-        for scope in self.registered_workers.keys() {
-            if longest_prefix_match(&scope, load_url) {
-                return Some(scope.clone());
+    /// <https://w3c.github.io/ServiceWorker/#scope-match-algorithm>
+    fn get_matching_scope(&self, load_url: &ServoUrl) -> Option<ServoUrl> {
+        self.registrations
+            .iter()
+            .filter(|&(scope, registration)| {
+                registration.active.is_some() && longest_prefix_match(scope, load_url)
+            })
+            .map(|(scope, _)| scope)
+            .max_by_key(|scope| scope.as_str().len())
+            .cloned()
+    }
+
+    /// Fetches the script of the registration of `scope`, without blocking the manager:
+    /// the result comes back as a `Message::FromFetch`.
+    fn fetch_script(
+        &self,
+        scope: ServoUrl,
+        scope_things: ScopeThings,
+        job_pipeline: Option<PipelineId>,
+    ) {
+        let request = {
+            let WorkerScriptLoadOrigin {
+                ref referrer_url,
+                ref referrer_policy,
+                pipeline_id,
+            } = scope_things.worker_load_origin;
+            RequestInit {
+                url: scope_things.script_url.clone(),
+                destination: Destination::ServiceWorker,
+                credentials_mode: CredentialsMode::Include,
+                use_url_credentials: true,
+                pipeline_id: pipeline_id,
+                referrer_url: referrer_url.clone(),
+                referrer_policy: referrer_policy.clone(),
+                origin: scope_things.init.origin.clone(),
+                ..RequestInit::default()
+            }
+        };
+        let core_resource_thread = scope_things.init.resource_threads.sender();
+        let mut fetch = Some(ScriptFetch {
+            scope: scope,
+            scope_things: scope_things,
+            job_pipeline: job_pipeline,
+            result: Err(()),
+        });
+        let (action_sender, action_receiver) = ipc::channel().unwrap();
+        let fetch_sender = self.fetch_sender.clone();
+        let mut metadata = None;
+        let mut bytes = vec![];
+        ROUTER.add_route(
+            action_receiver.to_opaque(),
+            Box::new(move |message| {
+                let result = match message.to() {
+                    Ok(FetchResponseMsg::ProcessRequestBody) |
+                    Ok(FetchResponseMsg::ProcessRequestEOF) => return,
+                    Ok(FetchResponseMsg::ProcessResponse(Ok(fetch_metadata))) => {
+                        metadata = Some(match fetch_metadata {
+                            FetchMetadata::Unfiltered(m) => m,
+                            FetchMetadata::Filtered { unsafe_, .. } => unsafe_,
+                        });
+                        return;
+                    },
+                    Ok(FetchResponseMsg::ProcessResponseChunk(chunk)) => {
+                        bytes.extend_from_slice(&chunk);
+                        return;
+                    },
+                    Ok(FetchResponseMsg::ProcessResponseEOF(Ok(_))) => {
+                        let bytes = mem::replace(&mut bytes, vec![]);
+                        match (metadata.take(), String::from_utf8(bytes)) {
+                            (Some(metadata), Ok(source)) => Ok((metadata.final_url, source)),
+                            _ => Err(()),
+                        }
+                    },
+                    Ok(FetchResponseMsg::ProcessResponse(Err(_))) |
+                    Ok(FetchResponseMsg::ProcessResponseEOF(Err(_))) |
+                    Err(_) => Err(()),
+                };
+                if let Some(mut fetch) = fetch.take() {
+                    fetch.result = result;
+                    let _ = fetch_sender.send(fetch);
+                }
+            }),
+        );
+        let _ = core_resource_thread.send(CoreResourceMsg::Fetch(
+            request,
+            FetchChannels::ResponseMsg(action_sender, None),
+        ));
+    }
+
+    /// Runs the update algorithm, `job_pipeline` being the pipeline whose register or
+    /// update job waits for its result, if any.
+    // https://w3c.github.io/ServiceWorker/#update-algorithm
+    fn update(
+        &mut self,
+        scope: ServoUrl,
+        scope_things: ScopeThings,
+        job_pipeline: Option<PipelineId>,
+    ) {
+        self.fetch_script(scope, scope_things, job_pipeline);
+    }
+
+    /// The rest of the update algorithm, once the script is fetched.
+    // https://w3c.github.io/ServiceWorker/#update-algorithm
+    fn handle_script_fetched(&mut self, fetch: ScriptFetch) {
+        let ScriptFetch {
+            scope,
+            scope_things,
+            job_pipeline,
+            result,
+        } = fetch;
+        let result = match result {
+            Err(()) => {
+                warn!(
+                    "Unable to fetch service worker script {}",
+                    scope_things.script_url
+                );
+                ServiceWorkerUpdateResult::Failed(format!(
+                    "Failed to fetch {}",
+                    scope_things.script_url
+                ))
+            },
+            Ok((script_url, source)) => {
+                let registration = self
+                    .registrations
+                    .entry(scope.clone())
+                    .or_insert_with(Default::default);
+                registration.last_update_check = Some(Instant::now());
+                let up_to_date = registration.newest_worker().map_or(false, |newest| {
+                    newest.script_url == script_url && newest.source == source
+                });
+                if up_to_date {
+                    ServiceWorkerUpdateResult::UpToDate
+                } else {
+                    let id = ServiceWorkerId(self.next_worker_id);
+                    self.next_worker_id += 1;
+                    self.install(
+                        &scope,
+                        ServiceWorkerRecord {
+                            id: id,
+                            scope_things: scope_things,
+                            script_url: script_url.clone(),
+                            source: source,
+                            skip_waiting: false,
+                        },
+                    );
+                    ServiceWorkerUpdateResult::Installing(id, script_url)
+                }
+            },
+        };
+        if let Some(pipeline_id) = job_pipeline {
+            let _ = self.constellation_sender.send(SWManagerMsg::UpdateFinished(
+                pipeline_id,
+                scope,
+                result,
+            ));
+        }
+    }
+
+    // https://w3c.github.io/ServiceWorker/#installation-algorithm
+    fn install(&mut self, scope: &ServoUrl, worker: ServiceWorkerRecord) {
+        let id = worker.id;
+        let previous = self
+            .registrations
+            .get_mut(scope)
+            .and_then(|registration| registration.installing.replace(worker.clone()));
+        if let Some(previous) = previous {
+            self.make_redundant(scope, &previous);
+        }
+        self.notify_state(scope, &worker, ServiceWorkerLifecycleState::Installing);
+        let sender = self.start_worker(scope, &worker);
+        let _ = sender.send(ServiceWorkerScriptMsg::Install);
+        debug!("Installing service worker {:?} for {}", id, scope);
+    }
+
+    fn handle_install_finished(&mut self, id: ServiceWorkerId, succeeded: bool) {
+        let scope = match self.scope_of_installing(id) {
+            Some(scope) => scope,
+            None => return,
+        };
+        let worker = match self.registrations.get_mut(&scope) {
+            Some(registration) => registration.installing.take(),
+            None => return,
+        };
+        let worker = match worker {
+            Some(worker) => worker,
+            None => return,
+        };
+        if !succeeded {
+            self.make_redundant(&scope, &worker);
+            return;
+        }
+        let previous = self
+            .registrations
+            .get_mut(&scope)
+            .and_then(|registration| registration.waiting.replace(worker.clone()));
+        if let Some(previous) = previous {
+            self.make_redundant(&scope, &previous);
+        }
+        self.notify_state(&scope, &worker, ServiceWorkerLifecycleState::Installed);
+        self.try_activate(&scope);
+    }
+
+    // https://w3c.github.io/ServiceWorker/#service-worker-global-scope-skipwaiting
+    fn handle_skip_waiting(&mut self, id: ServiceWorkerId) {
+        let scope = match self.scope_of_worker(id) {
+            Some(scope) => scope,
+            None => return,
+        };
+        let mut waiting = false;
+        if let Some(registration) = self.registrations.get_mut(&scope) {
+            if let Some(ref mut worker) = registration.installing {
+                if worker.id == id {
+                    worker.skip_waiting = true;
+                }
+            }
+            if let Some(ref mut worker) = registration.waiting {
+                if worker.id == id {
+                    worker.skip_waiting = true;
+                    waiting = true;
+                }
             }
         }
-        None
+        if waiting {
+            self.try_activate(&scope);
+        }
+    }
+
+    fn scope_of_installing(&self, id: ServiceWorkerId) -> Option<ServoUrl> {
+        self.registrations
+            .iter()
+            .find(|&(_, registration)| {
+                registration
+                    .installing
+                    .as_ref()
+                    .map_or(false, |worker| worker.id == id)
+            })
+            .map(|(scope, _)| scope.clone())
+    }
+
+    fn scope_of_worker(&self, id: ServiceWorkerId) -> Option<ServoUrl> {
+        self.registrations
+            .iter()
+            .find(|&(_, registration)| {
+                [
+                    &registration.installing,
+                    &registration.waiting,
+                    &registration.active,
+                ]
+                .iter()
+                .any(|worker| worker.as_ref().map_or(false, |worker| worker.id == id))
+            })
+            .map(|(scope, _)| scope.clone())
+    }
+
+    // https://w3c.github.io/ServiceWorker/#try-activate-algorithm
+    fn try_activate(&mut self, scope: &ServoUrl) {
+        let should_activate = match self.registrations.get(scope) {
+            Some(registration) => match registration.waiting {
+                Some(ref waiting) => {
+                    registration.active.is_none() ||
+                        waiting.skip_waiting ||
+                        !self.controlled_clients.values().any(|s| s == scope)
+                },
+                None => false,
+            },
+            None => false,
+        };
+        if should_activate {
+            self.activate(scope);
+        }
+    }
+
+    // https://w3c.github.io/ServiceWorker/#activation-algorithm
+    fn activate(&mut self, scope: &ServoUrl) {
+        let (worker, previous) = match self.registrations.get_mut(scope) {
+            Some(registration) => match registration.waiting.take() {
+                Some(worker) => {
+                    let previous = registration.active.replace(worker.clone());
+                    (worker, previous)
+                },
+                None => return,
+            },
+            None => return,
+        };
+        if let Some(previous) = previous {
+            self.make_redundant(scope, &previous);
+        }
+        self.notify_state(scope, &worker, ServiceWorkerLifecycleState::Activating);
+        let controlled: Vec<PipelineId> = self
+            .controlled_clients
+            .iter()
+            .filter(|&(_, s)| s == scope)
+            .map(|(pipeline_id, _)| *pipeline_id)
+            .collect();
+        for pipeline_id in controlled {
+            let _ = self
+                .constellation_sender
+                .send(SWManagerMsg::SetController(pipeline_id, scope.clone()));
+        }
+        let sender = self.worker_sender(scope, &worker);
+        let _ = sender.send(ServiceWorkerScriptMsg::Activate);
+    }
+
+    fn handle_activate_finished(&mut self, id: ServiceWorkerId) {
+        let scope = match self.scope_of_worker(id) {
+            Some(scope) => scope,
+            None => return,
+        };
+        let worker = match self.registrations.get(&scope) {
+            Some(registration) => match registration.active {
+                Some(ref active) if active.id == id => active.clone(),
+                _ => return,
+            },
+            None => return,
+        };
+        self.notify_state(&scope, &worker, ServiceWorkerLifecycleState::Activated);
+    }
+
+    fn make_redundant(&mut self, scope: &ServoUrl, worker: &ServiceWorkerRecord) {
+        self.running_workers.remove(&worker.id);
+        self.notify_state(scope, worker, ServiceWorkerLifecycleState::Redundant);
+    }
+
+    fn notify_state(
+        &self,
+        scope: &ServoUrl,
+        worker: &ServiceWorkerRecord,
+        state: ServiceWorkerLifecycleState,
+    ) {
+        let _ = self.constellation_sender.send(SWManagerMsg::StateChanged(
+            scope.clone(),
+            worker.id,
+            worker.script_url.clone(),
+            state,
+        ));
+    }
+
+    /// The sender of the given worker, starting it if it is not running.
+    fn worker_sender(
+        &mut self,
+        scope: &ServoUrl,
+        worker: &ServiceWorkerRecord,
+    ) -> Sender<ServiceWorkerScriptMsg> {
+        match self.running_workers.get(&worker.id) {
+            Some(sender) => sender.clone(),
+            None => self.start_worker(scope, worker),
+        }
     }
 
-    pub fn wakeup_serviceworker(
+    // https://w3c.github.io/ServiceWorker/#run-service-worker-algorithm
+    fn start_worker(
         &mut self,
-        scope_url: ServoUrl,
-    ) -> Option<Sender<ServiceWorkerScriptMsg>> {
-        let scope_things = self.registered_workers.get(&scope_url);
-        if let Some(scope_things) = scope_things {
-            let (sender, receiver) = unbounded();
-            let (devtools_sender, devtools_receiver) = ipc::channel().unwrap();
-            if let Some(ref chan) = scope_things.devtools_chan {
-                let title = format!("ServiceWorker for {}", scope_things.script_url);
-                let page_info = DevtoolsPageInfo {
-                    title: title,
-                    url: scope_things.script_url.clone(),
-                };
-                let _ = chan.send(ScriptToDevtoolsControlMsg::NewGlobal(
-                    (scope_things.init.pipeline_id, Some(scope_things.worker_id)),
-                    devtools_sender,
-                    page_info,
-                ));
+        scope: &ServoUrl,
+        worker: &ServiceWorkerRecord,
+    ) -> Sender<ServiceWorkerScriptMsg> {
+        let scope_things = worker.scope_things.clone();
+        let active = self
+            .registrations
+            .get(scope)
+            .and_then(|registration| registration.active.as_ref())
+            .map_or(false, |active| active.id == worker.id);
+        let (sender, receiver) = unbounded();
+        let (devtools_sender, devtools_receiver) = ipc::channel().unwrap();
+        if let Some(ref chan) = scope_things.devtools_chan {
+            let title = format!("ServiceWorker for {}", worker.script_url);
+            let page_info = DevtoolsPageInfo {
+                title: title,
+                url: worker.script_url.clone(),
             };
-            ServiceWorkerGlobalScope::run_serviceworker_scope(
-                scope_things.clone(),
-                sender.clone(),
-                receiver,
-                devtools_receiver,
-                self.own_sender.clone(),
-                scope_url.clone(),
-            );
-            // We store the activated worker
-            self.active_workers.insert(scope_url, sender.clone());
-            return Some(sender);
-        } else {
-            warn!("Unable to activate service worker");
-            None
-        }
+            let _ = chan.send(ScriptToDevtoolsControlMsg::NewGlobal(
+                (scope_things.init.pipeline_id, Some(scope_things.worker_id)),
+                devtools_sender,
+                page_info,
+            ));
+        };
+        ServiceWorkerGlobalScope::run_serviceworker_scope(
+            scope_things,
+            worker.id,
+            active,
+            worker.script_url.clone(),
+            worker.source.clone(),
+            sender.clone(),
+            receiver,
+            devtools_receiver,
+            self.own_sender.clone(),
+            scope.clone(),
+        );
+        // We store the running worker
+        self.running_workers.insert(worker.id, sender.clone());
+        sender
+    }
+
+    fn active_worker_sender(&mut self, scope: &ServoUrl) -> Option<Sender<ServiceWorkerScriptMsg>> {
+        let worker = self
+            .registrations
+            .get(scope)
+            .and_then(|registration| registration.active.clone())?;
+        Some(self.worker_sender(scope, &worker))
     }
 
     fn handle_message(&mut self) {
@@ -127,6 +562,10 @@ impl ServiceWorkerManager {
             let should_continue = match message {
                 Message::FromConstellation(msg) => self.handle_message_from_constellation(msg),
                 Message::FromResource(msg) => self.handle_message_from_resource(msg),
+                Message::FromFetch(fetch) => {
+                    self.handle_script_fetched(fetch);
+                    true
+                },
             };
             if !should_continue {
                 break;
@@ -145,30 +584,112 @@ impl ServiceWorkerManager {
     fn handle_message_from_constellation(&mut self, msg: ServiceWorkerMsg) -> bool {
         match msg {
             ServiceWorkerMsg::RegisterServiceWorker(scope_things, scope) => {
-                if self.registered_workers.contains_key(&scope) {
-                    warn!("ScopeThings for {:?} already stored in SW-Manager", scope);
-                } else {
-                    self.registered_workers.insert(scope, scope_things);
-                }
+                let job_pipeline = scope_things.init.pipeline_id;
+                self.update(scope, scope_things, Some(job_pipeline));
                 true
             },
-            ServiceWorkerMsg::Timeout(scope) => {
-                if self.active_workers.contains_key(&scope) {
-                    let _ = self.active_workers.remove(&scope);
-                } else {
-                    warn!("ServiceWorker for {:?} is not active", scope);
+            ServiceWorkerMsg::Timeout(id) => {
+                if self.running_workers.remove(&id).is_none() {
+                    warn!("ServiceWorker {:?} is not running", id);
                 }
                 true
             },
             ServiceWorkerMsg::ForwardDOMMessage(msg, scope_url) => {
-                if self.active_workers.contains_key(&scope_url) {
-                    if let Some(ref sender) = self.active_workers.get(&scope_url) {
-                        self.forward_message(msg, &sender);
-                    }
-                } else {
-                    if let Some(ref sender) = self.wakeup_serviceworker(scope_url) {
-                        self.forward_message(msg, &sender);
-                    }
+                match self.active_worker_sender(&scope_url) {
+                    Some(sender) => self.forward_message(msg, &sender),
+                    None => warn!("No active service worker for {}", scope_url),
+                }
+                true
+            },
+            ServiceWorkerMsg::InstallFinished(id, succeeded) => {
+                self.handle_install_finished(id, succeeded);
+                true
+            },
+            ServiceWorkerMsg::ActivateFinished(id) => {
+                self.handle_activate_finished(id);
+                true
+            },
+            ServiceWorkerMsg::SkipWaiting(id, sender) => {
+                self.handle_skip_waiting(id);
+                let _ = sender.send(());
+                true
+            },
+            ServiceWorkerMsg::Claim(id) => {
+                // https://w3c.github.io/ServiceWorker/#clients-claim
+                let scope = match self.scope_of_worker(id) {
+                    Some(scope) => scope,
+                    None => return true,
+                };
+                let is_active = self
+                    .registrations
+                    .get(&scope)
+                    .map_or(false, |registration| {
+                        registration
+                            .active
+                            .as_ref()
+                            .map_or(false, |active| active.id == id)
+                    });
+                if !is_active {
+                    return true;
+                }
+                let claimed: Vec<PipelineId> = self
+                    .clients
+                    .iter()
+                    .filter(|&(_, url)| self.get_matching_scope(url).as_ref() == Some(&scope))
+                    .map(|(pipeline_id, _)| *pipeline_id)
+                    .filter(|pipeline_id| self.controlled_clients.get(pipeline_id) != Some(&scope))
+                    .collect();
+                for pipeline_id in claimed {
+                    self.controlled_clients.insert(pipeline_id, scope.clone());
+                    let _ = self
+                        .constellation_sender
+                        .send(SWManagerMsg::SetController(pipeline_id, scope.clone()));
+                }
+                true
+            },
+            ServiceWorkerMsg::PipelineExited(pipeline_id) => {
+                self.clients.remove(&pipeline_id);
+                if let Some(scope) = self.controlled_clients.remove(&pipeline_id) {
+                    self.try_activate(&scope);
+                }
+                true
+            },
+            ServiceWorkerMsg::SetOffline(offline) => {
+                if self.offline == offline {
+                    return true;
+                }
+                self.offline = offline;
+                // `navigator.onLine` of service workers reads the network state from here.
+                PREFS.set("network.offline", PrefValue::Boolean(offline));
+                for sender in self.running_workers.values() {
+                    let _ = sender.send(ServiceWorkerScriptMsg::NetworkStateChanged(!offline));
+                }
+                true
+            },
+            ServiceWorkerMsg::GetPermission(origin, name, sender) => {
+                let _ = self
+                    .constellation_sender
+                    .send(SWManagerMsg::GetPermission(origin, name, sender));
+                true
+            },
+            ServiceWorkerMsg::ShowNotification(scope, notification) => {
+                self.show_notification(scope, notification);
+                true
+            },
+            ServiceWorkerMsg::NotificationEvent(scope, notification, event) => {
+                // Only activating and closing persistent notifications are reported to
+                // service workers.
+                if event != NotificationEvent::Clicked && event != NotificationEvent::Closed {
+                    return true;
+                }
+                match self.active_worker_sender(&scope) {
+                    Some(sender) => {
+                        let _ = sender.send(ServiceWorkerScriptMsg::NotificationEvent(
+                            notification,
+                            event,
+                        ));
+                    },
+                    None => warn!("No active service worker for {}", scope),
                 }
                 true
             },
@@ -176,31 +697,109 @@ impl ServiceWorkerManager {
         }
     }
 
+    /// Asks the embedder to display a persistent notification, whose events are handled by
+    /// the active worker of the registration for `scope`, starting it if needed.
+    // https://notifications.spec.whatwg.org/#dom-serviceworkerregistration-shownotification
+    fn show_notification(&self, scope: ServoUrl, notification: Notification) {
+        let own_sender = self.own_sender.clone();
+        let data = notification.clone();
+        let (sender, receiver) = ipc::channel().unwrap();
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                if let Ok(event) = message.to() {
+                    let _ = own_sender.send(ServiceWorkerMsg::NotificationEvent(
+                        scope.clone(),
+                        data.clone(),
+                        event,
+                    ));
+                }
+            }),
+        );
+        let _ = self
+            .constellation_sender
+            .send(SWManagerMsg::ForwardToEmbedder(
+                EmbedderMsg::ShowNotification(notification, Some(sender)),
+            ));
+    }
+
+    // https://w3c.github.io/ServiceWorker/#handle-fetch
     fn handle_message_from_resource(&mut self, mediator: CustomResponseMediator) -> bool {
-        if serviceworker_enabled() {
-            if let Some(scope) = self.get_matching_scope(&mediator.load_url) {
-                if self.active_workers.contains_key(&scope) {
-                    if let Some(sender) = self.active_workers.get(&scope) {
-                        let _ = sender.send(ServiceWorkerScriptMsg::Response(mediator));
-                    }
-                } else {
-                    if let Some(sender) = self.wakeup_serviceworker(scope) {
-                        let _ = sender.send(ServiceWorkerScriptMsg::Response(mediator));
-                    }
+        if !serviceworker_enabled() {
+            let _ = mediator.response_chan.send(None);
+            return true;
+        }
+        let scope = if mediator.is_navigation {
+            // Step 12, a navigation is controlled by the registration matching its url
+            let scope = self.get_matching_scope(&mediator.load_url);
+            if let Some(pipeline_id) = mediator.pipeline_id {
+                self.clients.insert(pipeline_id, mediator.load_url.clone());
+                match scope {
+                    Some(ref scope) => {
+                        self.controlled_clients.insert(pipeline_id, scope.clone());
+                    },
+                    None => {
+                        self.controlled_clients.remove(&pipeline_id);
+                    },
                 }
-            } else {
-                let _ = mediator.response_chan.send(None);
             }
+            scope
         } else {
-            let _ = mediator.response_chan.send(None);
+            // Step 13, a subresource fetch is handled by the controller of its client
+            mediator
+                .pipeline_id
+                .and_then(|pipeline_id| self.controlled_clients.get(&pipeline_id).cloned())
+        };
+        let scope = match scope {
+            Some(scope) => scope,
+            None => {
+                let _ = mediator.response_chan.send(None);
+                return true;
+            },
+        };
+        let is_navigation = mediator.is_navigation;
+        match self.active_worker_sender(&scope) {
+            Some(sender) => {
+                let _ = sender.send(ServiceWorkerScriptMsg::Response(mediator));
+            },
+            None => {
+                let _ = mediator.response_chan.send(None);
+            },
+        }
+        // https://w3c.github.io/ServiceWorker/#soft-update
+        if is_navigation {
+            self.soft_update(scope);
         }
         true
     }
 
+    /// Runs the update algorithm for the registration of the given scope, if it was not
+    /// checked for updates for more than 24 hours.
+    // https://w3c.github.io/ServiceWorker/#soft-update
+    fn soft_update(&mut self, scope: ServoUrl) {
+        let scope_things = match self.registrations.get(&scope) {
+            Some(registration) => {
+                let stale = registration
+                    .last_update_check
+                    .map_or(true, |last| last.elapsed() > Duration::from_secs(86400));
+                if !stale {
+                    return;
+                }
+                match registration.newest_worker() {
+                    Some(newest) => newest.scope_things.clone(),
+                    None => return,
+                }
+            },
+            None => return,
+        };
+        self.update(scope, scope_things, None);
+    }
+
     fn receive_message(&mut self) -> Result<Message, RecvError> {
         select! {
             recv(self.own_port) -> msg => msg.map(Message::FromConstellation),
             recv(self.resource_receiver) -> msg => msg.map(Message::FromResource),
+            recv(self.fetch_receiver) -> msg => msg.map(Message::FromFetch),
         }
     }
 }
@@ -6,6 +6,8 @@
 //! Each Job is uniquely identified by its scope_url, and is keyed accordingly under
 //! the script thread. The script thread contains a JobQueue, which stores all scheduled Jobs
 //! by multiple service worker clients in a Vec.
+//! Register and update jobs finish once the service worker manager ran the update
+//! algorithm, see `handle_update_finished`.
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::error::Error;
@@ -19,6 +21,7 @@ use crate::dom::urlhelper::UrlHelper;
 use crate::script_thread::ScriptThread;
 use crate::task_source::dom_manipulation::DOMManipulationTaskSource;
 use crate::task_source::TaskSource;
+use script_traits::{ScriptMsg, ServiceWorkerUpdateResult};
 use servo_url::ServoUrl;
 use std::cmp::PartialEq;
 use std::collections::HashMap;
@@ -134,26 +137,29 @@ impl JobQueue {
     // https://w3c.github.io/ServiceWorker/#run-job-algorithm
     pub fn run_job(&self, scope_url: ServoUrl, script_thread: &ScriptThread) {
         debug!("running a job");
-        let url = {
+        let (url, finished) = {
             let queue_ref = self.0.borrow();
             let front_job = {
                 let job_vec = queue_ref.get(&scope_url);
                 job_vec.unwrap().first().unwrap()
             };
             let front_scope_url = front_job.scope_url.clone();
-            match front_job.job_type {
+            let finished = match front_job.job_type {
                 JobType::Register => self.run_register(front_job, scope_url, script_thread),
                 JobType::Update => self.update(front_job, script_thread),
                 JobType::Unregister => unreachable!(),
             };
-            front_scope_url
+            (front_scope_url, finished)
         };
-        self.finish_job(url, script_thread);
+        // Jobs waiting for the service worker manager are finished by handle_update_finished
+        if finished {
+            self.finish_job(url, script_thread);
+        }
     }
 
     #[allow(unrooted_must_root)]
     // https://w3c.github.io/ServiceWorker/#register-algorithm
-    fn run_register(&self, job: &Job, scope_url: ServoUrl, script_thread: &ScriptThread) {
+    fn run_register(&self, job: &Job, scope_url: ServoUrl, script_thread: &ScriptThread) -> bool {
         debug!("running register job");
         let global = &*job.client.global();
         let pipeline_id = global.pipeline_id();
@@ -166,7 +172,7 @@ impl JobQueue {
                 &script_thread.dom_manipulation_task_source(pipeline_id),
             );
             // Step 1.2 (see run_job)
-            return;
+            return true;
         } else if job.script_url.origin() != job.referrer.origin() ||
             job.scope_url.origin() != job.referrer.origin()
         {
@@ -177,7 +183,7 @@ impl JobQueue {
                 &script_thread.dom_manipulation_task_source(pipeline_id),
             );
             // Step 2.2/3.2 (see run_job)
-            return;
+            return true;
         }
 
         // Step 4-5
@@ -196,13 +202,13 @@ impl JobQueue {
                         &script_thread.dom_manipulation_task_source(pipeline_id),
                     );
                     // Step 5.3.2 (see run_job)
-                    return;
+                    return true;
                 }
             }
         } else {
             // Step 6.1
-            let new_reg = ServiceWorkerRegistration::new(&*global, &job.script_url, scope_url);
-            script_thread.handle_serviceworker_registration(&job.scope_url, &*new_reg, pipeline_id);
+            let new_reg = ServiceWorkerRegistration::new(&*global, scope_url);
+            script_thread.handle_serviceworker_registration(&job.scope_url, &*new_reg);
         }
         // Step 7
         self.update(job, script_thread)
@@ -228,7 +234,7 @@ impl JobQueue {
     }
 
     // https://w3c.github.io/ServiceWorker/#update-algorithm
-    fn update(&self, job: &Job, script_thread: &ScriptThread) {
+    fn update(&self, job: &Job, script_thread: &ScriptThread) -> bool {
         debug!("running update job");
 
         let global = &*job.client.global();
@@ -245,7 +251,7 @@ impl JobQueue {
                     &script_thread.dom_manipulation_task_source(pipeline_id),
                 );
                 // Step 2.2 (see run_job)
-                return;
+                return true;
             },
         };
         // Step 2
@@ -258,13 +264,13 @@ impl JobQueue {
                 &script_thread.dom_manipulation_task_source(pipeline_id),
             );
             // Step 2.2 (see run_job)
-            return;
+            return true;
         }
         // Step 3
         let newest_worker = reg.get_newest_worker();
         let newest_worker_url = newest_worker.as_ref().map(|w| w.get_script_url());
         // Step 4
-        if newest_worker_url.as_ref() == Some(&job.script_url) && job.job_type == JobType::Update {
+        if job.job_type == JobType::Update && newest_worker_url.as_ref() != Some(&job.script_url) {
             let err_type = Error::Type("Invalid script ServoURL".to_owned());
             // Step 4.1
             reject_job_promise(
@@ -273,20 +279,61 @@ impl JobQueue {
                 &script_thread.dom_manipulation_task_source(pipeline_id),
             );
             // Step 4.2 (see run_job)
-            return;
+            return true;
         }
-        // Step 8
-        if let Some(newest_worker) = newest_worker {
-            job.client.set_controller(&*newest_worker);
-            // Step 8.1
-            resolve_job_promise(
-                job,
-                &*reg,
-                &script_thread.dom_manipulation_task_source(pipeline_id),
-            );
-            // Step 8.2 present in run_job
+        // Step 5-9 are run by the service worker manager, which fetches the script and
+        // installs a new worker if it changed
+        let scope_things =
+            ServiceWorkerRegistration::create_scope_things(global, job.script_url.clone());
+        let _ = global
+            .script_to_constellation_chan()
+            .send(ScriptMsg::RegisterServiceWorker(
+                scope_things,
+                job.scope_url.clone(),
+            ));
+        false
+    }
+
+    #[allow(unrooted_must_root)]
+    /// Settles the front job of the given scope once the service worker manager ran the
+    /// update algorithm for it.
+    // https://w3c.github.io/ServiceWorker/#update-algorithm Step 8-9
+    pub fn handle_update_finished(
+        &self,
+        scope_url: ServoUrl,
+        result: ServiceWorkerUpdateResult,
+        script_thread: &ScriptThread,
+    ) {
+        {
+            let queue_ref = self.0.borrow();
+            let job = match queue_ref.get(&scope_url).and_then(|jobs| jobs.first()) {
+                Some(job) => job,
+                None => return warn!("No job waiting for the update of {}", scope_url),
+            };
+            let pipeline_id = job.client.global().pipeline_id();
+            let task_source = script_thread.dom_manipulation_task_source(pipeline_id);
+            let reg = script_thread.handle_get_registration(&scope_url);
+            match (result, reg) {
+                (ServiceWorkerUpdateResult::Failed(message), _) => {
+                    // Step 8.1
+                    reject_job_promise(job, Error::Type(message), &task_source);
+                },
+                (_, None) => {
+                    let message = "No registration to update".to_owned();
+                    reject_job_promise(job, Error::Type(message), &task_source);
+                },
+                (ServiceWorkerUpdateResult::UpToDate, Some(reg)) => {
+                    // Step 9.4.1
+                    resolve_job_promise(job, &*reg, &task_source);
+                },
+                (ServiceWorkerUpdateResult::Installing(..), Some(reg)) => {
+                    // https://w3c.github.io/ServiceWorker/#installation-algorithm Step 4,
+                    // the new worker was put in the installing slot by its state change
+                    resolve_job_promise(job, &*reg, &task_source);
+                },
+            }
         }
-        // TODO Step 9 (create new service worker)
+        self.finish_job(scope_url, script_thread);
     }
 }
 
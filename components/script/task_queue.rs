@@ -125,7 +125,7 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
                     None => return false,
                 };
                 match task_source {
-                    TaskSourceName::PerformanceTimeline => return true,
+                    TaskSourceName::PerformanceTimeline | TaskSourceName::Idle => return true,
                     _ => {
                         // A task that will not be throttled, start counting "business"
                         self.taken_task_counter
@@ -183,6 +183,17 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
         self.recv()
     }
 
+    /// Whether there are no tasks left to be handled by the event-loop,
+    /// including throttled ones and those yet to be received on the port.
+    pub fn is_empty(&self) -> bool {
+        self.msg_queue.borrow().is_empty() &&
+            self.throttled
+                .borrow()
+                .values()
+                .all(|queue| queue.is_empty()) &&
+            self.port.is_empty()
+    }
+
     /// Drain the queue for the current iteration of the event-loop.
     /// Holding-back throttles above a given high-water mark.
     pub fn take_tasks(&self, first_msg: T) {
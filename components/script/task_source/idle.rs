@@ -0,0 +1,58 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::window::Window;
+use crate::script_runtime::{CommonScriptMsg, ScriptChan, ScriptThreadEventCategory};
+use crate::task::{TaskCanceller, TaskOnce};
+use crate::task_source::{TaskSource, TaskSourceName};
+use msg::constellation_msg::PipelineId;
+use std::fmt;
+use std::result::Result;
+
+/// <https://w3c.github.io/requestidlecallback/#dfn-idle-task-source>
+#[derive(JSTraceable)]
+pub struct IdleTaskSource(pub Box<dyn ScriptChan + Send + 'static>, pub PipelineId);
+
+impl Clone for IdleTaskSource {
+    fn clone(&self) -> IdleTaskSource {
+        IdleTaskSource(self.0.clone(), self.1.clone())
+    }
+}
+
+impl fmt::Debug for IdleTaskSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "IdleTaskSource(...)")
+    }
+}
+
+impl TaskSource for IdleTaskSource {
+    const NAME: TaskSourceName = TaskSourceName::Idle;
+
+    fn queue_with_canceller<T>(&self, task: T, canceller: &TaskCanceller) -> Result<(), ()>
+    where
+        T: TaskOnce + 'static,
+    {
+        let msg = CommonScriptMsg::Task(
+            ScriptThreadEventCategory::ScriptEvent,
+            Box::new(canceller.wrap_task(task)),
+            Some(self.1),
+            IdleTaskSource::NAME,
+        );
+        self.0.send(msg).map_err(|_| ())
+    }
+}
+
+impl IdleTaskSource {
+    /// <https://w3c.github.io/requestidlecallback/#start-an-idle-period-algorithm> step 5
+    pub fn queue_invoke_idle_callbacks(&self, window: &Window, deadline: u64) -> Result<(), ()> {
+        let window_ = Trusted::new(window);
+        self.queue(
+            task!(invoke_idle_callbacks: move || {
+                window_.root().invoke_idle_callbacks(deadline);
+            }),
+            window.upcast(),
+        )
+    }
+}
@@ -5,6 +5,7 @@
 pub mod dom_manipulation;
 pub mod file_reading;
 pub mod history_traversal;
+pub mod idle;
 pub mod media_element;
 pub mod networking;
 pub mod performance_timeline;
@@ -26,6 +27,7 @@ pub enum TaskSourceName {
     DOMManipulation,
     FileReading,
     HistoryTraversal,
+    Idle,
     Networking,
     PerformanceTimeline,
     UserInteraction,
@@ -15,6 +15,10 @@ pub mod area {
     pub use crate::dom::htmlareaelement::{Area, Shape};
 }
 
+pub mod image_encoder {
+    pub use crate::image_encoder::{encode, ImageType};
+}
+
 pub mod size_of {
     use crate::dom::characterdata::CharacterData;
     use crate::dom::element::Element;
@@ -66,3 +70,10 @@ pub mod srcset {
 pub mod timeranges {
     pub use crate::dom::timeranges::TimeRangesContainer;
 }
+
+pub mod webvtt {
+    pub use crate::webvtt::{
+        cue_text_content, parse, parse_cue_text, CueNode, CueSettings, LineAlignment,
+        PositionAlignment, TextAlignment, WritingDirection,
+    };
+}
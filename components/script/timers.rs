@@ -9,9 +9,12 @@ use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::FakeRequestAnimationFrameCallback;
 use crate::dom::eventsource::EventSourceTimeoutCallback;
+use crate::dom::geolocation::GeolocationTimeoutCallback;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::testbinding::TestBindingCallback;
+use crate::dom::window::IdleRequestTimeoutCallback;
 use crate::dom::xmlhttprequest::XHRTimeoutCallback;
+use crate::dom::xrsession::XRSimulatedFrameCallback;
 use euclid::Length;
 use ipc_channel::ipc::IpcSender;
 use js::jsapi::Heap;
@@ -72,6 +75,9 @@ pub enum OneshotTimerCallback {
     JsTimer(JsTimerTask),
     TestBindingCallback(TestBindingCallback),
     FakeRequestAnimationFrame(FakeRequestAnimationFrameCallback),
+    IdleRequestTimeout(IdleRequestTimeoutCallback),
+    GeolocationTimeout(GeolocationTimeoutCallback),
+    XRSimulatedFrame(XRSimulatedFrameCallback),
 }
 
 impl OneshotTimerCallback {
@@ -82,6 +88,9 @@ impl OneshotTimerCallback {
             OneshotTimerCallback::JsTimer(task) => task.invoke(this, js_timers),
             OneshotTimerCallback::TestBindingCallback(callback) => callback.invoke(),
             OneshotTimerCallback::FakeRequestAnimationFrame(callback) => callback.invoke(),
+            OneshotTimerCallback::IdleRequestTimeout(callback) => callback.invoke(),
+            OneshotTimerCallback::GeolocationTimeout(callback) => callback.invoke(),
+            OneshotTimerCallback::XRSimulatedFrame(callback) => callback.invoke(),
         }
     }
 }
@@ -217,6 +226,18 @@ impl OneshotTimers {
         self.schedule_timer_call();
     }
 
+    /// The time at which the next scheduled timer is due, in the same time base as
+    /// `precise_time_ms`, or `None` if there are no pending timers.
+    pub fn next_timer_deadline(&self) -> Option<MsDuration> {
+        if self.suspended_since.get().is_some() {
+            return None;
+        }
+        self.timers
+            .borrow()
+            .last()
+            .map(|timer| timer.scheduled_for + self.suspension_offset.get())
+    }
+
     fn base_time(&self) -> MsDuration {
         let offset = self.suspension_offset.get();
 
@@ -0,0 +1,436 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A parser for WebVTT files, as loaded by `<track>` elements.
+//!
+//! <https://w3c.github.io/webvtt/#file-parsing>
+
+/// <https://w3c.github.io/webvtt/#webvtt-cue-writing-direction>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum WritingDirection {
+    Horizontal,
+    VerticalGrowingLeft,
+    VerticalGrowingRight,
+}
+
+/// <https://w3c.github.io/webvtt/#webvtt-cue-line-alignment>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum LineAlignment {
+    Start,
+    Center,
+    End,
+}
+
+/// <https://w3c.github.io/webvtt/#webvtt-cue-position-alignment>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum PositionAlignment {
+    LineLeft,
+    Center,
+    LineRight,
+    Auto,
+}
+
+/// <https://w3c.github.io/webvtt/#webvtt-cue-text-alignment>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum TextAlignment {
+    Start,
+    Center,
+    End,
+    Left,
+    Right,
+}
+
+/// The settings of a cue, which drive where its box is rendered.
+/// <https://w3c.github.io/webvtt/#webvtt-cue-settings>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub struct CueSettings {
+    pub vertical: WritingDirection,
+    pub snap_to_lines: bool,
+    /// The line position, `None` standing for "auto".
+    pub line: Option<f64>,
+    pub line_align: LineAlignment,
+    /// The position as a percentage, `None` standing for "auto".
+    pub position: Option<f64>,
+    pub position_align: PositionAlignment,
+    /// The size as a percentage of the video.
+    pub size: f64,
+    pub align: TextAlignment,
+}
+
+impl Default for CueSettings {
+    fn default() -> CueSettings {
+        CueSettings {
+            vertical: WritingDirection::Horizontal,
+            snap_to_lines: true,
+            line: None,
+            line_align: LineAlignment::Start,
+            position: None,
+            position_align: PositionAlignment::Auto,
+            size: 100.,
+            align: TextAlignment::Center,
+        }
+    }
+}
+
+/// A cue as found in a WebVTT file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cue {
+    pub id: String,
+    pub start_time: f64,
+    pub end_time: f64,
+    pub settings: CueSettings,
+    /// The raw cue text, which may contain markup.
+    pub text: String,
+}
+
+/// Parses a WebVTT file, returning its cues in file order, or an error if
+/// the file does not start with the WebVTT signature.
+/// <https://w3c.github.io/webvtt/#webvtt-parser-algorithm>
+pub fn parse(input: &str) -> Result<Vec<Cue>, ()> {
+    // Steps 1-2.
+    let input = input.replace("\r\n", "\n").replace('\r', "\n");
+    let input = input.trim_start_matches('\u{FEFF}');
+
+    let mut lines = input.split('\n').peekable();
+
+    // Steps 3-9.
+    match lines.next() {
+        Some(signature) if is_signature(signature) => {},
+        _ => return Err(()),
+    }
+
+    // Steps 10-11: the header is everything up to the first blank line.
+    while let Some(line) = lines.next() {
+        if line.is_empty() {
+            break;
+        }
+    }
+
+    // Step 12 onwards, ignoring regions and style sheets.
+    let mut cues = vec![];
+    loop {
+        while lines.peek().map_or(false, |line| line.is_empty()) {
+            lines.next();
+        }
+        let mut block = vec![];
+        while let Some(line) = lines.next() {
+            if line.is_empty() {
+                break;
+            }
+            block.push(line);
+        }
+        if block.is_empty() {
+            break;
+        }
+        if let Some(cue) = parse_cue_block(&block) {
+            cues.push(cue);
+        }
+    }
+    Ok(cues)
+}
+
+fn is_signature(line: &str) -> bool {
+    if !line.starts_with("WEBVTT") {
+        return false;
+    }
+    match line[6..].chars().next() {
+        None | Some(' ') | Some('\t') => true,
+        _ => false,
+    }
+}
+
+/// <https://w3c.github.io/webvtt/#collect-a-webvtt-block>
+fn parse_cue_block(block: &[&str]) -> Option<Cue> {
+    let (id, timings, text) = if block[0].contains("-->") {
+        ("", block[0], &block[1..])
+    } else if block.len() > 1 && block[1].contains("-->") {
+        (block[0], block[1], &block[2..])
+    } else {
+        // A NOTE, STYLE or REGION block, or garbage.
+        return None;
+    };
+
+    let (start_time, end_time, settings) = parse_timings_and_settings(timings)?;
+    Some(Cue {
+        id: id.to_owned(),
+        start_time,
+        end_time,
+        settings,
+        text: text.join("\n"),
+    })
+}
+
+/// <https://w3c.github.io/webvtt/#collect-webvtt-cue-timings-and-settings>
+fn parse_timings_and_settings(line: &str) -> Option<(f64, f64, CueSettings)> {
+    let mut parts = line.splitn(2, "-->");
+    let start_time = parse_timestamp(parts.next()?.trim())?;
+    let rest = parts.next()?.trim_start();
+    let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let end_time = parse_timestamp(&rest[..end])?;
+    Some((start_time, end_time, parse_settings(&rest[end..])))
+}
+
+/// Parses a `[hh:]mm:ss.ttt` timestamp into seconds.
+/// <https://w3c.github.io/webvtt/#collect-a-webvtt-timestamp>
+fn parse_timestamp(input: &str) -> Option<f64> {
+    fn digits(input: &str) -> Option<u64> {
+        if input.is_empty() || !input.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        input.parse().ok()
+    }
+
+    let mut dot = input.splitn(2, '.');
+    let clock = dot.next()?;
+    let fraction = dot.next()?;
+    if fraction.len() != 3 {
+        return None;
+    }
+    let milliseconds = digits(fraction)?;
+
+    let components = clock.split(':').collect::<Vec<_>>();
+    let (hours, minutes, seconds) = match components.len() {
+        2 => (0, components[0], components[1]),
+        3 => {
+            if components[0].len() < 2 {
+                return None;
+            }
+            (digits(components[0])?, components[1], components[2])
+        },
+        _ => return None,
+    };
+    if minutes.len() != 2 || seconds.len() != 2 {
+        return None;
+    }
+    let minutes = digits(minutes)?;
+    let seconds = digits(seconds)?;
+    if minutes > 59 || seconds > 59 {
+        return None;
+    }
+    Some((hours * 3600 + minutes * 60 + seconds) as f64 + milliseconds as f64 / 1000.)
+}
+
+/// Parses a `N%` percentage between 0 and 100.
+/// <https://w3c.github.io/webvtt/#parse-a-percentage-string>
+fn parse_percentage(input: &str) -> Option<f64> {
+    if !input.ends_with('%') {
+        return None;
+    }
+    let number = &input[..input.len() - 1];
+    if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+        return None;
+    }
+    match number.parse::<f64>() {
+        Ok(value) if value >= 0. && value <= 100. => Some(value),
+        _ => None,
+    }
+}
+
+/// <https://w3c.github.io/webvtt/#parse-the-webvtt-cue-settings>
+fn parse_settings(input: &str) -> CueSettings {
+    let mut settings = CueSettings::default();
+    for setting in input.split_whitespace() {
+        let mut parts = setting.splitn(2, ':');
+        let (name, value) = match (parts.next(), parts.next()) {
+            (Some(name), Some(value)) if !name.is_empty() && !value.is_empty() => (name, value),
+            _ => continue,
+        };
+        match name {
+            "vertical" => match value {
+                "rl" => settings.vertical = WritingDirection::VerticalGrowingLeft,
+                "lr" => settings.vertical = WritingDirection::VerticalGrowingRight,
+                _ => {},
+            },
+            "line" => {
+                let mut parts = value.splitn(2, ',');
+                let position = parts.next().unwrap_or("");
+                let align = match parts.next() {
+                    None => Some(LineAlignment::Start),
+                    Some("start") => Some(LineAlignment::Start),
+                    Some("center") => Some(LineAlignment::Center),
+                    Some("end") => Some(LineAlignment::End),
+                    Some(_) => None,
+                };
+                let align = match align {
+                    Some(align) => align,
+                    None => continue,
+                };
+                let (line, snap_to_lines) = if position.ends_with('%') {
+                    match parse_percentage(position) {
+                        Some(line) => (line, false),
+                        None => continue,
+                    }
+                } else {
+                    let valid = !position.is_empty() &&
+                        position.bytes().enumerate().all(|(i, b)| {
+                            b.is_ascii_digit() || b == b'.' || (i == 0 && b == b'-')
+                        });
+                    match position.parse::<f64>() {
+                        Ok(line) if valid => (line, true),
+                        _ => continue,
+                    }
+                };
+                settings.line = Some(line);
+                settings.snap_to_lines = snap_to_lines;
+                settings.line_align = align;
+            },
+            "position" => {
+                let mut parts = value.splitn(2, ',');
+                let position = match parse_percentage(parts.next().unwrap_or("")) {
+                    Some(position) => position,
+                    None => continue,
+                };
+                let align = match parts.next() {
+                    None => PositionAlignment::Auto,
+                    Some("line-left") => PositionAlignment::LineLeft,
+                    Some("center") => PositionAlignment::Center,
+                    Some("line-right") => PositionAlignment::LineRight,
+                    Some(_) => continue,
+                };
+                settings.position = Some(position);
+                settings.position_align = align;
+            },
+            "size" => {
+                if let Some(size) = parse_percentage(value) {
+                    settings.size = size;
+                }
+            },
+            "align" => match value {
+                "start" => settings.align = TextAlignment::Start,
+                "center" => settings.align = TextAlignment::Center,
+                "end" => settings.align = TextAlignment::End,
+                "left" => settings.align = TextAlignment::Left,
+                "right" => settings.align = TextAlignment::Right,
+                _ => {},
+            },
+            // FIXME: Implement regions.
+            _ => {},
+        }
+    }
+    settings
+}
+
+/// A node of a parsed cue text.
+/// <https://w3c.github.io/webvtt/#webvtt-node-objects>
+#[derive(Clone, Debug, PartialEq)]
+pub enum CueNode {
+    Text(String),
+    /// An internal node, with the tag name of the element it stands for
+    /// (`c`, `i`, `b`, `u`, `ruby`, `rt`, `v` or `lang`), its classes and its
+    /// annotation (the voice name or language).
+    Element {
+        name: String,
+        classes: Vec<String>,
+        annotation: Option<String>,
+        children: Vec<CueNode>,
+    },
+}
+
+impl CueNode {
+    /// The text of this node with all the markup stripped.
+    pub fn text_content(&self) -> String {
+        match *self {
+            CueNode::Text(ref text) => text.clone(),
+            CueNode::Element { ref children, .. } => {
+                children.iter().map(CueNode::text_content).collect()
+            },
+        }
+    }
+}
+
+/// Returns the plain text of a cue text, once its markup is stripped.
+pub fn cue_text_content(text: &str) -> String {
+    parse_cue_text(text)
+        .iter()
+        .map(CueNode::text_content)
+        .collect()
+}
+
+/// Parses the markup of a cue text. Timestamp tags are dropped.
+/// <https://w3c.github.io/webvtt/#cue-text-parsing-rules>
+pub fn parse_cue_text(text: &str) -> Vec<CueNode> {
+    // The stack of open elements, as (name, classes, annotation, children).
+    let mut stack: Vec<(String, Vec<String>, Option<String>, Vec<CueNode>)> =
+        vec![(String::new(), vec![], None, vec![])];
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let text = unescape(&rest[..end]);
+            stack.last_mut().unwrap().3.push(CueNode::Text(text));
+            rest = &rest[end..];
+            continue;
+        }
+
+        let end = rest.find('>').unwrap_or(rest.len());
+        let tag = &rest[1..end];
+        rest = &rest[(end + 1).min(rest.len())..];
+
+        if tag.starts_with('/') {
+            // End tag, which closes the matching element, or the ruby text
+            // element that is implicitly closed by `</ruby>`.
+            let name = tag[1..].trim();
+            let closes = stack.len() > 1 &&
+                (stack.last().unwrap().0 == name ||
+                    (name == "ruby" && stack.last().unwrap().0 == "rt"));
+            if closes {
+                let close_ruby = name == "ruby" && stack.last().unwrap().0 == "rt";
+                close_element(&mut stack);
+                if close_ruby && stack.len() > 1 && stack.last().unwrap().0 == "ruby" {
+                    close_element(&mut stack);
+                }
+            }
+            continue;
+        }
+
+        let mut parts = tag.splitn(2, |c: char| c.is_whitespace());
+        let mut classes = parts.next().unwrap_or("").split('.');
+        let name = classes.next().unwrap_or("");
+        let classes = classes
+            .filter(|class| !class.is_empty())
+            .map(|class| class.to_owned())
+            .collect();
+        let annotation = parts
+            .next()
+            .map(|annotation| unescape(annotation.trim()))
+            .filter(|annotation| !annotation.is_empty());
+
+        match name {
+            "c" | "i" | "b" | "u" | "ruby" | "v" | "lang" => {
+                stack.push((name.to_owned(), classes, annotation, vec![]));
+            },
+            "rt" if stack.last().unwrap().0 == "ruby" => {
+                stack.push((name.to_owned(), classes, annotation, vec![]));
+            },
+            // Timestamps and unknown tags.
+            _ => {},
+        }
+    }
+
+    while stack.len() > 1 {
+        close_element(&mut stack);
+    }
+    stack.pop().unwrap().3
+}
+
+fn close_element(stack: &mut Vec<(String, Vec<String>, Option<String>, Vec<CueNode>)>) {
+    let (name, classes, annotation, children) = stack.pop().unwrap();
+    stack.last_mut().unwrap().3.push(CueNode::Element {
+        name,
+        classes,
+        annotation,
+        children,
+    });
+}
+
+/// Replaces the character references allowed in cue texts.
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&lrm;", "\u{200E}")
+        .replace("&rlm;", "\u{200F}")
+        .replace("&nbsp;", "\u{A0}")
+        .replace("&amp;", "&")
+}
@@ -104,6 +104,9 @@ pub enum LayoutElementType {
 pub enum HTMLCanvasDataSource {
     WebGL(webrender_api::ImageKey),
     Image(Option<IpcSender<CanvasMsg>>),
+    /// The canvas was transferred to an `OffscreenCanvas`, which presents
+    /// its frames in this image.
+    Placeholder(webrender_api::ImageKey),
 }
 
 pub struct HTMLCanvasData {
@@ -142,4 +145,30 @@ pub struct PendingImage {
 
 pub struct HTMLMediaData {
     pub current_frame: Option<(webrender_api::ImageKey, i32, i32)>,
+    pub cues: Vec<HTMLMediaCue>,
+}
+
+/// The alignment of the lines of a cue inside its box.
+#[derive(Clone, Copy, Debug, MallocSizeOf, PartialEq)]
+pub enum HTMLMediaCueAlign {
+    Start,
+    Center,
+    End,
+}
+
+/// A text track cue to render over a video.
+#[derive(Clone, Debug, MallocSizeOf, PartialEq)]
+pub struct HTMLMediaCue {
+    /// The cue text, without markup.
+    pub text: String,
+    /// The line position, `None` standing for "auto". This is a line number if
+    /// `snap_to_lines` is set, and a percentage of the video height otherwise.
+    pub line: Option<f64>,
+    pub snap_to_lines: bool,
+    /// The position of the cue box as a percentage of the video width, `None`
+    /// standing for "auto".
+    pub position: Option<f64>,
+    /// The width of the cue box as a percentage of the video width.
+    pub size: f64,
+    pub align: HTMLMediaCueAlign,
 }
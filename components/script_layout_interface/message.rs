@@ -100,6 +100,10 @@ pub enum Msg {
     /// Send to layout the precise time when the navigation started.
     SetNavigationStart(u64),
 
+    /// Tells layout whether script observes largest contentful paint and layout shift
+    /// entries, which are computed from the painted display lists.
+    SetObservedPaintMetrics(bool, bool),
+
     /// Request the current number of animations that are running.
     GetRunningAnimations(IpcSender<usize>),
 }
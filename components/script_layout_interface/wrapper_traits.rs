@@ -167,9 +167,8 @@ pub trait ThreadSafeLayoutNode:
     type ConcreteNode: LayoutNode<ConcreteThreadSafeLayoutNode = Self>;
     type ConcreteElement: TElement;
 
-    type ConcreteThreadSafeLayoutElement: ThreadSafeLayoutElement<
-            ConcreteThreadSafeLayoutNode = Self,
-        > + ::selectors::Element<Impl = SelectorImpl>;
+    type ConcreteThreadSafeLayoutElement: ThreadSafeLayoutElement<ConcreteThreadSafeLayoutNode = Self>
+        + ::selectors::Element<Impl = SelectorImpl>;
     type ChildrenIterator: Iterator<Item = Self> + Sized;
 
     /// Converts self into an `OpaqueNode`.
@@ -467,6 +466,26 @@ pub trait ThreadSafeLayoutElement:
             .clone()
     }
 
+    /// Returns the style of the `::cue` pseudo-element of this element, which
+    /// applies to the text track cues rendered over a video.
+    #[inline]
+    fn cue_style(&self, context: &SharedStyleContext) -> Arc<ComputedValues> {
+        let data = self.style_data();
+        context
+            .stylist
+            .lazily_compute_pseudo_element_style(
+                &context.guards,
+                unsafe { self.unsafe_get() },
+                &PseudoElement::Cue,
+                RuleInclusion::All,
+                data.styles.primary(),
+                /* is_probe = */ false,
+                &ServoMetricsProvider,
+                /* matching_func = */ None,
+            )
+            .unwrap()
+    }
+
     /// Returns the already resolved style of the node.
     ///
     /// This differs from `style(ctx)` in that if the pseudo-element has not yet
@@ -24,7 +24,7 @@ use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLPipeline;
 use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use devtools_traits::{DevtoolScriptControlMsg, ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::Cursor;
+use embedder_traits::{Cursor, GeolocationPosition};
 use euclid::{Length, Point2D, Rect, TypedScale, TypedSize2D, Vector2D};
 use gfx_traits::Epoch;
 use http::HeaderMap;
@@ -64,6 +64,9 @@ pub use crate::script_msg::{
 pub use crate::script_msg::{
     EventResult, IFrameSize, IFrameSizeMsg, LayoutMsg, LogEntry, ScriptMsg,
 };
+pub use crate::script_msg::{
+    ServiceWorkerId, ServiceWorkerLifecycleState, ServiceWorkerUpdateResult,
+};
 
 /// The address of a node. Layout sends these back. They must be validated via
 /// `from_untrusted_node_address` before they can be used, because we do not trust layout.
@@ -350,6 +353,33 @@ pub enum ConstellationControlMsg {
     WebVREvents(PipelineId, Vec<WebVREvent>),
     /// Notifies the script thread about a new recorded paint metric.
     PaintMetric(PipelineId, ProgressiveWebMetricType, u64),
+    /// Notifies the script thread about a new largest contentful paint candidate: the
+    /// paint time, the painted area and the node that was painted.
+    LargestContentfulPaint(PipelineId, u64, u64, UntrustedNodeAddress),
+    /// Notifies the script thread about a layout shift: its score and the paint time.
+    LayoutShift(PipelineId, f64, u64),
+    /// Notifies the script thread that the user's preferred languages changed.
+    SetLanguages(PipelineId, Vec<String>),
+    /// Notifies the script thread that the network went offline or came back online.
+    SetOffline(PipelineId, bool),
+    /// Notifies the script thread that the position of the device changed, `None` if it
+    /// became unavailable.
+    SetGeolocation(PipelineId, Option<GeolocationPosition>),
+    /// Notifies the script thread that the update started by one of its register or
+    /// update jobs finished.
+    ServiceWorkerUpdateFinished(PipelineId, ServoUrl, ServiceWorkerUpdateResult),
+    /// Notifies the script thread that a service worker of the registration for the
+    /// given scope changed state.
+    ServiceWorkerStateChanged(
+        PipelineId,
+        ServoUrl,
+        ServiceWorkerId,
+        ServoUrl,
+        ServiceWorkerLifecycleState,
+    ),
+    /// Notifies the script thread that the pipeline is now controlled by the active
+    /// worker of the registration for the given scope.
+    SetServiceWorkerController(PipelineId, ServoUrl),
 }
 
 impl fmt::Debug for ConstellationControlMsg {
@@ -387,6 +417,14 @@ impl fmt::Debug for ConstellationControlMsg {
             Reload(..) => "Reload",
             WebVREvents(..) => "WebVREvents",
             PaintMetric(..) => "PaintMetric",
+            LargestContentfulPaint(..) => "LargestContentfulPaint",
+            LayoutShift(..) => "LayoutShift",
+            SetLanguages(..) => "SetLanguages",
+            SetOffline(..) => "SetOffline",
+            SetGeolocation(..) => "SetGeolocation",
+            ServiceWorkerUpdateFinished(..) => "ServiceWorkerUpdateFinished",
+            ServiceWorkerStateChanged(..) => "ServiceWorkerStateChanged",
+            SetServiceWorkerController(..) => "SetServiceWorkerController",
         };
         write!(formatter, "ConstellationControlMsg::{}", variant)
     }
@@ -777,6 +815,12 @@ pub enum ConstellationMsg {
     ForwardEvent(PipelineId, CompositorEvent),
     /// Requesting a change to the onscreen cursor.
     SetCursor(Cursor),
+    /// The user's preferred languages changed, most preferred first.
+    SetLanguages(Vec<String>),
+    /// Switch the network offline or back online.
+    SetOffline(bool),
+    /// The position of the device changed, `None` if it became unavailable.
+    SetGeolocation(Option<GeolocationPosition>),
 }
 
 impl fmt::Debug for ConstellationMsg {
@@ -804,6 +848,9 @@ impl fmt::Debug for ConstellationMsg {
             SelectBrowser(..) => "SelectBrowser",
             ForwardEvent(..) => "ForwardEvent",
             SetCursor(..) => "SetCursor",
+            SetLanguages(..) => "SetLanguages",
+            SetOffline(..) => "SetOffline",
+            SetGeolocation(..) => "SetGeolocation",
         };
         write!(formatter, "ConstellationMsg::{}", variant)
     }
@@ -14,7 +14,7 @@ use crate::WorkerGlobalScopeInit;
 use crate::WorkerScriptLoadOrigin;
 use canvas_traits::canvas::{CanvasId, CanvasMsg};
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::{Cursor, EmbedderMsg};
+use embedder_traits::{Cursor, EmbedderMsg, Notification, NotificationEvent};
 use euclid::{Size2D, TypedSize2D};
 use gfx_traits::Epoch;
 use ipc_channel::ipc::{IpcReceiver, IpcSender};
@@ -215,6 +215,15 @@ pub enum ScriptMsg {
     GetScreenSize(IpcSender<(DeviceIntSize)>),
     /// Get the available screen size (pixel)
     GetScreenAvailSize(IpcSender<(DeviceIntSize)>),
+    /// Record whether the user granted (`true`) or denied (`false`) the permission of the
+    /// given name to the given origin
+    RecordPermission(ImmutableOrigin, String, bool),
+    /// Get whether the user granted or denied the permission of the given name to the
+    /// given origin, if they were asked
+    GetPermission(ImmutableOrigin, String, IpcSender<Option<bool>>),
+    /// Display a notification whose events go to the active service worker of the
+    /// registration for the given scope
+    ShowPersistentNotification(ServoUrl, Notification),
 }
 
 impl fmt::Debug for ScriptMsg {
@@ -259,6 +268,9 @@ impl fmt::Debug for ScriptMsg {
             GetClientWindow(..) => "GetClientWindow",
             GetScreenSize(..) => "GetScreenSize",
             GetScreenAvailSize(..) => "GetScreenAvailSize",
+            RecordPermission(..) => "RecordPermission",
+            GetPermission(..) => "GetPermission",
+            ShowPersistentNotification(..) => "ShowPersistentNotification",
         };
         write!(formatter, "ScriptMsg::{}", variant)
     }
@@ -291,15 +303,64 @@ pub struct SWManagerSenders {
     pub resource_sender: IpcSender<CoreResourceMsg>,
 }
 
+/// Identifies a service worker across all the registrations of the service worker manager
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub struct ServiceWorkerId(pub u64);
+
+/// <https://w3c.github.io/ServiceWorker/#dfn-state>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ServiceWorkerLifecycleState {
+    Installing,
+    Installed,
+    Activating,
+    Activated,
+    Redundant,
+}
+
+/// Outcome of the update algorithm run by the service worker manager for a register or
+/// update job
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum ServiceWorkerUpdateResult {
+    /// The script could not be fetched
+    Failed(String),
+    /// The newest worker of the registration already runs the same script
+    UpToDate,
+    /// A new worker was created from the given script and is being installed
+    Installing(ServiceWorkerId, ServoUrl),
+}
+
 /// Messages sent to Service Worker Manager thread
 #[derive(Debug, Deserialize, Serialize)]
 pub enum ServiceWorkerMsg {
-    /// Message to register the service worker
+    /// Message to run the update algorithm for the registration of the given scope,
+    /// creating the registration if needed
     RegisterServiceWorker(ScopeThings, ServoUrl),
-    /// Timeout message sent by active service workers
-    Timeout(ServoUrl),
+    /// Timeout message sent by running service workers
+    Timeout(ServiceWorkerId),
     /// Message sent by constellation to forward to a running service worker
     ForwardDOMMessage(DOMMessage, ServoUrl),
+    /// Sent by a service worker once the promises extending its install event settled,
+    /// along with whether all of them were fulfilled
+    InstallFinished(ServiceWorkerId, bool),
+    /// Sent by a service worker once the promises extending its activate event settled
+    ActivateFinished(ServiceWorkerId),
+    /// Sent by a service worker calling `skipWaiting()`, answered once it is handled
+    SkipWaiting(ServiceWorkerId, IpcSender<()>),
+    /// Sent by a service worker calling `clients.claim()`
+    Claim(ServiceWorkerId),
+    /// A pipeline went away and is no longer a client of any service worker
+    PipelineExited(PipelineId),
+    /// The embedder put the browser in (`true`) or out of (`false`) offline mode
+    SetOffline(bool),
+    /// Sent by a service worker to get whether the user granted or denied the permission
+    /// of the given name to the given origin, if they were asked
+    GetPermission(ImmutableOrigin, String, IpcSender<Option<bool>>),
+    /// Display a notification whose events go to the active worker of the registration
+    /// for the given scope
+    ShowNotification(ServoUrl, Notification),
+    /// The embedder reported an event of a notification shown for the registration of the
+    /// given scope
+    NotificationEvent(ServoUrl, Notification, NotificationEvent),
     /// Exit the service worker manager
     Exit,
 }
@@ -309,4 +370,21 @@ pub enum ServiceWorkerMsg {
 pub enum SWManagerMsg {
     /// Provide the constellation with a means of communicating with the Service Worker Manager
     OwnSender(IpcSender<ServiceWorkerMsg>),
+    /// The update started by the register or update job of the given pipeline finished
+    UpdateFinished(PipelineId, ServoUrl, ServiceWorkerUpdateResult),
+    /// A service worker of the registration for the given scope changed state
+    StateChanged(
+        ServoUrl,
+        ServiceWorkerId,
+        ServoUrl,
+        ServiceWorkerLifecycleState,
+    ),
+    /// The pipeline is now controlled by the active worker of the registration for the
+    /// given scope
+    SetController(PipelineId, ServoUrl),
+    /// Get whether the user granted or denied the permission of the given name to the
+    /// given origin, if they were asked
+    GetPermission(ImmutableOrigin, String, IpcSender<Option<bool>>),
+    /// A message for the embedder
+    ForwardToEmbedder(EmbedderMsg),
 }
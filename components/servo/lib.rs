@@ -469,6 +469,36 @@ where
                     );
                 }
             },
+
+            WindowEvent::SetLanguages(languages) => {
+                let msg = ConstellationMsg::SetLanguages(languages);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetLanguages message to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::SetOffline(offline) => {
+                let msg = ConstellationMsg::SetOffline(offline);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetOffline message to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::SetGeolocation(position) => {
+                let msg = ConstellationMsg::SetGeolocation(position);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetGeolocation message to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
         }
     }
 
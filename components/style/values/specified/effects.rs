@@ -18,6 +18,7 @@ use crate::values::specified::length::{Length, NonNegativeLength};
 #[cfg(feature = "gecko")]
 use crate::values::specified::url::SpecifiedUrl;
 use crate::values::specified::{Angle, NumberOrPercentage};
+use crate::values::CSSFloat;
 #[cfg(not(feature = "gecko"))]
 use crate::values::Impossible;
 use crate::Zero;
@@ -50,6 +51,15 @@ impl Factor {
         Factor::parse(context, input).map(|v| v.clamp_to_one())
     }
 
+    /// Returns this factor as a number, where `100%` is `1`.
+    #[inline]
+    pub fn to_number(&self) -> CSSFloat {
+        match self.0 {
+            NumberOrPercentage::Percentage(ref percent) => percent.get(),
+            NumberOrPercentage::Number(ref number) => number.get(),
+        }
+    }
+
     /// Clamp the value to 1 if the value is over 100%.
     #[inline]
     fn clamp_to_one(self) -> Self {
@@ -70,6 +70,16 @@ fn extension_routes() -> Vec<(Method, &'static str, ServoExtensionRoute)> {
             "/session/{sessionId}/servo/prefs/reset",
             ServoExtensionRoute::ResetPrefs,
         ),
+        (
+            Method::POST,
+            "/session/{sessionId}/servo/network/offline",
+            ServoExtensionRoute::SetOffline,
+        ),
+        (
+            Method::POST,
+            "/session/{sessionId}/servo/languages",
+            ServoExtensionRoute::SetLanguages,
+        ),
     ];
 }
 
@@ -147,6 +157,8 @@ enum ServoExtensionRoute {
     GetPrefs,
     SetPrefs,
     ResetPrefs,
+    SetOffline,
+    SetLanguages,
 }
 
 impl WebDriverExtensionRoute for ServoExtensionRoute {
@@ -170,6 +182,14 @@ impl WebDriverExtensionRoute for ServoExtensionRoute {
                 let parameters: GetPrefsParameters = serde_json::from_value(body_data.clone())?;
                 ServoExtensionCommand::ResetPrefs(parameters)
             },
+            ServoExtensionRoute::SetOffline => {
+                let parameters: SetOfflineParameters = serde_json::from_value(body_data.clone())?;
+                ServoExtensionCommand::SetOffline(parameters)
+            },
+            ServoExtensionRoute::SetLanguages => {
+                let parameters: SetLanguagesParameters = serde_json::from_value(body_data.clone())?;
+                ServoExtensionCommand::SetLanguages(parameters)
+            },
         };
         Ok(WebDriverCommand::Extension(command))
     }
@@ -180,6 +200,8 @@ enum ServoExtensionCommand {
     GetPrefs(GetPrefsParameters),
     SetPrefs(SetPrefsParameters),
     ResetPrefs(GetPrefsParameters),
+    SetOffline(SetOfflineParameters),
+    SetLanguages(SetLanguagesParameters),
 }
 
 impl WebDriverExtensionCommand for ServoExtensionCommand {
@@ -188,6 +210,8 @@ impl WebDriverExtensionCommand for ServoExtensionCommand {
             ServoExtensionCommand::GetPrefs(ref x) => serde_json::to_value(x).ok(),
             ServoExtensionCommand::SetPrefs(ref x) => serde_json::to_value(x).ok(),
             ServoExtensionCommand::ResetPrefs(ref x) => serde_json::to_value(x).ok(),
+            ServoExtensionCommand::SetOffline(ref x) => serde_json::to_value(x).ok(),
+            ServoExtensionCommand::SetLanguages(ref x) => serde_json::to_value(x).ok(),
         }
     }
 }
@@ -291,6 +315,16 @@ struct SetPrefsParameters {
     prefs: Vec<(String, WebDriverPrefValue)>,
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct SetOfflineParameters {
+    offline: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct SetLanguagesParameters {
+    languages: Vec<String>,
+}
+
 fn map_to_vec<'de, D>(de: D) -> Result<Vec<(String, WebDriverPrefValue)>, D::Error>
 where
     D: Deserializer<'de>,
@@ -1187,6 +1221,26 @@ impl Handler {
             serde_json::to_value(prefs)?,
         )))
     }
+
+    fn handle_set_offline(
+        &self,
+        parameters: &SetOfflineParameters,
+    ) -> WebDriverResult<WebDriverResponse> {
+        self.constellation_chan
+            .send(ConstellationMsg::SetOffline(parameters.offline))
+            .unwrap();
+        Ok(WebDriverResponse::Void)
+    }
+
+    fn handle_set_languages(
+        &self,
+        parameters: &SetLanguagesParameters,
+    ) -> WebDriverResult<WebDriverResponse> {
+        self.constellation_chan
+            .send(ConstellationMsg::SetLanguages(parameters.languages.clone()))
+            .unwrap();
+        Ok(WebDriverResponse::Void)
+    }
 }
 
 impl WebDriverHandler<ServoExtensionRoute> for Handler {
@@ -1256,6 +1310,8 @@ impl WebDriverHandler<ServoExtensionRoute> for Handler {
                 ServoExtensionCommand::GetPrefs(ref x) => self.handle_get_prefs(x),
                 ServoExtensionCommand::SetPrefs(ref x) => self.handle_set_prefs(x),
                 ServoExtensionCommand::ResetPrefs(ref x) => self.handle_reset_prefs(x),
+                ServoExtensionCommand::SetOffline(ref x) => self.handle_set_offline(x),
+                ServoExtensionCommand::SetLanguages(ref x) => self.handle_set_languages(x),
             },
             _ => Err(WebDriverError::new(
                 ErrorStatus::UnsupportedOperation,
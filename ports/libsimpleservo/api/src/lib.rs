@@ -519,6 +519,8 @@ impl ServoGlue {
                 EmbedderMsg::SetFullscreenState(..) |
                 EmbedderMsg::ShowIME(..) |
                 EmbedderMsg::HideIME |
+                EmbedderMsg::ShowNotification(..) |
+                EmbedderMsg::CloseNotification(..) |
                 EmbedderMsg::Panic(..) => {},
             }
         }
@@ -2,13 +2,13 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use crate::glutin_app::keyutils::{CMD_OR_CONTROL, CMD_OR_ALT};
+use crate::glutin_app::keyutils::{CMD_OR_ALT, CMD_OR_CONTROL};
 use crate::glutin_app::window::{Window, LINE_HEIGHT};
 use euclid::{TypedPoint2D, TypedVector2D};
 use keyboard_types::{Key, KeyboardEvent, Modifiers, ShortcutMatcher};
 use servo::compositing::windowing::{WebRenderDebugOption, WindowEvent};
-use servo::embedder_traits::{EmbedderMsg, FilterPattern};
-use servo::msg::constellation_msg::{TopLevelBrowsingContextId as BrowserId};
+use servo::embedder_traits::{EmbedderMsg, FilterPattern, NotificationEvent};
+use servo::msg::constellation_msg::TopLevelBrowsingContextId as BrowserId;
 use servo::msg::constellation_msg::TraversalDirection;
 use servo::net_traits::pub_domains::is_reg_domain;
 use servo::script_traits::TouchEventType;
@@ -378,6 +378,34 @@ impl Browser {
                 EmbedderMsg::HideIME => {
                     debug!("HideIME received");
                 },
+                EmbedderMsg::ShowNotification(notification, sender) => {
+                    // There is no notification center to show them in, log them instead.
+                    info!(
+                        "Notification: {}\n{}",
+                        notification.title, notification.body
+                    );
+                    let mut events = vec![NotificationEvent::Shown];
+                    // Nobody can click the logged notifications, tests get them clicked
+                    // and closed right away instead.
+                    if PREFS
+                        .get("shell.notifications.testing.activate")
+                        .as_boolean()
+                        .unwrap_or(false)
+                    {
+                        events.push(NotificationEvent::Clicked);
+                        events.push(NotificationEvent::Closed);
+                    }
+                    if let Some(sender) = sender {
+                        for event in events {
+                            if let Err(e) = sender.send(event) {
+                                let reason = format!("Failed to send notification event: {}", e);
+                                self.event_queue.push(WindowEvent::SendError(None, reason));
+                                break;
+                            }
+                        }
+                    }
+                },
+                EmbedderMsg::CloseNotification(_id) => {},
             }
         }
     }
@@ -0,0 +1,347 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Renders the same scenes with the Azure and software backends, whose
+//! results should only differ by antialiasing and rounding.
+
+use canvas::azure_backend::AzureBackend;
+use canvas::canvas_data::{
+    Backend, DrawOptions, Filter, GenericDrawTarget, Pattern, StrokeOptions,
+};
+use canvas::software_backend::SoftwareBackend;
+use canvas_traits::canvas::*;
+use cssparser::RGBA;
+use euclid::{Point2D, Rect, Size2D, Transform2D, Vector2D};
+
+const SIZE: u32 = 64;
+
+fn compare<F>(scene: F)
+where
+    F: Fn(&dyn GenericDrawTarget),
+{
+    let azure = AzureBackend.create_drawtarget(Size2D::new(SIZE, SIZE));
+    let software = SoftwareBackend.create_drawtarget(Size2D::new(SIZE, SIZE));
+    scene(&*azure);
+    scene(&*software);
+    let expected = azure.snapshot_data();
+    let actual = software.snapshot_data();
+    assert_eq!(expected.len(), actual.len());
+
+    // Edges are antialiased differently, so only a few pixels may differ
+    // noticeably while the rest should match up to rounding.
+    let mut different_pixels = 0;
+    let mut total_difference = 0;
+    for (expected, actual) in expected.chunks(4).zip(actual.chunks(4)) {
+        let difference = expected
+            .iter()
+            .zip(actual.iter())
+            .map(|(a, b)| (*a as i32 - *b as i32).abs())
+            .max()
+            .unwrap();
+        if difference > 8 {
+            different_pixels += 1;
+        }
+        total_difference += difference;
+    }
+    let pixel_count = (SIZE * SIZE) as i32;
+    assert!(
+        different_pixels * 20 <= pixel_count,
+        "{} pixels differ",
+        different_pixels
+    );
+    assert!(
+        total_difference <= pixel_count * 2,
+        "mean difference {}",
+        total_difference as f32 / pixel_count as f32
+    );
+}
+
+fn color(draw_target: &dyn GenericDrawTarget, red: u8, green: u8, blue: u8, alpha: u8) -> Pattern {
+    draw_target
+        .create_pattern(&FillOrStrokeStyle::Color(RGBA::new(
+            red, green, blue, alpha,
+        )))
+        .unwrap()
+}
+
+fn draw_options(composition: CompositionOrBlending) -> DrawOptions {
+    DrawOptions::new(1., composition, true)
+}
+
+fn rect(x: f32, y: f32, width: f32, height: f32) -> Rect<f32> {
+    Rect::new(Point2D::new(x, y), Size2D::new(width, height))
+}
+
+fn stops() -> Vec<CanvasGradientStop> {
+    vec![
+        CanvasGradientStop {
+            offset: 0.,
+            color: RGBA::new(255, 0, 0, 255),
+        },
+        CanvasGradientStop {
+            offset: 0.5,
+            color: RGBA::new(0, 255, 0, 128),
+        },
+        CanvasGradientStop {
+            offset: 1.,
+            color: RGBA::new(0, 0, 255, 255),
+        },
+    ]
+}
+
+#[test]
+fn test_fill_rect() {
+    compare(|draw_target| {
+        let pattern = color(draw_target, 255, 128, 0, 255);
+        let options = draw_options(CompositionOrBlending::default());
+        draw_target.fill_rect(&rect(4., 4., 30., 20.), &pattern, &options);
+        draw_target.set_transform(&Transform2D::create_rotation(euclid::Angle::radians(0.3)));
+        draw_target.fill_rect(&rect(20.5, 10.25, 30., 30.), &pattern, &options);
+    });
+}
+
+#[test]
+fn test_fill_path() {
+    compare(|draw_target| {
+        let mut builder = draw_target.create_path_builder();
+        builder.move_to(Point2D::new(8., 8.));
+        builder.bezier_curve_to(
+            &Point2D::new(60., 0.),
+            &Point2D::new(0., 60.),
+            &Point2D::new(56., 56.),
+        );
+        builder.quadratic_curve_to(&Point2D::new(8., 60.), &Point2D::new(8., 8.));
+        builder.arc(Point2D::new(32., 32.), 12., 0., 5., true);
+        builder.ellipse(Point2D::new(40., 16.), 10., 5., 0.5, 0., 6.2, false);
+        let path = builder.finish();
+        let pattern = color(draw_target, 0, 0, 255, 200);
+        draw_target.fill(
+            &path,
+            &pattern,
+            &draw_options(CompositionOrBlending::default()),
+        );
+    });
+}
+
+#[test]
+fn test_stroke() {
+    for &join in &[
+        LineJoinStyle::Round,
+        LineJoinStyle::Bevel,
+        LineJoinStyle::Miter,
+    ] {
+        for &cap in &[
+            LineCapStyle::Butt,
+            LineCapStyle::Round,
+            LineCapStyle::Square,
+        ] {
+            compare(|draw_target| {
+                let mut builder = draw_target.create_path_builder();
+                builder.move_to(Point2D::new(10., 10.));
+                builder.line_to(Point2D::new(50., 16.));
+                builder.line_to(Point2D::new(20., 30.));
+                builder.quadratic_curve_to(&Point2D::new(60., 60.), &Point2D::new(12., 52.));
+                let pattern = color(draw_target, 0, 128, 0, 255);
+                let stroke_options = StrokeOptions::new(5., join, cap, 10.);
+                draw_target.stroke(
+                    &builder.finish(),
+                    &pattern,
+                    &stroke_options,
+                    &draw_options(CompositionOrBlending::default()),
+                );
+                draw_target.stroke_rect(
+                    &rect(40., 40., 16., 16.),
+                    &pattern,
+                    &stroke_options,
+                    &draw_options(CompositionOrBlending::default()),
+                );
+            });
+        }
+    }
+}
+
+#[test]
+fn test_gradients() {
+    compare(|draw_target| {
+        let options = draw_options(CompositionOrBlending::default());
+        let linear = draw_target
+            .create_pattern(&FillOrStrokeStyle::LinearGradient(
+                LinearGradientStyle::new(4., 0., 60., 20., stops()),
+            ))
+            .unwrap();
+        draw_target.fill_rect(&rect(0., 0., 64., 32.), &linear, &options);
+        let radial = draw_target
+            .create_pattern(&FillOrStrokeStyle::RadialGradient(
+                RadialGradientStyle::new(24., 48., 4., 32., 48., 24., stops()),
+            ))
+            .unwrap();
+        draw_target.fill_rect(&rect(0., 32., 64., 32.), &radial, &options);
+    });
+}
+
+#[test]
+fn test_surface_pattern() {
+    compare(|draw_target| {
+        let data = (0..8 * 8)
+            .flat_map(|i| vec![(i * 4) as u8, 255 - (i * 4) as u8, 0, 255])
+            .collect();
+        let pattern = draw_target
+            .create_pattern(&FillOrStrokeStyle::Surface(SurfaceStyle::new(
+                data,
+                Size2D::new(8, 8),
+                true,
+                true,
+            )))
+            .unwrap();
+        draw_target.set_transform(&Transform2D::create_translation(3., 5.));
+        draw_target.fill_rect(
+            &rect(0., 0., 48., 48.),
+            &pattern,
+            &draw_options(CompositionOrBlending::default()),
+        );
+    });
+}
+
+#[test]
+fn test_composition() {
+    let operators = [
+        CompositionStyle::SrcIn,
+        CompositionStyle::SrcOut,
+        CompositionStyle::SrcOver,
+        CompositionStyle::SrcAtop,
+        CompositionStyle::DestIn,
+        CompositionStyle::DestOut,
+        CompositionStyle::DestOver,
+        CompositionStyle::DestAtop,
+        CompositionStyle::Copy,
+        CompositionStyle::Lighter,
+        CompositionStyle::Xor,
+    ];
+    for &operator in &operators {
+        compare(|draw_target| {
+            draw_target.fill_rect(
+                &rect(8., 8., 32., 32.),
+                &color(draw_target, 0, 0, 255, 192),
+                &draw_options(CompositionOrBlending::default()),
+            );
+            draw_target.fill_rect(
+                &rect(24., 24., 32., 32.),
+                &color(draw_target, 255, 0, 0, 128),
+                &draw_options(CompositionOrBlending::Composition(operator)),
+            );
+        });
+    }
+}
+
+#[test]
+fn test_blending() {
+    let modes = [
+        BlendingStyle::Multiply,
+        BlendingStyle::Screen,
+        BlendingStyle::Overlay,
+        BlendingStyle::Darken,
+        BlendingStyle::Lighten,
+        BlendingStyle::ColorDodge,
+        BlendingStyle::ColorBurn,
+        BlendingStyle::HardLight,
+        BlendingStyle::SoftLight,
+        BlendingStyle::Difference,
+        BlendingStyle::Exclusion,
+        BlendingStyle::Hue,
+        BlendingStyle::Saturation,
+        BlendingStyle::Color,
+        BlendingStyle::Luminosity,
+    ];
+    for &mode in &modes {
+        compare(|draw_target| {
+            draw_target.fill_rect(
+                &rect(8., 8., 32., 32.),
+                &color(draw_target, 40, 120, 200, 255),
+                &draw_options(CompositionOrBlending::default()),
+            );
+            draw_target.fill_rect(
+                &rect(24., 24., 32., 32.),
+                &color(draw_target, 220, 160, 30, 200),
+                &draw_options(CompositionOrBlending::Blending(mode)),
+            );
+        });
+    }
+}
+
+#[test]
+fn test_clip() {
+    compare(|draw_target| {
+        let mut builder = draw_target.create_path_builder();
+        builder.arc(Point2D::new(32., 32.), 20., 0., 6.3, false);
+        draw_target.push_clip(&builder.finish());
+        draw_target.fill_rect(
+            &rect(0., 0., 40., 40.),
+            &color(draw_target, 255, 0, 255, 255),
+            &draw_options(CompositionOrBlending::default()),
+        );
+        draw_target.pop_clip();
+        draw_target.fill_rect(
+            &rect(40., 40., 20., 20.),
+            &color(draw_target, 0, 255, 255, 255),
+            &draw_options(CompositionOrBlending::default()),
+        );
+    });
+}
+
+#[test]
+fn test_draw_surface() {
+    compare(|draw_target| {
+        let source = draw_target.create_similar_draw_target(&Size2D::new(16, 16));
+        source.fill_rect(
+            &rect(0., 0., 8., 16.),
+            &color(draw_target, 255, 0, 0, 255),
+            &draw_options(CompositionOrBlending::default()),
+        );
+        draw_target.draw_surface(
+            source.snapshot(),
+            rect(4., 4., 48., 32.),
+            rect(0., 0., 16., 16.),
+            Filter::Point,
+            &DrawOptions::new(0.75, CompositionOrBlending::default(), true),
+        );
+    });
+}
+
+#[test]
+fn test_shadow() {
+    compare(|draw_target| {
+        let source = draw_target.create_similar_draw_target(&Size2D::new(24, 24));
+        source.fill_rect(
+            &rect(4., 4., 16., 16.),
+            &color(draw_target, 0, 0, 255, 255),
+            &draw_options(CompositionOrBlending::default()),
+        );
+        draw_target.draw_surface_with_shadow(
+            source.snapshot(),
+            &Point2D::new(8., 8.),
+            &RGBA::new(0, 0, 0, 128),
+            &Vector2D::new(12., 16.),
+            3.,
+            CompositionOrBlending::default(),
+        );
+    });
+}
+
+#[test]
+fn test_clear_and_copy() {
+    compare(|draw_target| {
+        draw_target.fill_rect(
+            &rect(0., 0., 64., 64.),
+            &color(draw_target, 10, 200, 30, 255),
+            &draw_options(CompositionOrBlending::default()),
+        );
+        draw_target.clear_rect(&rect(10., 10., 20., 20.));
+        let surface = draw_target.snapshot();
+        draw_target.copy_surface(
+            surface,
+            Rect::new(Point2D::new(0, 0), Size2D::new(32, 32)),
+            Point2D::new(32, 32),
+        );
+    });
+}
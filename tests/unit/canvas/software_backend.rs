@@ -0,0 +1,233 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use canvas::canvas_data::{Backend, DrawOptions, GenericDrawTarget, Pattern, StrokeOptions};
+use canvas::software_backend::SoftwareBackend;
+use canvas_traits::canvas::*;
+use cssparser::RGBA;
+use euclid::{Point2D, Rect, Size2D, Transform2D};
+
+fn draw_target(width: u32, height: u32) -> Box<dyn GenericDrawTarget> {
+    SoftwareBackend.create_drawtarget(Size2D::new(width, height))
+}
+
+/// Returns a pixel as premultiplied BGRA.
+fn pixel(draw_target: &dyn GenericDrawTarget, x: i32, y: i32) -> [u8; 4] {
+    let data = draw_target.snapshot_data();
+    let index = ((y * draw_target.get_size().width + x) * 4) as usize;
+    [
+        data[index],
+        data[index + 1],
+        data[index + 2],
+        data[index + 3],
+    ]
+}
+
+fn color(draw_target: &dyn GenericDrawTarget, red: u8, green: u8, blue: u8, alpha: u8) -> Pattern {
+    draw_target
+        .create_pattern(&FillOrStrokeStyle::Color(RGBA::new(
+            red, green, blue, alpha,
+        )))
+        .unwrap()
+}
+
+fn draw_options() -> DrawOptions {
+    DrawOptions::new(1., CompositionOrBlending::default(), true)
+}
+
+fn rect(x: f32, y: f32, width: f32, height: f32) -> Rect<f32> {
+    Rect::new(Point2D::new(x, y), Size2D::new(width, height))
+}
+
+#[test]
+fn test_fill_rect() {
+    let dt = draw_target(20, 20);
+    let red = color(&*dt, 255, 0, 0, 255);
+    dt.fill_rect(&rect(2., 2., 10., 10.), &red, &draw_options());
+    assert_eq!(pixel(&*dt, 2, 2), [0, 0, 255, 255]);
+    assert_eq!(pixel(&*dt, 11, 11), [0, 0, 255, 255]);
+    assert_eq!(pixel(&*dt, 1, 5), [0, 0, 0, 0]);
+    assert_eq!(pixel(&*dt, 12, 5), [0, 0, 0, 0]);
+
+    // Half covered pixels get half of the color.
+    let dt = draw_target(20, 20);
+    dt.fill_rect(&rect(2.5, 2., 10., 10.), &red, &draw_options());
+    let [_, _, red_value, alpha] = pixel(&*dt, 2, 5);
+    assert!(alpha >= 127 && alpha <= 128);
+    assert_eq!(red_value, alpha);
+}
+
+#[test]
+fn test_fill_rect_without_antialiasing() {
+    let dt = draw_target(20, 20);
+    let options = DrawOptions::new(1., CompositionOrBlending::default(), false);
+    dt.fill_rect(
+        &rect(2.25, 2.75, 10., 10.),
+        &color(&*dt, 255, 0, 0, 255),
+        &options,
+    );
+    assert_eq!(pixel(&*dt, 2, 5), [0, 0, 255, 255]);
+    assert_eq!(pixel(&*dt, 5, 2), [0, 0, 0, 0]);
+}
+
+#[test]
+fn test_transform_and_clip() {
+    let dt = draw_target(20, 20);
+    dt.set_transform(&Transform2D::create_translation(5., 5.));
+    let mut builder = dt.create_path_builder();
+    builder.move_to(Point2D::new(0., 0.));
+    builder.line_to(Point2D::new(5., 0.));
+    builder.line_to(Point2D::new(5., 5.));
+    builder.line_to(Point2D::new(0., 5.));
+    builder.close();
+    dt.push_clip(&builder.finish());
+    dt.fill_rect(
+        &rect(-10., -10., 100., 100.),
+        &color(&*dt, 0, 255, 0, 255),
+        &draw_options(),
+    );
+    dt.pop_clip();
+    assert_eq!(pixel(&*dt, 7, 7), [0, 255, 0, 255]);
+    assert_eq!(pixel(&*dt, 4, 7), [0, 0, 0, 0]);
+    assert_eq!(pixel(&*dt, 10, 7), [0, 0, 0, 0]);
+}
+
+#[test]
+fn test_stroke_miter_join() {
+    let dt = draw_target(40, 40);
+    let stroke_options = StrokeOptions::new(4., LineJoinStyle::Miter, LineCapStyle::Butt, 10.);
+    dt.stroke_rect(
+        &rect(10., 10., 20., 20.),
+        &color(&*dt, 0, 0, 255, 255),
+        &stroke_options,
+        &draw_options(),
+    );
+    assert_eq!(pixel(&*dt, 10, 20), [255, 0, 0, 255]);
+    assert_eq!(pixel(&*dt, 8, 8), [255, 0, 0, 255]);
+    assert_eq!(pixel(&*dt, 20, 20), [0, 0, 0, 0]);
+}
+
+#[test]
+fn test_arc_fill_and_hit_testing() {
+    let dt = draw_target(40, 40);
+    let mut builder = dt.create_path_builder();
+    builder.arc(
+        Point2D::new(20., 20.),
+        10.,
+        0.,
+        2. * ::std::f32::consts::PI,
+        false,
+    );
+    let path = builder.finish();
+    dt.fill(&path, &color(&*dt, 0, 0, 255, 255), &draw_options());
+    assert_eq!(pixel(&*dt, 20, 20), [255, 0, 0, 255]);
+    assert_eq!(pixel(&*dt, 28, 20), [255, 0, 0, 255]);
+    assert_eq!(pixel(&*dt, 31, 20), [0, 0, 0, 0]);
+    assert_eq!(pixel(&*dt, 28, 28), [0, 0, 0, 0]);
+    assert!(path.contains_point(20., 20., &Transform2D::identity()));
+    assert!(!path.contains_point(29., 29., &Transform2D::identity()));
+}
+
+#[test]
+fn test_linear_gradient() {
+    let dt = draw_target(100, 10);
+    let stops = vec![
+        CanvasGradientStop {
+            offset: 0.,
+            color: RGBA::new(0, 0, 0, 255),
+        },
+        CanvasGradientStop {
+            offset: 1.,
+            color: RGBA::new(255, 255, 255, 255),
+        },
+    ];
+    let gradient = dt
+        .create_pattern(&FillOrStrokeStyle::LinearGradient(
+            LinearGradientStyle::new(0., 0., 100., 0., stops),
+        ))
+        .unwrap();
+    dt.fill_rect(&rect(0., 0., 100., 10.), &gradient, &draw_options());
+    assert!(pixel(&*dt, 0, 5)[0] < 5);
+    assert!((pixel(&*dt, 50, 5)[0] as i32 - 128).abs() < 4);
+    assert!(pixel(&*dt, 99, 5)[0] > 250);
+}
+
+#[test]
+fn test_unbounded_composition() {
+    let dt = draw_target(10, 10);
+    dt.fill_rect(
+        &rect(0., 0., 10., 10.),
+        &color(&*dt, 255, 0, 0, 255),
+        &draw_options(),
+    );
+    let source_in = DrawOptions::new(
+        1.,
+        CompositionOrBlending::Composition(CompositionStyle::SrcIn),
+        true,
+    );
+    dt.fill_rect(
+        &rect(0., 0., 5., 5.),
+        &color(&*dt, 0, 0, 255, 255),
+        &source_in,
+    );
+    // The destination is cleared outside of the source.
+    assert_eq!(pixel(&*dt, 2, 2), [255, 0, 0, 255]);
+    assert_eq!(pixel(&*dt, 7, 7), [0, 0, 0, 0]);
+}
+
+#[test]
+fn test_multiply() {
+    let dt = draw_target(2, 2);
+    dt.fill_rect(
+        &rect(0., 0., 2., 2.),
+        &color(&*dt, 255, 128, 0, 255),
+        &draw_options(),
+    );
+    let multiply = DrawOptions::new(
+        1.,
+        CompositionOrBlending::Blending(BlendingStyle::Multiply),
+        true,
+    );
+    dt.fill_rect(
+        &rect(0., 0., 2., 2.),
+        &color(&*dt, 128, 255, 255, 255),
+        &multiply,
+    );
+    assert_eq!(pixel(&*dt, 0, 0), [0, 128, 128, 255]);
+}
+
+#[test]
+fn test_shadow_without_blur() {
+    let dt = draw_target(30, 30);
+    let source = draw_target(5, 5);
+    source.fill_rect(
+        &rect(0., 0., 5., 5.),
+        &color(&*source, 0, 255, 0, 255),
+        &draw_options(),
+    );
+    dt.draw_surface_with_shadow(
+        source.snapshot(),
+        &Point2D::new(2., 2.),
+        &RGBA::new(0, 0, 0, 255),
+        &euclid::Vector2D::new(10., 10.),
+        0.,
+        CompositionOrBlending::default(),
+    );
+    assert_eq!(pixel(&*dt, 3, 3), [0, 255, 0, 255]);
+    assert_eq!(pixel(&*dt, 13, 13), [0, 0, 0, 255]);
+    assert_eq!(pixel(&*dt, 20, 20), [0, 0, 0, 0]);
+}
+
+#[test]
+fn test_clear_rect() {
+    let dt = draw_target(10, 10);
+    dt.fill_rect(
+        &rect(0., 0., 10., 10.),
+        &color(&*dt, 255, 0, 0, 255),
+        &draw_options(),
+    );
+    dt.clear_rect(&rect(0., 0., 3., 3.));
+    assert_eq!(pixel(&*dt, 2, 2), [0, 0, 0, 0]);
+    assert_eq!(pixel(&*dt, 3, 3), [0, 0, 255, 255]);
+}
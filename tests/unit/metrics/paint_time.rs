@@ -2,14 +2,20 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use gfx_traits::Epoch;
+use euclid::{Point2D, Rect, Size2D};
+use gfx_traits::{Epoch, PaintedNode};
 use ipc_channel::ipc;
+use layout::display_list::items::OpaqueNode;
 use layout::display_list::items::{BaseDisplayItem, CommonDisplayItem, DisplayItem, DisplayList};
-use metrics::{PaintTimeMetrics, ProfilerMetadataFactory, ProgressiveWebMetric};
+use metrics::{
+    layout_shift_score, PaintTimeMetrics, ProfilerMetadataFactory, ProgressiveWebMetric,
+};
 use msg::constellation_msg::TEST_PIPELINE_ID;
 use profile_traits::time::{ProfilerChan, TimerMetadata};
 use servo_url::ServoUrl;
-use webrender_api::{AlphaType, ColorF, ImageDisplayItem, ImageKey, ImageRendering, LayoutSize};
+use std::collections::HashMap;
+use webrender_api::{AlphaType, ColorF, ImageDisplayItem, ImageKey, ImageRendering};
+use webrender_api::{LayoutPoint, LayoutRect, LayoutSize};
 
 struct DummyProfilerMetadataFactory {}
 impl ProfilerMetadataFactory for DummyProfilerMetadataFactory {
@@ -48,6 +54,31 @@ fn test_paint_metrics_construction() {
     );
 }
 
+fn viewport_size() -> Size2D<f32> {
+    Size2D::new(800., 600.)
+}
+
+fn image_display_list(node: usize, bounds: LayoutRect) -> DisplayList {
+    let mut base = BaseDisplayItem::empty();
+    base.bounds = bounds;
+    base.metadata.node = OpaqueNode(node);
+    let image = DisplayItem::Image(CommonDisplayItem::new(
+        base,
+        ImageDisplayItem {
+            image_key: ImageKey::DUMMY,
+            stretch_size: bounds.size,
+            tile_spacing: LayoutSize::zero(),
+            image_rendering: ImageRendering::Auto,
+            alpha_type: AlphaType::PremultipliedAlpha,
+            color: ColorF::WHITE,
+        },
+    ));
+    DisplayList {
+        list: vec![image],
+        clip_scroll_nodes: Vec::new(),
+    }
+}
+
 fn test_common(display_list: &DisplayList, epoch: Epoch) -> PaintTimeMetrics {
     let (sender, _) = ipc::channel().unwrap();
     let profiler_chan = ProfilerChan(sender);
@@ -61,11 +92,13 @@ fn test_common(display_list: &DisplayList, epoch: Epoch) -> PaintTimeMetrics {
         ServoUrl::parse("about:blank").unwrap(),
     );
     let dummy_profiler_metadata_factory = DummyProfilerMetadataFactory {};
+    paint_time_metrics.set_observed_metrics(true, true);
 
     paint_time_metrics.maybe_observe_paint_time(
         &dummy_profiler_metadata_factory,
         epoch,
         &*display_list,
+        viewport_size(),
     );
 
     // Should not set any metric until navigation start is set.
@@ -143,3 +176,226 @@ fn test_first_contentful_paint_setter() {
         "first paint is set"
     );
 }
+
+#[test]
+fn test_largest_contentful_paint_setter() {
+    let display_list = image_display_list(
+        1,
+        LayoutRect::new(LayoutPoint::zero(), LayoutSize::new(100., 100.)),
+    );
+    let epoch = Epoch(0);
+    let paint_time_metrics = test_common(&display_list, epoch);
+    let now = time::precise_time_ns();
+    paint_time_metrics.maybe_set_metric(epoch, now);
+    assert_eq!(
+        paint_time_metrics.get_largest_contentful_paint(),
+        paint_time_metrics.get_first_contentful_paint(),
+        "largest contentful paint is the first contentful paint"
+    );
+
+    // Smaller content does not update the largest contentful paint.
+    let smaller_display_list = image_display_list(
+        2,
+        LayoutRect::new(LayoutPoint::zero(), LayoutSize::new(10., 10.)),
+    );
+    let smaller_epoch = Epoch(1);
+    paint_time_metrics.maybe_observe_paint_time(
+        &DummyProfilerMetadataFactory {},
+        smaller_epoch,
+        &smaller_display_list,
+        viewport_size(),
+    );
+    let largest_contentful_paint = paint_time_metrics.get_largest_contentful_paint();
+    paint_time_metrics.maybe_set_metric(smaller_epoch, now + 1);
+    assert_eq!(
+        paint_time_metrics.get_largest_contentful_paint(),
+        largest_contentful_paint,
+        "largest contentful paint is not updated"
+    );
+
+    // Larger content does.
+    let larger_display_list = image_display_list(
+        3,
+        LayoutRect::new(LayoutPoint::zero(), LayoutSize::new(200., 200.)),
+    );
+    let larger_epoch = Epoch(2);
+    paint_time_metrics.maybe_observe_paint_time(
+        &DummyProfilerMetadataFactory {},
+        larger_epoch,
+        &larger_display_list,
+        viewport_size(),
+    );
+    paint_time_metrics.maybe_set_metric(larger_epoch, now + 2);
+    assert!(
+        paint_time_metrics.get_largest_contentful_paint() > largest_contentful_paint,
+        "largest contentful paint is updated"
+    );
+}
+
+#[test]
+fn test_largest_contentful_paint_stops_when_not_observed() {
+    let display_list = image_display_list(
+        1,
+        LayoutRect::new(LayoutPoint::zero(), LayoutSize::new(100., 100.)),
+    );
+    let epoch = Epoch(0);
+    let paint_time_metrics = test_common(&display_list, epoch);
+    let now = time::precise_time_ns();
+    paint_time_metrics.maybe_set_metric(epoch, now);
+    let largest_contentful_paint = paint_time_metrics.get_largest_contentful_paint();
+    assert!(
+        largest_contentful_paint.is_some(),
+        "largest contentful paint is set"
+    );
+
+    // Script stops observing the largest contentful paint after the first input, so
+    // larger content does not update it anymore, even if it was already observed.
+    let larger_display_list = image_display_list(
+        2,
+        LayoutRect::new(LayoutPoint::zero(), LayoutSize::new(200., 200.)),
+    );
+    let larger_epoch = Epoch(1);
+    paint_time_metrics.maybe_observe_paint_time(
+        &DummyProfilerMetadataFactory {},
+        larger_epoch,
+        &larger_display_list,
+        viewport_size(),
+    );
+    paint_time_metrics.set_observed_metrics(false, true);
+    paint_time_metrics.maybe_set_metric(larger_epoch, now + 1);
+    assert_eq!(
+        paint_time_metrics.get_largest_contentful_paint(),
+        largest_contentful_paint,
+        "largest contentful paint is not updated after observation stopped"
+    );
+
+    let largest_display_list = image_display_list(
+        3,
+        LayoutRect::new(LayoutPoint::zero(), LayoutSize::new(300., 300.)),
+    );
+    let largest_epoch = Epoch(2);
+    paint_time_metrics.maybe_observe_paint_time(
+        &DummyProfilerMetadataFactory {},
+        largest_epoch,
+        &largest_display_list,
+        viewport_size(),
+    );
+    paint_time_metrics.maybe_set_metric(largest_epoch, now + 2);
+    assert_eq!(
+        paint_time_metrics.get_largest_contentful_paint(),
+        largest_contentful_paint,
+        "largest contentful paint is not computed when not observed"
+    );
+}
+
+#[test]
+fn test_unobserved_paint_metrics() {
+    let (sender, _) = ipc::channel().unwrap();
+    let profiler_chan = ProfilerChan(sender);
+    let (layout_sender, _) = ipc::channel().unwrap();
+    let (script_sender, _) = ipc::channel().unwrap();
+    let mut paint_time_metrics = PaintTimeMetrics::new(
+        TEST_PIPELINE_ID,
+        profiler_chan,
+        layout_sender,
+        script_sender,
+        ServoUrl::parse("about:blank").unwrap(),
+    );
+    paint_time_metrics.set_navigation_start(time::precise_time_ns());
+
+    let bounds = [
+        LayoutRect::new(LayoutPoint::zero(), LayoutSize::new(100., 100.)),
+        LayoutRect::new(LayoutPoint::new(0., 100.), LayoutSize::new(100., 100.)),
+    ];
+    for (i, bounds) in bounds.iter().enumerate() {
+        let epoch = Epoch(i as u32);
+        paint_time_metrics.maybe_observe_paint_time(
+            &DummyProfilerMetadataFactory {},
+            epoch,
+            &image_display_list(1, *bounds),
+            viewport_size(),
+        );
+        paint_time_metrics.maybe_set_metric(epoch, time::precise_time_ns());
+    }
+    assert!(
+        paint_time_metrics.get_first_contentful_paint().is_some(),
+        "first contentful paint is set"
+    );
+    assert_eq!(
+        paint_time_metrics.get_largest_contentful_paint(),
+        None,
+        "largest contentful paint is not computed without observers"
+    );
+    assert_eq!(
+        paint_time_metrics.get_cumulative_layout_shift(),
+        0.,
+        "layout shifts are not computed without observers"
+    );
+}
+
+#[test]
+fn test_layout_shift_score() {
+    let mut previous_node_bounds = HashMap::new();
+    previous_node_bounds.insert(1, Rect::new(Point2D::zero(), Size2D::new(100., 100.)));
+    let mut painted_nodes = HashMap::new();
+    painted_nodes.insert(
+        1,
+        PaintedNode {
+            bounds: Rect::new(Point2D::new(0., 100.), Size2D::new(100., 100.)),
+            contentful_area: 0.,
+        },
+    );
+    // A node that was not painted before does not shift.
+    painted_nodes.insert(
+        2,
+        PaintedNode {
+            bounds: Rect::new(Point2D::new(0., 200.), Size2D::new(800., 400.)),
+            contentful_area: 0.,
+        },
+    );
+
+    // The impact region is 100x200 in a 800x600 viewport and the node moved by 100px.
+    let expected = (100. * 200.) / (800. * 600.) * (100. / 800.);
+    let score = layout_shift_score(&previous_node_bounds, &painted_nodes, viewport_size());
+    assert!(
+        (score - expected).abs() < 1e-6,
+        "layout shift score is {}",
+        score
+    );
+
+    let score = layout_shift_score(&HashMap::new(), &painted_nodes, viewport_size());
+    assert_eq!(score, 0., "no layout shift without previous layout");
+}
+
+#[test]
+fn test_cumulative_layout_shift() {
+    let display_list = image_display_list(
+        1,
+        LayoutRect::new(LayoutPoint::zero(), LayoutSize::new(100., 100.)),
+    );
+    let epoch = Epoch(0);
+    let paint_time_metrics = test_common(&display_list, epoch);
+    paint_time_metrics.maybe_set_metric(epoch, time::precise_time_ns());
+    assert_eq!(
+        paint_time_metrics.get_cumulative_layout_shift(),
+        0.,
+        "no layout shift on first layout"
+    );
+
+    let shifted_display_list = image_display_list(
+        1,
+        LayoutRect::new(LayoutPoint::new(0., 100.), LayoutSize::new(100., 100.)),
+    );
+    let shifted_epoch = Epoch(1);
+    paint_time_metrics.maybe_observe_paint_time(
+        &DummyProfilerMetadataFactory {},
+        shifted_epoch,
+        &shifted_display_list,
+        viewport_size(),
+    );
+    paint_time_metrics.maybe_set_metric(shifted_epoch, time::precise_time_ns());
+    assert!(
+        paint_time_metrics.get_cumulative_layout_shift() > 0.,
+        "layout shift is accumulated"
+    );
+}
@@ -0,0 +1,276 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::Size2D;
+use script::test::image_encoder::{encode, ImageType};
+use std::collections::HashMap;
+
+#[test]
+fn image_type() {
+    let size = Size2D::new(10, 10);
+    assert_eq!(ImageType::new(None, size), ImageType::Png);
+    assert_eq!(ImageType::new(Some("image/JPEG"), size), ImageType::Jpeg);
+    assert_eq!(ImageType::new(Some("image/webp"), size), ImageType::Webp);
+    assert_eq!(ImageType::new(Some("image/gif"), size), ImageType::Png);
+    let size = Size2D::new(1 << 14, (1 << 14) + 1);
+    assert_eq!(ImageType::new(Some("image/webp"), size), ImageType::Png);
+    assert_eq!(ImageType::new(Some("image/jpeg"), size), ImageType::Jpeg);
+}
+
+#[test]
+fn webp_known_encodings() {
+    let encoded = encode(ImageType::Webp, &[255, 0, 0, 255], Size2D::new(1, 1), None);
+    let mut expected = b"RIFF\x16\0\0\0WEBPVP8L\x0a\0\0\0".to_vec();
+    expected.extend_from_slice(&[0x2f, 0x00, 0x00, 0x00, 0x00, 0x45, 0xf4, 0x3f, 0xfa, 0x1f]);
+    assert_eq!(encoded, expected);
+
+    // An odd sized chunk is padded.
+    let pixels = [0, 0, 255, 128, 0, 255, 0, 255];
+    let encoded = encode(ImageType::Webp, &pixels, Size2D::new(2, 1), None);
+    let mut expected = b"RIFF\x1e\0\0\0WEBPVP8L\x11\0\0\0".to_vec();
+    expected.extend_from_slice(&[
+        0x2f, 0x01, 0x00, 0x00, 0x10, 0xc5, 0x01, 0xfe, 0x0f, 0x10, 0xf0, 0x80, 0xff, 0x03, 0xfe,
+        0x07, 0x2d, 0x00,
+    ]);
+    assert_eq!(encoded, expected);
+}
+
+#[test]
+fn webp_round_trip() {
+    let opaque = [0x20, 0x40, 0x60, 0xff]
+        .iter()
+        .cloned()
+        .cycle()
+        .take(20 * 20 * 4)
+        .collect::<Vec<u8>>();
+    assert_round_trip(&opaque, 20, 20, false);
+
+    let gradient = (0..7 * 5)
+        .flat_map(|i| vec![i * 7, 255 - i * 7, i * 3, 128 + i])
+        .collect::<Vec<u8>>();
+    assert_round_trip(&gradient, 7, 5, true);
+
+    // Pseudo-random pixels use every symbol, so none of the codes are simple.
+    let mut state = 1u32;
+    let noise = (0..33 * 17 * 4)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (state >> 16) as u8
+        })
+        .collect::<Vec<u8>>();
+    assert_round_trip(&noise, 33, 17, true);
+}
+
+#[test]
+fn webp_round_trip_limits_code_lengths() {
+    // Fibonacci distributed green values would need codes longer than the
+    // 15 bits WebP allows.
+    let mut pixels = vec![];
+    let (mut count, mut previous) = (1, 1);
+    for green in 0..25 {
+        for _ in 0..count {
+            pixels.extend_from_slice(&[0, green, 0, 255]);
+        }
+        let next = count + previous;
+        previous = count;
+        count = next;
+    }
+    let width = 1024;
+    pixels.resize(pixels.len() + width * 4 - pixels.len() % (width * 4), 255);
+    let height = pixels.len() / 4 / width;
+    assert_round_trip(&pixels, width as u32, height as u32, false);
+}
+
+fn assert_round_trip(pixels: &[u8], width: u32, height: u32, alpha_is_used: bool) {
+    let encoded = encode(ImageType::Webp, pixels, Size2D::new(width, height), None);
+    let decoded = decode_webp(&encoded);
+    assert_eq!(decoded.width, width);
+    assert_eq!(decoded.height, height);
+    assert_eq!(decoded.alpha_is_used, alpha_is_used);
+    assert!(decoded.pixels == pixels);
+}
+
+struct DecodedImage {
+    width: u32,
+    height: u32,
+    alpha_is_used: bool,
+    pixels: Vec<u8>,
+}
+
+/// Decodes a lossless WebP image, only supporting the features the encoder
+/// uses: the subtract green transform and literal pixels.
+///
+/// https://developers.google.com/speed/webp/docs/webp_lossless_bitstream_specification
+fn decode_webp(data: &[u8]) -> DecodedImage {
+    assert_eq!(&data[..4], b"RIFF");
+    assert_eq!(read_u32(&data[4..]) as usize, data.len() - 8);
+    assert_eq!(data.len() % 2, 0);
+    assert_eq!(&data[8..16], b"WEBPVP8L");
+    let chunk_size = read_u32(&data[16..]) as usize;
+    assert!(data.len() - 20 - chunk_size < 2);
+
+    let mut reader = BitReader::new(&data[20..20 + chunk_size]);
+    assert_eq!(reader.read(8), 0x2f);
+    let width = reader.read(14) + 1;
+    let height = reader.read(14) + 1;
+    let alpha_is_used = reader.read(1) == 1;
+    assert_eq!(reader.read(3), 0);
+
+    let mut subtract_green = false;
+    while reader.read(1) == 1 {
+        assert_eq!(reader.read(2), 2, "unsupported transform");
+        assert!(!subtract_green, "transforms can only be used once");
+        subtract_green = true;
+    }
+    assert_eq!(reader.read(1), 0, "unsupported color cache");
+    assert_eq!(reader.read(1), 0, "unsupported meta prefix codes");
+
+    let green = PrefixCode::read(&mut reader, 256 + 24);
+    let red = PrefixCode::read(&mut reader, 256);
+    let blue = PrefixCode::read(&mut reader, 256);
+    let alpha = PrefixCode::read(&mut reader, 256);
+    PrefixCode::read(&mut reader, 40);
+
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for _ in 0..width * height {
+        let g = green.read_symbol(&mut reader);
+        assert!(g < 256, "unsupported backward reference");
+        let (mut r, mut b) = (red.read_symbol(&mut reader), blue.read_symbol(&mut reader));
+        if subtract_green {
+            r += g;
+            b += g;
+        }
+        let a = alpha.read_symbol(&mut reader);
+        pixels.extend_from_slice(&[r as u8, g as u8, b as u8, a as u8]);
+    }
+    assert_eq!((reader.position + 7) / 8, chunk_size);
+
+    DecodedImage {
+        width,
+        height,
+        alpha_is_used,
+        pixels,
+    }
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    (0..4).fold(0, |value, i| value | (bytes[i] as u32) << (8 * i))
+}
+
+/// Reads values least significant bit first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> BitReader<'a> {
+        BitReader { data, position: 0 }
+    }
+
+    fn read(&mut self, bits: u32) -> u32 {
+        let mut value = 0;
+        for bit in 0..bits {
+            let byte = self.data[self.position / 8];
+            value |= ((byte >> (self.position % 8)) as u32 & 1) << bit;
+            self.position += 1;
+        }
+        value
+    }
+}
+
+/// The order in which the code lengths of the code length code are read.
+const CODE_LENGTH_CODE_ORDER: [usize; 19] = [
+    17, 18, 0, 1, 2, 3, 4, 5, 16, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+];
+
+/// A canonical prefix code, mapping `(length, code)` to symbols.
+struct PrefixCode {
+    symbols: HashMap<(u32, u32), u32>,
+}
+
+impl PrefixCode {
+    fn new(lengths: &[u32]) -> PrefixCode {
+        let mut symbols = (0..lengths.len() as u32)
+            .filter(|&symbol| lengths[symbol as usize] != 0)
+            .collect::<Vec<_>>();
+        assert!(!symbols.is_empty());
+        if symbols.len() == 1 {
+            // A code with a single symbol uses zero bits per symbol.
+            return PrefixCode {
+                symbols: vec![((0, 0), symbols[0])].into_iter().collect(),
+            };
+        }
+        symbols.sort_by_key(|&symbol| lengths[symbol as usize]);
+        let mut codes = HashMap::new();
+        let (mut code, mut previous_length) = (0, 0);
+        for symbol in symbols {
+            let length = lengths[symbol as usize];
+            code <<= length - previous_length;
+            assert!(code < 1 << length, "over-subscribed code");
+            codes.insert((length, code), symbol);
+            code += 1;
+            previous_length = length;
+        }
+        assert_eq!(code, 1 << previous_length, "incomplete code");
+        PrefixCode { symbols: codes }
+    }
+
+    fn read(reader: &mut BitReader, alphabet_size: usize) -> PrefixCode {
+        let mut lengths = vec![0; alphabet_size];
+        if reader.read(1) == 1 {
+            // Simple code.
+            let count = reader.read(1) + 1;
+            let first_bits = if reader.read(1) == 1 { 8 } else { 1 };
+            lengths[reader.read(first_bits) as usize] = 1;
+            if count == 2 {
+                lengths[reader.read(8) as usize] = 1;
+            }
+            return PrefixCode::new(&lengths);
+        }
+
+        let count = reader.read(4) as usize + 4;
+        let mut length_code_lengths = [0; 19];
+        for &symbol in &CODE_LENGTH_CODE_ORDER[..count] {
+            length_code_lengths[symbol] = reader.read(3);
+        }
+        let length_code = PrefixCode::new(&length_code_lengths);
+        let mut max_symbol = if reader.read(1) == 1 {
+            let bits = 2 + 2 * reader.read(3);
+            2 + reader.read(bits) as usize
+        } else {
+            alphabet_size
+        };
+        let (mut symbol, mut previous_length) = (0, 8);
+        while symbol < alphabet_size && max_symbol > 0 {
+            max_symbol -= 1;
+            let (length, repeat) = match length_code.read_symbol(reader) {
+                length @ 0..=15 => (length, 1),
+                16 => (previous_length, 3 + reader.read(2)),
+                17 => (0, 3 + reader.read(3)),
+                _ => (0, 11 + reader.read(7)),
+            };
+            for _ in 0..repeat {
+                lengths[symbol] = length;
+                symbol += 1;
+            }
+            if length != 0 {
+                previous_length = length;
+            }
+        }
+        PrefixCode::new(&lengths)
+    }
+
+    fn read_symbol(&self, reader: &mut BitReader) -> u32 {
+        let (mut length, mut code) = (0, 0);
+        loop {
+            if let Some(&symbol) = self.symbols.get(&(length, code)) {
+                return symbol;
+            }
+            assert!(length < 15, "invalid code");
+            code = (code << 1) | reader.read(1);
+            length += 1;
+        }
+    }
+}
@@ -85,3 +85,52 @@ fn add_overlapping() {
     time_ranges.add(6., 9.).unwrap();
     check(&time_ranges, "[-1,11)");
 }
+
+#[test]
+fn remove() {
+    let mut time_ranges = TimeRangesContainer::new();
+
+    time_ranges.add(0., 10.).unwrap();
+    time_ranges.add(20., 30.).unwrap();
+    check(&time_ranges, "[0,10), [20,30)");
+
+    time_ranges.remove(10., 20.).unwrap();
+    check(&time_ranges, "[0,10), [20,30)");
+
+    time_ranges.remove(2., 4.).unwrap();
+    check(&time_ranges, "[0,2), [4,10), [20,30)");
+
+    time_ranges.remove(5., 25.).unwrap();
+    check(&time_ranges, "[0,2), [4,5), [25,30)");
+
+    time_ranges.remove(-1., 2.).unwrap();
+    check(&time_ranges, "[4,5), [25,30)");
+
+    time_ranges.remove(0., 100.).unwrap();
+    check(&time_ranges, "");
+
+    assert!(time_ranges.remove(2., 1.).is_err());
+}
+
+#[test]
+fn remove_then_reappend() {
+    // The buffered ranges of a SourceBuffer hide the ranges it removed until
+    // the player reports them as buffered after another append.
+    let mut player_ranges = TimeRangesContainer::new();
+    player_ranges.add(0., 10.).unwrap();
+
+    let mut removed_ranges = TimeRangesContainer::new();
+    removed_ranges.add(2., 4.).unwrap();
+    removed_ranges.add(20., 30.).unwrap();
+
+    let mut buffered = player_ranges.clone();
+    buffered.remove_ranges(&removed_ranges);
+    check(&buffered, "[0,2), [4,10)");
+
+    removed_ranges.remove_ranges(&player_ranges);
+    check(&removed_ranges, "[20,30)");
+
+    let mut buffered = player_ranges.clone();
+    buffered.remove_ranges(&removed_ranges);
+    check(&buffered, "[0,10)");
+}
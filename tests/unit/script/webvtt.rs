@@ -0,0 +1,117 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::webvtt::{
+    cue_text_content, parse, parse_cue_text, CueNode, CueSettings, LineAlignment,
+    PositionAlignment, TextAlignment, WritingDirection,
+};
+
+#[test]
+fn signature() {
+    assert!(parse("WEBVTT").unwrap().is_empty());
+    assert!(parse("\u{FEFF}WEBVTT - title\n").unwrap().is_empty());
+    assert!(parse("WEBVTT\tfoo").is_ok());
+    assert!(parse("WEBVTTfoo").is_err());
+    assert!(parse("webvtt").is_err());
+    assert!(parse("").is_err());
+}
+
+#[test]
+fn cues() {
+    let cues = parse(
+        "WEBVTT\r\nKind: captions\r\n\r\n\
+         intro\r\n00:01.000 --> 00:02.500\r\nHello\r\nworld\r\n\r\n\
+         NOTE a comment\r\n\r\n\
+         01:00:00.250 --> 01:00:01.000\r\n<v Bob>Bye</v>\r\n",
+    )
+    .unwrap();
+    assert_eq!(cues.len(), 2);
+
+    assert_eq!(cues[0].id, "intro");
+    assert_eq!(cues[0].start_time, 1.);
+    assert_eq!(cues[0].end_time, 2.5);
+    assert_eq!(cues[0].text, "Hello\nworld");
+    assert_eq!(cues[0].settings, CueSettings::default());
+
+    assert_eq!(cues[1].id, "");
+    assert_eq!(cues[1].start_time, 3600.25);
+    assert_eq!(cues[1].end_time, 3601.);
+    assert_eq!(cues[1].text, "<v Bob>Bye</v>");
+}
+
+#[test]
+fn invalid_timings() {
+    let cues = parse(
+        "WEBVTT\n\n\
+         00:01.00 --> 00:02.000\nshort fraction\n\n\
+         00:60.000 --> 00:61.000\nout of range\n\n\
+         1:00:01.000 --> 1:00:02.000\nshort hours\n\n\
+         00:01.000 -> 00:02.000\nno arrow\n\n\
+         00:03.000 --> 00:04.000\nvalid\n",
+    )
+    .unwrap();
+    assert_eq!(cues.len(), 1);
+    assert_eq!(cues[0].text, "valid");
+}
+
+#[test]
+fn settings() {
+    let cues = parse(
+        "WEBVTT\n\n\
+         00:00.000 --> 00:01.000 vertical:rl line:-2,end position:20%,line-left size:50% align:left\n\
+         a\n\n\
+         00:00.000 --> 00:01.000 line:30% position:150% size:x align:middle foo:bar\n\
+         b\n",
+    )
+    .unwrap();
+
+    let settings = cues[0].settings;
+    assert_eq!(settings.vertical, WritingDirection::VerticalGrowingLeft);
+    assert_eq!(settings.line, Some(-2.));
+    assert!(settings.snap_to_lines);
+    assert_eq!(settings.line_align, LineAlignment::End);
+    assert_eq!(settings.position, Some(20.));
+    assert_eq!(settings.position_align, PositionAlignment::LineLeft);
+    assert_eq!(settings.size, 50.);
+    assert_eq!(settings.align, TextAlignment::Left);
+
+    let settings = cues[1].settings;
+    assert_eq!(settings.line, Some(30.));
+    assert!(!settings.snap_to_lines);
+    assert_eq!(settings.position, None);
+    assert_eq!(settings.size, 100.);
+    assert_eq!(settings.align, TextAlignment::Center);
+}
+
+#[test]
+fn cue_text() {
+    assert_eq!(
+        parse_cue_text("<c.loud>Hi</c> &lt;<00:01.000>there&gt;"),
+        vec![
+            CueNode::Element {
+                name: "c".to_owned(),
+                classes: vec!["loud".to_owned()],
+                annotation: None,
+                children: vec![CueNode::Text("Hi".to_owned())],
+            },
+            CueNode::Text(" <".to_owned()),
+            CueNode::Text("there>".to_owned()),
+        ]
+    );
+
+    assert_eq!(
+        parse_cue_text("<v Esme>unclosed"),
+        vec![CueNode::Element {
+            name: "v".to_owned(),
+            classes: vec![],
+            annotation: Some("Esme".to_owned()),
+            children: vec![CueNode::Text("unclosed".to_owned())],
+        }]
+    );
+
+    assert_eq!(
+        cue_text_content("<ruby>漢<rt>kan</ruby> <b><i>bold</b></i>"),
+        "漢kan bold"
+    );
+}
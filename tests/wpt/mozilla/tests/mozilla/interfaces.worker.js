@@ -8,6 +8,8 @@ importScripts("interfaces.js");
 // IMPORTANT: Do not change the list below without review from a DOM peer!
 test_interfaces([
   "Blob",
+  "Cache",
+  "CacheStorage",
   "CloseEvent",
   "DOMMatrix",
   "DOMMatrixReadOnly",
@@ -48,6 +50,18 @@ test_interfaces([
   "TextEncoder",
   "URL",
   "URLSearchParams",
+  "WebGLActiveInfo",
+  "WebGLBuffer",
+  "WebGLContextEvent",
+  "WebGLFramebuffer",
+  "WebGLObject",
+  "WebGLProgram",
+  "WebGLRenderbuffer",
+  "WebGLRenderingContext",
+  "WebGLShader",
+  "WebGLShaderPrecisionFormat",
+  "WebGLTexture",
+  "WebGLUniformLocation",
   "WebSocket",
   "Worker",
   "WorkerGlobalScope",
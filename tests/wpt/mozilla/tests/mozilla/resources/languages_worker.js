@@ -0,0 +1,13 @@
+addEventListener("languagechange", function() {
+  postMessage({ type: "languagechange", languages: navigator.languages.slice() });
+});
+
+addEventListener("message", function() {
+  var languages = navigator.languages;
+  postMessage({
+    type: "check",
+    languages: languages,
+    same: languages === navigator.languages,
+    frozen: Object.isFrozen(languages),
+  });
+});
@@ -0,0 +1,17 @@
+# This Source Code Form is subject to the terms of the Mozilla Public
+# License, v. 2.0. If a copy of the MPL was not distributed with this
+# file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+import json
+
+
+def main(request, response):
+    # Records the event given in the query if any, then returns all the recorded ones.
+    key = request.GET.first('key')
+    event = request.GET.first('event', None)
+    with request.server.stash.lock:
+        events = request.server.stash.take(key) or []
+        if event is not None:
+            events.append(event)
+        request.server.stash.put(key, events)
+    return 200, [('Content-Type', 'application/json'), ('Cache-Control', 'no-cache')], json.dumps(events)
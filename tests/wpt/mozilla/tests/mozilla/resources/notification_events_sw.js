@@ -0,0 +1,20 @@
+// Records the notification events at notification_events.py, under the key given in
+// the query of the script url.
+var key = new URL(location.href).searchParams.get("key");
+
+function record(event) {
+  var description = event.type + " " + event.notification.title;
+  return fetch("notification_events.py?key=" + key + "&event=" + encodeURIComponent(description));
+}
+
+self.addEventListener("message", function(event) {
+  event.waitUntil(self.registration.showNotification(event.data, { body: "Body" }));
+});
+
+self.addEventListener("notificationclick", function(event) {
+  event.waitUntil(record(event));
+});
+
+self.addEventListener("notificationclose", function(event) {
+  event.waitUntil(record(event));
+});
@@ -0,0 +1 @@
+postMessage(Notification.permission);
@@ -0,0 +1,4 @@
+// Never finishes installing, so the registration has no active worker.
+self.addEventListener("install", function(event) {
+  event.waitUntil(new Promise(function() {}));
+});
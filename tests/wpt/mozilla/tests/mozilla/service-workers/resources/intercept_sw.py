@@ -0,0 +1,14 @@
+# This Source Code Form is subject to the terms of the Mozilla Public
+# License, v. 2.0. If a copy of the MPL was not distributed with this
+# file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+import os
+import time
+
+
+def main(request, response):
+    # Every fetch of the script gives a different version, so updates always
+    # install a new worker.
+    headers = [('Content-Type', 'application/javascript'), ('Cache-Control', 'no-cache')]
+    with open(os.path.join(os.path.dirname(__file__), 'intercept_worker.js')) as f:
+        return 200, headers, 'var version = "%s";\n%s' % (time.time(), f.read())
@@ -0,0 +1,40 @@
+var events = [];
+
+addEventListener("install", function(event) {
+  events.push("install");
+  event.waitUntil(skipWaiting());
+});
+
+addEventListener("activate", function(event) {
+  events.push("activate");
+  event.waitUntil(clients.claim());
+});
+
+// The page reports what the worker saw of its navigation, and the result of
+// two subresource requests the worker answers too.
+function page(event) {
+  var navigation = {
+    mode: event.request.mode,
+    has_resulting_client_id: event.resultingClientId !== "",
+  };
+  return "<!doctype html><script>" +
+    "var navigation = " + JSON.stringify(navigation) + ";" +
+    "Promise.all([" +
+    "  fetch('data.txt').then(function(response) { return response.text(); })," +
+    "  fetch('state.json').then(function(response) { return response.json(); })" +
+    "]).then(function(results) {" +
+    "  parent.postMessage({ navigation: navigation, data: results[0], state: results[1] }, '*');" +
+    "});" +
+    "</script>";
+}
+
+addEventListener("fetch", function(event) {
+  var path = new URL(event.request.url).pathname;
+  if (path.endsWith("/intercepted/page.html")) {
+    event.respondWith(new Response(page(event), { headers: { "Content-Type": "text/html" } }));
+  } else if (path.endsWith("/intercepted/data.txt")) {
+    event.respondWith(new Response("intercepted " + event.request.mode));
+  } else if (path.endsWith("/intercepted/state.json")) {
+    event.respondWith(new Response(JSON.stringify({ version: version, events: events })));
+  }
+});
@@ -1 +1,6 @@
 console.log("Hey Servo; lets cache something! :)");
+
+// Control the page which registered this worker once it is activated.
+addEventListener("activate", function(event) {
+  event.waitUntil(clients.claim());
+});
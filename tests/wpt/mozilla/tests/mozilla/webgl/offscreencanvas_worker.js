@@ -0,0 +1,19 @@
+onmessage = function(e) {
+  var canvas = e.data.canvas;
+  var gl = canvas.getContext("webgl");
+  if (!gl) {
+    postMessage({ error: "no WebGL context" });
+    return;
+  }
+  gl.clearColor(0, 1, 0, 1);
+  gl.clear(gl.COLOR_BUFFER_BIT);
+  gl.commit();
+  var bitmap = canvas.transferToImageBitmap();
+  postMessage({
+    isOffscreenCanvas: gl.canvas === canvas,
+    width: canvas.width,
+    height: canvas.height,
+    bitmapWidth: bitmap.width,
+    bitmapHeight: bitmap.height,
+  });
+};
@@ -0,0 +1,56 @@
+var IDENTITY_MATRIX = [1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1];
+
+var VALID_VIEWS = [{
+  eye: "left",
+  projectionMatrix: IDENTITY_MATRIX,
+  viewOffset: { position: [-0.1, 0, 0], orientation: [0, 0, 0, 1] },
+}, {
+  eye: "right",
+  projectionMatrix: IDENTITY_MATRIX,
+  viewOffset: { position: [0.1, 0, 0], orientation: [0, 0, 0, 1] },
+}];
+
+var IDENTITY_TRANSFORM = { position: [0, 0, 0], orientation: [0, 0, 0, 1] };
+
+// Connects a simulated device, runs `callback` in an immersive session on
+// it and disconnects the device once the returned promise settles.
+function xr_session_test(deviceInit, callback, name) {
+  promise_test(function(t) {
+    return navigator.xr.test.simulateDeviceConnection(deviceInit).then(function(device) {
+      return navigator.xr.requestSession({ mode: "immersive-vr" }).then(function(session) {
+        return Promise.resolve(callback(t, session, device)).then(function() {
+          // The test may already have ended the session.
+          return session.end().catch(function() {});
+        });
+      }).then(function() {
+        return device.disconnect();
+      }, function(e) {
+        return device.disconnect().then(function() { throw e; });
+      });
+    });
+  }, name);
+}
+
+// Runs `callback` with the next frame of `session`, resolving with its
+// result.
+function next_frame(session, callback) {
+  return new Promise(function(resolve, reject) {
+    session.requestAnimationFrame(function(time, frame) {
+      try {
+        resolve(callback ? callback(frame) : undefined);
+      } catch (e) {
+        reject(e);
+      }
+    });
+  });
+}
+
+function assert_point_approx_equals(point, expected, description) {
+  var epsilon = 0.0001;
+  assert_approx_equals(point.x, expected[0], epsilon, description + " x");
+  assert_approx_equals(point.y, expected[1], epsilon, description + " y");
+  assert_approx_equals(point.z, expected[2], epsilon, description + " z");
+  if (expected.length > 3) {
+    assert_approx_equals(point.w, expected[3], epsilon, description + " w");
+  }
+}
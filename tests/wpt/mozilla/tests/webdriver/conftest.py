@@ -0,0 +1,33 @@
+# This Source Code Form is subject to the terms of the Mozilla Public
+# License, v. 2.0. If a copy of the MPL was not distributed with this
+# file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+import json
+import os
+import urlparse
+
+import pytest
+import webdriver
+
+
+@pytest.fixture(scope="module")
+def session():
+    capabilities = json.loads(os.environ.get("WD_CAPABILITIES", "{}"))
+    session = webdriver.Session(os.environ["WD_HOST"],
+                                int(os.environ["WD_PORT"]),
+                                capabilities={"alwaysMatch": capabilities})
+    session.start()
+    yield session
+    session.end()
+
+
+@pytest.fixture
+def url():
+    server_config = json.loads(os.environ["WD_SERVER_CONFIG"])
+
+    def url(path, protocol="http"):
+        port = server_config["ports"][protocol][0]
+        host = "%s:%s" % (server_config["browser_host"], port)
+        return urlparse.urlunsplit((protocol, host, path, "", ""))
+
+    return url
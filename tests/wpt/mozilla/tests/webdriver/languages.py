@@ -0,0 +1,92 @@
+# This Source Code Form is subject to the terms of the Mozilla Public
+# License, v. 2.0. If a copy of the MPL was not distributed with this
+# file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+import pytest
+
+PAGE = "/_mozilla/mozilla/resources/languages.html"
+DEFAULT_LANGUAGES = ["en-US", "en"]
+
+WAIT_FOR_EVENTS = """
+var count = arguments[0], done = arguments[1];
+(function check() {
+    if (window.events.length >= count) {
+        done(window.events);
+    } else {
+        setTimeout(check, 10);
+    }
+})();
+"""
+
+CHECK_WINDOW = """
+var languages = navigator.languages;
+return {
+    languages: languages,
+    same: languages === navigator.languages,
+    frozen: Object.isFrozen(languages),
+};
+"""
+
+CHECK_WORKER = """
+var done = arguments[0];
+worker.onmessage = function(event) {
+    if (event.data.type == "check") {
+        worker.onmessage = null;
+        done(event.data);
+    }
+};
+worker.postMessage("check");
+"""
+
+FETCH_ACCEPT_LANGUAGE = """
+var done = arguments[0];
+fetch("accept_language.py").then(function(response) {
+    return response.text();
+}).then(done);
+"""
+
+
+def set_languages(session, languages):
+    session.send_session_command("POST", "servo/languages", {"languages": languages})
+
+
+@pytest.fixture
+def page(session, url):
+    session.url = url(PAGE)
+    # Wait for the worker to be running, so it sees the change.
+    session.execute_async_script(CHECK_WORKER)
+    yield session
+    set_languages(session, DEFAULT_LANGUAGES)
+
+
+def test_languages_are_frozen_and_stable(page):
+    for result in [page.execute_script(CHECK_WINDOW), page.execute_async_script(CHECK_WORKER)]:
+        assert result["languages"] == DEFAULT_LANGUAGES
+        assert result["same"]
+        assert result["frozen"]
+
+
+def test_languagechange(page):
+    page.execute_script("window.before = navigator.languages;")
+    set_languages(page, ["fr-CA", "fr", "en"])
+
+    events = page.execute_async_script(WAIT_FOR_EVENTS, [2])
+    assert sorted(events) == [
+        ["window", ["fr-CA", "fr", "en"]],
+        ["worker", ["fr-CA", "fr", "en"]],
+    ]
+
+    # A new frozen array is returned once the languages changed.
+    assert not page.execute_script("return window.before === navigator.languages;")
+    for result in [page.execute_script(CHECK_WINDOW), page.execute_async_script(CHECK_WORKER)]:
+        assert result["languages"] == ["fr-CA", "fr", "en"]
+        assert result["same"]
+        assert result["frozen"]
+
+
+def test_accept_language(page):
+    assert page.execute_async_script(FETCH_ACCEPT_LANGUAGE) == "en-US, en; q=0.5"
+
+    set_languages(page, ["fr-CA", "fr", "en"])
+    page.execute_async_script(WAIT_FOR_EVENTS, [2])
+    assert page.execute_async_script(FETCH_ACCEPT_LANGUAGE) == "fr-CA, fr; q=0.667, en; q=0.334"